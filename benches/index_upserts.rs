@@ -0,0 +1,54 @@
+//! `cargo bench --bench index_upserts`: upsert throughput of the index per
+//! write strategy, the same measurement as `stash db bench` with criterion's
+//! statistics. Each iteration writes into a fresh WAL database.
+
+use std::time::{Duration, Instant};
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use stash::{
+    index::Db,
+    index_bench::{SampleRows, UpsertStrategy, DEFAULT_SIZES},
+};
+use tokio::runtime::Runtime;
+
+/// Time only the last write of `sample` into a fresh database, after
+/// writing it once first when measuring updates
+async fn timed_upsert(sample: &SampleRows, strategy: UpsertStrategy, update: bool) -> Duration {
+    let dir = tempfile::tempdir().unwrap();
+    let db = Db::new(&dir.path().join("bench.db")).await.unwrap();
+    if update {
+        sample.upsert(&db, strategy).await.unwrap();
+    }
+    let start = Instant::now();
+    sample.upsert(&db, strategy).await.unwrap();
+    start.elapsed()
+}
+
+fn upserts(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let permissions = std::fs::metadata(std::env::temp_dir()).unwrap().permissions();
+
+    for (label, update) in [("insert", false), ("update", true)] {
+        let mut group = c.benchmark_group(format!("index_{}", label));
+        group.sample_size(10);
+        for rows in DEFAULT_SIZES {
+            let sample = SampleRows::new(rows, permissions.clone());
+            group.throughput(Throughput::Elements(rows as u64));
+            for strategy in UpsertStrategy::ALL {
+                group.bench_with_input(BenchmarkId::new(strategy.name(), rows), &sample, |b, sample| {
+                    b.to_async(&rt).iter_custom(|iters| async move {
+                        let mut total = Duration::ZERO;
+                        for _ in 0..iters {
+                            total += timed_upsert(sample, strategy, update).await;
+                        }
+                        total
+                    });
+                });
+            }
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, upserts);
+criterion_main!(benches);
@@ -0,0 +1,13 @@
+#![no_main]
+
+//! Any archive entry name is either refused or stays inside the extraction directory
+
+use libfuzzer_sys::fuzz_target;
+use stash::path_safety::{archive_entry_path, is_contained};
+
+fuzz_target!(|name: &str| {
+    if let Some(path) = archive_entry_path(name) {
+        assert!(is_contained(&path), "{:?} from {:?}", path, name);
+        assert!(!path.as_os_str().is_empty());
+    }
+});
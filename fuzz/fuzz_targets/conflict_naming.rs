@@ -0,0 +1,18 @@
+#![no_main]
+
+//! A conflict candidate stays in the same directory and never reuses the name
+
+use std::path::Path;
+
+use libfuzzer_sys::fuzz_target;
+use stash::path_safety::conflict_candidate;
+
+fuzz_target!(|input: (&str, u32)| {
+    let (name, n) = input;
+    let path = Path::new(name);
+    let candidate = conflict_candidate(path, n);
+
+    let parent = path.parent().unwrap_or(Path::new("."));
+    assert_eq!(candidate.parent(), Some(parent), "{:?} moved to {:?}", path, candidate);
+    assert_ne!(candidate, path);
+});
@@ -0,0 +1,17 @@
+#![no_main]
+
+//! Any OCR output either yields no name or a single safe file name
+
+use libfuzzer_sys::fuzz_target;
+use stash::{
+    doc_naming::{looks_generic, suggest_name},
+    path_safety::is_valid_folder_name,
+};
+
+fuzz_target!(|text: &str| {
+    looks_generic(text);
+    if let Some(name) = suggest_name(text) {
+        let file_name = name.file_name(Some("pdf"));
+        assert!(is_valid_folder_name(&file_name), "{:?} from {:?}", file_name, text);
+    }
+});
@@ -0,0 +1,23 @@
+#![no_main]
+
+//! Any file name classifies without panicking and lands inside the destination root
+
+use std::path::Path;
+
+use libfuzzer_sys::fuzz_target;
+use stash::{
+    path_builder::PathBuilder,
+    path_safety::{is_contained, is_valid_folder_name, sanitize_component},
+    preview::classify_name,
+};
+
+fuzz_target!(|name: &str| {
+    assert!(is_valid_folder_name(&sanitize_component(name)));
+
+    let classified = classify_name(name, 0, None);
+    let base = Path::new("Organized");
+    if let Some(dest) = PathBuilder::new(&classified).base(base).destination_for(Path::new(name)) {
+        let relative = dest.strip_prefix(base).expect("destination left the root");
+        assert!(is_contained(relative), "{:?} escapes via {:?}", name, dest);
+    }
+});
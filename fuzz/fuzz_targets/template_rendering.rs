@@ -0,0 +1,18 @@
+#![no_main]
+
+//! Any accepted group template expands, for any provenance, to a path that
+//! stays below the destination root
+
+use libfuzzer_sys::fuzz_target;
+use stash::{path_safety::is_contained, provenance::{GroupTemplate, Provenance}};
+
+fuzz_target!(|input: (&str, Option<String>, Option<String>)| {
+    let (template, source_app, source_domain) = input;
+    let Ok(template) = template.parse::<GroupTemplate>() else {
+        return;
+    };
+
+    let provenance = Provenance { source_app, source_domain, ..Default::default() };
+    let expanded = template.expand(&provenance);
+    assert!(is_contained(&expanded), "{:?} expanded to {:?}", template, expanded);
+});
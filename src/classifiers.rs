@@ -1,4 +1,6 @@
 pub mod metadata;
+pub mod builtin;
+#[cfg(feature = "native")]
 pub mod registry;
 pub mod generic;
 pub mod docs_classifier;
@@ -8,6 +10,13 @@ pub mod archive_classifier;
 pub mod audio_classifier;
 pub mod executable_classifier;
 pub mod code_classifier;
+pub mod design_classifier;
+pub mod font_classifier;
+pub mod backup_classifier;
+pub mod rule_classifier;
 pub mod path_builder;
+pub mod path_safety;
+pub mod doc_naming;
 pub mod code_const;
-pub mod executables_const;
\ No newline at end of file
+pub mod executables_const;
+pub mod preview;
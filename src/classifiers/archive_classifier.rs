@@ -1,21 +1,22 @@
+use crate::metadata::ArchiveSubcategory;
+#[cfg(feature = "native")]
 use std::path::Path;
+#[cfg(feature = "native")]
 use async_trait::async_trait;
+#[cfg(feature = "native")]
 use crate::{
     errors::Result, 
-    metadata::{ArchiveSubcategory, ClassifiedFileMetadata, FileCategory}, 
+    metadata::{ClassifiedFileMetadata, FileCategory}, 
     registry::Classifier, 
     utils::{detect_mime, system_time_to_year}
 };
 
 pub struct ArchiveClassifier;
 
-#[async_trait]
-impl Classifier for ArchiveClassifier {
-    fn name(&self) -> &'static str {
-        "ArchiveClassifier"
-    }
+impl ArchiveClassifier {
+    pub const PRIORITY: u8 = 75;
 
-    fn confidence(&self, extension: &str, mime_type: &str) -> u8 {
+    pub fn score(extension: &str, mime_type: &str) -> u8 {
         // High confidence for common archive formats
         if matches!(
             extension,
@@ -52,6 +53,32 @@ impl Classifier for ArchiveClassifier {
         0
     }
 
+    /// Map a lowercase extension to its archive subcategory
+    pub fn subcategory(ext: &str) -> ArchiveSubcategory {
+        match ext {
+            "zip" => ArchiveSubcategory::Zip,
+            "tar" => ArchiveSubcategory::Tar,
+            "gz" | "tgz" => ArchiveSubcategory::Gz,
+            "rar" => ArchiveSubcategory::Rar,
+            "7z" => ArchiveSubcategory::SevenZ,
+            "bz2" | "tbz2" => ArchiveSubcategory::Bz2,
+            "xz" | "txz" => ArchiveSubcategory::Xz,
+            _ => ArchiveSubcategory::Other,
+        }
+    }
+}
+
+#[cfg(feature = "native")]
+#[async_trait]
+impl Classifier for ArchiveClassifier {
+    fn name(&self) -> &'static str {
+        "ArchiveClassifier"
+    }
+
+    fn confidence(&self, extension: &str, mime_type: &str) -> u8 {
+        Self::score(extension, mime_type)
+    }
+
     async fn extract_metadata(&self, path: &Path) -> Result<ClassifiedFileMetadata> {
         let raw = tokio::fs::metadata(path).await?;
         let size = raw.len();
@@ -68,18 +95,9 @@ impl Classifier for ArchiveClassifier {
             .modified()
             .ok()
             .or_else(|| raw.created().ok())
-            .and_then(|t| system_time_to_year(t));
+            .and_then(system_time_to_year);
 
-        let subcategory = match ext.as_str() {
-            "zip" => ArchiveSubcategory::Zip,
-            "tar" => ArchiveSubcategory::Tar,
-            "gz" | "tgz" => ArchiveSubcategory::Gz,
-            "rar" => ArchiveSubcategory::Rar,
-            "7z" => ArchiveSubcategory::SevenZ,
-            "bz2" | "tbz2" => ArchiveSubcategory::Bz2,
-            "xz" | "txz" => ArchiveSubcategory::Xz,
-            _ => ArchiveSubcategory::Other,
-        };
+        let subcategory = Self::subcategory(&ext);
 
         let mut classified = ClassifiedFileMetadata::new(
             path.to_path_buf(),
@@ -91,4 +109,4 @@ impl Classifier for ArchiveClassifier {
 
         Ok(classified)
     }
-}
\ No newline at end of file
+}
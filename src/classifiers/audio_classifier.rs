@@ -1,18 +1,19 @@
+use crate::metadata::AudioSubcategory;
+#[cfg(feature = "native")]
 use std::path::Path;
+#[cfg(feature = "native")]
 use async_trait::async_trait;
+#[cfg(feature = "native")]
 use crate::{
-    errors::Result, metadata::{AudioSubcategory, ClassifiedFileMetadata, FileCategory}, registry::Classifier, utils::{detect_mime, system_time_to_year}
+    errors::Result, metadata::{ClassifiedFileMetadata, FileCategory}, registry::Classifier, utils::{detect_mime, system_time_to_year}
 };
 
 pub struct AudioClassifier;
 
-#[async_trait]
-impl Classifier for AudioClassifier {
-    fn name(&self) -> &'static str {
-        "AudioClassifier"
-    }
+impl AudioClassifier {
+    pub const PRIORITY: u8 = 95;
 
-    fn confidence(&self, extension: &str, mime_type: &str) -> u8 {
+    pub fn score(extension: &str, mime_type: &str) -> u8 {
         // High confidence for common audio formats
         if matches!(
             extension,
@@ -44,6 +45,35 @@ impl Classifier for AudioClassifier {
         0
     }
 
+    /// Map a lowercase extension to its audio subcategory
+    pub fn subcategory(ext: &str) -> AudioSubcategory {
+        match ext {
+            "mp3" => AudioSubcategory::Mp3,
+            "wav" => AudioSubcategory::Wav,
+            "flac" => AudioSubcategory::Flac,
+            "aac" => AudioSubcategory::Aac,
+            "ogg" => AudioSubcategory::Ogg,
+            "m4a" => AudioSubcategory::M4a,
+            "opus" => AudioSubcategory::Opus,
+            "alac" => AudioSubcategory::Alac,
+            "aiff" | "aif" => AudioSubcategory::Aiff,
+            "wma" => AudioSubcategory::Wma,
+            _ => AudioSubcategory::Other,
+        }
+    }
+}
+
+#[cfg(feature = "native")]
+#[async_trait]
+impl Classifier for AudioClassifier {
+    fn name(&self) -> &'static str {
+        "AudioClassifier"
+    }
+
+    fn confidence(&self, extension: &str, mime_type: &str) -> u8 {
+        Self::score(extension, mime_type)
+    }
+
     async fn extract_metadata(&self, path: &Path) -> Result<ClassifiedFileMetadata> {
         let raw = tokio::fs::metadata(path).await?;
         let size = raw.len();
@@ -60,21 +90,9 @@ impl Classifier for AudioClassifier {
             .modified()
             .ok()
             .or_else(|| raw.created().ok())
-            .and_then(|t| system_time_to_year(t));
+            .and_then(system_time_to_year);
 
-        let subcategory = match ext.as_str() {
-            "mp3" => AudioSubcategory::Mp3,
-            "wav" => AudioSubcategory::Wav,
-            "flac" => AudioSubcategory::Flac,
-            "aac" => AudioSubcategory::Aac,
-            "ogg" => AudioSubcategory::Ogg,
-            "m4a" => AudioSubcategory::M4a,
-            "opus" => AudioSubcategory::Opus,
-            "alac" => AudioSubcategory::Alac,
-            "aiff" | "aif" => AudioSubcategory::Aiff,
-            "wma" => AudioSubcategory::Wma,
-            _ => AudioSubcategory::Other,
-        };
+        let subcategory = Self::subcategory(&ext);
 
         let mut classified = ClassifiedFileMetadata::new(
             path.to_path_buf(),
@@ -86,4 +104,4 @@ impl Classifier for AudioClassifier {
 
         Ok(classified)
     }
-}
\ No newline at end of file
+}
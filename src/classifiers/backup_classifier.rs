@@ -0,0 +1,134 @@
+use crate::metadata::BackupSubcategory;
+#[cfg(feature = "native")]
+use std::path::Path;
+#[cfg(feature = "native")]
+use async_trait::async_trait;
+#[cfg(feature = "native")]
+use crate::{
+    errors::Result,
+    metadata::{ClassifiedFileMetadata, FileCategory},
+    registry::Classifier,
+    utils::{detect_mime, system_time_to_year}
+};
+
+pub struct BackupClassifier;
+
+/// Archive extensions that hold a backup when the name says so
+const ARCHIVE_EXTENSIONS: [&str; 9] = ["zip", "7z", "rar", "tar", "gz", "tgz", "bz2", "xz", "zst"];
+
+impl BackupClassifier {
+    /// Highest of the built-ins: a backup is recognized by its name, and
+    /// `backup-2024.zip` should not land in Archives
+    pub const PRIORITY: u8 = 105;
+
+    /// Confidence from the extension alone
+    pub fn score(extension: &str, _mime_type: &str) -> u8 {
+        // Copies editors and installers leave next to the original
+        if matches!(extension, "bak" | "bkp" | "bck" | "backup" | "wbk" | "old") || extension.ends_with('~') {
+            return 100;
+        }
+
+        // Android `adb backup`, Nokia Suite, iTunes manifests, Firefox bookmark backups
+        if matches!(extension, "ab" | "nbu" | "mbdb" | "jsonlz4") {
+            return 90;
+        }
+
+        0
+    }
+
+    /// Confidence from a lowercase file name and its extension, which also
+    /// recognizes `backup-2024-03-12.zip`, `bookmarks_3_12_24.html` and the
+    /// `sms-*.xml` exports of Android backup apps
+    pub fn score_name(file_name: &str, extension: &str, mime_type: &str) -> u8 {
+        let score = Self::score(extension, mime_type);
+        if score > 0 {
+            return score;
+        }
+        if Self::is_named_archive(file_name, extension)
+            || Self::is_bookmark_export(file_name, extension)
+            || Self::is_phone_export(file_name, extension)
+        {
+            return 100;
+        }
+        0
+    }
+
+    /// Map a lowercase file name / extension to its backup subcategory
+    pub fn subcategory(file_name: &str, ext: &str) -> BackupSubcategory {
+        if ext == "jsonlz4" || Self::is_bookmark_export(file_name, ext) {
+            BackupSubcategory::Bookmarks
+        } else if matches!(ext, "ab" | "nbu" | "mbdb") || Self::is_phone_export(file_name, ext) {
+            BackupSubcategory::Phone
+        } else if Self::is_named_archive(file_name, ext) {
+            BackupSubcategory::Archives
+        } else if Self::score(ext, "") > 0 {
+            BackupSubcategory::Copies
+        } else {
+            BackupSubcategory::Other
+        }
+    }
+
+    /// `backup.zip`, `backup-2024.tar.gz`, `site_backup.7z`
+    fn is_named_archive(file_name: &str, ext: &str) -> bool {
+        if !ARCHIVE_EXTENSIONS.contains(&ext) {
+            return false;
+        }
+        file_name.starts_with("backup")
+            || file_name.split(|c: char| !c.is_ascii_alphanumeric()).any(|word| word == "backup")
+    }
+
+    /// Browser bookmark exports: `bookmarks.html`, `bookmarks_3_12_24.html`,
+    /// `bookmarks-2024-03-12.json`
+    fn is_bookmark_export(file_name: &str, ext: &str) -> bool {
+        matches!(ext, "html" | "htm" | "json") && file_name.starts_with("bookmarks")
+    }
+
+    /// Message and call log exports: `sms-20240312101500.xml`, `calls-….xml`
+    fn is_phone_export(file_name: &str, ext: &str) -> bool {
+        ext == "xml" && (file_name.starts_with("sms-") || file_name.starts_with("calls-"))
+    }
+}
+
+#[cfg(feature = "native")]
+#[async_trait]
+impl Classifier for BackupClassifier {
+    fn name(&self) -> &'static str {
+        "BackupClassifier"
+    }
+
+    fn confidence(&self, extension: &str, mime_type: &str) -> u8 {
+        Self::score(extension, mime_type)
+    }
+
+    async fn extract_metadata(&self, path: &Path) -> Result<ClassifiedFileMetadata> {
+        let raw = tokio::fs::metadata(path).await?;
+
+        let file_name = path
+            .file_name()
+            .and_then(|f| f.to_str())
+            .map(|s| s.to_ascii_lowercase())
+            .unwrap_or_default();
+
+        let ext = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(str::to_ascii_lowercase)
+            .unwrap_or_default();
+
+        let year = raw
+            .modified()
+            .ok()
+            .or_else(|| raw.created().ok())
+            .and_then(system_time_to_year);
+
+        let mut classified = ClassifiedFileMetadata::new(
+            path.to_path_buf(),
+            FileCategory::Backups(Self::subcategory(&file_name, &ext)),
+        );
+        classified.mime_type = Some(detect_mime(&ext));
+        classified.file_size = Some(raw.len());
+        classified.year = year;
+
+        Ok(classified)
+    }
+}
@@ -0,0 +1,125 @@
+//! The classifiers compiled into stash and how one is picked for a file,
+//! without any I/O. The registry classifies with them, and the no-I/O
+//! [`preview`](crate::preview) ranks them the same way.
+
+use crate::{
+    archive_classifier::ArchiveClassifier,
+    audio_classifier::AudioClassifier,
+    backup_classifier::BackupClassifier,
+    code_classifier::CodeClassifier,
+    design_classifier::DesignClassifier,
+    docs_classifier::DocumentClassifier,
+    executable_classifier::ExecutableClassifier,
+    font_classifier::FontClassifier,
+    generic::GenericClassifier,
+    image_classifier::{is_screenshot_name, ImageClassifier},
+    metadata::{FileCategory, ImageSubcategory},
+    video_classifier::VideoClassifier,
+};
+
+/// The classifiers compiled into stash, dispatched with a `match` instead of a vtable
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuiltinClassifier {
+    Backup,
+    Image,
+    Audio,
+    Design,
+    Video,
+    Document,
+    Code,
+    Font,
+    Archive,
+    Executable,
+    Generic,
+}
+
+impl BuiltinClassifier {
+    /// Every built-in classifier, highest priority first
+    pub const ALL: [BuiltinClassifier; 11] = [
+        Self::Backup, Self::Image, Self::Audio, Self::Design, Self::Video, Self::Document,
+        Self::Code, Self::Font, Self::Archive, Self::Executable, Self::Generic,
+    ];
+
+    pub fn priority(self) -> u8 {
+        match self {
+            Self::Backup => BackupClassifier::PRIORITY,
+            Self::Image => ImageClassifier::PRIORITY,
+            Self::Audio => AudioClassifier::PRIORITY,
+            Self::Design => DesignClassifier::PRIORITY,
+            Self::Video => VideoClassifier::PRIORITY,
+            Self::Document => DocumentClassifier::PRIORITY,
+            Self::Code => CodeClassifier::PRIORITY,
+            Self::Font => FontClassifier::PRIORITY,
+            Self::Archive => ArchiveClassifier::PRIORITY,
+            Self::Executable => ExecutableClassifier::PRIORITY,
+            Self::Generic => GenericClassifier::PRIORITY,
+        }
+    }
+
+    pub fn confidence(self, extension: &str, mime_type: &str) -> u8 {
+        match self {
+            Self::Backup => BackupClassifier::score(extension, mime_type),
+            Self::Image => ImageClassifier::score(extension, mime_type),
+            Self::Audio => AudioClassifier::score(extension, mime_type),
+            Self::Design => DesignClassifier::score(extension, mime_type),
+            Self::Video => VideoClassifier::score(extension, mime_type),
+            Self::Document => DocumentClassifier::score(extension, mime_type),
+            Self::Code => CodeClassifier::score(extension, mime_type),
+            Self::Font => FontClassifier::score(extension, mime_type),
+            Self::Archive => ArchiveClassifier::score(extension, mime_type),
+            Self::Executable => ExecutableClassifier::score(extension, mime_type),
+            Self::Generic => GenericClassifier::score(extension, mime_type),
+        }
+    }
+
+    /// Like [`confidence`](Self::confidence), for classifiers that also
+    /// recognize files by name; `file_name` is lowercase
+    pub fn confidence_named(self, file_name: &str, extension: &str, mime_type: &str) -> u8 {
+        match self {
+            Self::Backup => BackupClassifier::score_name(file_name, extension, mime_type),
+            other => other.confidence(extension, mime_type),
+        }
+    }
+
+    /// The category this classifier puts a file in going by its (lowercase)
+    /// name alone. Classifying the file itself may look inside it too, e.g.
+    /// for a screenshot tool's mark in a PNG.
+    pub fn category_named(self, file_name: &str, extension: &str) -> FileCategory {
+        match self {
+            Self::Backup => FileCategory::Backups(BackupClassifier::subcategory(file_name, extension)),
+            Self::Image if is_screenshot_name(file_name) => FileCategory::Images(ImageSubcategory::Screenshots),
+            Self::Image => FileCategory::Images(ImageClassifier::subcategory(extension)),
+            Self::Audio => FileCategory::Audio(AudioClassifier::subcategory(extension)),
+            Self::Design => FileCategory::Design(DesignClassifier::subcategory(extension)),
+            Self::Video => FileCategory::Videos(VideoClassifier::subcategory(extension)),
+            Self::Document => FileCategory::Documents(DocumentClassifier::subcategory(extension)),
+            Self::Code => FileCategory::Code(CodeClassifier::subcategory(file_name, extension)),
+            Self::Font => FileCategory::Fonts(FontClassifier::subcategory(extension)),
+            Self::Archive => FileCategory::Archives(ArchiveClassifier::subcategory(extension)),
+            Self::Executable => FileCategory::Executables(ExecutableClassifier::subcategory(extension)),
+            Self::Generic => FileCategory::Others,
+        }
+    }
+
+    /// The built-in classifier the registry tries first for a file: the
+    /// highest [`weighted_score`], the higher priority of equal ones
+    pub fn best_named(file_name: &str, extension: &str, mime_type: &str, min_score: u16) -> Option<Self> {
+        let mut best: Option<(Self, u16)> = None;
+        for builtin in Self::ALL {
+            let confidence = builtin.confidence_named(file_name, extension, mime_type);
+            if let Some(score) = weighted_score(builtin.priority(), confidence, min_score)
+                && best.is_none_or(|(_, top)| score > top)
+            {
+                best = Some((builtin, score));
+            }
+        }
+        best.map(|(builtin, _)| builtin)
+    }
+}
+
+/// Priority × confidence, what classifiers are ranked by; `None` when the
+/// classifier has no confidence in the file or falls short of `min_score`
+pub fn weighted_score(priority: u8, confidence: u8, min_score: u16) -> Option<u16> {
+    let score = (priority as u16) * (confidence as u16);
+    (confidence > 0 && score >= min_score).then_some(score)
+}
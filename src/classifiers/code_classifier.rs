@@ -1,18 +1,21 @@
+use crate::{
+    code_const::{CODE_MIME_PATTERNS, EXTENSION_MAP}, metadata::CodeSubcategory
+};
+#[cfg(feature = "native")]
 use std::path::Path;
+#[cfg(feature = "native")]
 use async_trait::async_trait;
+#[cfg(feature = "native")]
 use crate::{
-    code_const::{CODE_MIME_PATTERNS, EXTENSION_MAP}, errors::Result, metadata::{ClassifiedFileMetadata, CodeSubcategory, FileCategory}, registry::Classifier, utils::{detect_mime, system_time_to_year}
+    errors::Result, metadata::{ClassifiedFileMetadata, FileCategory}, registry::Classifier, utils::{detect_mime, system_time_to_year}
 };
 
 pub struct CodeClassifier;
 
-#[async_trait]
-impl Classifier for CodeClassifier {
-    fn name(&self) -> &'static str {
-        "CodeClassifier"
-    }
+impl CodeClassifier {
+    pub const PRIORITY: u8 = 80;
 
-    fn confidence(&self, extension: &str, mime_type: &str) -> u8 {
+    pub fn score(extension: &str, mime_type: &str) -> u8 {
         // High confidence for programming languages
         if matches!(
             extension,
@@ -70,6 +73,33 @@ impl Classifier for CodeClassifier {
         0
     }
 
+    /// Map a lowercase file name / extension to its code subcategory
+    pub fn subcategory(file_name: &str, ext: &str) -> CodeSubcategory {
+        EXTENSION_MAP
+            .get(file_name)
+            .cloned()
+            .or_else(|| EXTENSION_MAP.get(ext).cloned())
+            .unwrap_or_else(|| {
+                CodeSubcategory::Other(if ext.is_empty() {
+                    file_name.to_string()
+                } else {
+                    ext.to_string()
+                })
+            })
+    }
+}
+
+#[cfg(feature = "native")]
+#[async_trait]
+impl Classifier for CodeClassifier {
+    fn name(&self) -> &'static str {
+        "CodeClassifier"
+    }
+
+    fn confidence(&self, extension: &str, mime_type: &str) -> u8 {
+        Self::score(extension, mime_type)
+    }
+
     async fn extract_metadata(&self, path: &Path) -> Result<ClassifiedFileMetadata> {
         let raw = tokio::fs::metadata(path).await?;
         let size = raw.len();
@@ -92,20 +122,10 @@ impl Classifier for CodeClassifier {
             .modified()
             .ok()
             .or_else(|| raw.created().ok())
-            .and_then(|t| system_time_to_year(t));
+            .and_then(system_time_to_year);
 
         // Determine subcategory using the extension map
-        let subcategory = EXTENSION_MAP
-            .get(file_name.as_str())
-            .cloned()
-            .or_else(|| EXTENSION_MAP.get(ext.as_str()).cloned())
-            .unwrap_or_else(|| {
-                CodeSubcategory::Other(if ext.is_empty() {
-                    file_name.clone()
-                } else {
-                    ext.clone()
-                })
-            });
+        let subcategory = Self::subcategory(&file_name, &ext);
 
         let mut classified = ClassifiedFileMetadata::new(
             path.to_path_buf(),
@@ -117,4 +137,4 @@ impl Classifier for CodeClassifier {
 
         Ok(classified)
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,95 @@
+use crate::metadata::DesignSubcategory;
+#[cfg(feature = "native")]
+use std::path::Path;
+#[cfg(feature = "native")]
+use async_trait::async_trait;
+#[cfg(feature = "native")]
+use crate::{
+    errors::Result,
+    metadata::{ClassifiedFileMetadata, FileCategory},
+    registry::Classifier,
+    utils::{detect_mime, system_time_to_year}
+};
+
+pub struct DesignClassifier;
+
+impl DesignClassifier {
+    /// Above video and documents, and enough to beat the image classifier's
+    /// `image/` MIME match for Photoshop files
+    pub const PRIORITY: u8 = 92;
+
+    pub fn score(extension: &str, mime_type: &str) -> u8 {
+        // Design tool and 3D formats
+        if matches!(
+            extension,
+            "psd" | "psb" | "ai" | "xd" | "fig" | "sketch" | "blend" |
+            "obj" | "fbx" | "stl" | "gltf" | "glb" | "3ds" | "dae" | "ply"
+        ) {
+            return 100;
+        }
+
+        // Less common 3D formats (3ds Max, Cinema 4D, Maya, 3MF, USDZ)
+        if matches!(extension, "max" | "c4d" | "ma" | "mb" | "3mf" | "usdz") {
+            return 80;
+        }
+
+        if mime_type.starts_with("model/") || mime_type == "image/vnd.adobe.photoshop" {
+            return 90;
+        }
+
+        0
+    }
+
+    /// Map a lowercase extension to its design subcategory
+    pub fn subcategory(ext: &str) -> DesignSubcategory {
+        match ext {
+            "psd" | "psb" => DesignSubcategory::Photoshop,
+            "ai" => DesignSubcategory::Illustrator,
+            "xd" => DesignSubcategory::AdobeXd,
+            "fig" => DesignSubcategory::Figma,
+            "sketch" => DesignSubcategory::Sketch,
+            "blend" => DesignSubcategory::Blender,
+            "obj" | "fbx" | "stl" | "gltf" | "glb" | "3ds" | "dae" | "ply" | "max" | "c4d" | "ma" | "mb"
+            | "3mf" | "usdz" => DesignSubcategory::Models,
+            _ => DesignSubcategory::Other,
+        }
+    }
+}
+
+#[cfg(feature = "native")]
+#[async_trait]
+impl Classifier for DesignClassifier {
+    fn name(&self) -> &'static str {
+        "DesignClassifier"
+    }
+
+    fn confidence(&self, extension: &str, mime_type: &str) -> u8 {
+        Self::score(extension, mime_type)
+    }
+
+    async fn extract_metadata(&self, path: &Path) -> Result<ClassifiedFileMetadata> {
+        let raw = tokio::fs::metadata(path).await?;
+
+        let ext = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(str::to_ascii_lowercase)
+            .unwrap_or_default();
+
+        let year = raw
+            .modified()
+            .ok()
+            .or_else(|| raw.created().ok())
+            .and_then(system_time_to_year);
+
+        let mut classified = ClassifiedFileMetadata::new(
+            path.to_path_buf(),
+            FileCategory::Design(Self::subcategory(&ext)),
+        );
+        classified.mime_type = Some(detect_mime(&ext));
+        classified.file_size = Some(raw.len());
+        classified.year = year;
+
+        Ok(classified)
+    }
+}
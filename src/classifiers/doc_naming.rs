@@ -0,0 +1,174 @@
+//! Names for scanned documents from their text: `2024-03-12 Electric Bill.pdf`
+//! instead of `SCAN_0042.pdf`. Pure, so the OCR output it reads (untrusted,
+//! often garbled) is fuzzed along with the other naming code.
+
+use chrono::NaiveDate;
+
+use crate::path_safety::sanitize_component;
+
+/// Longest title kept, in characters
+const MAX_TITLE: usize = 60;
+
+/// Words that make a line a likely document title
+const TITLE_WORDS: [&str; 14] = [
+    "bill", "invoice", "statement", "receipt", "contract", "agreement", "letter", "report", "certificate",
+    "payslip", "policy", "notice", "tax", "quote",
+];
+
+/// Prefixes scanners, phones and cameras give files before a counter
+const GENERIC_PREFIXES: [&str; 11] =
+    ["scanned", "scan", "image", "img", "document", "doc", "file", "page", "untitled", "pxl", "dsc"];
+
+const MONTHS: [&str; 12] = [
+    "january", "february", "march", "april", "may", "june", "july", "august", "september", "october",
+    "november", "december",
+];
+
+/// What a document's text says about it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocName {
+    pub date: Option<NaiveDate>,
+    pub title: String,
+}
+
+impl DocName {
+    /// `2024-03-12 Electric Bill.pdf`; just the title when there is no date
+    pub fn file_name(&self, extension: Option<&str>) -> String {
+        let stem = match self.date {
+            Some(date) => format!("{} {}", date.format("%Y-%m-%d"), self.title),
+            None => self.title.clone(),
+        };
+        match extension {
+            Some(ext) if !ext.is_empty() => format!("{}.{}", stem, sanitize_component(ext)),
+            _ => stem,
+        }
+    }
+}
+
+/// Whether a file stem carries no meaning a person gave it, e.g. `SCAN_0042`,
+/// `IMG_20240312_101500` or `00017`. Only such files are renamed.
+pub fn looks_generic(stem: &str) -> bool {
+    let lower = stem.to_lowercase();
+    let mut rest = lower.as_str();
+    // `Scanned Document 3` drops both words
+    while let Some(stripped) = GENERIC_PREFIXES
+        .iter()
+        .find_map(|prefix| rest.trim_start_matches(|c: char| !c.is_alphanumeric()).strip_prefix(prefix))
+    {
+        rest = stripped;
+    }
+    rest.chars().filter(|c| c.is_alphabetic()).count() < 3
+}
+
+/// A title and date read from extracted text, or `None` if no line looks
+/// like a title
+pub fn suggest_name(text: &str) -> Option<DocName> {
+    let title = find_title(text)?;
+    Some(DocName { date: find_date(text), title })
+}
+
+/// The first short line naming a kind of document, else the first short
+/// line that reads like words
+fn find_title(text: &str) -> Option<String> {
+    let candidates: Vec<&str> = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| {
+            let words = line.split_whitespace().count();
+            let letters = line.chars().filter(|c| c.is_alphabetic()).count();
+            (1..=8).contains(&words) && letters >= 4 && letters * 2 >= line.chars().count()
+        })
+        .take(20)
+        .collect();
+    let line = candidates
+        .iter()
+        .find(|line| {
+            line.split(|c: char| !c.is_alphanumeric())
+                .any(|word| TITLE_WORDS.contains(&word.to_lowercase().as_str()))
+        })
+        .or(candidates.first())?;
+
+    let title = title_case(line);
+    let title: String = title.chars().take(MAX_TITLE).collect();
+    let title = sanitize_component(title.trim());
+    (title != "Unknown").then_some(title)
+}
+
+/// `ELECTRIC BILL` / `electric bill` → `Electric Bill`; mixed case is kept
+fn title_case(line: &str) -> String {
+    let uniform = !line.chars().any(char::is_lowercase) || !line.chars().any(char::is_uppercase);
+    if !uniform {
+        return line.split_whitespace().collect::<Vec<_>>().join(" ");
+    }
+    line.split_whitespace()
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars.flat_map(char::to_lowercase)).collect(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The first unambiguous date: `2024-03-12`, `2024/03/12`, `March 12, 2024`
+/// or `12 Mar 2024`. Numeric day/month orders are skipped as ambiguous.
+fn find_date(text: &str) -> Option<NaiveDate> {
+    let tokens: Vec<&str> = text
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .map(|t| t.trim_matches(|c: char| !c.is_alphanumeric()))
+        .filter(|t| !t.is_empty())
+        .collect();
+
+    for (i, token) in tokens.iter().enumerate() {
+        if let Some(date) = iso_date(token) {
+            return Some(date);
+        }
+        let Some(month) = month_number(token) else {
+            continue;
+        };
+        let number = |j: usize| tokens.get(j).and_then(|t| t.parse::<u32>().ok());
+        // March 12 2024
+        if let (Some(day), Some(year)) = (number(i + 1), number(i + 2))
+            && let Some(date) = ymd(year, month, day)
+        {
+            return Some(date);
+        }
+        // 12 March 2024
+        if let (Some(day), Some(year)) = (i.checked_sub(1).and_then(number), number(i + 1))
+            && let Some(date) = ymd(year, month, day)
+        {
+            return Some(date);
+        }
+    }
+    None
+}
+
+fn iso_date(token: &str) -> Option<NaiveDate> {
+    let mut parts = token.split(['-', '/']);
+    let (year, month, day) = (parts.next()?, parts.next()?, parts.next()?);
+    if parts.next().is_some() || year.len() != 4 || month.len() > 2 || day.len() > 2 {
+        return None;
+    }
+    ymd(year.parse().ok()?, month.parse().ok()?, day.parse().ok()?)
+}
+
+fn month_number(token: &str) -> Option<u32> {
+    if token.len() < 3 || !token.is_ascii() {
+        return None;
+    }
+    let lower = token.to_ascii_lowercase();
+    MONTHS
+        .iter()
+        .position(|month| month.starts_with(&lower) && (lower.len() == 3 || lower.len() == month.len()))
+        .map(|i| i as u32 + 1)
+}
+
+/// Dates from a plausible range only; OCR noise makes odd years
+fn ymd(year: u32, month: u32, day: u32) -> Option<NaiveDate> {
+    if !(1900..=2200).contains(&year) {
+        return None;
+    }
+    NaiveDate::from_ymd_opt(year as i32, month, day)
+}
@@ -1,26 +1,37 @@
+use crate::metadata::DocumentSubcategory;
+#[cfg(feature = "native")]
 use std::path::Path;
-
+#[cfg(feature = "native")]
 use async_trait::async_trait;
-
+#[cfg(feature = "native")]
 use crate::{
-    errors::Result, metadata::{ClassifiedFileMetadata, DocumentSubcategory, FileCategory}, registry::Classifier, utils::{detect_mime, system_time_to_year}
+    errors::Result,
+    metadata::{ClassifiedFileMetadata, ExtraMetadataValue, FileCategory},
+    registry::Classifier,
+    utils::{detect_mime, system_time_to_year}
 };
 
 pub struct DocumentClassifier;
 
-#[async_trait]
-impl Classifier for DocumentClassifier {
-    fn name(&self) -> &'static str {
-        "DocumentClassifier"
-    }
+impl DocumentClassifier {
+    pub const PRIORITY: u8 = 85;
 
-    fn confidence(&self, extension: &str, mime_type: &str) -> u8 {
+    pub fn score(extension: &str, mime_type: &str) -> u8 {
         // High confidence for specific document extensions
         if matches!(
             extension,
             "pdf" | "doc" | "docx" | "ppt" | "pptx" | "xls" | "xlsx" | 
             "odt" | "ods" | "odp" | "docm" | "dotx" | "dotm" | "xlsm" |
-            "xltx" | "xltm" | "pptm" | "potx" | "potm" | "ppsx" | "ppsm" | "epub"
+            "xltx" | "xltm" | "pptm" | "potx" | "potm" | "ppsx" | "ppsm"
+        ) {
+            return 100;
+        }
+
+        // E-books and comic archives, which archive or image MIME types would otherwise claim
+        if matches!(
+            extension,
+            "epub" | "mobi" | "azw" | "azw3" | "fb2" | "djvu" | "djv" |
+            "cbz" | "cbr" | "cb7" | "cbt"
         ) {
             return 100;
         }
@@ -42,7 +53,9 @@ impl Classifier for DocumentClassifier {
            mime_type.contains("opendocument") ||
            mime_type.contains("officedocument") ||
            mime_type == "application/pdf" ||
-           mime_type == "application/epub+zip" {
+           mime_type == "application/epub+zip" ||
+           mime_type == "application/x-mobipocket-ebook" ||
+           mime_type == "application/x-fictionbook+xml" {
             return 90;
         }
 
@@ -54,6 +67,40 @@ impl Classifier for DocumentClassifier {
         0
     }
 
+    /// Map a lowercase extension to its document subcategory
+    pub fn subcategory(ext: &str) -> DocumentSubcategory {
+        match ext {
+            "pdf" => DocumentSubcategory::Pdf,
+            "doc" | "docx" | "docm" | "dotx" | "dotm" | "odt" | "rtf" => {
+                DocumentSubcategory::Word
+            }
+            "xls" | "xlsx" | "xlsm" | "xltx" | "xltm" | "ods" | "csv" => {
+                DocumentSubcategory::Spreadsheet
+            }
+            "ppt" | "pptx" | "pptm" | "potx" | "potm" | "ppsx" | "ppsm" | "odp" => {
+                DocumentSubcategory::Presentation
+            }
+            "txt" | "md" | "markdown" => DocumentSubcategory::Text,
+            "tex" | "ltx" | "sty" | "cls" | "bib" => DocumentSubcategory::Technical,
+            "odg" | "odf" => DocumentSubcategory::OpenDocument,
+            "epub" | "mobi" | "azw" | "azw3" | "fb2" | "djvu" | "djv" => DocumentSubcategory::Ebook,
+            "cbz" | "cbr" | "cb7" | "cbt" => DocumentSubcategory::Comic,
+            _ => DocumentSubcategory::Other,
+        }
+    }
+}
+
+#[cfg(feature = "native")]
+#[async_trait]
+impl Classifier for DocumentClassifier {
+    fn name(&self) -> &'static str {
+        "DocumentClassifier"
+    }
+
+    fn confidence(&self, extension: &str, mime_type: &str) -> u8 {
+        Self::score(extension, mime_type)
+    }
+
     async fn extract_metadata(&self, path: &Path) -> Result<ClassifiedFileMetadata> {
         let raw = tokio::fs::metadata(path).await?;
         let size = raw.len();
@@ -70,30 +117,10 @@ impl Classifier for DocumentClassifier {
             .modified()
             .ok()
             .or_else(|| raw.created().ok())
-            .and_then(|t| system_time_to_year(t));
+            .and_then(system_time_to_year);
 
         // Subcategory
-        let subcategory = path
-            .extension()
-            .and_then(|e| e.to_str())
-            .map(|ext| match ext.to_ascii_lowercase().as_str() {
-                "pdf" => DocumentSubcategory::Pdf,
-                "doc" | "docx" | "docm" | "dotx" | "dotm" | "odt" | "rtf" => {
-                    DocumentSubcategory::Word
-                }
-                "xls" | "xlsx" | "xlsm" | "xltx" | "xltm" | "ods" | "csv" => {
-                    DocumentSubcategory::Spreadsheet
-                }
-                "ppt" | "pptx" | "pptm" | "potx" | "potm" | "ppsx" | "ppsm" | "odp" => {
-                    DocumentSubcategory::Presentation
-                }
-                "txt" | "md" | "markdown" => DocumentSubcategory::Text,
-                "tex" | "ltx" | "sty" | "cls" | "bib" => DocumentSubcategory::Technical,
-                "odg" | "odf" => DocumentSubcategory::OpenDocument,
-                "epub" => DocumentSubcategory::Ebook,
-                _ => DocumentSubcategory::Other,
-            })
-            .unwrap_or(DocumentSubcategory::Other);
+        let subcategory = Self::subcategory(&ext.to_ascii_lowercase());
 
         let mut classified = ClassifiedFileMetadata::new(
             path.to_path_buf(),
@@ -103,6 +130,90 @@ impl Classifier for DocumentClassifier {
         classified.file_size = Some(size);
         classified.year = year;
 
+        if ext.eq_ignore_ascii_case("epub") {
+            let epub = path.to_path_buf();
+            if let Ok(Some(info)) = tokio::task::spawn_blocking(move || read_epub_info(&epub)).await {
+                if let Some(title) = info.title {
+                    classified.extra.insert("title".into(), ExtraMetadataValue::String(title));
+                }
+                if !info.authors.is_empty() {
+                    classified.extra.insert("author".into(), ExtraMetadataValue::String(info.authors.join(", ")));
+                }
+            }
+        }
+
         Ok(classified)
     }
-}
\ No newline at end of file
+}
+
+/// Title and authors from an EPUB's OPF package document
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EpubInfo {
+    pub title: Option<String>,
+    pub authors: Vec<String>,
+}
+
+/// Path of the OPF package inside the archive, from `META-INF/container.xml`
+pub fn opf_path(container_xml: &str) -> Option<String> {
+    let tag = container_xml
+        .match_indices("<rootfile")
+        .map(|(start, _)| &container_xml[start + "<rootfile".len()..])
+        .find(|rest| rest.starts_with(char::is_whitespace))?;
+    let tag = &tag[..tag.find('>')?];
+    let value = tag.split("full-path=").nth(1)?;
+    let quote = value.chars().next().filter(|q| *q == '"' || *q == '\'')?;
+    let value = &value[1..];
+    Some(unescape_xml(&value[..value.find(quote)?]))
+}
+
+/// Read `dc:title` and every `dc:creator` from an OPF package document
+pub fn parse_opf(opf: &str) -> EpubInfo {
+    EpubInfo {
+        title: element_texts(opf, "dc:title").into_iter().next(),
+        authors: element_texts(opf, "dc:creator"),
+    }
+}
+
+/// Non-empty text of every `<name ...>text</name>` element
+fn element_texts(xml: &str, name: &str) -> Vec<String> {
+    let open = format!("<{}", name);
+    let close = format!("</{}>", name);
+    let mut texts = Vec::new();
+    let mut rest = xml;
+    while let Some(start) = rest.find(&open) {
+        rest = &rest[start + open.len()..];
+        // `<dc:title>` or `<dc:title id="...">`, not `<dc:titles>`
+        if !rest.starts_with(['>', ' ', '\t', '\n', '\r']) {
+            continue;
+        }
+        let Some(body_start) = rest.find('>') else { break };
+        let Some(end) = rest.find(&close) else { break };
+        if body_start < end {
+            let text = unescape_xml(rest[body_start + 1..end].trim());
+            if !text.is_empty() {
+                texts.push(text);
+            }
+        }
+        rest = &rest[end..];
+    }
+    texts
+}
+
+fn unescape_xml(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Open an EPUB and read its OPF metadata; `None` if it is not a readable EPUB
+#[cfg(feature = "native")]
+pub fn read_epub_info(path: &Path) -> Option<EpubInfo> {
+    let read = |name: &str| -> Option<String> {
+        String::from_utf8(crate::archive::read_entry(path, name, 1 << 20).ok()??).ok()
+    };
+    let opf_path = opf_path(&read("META-INF/container.xml")?)?;
+    let opf = read(&opf_path)?;
+    Some(parse_opf(&opf))
+}
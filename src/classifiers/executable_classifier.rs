@@ -1,18 +1,21 @@
+use crate::{
+    classifiers::executables_const::{EXECUTABLE_EXTENSION_MAP, EXECUTABLE_MIME_PATTERNS}, metadata::ExecutableSubcategory
+};
+#[cfg(feature = "native")]
 use std::path::Path;
+#[cfg(feature = "native")]
 use async_trait::async_trait;
+#[cfg(feature = "native")]
 use crate::{
-    classifiers::executables_const::{EXECUTABLE_EXTENSION_MAP, EXECUTABLE_MIME_PATTERNS}, errors::Result, metadata::{ClassifiedFileMetadata, ExecutableSubcategory, FileCategory}, registry::Classifier, utils::{detect_mime, system_time_to_year}
+    errors::Result, metadata::{ClassifiedFileMetadata, FileCategory}, registry::Classifier, utils::{detect_mime, system_time_to_year}
 };
 
 pub struct ExecutableClassifier;
 
-#[async_trait]
-impl Classifier for ExecutableClassifier {
-    fn name(&self) -> &'static str {
-        "ExecutableClassifier"
-    }
+impl ExecutableClassifier {
+    pub const PRIORITY: u8 = 70;
 
-    fn confidence(&self, extension: &str, mime_type: &str) -> u8 {
+    pub fn score(extension: &str, mime_type: &str) -> u8 {
         // High confidence for binary executables
         if matches!(
             extension,
@@ -41,6 +44,26 @@ impl Classifier for ExecutableClassifier {
         0
     }
 
+    /// Map a lowercase extension to its executable subcategory
+    pub fn subcategory(ext: &str) -> ExecutableSubcategory {
+        EXECUTABLE_EXTENSION_MAP
+            .get(ext)
+            .cloned()
+            .unwrap_or(ExecutableSubcategory::Other)
+    }
+}
+
+#[cfg(feature = "native")]
+#[async_trait]
+impl Classifier for ExecutableClassifier {
+    fn name(&self) -> &'static str {
+        "ExecutableClassifier"
+    }
+
+    fn confidence(&self, extension: &str, mime_type: &str) -> u8 {
+        Self::score(extension, mime_type)
+    }
+
     async fn extract_metadata(&self, path: &Path) -> Result<ClassifiedFileMetadata> {
         let raw = tokio::fs::metadata(path).await?;
         let size = raw.len();
@@ -57,12 +80,9 @@ impl Classifier for ExecutableClassifier {
             .modified()
             .ok()
             .or_else(|| raw.created().ok())
-            .and_then(|t| system_time_to_year(t));
+            .and_then(system_time_to_year);
 
-        let subcategory = EXECUTABLE_EXTENSION_MAP
-            .get(ext.as_str())
-            .cloned()
-            .unwrap_or(ExecutableSubcategory::Other);
+        let subcategory = Self::subcategory(&ext);
 
         let mut classified = ClassifiedFileMetadata::new(
             path.to_path_buf(),
@@ -74,4 +94,4 @@ impl Classifier for ExecutableClassifier {
 
         Ok(classified)
     }
-}
\ No newline at end of file
+}
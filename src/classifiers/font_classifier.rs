@@ -0,0 +1,88 @@
+use crate::metadata::FontSubcategory;
+#[cfg(feature = "native")]
+use std::path::Path;
+#[cfg(feature = "native")]
+use async_trait::async_trait;
+#[cfg(feature = "native")]
+use crate::{
+    errors::Result,
+    metadata::{ClassifiedFileMetadata, FileCategory},
+    registry::Classifier,
+    utils::{detect_mime, system_time_to_year}
+};
+
+pub struct FontClassifier;
+
+impl FontClassifier {
+    pub const PRIORITY: u8 = 78;
+
+    pub fn score(extension: &str, mime_type: &str) -> u8 {
+        // Font extensions are not shared with any other format
+        if matches!(extension, "ttf" | "otf" | "woff" | "woff2" | "eot" | "ttc") {
+            return 100;
+        }
+
+        // Legacy PostScript fonts
+        if matches!(extension, "pfb" | "pfm" | "afm") {
+            return 80;
+        }
+
+        if mime_type.starts_with("font/")
+            || mime_type.contains("font-")
+            || mime_type == "application/vnd.ms-fontobject" {
+            return 90;
+        }
+
+        0
+    }
+
+    /// Map a lowercase extension to its font subcategory
+    pub fn subcategory(ext: &str) -> FontSubcategory {
+        match ext {
+            "ttf" | "ttc" => FontSubcategory::TrueType,
+            "otf" => FontSubcategory::OpenType,
+            "woff" => FontSubcategory::Woff,
+            "woff2" => FontSubcategory::Woff2,
+            "eot" => FontSubcategory::Eot,
+            _ => FontSubcategory::Other,
+        }
+    }
+}
+
+#[cfg(feature = "native")]
+#[async_trait]
+impl Classifier for FontClassifier {
+    fn name(&self) -> &'static str {
+        "FontClassifier"
+    }
+
+    fn confidence(&self, extension: &str, mime_type: &str) -> u8 {
+        Self::score(extension, mime_type)
+    }
+
+    async fn extract_metadata(&self, path: &Path) -> Result<ClassifiedFileMetadata> {
+        let raw = tokio::fs::metadata(path).await?;
+
+        let ext = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(str::to_ascii_lowercase)
+            .unwrap_or_default();
+
+        let year = raw
+            .modified()
+            .ok()
+            .or_else(|| raw.created().ok())
+            .and_then(system_time_to_year);
+
+        let mut classified = ClassifiedFileMetadata::new(
+            path.to_path_buf(),
+            FileCategory::Fonts(Self::subcategory(&ext)),
+        );
+        classified.mime_type = Some(detect_mime(&ext));
+        classified.file_size = Some(raw.len());
+        classified.year = year;
+
+        Ok(classified)
+    }
+}
@@ -1,19 +1,31 @@
+#[cfg(feature = "native")]
 use std::path::Path;
+#[cfg(feature = "native")]
 use async_trait::async_trait;
 
+#[cfg(feature = "native")]
 use crate::{errors::Result, metadata::{ClassifiedFileMetadata, FileCategory}, registry::Classifier};
 
 
 pub struct GenericClassifier;
 
+impl GenericClassifier {
+    pub const PRIORITY: u8 = 10;
+
+    pub fn score(_: &str, _: &str) -> u8 {
+        1
+    }
+}
+
+#[cfg(feature = "native")]
 #[async_trait]
 impl Classifier for GenericClassifier {
     fn name(&self) -> &'static str {
         "GenericClassifier"
     }
 
-    fn confidence(&self, _: &str, _: &str) -> u8 {
-        1
+    fn confidence(&self, extension: &str, mime_type: &str) -> u8 {
+        Self::score(extension, mime_type)
     }
 
     async fn extract_metadata(&self, path: &Path) -> Result<ClassifiedFileMetadata> {
@@ -22,4 +34,4 @@ impl Classifier for GenericClassifier {
             FileCategory::Others,
         ))
     }
-}
\ No newline at end of file
+}
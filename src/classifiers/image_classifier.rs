@@ -1,18 +1,80 @@
+use crate::metadata::ImageSubcategory;
+#[cfg(feature = "native")]
 use std::path::Path;
+#[cfg(feature = "native")]
 use async_trait::async_trait;
+#[cfg(feature = "native")]
 use crate::{
-    errors::Result, metadata::{ClassifiedFileMetadata, FileCategory, ImageSubcategory}, registry::Classifier, utils::{detect_mime, system_time_to_year}
+    errors::Result, metadata::{ClassifiedFileMetadata, FileCategory}, registry::Classifier, utils::{detect_mime, system_time_to_year}
 };
 
 pub struct ImageClassifier;
 
-#[async_trait]
-impl Classifier for ImageClassifier {
-    fn name(&self) -> &'static str {
-        "ImageClassifier"
+/// Name prefixes screenshot tools give their files, compared ignoring case:
+/// macOS (`Screenshot 2024-03-12 at 10.15.02`, before Mojave `Screen Shot`),
+/// Windows (`Screenshot (3)`, Snipping Tool `Snip_...`), GNOME (`Screenshot
+/// from ...`), Android (`Screenshot_20240312-101502`) and localized macOS
+const SCREENSHOT_PREFIXES: [&str; 9] = [
+    "screenshot",
+    "screen shot",
+    "snip_",
+    "snip ",
+    "bildschirmfoto",
+    "capture d’écran",
+    "capture d'écran",
+    "captura de pantalla",
+    "schermafbeelding",
+];
+
+/// Text in a PNG's metadata written by screenshot tools: macOS puts
+/// `Screenshot` in the XMP user comment, the others sign as the software
+const SCREENSHOT_MARKERS: [&[u8]; 6] =
+    [b"Screenshot", b"gnome-screenshot", b"ShareX", b"Greenshot", b"Flameshot", b"Spectacle"];
+
+/// Whether the file name `name` is one a screenshot tool would give
+pub fn is_screenshot_name(name: &str) -> bool {
+    let name = name.to_lowercase();
+    SCREENSHOT_PREFIXES.iter().any(|prefix| name.starts_with(prefix))
+}
+
+/// Whether the start of a PNG (`head`) has a text chunk left by a screenshot
+/// tool. Only chunks before the image data are looked at.
+pub fn is_screenshot_png(head: &[u8]) -> bool {
+    let Some(mut rest) = head.strip_prefix(b"\x89PNG\r\n\x1a\n".as_slice()) else {
+        return false;
+    };
+    while rest.len() >= 8 {
+        let len = u32::from_be_bytes([rest[0], rest[1], rest[2], rest[3]]) as usize;
+        let kind = &rest[4..8];
+        if kind == b"IDAT" {
+            break;
+        }
+        let Some(data) = rest.get(8..8usize.saturating_add(len)) else {
+            break;
+        };
+        if matches!(kind, b"tEXt" | b"iTXt")
+            && SCREENSHOT_MARKERS.iter().any(|marker| data.windows(marker.len()).any(|window| window == *marker))
+        {
+            return true;
+        }
+        // Data and CRC
+        rest = rest.get(8 + len + 4..).unwrap_or_default();
     }
+    false
+}
 
-    fn confidence(&self, extension: &str, mime_type: &str) -> u8 {
+/// Year in a file name like `Screenshot 2024-03-12 ...` or
+/// `Screenshot_20240312-101502`: the first run of digits starting 19xx or 20xx
+pub fn year_in_name(name: &str) -> Option<i32> {
+    name.split(|c: char| !c.is_ascii_digit())
+        .filter(|run| run.len() >= 4 && (run.starts_with("19") || run.starts_with("20")))
+        .find_map(|run| run[..4].parse().ok())
+}
+
+impl ImageClassifier {
+    pub const PRIORITY: u8 = 100;
+
+    pub fn score(extension: &str, mime_type: &str) -> u8 {
         // High confidence for common image formats
         if matches!(
             extension,
@@ -34,6 +96,11 @@ impl Classifier for ImageClassifier {
             return 85;
         }
 
+        // DjVu is a document format despite its `image/` MIME type
+        if matches!(extension, "djvu" | "djv") {
+            return 0;
+        }
+
         // MIME type based confidence
         if mime_type.starts_with("image/") {
             return 90;
@@ -43,6 +110,35 @@ impl Classifier for ImageClassifier {
         0
     }
 
+    /// Map a lowercase extension to its image subcategory
+    pub fn subcategory(ext: &str) -> ImageSubcategory {
+        match ext {
+            "jpg" | "jpeg" => ImageSubcategory::Jpeg,
+            "png" => ImageSubcategory::Png,
+            "gif" => ImageSubcategory::Gif,
+            "svg" => ImageSubcategory::Svg,
+            "raw" | "cr2" | "nef" | "arw" | "dng" => ImageSubcategory::Raw,
+            "tiff" | "tif" => ImageSubcategory::Tiff,
+            "webp" => ImageSubcategory::Webp,
+            "bmp" => ImageSubcategory::Bmp,
+            "ico" => ImageSubcategory::Ico,
+            "heic" | "heif" => ImageSubcategory::Heic,
+            _ => ImageSubcategory::Other,
+        }
+    }
+}
+
+#[cfg(feature = "native")]
+#[async_trait]
+impl Classifier for ImageClassifier {
+    fn name(&self) -> &'static str {
+        "ImageClassifier"
+    }
+
+    fn confidence(&self, extension: &str, mime_type: &str) -> u8 {
+        Self::score(extension, mime_type)
+    }
+
     async fn extract_metadata(&self, path: &Path) -> Result<ClassifiedFileMetadata> {
         let raw = tokio::fs::metadata(path).await?;
         let size = raw.len();
@@ -59,20 +155,14 @@ impl Classifier for ImageClassifier {
             .modified()
             .ok()
             .or_else(|| raw.created().ok())
-            .and_then(|t| system_time_to_year(t));
+            .and_then(system_time_to_year);
 
-        let subcategory = match ext.as_str() {
-            "jpg" | "jpeg" => ImageSubcategory::Jpeg,
-            "png" => ImageSubcategory::Png,
-            "gif" => ImageSubcategory::Gif,
-            "svg" => ImageSubcategory::Svg,
-            "raw" | "cr2" | "nef" | "arw" | "dng" => ImageSubcategory::Raw,
-            "tiff" | "tif" => ImageSubcategory::Tiff,
-            "webp" => ImageSubcategory::Webp,
-            "bmp" => ImageSubcategory::Bmp,
-            "ico" => ImageSubcategory::Ico,
-            "heic" | "heif" => ImageSubcategory::Heic,
-            _ => ImageSubcategory::Other,
+        let name = path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+        let screenshot = is_screenshot_name(&name) || (ext == "png" && png_says_screenshot(path).await);
+        let (subcategory, year) = if screenshot {
+            (ImageSubcategory::Screenshots, year_in_name(&name).or(year))
+        } else {
+            (Self::subcategory(&ext), year)
         };
 
         let mut classified = ClassifiedFileMetadata::new(
@@ -85,4 +175,17 @@ impl Classifier for ImageClassifier {
 
         Ok(classified)
     }
-}
\ No newline at end of file
+}
+
+/// Whether the PNG at `path` was saved by a screenshot tool, by its text
+/// chunks; an unreadable file is not
+#[cfg(feature = "native")]
+async fn png_says_screenshot(path: &Path) -> bool {
+    use tokio::io::AsyncReadExt;
+
+    let mut head = Vec::new();
+    match tokio::fs::File::open(path).await {
+        Ok(file) => file.take(64 << 10).read_to_end(&mut head).await.is_ok() && is_screenshot_png(&head),
+        Err(_) => false,
+    }
+}
@@ -1,11 +1,13 @@
 use std::{collections::HashMap, path::PathBuf, fmt};
 
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "native")]
 use chrono::{DateTime, Utc, Datelike};
 
+#[cfg(feature = "native")]
 use crate::scanner::RawFileMetadata;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub enum FileCategory {
     Documents(DocumentSubcategory),
     Images(ImageSubcategory),
@@ -14,12 +16,49 @@ pub enum FileCategory {
     Archives(ArchiveSubcategory),
     Executables(ExecutableSubcategory),
     Code(CodeSubcategory),
+    Fonts(FontSubcategory),
+    Design(DesignSubcategory),
+    Backups(BackupSubcategory),
+    /// Declared in config by a rule classifier
+    Custom(CustomCategory),
+    #[default]
     Others,
 }
 
-impl Default for FileCategory {
-    fn default() -> Self {
-        FileCategory::Others
+/// A category from config: its folder and an optional subfolder below it,
+/// which may be nested (`Drawings/2D`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CustomCategory {
+    pub name: &'static str,
+    pub subfolder: Option<&'static str>,
+    /// Folders below the category instead of the subfolder and date
+    /// folders, e.g. `{year}/{subfolder}` (see [`crate::path_builder::render_layout`])
+    pub layout: Option<&'static str>,
+}
+
+impl FileCategory {
+    /// Names of the top-level categories, as used for destination folders
+    pub const TOP_LEVEL: [&'static str; 11] = [
+        "Documents", "Images", "Videos", "Audio", "Archives", "Executables", "Code", "Fonts", "Design",
+        "Backups", "Others",
+    ];
+
+    /// Top-level name without the subcategory, e.g. `Images`
+    pub fn top_level(&self) -> &'static str {
+        match self {
+            FileCategory::Documents(_) => "Documents",
+            FileCategory::Images(_) => "Images",
+            FileCategory::Videos(_) => "Videos",
+            FileCategory::Audio(_) => "Audio",
+            FileCategory::Archives(_) => "Archives",
+            FileCategory::Executables(_) => "Executables",
+            FileCategory::Code(_) => "Code",
+            FileCategory::Fonts(_) => "Fonts",
+            FileCategory::Design(_) => "Design",
+            FileCategory::Backups(_) => "Backups",
+            FileCategory::Custom(custom) => custom.name,
+            FileCategory::Others => "Others",
+        }
     }
 }
 
@@ -33,6 +72,17 @@ impl fmt::Display for FileCategory {
             FileCategory::Archives(sub) => write!(f, "Archives::{:?}", sub),
             FileCategory::Executables(sub) => write!(f, "Executables::{:?}", sub),
             FileCategory::Code(sub) => write!(f, "Code::{:?}", sub),
+            FileCategory::Fonts(sub) => write!(f, "Fonts::{:?}", sub),
+            FileCategory::Design(sub) => write!(f, "Design::{:?}", sub),
+            FileCategory::Backups(sub) => write!(f, "Backups::{:?}", sub),
+            FileCategory::Custom(CustomCategory { name, subfolder, .. }) => {
+                write!(f, "{}", name)?;
+                // Nested subfolders are levels of the category: `Work::Invoices::Acme`
+                for level in subfolder.iter().flat_map(|sub| sub.split('/')) {
+                    write!(f, "::{}", level)?;
+                }
+                Ok(())
+            }
             FileCategory::Others => write!(f, "Others"),
         }
     }
@@ -53,6 +103,10 @@ pub struct ClassifiedFileMetadata {
     pub path: PathBuf,
     pub category: FileCategory,
     pub year: Option<i32>,
+    /// 1–12, from the same date as `year`
+    pub month: Option<u32>,
+    /// 1–31, from the same date as `year`
+    pub day: Option<u32>,
     pub created_date: Option<String>,
     pub modified_date: Option<String>,
     pub file_size: Option<u64>,
@@ -67,6 +121,8 @@ impl ClassifiedFileMetadata {
             path,
             category,
             year: None,
+            month: None,
+            day: None,
             created_date: None,
             modified_date: None,
             file_size: None,
@@ -76,6 +132,7 @@ impl ClassifiedFileMetadata {
     }
 }
 
+#[cfg(feature = "native")]
 impl From<RawFileMetadata> for ClassifiedFileMetadata {
     fn from(raw: RawFileMetadata) -> Self {
         let mime = raw.path.extension()
@@ -90,16 +147,18 @@ impl From<RawFileMetadata> for ClassifiedFileMetadata {
             .modified
             .map(|t| DateTime::<Utc>::from(t).to_rfc3339());
 
-        // Prefer modified timestamp for year, fall back to created
-        let year = raw
+        // Prefer modified timestamp for the date, fall back to created
+        let date = raw
             .modified
             .or(raw.created)
-            .map(|t| DateTime::<Utc>::from(t).year());
+            .map(DateTime::<Utc>::from);
 
         Self {
             path: raw.path,
             category: FileCategory::default(),
-            year,
+            year: date.map(|d| d.year()),
+            month: date.map(|d| d.month()),
+            day: date.map(|d| d.day()),
             created_date,
             modified_date,
             file_size: Some(raw.size),
@@ -118,7 +177,11 @@ pub enum DocumentSubcategory {
     Text,
     OpenDocument,
     Ebook,
+    /// Comic book archives (cbz, cbr)
+    Comic,
     Technical,
+    /// Saved out of a mail client (see `--mail-attachments`)
+    Attachments,
     Other,
 }
 
@@ -134,6 +197,8 @@ pub enum ImageSubcategory {
     Bmp,
     Ico,
     Heic,
+    /// Any format, told apart by name or embedded metadata
+    Screenshots,
     Other,
 }
 
@@ -192,6 +257,37 @@ pub enum ExecutableSubcategory {
     Other,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FontSubcategory {
+    TrueType,      // .ttf, .ttc
+    OpenType,      // .otf
+    Woff,
+    Woff2,
+    Eot,
+    Other,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DesignSubcategory {
+    Photoshop,     // .psd, .psb
+    Illustrator,   // .ai
+    AdobeXd,       // .xd
+    Figma,         // .fig
+    Sketch,
+    Blender,       // .blend
+    Models,        // .obj, .fbx, .stl, .gltf, ...
+    Other,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BackupSubcategory {
+    Copies,        // .bak, .old, file~
+    Archives,      // backup-2024.zip
+    Bookmarks,     // bookmarks.html, .jsonlz4
+    Phone,         // .ab, .nbu, sms-*.xml
+    Other,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum CodeSubcategory {
     // Programming Languages
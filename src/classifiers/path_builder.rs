@@ -1,7 +1,15 @@
-use std::path::{Path, PathBuf};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use serde::Deserialize;
+
 use crate::metadata::{
-    ArchiveSubcategory, AudioSubcategory, ClassifiedFileMetadata, CodeSubcategory,
-    DocumentSubcategory, ExecutableSubcategory, FileCategory, ImageSubcategory, VideoSubcategory,
+    ArchiveSubcategory, AudioSubcategory, BackupSubcategory, ClassifiedFileMetadata, CodeSubcategory,
+    DesignSubcategory, DocumentSubcategory, ExecutableSubcategory, FileCategory, FontSubcategory,
+    ImageSubcategory, VideoSubcategory,
 };
 
 // Convert each subcategory to a string
@@ -16,6 +24,8 @@ impl AsRef<str> for DocumentSubcategory {
             DocumentSubcategory::OpenDocument => "OpenDocument",
             DocumentSubcategory::Technical => "Technical",
             DocumentSubcategory::Ebook => "Ebook",
+            DocumentSubcategory::Comic => "Comics",
+            DocumentSubcategory::Attachments => "Attachments",
             DocumentSubcategory::Other => "Other",
         }
     }
@@ -34,6 +44,7 @@ impl AsRef<str> for ImageSubcategory {
             ImageSubcategory::Bmp => "Bmp",
             ImageSubcategory::Ico => "Ico",
             ImageSubcategory::Heic => "Heic",
+            ImageSubcategory::Screenshots => "Screenshots",
             ImageSubcategory::Other => "Other",
         }
     }
@@ -106,6 +117,46 @@ impl AsRef<str> for ExecutableSubcategory {
     }
 }
 
+impl AsRef<str> for FontSubcategory {
+    fn as_ref(&self) -> &str {
+        match self {
+            FontSubcategory::TrueType => "TrueType",
+            FontSubcategory::OpenType => "OpenType",
+            FontSubcategory::Woff => "Woff",
+            FontSubcategory::Woff2 => "Woff2",
+            FontSubcategory::Eot => "Eot",
+            FontSubcategory::Other => "Other",
+        }
+    }
+}
+
+impl AsRef<str> for DesignSubcategory {
+    fn as_ref(&self) -> &str {
+        match self {
+            DesignSubcategory::Photoshop => "Photoshop",
+            DesignSubcategory::Illustrator => "Illustrator",
+            DesignSubcategory::AdobeXd => "AdobeXd",
+            DesignSubcategory::Figma => "Figma",
+            DesignSubcategory::Sketch => "Sketch",
+            DesignSubcategory::Blender => "Blender",
+            DesignSubcategory::Models => "3dModels",
+            DesignSubcategory::Other => "Other",
+        }
+    }
+}
+
+impl AsRef<str> for BackupSubcategory {
+    fn as_ref(&self) -> &str {
+        match self {
+            BackupSubcategory::Copies => "Copies",
+            BackupSubcategory::Archives => "Archives",
+            BackupSubcategory::Bookmarks => "Bookmarks",
+            BackupSubcategory::Phone => "Phone",
+            BackupSubcategory::Other => "Other",
+        }
+    }
+}
+
 impl AsRef<str> for CodeSubcategory {
     fn as_ref(&self) -> &str {
         match self {
@@ -165,15 +216,167 @@ impl AsRef<str> for CodeSubcategory {
     }
 }
 
+/// Date directories under a file's category
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DateFolders {
+    /// No date directories
+    None,
+    /// `2024`
+    #[default]
+    Year,
+    /// `2024/03`
+    YearMonth,
+    /// `2024/03/12`
+    YearMonthDay,
+}
+
+impl FromStr for DateFolders {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "none" => Ok(Self::None),
+            "year" => Ok(Self::Year),
+            "year-month" => Ok(Self::YearMonth),
+            "year-month-day" => Ok(Self::YearMonthDay),
+            other => Err(format!("unknown date folders `{}` (year, year-month, year-month-day, none)", other)),
+        }
+    }
+}
+
+/// Case of the category and subcategory folders stash names itself
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NameStyle {
+    /// `Images/Screenshots`
+    #[default]
+    Unchanged,
+    /// `images/screenshots`
+    Lowercase,
+    /// `IMAGES/SCREENSHOTS`
+    Uppercase,
+}
+
+impl NameStyle {
+    fn apply(self, name: &str) -> String {
+        match self {
+            Self::Unchanged => name.to_string(),
+            Self::Lowercase => name.to_lowercase(),
+            Self::Uppercase => name.to_uppercase(),
+        }
+    }
+}
+
+/// Folder names for the categories: the built-in English ones unless
+/// renamed, e.g. `Images` → `Bilder`. Renamed folders are used as given;
+/// the [`NameStyle`] applies to the rest and to subcategory folders.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FolderNames {
+    /// By top-level category (see [`FileCategory::TOP_LEVEL`])
+    renamed: BTreeMap<&'static str, String>,
+    style: NameStyle,
+}
+
+static DEFAULT_NAMES: FolderNames = FolderNames { renamed: BTreeMap::new(), style: NameStyle::Unchanged };
+
+impl FolderNames {
+    pub fn new(style: NameStyle) -> Self {
+        Self { renamed: BTreeMap::new(), style }
+    }
+
+    /// Put files of `category` (a top-level name) into a folder called `folder`
+    pub fn rename(mut self, category: &'static str, folder: impl Into<String>) -> Self {
+        self.renamed.insert(category, folder.into());
+        self
+    }
+
+    /// Folder for a top-level category, e.g. `Images`
+    pub fn category(&self, category: &str) -> String {
+        match self.renamed.get(category) {
+            Some(folder) => folder.clone(),
+            None => self.style.apply(category),
+        }
+    }
+
+    /// Folder for a subcategory, e.g. `Screenshots`
+    pub fn subcategory(&self, subcategory: &str) -> String {
+        self.style.apply(subcategory)
+    }
+}
+
+/// Tokens a custom category's layout may use
+const LAYOUT_TOKENS: [&str; 4] = ["{subfolder}", "{year}", "{month}", "{day}"];
+
+/// Check a custom category's layout, e.g. `{subfolder}/{year}/{month}`:
+/// `/`-separated folders of literal text and [`LAYOUT_TOKENS`], where
+/// `{subfolder}` must be a whole folder
+pub fn validate_layout(layout: &str) -> Result<(), String> {
+    if layout.trim().is_empty() {
+        return Err("empty layout".into());
+    }
+    for folder in layout.split('/') {
+        let mut literal = folder.to_string();
+        for token in LAYOUT_TOKENS {
+            literal = literal.replace(token, "");
+        }
+        if literal.contains(['{', '}']) {
+            return Err(format!("layout `{}`: unknown token in `{}` (expected {})", layout, folder, LAYOUT_TOKENS.join(", ")));
+        }
+        if folder.contains("{subfolder}") && folder != "{subfolder}" {
+            return Err(format!("layout `{}`: `{{subfolder}}` must be a folder of its own", layout));
+        }
+        if folder.is_empty() || folder == "." || folder == ".." || literal.contains(['\\', ':']) {
+            return Err(format!("layout `{}`: `{}` is not a valid folder", layout, folder));
+        }
+    }
+    Ok(())
+}
+
+/// The folders `layout` gives a file of `subfolder` dated by `meta`. A
+/// folder whose token has no value, like `{month}` for a file with only a
+/// year or `{subfolder}` for a category without one, is left out.
+pub fn render_layout(layout: &str, subfolder: Option<&str>, meta: &ClassifiedFileMetadata, names: &FolderNames) -> Vec<String> {
+    let mut folders = Vec::new();
+    for folder in layout.split('/') {
+        if folder == "{subfolder}" {
+            folders.extend(subfolder.iter().flat_map(|sub| sub.split('/')).map(|level| names.subcategory(level)));
+            continue;
+        }
+        let values = [
+            ("{year}", meta.year.map(|year| year.to_string())),
+            ("{month}", meta.month.map(|month| format!("{:02}", month))),
+            ("{day}", meta.day.map(|day| format!("{:02}", day))),
+        ];
+        let mut rendered = folder.to_string();
+        let mut complete = true;
+        for (token, value) in values {
+            if rendered.contains(token) {
+                match value {
+                    Some(value) => rendered = rendered.replace(token, &value),
+                    None => complete = false,
+                }
+            }
+        }
+        if complete && !rendered.is_empty() {
+            folders.push(rendered);
+        }
+    }
+    folders
+}
+
 /// Builder for constructing a destination path
 pub struct PathBuilder<'a> {
     meta: &'a ClassifiedFileMetadata,
     base: Option<&'a Path>,
+    after: Option<&'a Path>,
+    date_folders: DateFolders,
+    names: &'a FolderNames,
+    literal: Option<&'a Path>,
 }
 
 impl<'a> PathBuilder<'a> {
     pub fn new(meta: &'a ClassifiedFileMetadata) -> Self {
-        Self { meta, base: None }
+        Self { meta, base: None, after: None, date_folders: DateFolders::default(), names: &DEFAULT_NAMES, literal: None }
     }
 
     pub fn base(mut self, base: &'a Path) -> Self {
@@ -181,37 +384,94 @@ impl<'a> PathBuilder<'a> {
         self
     }
 
+    /// Directories appended after the category and date, e.g. `github.com`
+    pub fn after(mut self, dirs: &'a Path) -> Self {
+        self.after = Some(dirs);
+        self
+    }
+
+    pub fn date_folders(mut self, date_folders: DateFolders) -> Self {
+        self.date_folders = date_folders;
+        self
+    }
+
+    /// Category folder names to use instead of the built-in ones
+    pub fn names(mut self, names: &'a FolderNames) -> Self {
+        self.names = names;
+        self
+    }
+
+    /// Use `dir` below the base as it is, instead of the category, date and
+    /// `after` folders (an extension override, e.g. `Design/Illustrator`)
+    pub fn literal(mut self, dir: &'a Path) -> Self {
+        self.literal = Some(dir);
+        self
+    }
+
     pub fn build(self) -> PathBuf {
         let mut path = self.base.unwrap_or(Path::new("Organized")).to_path_buf();
 
-        match &self.meta.category {
-            FileCategory::Documents(_) => path.push("Documents"),
-            FileCategory::Images(_) => path.push("Images"),
-            FileCategory::Videos(_) => path.push("Videos"),
-            FileCategory::Audio(_) => path.push("Audio"),
-            FileCategory::Archives(_) => path.push("Archives"),
-            FileCategory::Executables(_) => path.push("Executables"),
-            FileCategory::Code(_) => path.push("Code"),
-            FileCategory::Others => path.push("Others"),
+        if let Some(dir) = self.literal {
+            path.push(dir);
+            return path;
+        }
+
+        path.push(self.names.category(self.meta.category.top_level()));
+
+        // A custom category's layout replaces the subcategory and date folders
+        if let FileCategory::Custom(custom) = &self.meta.category
+            && let Some(layout) = custom.layout
+        {
+            path.extend(render_layout(layout, custom.subfolder, self.meta, self.names));
+            if let Some(after) = self.after {
+                path.push(after);
+            }
+            return path;
         }
 
         // Push the subcategory string if it exists
-        match &self.meta.category {
-            FileCategory::Documents(sub) => path.push(sub.as_ref()),
-            FileCategory::Images(sub) => path.push(sub.as_ref()),
-            FileCategory::Videos(sub) => path.push(sub.as_ref()),
-            FileCategory::Audio(sub) => path.push(sub.as_ref()),
-            FileCategory::Archives(sub) => path.push(sub.as_ref()),
-            FileCategory::Executables(sub) => path.push(sub.as_ref()),
-            FileCategory::Code(sub) => path.push(sub.as_ref()),
-            FileCategory::Others => {}
-        }
-
-        // Append year if available
-        if let Some(year) = self.meta.year {
+        let subcategory = match &self.meta.category {
+            FileCategory::Documents(sub) => Some(sub.as_ref()),
+            FileCategory::Images(sub) => Some(sub.as_ref()),
+            FileCategory::Videos(sub) => Some(sub.as_ref()),
+            FileCategory::Audio(sub) => Some(sub.as_ref()),
+            FileCategory::Archives(sub) => Some(sub.as_ref()),
+            FileCategory::Executables(sub) => Some(sub.as_ref()),
+            FileCategory::Code(sub) => Some(sub.as_ref()),
+            FileCategory::Fonts(sub) => Some(sub.as_ref()),
+            FileCategory::Design(sub) => Some(sub.as_ref()),
+            FileCategory::Backups(sub) => Some(sub.as_ref()),
+            FileCategory::Custom(custom) => custom.subfolder,
+            FileCategory::Others => None,
+        };
+        for level in subcategory.iter().flat_map(|sub| sub.split('/')) {
+            path.push(self.names.subcategory(level));
+        }
+
+        // Append the date down to the chosen granularity, as far as it is known
+        if let Some(year) = self.meta.year.filter(|_| self.date_folders >= DateFolders::Year) {
             path.push(year.to_string());
+            if let Some(month) = self.meta.month.filter(|_| self.date_folders >= DateFolders::YearMonth) {
+                path.push(format!("{:02}", month));
+                if let Some(day) = self.meta.day.filter(|_| self.date_folders >= DateFolders::YearMonthDay) {
+                    path.push(format!("{:02}", day));
+                }
+            }
+        }
+
+        if let Some(after) = self.after {
+            path.push(after);
         }
 
         path
     }
+
+    /// Destination for `source`: the built directory plus its file name.
+    /// `None` when `source` has no file name (`/`, `..`).
+    pub fn destination_for(self, source: &Path) -> Option<PathBuf> {
+        let file_name = source.file_name()?;
+        let mut path = self.build();
+        path.push(file_name);
+        Some(path)
+    }
 }
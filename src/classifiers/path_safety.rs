@@ -0,0 +1,59 @@
+//! Pure checks and rewrites for names that end up in destination paths. They
+//! take file names, config values and download metadata as untrusted input
+//! and are fuzzed (see `fuzz/`), so none of them may panic.
+
+use std::path::{Component, Path, PathBuf};
+
+/// Characters Windows refuses in a file name, on top of the separators
+const RESERVED: &str = "/\\:*?\"<>|";
+
+/// Make `value` safe to use as a single path component on any platform.
+/// Never empty, `.` or `..`; falls back to `Unknown`.
+pub fn sanitize_component(value: &str) -> String {
+    let cleaned: String = value
+        .chars()
+        .map(|c| if c.is_control() || RESERVED.contains(c) { '_' } else { c })
+        .collect();
+    let cleaned = cleaned.trim_matches(|c: char| c.is_whitespace() || c == '.');
+    if cleaned.is_empty() { "Unknown".to_string() } else { cleaned.to_string() }
+}
+
+/// Whether `folder` can be used as one directory name as written
+pub fn is_valid_folder_name(folder: &str) -> bool {
+    !(folder.trim().is_empty() || folder == "." || folder == ".." || folder.contains(['/', '\\', ':']))
+}
+
+/// Whether `path` only descends: no root, drive prefix or `..`
+pub fn is_contained(path: &Path) -> bool {
+    path.components().all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+}
+
+/// The `n`th name tried when `path` is taken: `file.txt` → `file_n.txt`,
+/// in the same directory
+pub fn conflict_candidate(path: &Path, n: u32) -> PathBuf {
+    let parent = path.parent().unwrap_or_else(|| Path::new("."));
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let ext = path.extension().map(|e| format!(".{}", e.to_string_lossy())).unwrap_or_default();
+    parent.join(format!("{}_{}{}", stem, n, ext))
+}
+
+/// Where an archive entry named `name` may be written, relative to the
+/// extraction directory. Both `/` and `\` count as separators, since Windows
+/// tools write either; `None` for names that are empty, absolute, carry a
+/// drive prefix or NUL, or climb out with `..`.
+pub fn archive_entry_path(name: &str) -> Option<PathBuf> {
+    if name.contains('\0') || name.starts_with(['/', '\\']) {
+        return None;
+    }
+    let mut path = PathBuf::new();
+    for part in name.split(['/', '\\']) {
+        match part {
+            "" | "." => continue,
+            ".." => return None,
+            // `C:` or `C:file` would be relative to a drive on Windows
+            _ if part.contains(':') => return None,
+            _ => path.push(part),
+        }
+    }
+    (!path.as_os_str().is_empty()).then_some(path)
+}
@@ -0,0 +1,103 @@
+//! No-I/O classification and destination preview.
+//!
+//! Everything here works from file names and listing metadata only, so it
+//! builds for `wasm32` with `--no-default-features` and can back an in-browser
+//! "here's how your files would be organized" demo.
+
+use std::path::Path;
+
+use chrono::{DateTime, Datelike};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    builtin::BuiltinClassifier,
+    image_classifier::year_in_name,
+    metadata::{ClassifiedFileMetadata, FileCategory, ImageSubcategory},
+    path_builder::PathBuilder,
+};
+
+/// One entry of a file listing (e.g. from a drag-and-dropped folder)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListingEntry {
+    pub name: String,
+    #[serde(default)]
+    pub size: u64,
+    /// Last modification time in seconds since the Unix epoch
+    #[serde(default)]
+    pub modified: Option<i64>,
+}
+
+/// Where a listed file would end up
+#[derive(Debug, Clone, Serialize)]
+pub struct PreviewEntry {
+    pub name: String,
+    pub category: String,
+    pub destination: String,
+}
+
+/// Classify a file from its name alone, mirroring `ClassifierRegistry::classify`
+/// with the built-in classifiers
+pub fn classify_name(name: &str, size: u64, modified: Option<i64>) -> ClassifiedFileMetadata {
+    classify_name_with(name, size, modified, 0)
+}
+
+/// [`classify_name`] for a registry with a `min_classifier_score`
+pub fn classify_name_with(name: &str, size: u64, modified: Option<i64>, min_score: u16) -> ClassifiedFileMetadata {
+    let path = Path::new(name);
+    let file_name = path
+        .file_name()
+        .and_then(|f| f.to_str())
+        .map(str::to_ascii_lowercase)
+        .unwrap_or_default();
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_ascii_lowercase)
+        .unwrap_or_default();
+    let mime = mime_guess::from_ext(&ext)
+        .first_or_octet_stream()
+        .essence_str()
+        .to_string();
+
+    let category = BuiltinClassifier::best_named(&file_name, &ext, &mime, min_score)
+        .map(|builtin| builtin.category_named(&file_name, &ext))
+        .unwrap_or_default();
+
+    let mut classified = ClassifiedFileMetadata::new(path.to_path_buf(), category);
+    if !matches!(classified.category, FileCategory::Others) {
+        let date = modified.and_then(|secs| DateTime::from_timestamp(secs, 0));
+        classified.year = date.map(|dt| dt.year());
+        classified.month = date.map(|dt| dt.month());
+        classified.day = date.map(|dt| dt.day());
+    }
+    // Screenshots are dated by their name first, as when organized
+    if matches!(classified.category, FileCategory::Images(ImageSubcategory::Screenshots)) {
+        classified.year = year_in_name(&file_name).or(classified.year);
+    }
+    classified.file_size = Some(size);
+    classified.mime_type = Some(mime);
+    classified
+}
+
+/// Preview the destination of every entry in a listing, relative to `Organized/`
+pub fn preview(entries: &[ListingEntry]) -> Vec<PreviewEntry> {
+    entries
+        .iter()
+        .map(|entry| {
+            let classified = classify_name(&entry.name, entry.size, entry.modified);
+            let destination = PathBuilder::new(&classified)
+                .destination_for(Path::new(&entry.name))
+                .unwrap_or_else(|| PathBuilder::new(&classified).build());
+
+            PreviewEntry {
+                name: entry.name.clone(),
+                category: classified.category.to_string(),
+                destination: destination
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/"),
+            }
+        })
+        .collect()
+}
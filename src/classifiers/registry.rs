@@ -6,13 +6,27 @@ use std::{
 };
 
 use async_trait::async_trait;
+use chrono::{DateTime, Datelike, Utc};
 use futures::future::join_all;
 use tokio::sync::RwLock;
 
+pub use crate::builtin::{weighted_score, BuiltinClassifier};
 use crate::{
+    archive_classifier::ArchiveClassifier,
+    audio_classifier::AudioClassifier,
+    backup_classifier::BackupClassifier,
+    code_classifier::CodeClassifier,
+    design_classifier::DesignClassifier,
+    docs_classifier::DocumentClassifier,
     errors::{FileOrganizerError, Result},
-    metadata::ClassifiedFileMetadata,
+    executable_classifier::ExecutableClassifier,
+    font_classifier::FontClassifier,
+    generic::GenericClassifier,
+    image_classifier::ImageClassifier,
+    metadata::{ClassifiedFileMetadata, FileCategory},
+    rule_classifier::ExtensionOverrides,
     scanner::RawFileMetadata, utils::detect_mime,
+    video_classifier::VideoClassifier,
 };
 
 #[async_trait]
@@ -22,28 +36,152 @@ pub trait Classifier: Send + Sync {
     async fn extract_metadata(&self, path: &Path) -> Result<ClassifiedFileMetadata>;
 }
 
+impl BuiltinClassifier {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Backup => BackupClassifier.name(),
+            Self::Image => ImageClassifier.name(),
+            Self::Audio => AudioClassifier.name(),
+            Self::Design => DesignClassifier.name(),
+            Self::Video => VideoClassifier.name(),
+            Self::Document => DocumentClassifier.name(),
+            Self::Code => CodeClassifier.name(),
+            Self::Font => FontClassifier.name(),
+            Self::Archive => ArchiveClassifier.name(),
+            Self::Executable => ExecutableClassifier.name(),
+            Self::Generic => GenericClassifier.name(),
+        }
+    }
+
+    pub async fn extract_metadata(self, path: &Path) -> Result<ClassifiedFileMetadata> {
+        match self {
+            Self::Backup => BackupClassifier.extract_metadata(path).await,
+            Self::Image => ImageClassifier.extract_metadata(path).await,
+            Self::Audio => AudioClassifier.extract_metadata(path).await,
+            Self::Design => DesignClassifier.extract_metadata(path).await,
+            Self::Video => VideoClassifier.extract_metadata(path).await,
+            Self::Document => DocumentClassifier.extract_metadata(path).await,
+            Self::Code => CodeClassifier.extract_metadata(path).await,
+            Self::Font => FontClassifier.extract_metadata(path).await,
+            Self::Archive => ArchiveClassifier.extract_metadata(path).await,
+            Self::Executable => ExecutableClassifier.extract_metadata(path).await,
+            Self::Generic => GenericClassifier.extract_metadata(path).await,
+        }
+    }
+}
+
+/// A classifier in the registry: a built-in one, or any [`Classifier`]
+/// implementation (config rules, plugins, test doubles)
+#[derive(Clone)]
+pub enum RegisteredClassifier {
+    Builtin(BuiltinClassifier),
+    Dyn(Arc<dyn Classifier>),
+}
+
+impl RegisteredClassifier {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Builtin(builtin) => builtin.name(),
+            Self::Dyn(classifier) => classifier.name(),
+        }
+    }
+
+    pub fn confidence(&self, extension: &str, mime_type: &str) -> u8 {
+        match self {
+            Self::Builtin(builtin) => builtin.confidence(extension, mime_type),
+            Self::Dyn(classifier) => classifier.confidence(extension, mime_type),
+        }
+    }
+
+    /// Confidence from the whole (lowercase) file name; classifiers that only
+    /// look at extensions ignore it
+    pub fn confidence_named(&self, file_name: &str, extension: &str, mime_type: &str) -> u8 {
+        match self {
+            Self::Builtin(builtin) => builtin.confidence_named(file_name, extension, mime_type),
+            Self::Dyn(classifier) => classifier.confidence(extension, mime_type),
+        }
+    }
+
+    pub async fn extract_metadata(&self, path: &Path) -> Result<ClassifiedFileMetadata> {
+        match self {
+            Self::Builtin(builtin) => builtin.extract_metadata(path).await,
+            Self::Dyn(classifier) => classifier.extract_metadata(path).await,
+        }
+    }
+}
+
+/// How one classifier rated a file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClassifierScore {
+    pub name: &'static str,
+    pub priority: u8,
+    /// 1–100
+    pub confidence: u8,
+    /// Priority × confidence, what classifiers are ranked by
+    pub weighted: u16,
+}
+
+/// Lowercase extension and file name of `path`, as classifiers match them
+fn lowercase_names(path: &Path) -> (String, String) {
+    let lowercase = |part: Option<&OsStr>| part.and_then(OsStr::to_str).map(str::to_ascii_lowercase).unwrap_or_default();
+    (lowercase(path.extension()), lowercase(path.file_name()))
+}
+
 #[derive(Default, Clone)]
 pub struct ClassifierRegistry {
-    pub classifiers: Arc<Vec<(u8, Arc<dyn Classifier>)>>, // (priority, classifier)
+    pub classifiers: Arc<Vec<(u8, RegisteredClassifier)>>, // (priority, classifier)
     pub mime_cache: Arc<RwLock<HashMap<String, String>>>,
+    /// Lowest weighted score (priority × confidence) a classifier needs to
+    /// be tried; files no classifier reaches it for go to `Others`. 0 tries
+    /// every classifier with any confidence.
+    pub min_score: u16,
+    /// Extensions classified by config alone, before any classifier is asked
+    pub overrides: Arc<ExtensionOverrides>,
 }
 
+/// Name [`ClassifierRegistry::classify_named`] gives files whose extension is overridden
+pub const OVERRIDE: &str = "override";
+
 impl ClassifierRegistry {
     pub fn new() -> Self {
         Self {
             classifiers: Arc::new(Vec::new()),
             mime_cache: Arc::new(RwLock::new(HashMap::new())),
+            min_score: 0,
+            overrides: Arc::default(),
         }
     }
 
+    /// Put files with these extensions in their override's category without asking the classifiers
+    pub fn with_overrides(mut self, overrides: ExtensionOverrides) -> Self {
+        self.overrides = Arc::new(overrides);
+        self
+    }
+
+    /// Only try classifiers whose weighted score reaches `min_score`
+    pub fn with_min_score(mut self, min_score: u16) -> Self {
+        self.min_score = min_score;
+        self
+    }
+
     pub fn register_with_priority(&mut self, priority: u8, classifier: Arc<dyn Classifier>) {
+        self.insert(priority, RegisteredClassifier::Dyn(classifier));
+    }
+
+    /// Register a built-in classifier at its own priority
+    pub fn register_builtin(&mut self, builtin: BuiltinClassifier) {
+        self.insert(builtin.priority(), RegisteredClassifier::Builtin(builtin));
+    }
+
+    fn insert(&mut self, priority: u8, classifier: RegisteredClassifier) {
         let classifiers = Arc::get_mut(&mut self.classifiers)
             .expect("Cannot mutate classifiers after sharing");
         
         classifiers.push((priority, classifier));
         
-        // Sort by priority (highest first)
-        classifiers.sort_by(|a, b| b.0.cmp(&a.0));
+        // Highest priority first, then by name so ties do not depend on
+        // the order classifiers were registered in
+        classifiers.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.name().cmp(b.1.name())));
     }
 
     // Keep the original register method for backward compatibility
@@ -74,55 +212,135 @@ impl ClassifierRegistry {
     }
 
     pub async fn classify(&self, raw: &RawFileMetadata) -> Result<ClassifiedFileMetadata> {
-        let ext = raw
-            .path
-            .extension()
-            .and_then(OsStr::to_str)
-            .map(str::to_ascii_lowercase)
-            .unwrap_or_default();
+        self.classify_named(raw).await.map(|(_, metadata)| metadata)
+    }
 
+    /// Like [`classify`](Self::classify), also returning the name of the classifier that won
+    pub async fn classify_named(&self, raw: &RawFileMetadata) -> Result<(&'static str, ClassifiedFileMetadata)> {
+        let (ext, file_name) = lowercase_names(&raw.path);
         let mime = self.get_cached_mime(&ext).await;
 
-        // Collect all classifiers with their confidence scores
-        let mut candidates = Vec::new();
-        for (priority, classifier) in &*self.classifiers {
-            let confidence = classifier.confidence(&ext, &mime);
-            if confidence > 0 {
-                // Combine priority and confidence for weighted score
-                let weighted_score = (*priority as u16) * (confidence as u16);
-                candidates.push((classifier, weighted_score, confidence));
+        if let Some((_, category)) = self.overrides.get(&ext) {
+            let mut metadata = ClassifiedFileMetadata::new(raw.path.clone(), FileCategory::Custom(category));
+            metadata.file_size = Some(raw.size);
+            metadata.mime_type = Some(mime);
+            if let Some(date) = raw.modified.or(raw.created).map(DateTime::<Utc>::from) {
+                metadata.year = Some(date.year());
+                metadata.month = Some(date.month());
+                metadata.day = Some(date.day());
             }
+            return Ok((OVERRIDE, metadata));
         }
 
-        // Sort by weighted score (highest first)
-        candidates.sort_by(|a, b| b.1.cmp(&a.1));
-
-        // Try classifiers in weighted score order
-        for (classifier, weighted_score, confidence) in candidates {
-            tracing::debug!(
-                "Trying {} with weighted score {} (confidence: {}) for {:?}",
-                classifier.name(),
-                weighted_score,
-                confidence,
-                raw.path
-            );
-
-            match classifier.extract_metadata(&raw.path).await {
-                Ok(mut metadata) => {
-                    metadata.file_size = Some(raw.size);
-                    metadata.mime_type = Some(mime.clone());
-                    return Ok(metadata);
-                }
-                Err(e) => {
-                    tracing::debug!("Classifier {} failed: {}", classifier.name(), e);
-                }
+        // Combine priority and confidence for a weighted score; of equal
+        // scores the first (higher priority, then name) wins. Picking the best
+        // needs no allocation, which matters when it succeeds for nearly every
+        // file.
+        let weighted = |(priority, classifier): &(u8, RegisteredClassifier)| {
+            weighted_score(*priority, classifier.confidence_named(&file_name, &ext, &mime), self.min_score)
+        };
+        let mut best: Option<(usize, u16)> = None;
+        for (idx, entry) in self.classifiers.iter().enumerate() {
+            if let Some(score) = weighted(entry)
+                && best.is_none_or(|(_, top)| score > top)
+            {
+                best = Some((idx, score));
             }
         }
+        let Some((best_idx, best_score)) = best else {
+            return self.below_min_score(raw, &mime).await;
+        };
+        if let Some(found) = self.try_classifier(&self.classifiers[best_idx].1, best_score, raw, &mime).await {
+            return Ok(found);
+        }
+
+        // The best one failed: try the others in weighted score order
+        let mut candidates: Vec<(usize, u16)> = self
+            .classifiers
+            .iter()
+            .enumerate()
+            .filter(|(idx, _)| *idx != best_idx)
+            .filter_map(|(idx, entry)| weighted(entry).map(|score| (idx, score)))
+            .collect();
+        candidates.sort_by_key(|c| std::cmp::Reverse(c.1));
+
+        for (idx, score) in candidates {
+            if let Some(found) = self.try_classifier(&self.classifiers[idx].1, score, raw, &mime).await {
+                return Ok(found);
+            }
+        }
+
+        self.below_min_score(raw, &mime).await
+    }
+
+    /// No classifier reached the threshold (or those that did failed): with
+    /// one set the file is `Others`, as the generic classifier would make it
+    async fn below_min_score(&self, raw: &RawFileMetadata, mime: &str) -> Result<(&'static str, ClassifiedFileMetadata)> {
+        if self.min_score == 0 {
+            return Err(FileOrganizerError::Classify(format!("No classifier found for {:?}", raw.path)));
+        }
+        tracing::debug!("No classifier reached score {} for {:?}", self.min_score, raw.path);
+        let generic = RegisteredClassifier::Builtin(BuiltinClassifier::Generic);
+        self.try_classifier(&generic, 0, raw, mime)
+            .await
+            .ok_or_else(|| FileOrganizerError::Classify(format!("No classifier found for {:?}", raw.path)))
+    }
 
-        Err(FileOrganizerError::Classify(format!(
-            "No classifier found for {:?}",
+    async fn try_classifier(
+        &self,
+        classifier: &RegisteredClassifier,
+        weighted_score: u16,
+        raw: &RawFileMetadata,
+        mime: &str,
+    ) -> Option<(&'static str, ClassifiedFileMetadata)> {
+        tracing::debug!(
+            "Trying {} with weighted score {} for {:?}",
+            classifier.name(),
+            weighted_score,
             raw.path
-        )))
+        );
+
+        match classifier.extract_metadata(&raw.path).await {
+            Ok(mut metadata) => {
+                metadata.file_size = Some(raw.size);
+                metadata.mime_type = Some(mime.to_string());
+                // Classifiers only read the year; month and day come from the same timestamp
+                if let Some(date) = raw.modified.or(raw.created).map(DateTime::<Utc>::from)
+                    && metadata.year == Some(date.year())
+                {
+                    metadata.month = Some(date.month());
+                    metadata.day = Some(date.day());
+                }
+                Some((classifier.name(), metadata))
+            }
+            Err(e) => {
+                tracing::debug!("Classifier {} failed: {}", classifier.name(), e);
+                None
+            }
+        }
+    }
+
+    /// How every registered classifier rates `raw`, highest weighted score
+    /// first in the order ties are broken; classifiers with no confidence
+    /// are left out. Shows what [`classify_named`](Self::classify_named) chooses from.
+    pub async fn scores(&self, raw: &RawFileMetadata) -> (String, Vec<ClassifierScore>) {
+        let (ext, file_name) = lowercase_names(&raw.path);
+        let mime = self.get_cached_mime(&ext).await;
+        let mut scores: Vec<ClassifierScore> = self
+            .classifiers
+            .iter()
+            .filter_map(|(priority, classifier)| {
+                let confidence = classifier.confidence_named(&file_name, &ext, &mime);
+                (confidence > 0).then(|| ClassifierScore {
+                    name: classifier.name(),
+                    priority: *priority,
+                    confidence,
+                    weighted: (*priority as u16) * (confidence as u16),
+                })
+            })
+            .collect();
+        scores.sort_by_key(|score| std::cmp::Reverse(score.weighted));
+        (mime, scores)
     }
 
     pub async fn get_cached_mime(&self, ext: &str) -> String {
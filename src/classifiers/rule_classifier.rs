@@ -0,0 +1,299 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+#[cfg(feature = "native")]
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use crate::{
+    metadata::{CustomCategory, FileCategory},
+    path_builder::validate_layout,
+    path_safety::is_valid_folder_name,
+};
+#[cfg(feature = "native")]
+use crate::{errors::Result, metadata::ClassifiedFileMetadata, registry::Classifier, utils::system_time_to_year};
+
+/// A classifier declared in config, e.g.
+/// `{ "name": "CAD", "extensions": ["dwg", "dxf"], "category": "CAD" }`
+#[derive(Debug, Clone, Deserialize)]
+pub struct ClassifierRule {
+    pub name: String,
+    /// Extensions without the dot, matched case-insensitively
+    #[serde(default)]
+    pub extensions: Vec<String>,
+    /// MIME types; a trailing `*` matches a prefix (`font/*`)
+    #[serde(default)]
+    pub mime_patterns: Vec<String>,
+    /// 1–100, how sure a match is
+    #[serde(default = "ClassifierRule::default_confidence")]
+    pub confidence: u8,
+    /// Weight against other classifiers; built-in ones use 10–105
+    #[serde(default = "ClassifierRule::default_priority")]
+    pub priority: u8,
+    /// Top-level destination folder
+    pub category: String,
+    /// Folder below the category; `/` nests folders (`Drawings/2D`)
+    #[serde(default)]
+    pub subfolder: Option<String>,
+    /// Folders below the category instead of the subfolder and date
+    /// folders, e.g. `{year}/{subfolder}` (see [`validate_layout`])
+    #[serde(default)]
+    pub layout: Option<String>,
+}
+
+impl ClassifierRule {
+    fn default_confidence() -> u8 {
+        90
+    }
+
+    /// Above every built-in classifier, so a rule wins where extensions overlap
+    fn default_priority() -> u8 {
+        120
+    }
+
+    /// Check the rule without building it
+    pub fn validate(&self) -> std::result::Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("classifier without a name".into());
+        }
+        if self.extensions.is_empty() && self.mime_patterns.is_empty() {
+            return Err(format!("classifier `{}` has no extensions or mime_patterns", self.name));
+        }
+        if !(1..=100).contains(&self.confidence) {
+            return Err(format!("classifier `{}`: confidence must be 1-100", self.name));
+        }
+        let subfolders = self.subfolder.iter().flat_map(|sub| sub.split('/'));
+        for folder in std::iter::once(self.category.as_str()).chain(subfolders) {
+            if !is_valid_folder_name(folder) {
+                return Err(format!("classifier `{}`: `{}` is not a valid folder name", self.name, folder));
+            }
+        }
+        if let Some(layout) = &self.layout {
+            validate_layout(layout).map_err(|e| format!("classifier `{}`: {}", self.name, e))?;
+        }
+        Ok(())
+    }
+}
+
+/// A category of its own declared in config, with the rules that put files
+/// in it and the folders it has, e.g.
+/// `{ "name": "Work", "layout": "{subfolder}/{year}", "rules": [{ "extensions": ["dwg"], "subfolder": "Drawings" }] }`
+#[derive(Debug, Clone, Deserialize)]
+pub struct CategoryDefinition {
+    /// Top-level destination folder; not one of [`FileCategory::TOP_LEVEL`]
+    pub name: String,
+    /// Folders below the category for all its rules; subfolder and date
+    /// folders when unset
+    #[serde(default)]
+    pub layout: Option<String>,
+    pub rules: Vec<CategoryRule>,
+}
+
+/// One way files end up in a [`CategoryDefinition`]: a [`ClassifierRule`]
+/// without the category
+#[derive(Debug, Clone, Deserialize)]
+pub struct CategoryRule {
+    /// Shown by `stash classify`; the category and subfolder by default
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub extensions: Vec<String>,
+    #[serde(default)]
+    pub mime_patterns: Vec<String>,
+    #[serde(default = "ClassifierRule::default_confidence")]
+    pub confidence: u8,
+    #[serde(default = "ClassifierRule::default_priority")]
+    pub priority: u8,
+    /// Folder below the category; `/` nests folders
+    #[serde(default)]
+    pub subfolder: Option<String>,
+}
+
+impl CategoryDefinition {
+    /// The definition's rules as classifier rules
+    pub fn classifier_rules(&self) -> Vec<ClassifierRule> {
+        let name = self.name.trim();
+        self.rules
+            .iter()
+            .map(|rule| ClassifierRule {
+                name: rule.name.clone().unwrap_or_else(|| match &rule.subfolder {
+                    Some(subfolder) => format!("{}/{}", name, subfolder),
+                    None => name.to_string(),
+                }),
+                extensions: rule.extensions.clone(),
+                mime_patterns: rule.mime_patterns.clone(),
+                confidence: rule.confidence,
+                priority: rule.priority,
+                category: name.to_string(),
+                subfolder: rule.subfolder.clone(),
+                layout: self.layout.clone(),
+            })
+            .collect()
+    }
+
+    pub fn validate(&self) -> std::result::Result<(), String> {
+        let name = self.name.trim();
+        if FileCategory::TOP_LEVEL.iter().any(|builtin| builtin.eq_ignore_ascii_case(name)) {
+            return Err(format!("custom category `{}` is built in; add to it with `classifiers` instead", name));
+        }
+        if self.rules.is_empty() {
+            return Err(format!("custom category `{}` has no rules", name));
+        }
+        self.classifier_rules().iter().try_for_each(ClassifierRule::validate)
+    }
+}
+
+/// Extensions sent straight to a folder of their own, skipping the
+/// classifiers, e.g. `{ "ai": "Design/Illustrator" }`. The folder is used as
+/// written: no renaming, name style or date folders.
+#[derive(Debug, Clone, Default)]
+pub struct ExtensionOverrides {
+    /// By lowercased extension: the folder below the destination root, and
+    /// its first folder as the file's category
+    by_extension: HashMap<String, (PathBuf, CustomCategory)>,
+}
+
+impl ExtensionOverrides {
+    /// Check `overrides` (extension → relative folder) without building them
+    pub fn validate(overrides: &HashMap<String, String>) -> std::result::Result<(), String> {
+        let mut seen = HashMap::new();
+        for (extension, folder) in overrides {
+            let normalized = normalize_extension(extension);
+            if normalized.is_empty() || normalized.contains(['/', '\\', '.']) {
+                return Err(format!("override: `{}` is not an extension", extension));
+            }
+            if let Some(other) = seen.insert(normalized, extension) {
+                return Err(format!("override: `{}` and `{}` are the same extension", other, extension));
+            }
+            let folder = folder.trim().trim_end_matches('/');
+            if folder.is_empty() || !folder.split('/').all(is_valid_folder_name) {
+                return Err(format!("override for `{}`: `{}` is not a relative folder", extension, folder));
+            }
+        }
+        Ok(())
+    }
+
+    /// Build validated overrides. Their category names live for the rest of
+    /// the program, like those of rule classifiers; build them once.
+    pub fn new(overrides: &HashMap<String, String>) -> std::result::Result<Self, String> {
+        Self::validate(overrides)?;
+        let leak = |s: &str| -> &'static str { Box::leak(s.to_string().into_boxed_str()) };
+        let by_extension = overrides
+            .iter()
+            .map(|(extension, folder)| {
+                let folder = folder.trim().trim_end_matches('/');
+                let (name, subfolder) = match folder.split_once('/') {
+                    Some((name, subfolder)) => (name, Some(leak(subfolder))),
+                    None => (folder, None),
+                };
+                let category = CustomCategory { name: leak(name), subfolder, layout: None };
+                (normalize_extension(extension), (PathBuf::from(folder), category))
+            })
+            .collect();
+        Ok(Self { by_extension })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_extension.is_empty()
+    }
+
+    /// The folder and category for files with `extension` (lowercase, without the dot)
+    pub fn get(&self, extension: &str) -> Option<(&Path, CustomCategory)> {
+        self.by_extension.get(extension).map(|(folder, category)| (folder.as_path(), *category))
+    }
+
+    /// The folder for `path`, if its extension is overridden
+    pub fn folder_for(&self, path: &Path) -> Option<&Path> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        self.get(&extension).map(|(folder, _)| folder)
+    }
+}
+
+fn normalize_extension(extension: &str) -> String {
+    extension.trim().trim_start_matches('.').to_ascii_lowercase()
+}
+
+/// Classifies by extension and MIME type into a category from config
+#[derive(Debug)]
+pub struct RuleClassifier {
+    name: &'static str,
+    extensions: Vec<String>,
+    mime_patterns: Vec<String>,
+    confidence: u8,
+    priority: u8,
+    category: CustomCategory,
+}
+
+impl RuleClassifier {
+    /// Build a classifier from a validated rule. Its names live for the rest
+    /// of the program, like those of built-in classifiers; build each rule once.
+    pub fn from_rule(rule: &ClassifierRule) -> std::result::Result<Self, String> {
+        rule.validate()?;
+        let leak = |s: &str| -> &'static str { Box::leak(s.trim().to_string().into_boxed_str()) };
+
+        Ok(Self {
+            name: leak(&rule.name),
+            extensions: rule
+                .extensions
+                .iter()
+                .map(|e| normalize_extension(e))
+                .collect(),
+            mime_patterns: rule.mime_patterns.iter().map(|m| m.trim().to_ascii_lowercase()).collect(),
+            confidence: rule.confidence,
+            priority: rule.priority,
+            category: CustomCategory {
+                name: leak(&rule.category),
+                subfolder: rule.subfolder.as_deref().map(leak),
+                layout: rule.layout.as_deref().map(leak),
+            },
+        })
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn priority(&self) -> u8 {
+        self.priority
+    }
+
+    pub fn category(&self) -> FileCategory {
+        FileCategory::Custom(self.category)
+    }
+
+    pub fn score(&self, extension: &str, mime_type: &str) -> u8 {
+        let ext_match = self.extensions.iter().any(|e| e == extension);
+        let mime_match = self.mime_patterns.iter().any(|p| match p.strip_suffix('*') {
+            Some(prefix) => mime_type.starts_with(prefix),
+            None => p == mime_type,
+        });
+        if ext_match || mime_match { self.confidence } else { 0 }
+    }
+}
+
+#[cfg(feature = "native")]
+#[async_trait]
+impl Classifier for RuleClassifier {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn confidence(&self, extension: &str, mime_type: &str) -> u8 {
+        self.score(extension, mime_type)
+    }
+
+    async fn extract_metadata(&self, path: &Path) -> Result<ClassifiedFileMetadata> {
+        let raw = tokio::fs::metadata(path).await?;
+
+        let mut classified = ClassifiedFileMetadata::new(path.to_path_buf(), self.category());
+        classified.file_size = Some(raw.len());
+        classified.year = raw
+            .modified()
+            .ok()
+            .or_else(|| raw.created().ok())
+            .and_then(system_time_to_year);
+
+        Ok(classified)
+    }
+}
@@ -1,18 +1,19 @@
+use crate::metadata::VideoSubcategory;
+#[cfg(feature = "native")]
 use std::path::Path;
+#[cfg(feature = "native")]
 use async_trait::async_trait;
+#[cfg(feature = "native")]
 use crate::{
-    errors::Result, metadata::{ClassifiedFileMetadata, FileCategory, VideoSubcategory}, registry::Classifier, utils::{detect_mime, system_time_to_year}
+    errors::Result, metadata::{ClassifiedFileMetadata, FileCategory}, registry::Classifier, utils::{detect_mime, system_time_to_year}
 };
 
 pub struct VideoClassifier;
 
-#[async_trait]
-impl Classifier for VideoClassifier {
-    fn name(&self) -> &'static str {
-        "VideoClassifier"
-    }
+impl VideoClassifier {
+    pub const PRIORITY: u8 = 90;
 
-    fn confidence(&self, extension: &str, mime_type: &str) -> u8 {
+    pub fn score(extension: &str, mime_type: &str) -> u8 {
         // High confidence for common video formats
         if matches!(
             extension,
@@ -38,6 +39,36 @@ impl Classifier for VideoClassifier {
         0
     }
 
+    /// Map a lowercase extension to its video subcategory
+    pub fn subcategory(ext: &str) -> VideoSubcategory {
+        match ext {
+            "mp4" | "m4v" => VideoSubcategory::Mp4,
+            "avi" | "divx" => VideoSubcategory::Avi,
+            "mkv" => VideoSubcategory::Mkv,
+            "mov" => VideoSubcategory::Mov,
+            "webm" | "ogv" => VideoSubcategory::Webm,
+            "wmv" => VideoSubcategory::Wmv,
+            "flv" => VideoSubcategory::Flv,
+            "mpg" | "mpeg" => VideoSubcategory::Mpeg,
+            "3gp" => VideoSubcategory::ThreeGp,
+            "ts" | "mts" | "m2ts" => VideoSubcategory::Ts,
+            "vob" => VideoSubcategory::Vob,
+            _ => VideoSubcategory::Other,
+        }
+    }
+}
+
+#[cfg(feature = "native")]
+#[async_trait]
+impl Classifier for VideoClassifier {
+    fn name(&self) -> &'static str {
+        "VideoClassifier"
+    }
+
+    fn confidence(&self, extension: &str, mime_type: &str) -> u8 {
+        Self::score(extension, mime_type)
+    }
+
     async fn extract_metadata(&self, path: &Path) -> Result<ClassifiedFileMetadata> {
         let raw = tokio::fs::metadata(path).await?;
         let size = raw.len();
@@ -54,22 +85,9 @@ impl Classifier for VideoClassifier {
             .modified()
             .ok()
             .or_else(|| raw.created().ok())
-            .and_then(|t| system_time_to_year(t));
+            .and_then(system_time_to_year);
 
-        let subcategory = match ext.as_str() {
-            "mp4" | "m4v" => VideoSubcategory::Mp4,
-            "avi" | "divx" => VideoSubcategory::Avi,
-            "mkv" => VideoSubcategory::Mkv,
-            "mov" => VideoSubcategory::Mov,
-            "webm" | "ogv" => VideoSubcategory::Webm,
-            "wmv" => VideoSubcategory::Wmv,
-            "flv" => VideoSubcategory::Flv,
-            "mpg" | "mpeg" => VideoSubcategory::Mpeg,
-            "3gp" => VideoSubcategory::ThreeGp,
-            "ts" | "mts" | "m2ts" => VideoSubcategory::Ts,
-            "vob" => VideoSubcategory::Vob,
-            _ => VideoSubcategory::Other,
-        };
+        let subcategory = Self::subcategory(&ext);
 
         let mut classified = ClassifiedFileMetadata::new(
             path.to_path_buf(),
@@ -81,4 +99,4 @@ impl Classifier for VideoClassifier {
 
         Ok(classified)
     }
-}
\ No newline at end of file
+}
@@ -1,8 +1,49 @@
 pub mod config;
+pub mod config_schema;
 pub mod index;
 pub mod scanner;
 pub mod utils;
 pub mod watcher;
 pub mod hasher;
 pub mod organizer;
-pub mod reverter;
\ No newline at end of file
+pub mod reverter;
+pub mod snapshot;
+pub mod daemon;
+pub mod calibration;
+pub mod dedupe;
+pub mod undo_bundle;
+pub mod progress;
+pub mod history;
+pub mod index_transfer;
+pub mod index_bench;
+pub mod index_prune;
+pub mod verify;
+pub mod provenance;
+pub mod filename_date;
+pub mod run_status;
+pub mod repair;
+pub mod post_process;
+pub mod retention;
+pub mod cancel;
+pub mod control;
+pub mod shell_integration;
+pub mod exclusions;
+pub mod trash;
+pub mod mail_export;
+pub mod run_report;
+pub mod query;
+pub mod chunking;
+pub mod manifest;
+pub mod cleanup;
+pub mod explain;
+pub mod indexing;
+pub mod workspace;
+pub mod guard;
+pub mod schedule;
+pub mod instance_lock;
+#[cfg(feature = "self-update")]
+pub mod self_update;
+#[cfg(feature = "ocr")]
+pub mod ocr;
+#[cfg(feature = "tui")]
+pub mod tui;
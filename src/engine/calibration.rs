@@ -0,0 +1,208 @@
+use std::{
+    collections::BTreeMap,
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use colored::*;
+
+use crate::{
+    errors::{FileOrganizerError, Result},
+    metadata::FileCategory,
+    registry::ClassifierRegistry,
+    scanner::{ScanConfig, Scanner, SymlinkPolicy},
+};
+
+/// Misclassified examples shown under the table
+const MAX_EXAMPLES: usize = 20;
+
+/// One row of a labeled corpus
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LabeledFile {
+    pub path: PathBuf,
+    /// Canonical top-level category, e.g. `Images`
+    pub expected: &'static str,
+}
+
+/// Map `Images`, `images` or `Images::Jpeg` to the canonical top-level name
+pub fn normalize_category(label: &str) -> Option<&'static str> {
+    let top = label.split("::").next().unwrap_or_default().trim();
+    FileCategory::TOP_LEVEL.iter().copied().find(|c| c.eq_ignore_ascii_case(top))
+}
+
+/// Read a `path,category` CSV. A header row is optional; relative paths
+/// are resolved against the CSV's directory.
+pub fn load_labels(csv_path: &Path) -> Result<Vec<LabeledFile>> {
+    let base = csv_path.parent().unwrap_or(Path::new("."));
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .trim(csv::Trim::All)
+        .comment(Some(b'#'))
+        .from_path(csv_path)
+        .map_err(|e| FileOrganizerError::Config(e.into()))?;
+
+    let mut labels = Vec::new();
+    for (idx, record) in reader.records().enumerate() {
+        let record = record.map_err(|e| FileOrganizerError::Config(e.into()))?;
+        let (Some(path), Some(label)) = (record.get(0), record.get(1)) else {
+            return Err(FileOrganizerError::Config(anyhow::anyhow!(
+                "{:?} line {}: expected `path,category`",
+                csv_path,
+                idx + 1
+            )));
+        };
+
+        if idx == 0 && label.eq_ignore_ascii_case("category") {
+            continue;
+        }
+
+        let expected = normalize_category(label).ok_or_else(|| {
+            FileOrganizerError::Config(anyhow::anyhow!(
+                "{:?} line {}: unknown category `{}` (expected one of {})",
+                csv_path,
+                idx + 1,
+                label,
+                FileCategory::TOP_LEVEL.join(", ")
+            ))
+        })?;
+
+        labels.push(LabeledFile { path: base.join(path), expected });
+    }
+
+    Ok(labels)
+}
+
+/// Confusion counts for one category
+#[derive(Debug, Clone, Default)]
+pub struct CategoryStats {
+    /// Classifier that produced this category, if it was ever predicted
+    pub classifier: Option<&'static str>,
+    pub true_positives: usize,
+    pub false_positives: usize,
+    pub false_negatives: usize,
+}
+
+impl CategoryStats {
+    pub fn precision(&self) -> Option<f64> {
+        ratio(self.true_positives, self.true_positives + self.false_positives)
+    }
+
+    pub fn recall(&self) -> Option<f64> {
+        ratio(self.true_positives, self.true_positives + self.false_negatives)
+    }
+}
+
+fn ratio(num: usize, den: usize) -> Option<f64> {
+    (den > 0).then(|| num as f64 / den as f64)
+}
+
+/// Result of running the registry over a labeled corpus
+#[derive(Debug, Clone, Default)]
+pub struct EvalReport {
+    pub per_category: BTreeMap<&'static str, CategoryStats>,
+    pub evaluated: usize,
+    pub correct: usize,
+    /// Listed files that could not be read
+    pub missing: Vec<PathBuf>,
+    /// (path, expected, predicted) for wrong predictions
+    pub misclassified: Vec<(PathBuf, &'static str, String)>,
+}
+
+impl EvalReport {
+    pub fn accuracy(&self) -> Option<f64> {
+        ratio(self.correct, self.evaluated)
+    }
+}
+
+/// Classify every labeled file with `registry` and tally the outcomes
+pub async fn evaluate(registry: &ClassifierRegistry, labels: &[LabeledFile]) -> EvalReport {
+    let mut report = EvalReport::default();
+    let config = ScanConfig {
+        max_depth: 0,
+        include_hidden: true,
+        symlinks: SymlinkPolicy::Follow,
+        ..Default::default()
+    };
+
+    for label in labels {
+        let raw = match Scanner::new(&label.path, config.clone()).next() {
+            Some(Ok(raw)) if raw.is_file => raw,
+            _ => {
+                report.missing.push(label.path.clone());
+                continue;
+            }
+        };
+
+        let (classifier, predicted) = match registry.classify_named(&raw).await {
+            Ok((name, metadata)) => {
+                let category = metadata.category.to_string();
+                (Some(name), normalize_category(&category).map(str::to_string).unwrap_or(category))
+            }
+            Err(_) => (None, "Unclassified".to_string()),
+        };
+
+        report.evaluated += 1;
+        if predicted == label.expected {
+            report.correct += 1;
+            let stats = report.per_category.entry(label.expected).or_default();
+            stats.true_positives += 1;
+            stats.classifier = stats.classifier.or(classifier);
+            continue;
+        }
+
+        report.per_category.entry(label.expected).or_default().false_negatives += 1;
+        if let Some(category) = normalize_category(&predicted) {
+            let stats = report.per_category.entry(category).or_default();
+            stats.false_positives += 1;
+            stats.classifier = stats.classifier.or(classifier);
+        }
+        report.misclassified.push((label.path.clone(), label.expected, predicted));
+    }
+
+    report
+}
+
+fn format_ratio(value: Option<f64>) -> String {
+    value.map(|v| format!("{:.1}%", v * 100.0)).unwrap_or_else(|| "-".into())
+}
+
+impl fmt::Display for EvalReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", "Classifier evaluation".bold().blue())?;
+        writeln!(f, "  {:<12} {:<22} {:>9} {:>9} {:>5} {:>5} {:>5}",
+            "Category", "Classifier", "Precision", "Recall", "TP", "FP", "FN")?;
+
+        for (category, stats) in &self.per_category {
+            writeln!(f, "  {:<12} {:<22} {:>9} {:>9} {:>5} {:>5} {:>5}",
+                category,
+                stats.classifier.unwrap_or("-"),
+                format_ratio(stats.precision()),
+                format_ratio(stats.recall()),
+                stats.true_positives,
+                stats.false_positives,
+                stats.false_negatives,
+            )?;
+        }
+
+        writeln!(f, "\n  Accuracy: {} ({}/{} files)",
+            format_ratio(self.accuracy()).green(),
+            self.correct,
+            self.evaluated
+        )?;
+        if !self.missing.is_empty() {
+            writeln!(f, "  Missing:  {} files could not be read", self.missing.len().to_string().yellow())?;
+        }
+
+        if !self.misclassified.is_empty() {
+            writeln!(f, "\n{}", "Misclassified:".bold().blue())?;
+            for (path, expected, predicted) in self.misclassified.iter().take(MAX_EXAMPLES) {
+                writeln!(f, "  {} expected {}, got {}", path.display(), expected, predicted.red())?;
+            }
+            if self.misclassified.len() > MAX_EXAMPLES {
+                writeln!(f, "  … and {} more", self.misclassified.len() - MAX_EXAMPLES)?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,63 @@
+//! Stopping a run part way without leaving files half moved: once cancelled,
+//! files already being moved finish and are indexed, the rest are left alone.
+
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use tokio::sync::Notify;
+
+use crate::daemon::shutdown_signal;
+
+/// Shared flag that asks a run to stop. Clones cancel together.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<Inner>);
+
+#[derive(Debug, Default)]
+struct Inner {
+    cancelled: AtomicBool,
+    notify: Notify,
+}
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        if !self.0.cancelled.swap(true, Ordering::SeqCst) {
+            self.0.notify.notify_waiters();
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Resolves once [`cancel`](Self::cancel) has been called
+    pub async fn cancelled(&self) {
+        let notified = self.0.notify.notified();
+        tokio::pin!(notified);
+        // Register before checking, so a cancel in between is not missed
+        notified.as_mut().enable();
+        if self.is_cancelled() {
+            return;
+        }
+        notified.await;
+    }
+
+    /// Cancel on the first SIGTERM / Ctrl-C; a second Ctrl-C exits at once
+    pub fn cancel_on_signal(&self) {
+        let token = self.clone();
+        tokio::spawn(async move {
+            shutdown_signal().await;
+            eprintln!("\n⏹ Stopping after the files in progress (Ctrl-C again to quit now)");
+            tracing::info!(target: "organizer", "Cancellation requested");
+            token.cancel();
+
+            let _ = tokio::signal::ctrl_c().await;
+            std::process::exit(130);
+        });
+    }
+}
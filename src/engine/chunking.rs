@@ -0,0 +1,229 @@
+//! Content-defined chunk signatures for spotting near-duplicates: a re-encode
+//! that kept most of the stream, or a download that stopped partway. Files
+//! are cut where a rolling (gear) hash of the last bytes hits a pattern, so
+//! boundaries follow the content rather than offsets and two files sharing a
+//! stretch of bytes share the chunks inside it even when what comes before
+//! differs. Each chunk is kept as its XXH3 hash and length.
+
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+
+use xxhash_rust::xxh3::xxh3_64;
+
+/// Files smaller than this are not worth a signature
+pub const MIN_SIGNED_SIZE: u64 = 8 << 20;
+
+/// Chunk lengths: cuts fall about every `avg` bytes, never before `min` or past `max`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkParams {
+    pub min: usize,
+    /// Power of two
+    pub avg: usize,
+    pub max: usize,
+}
+
+impl Default for ChunkParams {
+    fn default() -> Self {
+        Self { min: 256 << 10, avg: 1 << 20, max: 4 << 20 }
+    }
+}
+
+/// Gear table: one pseudo-random word per byte value, fixed so signatures
+/// from different runs and machines compare
+const GEAR: [u64; 256] = {
+    let mut table = [0u64; 256];
+    let mut state: u64 = 0x5354_4153_485f_4344;
+    let mut i = 0;
+    while i < 256 {
+        // splitmix64
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        table[i] = z ^ (z >> 31);
+        i += 1;
+    }
+    table
+};
+
+/// One chunk of a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Chunk {
+    pub hash: u64,
+    pub len: u32,
+}
+
+/// A file's chunks, in order
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChunkSignature {
+    pub chunks: Vec<Chunk>,
+}
+
+impl ChunkSignature {
+    /// Signature of everything `reader` yields
+    pub fn of_reader(mut reader: impl Read, params: ChunkParams) -> io::Result<Self> {
+        let mask = (params.avg.max(1).next_power_of_two() - 1) as u64;
+        let mut chunks = Vec::new();
+        let mut current: Vec<u8> = Vec::with_capacity(params.max);
+        let mut rolling: u64 = 0;
+        let mut buf = vec![0u8; 64 << 10];
+        loop {
+            let n = reader.read(&mut buf)?;
+            if n == 0 {
+                break;
+            }
+            for &byte in &buf[..n] {
+                current.push(byte);
+                rolling = (rolling << 1).wrapping_add(GEAR[byte as usize]);
+                let len = current.len();
+                if (len >= params.min && rolling & mask == 0) || len >= params.max {
+                    chunks.push(Chunk { hash: xxh3_64(&current), len: len as u32 });
+                    current.clear();
+                    rolling = 0;
+                }
+            }
+        }
+        if !current.is_empty() {
+            chunks.push(Chunk { hash: xxh3_64(&current), len: current.len() as u32 });
+        }
+        Ok(Self { chunks })
+    }
+
+    /// Signature of the file at `path`, with the default chunk sizes
+    pub fn of_file(path: &Path) -> io::Result<Self> {
+        Self::of_reader(io::BufReader::new(File::open(path)?), ChunkParams::default())
+    }
+
+    /// Bytes covered
+    pub fn len(&self) -> u64 {
+        self.chunks.iter().map(|chunk| chunk.len as u64).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+
+    /// Stored form: 12 bytes per chunk, hash then length, little-endian
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.chunks.len() * 12);
+        for chunk in &self.chunks {
+            bytes.extend_from_slice(&chunk.hash.to_le_bytes());
+            bytes.extend_from_slice(&chunk.len.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Read back [`to_bytes`](Self::to_bytes); `None` if it is not a whole number of chunks
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if !bytes.len().is_multiple_of(12) {
+            return None;
+        }
+        let chunks = bytes
+            .chunks_exact(12)
+            .map(|raw| Chunk {
+                hash: u64::from_le_bytes(raw[..8].try_into().unwrap()),
+                len: u32::from_le_bytes(raw[8..].try_into().unwrap()),
+            })
+            .collect();
+        Some(Self { chunks })
+    }
+
+    /// How much of this signature's content `other` shares
+    pub fn compare(&self, other: &ChunkSignature) -> Similarity {
+        let mut theirs: HashMap<Chunk, usize> = HashMap::new();
+        for chunk in &other.chunks {
+            *theirs.entry(*chunk).or_default() += 1;
+        }
+        let mut shared = 0;
+        for chunk in &self.chunks {
+            if let Some(count) = theirs.get_mut(chunk).filter(|count| **count > 0) {
+                *count -= 1;
+                shared += chunk.len as u64;
+            }
+        }
+        let prefix = self
+            .chunks
+            .iter()
+            .zip(&other.chunks)
+            .take_while(|(a, b)| a == b)
+            .map(|(chunk, _)| chunk.len as u64)
+            .sum();
+        Similarity { shared, prefix, smaller: self.len().min(other.len()) }
+    }
+}
+
+/// Content two signatures have in common
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Similarity {
+    /// Bytes in chunks found in both
+    pub shared: u64,
+    /// Bytes in the chunks both start with
+    pub prefix: u64,
+    /// Size of the smaller of the two
+    pub smaller: u64,
+}
+
+impl Similarity {
+    /// Share of the smaller file found in the other one, 0 to 1
+    pub fn ratio(&self) -> f64 {
+        if self.smaller == 0 {
+            return 0.0;
+        }
+        self.shared as f64 / self.smaller as f64
+    }
+
+    /// All the shared content is at the start: one file is the other with a
+    /// different (or missing) tail
+    pub fn same_start(&self) -> bool {
+        self.shared > 0 && self.prefix == self.shared
+    }
+}
+
+/// Two files that are probably the same content
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimilarPair {
+    pub a: PathBuf,
+    pub b: PathBuf,
+    pub similarity: Similarity,
+}
+
+/// Pairs of files sharing at least `min_ratio` of the smaller one, most
+/// similar first. Files with identical signatures are left out: the full
+/// hash already calls those duplicates.
+pub fn find_similar(files: &[(PathBuf, ChunkSignature)], min_ratio: f64) -> Vec<SimilarPair> {
+    // Only files sharing a chunk are compared
+    let mut by_chunk: HashMap<Chunk, Vec<usize>> = HashMap::new();
+    for (i, (_, signature)) in files.iter().enumerate() {
+        for chunk in &signature.chunks {
+            let holders = by_chunk.entry(*chunk).or_default();
+            if holders.last() != Some(&i) {
+                holders.push(i);
+            }
+        }
+    }
+    let mut candidates: Vec<(usize, usize)> = by_chunk
+        .values()
+        .flat_map(|holders| {
+            holders.iter().enumerate().flat_map(move |(n, &a)| holders[n + 1..].iter().map(move |&b| (a, b)))
+        })
+        .collect();
+    candidates.sort_unstable();
+    candidates.dedup();
+
+    let mut pairs: Vec<SimilarPair> = candidates
+        .into_iter()
+        .filter(|&(a, b)| files[a].1 != files[b].1)
+        .map(|(a, b)| SimilarPair {
+            a: files[a].0.clone(),
+            b: files[b].0.clone(),
+            similarity: files[a].1.compare(&files[b].1),
+        })
+        .filter(|pair| pair.similarity.ratio() >= min_ratio)
+        .collect();
+    pairs.sort_by(|x, y| y.similarity.ratio().total_cmp(&x.similarity.ratio()).then_with(|| x.a.cmp(&y.a)));
+    pairs
+}
@@ -0,0 +1,168 @@
+//! `stash suggest-cleanup`: organized installers and archives nobody has
+//! touched in a while (the `.dmg` from two years ago, an old `.iso`), large
+//! enough to be worth removing. Listing is read-only; deleting moves them to
+//! stash's trash like `stash prune` does.
+
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use colored::*;
+use serde::Serialize;
+
+use crate::{
+    dedupe::DedupeAction,
+    errors::{FileOrganizerError, Result},
+    file_mover::FileMover,
+    index::Db,
+    stats::format_size,
+    trash,
+};
+
+/// Top-level categories whose files are usually safe to download again
+pub const CLEANUP_CATEGORIES: [&str; 2] = ["Executables", "Archives"];
+
+/// Which files to suggest
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CleanupCriteria {
+    /// Not modified for at least this long
+    pub older_than: Duration,
+    /// At least this many bytes
+    pub min_size: u64,
+}
+
+impl Default for CleanupCriteria {
+    fn default() -> Self {
+        Self { older_than: Duration::from_secs(180 * 24 * 60 * 60), min_size: 50 << 20 }
+    }
+}
+
+/// A file that could go
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CleanupCandidate {
+    /// Where the file is now
+    pub path: PathBuf,
+    pub category: String,
+    pub size: u64,
+    /// Whole days since it was last modified
+    pub age_days: u64,
+    /// Hash from the index, kept with the trash record
+    #[serde(skip)]
+    pub hash: Option<String>,
+}
+
+/// Suggested files, largest first
+#[derive(Debug, Default)]
+pub struct CleanupSuggestions(pub Vec<CleanupCandidate>);
+
+impl CleanupSuggestions {
+    /// Bytes freed by removing every suggested file
+    pub fn reclaimable(&self) -> u64 {
+        self.0.iter().map(|candidate| candidate.size).sum()
+    }
+}
+
+impl fmt::Display for CleanupSuggestions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.is_empty() {
+            return writeln!(f, "Nothing to clean up.");
+        }
+        writeln!(f, "{}", format!("{:>10}  {:>6}  {:<22}  {}", "Size", "Days", "Category", "File").bold().blue())?;
+        for candidate in &self.0 {
+            writeln!(
+                f,
+                "{:>10}  {:>6}  {:<22}  {}",
+                format_size(candidate.size),
+                candidate.age_days,
+                candidate.category,
+                candidate.path.display()
+            )?;
+        }
+        writeln!(f, "{} files, {} reclaimable", self.0.len().to_string().green(), format_size(self.reclaimable()).green())
+    }
+}
+
+/// Indexed executables and archives still at their organized location that
+/// match `criteria`. Age and size are read from the file, not the index.
+pub async fn suggest(db: &Db, criteria: &CleanupCriteria) -> Result<CleanupSuggestions> {
+    let now = SystemTime::now();
+    let mut candidates = Vec::new();
+    for entry in db.get_all_files().await? {
+        let Some(category) = entry.category else {
+            continue;
+        };
+        let top = category.split("::").next().unwrap_or(&category);
+        if !CLEANUP_CATEGORIES.contains(&top) {
+            continue;
+        }
+        // Moved or deleted since, or an app bundle kept whole
+        let Ok(metadata) = tokio::fs::metadata(&entry.dest_path).await else {
+            continue;
+        };
+        if !metadata.is_file() || metadata.len() < criteria.min_size {
+            continue;
+        }
+        let age = metadata
+            .modified()
+            .ok()
+            .and_then(|modified| now.duration_since(modified).ok())
+            .unwrap_or_default();
+        if age < criteria.older_than {
+            continue;
+        }
+        candidates.push(CleanupCandidate {
+            path: entry.dest_path,
+            category,
+            size: metadata.len(),
+            age_days: age.as_secs() / 86_400,
+            hash: entry.hash,
+        });
+    }
+    candidates.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path)));
+    Ok(CleanupSuggestions(candidates))
+}
+
+/// What [`delete`] did
+#[derive(Debug, Default)]
+pub struct CleanupReport {
+    /// Files moved to the trash
+    pub trashed: Vec<PathBuf>,
+    pub bytes: u64,
+    pub errors: Vec<(PathBuf, FileOrganizerError)>,
+}
+
+impl fmt::Display for CleanupReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", "Clean up".bold().blue())?;
+        writeln!(f, "  Trashed:  {} files, {}", self.trashed.len().to_string().green(), format_size(self.bytes))?;
+        writeln!(f, "  Errors:   {} files", self.errors.len().to_string().red())?;
+        for (path, error) in &self.errors {
+            writeln!(f, "    {}: {}", path.display(), error)?;
+        }
+        Ok(())
+    }
+}
+
+/// Move every suggested file to a new run folder under `trash_root`,
+/// recorded so `stash trash restore` can bring them back
+pub async fn delete(db: &Db, suggestions: &CleanupSuggestions, trash_root: &Path) -> Result<CleanupReport> {
+    let mover = FileMover::new();
+    let trash_dir = trash::run_dir(trash_root)?;
+    let mut report = CleanupReport::default();
+    for candidate in &suggestions.0 {
+        match trash::move_to(&mover, &candidate.path, &trash_dir).await {
+            Ok(target) => {
+                let kind = DedupeAction::Trash.as_str();
+                db.record_action(kind, &candidate.path, &target, candidate.hash.as_deref(), candidate.size).await?;
+                tracing::info!(target: "cleanup", "Trashed {:?} ({} days old)", candidate.path, candidate.age_days);
+                report.bytes += candidate.size;
+                report.trashed.push(candidate.path.clone());
+            }
+            Err(e) => report.errors.push((candidate.path.clone(), e)),
+        }
+    }
+    trash::discard_if_empty(&trash_dir).await;
+    Ok(report)
+}
@@ -1,8 +1,25 @@
 use serde::Deserialize;
 use regex::{Regex, RegexBuilder};
-use std::{fs, path::Path};
+use std::{collections::{BTreeMap, BTreeSet, HashMap}, fs, path::{Path, PathBuf}, sync::Arc, time::Duration};
 
-use crate::errors::{FileOrganizerError, Result};
+use crate::{
+    config_schema::{self, CONFIG_VERSION},
+    errors::{FileOrganizerError, Result},
+    exclusions::ExcludeGlobs,
+    filename_date::DatePatterns,
+    guard::Guard,
+    fs::Preserve,
+    metadata::FileCategory,
+    organizer::OrganizeOptions,
+    path_builder::{FolderNames, NameStyle},
+    permissions::{parse_mode, DestPermissions},
+    post_process::{PostCommands, DEFAULT_JOBS},
+    schedule::Schedule,
+    rule_classifier::{CategoryDefinition, ClassifierRule, ExtensionOverrides, RuleClassifier},
+    stats::{CategoryLegend, CategoryStyle},
+    utils::{expand_tilde, group_id, parse_duration},
+    volumes::Volumes,
+};
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct Rule {
@@ -16,20 +33,43 @@ pub struct Rule {
 
     pub destination: String,
 
+    /// Higher wins when several rules match a file; ties go to the earlier rule
+    #[serde(default)]
+    pub priority: i32,
+
     // Compiled regex (not from JSON, built at runtime)
     #[serde(skip)]
     pub compiled_regex: Option<Regex>,
 }
 
+impl Rule {
+    fn matches(&self, file_name: &str, extension: &str) -> bool {
+        self.extensions.iter().any(|e| e == extension)
+            || self.compiled_regex.as_ref().is_some_and(|re| re.is_match(file_name))
+    }
+}
+
+/// Several rules claiming the same extension
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleOverlap {
+    pub extension: String,
+    /// Indices into `rules`, winner first
+    pub rules: Vec<usize>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct RulesConfig {
     pub rules: Vec<Rule>,
+
+    /// Extensions claimed by more than one rule, found at load time
+    #[serde(skip)]
+    pub overlaps: Vec<RuleOverlap>,
 }
 
 impl RulesConfig {
     pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
         let data = fs::read_to_string(&path)
-            .map_err(|e| FileOrganizerError::Io(e))?;
+            .map_err(FileOrganizerError::Io)?;
 
         let mut config: RulesConfig = serde_json::from_str(&data)
             .map_err(|e| FileOrganizerError::Json {
@@ -55,7 +95,7 @@ impl RulesConfig {
 
             // Compile regex if present
             if let Some(pattern) = &rule.regex {
-                let compiled = RegexBuilder::new(&pattern)
+                let compiled = RegexBuilder::new(pattern)
                     .case_insensitive(true)
                     .build()
                     .map_err(|e| FileOrganizerError::Regex {
@@ -66,6 +106,613 @@ impl RulesConfig {
             }
         }
 
+        config.overlaps = config.find_overlaps();
+        for overlap in &config.overlaps {
+            let winner = &config.rules[overlap.rules[0]];
+            let losers: Vec<String> = overlap.rules[1..]
+                .iter()
+                .map(|&idx| format!("'{}' (index {})", config.rules[idx].category, idx + 1))
+                .collect();
+            tracing::warn!(
+                target: "config",
+                "Extension '{}' is claimed by several rules; '{}' (index {}, priority {}) wins over {}",
+                overlap.extension,
+                winner.category,
+                overlap.rules[0] + 1,
+                winner.priority,
+                losers.join(", ")
+            );
+        }
+
         Ok(config)
     }
-}
\ No newline at end of file
+
+    /// Rule indices in the order they are tried: priority, then position in the file
+    fn ranked(&self) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.rules.len()).collect();
+        order.sort_by_key(|&idx| (std::cmp::Reverse(self.rules[idx].priority), idx));
+        order
+    }
+
+    fn find_overlaps(&self) -> Vec<RuleOverlap> {
+        let mut claims: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+        for idx in self.ranked() {
+            let extensions: BTreeSet<&str> = self.rules[idx].extensions.iter().map(String::as_str).collect();
+            for ext in extensions {
+                claims.entry(ext).or_default().push(idx);
+            }
+        }
+        claims
+            .into_iter()
+            .filter(|(_, rules)| rules.len() > 1)
+            .map(|(extension, rules)| RuleOverlap { extension: extension.to_string(), rules })
+            .collect()
+    }
+
+    /// The rule that applies to `file_name`: the highest-priority match,
+    /// the earliest one among equals
+    pub fn rule_for(&self, file_name: &str) -> Option<&Rule> {
+        let extension = Path::new(file_name)
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(str::to_lowercase)
+            .unwrap_or_default();
+        self.ranked()
+            .into_iter()
+            .map(|idx| &self.rules[idx])
+            .find(|rule| rule.matches(file_name, &extension))
+    }
+}
+
+/// Canonical top-level name for `name`, matched case-insensitively against
+/// the built-in categories and the `custom` ones declared in config
+fn top_level_category(name: &str, custom: &[&str]) -> Result<&'static str> {
+    let name = name.trim();
+    if let Some(category) = FileCategory::TOP_LEVEL.iter().copied().find(|c| c.eq_ignore_ascii_case(name)) {
+        return Ok(category);
+    }
+    match custom.iter().find(|c| c.eq_ignore_ascii_case(name)) {
+        // Lives as long as the classifiers that produce it, see `RuleClassifier::from_rule`
+        Some(category) => Ok(Box::leak(category.to_string().into_boxed_str())),
+        None => Err(FileOrganizerError::Config(anyhow::anyhow!(
+            "unknown category `{}` (expected one of {}, or one declared in `classifiers` or `custom_categories`)",
+            name,
+            FileCategory::TOP_LEVEL.join(", ")
+        ))),
+    }
+}
+
+/// Turn `{ "code": false, "executables": false }` into the set of disabled
+/// top-level categories. Names are matched case-insensitively, against the
+/// built-in categories and `custom` ones.
+pub fn disabled_categories(categories: &HashMap<String, bool>, custom: &[&str]) -> Result<BTreeSet<&'static str>> {
+    let mut disabled = BTreeSet::new();
+    for (name, enabled) in categories {
+        let category = top_level_category(name, custom)?;
+        if !enabled {
+            disabled.insert(category);
+        }
+    }
+    Ok(disabled)
+}
+
+/// Categories whose download quarantine marker is removed when organized.
+/// Executables always keep theirs.
+pub fn trusted_categories<S: AsRef<str>>(names: &[S], custom: &[&str]) -> Result<BTreeSet<&'static str>> {
+    let mut trusted = BTreeSet::new();
+    for name in names {
+        let category = top_level_category(name.as_ref(), custom)?;
+        if category == "Executables" {
+            return Err(FileOrganizerError::Config(anyhow::anyhow!(
+                "refusing to strip the quarantine marker from executables"
+            )));
+        }
+        trusted.insert(category);
+    }
+    Ok(trusted)
+}
+
+/// Turn `{ "images": ["exiftool", "-P", "{dest}"] }` into commands keyed by
+/// top-level category. Names are matched case-insensitively.
+pub fn post_commands(after_move: &HashMap<String, Vec<String>>, custom: &[&str]) -> Result<BTreeMap<&'static str, Vec<String>>> {
+    let mut commands = BTreeMap::new();
+    for (name, args) in after_move {
+        let category = top_level_category(name, custom)?;
+        if args.first().is_none_or(|program| program.trim().is_empty()) {
+            return Err(FileOrganizerError::Config(anyhow::anyhow!(
+                "empty after_move command for category `{}`",
+                name
+            )));
+        }
+        commands.insert(category, args.clone());
+    }
+    Ok(commands)
+}
+
+/// Turn `{ "backups": "90d" }` into age limits keyed by top-level category.
+/// Names are matched case-insensitively.
+pub fn retention_limits(retention: &HashMap<String, String>, custom: &[&str]) -> Result<BTreeMap<&'static str, Duration>> {
+    let mut limits = BTreeMap::new();
+    for (name, age) in retention {
+        let category = top_level_category(name, custom)?;
+        let limit = parse_duration(age).map_err(|e| FileOrganizerError::Config(anyhow::anyhow!(
+            "retention for category `{}`: {}",
+            name,
+            e
+        )))?;
+        limits.insert(category, limit);
+    }
+    Ok(limits)
+}
+
+/// Turn `{ "videos": ["/mnt/media/Organized", "~/Organized"] }` into
+/// destination roots keyed by top-level category. Names are matched
+/// case-insensitively; each category needs at least one root.
+pub fn volumes(volumes: &HashMap<String, Vec<String>>, custom: &[&str]) -> Result<Volumes> {
+    let mut resolved = Volumes::new();
+    for (name, roots) in volumes {
+        let category = top_level_category(name, custom)?;
+        if roots.is_empty() {
+            return Err(FileOrganizerError::Config(anyhow::anyhow!("volumes for category `{}`: no destination listed", name)));
+        }
+        resolved.insert(category, roots.iter().map(expand_tilde).collect());
+    }
+    Ok(resolved)
+}
+
+/// Mode and group for a category's organized files, as written in the
+/// config: `{ "mode": "0750", "group": "staff" }`
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct PermissionRule {
+    /// Octal permission bits
+    #[serde(default)]
+    pub mode: Option<String>,
+    /// Group name or numeric id
+    #[serde(default)]
+    pub group: Option<String>,
+}
+
+/// Turn `{ "images": "Bilder" }` into the category folder names. Names are
+/// matched case-insensitively; a folder must be a single, unused name.
+pub fn folder_names(names: &HashMap<String, String>, style: NameStyle) -> Result<FolderNames> {
+    let mut folders = FolderNames::new(style);
+    for (name, folder) in names {
+        let category = top_level_category(name, &[])?;
+        let folder = folder.trim();
+        if folder.is_empty() || folder == "." || folder == ".." || folder.contains(['/', '\\']) {
+            return Err(FileOrganizerError::Config(anyhow::anyhow!(
+                "folder name `{}` for category `{}` must be a single folder name",
+                folder,
+                name
+            )));
+        }
+        folders = folders.rename(category, folder);
+    }
+    let mut seen = BTreeMap::new();
+    for category in FileCategory::TOP_LEVEL {
+        if let Some(other) = seen.insert(folders.category(category).to_lowercase(), category) {
+            return Err(FileOrganizerError::Config(anyhow::anyhow!(
+                "categories `{}` and `{}` would share the folder `{}`",
+                other,
+                category,
+                folders.category(category)
+            )));
+        }
+    }
+    Ok(folders)
+}
+
+/// Turn `{ "documents": { "mode": "0640" } }` into permissions keyed by
+/// top-level category. Names are matched case-insensitively; groups must exist.
+pub fn dest_permissions(permissions: &HashMap<String, PermissionRule>, custom: &[&str]) -> Result<BTreeMap<&'static str, DestPermissions>> {
+    let mut resolved = BTreeMap::new();
+    for (name, rule) in permissions {
+        let category = top_level_category(name, custom)?;
+        let invalid = |e: String| FileOrganizerError::Config(anyhow::anyhow!("permissions for category `{}`: {}", name, e));
+        let mode = rule.mode.as_deref().map(parse_mode).transpose().map_err(invalid)?;
+        let gid = rule
+            .group
+            .as_deref()
+            .map(|group| group_id(group).ok_or_else(|| invalid(format!("unknown group `{}`", group))))
+            .transpose()?;
+        resolved.insert(category, DestPermissions { mode, gid });
+    }
+    Ok(resolved)
+}
+
+/// Settings for `stash organize`, read from a JSON file
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct OrganizerConfig {
+    /// Schema version, see [`CONFIG_VERSION`]; 0 when unset
+    #[serde(default)]
+    pub version: u64,
+
+    /// Categories switched on or off; unlisted ones stay enabled
+    #[serde(default)]
+    pub categories: HashMap<String, bool>,
+
+    /// Per-category color, icon and display name for summaries
+    #[serde(default)]
+    pub display: HashMap<String, CategoryStyle>,
+
+    /// Folder names used instead of the English category names, e.g.
+    /// `{ "images": "Bilder", "documents": "Dokumente" }`
+    #[serde(default)]
+    pub names: HashMap<String, String>,
+
+    /// Case of the other category and subcategory folders: `unchanged`
+    /// (the default), `lowercase` or `uppercase`
+    #[serde(default)]
+    pub name_style: NameStyle,
+
+    /// Extra classifiers for categories stash does not know, e.g. CAD files or fonts
+    #[serde(default)]
+    pub classifiers: Vec<ClassifierRule>,
+
+    /// Top-level categories of the user's own, each with the rules that
+    /// fill it and optionally its folder layout (see [`CategoryDefinition`])
+    #[serde(default)]
+    pub custom_categories: Vec<CategoryDefinition>,
+
+    /// Extensions moved to a fixed folder under the destination without
+    /// asking the classifiers, e.g. `{ "ai": "Design/Illustrator" }`
+    #[serde(default)]
+    pub overrides: HashMap<String, String>,
+
+    /// Lowest weighted score (classifier priority × confidence, up to about
+    /// 10000) that places a file in a specific category; below it the file
+    /// goes to `Others`. 0, the default, accepts any match.
+    #[serde(default)]
+    pub min_classifier_score: u16,
+
+    /// Regexes that read a file's date from its name (see [`DatePatterns`]);
+    /// `filename_dates` before version 1
+    #[serde(default)]
+    pub date_patterns: Vec<String>,
+
+    /// Globs of paths never organized, e.g. `["*.part", "node_modules"]` (see [`ExcludeGlobs`])
+    #[serde(default)]
+    pub exclude: Vec<String>,
+
+    /// Directories never organized or reverted into, on top of `/`, the home
+    /// directory and system directories, e.g. `["~/Documents/Taxes", "/mnt/*"]`
+    /// (see [`Guard`])
+    #[serde(default)]
+    pub protected: Vec<String>,
+
+    /// Metadata kept when files are copied to another drive, e.g.
+    /// `{ "timestamps": true, "xattrs": false }`; everything by default
+    #[serde(default)]
+    pub preserve: Preserve,
+
+    /// A command run on each file once organized, per category; see [`PostCommands`]
+    #[serde(default)]
+    pub after_move: HashMap<String, Vec<String>>,
+
+    /// How many `after_move` commands run at once
+    #[serde(default)]
+    pub after_move_jobs: Option<usize>,
+
+    /// How long organized files of a category are kept, e.g.
+    /// `{ "backups": "90d" }`; applied by `stash prune`
+    #[serde(default)]
+    pub retention: HashMap<String, String>,
+
+    /// How long index entries of files that are gone are kept, e.g. `"180d"`;
+    /// applied after every organize run
+    #[serde(default)]
+    pub index_retention: Option<String>,
+
+    /// Mode and group given to organized files per category, e.g.
+    /// `{ "executables": { "mode": "0750", "group": "staff" } }` (Unix only)
+    #[serde(default)]
+    pub permissions: HashMap<String, PermissionRule>,
+
+    /// Destination roots per category on other volumes, e.g.
+    /// `{ "videos": ["/mnt/media/Organized", "/mnt/usb/Organized"] }`; each
+    /// file goes to one on its own filesystem when it can (see [`crate::volumes::Routes`])
+    #[serde(default)]
+    pub volumes: HashMap<String, Vec<String>>,
+
+    /// Files copied onto one destination device at once; 1 (the default)
+    /// copies one after another, which suits spinning disks
+    #[serde(default)]
+    pub transfers_per_device: Option<usize>,
+
+    /// When watch mode may organize (see [`Schedule`])
+    #[serde(default)]
+    pub schedule: Schedule,
+}
+
+/// Build the summary legend from `{ "images": { "color": "magenta", "icon": "🖼", "name": "Pictures" } }`
+pub fn category_legend(display: &HashMap<String, CategoryStyle>, custom: &[&str]) -> Result<CategoryLegend> {
+    let mut legend = CategoryLegend::default();
+    for (name, style) in display {
+        let category = top_level_category(name, custom)?;
+        if let Some(color) = &style.color
+            && color.parse::<colored::Color>().is_err()
+        {
+            return Err(FileOrganizerError::Config(anyhow::anyhow!(
+                "unknown color `{}` for category `{}`",
+                color,
+                name
+            )));
+        }
+        legend = legend.with_style(category, style.clone());
+    }
+    Ok(legend)
+}
+
+impl OrganizerConfig {
+    /// `<config dir>/file_organizer/config.json`
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|d| d.join("file_organizer").join("config.json"))
+    }
+
+    /// Load and check a config. Deprecated keys are migrated and the file
+    /// rewritten, keeping the original as a backup (see [`config_schema`]).
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let data = fs::read_to_string(path)
+            .map_err(FileOrganizerError::Io)?;
+        let json_error = |source| FileOrganizerError::Json { path: path.to_path_buf(), source };
+
+        let mut value: serde_json::Value = serde_json::from_str(&data).map_err(json_error)?;
+        if let serde_json::Value::Object(map) = &mut value {
+            let version = config_schema::version_of(map);
+            if version > CONFIG_VERSION {
+                tracing::warn!(target: "config", "{:?} is version {}, newer than this stash understands ({})", path, version, CONFIG_VERSION);
+            }
+            let migrations = config_schema::migrate(map);
+            for migration in &migrations {
+                tracing::warn!(target: "config", "{:?}: {}", path, migration);
+            }
+            if !migrations.is_empty() {
+                match config_schema::write_migrated(path, &data, map, version) {
+                    Ok(backup) => tracing::warn!(target: "config", "Migrated {:?} to version {}; the original is in {:?}", path, CONFIG_VERSION, backup),
+                    Err(e) => tracing::warn!(target: "config", "Could not rewrite {:?} after migrating it: {}", path, e),
+                }
+            }
+        }
+
+        let config: OrganizerConfig = serde_json::from_value(value).map_err(json_error)?;
+        config.check()?;
+        Ok(config)
+    }
+
+    /// Reject typos up front rather than silently organizing everything
+    pub fn check(&self) -> Result<()> {
+        for rule in &self.classifiers {
+            rule.validate().map_err(FileOrganizerError::InvalidRule)?;
+        }
+        let mut defined = BTreeSet::new();
+        for definition in &self.custom_categories {
+            definition.validate().map_err(FileOrganizerError::InvalidRule)?;
+            if !defined.insert(definition.name.trim().to_lowercase()) {
+                return Err(FileOrganizerError::InvalidRule(format!("custom category `{}` is defined twice", definition.name.trim())));
+            }
+        }
+        ExtensionOverrides::validate(&self.overrides).map_err(FileOrganizerError::InvalidRule)?;
+        let custom = self.custom_category_names();
+        disabled_categories(&self.categories, &custom)?;
+        category_legend(&self.display, &custom)?;
+        folder_names(&self.names, self.name_style)?;
+        DatePatterns::new(&self.date_patterns)?;
+        ExcludeGlobs::new(&self.exclude)?;
+        Guard::new(&self.protected)?;
+        post_commands(&self.after_move, &custom)?;
+        retention_limits(&self.retention, &custom)?;
+        if let Some(age) = &self.index_retention {
+            parse_duration(age).map_err(|e| FileOrganizerError::InvalidRule(format!("index_retention: {}", e)))?;
+        }
+        dest_permissions(&self.permissions, &custom)?;
+        volumes(&self.volumes, &custom)?;
+        if self.transfers_per_device == Some(0) {
+            return Err(FileOrganizerError::InvalidRule("transfers_per_device must be at least 1".into()));
+        }
+        Ok(())
+    }
+
+    /// Top-level categories declared in `classifiers`, `custom_categories`
+    /// and `overrides`, which the per-category settings accept besides the
+    /// built-in ones
+    pub fn custom_category_names(&self) -> Vec<&str> {
+        let overridden = self.overrides.values().filter_map(|folder| folder.trim().split('/').next());
+        self.classifiers
+            .iter()
+            .map(|rule| rule.category.trim())
+            .chain(self.custom_categories.iter().map(|definition| definition.name.trim()))
+            .chain(overridden)
+            .collect()
+    }
+
+    /// Load the default config file, if there is one
+    pub fn load_default() -> Result<Self> {
+        match Self::default_path() {
+            Some(path) if path.exists() => Self::load_from_file(path),
+            _ => Ok(Self::default()),
+        }
+    }
+
+    pub fn disabled_categories(&self) -> BTreeSet<&'static str> {
+        disabled_categories(&self.categories, &self.custom_category_names()).unwrap_or_default()
+    }
+
+    /// Categories whose download quarantine marker `--strip-quarantine` removes
+    pub fn trusted_categories<S: AsRef<str>>(&self, names: &[S]) -> Result<BTreeSet<&'static str>> {
+        trusted_categories(names, &self.custom_category_names())
+    }
+
+    pub fn legend(&self) -> CategoryLegend {
+        category_legend(&self.display, &self.custom_category_names()).unwrap_or_default()
+    }
+
+    pub fn post_commands(&self) -> PostCommands {
+        PostCommands {
+            commands: post_commands(&self.after_move, &self.custom_category_names()).unwrap_or_default(),
+            jobs: self.after_move_jobs.unwrap_or(DEFAULT_JOBS),
+        }
+    }
+
+    pub fn index_retention(&self) -> Option<Duration> {
+        self.index_retention.as_deref().and_then(|age| parse_duration(age).ok())
+    }
+
+    pub fn retention_limits(&self) -> BTreeMap<&'static str, Duration> {
+        retention_limits(&self.retention, &self.custom_category_names()).unwrap_or_default()
+    }
+
+    pub fn dest_permissions(&self) -> BTreeMap<&'static str, DestPermissions> {
+        dest_permissions(&self.permissions, &self.custom_category_names()).unwrap_or_default()
+    }
+
+    pub fn volumes(&self) -> Volumes {
+        volumes(&self.volumes, &self.custom_category_names()).unwrap_or_default()
+    }
+
+    pub fn folder_names(&self) -> FolderNames {
+        folder_names(&self.names, self.name_style).unwrap_or_default()
+    }
+
+    pub fn date_patterns(&self) -> DatePatterns {
+        DatePatterns::new(&self.date_patterns).unwrap_or_default()
+    }
+
+    pub fn exclude_globs(&self) -> ExcludeGlobs {
+        ExcludeGlobs::new(&self.exclude).unwrap_or_default()
+    }
+
+    pub fn guard(&self) -> Guard {
+        Guard::new(&self.protected).unwrap_or_default()
+    }
+
+    /// Build the extension overrides (empty if they are invalid)
+    pub fn extension_overrides(&self) -> ExtensionOverrides {
+        ExtensionOverrides::new(&self.overrides).unwrap_or_default()
+    }
+
+    /// Build the configured rule classifiers, those of `custom_categories`
+    /// included (invalid rules are left out)
+    pub fn rule_classifiers(&self) -> Vec<Arc<RuleClassifier>> {
+        let defined = self.custom_categories.iter().flat_map(CategoryDefinition::classifier_rules);
+        self.classifiers
+            .iter()
+            .cloned()
+            .chain(defined)
+            .filter_map(|rule| RuleClassifier::from_rule(&rule).ok())
+            .map(Arc::new)
+            .collect()
+    }
+}
+
+/// A directory organized on every daemon pass
+#[derive(Debug, Deserialize, Clone)]
+pub struct WatchedDir {
+    pub path: PathBuf,
+
+    #[serde(default)]
+    pub portable: bool,
+
+    #[serde(default)]
+    pub dest: Option<PathBuf>,
+
+    #[serde(default)]
+    pub snapshot: bool,
+}
+
+impl WatchedDir {
+    /// Organize this directory with the organizer `config`, as `stash organize` would
+    pub fn options(&self, config: &OrganizerConfig) -> OrganizeOptions {
+        OrganizeOptions {
+            portable: self.portable,
+            dest: self.dest.clone(),
+            snapshot: self.snapshot,
+            volumes: config.volumes(),
+            date_patterns: config.date_patterns(),
+            exclude: config.exclude_globs(),
+            disabled_categories: config.disabled_categories(),
+            permissions: config.dest_permissions(),
+            legend: config.legend(),
+            folder_names: config.folder_names(),
+            classifiers: config.rule_classifiers(),
+            overrides: config.extension_overrides(),
+            min_classifier_score: config.min_classifier_score,
+            preserve: config.preserve,
+            post_process: config.post_commands(),
+            transfers_per_device: config.transfers_per_device,
+            guard: config.guard(),
+            index_retention: config.index_retention(),
+            ..Default::default()
+        }
+    }
+}
+
+fn default_interval_secs() -> u64 {
+    3600
+}
+
+/// Settings for `stash daemon`, read from a JSON file
+#[derive(Debug, Deserialize, Clone)]
+pub struct DaemonConfig {
+    /// Seconds between organize passes
+    #[serde(default = "default_interval_secs")]
+    pub interval_secs: u64,
+
+    /// Defaults to `file_organizer/stash.pid` in the local data dir
+    #[serde(default)]
+    pub pid_file: Option<PathBuf>,
+
+    pub directories: Vec<WatchedDir>,
+
+    /// Write the outcome of every pass here, as `stash organize --status-file` does
+    #[serde(default)]
+    pub status_file: Option<PathBuf>,
+
+    /// Categories switched on or off for every directory
+    #[serde(default)]
+    pub categories: HashMap<String, bool>,
+
+    /// When passes may run (see [`Schedule`])
+    #[serde(default)]
+    pub schedule: Schedule,
+}
+
+impl DaemonConfig {
+    /// `<config dir>/file_organizer/daemon.json`
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|d| d.join("file_organizer").join("daemon.json"))
+    }
+
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let data = fs::read_to_string(&path)
+            .map_err(FileOrganizerError::Io)?;
+
+        let mut config: DaemonConfig = serde_json::from_str(&data)
+            .map_err(|e| FileOrganizerError::Json {
+                path: path.as_ref().to_path_buf(),
+                source: e
+            })?;
+
+        disabled_categories(&config.categories, &[])?;
+
+        if config.interval_secs == 0 {
+            return Err(FileOrganizerError::Config(anyhow::anyhow!(
+                "interval_secs must be greater than zero"
+            )));
+        }
+
+        // Allow `~` and env vars in every configured path
+        let expand = |p: &Path| expand_tilde(p.to_string_lossy());
+        config.pid_file = config.pid_file.as_deref().map(expand);
+        for dir in &mut config.directories {
+            dir.path = expand(&dir.path);
+            dir.dest = dir.dest.as_deref().map(expand);
+        }
+
+        Ok(config)
+    }
+
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.interval_secs)
+    }
+}
@@ -0,0 +1,192 @@
+//! The organizer config's schema version and its deprecated keys. A config
+//! using an old key is migrated when loaded: the key is renamed, `version`
+//! set to [`CONFIG_VERSION`], and the file rewritten with the original kept
+//! next to it. `stash config validate` reports the same without writing.
+
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use colored::*;
+use serde_json::{Map, Value};
+
+use crate::{
+    config::OrganizerConfig,
+    errors::{FileOrganizerError, Result},
+};
+
+/// Schema version written into migrated configs. Configs without a
+/// `version` are version 0.
+pub const CONFIG_VERSION: u64 = 1;
+
+/// Top-level keys of the current schema; keep in sync with [`OrganizerConfig`]
+pub const CONFIG_KEYS: [&str; 21] = [
+    "version",
+    "categories",
+    "display",
+    "names",
+    "name_style",
+    "classifiers",
+    "custom_categories",
+    "overrides",
+    "min_classifier_score",
+    "date_patterns",
+    "exclude",
+    "protected",
+    "preserve",
+    "after_move",
+    "after_move_jobs",
+    "retention",
+    "index_retention",
+    "permissions",
+    "volumes",
+    "transfers_per_device",
+    "schedule",
+];
+
+/// Old keys and the keys that replaced them, with the version that did
+pub const DEPRECATED_KEYS: [DeprecatedKey; 1] = [DeprecatedKey { old: "filename_dates", new: "date_patterns", since: 1 }];
+
+/// A key renamed in a newer schema
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeprecatedKey {
+    pub old: &'static str,
+    pub new: &'static str,
+    pub since: u64,
+}
+
+/// One change [`migrate`] made
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Migration {
+    pub key: DeprecatedKey,
+    /// Both keys were set; the old one was dropped and the new one kept
+    pub dropped: bool,
+}
+
+impl fmt::Display for Migration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.dropped {
+            write!(f, "`{}` is deprecated and `{}` is set too; `{}` was dropped", self.key.old, self.key.new, self.key.old)
+        } else {
+            write!(f, "`{}` is deprecated; renamed to `{}`", self.key.old, self.key.new)
+        }
+    }
+}
+
+/// The `version` of a config, 0 when it has none
+pub fn version_of(config: &Map<String, Value>) -> u64 {
+    config.get("version").and_then(Value::as_u64).unwrap_or(0)
+}
+
+/// Rename deprecated keys in `config` and, when anything changed, set its
+/// `version` to [`CONFIG_VERSION`]
+pub fn migrate(config: &mut Map<String, Value>) -> Vec<Migration> {
+    let mut migrations = Vec::new();
+    for key in DEPRECATED_KEYS {
+        let Some(value) = config.remove(key.old) else {
+            continue;
+        };
+        let dropped = config.contains_key(key.new);
+        if !dropped {
+            config.insert(key.new.to_string(), value);
+        }
+        migrations.push(Migration { key, dropped });
+    }
+    if !migrations.is_empty() {
+        config.insert("version".to_string(), Value::from(CONFIG_VERSION.max(version_of(config))));
+    }
+    migrations
+}
+
+/// Where the original of a config migrated from `version` is kept
+pub fn backup_path(path: &Path, version: u64) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".v{}.bak", version));
+    path.with_file_name(name)
+}
+
+/// Keep the original `text` of the config at `path` as a backup, then
+/// replace the file with `config`. Returns the backup's path.
+pub fn write_migrated(path: &Path, text: &str, config: &Map<String, Value>, from_version: u64) -> Result<PathBuf> {
+    let backup = backup_path(path, from_version);
+    std::fs::write(&backup, text)?;
+    let mut json = serde_json::to_string_pretty(config)
+        .map_err(|source| FileOrganizerError::Json { path: path.to_path_buf(), source })?;
+    json.push('\n');
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, json)?;
+    std::fs::rename(&tmp, path)?;
+    Ok(backup)
+}
+
+/// What `stash config validate` found
+#[derive(Debug, Default)]
+pub struct ValidationReport {
+    pub path: PathBuf,
+    pub version: u64,
+    /// Deprecated keys, migrated on the next load
+    pub deprecated: Vec<Migration>,
+    /// Keys the schema does not know, most likely typos; they are ignored
+    pub unknown: Vec<String>,
+    /// Problems that stop the config from loading
+    pub errors: Vec<String>,
+}
+
+impl ValidationReport {
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} {}", "Config".bold().blue(), self.path.display())?;
+        writeln!(f, "  Version:  {} (current {})", self.version, CONFIG_VERSION)?;
+        for migration in &self.deprecated {
+            writeln!(f, "  {} {}, migrated when next loaded", "deprecated:".yellow(), migration)?;
+        }
+        for key in &self.unknown {
+            writeln!(f, "  {} `{}` is not a config key and is ignored", "unknown:".yellow(), key)?;
+        }
+        for error in &self.errors {
+            writeln!(f, "  {} {}", "error:".red(), error)?;
+        }
+        if self.is_valid() {
+            writeln!(f, "  {}", "valid".green())?;
+        }
+        Ok(())
+    }
+}
+
+/// Check the config at `path` against the current schema without changing it
+pub fn validate(path: &Path) -> Result<ValidationReport> {
+    let text = std::fs::read_to_string(path)?;
+    let mut report = ValidationReport { path: path.to_path_buf(), ..Default::default() };
+    let mut config = match serde_json::from_str::<Value>(&text) {
+        Ok(Value::Object(config)) => config,
+        Ok(_) => {
+            report.errors.push("expected a JSON object".to_string());
+            return Ok(report);
+        }
+        Err(e) => {
+            report.errors.push(e.to_string());
+            return Ok(report);
+        }
+    };
+    report.version = version_of(&config);
+    if report.version > CONFIG_VERSION {
+        report.errors.push(format!("version {} is newer than this stash understands ({})", report.version, CONFIG_VERSION));
+    }
+    report.deprecated = migrate(&mut config);
+    report.unknown = config.keys().filter(|key| !CONFIG_KEYS.contains(&key.as_str())).cloned().collect();
+    match serde_json::from_value::<OrganizerConfig>(Value::Object(config)) {
+        Ok(config) => {
+            if let Err(e) = config.check() {
+                report.errors.push(e.to_string());
+            }
+        }
+        Err(e) => report.errors.push(e.to_string()),
+    }
+    Ok(report)
+}
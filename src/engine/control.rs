@@ -0,0 +1,96 @@
+//! Steering a run while it is going: pausing before the next file is
+//! started, and leaving whole categories in place from then on. Used by the
+//! `stash tui` dashboard; files already being moved are never interrupted.
+
+use std::{
+    collections::BTreeSet,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use tokio::sync::Notify;
+
+/// Shared pause flag and skipped categories. Clones steer the same run.
+#[derive(Debug, Clone, Default)]
+pub struct RunControl(Arc<Inner>);
+
+#[derive(Debug, Default)]
+struct Inner {
+    paused: AtomicBool,
+    notify: Notify,
+    skipped: Mutex<BTreeSet<&'static str>>,
+}
+
+impl RunControl {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start no new files until [`resume`](Self::resume)
+    pub fn pause(&self) {
+        self.0.paused.store(true, Ordering::SeqCst);
+    }
+
+    pub fn resume(&self) {
+        if self.0.paused.swap(false, Ordering::SeqCst) {
+            self.0.notify.notify_waiters();
+        }
+    }
+
+    /// Pause if running, resume if paused; returns whether it is now paused
+    pub fn toggle_pause(&self) -> bool {
+        if self.is_paused() {
+            self.resume();
+            false
+        } else {
+            self.pause();
+            true
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.0.paused.load(Ordering::SeqCst)
+    }
+
+    /// Resolves at once when running, otherwise once resumed
+    pub async fn resumed(&self) {
+        loop {
+            let notified = self.0.notify.notified();
+            tokio::pin!(notified);
+            // Register before checking, so a resume in between is not missed
+            notified.as_mut().enable();
+            if !self.is_paused() {
+                return;
+            }
+            notified.await;
+        }
+    }
+
+    /// Leave files of the top-level `category` (e.g. `Images`) in place
+    pub fn skip_category(&self, category: &'static str) {
+        self.0.skipped.lock().unwrap().insert(category);
+    }
+
+    /// Skip `category` if it is organized, organize it again if skipped;
+    /// returns whether it is now skipped
+    pub fn toggle_category(&self, category: &'static str) -> bool {
+        let mut skipped = self.0.skipped.lock().unwrap();
+        if skipped.remove(category) {
+            false
+        } else {
+            skipped.insert(category);
+            true
+        }
+    }
+
+    pub fn is_skipped(&self, category: &str) -> bool {
+        self.0.skipped.lock().unwrap().contains(category)
+    }
+
+    /// Categories skipped so far
+    pub fn skipped(&self) -> BTreeSet<&'static str> {
+        self.0.skipped.lock().unwrap().clone()
+    }
+}
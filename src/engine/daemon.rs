@@ -0,0 +1,329 @@
+use std::{fmt, future::Future, mem::discriminant, path::{Path, PathBuf}, time::Duration};
+
+use chrono::{DateTime, Local};
+use colored::*;
+use futures::FutureExt;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::{disabled_categories, DaemonConfig, OrganizerConfig, WatchedDir},
+    errors::{FileOrganizerError, Result},
+    organizer::{organise_files, OrganizeOptions},
+    schedule::Pause,
+    snapshot::now_unix,
+    utils::{process_alive, write_json_atomically},
+};
+
+/// How often a paused daemon checks whether it may resume
+const PAUSE_RECHECK: Duration = Duration::from_secs(30);
+
+/// PID file that is removed again when dropped
+#[derive(Debug)]
+pub struct PidFile {
+    path: PathBuf,
+}
+
+impl PidFile {
+    /// Write the current PID to `path`.
+    /// Fails if the file names a process that is still running.
+    pub fn create(path: &Path) -> Result<Self> {
+        if let Ok(contents) = std::fs::read_to_string(path)
+            && let Ok(pid) = contents.trim().parse::<u32>()
+            && pid != std::process::id()
+            // Where that can't be checked, a leftover file is assumed stale
+            && process_alive(pid).unwrap_or(false)
+        {
+            return Err(FileOrganizerError::Other(format!(
+                "Daemon already running with PID {} ({:?})",
+                pid, path
+            )));
+        }
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, format!("{}\n", std::process::id()))?;
+
+        Ok(Self { path: path.to_path_buf() })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for PidFile {
+    fn drop(&mut self) {
+        if let Err(e) = std::fs::remove_file(&self.path) {
+            tracing::warn!(target: "daemon", pid_file = ?self.path, "Failed to remove PID file: {}", e);
+        }
+    }
+}
+
+/// Default PID file location: `<local data dir>/file_organizer/stash.pid`
+pub fn default_pid_path() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("file_organizer")
+        .join("stash.pid")
+}
+
+/// What a running daemon is doing, kept next to its PID file for
+/// `stash daemon status`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DaemonState {
+    pub pid: u32,
+    /// Unix seconds, like the other timestamps
+    pub started_at: i64,
+    /// Why passes are held off; `None` while the daemon organizes normally
+    pub paused: Option<String>,
+    pub paused_since: Option<i64>,
+    /// When the last complete pass finished
+    pub last_pass: Option<i64>,
+}
+
+impl DaemonState {
+    fn new() -> Self {
+        Self { pid: std::process::id(), started_at: now_unix(), paused: None, paused_since: None, last_pass: None }
+    }
+
+    /// Replace the file at `path` in one step, so a reader never sees half of it
+    pub fn write(&self, path: &Path) -> Result<()> {
+        write_json_atomically(path, self)
+    }
+
+    pub fn read(path: &Path) -> Result<Self> {
+        serde_json::from_slice(&std::fs::read(path)?)
+            .map_err(|source| FileOrganizerError::Json { path: path.to_path_buf(), source })
+    }
+}
+
+/// The state file for the daemon holding `pid_path`: `stash.pid` → `stash.state.json`
+pub fn state_path(pid_path: &Path) -> PathBuf {
+    pid_path.with_extension("state.json")
+}
+
+/// `stash daemon status`: whether the daemon runs, and whether it is paused
+#[derive(Debug, Clone)]
+pub struct DaemonStatus {
+    /// The running daemon's PID
+    pub pid: Option<u32>,
+    /// Only read while the daemon runs; a leftover file is stale
+    pub state: Option<DaemonState>,
+}
+
+impl DaemonStatus {
+    pub fn read(pid_path: &Path) -> Self {
+        let pid = std::fs::read_to_string(pid_path)
+            .ok()
+            .and_then(|contents| contents.trim().parse::<u32>().ok())
+            .filter(|pid| process_alive(*pid).unwrap_or(true));
+        let state = pid.and_then(|_| DaemonState::read(&state_path(pid_path)).ok());
+        Self { pid, state }
+    }
+}
+
+impl fmt::Display for DaemonStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some(pid) = self.pid else {
+            return writeln!(f, "The daemon is not running.");
+        };
+        let time = |ts: i64| {
+            DateTime::from_timestamp(ts, 0)
+                .map(|t| t.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S").to_string())
+                .unwrap_or_default()
+        };
+        writeln!(f, "{}", "Daemon:".bold().blue())?;
+        writeln!(f, "  {:<10} {}", "PID", pid)?;
+        let Some(state) = &self.state else {
+            return Ok(());
+        };
+        writeln!(f, "  {:<10} {}", "Started", time(state.started_at))?;
+        match (&state.paused, state.paused_since) {
+            (Some(reason), Some(since)) => {
+                writeln!(f, "  {:<10} {} since {}", "State", format!("paused, {}", reason).yellow(), time(since))?
+            }
+            _ => writeln!(f, "  {:<10} {}", "State", "active".green())?,
+        }
+        match state.last_pass {
+            Some(ts) => writeln!(f, "  {:<10} {}", "Last pass", time(ts)),
+            None => writeln!(f, "  {:<10} none yet", "Last pass"),
+        }
+    }
+}
+
+/// Resolves once SIGTERM or Ctrl-C is received
+pub async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        match signal(SignalKind::terminate()) {
+            Ok(mut term) => {
+                tokio::select! {
+                    _ = term.recv() => {}
+                    _ = tokio::signal::ctrl_c() => {}
+                }
+            }
+            Err(e) => {
+                tracing::warn!(target: "daemon", "Cannot listen for SIGTERM: {}", e);
+                let _ = tokio::signal::ctrl_c().await;
+            }
+        }
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+    }
+}
+
+/// Periodically organizes every configured directory
+pub struct Daemon {
+    config: DaemonConfig,
+    organizer: OrganizerConfig,
+    state_file: Option<PathBuf>,
+}
+
+impl Daemon {
+    pub fn new(config: DaemonConfig) -> Self {
+        Self { config, organizer: OrganizerConfig::default(), state_file: None }
+    }
+
+    /// Organize with `config` (protected paths, exclusions, folder names,
+    /// overrides...) rather than the built-in defaults
+    pub fn with_organizer_config(mut self, config: OrganizerConfig) -> Self {
+        self.organizer = config;
+        self
+    }
+
+    /// Keep the [`DaemonState`] in `path` rather than next to the PID file
+    pub fn with_state_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.state_file = Some(path.into());
+        self
+    }
+
+    /// Run until SIGTERM / Ctrl-C, holding the PID file for the whole lifetime
+    pub async fn run(&self) -> Result<()> {
+        let pid_path = self.config.pid_file.clone().unwrap_or_else(default_pid_path);
+        let pid_file = PidFile::create(&pid_path)?;
+        tracing::info!(target: "daemon", pid = std::process::id(), pid_file = ?pid_file.path(), "Daemon started");
+
+        let state_file = self.state_file.clone().unwrap_or_else(|| state_path(&pid_path));
+        self.pass_loop(shutdown_signal(), Some(&state_file)).await
+    }
+
+    /// Organize on every interval tick until `shutdown` resolves.
+    /// A directory being organized is always finished before stopping.
+    pub async fn run_until<F: Future<Output = ()>>(&self, shutdown: F) -> Result<()> {
+        self.pass_loop(shutdown, self.state_file.as_deref()).await
+    }
+
+    /// While the schedule pauses the daemon, it is asked again every
+    /// [`PAUSE_RECHECK`] and a pass starts as soon as it allows one
+    async fn pass_loop<F: Future<Output = ()>>(&self, shutdown: F, state_file: Option<&Path>) -> Result<()> {
+        tokio::pin!(shutdown);
+        let mut ticker = tokio::time::interval(self.config.interval());
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        let mut state = DaemonState::new();
+        let mut pause: Option<Pause> = None;
+        let save = |state: &DaemonState| {
+            if let Some(path) = state_file
+                && let Err(e) = state.write(path)
+            {
+                tracing::warn!(target: "daemon", state_file = ?path, "Failed to write state file: {}", e);
+            }
+        };
+        save(&state);
+
+        'passes: loop {
+            tokio::select! {
+                _ = &mut shutdown => break,
+                _ = ticker.tick() => {}
+                _ = tokio::time::sleep(PAUSE_RECHECK), if pause.is_some() => {}
+            }
+
+            let mut held = None;
+            for dir in &self.config.directories {
+                // Checked per directory, so a pause that starts mid-pass holds the rest
+                if let Some(reason) = self.pause_for(dir) {
+                    held = Some(reason);
+                    break;
+                }
+                self.organize_dir(dir).await;
+
+                // Never interrupt a directory mid-move; stop between directories
+                if (&mut shutdown).now_or_never().is_some() {
+                    break 'passes;
+                }
+            }
+
+            match held {
+                Some(reason) => {
+                    // A changing amount of free space is still the same pause
+                    if pause.as_ref().map(discriminant) != Some(discriminant(&reason)) {
+                        tracing::info!(target: "daemon", reason = %reason, "Daemon paused");
+                        state.paused_since = Some(now_unix());
+                    }
+                    state.paused = Some(reason.to_string());
+                    pause = Some(reason);
+                }
+                None => {
+                    if pause.take().is_some() {
+                        tracing::info!(target: "daemon", "Daemon resumed");
+                    }
+                    state.paused = None;
+                    state.paused_since = None;
+                    state.last_pass = Some(now_unix());
+                }
+            }
+            save(&state);
+        }
+
+        if let Some(path) = state_file {
+            let _ = std::fs::remove_file(path);
+        }
+        tracing::info!(target: "daemon", "Daemon stopped");
+        Ok(())
+    }
+
+    /// Run a single pass over all directories
+    pub async fn run_once(&self) {
+        for dir in &self.config.directories {
+            if let Some(reason) = self.pause_for(dir) {
+                tracing::info!(target: "daemon", reason = %reason, "Pass paused by the schedule");
+                return;
+            }
+            self.organize_dir(dir).await;
+        }
+    }
+
+    fn pause_for(&self, dir: &WatchedDir) -> Option<Pause> {
+        self.config.schedule.pause_for(&dir.options(&self.organizer).dest_root(&dir.path))
+    }
+
+    async fn organize_dir(&self, dir: &WatchedDir) {
+        let mut options = OrganizeOptions { status_file: self.config.status_file.clone(), ..dir.options(&self.organizer) };
+        // On top of the organizer config's; validated when the config was loaded
+        options.disabled_categories.extend(disabled_categories(&self.config.categories, &[]).unwrap_or_default());
+        match organise_files(&dir.path, &options).await {
+            Ok(summary) => tracing::info!(
+                target: "daemon",
+                dir = ?dir.path,
+                processed = summary.processed,
+                moved = summary.moved,
+                renamed = summary.renamed,
+                errors = summary.errors,
+                duration_ms = summary.duration.as_millis() as u64,
+                "Organize pass complete"
+            ),
+            Err(e) => tracing::error!(
+                target: "daemon",
+                dir = ?dir.path,
+                error = %e,
+                "Organize pass failed"
+            ),
+        }
+    }
+}
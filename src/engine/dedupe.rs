@@ -0,0 +1,395 @@
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+};
+
+use colored::*;
+use serde_json::Value;
+
+use crate::{
+    chunking::SimilarPair,
+    errors::{FileOrganizerError, Result},
+    file_mover::FileMover,
+    hasher::{create_hasher, FileHasher, HashAlgo},
+    index::{ActionRecord, Db},
+    stats::format_size,
+    trash,
+};
+
+/// Layout of an external duplicate report
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReportFormat {
+    /// Detect from the content
+    #[default]
+    Auto,
+    /// fdupes / jdupes plain text: one path per line, blank line between sets
+    Fdupes,
+    /// `jdupes -j` output
+    JdupesJson,
+    /// czkawka JSON export (groups of objects with a `path` field)
+    CzkawkaJson,
+}
+
+impl FromStr for ReportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "auto" => Ok(Self::Auto),
+            "fdupes" | "jdupes" => Ok(Self::Fdupes),
+            "jdupes-json" => Ok(Self::JdupesJson),
+            "czkawka" | "czkawka-json" => Ok(Self::CzkawkaJson),
+            other => Err(format!(
+                "unknown report format `{}` (auto, fdupes, jdupes-json, czkawka-json)",
+                other
+            )),
+        }
+    }
+}
+
+/// Files reported as identical; the first one is kept
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateSet {
+    pub files: Vec<PathBuf>,
+}
+
+/// Parse a duplicate report into sets of two or more files
+pub fn parse_report(text: &str, format: ReportFormat) -> Result<Vec<DuplicateSet>> {
+    let format = match format {
+        ReportFormat::Auto if text.trim_start().starts_with(['{', '[']) => {
+            if text.contains("\"matchSets\"") {
+                ReportFormat::JdupesJson
+            } else {
+                ReportFormat::CzkawkaJson
+            }
+        }
+        ReportFormat::Auto => ReportFormat::Fdupes,
+        other => other,
+    };
+
+    let sets = match format {
+        ReportFormat::Fdupes | ReportFormat::Auto => parse_fdupes(text),
+        ReportFormat::JdupesJson => parse_jdupes_json(&parse_json(text)?),
+        ReportFormat::CzkawkaJson => {
+            let mut sets = Vec::new();
+            collect_czkawka_groups(&parse_json(text)?, &mut sets);
+            sets
+        }
+    };
+
+    Ok(sets.into_iter().filter(|s| s.files.len() > 1).collect())
+}
+
+fn parse_json(text: &str) -> Result<Value> {
+    serde_json::from_str(text).map_err(|source| FileOrganizerError::Json {
+        path: PathBuf::from("<duplicate report>"),
+        source,
+    })
+}
+
+fn parse_fdupes(text: &str) -> Vec<DuplicateSet> {
+    let mut sets = Vec::new();
+    let mut current = Vec::new();
+
+    for line in text.lines() {
+        let line = line.trim_end_matches('\r');
+        if line.trim().is_empty() {
+            if !current.is_empty() {
+                sets.push(DuplicateSet { files: std::mem::take(&mut current) });
+            }
+            continue;
+        }
+        current.push(PathBuf::from(line));
+    }
+    if !current.is_empty() {
+        sets.push(DuplicateSet { files: current });
+    }
+
+    sets
+}
+
+fn parse_jdupes_json(value: &Value) -> Vec<DuplicateSet> {
+    value["matchSets"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|set| DuplicateSet {
+            files: set["fileList"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|f| f["filePath"].as_str())
+                .map(PathBuf::from)
+                .collect(),
+        })
+        .collect()
+}
+
+/// czkawka nests groups differently per mode; any array of `{ "path": .. }` objects is a group
+fn collect_czkawka_groups(value: &Value, sets: &mut Vec<DuplicateSet>) {
+    match value {
+        Value::Array(items) => {
+            let paths: Vec<PathBuf> = items
+                .iter()
+                .filter_map(|item| item.get("path").and_then(Value::as_str))
+                .map(PathBuf::from)
+                .collect();
+            if !paths.is_empty() && paths.len() == items.len() {
+                sets.push(DuplicateSet { files: paths });
+            } else {
+                items.iter().for_each(|item| collect_czkawka_groups(item, sets));
+            }
+        }
+        Value::Object(map) => map.values().for_each(|v| collect_czkawka_groups(v, sets)),
+        _ => {}
+    }
+}
+
+/// What to do with each duplicate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DedupeAction {
+    /// Replace the duplicate with a hard link to the kept file
+    Hardlink,
+    /// Move the duplicate into stash's trash directory
+    Trash,
+}
+
+impl DedupeAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DedupeAction::Hardlink => "hardlink",
+            DedupeAction::Trash => "trash",
+        }
+    }
+}
+
+impl FromStr for DedupeAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "hardlink" => Ok(Self::Hardlink),
+            "trash" => Ok(Self::Trash),
+            other => Err(format!("unknown action `{}` (hardlink, trash)", other)),
+        }
+    }
+}
+
+/// Result of applying a duplicate report
+#[derive(Debug, Default)]
+pub struct DedupeOutcome {
+    pub sets: usize,
+    pub acted: usize,
+    pub bytes_freed: u64,
+    /// Files whose content no longer matches the kept file
+    pub changed: usize,
+    /// Listed files that no longer exist
+    pub missing: usize,
+    pub errors: Vec<(PathBuf, FileOrganizerError)>,
+}
+
+impl fmt::Display for DedupeOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", "Dedupe".bold().blue())?;
+        writeln!(f, "  Sets:        {}", self.sets.to_string().yellow())?;
+        writeln!(f, "  Duplicates:  {} files, {}", self.acted.to_string().green(), format_size(self.bytes_freed))?;
+        writeln!(f, "  Changed:     {} files (content differs, left alone)", self.changed)?;
+        writeln!(f, "  Missing:     {} files", self.missing)?;
+        writeln!(f, "  Errors:      {} files", self.errors.len().to_string().red())?;
+        for (path, error) in &self.errors {
+            writeln!(f, "    {}: {}", path.display(), error)?;
+        }
+        Ok(())
+    }
+}
+
+/// Near-duplicates found from chunk signatures, printed one pair per entry
+pub struct SimilarReport<'a>(pub &'a [SimilarPair]);
+
+impl fmt::Display for SimilarReport<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.is_empty() {
+            return writeln!(f, "No similar files.");
+        }
+        writeln!(f, "{}", "Probably the same content".bold().blue())?;
+        for pair in self.0 {
+            let similarity = &pair.similarity;
+            let how = if similarity.same_start() { "same start, different tail" } else { "shared content" };
+            writeln!(
+                f,
+                "  {:>5.1}%  {} of {}  ({})",
+                similarity.ratio() * 100.0,
+                format_size(similarity.shared),
+                format_size(similarity.smaller),
+                how
+            )?;
+            writeln!(f, "    {}", pair.a.display())?;
+            writeln!(f, "    {}", pair.b.display())?;
+        }
+        writeln!(f, "{} pairs", self.0.len().to_string().yellow())
+    }
+}
+
+/// Re-verify every set by hash and apply `action` to all but the first file.
+/// Trashed files go to a per-run folder under `trash_root`.
+/// Each change is recorded in the index so it can be undone.
+pub async fn apply(
+    db: &Db,
+    sets: &[DuplicateSet],
+    action: DedupeAction,
+    dry_run: bool,
+    trash_root: &Path,
+) -> Result<DedupeOutcome> {
+    let hasher = create_hasher(HashAlgo::Blake3);
+    let mover = FileMover::new();
+    let trash_dir = match action {
+        DedupeAction::Trash if !dry_run => Some(trash::run_dir(trash_root)?),
+        _ => None,
+    };
+    let mut outcome = DedupeOutcome { sets: sets.len(), ..Default::default() };
+
+    for set in sets {
+        let mut files = Vec::with_capacity(set.files.len());
+        for path in &set.files {
+            if tokio::fs::try_exists(path).await? {
+                files.push(path.clone());
+            } else {
+                outcome.missing += 1;
+            }
+        }
+        let Some((keeper, duplicates)) = files.split_first() else {
+            continue;
+        };
+
+        let keeper_hash = match hash(&hasher, keeper).await {
+            Ok(h) => h,
+            Err(e) => {
+                outcome.errors.push((keeper.clone(), e));
+                continue;
+            }
+        };
+
+        for dup in duplicates {
+            if same_file(keeper, dup) {
+                continue;
+            }
+            match hash(&hasher, dup).await {
+                Ok(h) if h == keeper_hash => {}
+                Ok(_) => {
+                    outcome.changed += 1;
+                    continue;
+                }
+                Err(e) => {
+                    outcome.errors.push((dup.clone(), e));
+                    continue;
+                }
+            }
+
+            let size = tokio::fs::metadata(dup).await?.len();
+            if dry_run {
+                println!("Would {} {:?} (duplicate of {:?})", action.as_str(), dup, keeper);
+                outcome.acted += 1;
+                outcome.bytes_freed += size;
+                continue;
+            }
+
+            let target = match action {
+                DedupeAction::Hardlink => hardlink_over(keeper, dup).await.map(|_| keeper.clone()),
+                DedupeAction::Trash => {
+                    let trash_dir = trash_dir.as_deref().expect("trashing runs get a trash folder");
+                    trash::move_to(&mover, dup, trash_dir).await
+                }
+            };
+            match target {
+                Ok(target) => {
+                    db.record_action(action.as_str(), dup, &target, Some(&keeper_hash), size).await?;
+                    tracing::info!(target: "dedupe", "{} {:?} (duplicate of {:?})", action.as_str(), dup, keeper);
+                    outcome.acted += 1;
+                    outcome.bytes_freed += size;
+                }
+                Err(e) => outcome.errors.push((dup.clone(), e)),
+            }
+        }
+    }
+
+    if let Some(trash_dir) = &trash_dir {
+        trash::discard_if_empty(trash_dir).await;
+    }
+    Ok(outcome)
+}
+
+/// Undo recorded dedupe actions (all pending ones when `ids` is empty).
+/// Returns how many were restored.
+pub async fn undo(db: &Db, ids: &[i64]) -> Result<usize> {
+    let mover = FileMover::new();
+    let mut restored = 0;
+
+    for record in db.list_actions(false).await? {
+        if !ids.is_empty() && !ids.contains(&record.id) {
+            continue;
+        }
+        match undo_one(&mover, &record).await {
+            Ok(path) => {
+                db.mark_action_reverted(record.id).await?;
+                tracing::info!(target: "dedupe", "Undid {} #{} -> {:?}", record.kind, record.id, path);
+                restored += 1;
+            }
+            Err(e) => {
+                tracing::warn!(target: "dedupe", "Cannot undo {} #{}: {}", record.kind, record.id, e);
+                eprintln!("Cannot undo #{} ({:?}): {}", record.id, record.path, e);
+            }
+        }
+    }
+
+    Ok(restored)
+}
+
+async fn undo_one(mover: &FileMover, record: &ActionRecord) -> Result<PathBuf> {
+    match record.kind.parse::<DedupeAction>().map_err(FileOrganizerError::Other)? {
+        DedupeAction::Trash => trash::move_back(mover, record).await,
+        DedupeAction::Hardlink => {
+            // Give the path its own copy of the data again
+            let tmp = sibling_tmp(&record.path);
+            mover.copy_file(&record.target, &tmp).await?;
+            tokio::fs::rename(&tmp, &record.path).await?;
+            Ok(record.path.clone())
+        }
+    }
+}
+
+async fn hash(hasher: &Arc<dyn FileHasher>, path: &Path) -> Result<String> {
+    Ok(hex::encode(hasher.hash_file(path).await?))
+}
+
+/// Replace `dup` with a hard link to `keeper` without a window where `dup` is missing
+async fn hardlink_over(keeper: &Path, dup: &Path) -> Result<()> {
+    let tmp = sibling_tmp(dup);
+    tokio::fs::hard_link(keeper, &tmp).await?;
+    if let Err(e) = tokio::fs::rename(&tmp, dup).await {
+        let _ = tokio::fs::remove_file(&tmp).await;
+        return Err(e.into());
+    }
+    Ok(())
+}
+
+fn sibling_tmp(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".stash-tmp");
+    path.with_file_name(name)
+}
+
+#[cfg(unix)]
+pub(crate) fn same_file(a: &Path, b: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    match (std::fs::metadata(a), std::fs::metadata(b)) {
+        (Ok(a), Ok(b)) => a.dev() == b.dev() && a.ino() == b.ino(),
+        _ => false,
+    }
+}
+
+#[cfg(not(unix))]
+pub(crate) fn same_file(a: &Path, b: &Path) -> bool {
+    a == b
+}
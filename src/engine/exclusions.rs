@@ -0,0 +1,176 @@
+//! What stash never organizes, kept in one place for the scanner and the
+//! watcher: its own outputs (the destination tree, the index database with
+//! its WAL and journal files, logs, the run status file) and the user's
+//! `exclude` globs. Without this, a watched directory that holds any of them
+//! feeds stash's own writes back into it.
+
+use std::path::{Component, Path, PathBuf};
+
+use regex::Regex;
+
+use crate::{
+    errors::{FileOrganizerError, Result},
+    utils::PORTABLE_DB_DIR,
+};
+
+/// File name prefixes of the index database (`file_organizer.db`,
+/// `-wal`, `-shm`, `-journal`) and the daily log (`file_organizer.log.2025-01-31`)
+const OWN_FILE_PREFIXES: [&str; 2] = ["file_organizer.db", "file_organizer.log"];
+
+/// Left in each folder an in-place run (`--in-place`) sorts files into at the
+/// top of the root, so later scans do not organize them again
+pub const IN_PLACE_MARKER: &str = ".stash-organized";
+
+/// Globs of paths to leave alone, e.g. `*.part`, `node_modules` or
+/// `Projects/**`. `*` and `?` stay within one path component, `**` crosses
+/// them. A glob without `/` matches the name of the file or of any
+/// directory above it; one with `/` matches the whole path below the root.
+#[derive(Debug, Clone, Default)]
+pub struct ExcludeGlobs {
+    globs: Vec<(Regex, bool)>,
+}
+
+impl ExcludeGlobs {
+    pub fn new<S: AsRef<str>>(globs: &[S]) -> Result<Self> {
+        let globs = globs
+            .iter()
+            .map(|glob| {
+                let glob = glob.as_ref().trim_matches('/');
+                if glob.is_empty() {
+                    return Err(FileOrganizerError::InvalidRule("empty exclude glob".into()));
+                }
+                let regex = Regex::new(&glob_to_regex(glob))
+                    .map_err(|e| FileOrganizerError::InvalidRule(format!("exclude glob `{}`: {}", glob, e)))?;
+                Ok((regex, glob.contains('/')))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { globs })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.globs.is_empty()
+    }
+
+    /// Add the globs of `other`, e.g. command-line ones to the config's
+    pub fn append(&mut self, mut other: ExcludeGlobs) {
+        self.globs.append(&mut other.globs);
+    }
+
+    /// Whether `relative` (a path below the root, `/`-separated or native) matches
+    pub fn matches(&self, relative: &Path) -> bool {
+        let names: Vec<&str> = relative
+            .components()
+            .filter_map(|c| match c {
+                Component::Normal(name) => name.to_str(),
+                _ => None,
+            })
+            .collect();
+        let joined = names.join("/");
+        self.globs.iter().any(|(regex, anchored)| {
+            if *anchored {
+                regex.is_match(&joined)
+            } else {
+                names.iter().any(|name| regex.is_match(name))
+            }
+        })
+    }
+}
+
+/// Everything excluded below one root
+#[derive(Debug, Clone, Default)]
+pub struct Exclusions {
+    root: PathBuf,
+    /// Directories never descended into, e.g. the destination
+    dirs: Vec<PathBuf>,
+    /// Single files, e.g. the run status file
+    files: Vec<PathBuf>,
+    globs: ExcludeGlobs,
+    /// Folders directly under the root holding an [`IN_PLACE_MARKER`]
+    marked: bool,
+}
+
+impl Exclusions {
+    /// stash's own files under `root`: the portable index directory and
+    /// anything named like the index or the log
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        let root = root.into();
+        Self {
+            dirs: vec![root.join(PORTABLE_DB_DIR)],
+            root,
+            files: Vec::new(),
+            globs: ExcludeGlobs::default(),
+            marked: false,
+        }
+    }
+
+    /// Also leave out everything under `dir`, unless it is the root itself
+    pub fn dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        let dir = dir.into();
+        if dir != self.root {
+            self.dirs.push(dir);
+        }
+        self
+    }
+
+    /// Also leave out `file`
+    pub fn file(mut self, file: impl Into<PathBuf>) -> Self {
+        self.files.push(file.into());
+        self
+    }
+
+    /// Also leave out folders directly under the root that hold an
+    /// [`IN_PLACE_MARKER`], for roots organized in place
+    pub fn marked(mut self) -> Self {
+        self.marked = true;
+        self
+    }
+
+    pub fn globs(mut self, globs: ExcludeGlobs) -> Self {
+        self.globs = globs;
+        self
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Whether `path` (a file or directory) is left alone
+    pub fn excludes(&self, path: &Path) -> bool {
+        if self.dirs.iter().any(|dir| path.starts_with(dir)) || self.files.iter().any(|f| f == path) {
+            return true;
+        }
+        if self.marked && path.parent() == Some(self.root.as_path()) && path.join(IN_PLACE_MARKER).is_file() {
+            return true;
+        }
+        let own = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(|name| OWN_FILE_PREFIXES.iter().any(|prefix| name.starts_with(prefix)));
+        own || path.strip_prefix(&self.root).is_ok_and(|relative| self.globs.matches(relative))
+    }
+}
+
+/// `*.part` → `^[^/]*\.part$`
+fn glob_to_regex(glob: &str) -> String {
+    let mut regex = String::from("^");
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                // `**/` also matches no directories at all
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    regex.push_str("(?:.*/)?");
+                } else {
+                    regex.push_str(".*");
+                }
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex.push('$');
+    regex
+}
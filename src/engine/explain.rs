@@ -0,0 +1,177 @@
+//! `stash classify --explain`: why a file lands where it does. Shows every
+//! classifier's confidence and weighted score, which one won, and the
+//! destination organizing the file's folder would give it.
+//! `--explain-ext` does the same for an extension, to untangle collisions
+//! like `.md` counting as both a document and code.
+
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use colored::*;
+
+use crate::{
+    errors::{FileOrganizerError, Result},
+    filename_date::DatePatterns,
+    path_builder::{DateFolders, FolderNames, PathBuilder},
+    registry::{BuiltinClassifier, ClassifierRegistry, ClassifierScore, OVERRIDE},
+    scanner::{RawFileMetadata, ScanConfig, Scanner},
+};
+
+/// The registry's decision for one file
+#[derive(Debug, Clone)]
+pub struct Explanation {
+    pub path: PathBuf,
+    pub mime: String,
+    /// Highest first, see [`ClassifierRegistry::scores`]
+    pub scores: Vec<ClassifierScore>,
+    /// Scores below this are not tried (see [`ClassifierRegistry::min_score`])
+    pub min_score: u16,
+    /// Classifier that produced the category
+    pub winner: Option<&'static str>,
+    /// e.g. `Images::Photos`
+    pub category: Option<String>,
+    /// Where `stash organize` on the file's folder would move it
+    pub destination: Option<PathBuf>,
+    /// Why no classifier produced a category
+    pub error: Option<String>,
+    /// Set when explaining an extension rather than a file
+    pub extension: Option<String>,
+}
+
+/// Classify `raw` and explain the result. The destination assumes the
+/// default `Organized` folder next to the file, with `date_patterns` read and
+/// folders named as organizing does.
+pub async fn explain(
+    registry: &ClassifierRegistry,
+    raw: &RawFileMetadata,
+    date_patterns: &DatePatterns,
+    names: &FolderNames,
+) -> Explanation {
+    let (mime, scores) = registry.scores(raw).await;
+    let mut explanation = Explanation {
+        path: raw.path.clone(),
+        mime,
+        scores,
+        min_score: registry.min_score,
+        winner: None,
+        category: None,
+        destination: None,
+        error: None,
+        extension: None,
+    };
+    let mut classified = match registry.classify_named(raw).await {
+        Ok((winner, classified)) => {
+            explanation.winner = Some(winner);
+            classified
+        }
+        Err(e) => {
+            explanation.error = Some(e.to_string());
+            return explanation;
+        }
+    };
+    explanation.category = Some(classified.category.to_string());
+
+    let mut date_folders = DateFolders::default();
+    if let Some(date) = raw.path.file_name().and_then(|n| n.to_str()).and_then(|n| date_patterns.extract(n)) {
+        classified.year = Some(date.year);
+        classified.month = date.month;
+        classified.day = date.day;
+        if date.month.is_some() {
+            date_folders = DateFolders::YearMonth;
+        }
+    }
+    let base = raw.path.parent().unwrap_or(Path::new(".")).join("Organized");
+    let mut builder = PathBuilder::new(&classified).base(&base).date_folders(date_folders).names(names);
+    if let Some(folder) = registry.overrides.folder_for(&raw.path) {
+        builder = builder.literal(folder);
+    }
+    explanation.destination = builder.destination_for(&raw.path);
+    explanation
+}
+
+/// Explain how files with extension `ext` (`md`, `.tar`) are classified.
+/// An empty `example.<ext>` stands in for them, so classifiers that look
+/// inside files see nothing; the destination is relative to `Organized`'s parent.
+pub async fn explain_extension(
+    registry: &ClassifierRegistry,
+    ext: &str,
+    date_patterns: &DatePatterns,
+    names: &FolderNames,
+) -> Result<Explanation> {
+    let ext = ext.trim().trim_start_matches('.').to_ascii_lowercase();
+    if ext.is_empty() || ext.contains(['/', '\\']) {
+        return Err(FileOrganizerError::InvalidPath(PathBuf::from(ext)));
+    }
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join(format!("example.{}", ext));
+    std::fs::write(&path, b"")?;
+    let config = ScanConfig { max_depth: 0, include_hidden: true, ..Default::default() };
+    let raw = Scanner::new(&path, config)
+        .next()
+        .ok_or_else(|| FileOrganizerError::InvalidPath(path.clone()))??;
+
+    let mut explanation = explain(registry, &raw, date_patterns, names).await;
+    explanation.path = PathBuf::from(format!("*.{}", ext));
+    explanation.destination = explanation
+        .destination
+        .and_then(|destination| destination.strip_prefix(dir.path()).map(Path::to_path_buf).ok());
+    explanation.extension = Some(ext);
+    Ok(explanation)
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} ({})", self.path.display().to_string().bold(), self.mime)?;
+        writeln!(f, "  {}", format!("{:<22} {:>8} {:>10} {:>6}", "Classifier", "Priority", "Confidence", "Score").blue())?;
+        for score in &self.scores {
+            let note = if Some(score.name) == self.winner {
+                "  ← chosen".green().to_string()
+            } else if score.weighted < self.min_score {
+                format!("  below {}", self.min_score).dimmed().to_string()
+            } else {
+                String::new()
+            };
+            writeln!(
+                f,
+                "  {:<22} {:>8} {:>10} {:>6}{}",
+                score.name, score.priority, score.confidence, score.weighted, note
+            )?;
+        }
+        if let Some(error) = &self.error {
+            return writeln!(f, "  {} {}", "error:".red(), error);
+        }
+        if self.winner == Some(OVERRIDE) {
+            writeln!(f, "  Its extension is overridden in the config")?;
+        } else if let Some(winner) = self.winner
+            && self.scores.first().is_none_or(|top| top.name != winner)
+        {
+            writeln!(f, "  Higher-scoring classifiers failed or were below the minimum score")?;
+        }
+        writeln!(f, "  Category:     {}", self.category.as_deref().unwrap_or("-"))?;
+        match &self.destination {
+            Some(destination) => writeln!(f, "  Destination:  {}", destination.display())?,
+            None => writeln!(f, "  Destination:  -")?,
+        }
+        // A collision: more than one specific classifier wants the extension
+        let generic = BuiltinClassifier::Generic.name();
+        if let Some(ext) = &self.extension
+            && self.scores.iter().filter(|score| score.name != generic).count() > 1
+            && self.winner != Some(OVERRIDE)
+        {
+            // Overrides and classifiers match the last extension of `.tar.gz`
+            let ext = ext.rsplit('.').next().unwrap_or(ext);
+            writeln!(
+                f,
+                "  {}",
+                format!(
+                    "To change the winner, add a classifier for `{}` with a higher priority, or `\"overrides\": {{ \"{}\": \"<folder>\" }}` to the config",
+                    ext, ext
+                )
+                .dimmed()
+            )?;
+        }
+        Ok(())
+    }
+}
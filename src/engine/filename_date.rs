@@ -0,0 +1,62 @@
+use regex::Regex;
+
+use crate::errors::{FileOrganizerError, Result};
+
+/// A date read from a file name
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NameDate {
+    pub year: i32,
+    pub month: Option<u32>,
+    pub day: Option<u32>,
+}
+
+/// Regexes that find a date in a file name, tried in order. Each names its
+/// captures `year` (required), `month` and `day`, e.g.
+/// `^SCN_(?P<year>\d{4})(?P<month>\d{2})(?P<day>\d{2})_` for `SCN_20240131_0001.pdf`.
+#[derive(Debug, Clone, Default)]
+pub struct DatePatterns {
+    patterns: Vec<Regex>,
+}
+
+impl DatePatterns {
+    pub fn new<S: AsRef<str>>(patterns: &[S]) -> Result<Self> {
+        let patterns = patterns
+            .iter()
+            .map(|p| {
+                let p = p.as_ref();
+                let regex = Regex::new(p)
+                    .map_err(|e| FileOrganizerError::InvalidRule(format!("date pattern `{}`: {}", p, e)))?;
+                if !regex.capture_names().flatten().any(|name| name == "year") {
+                    return Err(FileOrganizerError::InvalidRule(format!(
+                        "date pattern `{}` has no `(?P<year>...)` capture",
+                        p
+                    )));
+                }
+                Ok(regex)
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { patterns })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// First valid date any pattern finds in `name`. A match with an
+    /// impossible month or day (`20241399`) is ignored.
+    pub fn extract(&self, name: &str) -> Option<NameDate> {
+        self.patterns.iter().find_map(|regex| {
+            let caps = regex.captures(name)?;
+            let year: i32 = caps.name("year")?.as_str().parse().ok()?;
+            let month = match caps.name("month") {
+                Some(m) => Some(m.as_str().parse::<u32>().ok().filter(|m| (1..=12).contains(m))?),
+                None => None,
+            };
+            let day = match caps.name("day") {
+                Some(d) => Some(d.as_str().parse::<u32>().ok().filter(|d| (1..=31).contains(d))?),
+                None => None,
+            };
+            (1900..=2999).contains(&year).then_some(NameDate { year, month, day })
+        })
+    }
+}
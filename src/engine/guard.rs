@@ -0,0 +1,160 @@
+//! Roots stash refuses to organize or revert into: the filesystem root, the
+//! home directory and the directories above it, operating system directories
+//! (`/usr`, `/etc`, `C:\Windows`, ...) and whatever the config's `protected`
+//! globs match. Organizing one of them would scatter files the system or the
+//! user's other programs rely on. `--i-know-what-im-doing` turns the check off.
+
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    errors::{FileOrganizerError, Result},
+    exclusions::ExcludeGlobs,
+};
+
+/// Why a directory is refused
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Danger {
+    /// `/`, or a drive root like `C:\`
+    FilesystemRoot,
+    Home,
+    /// A directory the home directory is in, e.g. `/home`
+    AboveHome,
+    /// An operating system directory or one below it
+    System,
+    /// Matched by one of the config's `protected` globs
+    Protected,
+}
+
+impl fmt::Display for Danger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Danger::FilesystemRoot => write!(f, "is the root of a filesystem"),
+            Danger::Home => write!(f, "is the home directory"),
+            Danger::AboveHome => write!(f, "contains the home directory"),
+            Danger::System => write!(f, "is a system directory"),
+            Danger::Protected => write!(f, "is protected in the config"),
+        }
+    }
+}
+
+/// Checks roots before anything is moved. The default refuses the built-in
+/// dangerous directories only.
+#[derive(Debug, Clone, Default)]
+pub struct Guard {
+    protected: ExcludeGlobs,
+    disabled: bool,
+}
+
+impl Guard {
+    /// Also refuse directories matching `protected` (and anything below
+    /// them), e.g. `["~/Documents/Taxes", "/mnt/*"]`. A leading `~` is the
+    /// home directory; globs without a `/` match a single directory name.
+    pub fn new<S: AsRef<str>>(protected: &[S]) -> Result<Self> {
+        let mut expanded = Vec::with_capacity(protected.len());
+        for glob in protected {
+            let glob = glob.as_ref().trim();
+            if glob.trim_matches('/').is_empty() {
+                return Err(FileOrganizerError::InvalidRule(format!("protected glob `{}` matches nothing", glob)));
+            }
+            expanded.push(shellexpand::tilde(glob).into_owned());
+        }
+        Ok(Self { protected: ExcludeGlobs::new(&expanded)?, disabled: false })
+    }
+
+    /// Allow every directory (`--i-know-what-im-doing`)
+    pub fn disabled() -> Self {
+        Self { disabled: true, ..Default::default() }
+    }
+
+    pub fn is_disabled(&self) -> bool {
+        self.disabled
+    }
+
+    /// Why `dir` must not be organized or reverted into, if it mustn't
+    pub fn danger(&self, dir: &Path) -> Option<Danger> {
+        if self.disabled {
+            return None;
+        }
+        let dir = resolve(dir);
+        if dir.parent().is_none() {
+            return Some(Danger::FilesystemRoot);
+        }
+        if let Some(home) = dirs::home_dir().map(|home| resolve(&home)) {
+            if dir == home {
+                return Some(Danger::Home);
+            }
+            if home.starts_with(&dir) {
+                return Some(Danger::AboveHome);
+            }
+        }
+        if system_dirs().iter().any(|(system, below)| if *below { dir.starts_with(system) } else { dir == *system }) {
+            return Some(Danger::System);
+        }
+        if !self.protected.is_empty() && dir.ancestors().any(|ancestor| self.protected.matches(ancestor)) {
+            return Some(Danger::Protected);
+        }
+        None
+    }
+
+    /// Fail with [`FileOrganizerError::DangerousRoot`] if `dir` is refused
+    pub fn check(&self, dir: &Path) -> Result<()> {
+        match self.danger(dir) {
+            Some(danger) => Err(FileOrganizerError::DangerousRoot { path: dir.to_path_buf(), danger }),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Absolute, with symlinks resolved where the path exists, so `/tmp/../usr`
+/// or a link to `/` is caught. Lowercase on Windows, whose paths ignore case.
+fn resolve(path: &Path) -> PathBuf {
+    let absolute = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    #[cfg(unix)]
+    let absolute = std::fs::canonicalize(&absolute).unwrap_or(absolute);
+    #[cfg(windows)]
+    let absolute = PathBuf::from(absolute.to_string_lossy().to_lowercase());
+    absolute
+}
+
+/// Operating system directories, and whether everything below one is
+/// refused too or only the directory itself
+#[cfg(unix)]
+fn system_dirs() -> Vec<(PathBuf, bool)> {
+    const TREES: &[&str] = &[
+        "/bin", "/boot", "/dev", "/etc", "/lib", "/lib32", "/lib64", "/libx32", "/proc", "/sbin", "/sys", "/usr",
+        "/var/lib", "/var/log", "/System", "/Library", "/private/etc",
+    ];
+    // Their children are often fine: `/var/folders` holds macOS's temp
+    // directories, `/opt/<app>` and `/srv/<site>` are the user's own
+    const ONLY: &[&str] = &["/var", "/opt", "/srv", "/run", "/snap", "/Applications", "/private", "/private/var", "/Volumes", "/mnt", "/media"];
+    TREES
+        .iter()
+        .map(|dir| (resolve(Path::new(dir)), true))
+        .chain(ONLY.iter().map(|dir| (resolve(Path::new(dir)), false)))
+        .collect()
+}
+
+#[cfg(windows)]
+fn system_dirs() -> Vec<(PathBuf, bool)> {
+    const VARS: &[(&str, &str)] = &[
+        ("SystemRoot", r"C:\Windows"),
+        ("ProgramFiles", r"C:\Program Files"),
+        ("ProgramFiles(x86)", r"C:\Program Files (x86)"),
+        ("ProgramData", r"C:\ProgramData"),
+    ];
+    VARS.iter()
+        .map(|(var, default)| {
+            let dir = std::env::var_os(var).map(PathBuf::from).unwrap_or_else(|| PathBuf::from(default));
+            (resolve(&dir), true)
+        })
+        .chain([(resolve(Path::new(r"C:\Users")), false)])
+        .collect()
+}
+
+#[cfg(not(any(unix, windows)))]
+fn system_dirs() -> Vec<(PathBuf, bool)> {
+    Vec::new()
+}
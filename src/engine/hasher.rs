@@ -7,8 +7,9 @@ use tokio::{
 };
 use sha2::{Sha256, Digest};
 use blake3::Hasher as Blake3Inner;
+use xxhash_rust::xxh3::Xxh3;
 
-use crate::errors::Result;
+use crate::errors::{FileOrganizerError, Result};
 
 const BUFFER_SIZE: usize = 8192; // 8KB
 const BLOCKING_THRESHOLD: u64 = 50 * 1024 * 1024; // 50MB
@@ -102,16 +103,98 @@ impl FileHasher for Blake3Hasher {
     }
 }
 
+/// ---------------- XXH3 ----------------
+/// Fast but not cryptographic: fine for spotting duplicates on your own
+/// disk, not for proving a file was not tampered with
+pub struct Xxh3Hasher;
+
+#[async_trait]
+impl FileHasher for Xxh3Hasher {
+    async fn hash_file(&self, path: &Path) -> Result<Vec<u8>> {
+        hash_file_with(
+            path,
+            Xxh3::new,
+            |h, chunk| h.update(chunk),
+            |h| h.digest128().to_be_bytes().to_vec(),
+        )
+        .await
+    }
+}
+
 /// ---------------- Factory ----------------
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub enum HashAlgo {
     Sha256,
+    #[default]
     Blake3,
+    Xxh3,
+}
+
+impl HashAlgo {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Sha256 => "sha256",
+            Self::Blake3 => "blake3",
+            Self::Xxh3 => "xxh3",
+        }
+    }
+
+    /// How a digest is stored in the index: hex, prefixed with the algorithm
+    /// name (`sha256:9f86…`) unless it is BLAKE3, which indexes written
+    /// before the algorithm was configurable hold untagged
+    pub fn encode(self, digest: &[u8]) -> String {
+        match self {
+            Self::Blake3 => hex::encode(digest),
+            other => format!("{}:{}", other.name(), hex::encode(digest)),
+        }
+    }
+
+    /// The algorithm a stored hash was made with, `None` for an unknown tag
+    pub fn of(stored: &str) -> Option<Self> {
+        match stored.split_once(':') {
+            Some((name, _)) => name.parse().ok(),
+            None => Some(Self::Blake3),
+        }
+    }
+
+    /// Hash `path` and encode it for the index
+    pub async fn hash_file(self, path: &Path) -> Result<String> {
+        Ok(self.encode(&create_hasher(self).hash_file(path).await?))
+    }
+}
+
+impl std::fmt::Display for HashAlgo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl std::str::FromStr for HashAlgo {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "blake3" => Ok(Self::Blake3),
+            "sha256" | "sha-256" => Ok(Self::Sha256),
+            "xxh3" => Ok(Self::Xxh3),
+            other => Err(format!("unknown hash algorithm `{}` (blake3, sha256, xxh3)", other)),
+        }
+    }
+}
+
+/// Hash `path` with the algorithm `stored` was made with, encoded the same
+/// way, so the two compare equal when the content is unchanged
+pub async fn rehash_like(stored: &str, path: &Path) -> Result<String> {
+    let algo = HashAlgo::of(stored)
+        .ok_or_else(|| FileOrganizerError::Index(format!("unknown hash algorithm in `{}`", stored)))?;
+    algo.hash_file(path).await
 }
 
 pub fn create_hasher(algo: HashAlgo) -> Arc<dyn FileHasher> {
     match algo {
         HashAlgo::Sha256 => Arc::new(Sha256Hasher),
         HashAlgo::Blake3 => Arc::new(Blake3Hasher),
+        HashAlgo::Xxh3 => Arc::new(Xxh3Hasher),
     }
 }
 
@@ -0,0 +1,242 @@
+use std::{fmt, io::Write, path::PathBuf, time::Duration};
+
+use chrono::{Local, TimeZone};
+use colored::*;
+use serde::Serialize;
+
+use crate::{
+    errors::{FileOrganizerError, Result},
+    index::{Db, RunStats},
+    query::{FileState, IndexScope},
+    stats::{format_duration, format_size},
+};
+
+/// Files and bytes in one top-level category
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CategoryCount {
+    pub category: String,
+    pub files: u64,
+    pub bytes: u64,
+}
+
+/// Files moved by organize runs on one day
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DayCount {
+    /// Local date, `YYYY-MM-DD`
+    pub day: String,
+    pub moves: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ExtensionCount {
+    /// Lowercased, without the dot; empty for files without one
+    pub extension: String,
+    pub files: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LargeFile {
+    pub path: PathBuf,
+    pub dest: PathBuf,
+    pub size: u64,
+}
+
+/// Aggregate numbers over everything the index knows about
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct IndexStats {
+    pub categories: Vec<CategoryCount>,
+    pub moves_per_day: Vec<DayCount>,
+    pub top_extensions: Vec<ExtensionCount>,
+    pub largest: Vec<LargeFile>,
+    #[serde(skip)]
+    pub scope: IndexScope,
+}
+
+impl IndexStats {
+    /// Numbers over the organized files; `limit` caps the days, extensions
+    /// and files listed
+    pub async fn collect(db: &Db, limit: usize) -> Result<Self> {
+        Self::collect_scoped(db, limit, &IndexScope::organized()).await
+    }
+
+    /// Numbers over the files in `scope`, e.g. a tree recorded by `stash index`
+    pub async fn collect_scoped(db: &Db, limit: usize, scope: &IndexScope) -> Result<Self> {
+        Ok(Self {
+            categories: db
+                .category_totals(scope)
+                .await?
+                .into_iter()
+                .map(|(category, files, bytes)| CategoryCount { category, files, bytes })
+                .collect(),
+            moves_per_day: db
+                .moves_per_day(limit, scope)
+                .await?
+                .into_iter()
+                .map(|(day, moves)| DayCount { day, moves })
+                .collect(),
+            top_extensions: db
+                .top_extensions(limit, scope)
+                .await?
+                .into_iter()
+                .map(|(extension, files)| ExtensionCount { extension, files })
+                .collect(),
+            largest: db
+                .largest_files(limit, scope)
+                .await?
+                .into_iter()
+                .map(|e| LargeFile {
+                    path: scope.current_path(&e.path),
+                    dest: scope.current_path(&e.dest_path),
+                    size: e.size,
+                })
+                .collect(),
+            scope: scope.clone(),
+        })
+    }
+}
+
+impl fmt::Display for IndexStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let organized = self.scope.state == Some(FileState::Organized);
+        if self.categories.is_empty() && self.moves_per_day.is_empty() {
+            return writeln!(f, "{}", if organized { "Nothing organized yet." } else { "Nothing indexed yet." });
+        }
+        if let Some(scope) = self.scope.describe().filter(|_| self.scope != IndexScope::organized()) {
+            writeln!(f, "{} {}", "Scope:".bold().blue(), scope)?;
+        }
+
+        writeln!(f, "{}", "Categories:".bold().blue())?;
+        for c in &self.categories {
+            let name = if c.category.is_empty() { "(none)" } else { c.category.as_str() };
+            writeln!(f, "  {:<12} {:>7} files  {:>10}", name, c.files, format_size(c.bytes))?;
+        }
+
+        if self.scope.state != Some(FileState::Unorganized) {
+            writeln!(f, "{}", "Moves per day:".bold().blue())?;
+            for d in &self.moves_per_day {
+                writeln!(f, "  {}  {:>7}", d.day, d.moves)?;
+            }
+        }
+
+        writeln!(f, "{}", "Top extensions:".bold().blue())?;
+        for e in &self.top_extensions {
+            let name = if e.extension.is_empty() { "(none)" } else { e.extension.as_str() };
+            writeln!(f, "  {:<12} {:>7}", name, e.files)?;
+        }
+
+        writeln!(f, "{}", if organized { "Largest files moved:" } else { "Largest files:" }.bold().blue())?;
+        for l in &self.largest {
+            writeln!(f, "  {:>10}  {}", format_size(l.size), l.dest.display())?;
+        }
+        Ok(())
+    }
+}
+
+/// What the organize runs of one period did, summed
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct PeriodTotals {
+    /// Local month, `YYYY-MM`; empty for the all-time totals
+    pub period: String,
+    pub runs: u64,
+    pub processed: u64,
+    pub moved: u64,
+    pub skipped: u64,
+    pub duplicates: u64,
+    pub errors: u64,
+    pub bytes: u64,
+    /// Summed over the runs whose duration was recorded
+    pub duration_secs: f64,
+}
+
+impl PeriodTotals {
+    fn add(&mut self, run: &RunStats) {
+        self.runs += 1;
+        self.processed += run.processed;
+        self.moved += run.moved;
+        self.skipped += run.skipped;
+        self.duplicates += run.duplicates;
+        self.errors += run.errors;
+        self.bytes += run.bytes;
+        self.duration_secs += run.duration_ms.unwrap_or(0) as f64 / 1000.0;
+    }
+}
+
+/// Every recorded organize run, summed per month and overall
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct RunHistory {
+    /// Oldest first
+    pub months: Vec<PeriodTotals>,
+    pub total: PeriodTotals,
+    /// Runs recorded before their durations and skip counts were
+    pub untimed_runs: u64,
+}
+
+impl RunHistory {
+    pub async fn collect(db: &Db) -> Result<Self> {
+        Ok(Self::from_runs(&db.all_run_stats().await?))
+    }
+
+    /// Sum `runs`, which are oldest first
+    pub fn from_runs(runs: &[RunStats]) -> Self {
+        let mut history = Self::default();
+        for run in runs {
+            let period = Local
+                .timestamp_opt(run.finished_at, 0)
+                .single()
+                .map(|time| time.format("%Y-%m").to_string())
+                .unwrap_or_default();
+            if history.months.last().is_none_or(|month| month.period != period) {
+                history.months.push(PeriodTotals { period, ..Default::default() });
+            }
+            if let Some(month) = history.months.last_mut() {
+                month.add(run);
+            }
+            history.total.add(run);
+            if run.duration_ms.is_none() {
+                history.untimed_runs += 1;
+            }
+        }
+        history
+    }
+
+    /// One row per month, for charting in a spreadsheet
+    pub fn write_csv(&self, out: impl Write) -> Result<()> {
+        let mut writer = csv::Writer::from_writer(out);
+        for month in &self.months {
+            writer.serialize(month).map_err(|e| FileOrganizerError::Other(e.to_string()))?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+impl fmt::Display for RunHistory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.total.runs == 0 {
+            return writeln!(f, "No runs recorded yet.");
+        }
+        let t = &self.total;
+        writeln!(f, "{}", "All runs:".bold().blue())?;
+        writeln!(f, "  {:<12} {:>9}", "Runs", t.runs)?;
+        writeln!(f, "  {:<12} {:>9}", "Processed", t.processed)?;
+        writeln!(f, "  {:<12} {:>9}  {:>10}", "Moved", t.moved, format_size(t.bytes))?;
+        writeln!(f, "  {:<12} {:>9}", "Skipped", t.skipped)?;
+        writeln!(f, "  {:<12} {:>9}", "Duplicates", t.duplicates)?;
+        writeln!(f, "  {:<12} {:>9}", "Errors", t.errors)?;
+        writeln!(f, "  {:<12} {:>9}", "Time", format_duration(Duration::from_secs_f64(t.duration_secs)))?;
+        if self.untimed_runs > 0 {
+            writeln!(f, "  ({} older runs only recorded their moves)", self.untimed_runs)?;
+        }
+
+        writeln!(f, "{}", "Per month:".bold().blue())?;
+        writeln!(f, "  {:<8} {:>5} {:>9} {:>9} {:>10} {:>7}", "Month", "Runs", "Processed", "Moved", "Size", "Errors")?;
+        for m in &self.months {
+            writeln!(
+                f,
+                "  {:<8} {:>5} {:>9} {:>9} {:>10} {:>7}",
+                m.period, m.runs, m.processed, m.moved, format_size(m.bytes), m.errors
+            )?;
+        }
+        Ok(())
+    }
+}
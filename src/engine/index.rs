@@ -1,16 +1,29 @@
-use std::{path::{Path, PathBuf}, sync::Arc, time::SystemTime};
+use std::{collections::{HashMap, HashSet}, path::{Path, PathBuf}, sync::Arc, time::SystemTime};
 
 use chrono::{DateTime, Local};
-use sqlx::{sqlite::SqlitePoolOptions, Pool, Row, Sqlite, Transaction};
+use serde::{Deserialize, Serialize};
+use sqlx::{sqlite::SqlitePoolOptions, Pool, QueryBuilder, Row, Sqlite, Transaction};
 use tokio::{fs, sync::Semaphore};
 
-use crate::{errors::{FileOrganizerError, Result}, scanner::RawFileMetadata, utils::{from_unix, to_unix}};
+use crate::{
+    chunking::ChunkSignature,
+    errors::{FileOrganizerError, Result},
+    permissions::OriginalPermissions,
+    query::{day_start, FileQuery, FileState, IndexScope},
+    scanner::RawFileMetadata,
+    snapshot::{self, Snapshot, SnapshotEntry, SnapshotInfo},
+    utils::{from_unix, portable_db_path, to_unix},
+};
 
+/// Bind values for one `files` row: (path, size, created, modified, accessed, category, dest_path, hash)
+type RowBinds = (String, i64, Option<i64>, Option<i64>, Option<i64>, String, String, String);
 
 #[derive(Clone)]
 pub struct Db {
     pool: Pool<Sqlite>,
     write_limit: Arc<Semaphore>,
+    /// Root that stored paths are relative to (portable mode only)
+    base: Option<PathBuf>,
 }
 
 impl Db {
@@ -50,7 +63,7 @@ impl Db {
         } else {
             let abs = db_path
                 .canonicalize()
-                .map_err(|e| FileOrganizerError::Io(e))?;
+                .map_err(FileOrganizerError::Io)?;
             format!("sqlite:///{}", abs.display())
         };
         let pool = SqlitePoolOptions::new()
@@ -101,13 +114,181 @@ impl Db {
         )
         .execute(&pool)
         .await?;
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_files_hash ON files(hash);")
+            .execute(&pool)
+            .await?;
+
+        // Audited, revertible changes (dedupe hardlinks, trashing)
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS actions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                kind TEXT NOT NULL,
+                path TEXT NOT NULL,
+                target TEXT NOT NULL,
+                hash TEXT,
+                size INTEGER NOT NULL,
+                created_at INTEGER NOT NULL DEFAULT (strftime('%s','now')),
+                reverted_at INTEGER
+            );
+            "#,
+        )
+        .execute(&pool)
+        .await?;
+
+        // Mode and group organized files had before their category's
+        // permissions were applied, by original path; restored on revert
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS permissions (
+                path TEXT PRIMARY KEY,
+                mode INTEGER NOT NULL,
+                gid INTEGER NOT NULL
+            );
+            "#,
+        )
+        .execute(&pool)
+        .await?;
+
+        // Content-defined chunk signatures of large organized files (see
+        // `chunking`), by original path, for finding near-duplicates
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS chunk_signatures (
+                path TEXT PRIMARY KEY,
+                chunks BLOB NOT NULL
+            );
+            "#,
+        )
+        .execute(&pool)
+        .await?;
+
+        // Files the user never wants organized
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS ignored (
+                path TEXT PRIMARY KEY,
+                added_at INTEGER NOT NULL DEFAULT (strftime('%s','now'))
+            );
+            "#,
+        )
+        .execute(&pool)
+        .await?;
+
+        // Directory listings, stored as gzip-compressed JSON
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS snapshots (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                root TEXT NOT NULL,
+                created_at INTEGER NOT NULL,
+                file_count INTEGER NOT NULL,
+                data BLOB NOT NULL
+            );
+            "#,
+        )
+        .execute(&pool)
+        .await?;
+
+        // Organize runs and the moves each one made, for exporting undo bundles
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS runs (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                root TEXT NOT NULL,
+                dest_root TEXT NOT NULL,
+                created_at INTEGER NOT NULL,
+                file_count INTEGER NOT NULL
+            );
+            "#,
+        )
+        .execute(&pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS run_moves (
+                run_id INTEGER NOT NULL REFERENCES runs(id) ON DELETE CASCADE,
+                source TEXT NOT NULL,
+                dest TEXT NOT NULL,
+                hash TEXT,
+                size INTEGER NOT NULL
+            );
+            "#,
+        )
+        .execute(&pool)
+        .await?;
+
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_run_moves_run ON run_moves(run_id);")
+            .execute(&pool)
+            .await?;
+
+        // What each organize run did per root, moves or not, for `stash stats --all-runs`
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS run_stats (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                run_id INTEGER REFERENCES runs(id) ON DELETE SET NULL,
+                root TEXT NOT NULL,
+                finished_at INTEGER NOT NULL,
+                processed INTEGER NOT NULL,
+                moved INTEGER NOT NULL,
+                skipped INTEGER NOT NULL,
+                duplicates INTEGER NOT NULL,
+                errors INTEGER NOT NULL,
+                bytes INTEGER NOT NULL,
+                duration_ms INTEGER
+            );
+            "#,
+        )
+        .execute(&pool)
+        .await?;
 
         Ok(Self { 
             pool,
             write_limit: Arc::new(Semaphore::new(1)),
+            base: None,
         })
     }
 
+    /// Open the portable database stored inside `root`.
+    /// Paths under `root` are stored relative to it, so the index stays valid
+    /// when the drive is mounted elsewhere or plugged into another machine.
+    pub async fn open_portable(root: &Path) -> Result<Self> {
+        let mut db = Self::new(&portable_db_path(root)).await?;
+        db.base = Some(root.to_path_buf());
+        Ok(db)
+    }
+
+    /// Root that stored paths are relative to, if this is a portable database
+    pub fn base(&self) -> Option<&Path> {
+        self.base.as_deref()
+    }
+
+    /// Convert a path into its stored form (relative with `/` separators when portable)
+    fn encode_path(&self, path: &Path) -> String {
+        if let Some(rel) = self.base.as_ref().and_then(|b| path.strip_prefix(b).ok()) {
+            return rel
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+        }
+        path.to_string_lossy().to_string()
+    }
+
+    /// Convert a stored path back into an absolute path
+    fn decode_path(&self, stored: &str) -> PathBuf {
+        match &self.base {
+            Some(base) if Path::new(stored).is_relative() => {
+                let mut path = base.clone();
+                path.extend(stored.split('/').filter(|c| !c.is_empty()));
+                path
+            }
+            _ => PathBuf::from(stored),
+        }
+    }
+
     /// Begin a transaction
     pub async fn begin(&self) -> Result<Transaction<'_, Sqlite>> {
         Ok(self.pool.begin().await?)
@@ -151,16 +332,7 @@ impl Db {
             let mut sql = String::from(
                 "INSERT INTO files (path, size, created, modified, accessed, category, dest_path, hash, updated_at) VALUES ",
             );
-            let mut binds: Vec<(
-                String,
-                i64,
-                Option<i64>,
-                Option<i64>,
-                Option<i64>,
-                String,
-                String,
-                String,
-            )> = Vec::with_capacity(chunk.len());
+            let mut binds: Vec<RowBinds> = Vec::with_capacity(chunk.len());
 
             for (i, (meta, category, dest, hash)) in chunk.iter().enumerate() {
                 if i > 0 {
@@ -173,13 +345,13 @@ impl Db {
                 let accessed = to_unix(meta.accessed);
 
                 binds.push((
-                    meta.path.to_string_lossy().to_string(),
+                    self.encode_path(&meta.path),
                     meta.size as i64,
                     created,
                     modified,
                     accessed,
                     category.clone(),
-                    dest.to_string_lossy().to_string(),
+                    self.encode_path(dest),
                     hash.clone(),
                 ));
             }
@@ -223,7 +395,7 @@ impl Db {
         let row = sqlx::query(
             "SELECT size, created, modified, accessed FROM files WHERE path = ?",
         )
-        .bind(path.to_string_lossy().to_string())
+        .bind(self.encode_path(path))
         .fetch_optional(&self.pool)
         .await?;
 
@@ -245,6 +417,7 @@ impl Db {
                 is_file: ft.is_file(),
                 is_dir: ft.is_dir(),
                 is_symlink: ft.is_symlink(),
+                owner: crate::scanner::file_owner(&fs_meta),
             }))
         } else {
             Ok(None)
@@ -253,22 +426,61 @@ impl Db {
 
 
     /// Convert a sqlx::Row into a DbFileEntry
-    fn row_to_entry(row: &sqlx::sqlite::SqliteRow) -> Result<DbFileEntry> {
+    fn row_to_entry(&self, row: &sqlx::sqlite::SqliteRow) -> Result<DbFileEntry> {
         use sqlx::Row;
         let modified: Option<i64> = row.try_get("modified")?;
         let path: String = row.try_get("path")?;
         let dest_path: String = row.try_get("dest_path")?;
 
         Ok(DbFileEntry {
-            path: PathBuf::from(path),
+            path: self.decode_path(&path),
             size: row.try_get::<i64, _>("size")? as u64,
             modified: from_unix(modified),
             hash: row.try_get("hash")?,
             category: row.try_get("category")?,
-            dest_path: PathBuf::from(dest_path),
+            dest_path: self.decode_path(&dest_path),
         })
     }
 
+    /// Every entry with when it was last written (Unix seconds), oldest first
+    pub async fn files_updated_at(&self) -> Result<Vec<(DbFileEntry, i64)>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT path, size, modified, hash, category, dest_path, updated_at
+            FROM files
+            ORDER BY updated_at
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter()
+            .map(|r| Ok((self.row_to_entry(r)?, r.try_get("updated_at")?)))
+            .collect()
+    }
+
+    /// Drop the entries for `paths` (original paths) with their chunk
+    /// signatures and saved permissions, all in one transaction
+    pub async fn forget_files(&self, paths: &[&Path]) -> Result<()> {
+        if paths.is_empty() {
+            return Ok(());
+        }
+        let _permit = self.acquire_write_permit().await?;
+        let mut tx = self.pool.begin().await?;
+        for path in paths {
+            let encoded = self.encode_path(path);
+            for sql in [
+                "DELETE FROM files WHERE path = ?1",
+                "DELETE FROM chunk_signatures WHERE path = ?1",
+                "DELETE FROM permissions WHERE path = ?1",
+            ] {
+                sqlx::query(sql).bind(&encoded).execute(&mut *tx).await?;
+            }
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
     /// Lookup single file entry by original path
     pub async fn lookup_full(&self, path: &Path) -> Result<Option<DbFileEntry>> {
         let row = sqlx::query(
@@ -278,11 +490,135 @@ impl Db {
             WHERE path = ?
             "#,
         )
-        .bind(path.to_string_lossy().to_string())
+        .bind(self.encode_path(path))
         .fetch_optional(&self.pool)
         .await?;
 
-        Ok(row.map(|r| Self::row_to_entry(&r)).transpose()?)
+        row.map(|r| self.row_to_entry(&r)).transpose()
+    }
+
+    /// Organized files whose content has `hash`, newest first, other than
+    /// the one at `path`; used to spot copies of files organized before
+    pub async fn find_by_hash(&self, hash: &str, path: &Path) -> Result<Vec<DbFileEntry>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT path, size, modified, hash, category, dest_path
+            FROM files
+            WHERE hash = ?1 AND path != ?2 AND dest_path != path
+            ORDER BY updated_at DESC
+            "#,
+        )
+        .bind(hash)
+        .bind(self.encode_path(path))
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(|r| self.row_to_entry(r)).collect()
+    }
+
+    /// Current locations of organized files (rows whose file was moved)
+    pub async fn organized_paths(&self) -> Result<HashSet<PathBuf>> {
+        let rows: Vec<(String,)> = sqlx::query_as("SELECT dest_path FROM files WHERE dest_path != path")
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(rows.into_iter().map(|(dest,)| self.decode_path(&dest)).collect())
+    }
+
+    /// Files classified as `category`, or under it when it is a top-level
+    /// category (`Documents` also finds `Documents::Pdf`), ignoring case
+    pub async fn find_by_category(&self, category: &str) -> Result<Vec<DbFileEntry>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT path, size, modified, hash, category, dest_path
+            FROM files
+            WHERE category = ?1 COLLATE NOCASE OR category LIKE ?2 ESCAPE '\'
+            ORDER BY path
+            "#,
+        )
+        .bind(category)
+        .bind(format!("{}::%", escape_like(category)))
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(|r| self.row_to_entry(r)).collect()
+    }
+
+    /// Files whose original or organized path matches `pattern`: a glob
+    /// (`*.pdf`, `*/2024/invoice-??.pdf`) matched against the whole path as
+    /// stored, or without wildcards any part of it. ASCII letters match
+    /// either case.
+    pub async fn search(&self, pattern: &str) -> Result<Vec<DbFileEntry>> {
+        let like = path_pattern(pattern);
+        let rows = sqlx::query(
+            r#"
+            SELECT path, size, modified, hash, category, dest_path
+            FROM files
+            WHERE path LIKE ?1 ESCAPE '\' OR dest_path LIKE ?1 ESCAPE '\'
+            ORDER BY path
+            "#,
+        )
+        .bind(like)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(|r| self.row_to_entry(r)).collect()
+    }
+
+    /// Files matching every filter of `query`, by original path, or largest
+    /// first when it has a limit
+    pub async fn query_files(&self, query: &FileQuery) -> Result<Vec<DbFileEntry>> {
+        let mut sql = QueryBuilder::<Sqlite>::new(
+            "SELECT path, size, modified, hash, category, dest_path FROM files WHERE 1 = 1",
+        );
+        if let Some(category) = &query.category {
+            sql.push(" AND (category = ")
+                .push_bind(category.clone())
+                .push(" COLLATE NOCASE OR category LIKE ")
+                .push_bind(format!("{}::%", escape_like(category)))
+                .push(" ESCAPE '\\')");
+        }
+        if !query.extensions.is_empty() {
+            sql.push(" AND (");
+            let mut any = sql.separated(" OR ");
+            for ext in &query.extensions {
+                any.push("path LIKE ")
+                    .push_bind_unseparated(format!("%.{}", escape_like(ext.trim_start_matches('.'))))
+                    .push_unseparated(" ESCAPE '\\'");
+            }
+            sql.push(")");
+        }
+        if let Some(pattern) = &query.name {
+            let like = path_pattern(pattern);
+            sql.push(" AND (path LIKE ")
+                .push_bind(like.clone())
+                .push(" ESCAPE '\\' OR dest_path LIKE ")
+                .push_bind(like)
+                .push(" ESCAPE '\\')");
+        }
+        if let Some(min) = query.min_size {
+            sql.push(" AND size >= ").push_bind(min as i64);
+        }
+        if let Some(max) = query.max_size {
+            sql.push(" AND size <= ").push_bind(max as i64);
+        }
+        if let Some(since) = query.since {
+            sql.push(" AND modified >= ").push_bind(day_start(since));
+        }
+        if let Some(until) = query.until.and_then(|day| day.succ_opt()) {
+            sql.push(" AND modified < ").push_bind(day_start(until));
+        }
+        self.push_scope(&mut sql, &query.scope);
+        match query.limit {
+            Some(limit) => {
+                sql.push(" ORDER BY size DESC, path LIMIT ").push_bind(limit as i64);
+            }
+            None => {
+                sql.push(" ORDER BY path");
+            }
+        }
+
+        let rows = sql.build().fetch_all(&self.pool).await?;
+        rows.iter().map(|r| self.row_to_entry(r)).collect()
     }
 
     /// Get all file entries
@@ -298,7 +634,7 @@ impl Db {
         .await?;
 
         rows.iter()
-            .map(|r| Self::row_to_entry(r))
+            .map(|r| self.row_to_entry(r))
             .collect::<Result<Vec<_>>>()
     }
 
@@ -306,28 +642,37 @@ impl Db {
     pub async fn update_file_entry(&self, entry: &DbFileEntry) -> Result<()> {
         let _permit = self.acquire_write_permit().await?;
         
-        sqlx::query(
-            r#"
-            INSERT INTO files (path, size, modified, category, dest_path, hash, updated_at)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, strftime('%s','now'))
-            ON CONFLICT(path) DO UPDATE SET
-                size=excluded.size,
-                modified=excluded.modified,
-                category=excluded.category,
-                dest_path=excluded.dest_path,
-                hash=excluded.hash,
-                updated_at=strftime('%s','now');
-            "#
-        )
-        .bind(entry.path.to_string_lossy().to_string())
-        .bind(entry.size as i64)
-        .bind(to_unix(entry.modified))
-        .bind(&entry.category)
-        .bind(entry.dest_path.to_string_lossy().to_string())
-        .bind(&entry.hash)
-        .execute(&self.pool)
-        .await?;
+        sqlx::query(UPSERT_FILE_ENTRY)
+            .bind(self.encode_path(&entry.path))
+            .bind(entry.size as i64)
+            .bind(to_unix(entry.modified))
+            .bind(&entry.category)
+            .bind(self.encode_path(&entry.dest_path))
+            .bind(&entry.hash)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Insert or replace many file entries in one transaction
+    pub async fn upsert_file_entries(&self, entries: &[DbFileEntry]) -> Result<()> {
+        let _permit = self.acquire_write_permit().await?;
+        let mut tx = self.pool.begin().await?;
+
+        for entry in entries {
+            sqlx::query(UPSERT_FILE_ENTRY)
+                .bind(self.encode_path(&entry.path))
+                .bind(entry.size as i64)
+                .bind(to_unix(entry.modified))
+                .bind(&entry.category)
+                .bind(self.encode_path(&entry.dest_path))
+                .bind(&entry.hash)
+                .execute(&mut *tx)
+                .await?;
+        }
 
+        tx.commit().await?;
         Ok(())
     }
 
@@ -346,14 +691,638 @@ impl Db {
             WHERE path = ?2
             "#
         )
-        .bind(new_dest.to_string_lossy().to_string())
-        .bind(path.to_string_lossy().to_string())
+        .bind(self.encode_path(new_dest))
+        .bind(self.encode_path(path))
         .execute(&mut **tx)
         .await?;
 
         Ok(())
     }
 
+    /// Point entries organized to `old_dest` at `new_dest`, e.g. when a file
+    /// comes back from the trash under a new name. Returns how many changed.
+    pub async fn replace_dest_path(&self, old_dest: &Path, new_dest: &Path) -> Result<u64> {
+        let _permit = self.acquire_write_permit().await?;
+        let result = sqlx::query("UPDATE files SET dest_path = ?1, updated_at = strftime('%s','now') WHERE dest_path = ?2")
+            .bind(self.encode_path(new_dest))
+            .bind(self.encode_path(old_dest))
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Hash stored for `path` if its size and mtime still match the indexed row
+    pub async fn cached_hash(
+        &self,
+        path: &Path,
+        size: u64,
+        modified: Option<SystemTime>,
+    ) -> Result<Option<String>> {
+        let Some(modified) = to_unix(modified) else {
+            return Ok(None);
+        };
+
+        let hash: Option<(Option<String>,)> = sqlx::query_as(
+            "SELECT hash FROM files WHERE path = ?1 AND size = ?2 AND modified = ?3"
+        )
+        .bind(self.encode_path(path))
+        .bind(size as i64)
+        .bind(modified)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(hash.and_then(|(h,)| h).filter(|h| !h.is_empty()))
+    }
+
+    /// Record an action on `path`; `target` is what undoing it needs
+    /// (the kept file for a hardlink, the trash location for a trashed file)
+    pub async fn record_action(
+        &self,
+        kind: &str,
+        path: &Path,
+        target: &Path,
+        hash: Option<&str>,
+        size: u64,
+    ) -> Result<i64> {
+        let _permit = self.acquire_write_permit().await?;
+        let result = sqlx::query(
+            "INSERT INTO actions (kind, path, target, hash, size) VALUES (?1, ?2, ?3, ?4, ?5)"
+        )
+        .bind(kind)
+        .bind(self.encode_path(path))
+        .bind(self.encode_path(target))
+        .bind(hash)
+        .bind(size as i64)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.last_insert_rowid())
+    }
+
+    /// Recorded actions, oldest first. Journal entries are not included.
+    pub async fn list_actions(&self, include_reverted: bool) -> Result<Vec<ActionRecord>> {
+        let sql = if include_reverted {
+            "SELECT * FROM actions WHERE kind NOT IN (?1, ?2) ORDER BY id"
+        } else {
+            "SELECT * FROM actions WHERE kind NOT IN (?1, ?2) AND reverted_at IS NULL ORDER BY id"
+        };
+        let rows = sqlx::query(sql)
+            .bind(REVERT_JOURNAL)
+            .bind(ORGANIZE_JOURNAL)
+            .fetch_all(&self.pool)
+            .await?;
+        self.action_records(&rows)
+    }
+
+    fn action_records(&self, rows: &[sqlx::sqlite::SqliteRow]) -> Result<Vec<ActionRecord>> {
+        rows.iter()
+            .map(|row| {
+                Ok(ActionRecord {
+                    id: row.try_get("id")?,
+                    kind: row.try_get("kind")?,
+                    path: self.decode_path(&row.try_get::<String, _>("path")?),
+                    target: self.decode_path(&row.try_get::<String, _>("target")?),
+                    hash: row.try_get("hash")?,
+                    size: row.try_get::<i64, _>("size")? as u64,
+                    created_at: row.try_get("created_at")?,
+                    reverted_at: row.try_get("reverted_at")?,
+                })
+            })
+            .collect()
+    }
+
+    pub async fn mark_action_reverted(&self, id: i64) -> Result<()> {
+        let _permit = self.acquire_write_permit().await?;
+        sqlx::query("UPDATE actions SET reverted_at = strftime('%s','now') WHERE id = ?1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Forget recorded actions whose files are gone for good (e.g. purged
+    /// from the trash), all in one transaction
+    pub async fn delete_actions(&self, ids: &[i64]) -> Result<()> {
+        if ids.is_empty() {
+            return Ok(());
+        }
+        let _permit = self.acquire_write_permit().await?;
+        let mut tx = self.pool.begin().await?;
+        for id in ids {
+            sqlx::query("DELETE FROM actions WHERE id = ?1")
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Journal revert moves that are about to happen, in one transaction:
+    /// `path` is the indexed file, `target` where it is being moved back to,
+    /// which is a new name when its original one was taken. The entries stay
+    /// in `actions` as the record of what each file was restored as.
+    /// Returns the journal ids in the same order.
+    pub async fn journal_reverts(&self, moves: &[(&Path, &Path, u64)]) -> Result<Vec<i64>> {
+        let _permit = self.acquire_write_permit().await?;
+        let mut tx = self.pool.begin().await?;
+        let mut ids = Vec::with_capacity(moves.len());
+        for (path, target, size) in moves {
+            let result = sqlx::query(
+                "INSERT INTO actions (kind, path, target, size) VALUES (?1, ?2, ?3, ?4)"
+            )
+            .bind(REVERT_JOURNAL)
+            .bind(self.encode_path(path))
+            .bind(self.encode_path(target))
+            .bind(*size as i64)
+            .execute(&mut *tx)
+            .await?;
+            ids.push(result.last_insert_rowid());
+        }
+        tx.commit().await?;
+        Ok(ids)
+    }
+
+    /// Point each journaled file at its new location and close its journal
+    /// entry, all in one transaction
+    pub async fn commit_reverts(&self, done: &[(i64, &Path, &Path)]) -> Result<()> {
+        if done.is_empty() {
+            return Ok(());
+        }
+        let _permit = self.acquire_write_permit().await?;
+        let mut tx = self.pool.begin().await?;
+        for (id, path, new_dest) in done {
+            self.update_dest_path_tx(&mut tx, path, new_dest).await?;
+            sqlx::query("UPDATE actions SET reverted_at = strftime('%s','now') WHERE id = ?1")
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Every path a finished revert moved a file back to
+    pub async fn revert_targets(&self) -> Result<HashSet<PathBuf>> {
+        let rows: Vec<(String,)> = sqlx::query_as("SELECT target FROM actions WHERE kind = ?1 AND reverted_at IS NOT NULL")
+            .bind(REVERT_JOURNAL)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(rows.iter().map(|(target,)| self.decode_path(target)).collect())
+    }
+
+    /// Revert moves journaled but never committed, e.g. after a crash
+    pub async fn pending_reverts(&self) -> Result<Vec<ActionRecord>> {
+        let rows = sqlx::query("SELECT * FROM actions WHERE kind = ?1 AND reverted_at IS NULL ORDER BY id")
+            .bind(REVERT_JOURNAL)
+            .fetch_all(&self.pool)
+            .await?;
+        self.action_records(&rows)
+    }
+
+    /// Journal an organize move that is about to happen: `path` is the file
+    /// being organized, `target` where it is going. Returns the journal id.
+    pub async fn journal_move(&self, path: &Path, target: &Path, hash: &str, size: u64) -> Result<i64> {
+        self.record_action(ORGANIZE_JOURNAL, path, target, Some(hash), size).await
+    }
+
+    /// Drop organize journal entries once their files are indexed (or were
+    /// never moved), all in one transaction
+    pub async fn clear_journal(&self, ids: &[i64]) -> Result<()> {
+        if ids.is_empty() {
+            return Ok(());
+        }
+        let _permit = self.acquire_write_permit().await?;
+        let mut tx = self.pool.begin().await?;
+        for id in ids {
+            sqlx::query("DELETE FROM actions WHERE id = ?1 AND kind = ?2")
+                .bind(id)
+                .bind(ORGANIZE_JOURNAL)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Organize moves journaled but never indexed, e.g. after a crash
+    pub async fn pending_moves(&self) -> Result<Vec<ActionRecord>> {
+        let rows = sqlx::query("SELECT * FROM actions WHERE kind = ?1 ORDER BY id")
+            .bind(ORGANIZE_JOURNAL)
+            .fetch_all(&self.pool)
+            .await?;
+        self.action_records(&rows)
+    }
+
+    /// Remember the permissions the file from `path` had before its category's were applied
+    pub async fn record_permissions(&self, path: &Path, original: &OriginalPermissions) -> Result<()> {
+        let _permit = self.acquire_write_permit().await?;
+        sqlx::query("INSERT OR REPLACE INTO permissions (path, mode, gid) VALUES (?1, ?2, ?3)")
+            .bind(self.encode_path(path))
+            .bind(original.mode as i64)
+            .bind(original.gid as i64)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Recorded original permissions by original path
+    pub async fn original_permissions(&self) -> Result<HashMap<PathBuf, OriginalPermissions>> {
+        let rows: Vec<(String, i64, i64)> = sqlx::query_as("SELECT path, mode, gid FROM permissions")
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(rows
+            .into_iter()
+            .map(|(path, mode, gid)| {
+                (self.decode_path(&path), OriginalPermissions { mode: mode as u32, gid: gid as u32 })
+            })
+            .collect())
+    }
+
+    /// Drop the recorded permissions of files that were restored
+    pub async fn forget_permissions(&self, paths: &[&Path]) -> Result<()> {
+        if paths.is_empty() {
+            return Ok(());
+        }
+        let _permit = self.acquire_write_permit().await?;
+        let mut tx = self.pool.begin().await?;
+        for path in paths {
+            sqlx::query("DELETE FROM permissions WHERE path = ?1")
+                .bind(self.encode_path(path))
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Store the chunk signature of the file organized from `path`
+    pub async fn record_chunk_signature(&self, path: &Path, signature: &ChunkSignature) -> Result<()> {
+        let _permit = self.acquire_write_permit().await?;
+        sqlx::query("INSERT OR REPLACE INTO chunk_signatures (path, chunks) VALUES (?1, ?2)")
+            .bind(self.encode_path(path))
+            .bind(signature.to_bytes())
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Current location and chunk signature of every indexed file that has one
+    pub async fn chunk_signatures(&self) -> Result<Vec<(PathBuf, ChunkSignature)>> {
+        let rows: Vec<(String, Vec<u8>)> = sqlx::query_as(
+            "SELECT files.dest_path, chunk_signatures.chunks FROM chunk_signatures JOIN files ON files.path = chunk_signatures.path ORDER BY files.dest_path",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows
+            .into_iter()
+            .filter_map(|(dest, chunks)| Some((self.decode_path(&dest), ChunkSignature::from_bytes(&chunks)?)))
+            .collect())
+    }
+
+    /// Never organize `path` again. Returns false if it was already ignored.
+    pub async fn add_ignored(&self, path: &Path) -> Result<bool> {
+        let _permit = self.acquire_write_permit().await?;
+        let result = sqlx::query("INSERT OR IGNORE INTO ignored (path) VALUES (?1)")
+            .bind(self.encode_path(path))
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Allow `path` to be organized again. Returns false if it was not ignored.
+    pub async fn remove_ignored(&self, path: &Path) -> Result<bool> {
+        let _permit = self.acquire_write_permit().await?;
+        let result = sqlx::query("DELETE FROM ignored WHERE path = ?1")
+            .bind(self.encode_path(path))
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Ignored paths with the unix time they were added, sorted by path
+    pub async fn list_ignored(&self) -> Result<Vec<(PathBuf, i64)>> {
+        let rows = sqlx::query("SELECT path, added_at FROM ignored ORDER BY path")
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.iter()
+            .map(|row| Ok((self.decode_path(&row.try_get::<String, _>("path")?), row.try_get("added_at")?)))
+            .collect()
+    }
+
+    /// Ignored paths as a set, for filtering scans
+    pub async fn ignored_paths(&self) -> Result<HashSet<PathBuf>> {
+        Ok(self.list_ignored().await?.into_iter().map(|(path, _)| path).collect())
+    }
+
+    /// Store a listing of `root` and return the new snapshot id
+    pub async fn insert_snapshot(&self, root: &Path, entries: &[SnapshotEntry]) -> Result<i64> {
+        let data = snapshot::compress(entries)?;
+        let _permit = self.acquire_write_permit().await?;
+
+        let result = sqlx::query(
+            "INSERT INTO snapshots (root, created_at, file_count, data) VALUES (?1, ?2, ?3, ?4)"
+        )
+        .bind(self.encode_path(root))
+        .bind(snapshot::now_unix())
+        .bind(entries.len() as i64)
+        .bind(data)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.last_insert_rowid())
+    }
+
+    /// Load a snapshot with its full listing
+    pub async fn get_snapshot(&self, id: i64) -> Result<Option<Snapshot>> {
+        let row = sqlx::query("SELECT id, root, created_at, data FROM snapshots WHERE id = ?1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+
+        let data: Vec<u8> = row.try_get("data")?;
+        Ok(Some(Snapshot {
+            id: row.try_get("id")?,
+            root: self.decode_path(&row.try_get::<String, _>("root")?),
+            created_at: row.try_get("created_at")?,
+            entries: snapshot::decompress(&data)?,
+        }))
+    }
+
+    /// All snapshots, oldest first, without their listings
+    pub async fn list_snapshots(&self) -> Result<Vec<SnapshotInfo>> {
+        let rows = sqlx::query("SELECT id, root, created_at, file_count FROM snapshots ORDER BY id")
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(SnapshotInfo {
+                    id: row.try_get("id")?,
+                    root: self.decode_path(&row.try_get::<String, _>("root")?),
+                    created_at: row.try_get("created_at")?,
+                    file_count: row.try_get::<i64, _>("file_count")? as usize,
+                })
+            })
+            .collect()
+    }
+
+    /// Record the files moved by one organize run and return the new run id
+    pub async fn record_run(&self, root: &Path, dest_root: &Path, moves: &[RunMove]) -> Result<i64> {
+        let _permit = self.acquire_write_permit().await?;
+        let mut tx = self.pool.begin().await?;
+
+        let run_id = sqlx::query(
+            "INSERT INTO runs (root, dest_root, created_at, file_count) VALUES (?1, ?2, ?3, ?4)"
+        )
+        .bind(self.encode_path(root))
+        .bind(self.encode_path(dest_root))
+        .bind(snapshot::now_unix())
+        .bind(moves.len() as i64)
+        .execute(&mut *tx)
+        .await?
+        .last_insert_rowid();
+
+        for mv in moves {
+            sqlx::query(
+                "INSERT INTO run_moves (run_id, source, dest, hash, size) VALUES (?1, ?2, ?3, ?4, ?5)"
+            )
+            .bind(run_id)
+            .bind(self.encode_path(&mv.source))
+            .bind(self.encode_path(&mv.dest))
+            .bind(mv.hash.as_deref())
+            .bind(mv.size as i64)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(run_id)
+    }
+
+    /// All recorded runs, oldest first
+    pub async fn list_runs(&self) -> Result<Vec<RunInfo>> {
+        let rows = sqlx::query("SELECT * FROM runs ORDER BY id")
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.iter().map(|row| self.run_info(row)).collect()
+    }
+
+    pub async fn get_run(&self, id: i64) -> Result<Option<RunInfo>> {
+        let row = sqlx::query("SELECT * FROM runs WHERE id = ?1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        row.map(|row| self.run_info(&row)).transpose()
+    }
+
+    /// Files moved by run `id`, in the order they were recorded
+    pub async fn run_moves(&self, id: i64) -> Result<Vec<RunMove>> {
+        let rows = sqlx::query("SELECT source, dest, hash, size FROM run_moves WHERE run_id = ?1 ORDER BY rowid")
+            .bind(id)
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(RunMove {
+                    source: self.decode_path(&row.try_get::<String, _>("source")?),
+                    dest: self.decode_path(&row.try_get::<String, _>("dest")?),
+                    hash: row.try_get("hash")?,
+                    size: row.try_get::<i64, _>("size")? as u64,
+                })
+            })
+            .collect()
+    }
+
+    /// Whether an organize run over `root` was ever recorded
+    pub async fn has_run_for(&self, root: &Path) -> Result<bool> {
+        let root = self.encode_path(root);
+        let row = sqlx::query(
+            "SELECT EXISTS (SELECT 1 FROM runs WHERE root = ?1) OR EXISTS (SELECT 1 FROM run_stats WHERE root = ?1)"
+        )
+        .bind(root)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(row.try_get::<bool, _>(0)?)
+    }
+
+    /// Record the totals of one organize run over one root
+    pub async fn record_run_stats(&self, stats: &RunStats) -> Result<()> {
+        let _permit = self.acquire_write_permit().await?;
+        sqlx::query(
+            r#"
+            INSERT INTO run_stats (run_id, root, finished_at, processed, moved, skipped, duplicates, errors, bytes, duration_ms)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+            "#,
+        )
+        .bind(stats.run_id)
+        .bind(self.encode_path(&stats.root))
+        .bind(stats.finished_at)
+        .bind(stats.processed as i64)
+        .bind(stats.moved as i64)
+        .bind(stats.skipped as i64)
+        .bind(stats.duplicates as i64)
+        .bind(stats.errors as i64)
+        .bind(stats.bytes as i64)
+        .bind(stats.duration_ms.map(|ms| ms as i64))
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Totals of every recorded run, oldest first. Runs recorded before
+    /// their totals were count only their moves, with no duration.
+    pub async fn all_run_stats(&self) -> Result<Vec<RunStats>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT run_id, root, finished_at, processed, moved, skipped, duplicates, errors, bytes, duration_ms
+            FROM run_stats
+            UNION ALL
+            SELECT runs.id, runs.root, runs.created_at, runs.file_count, runs.file_count, 0, 0, 0,
+                COALESCE((SELECT SUM(size) FROM run_moves WHERE run_moves.run_id = runs.id), 0), NULL
+            FROM runs
+            WHERE NOT EXISTS (SELECT 1 FROM run_stats WHERE run_stats.run_id = runs.id)
+            ORDER BY finished_at
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(RunStats {
+                    run_id: row.try_get("run_id")?,
+                    root: self.decode_path(&row.try_get::<String, _>("root")?),
+                    finished_at: row.try_get("finished_at")?,
+                    processed: row.try_get::<i64, _>("processed")? as u64,
+                    moved: row.try_get::<i64, _>("moved")? as u64,
+                    skipped: row.try_get::<i64, _>("skipped")? as u64,
+                    duplicates: row.try_get::<i64, _>("duplicates")? as u64,
+                    errors: row.try_get::<i64, _>("errors")? as u64,
+                    bytes: row.try_get::<i64, _>("bytes")? as u64,
+                    duration_ms: row.try_get::<Option<i64>, _>("duration_ms")?.map(|ms| ms as u64),
+                })
+            })
+            .collect()
+    }
+
+    fn run_info(&self, row: &sqlx::sqlite::SqliteRow) -> Result<RunInfo> {
+        Ok(RunInfo {
+            id: row.try_get("id")?,
+            root: self.decode_path(&row.try_get::<String, _>("root")?),
+            dest_root: self.decode_path(&row.try_get::<String, _>("dest_root")?),
+            created_at: row.try_get("created_at")?,
+            file_count: row.try_get::<i64, _>("file_count")? as usize,
+        })
+    }
+
+    /// Restrict a `files` query built on `WHERE ...` to `scope`
+    fn push_scope(&self, sql: &mut QueryBuilder<'_, Sqlite>, scope: &IndexScope) {
+        match scope.state {
+            Some(FileState::Organized) => sql.push(" AND dest_path != path"),
+            Some(FileState::Unorganized) => sql.push(" AND dest_path = path"),
+            None => sql,
+        };
+        if let Some(dir) = scope.recorded_under() {
+            self.push_under(sql, "dest_path", &dir);
+        }
+    }
+
+    /// Restrict a query to rows whose `column` is `dir` or a path below it
+    fn push_under(&self, sql: &mut QueryBuilder<'_, Sqlite>, column: &str, dir: &Path) {
+        let stored = self.encode_path(dir);
+        let stored = stored.trim_end_matches(['/', std::path::MAIN_SEPARATOR]);
+        // The portable root or `/`: everything is below it
+        if stored.is_empty() {
+            return;
+        }
+        let separator = if Path::new(stored).is_relative() { '/' } else { std::path::MAIN_SEPARATOR };
+        sql.push(format!(" AND ({} = ", column))
+            .push_bind(stored.to_string())
+            .push(format!(" OR {} LIKE ", column))
+            .push_bind(format!("{}{}%", escape_like(stored), separator))
+            .push(" ESCAPE '\\')");
+    }
+
+    /// Files in `scope` per top-level category: (category, files, bytes),
+    /// most files first
+    pub async fn category_totals(&self, scope: &IndexScope) -> Result<Vec<(String, u64, u64)>> {
+        let mut sql = QueryBuilder::<Sqlite>::new(
+            r#"
+            SELECT CASE WHEN instr(category, '::') > 0
+                        THEN substr(category, 1, instr(category, '::') - 1)
+                        ELSE category END AS top,
+                   COUNT(*), COALESCE(SUM(size), 0)
+            FROM files
+            WHERE 1 = 1"#,
+        );
+        self.push_scope(&mut sql, scope);
+        sql.push(" GROUP BY top ORDER BY COUNT(*) DESC, top");
+        let rows: Vec<(String, i64, i64)> = sql.build_query_as().fetch_all(&self.pool).await?;
+
+        Ok(rows.into_iter().map(|(c, n, b)| (c, n as u64, b as u64)).collect())
+    }
+
+    /// Files moved by recorded runs per local calendar day, newest first.
+    /// Only the scope's directory applies; unorganized files were never moved.
+    pub async fn moves_per_day(&self, days: usize, scope: &IndexScope) -> Result<Vec<(String, u64)>> {
+        if scope.state == Some(FileState::Unorganized) {
+            return Ok(Vec::new());
+        }
+        let mut sql = QueryBuilder::<Sqlite>::new(
+            r#"
+            SELECT date(runs.created_at, 'unixepoch', 'localtime') AS day, COUNT(*)
+            FROM run_moves JOIN runs ON runs.id = run_moves.run_id
+            WHERE 1 = 1"#,
+        );
+        if let Some(dir) = scope.recorded_under() {
+            self.push_under(&mut sql, "run_moves.dest", &dir);
+        }
+        sql.push(" GROUP BY day ORDER BY day DESC LIMIT ").push_bind(days as i64);
+        let rows: Vec<(String, i64)> = sql.build_query_as().fetch_all(&self.pool).await?;
+
+        Ok(rows.into_iter().map(|(d, n)| (d, n as u64)).collect())
+    }
+
+    /// Most common extensions (lowercased) among files in `scope`
+    pub async fn top_extensions(&self, limit: usize, scope: &IndexScope) -> Result<Vec<(String, u64)>> {
+        let mut sql = QueryBuilder::<Sqlite>::new("SELECT path FROM files WHERE 1 = 1");
+        self.push_scope(&mut sql, scope);
+        let paths: Vec<(String,)> = sql.build_query_as().fetch_all(&self.pool).await?;
+
+        let mut counts: HashMap<String, u64> = HashMap::new();
+        for (path,) in paths {
+            let ext = Path::new(&path)
+                .extension()
+                .map(|e| e.to_string_lossy().to_lowercase())
+                .unwrap_or_default();
+            *counts.entry(ext).or_default() += 1;
+        }
+
+        let mut counts: Vec<_> = counts.into_iter().collect();
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        counts.truncate(limit);
+        Ok(counts)
+    }
+
+    /// Largest files in `scope`, biggest first
+    pub async fn largest_files(&self, limit: usize, scope: &IndexScope) -> Result<Vec<DbFileEntry>> {
+        let mut sql = QueryBuilder::<Sqlite>::new("SELECT * FROM files WHERE 1 = 1");
+        self.push_scope(&mut sql, scope);
+        sql.push(" ORDER BY size DESC, path LIMIT ").push_bind(limit as i64);
+        let rows = sql.build().fetch_all(&self.pool).await?;
+
+        rows.iter().map(|row| self.row_to_entry(row)).collect()
+    }
+
     /// Print database information (file path, size, counts).
     pub async fn status(db_path: &Path) -> Result<()> {
         if !fs::try_exists(db_path).await? {
@@ -380,12 +1349,23 @@ impl Db {
             .fetch_one(&db.pool)
             .await
             .unwrap_or((0,));
+        let snapshots_count: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM snapshots;")
+            .fetch_one(&db.pool)
+            .await
+            .unwrap_or((0,));
+
+        let runs_count: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM runs;")
+            .fetch_one(&db.pool)
+            .await
+            .unwrap_or((0,));
 
         println!("📂 Database path : {:?}", db_path);
         println!("📏 File size     : {:.2} KB", size_kb);
         println!("🕒 Last modified   : {}", modified_str);
         println!("📊 Files tracked : {}", files_count.0);
         println!("📊 Actions saved : {}", actions_count.0);
+        println!("📸 Snapshots     : {}", snapshots_count.0);
+        println!("📝 Runs recorded : {}", runs_count.0);
 
         Ok(())
     }
@@ -409,6 +1389,33 @@ impl Db {
     }
 }
 
+/// `LIKE` pattern for a [`Db::search`] pattern: a glob over the whole path,
+/// or a substring without wildcards
+fn path_pattern(pattern: &str) -> String {
+    if pattern.contains(['*', '?']) {
+        escape_like(pattern).replace('*', "%").replace('?', "_")
+    } else {
+        format!("%{}%", escape_like(pattern))
+    }
+}
+
+/// `text` with the `LIKE` wildcards and the `\` escape taken literally
+fn escape_like(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+}
+
+const UPSERT_FILE_ENTRY: &str = r#"
+    INSERT INTO files (path, size, modified, category, dest_path, hash, updated_at)
+    VALUES (?1, ?2, ?3, ?4, ?5, ?6, strftime('%s','now'))
+    ON CONFLICT(path) DO UPDATE SET
+        size=excluded.size,
+        modified=excluded.modified,
+        category=excluded.category,
+        dest_path=excluded.dest_path,
+        hash=excluded.hash,
+        updated_at=strftime('%s','now');
+"#;
+
 #[derive(Debug, Clone, Default)]
 pub struct DbFileEntry {
     pub path: PathBuf,
@@ -417,4 +1424,61 @@ pub struct DbFileEntry {
     pub hash: Option<String>,
     pub category: Option<String>,
     pub dest_path: PathBuf,
-}
\ No newline at end of file
+}
+
+/// `actions` kind for journaled revert moves; see [`Db::journal_reverts`]
+pub const REVERT_JOURNAL: &str = "revert";
+
+/// `actions` kind for organize moves not yet indexed; see [`Db::journal_move`]
+pub const ORGANIZE_JOURNAL: &str = "organize";
+
+/// One audited change recorded in the `actions` table
+#[derive(Debug, Clone)]
+pub struct ActionRecord {
+    pub id: i64,
+    pub kind: String,
+    pub path: PathBuf,
+    pub target: PathBuf,
+    pub hash: Option<String>,
+    pub size: u64,
+    pub created_at: i64,
+    pub reverted_at: Option<i64>,
+}
+
+/// An organize run that moved at least one file
+#[derive(Debug, Clone)]
+pub struct RunInfo {
+    pub id: i64,
+    pub root: PathBuf,
+    pub dest_root: PathBuf,
+    pub created_at: i64,
+    pub file_count: usize,
+}
+
+/// What one organize run did over one root
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RunStats {
+    /// The run's entry in `runs`, when it moved anything
+    pub run_id: Option<i64>,
+    pub root: PathBuf,
+    pub finished_at: i64,
+    pub processed: u64,
+    /// Moved or renamed
+    pub moved: u64,
+    pub skipped: u64,
+    pub duplicates: u64,
+    pub errors: u64,
+    /// Bytes moved or renamed
+    pub bytes: u64,
+    /// Unknown for runs recorded before their totals were
+    pub duration_ms: Option<u64>,
+}
+
+/// One file moved during a run: undoing it moves `dest` back to `source`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunMove {
+    pub source: PathBuf,
+    pub dest: PathBuf,
+    pub hash: Option<String>,
+    pub size: u64,
+}
@@ -0,0 +1,195 @@
+//! Upsert throughput of the index, for `stash db bench` and the criterion
+//! benchmarks in `benches/`. The same synthetic rows are written with each
+//! strategy [`Db`] has, into a fresh WAL database per measurement, once as
+//! new rows and once more as updates of them.
+
+use std::{
+    fmt,
+    fs::Permissions,
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime},
+};
+
+use colored::*;
+
+use crate::{
+    errors::Result,
+    index::{Db, DbFileEntry},
+    scanner::RawFileMetadata,
+};
+
+/// Row counts measured when none are given
+pub const DEFAULT_SIZES: [usize; 3] = [1_000, 10_000, 100_000];
+
+/// How rows are written to the `files` table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpsertStrategy {
+    /// Multi-row `INSERT … VALUES (…), (…)` statements of up to 100 rows,
+    /// one transaction each ([`Db::update_files_batch`])
+    ChunkedValues,
+    /// One prepared statement run per row in a single transaction
+    /// ([`Db::upsert_file_entries`])
+    Prepared,
+}
+
+impl UpsertStrategy {
+    pub const ALL: [UpsertStrategy; 2] = [Self::ChunkedValues, Self::Prepared];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::ChunkedValues => "chunked-values",
+            Self::Prepared => "prepared",
+        }
+    }
+}
+
+impl fmt::Display for UpsertStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Synthetic rows, built up front so only the writes are timed
+pub struct SampleRows {
+    batch: Vec<(RawFileMetadata, String, PathBuf, String)>,
+    entries: Vec<DbFileEntry>,
+}
+
+impl SampleRows {
+    /// `count` rows spread over a few categories, with paths and hashes
+    /// shaped like real ones
+    pub fn new(count: usize, permissions: Permissions) -> Self {
+        const CATEGORIES: [&str; 4] = ["Documents/Pdf", "Images/Photos", "Audio/Music", "Code/Rust"];
+        let modified = Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000));
+
+        let mut batch = Vec::with_capacity(count);
+        let mut entries = Vec::with_capacity(count);
+        for i in 0..count {
+            let category = CATEGORIES[i % CATEGORIES.len()];
+            let path = PathBuf::from(format!("/home/user/Downloads/dir{:03}/file{:07}.bin", i % 1000, i));
+            let dest = PathBuf::from(format!("/home/user/Downloads/Organized/{}/file{:07}.bin", category, i));
+            let hash = blake3::hash(&i.to_le_bytes()).to_hex().to_string();
+            let size = (i as u64 % 10_000) * 1024;
+
+            entries.push(DbFileEntry {
+                path: path.clone(),
+                size,
+                modified,
+                hash: Some(hash.clone()),
+                category: Some(category.to_string()),
+                dest_path: dest.clone(),
+            });
+            let raw = RawFileMetadata {
+                path,
+                size,
+                created: modified,
+                modified,
+                accessed: modified,
+                permissions: permissions.clone(),
+                is_file: true,
+                is_dir: false,
+                is_symlink: false,
+                owner: None,
+            };
+            batch.push((raw, category.to_string(), dest, hash));
+        }
+        Self { batch, entries }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Write every row to `db` with `strategy`
+    pub async fn upsert(&self, db: &Db, strategy: UpsertStrategy) -> Result<()> {
+        match strategy {
+            UpsertStrategy::ChunkedValues => db.update_files_batch(&self.batch).await,
+            UpsertStrategy::Prepared => db.upsert_file_entries(&self.entries).await,
+        }
+    }
+}
+
+/// One strategy at one row count
+#[derive(Debug, Clone)]
+pub struct BenchResult {
+    pub strategy: UpsertStrategy,
+    pub rows: usize,
+    /// Writing the rows into an empty table
+    pub insert: Duration,
+    /// Writing the same rows again, so every one hits `ON CONFLICT`
+    pub update: Duration,
+}
+
+impl BenchResult {
+    pub fn insert_rate(&self) -> f64 {
+        rate(self.rows, self.insert)
+    }
+
+    pub fn update_rate(&self) -> f64 {
+        rate(self.rows, self.update)
+    }
+}
+
+fn rate(rows: usize, elapsed: Duration) -> f64 {
+    rows as f64 / elapsed.as_secs_f64().max(f64::EPSILON)
+}
+
+#[derive(Debug, Default)]
+pub struct BenchReport {
+    pub results: Vec<BenchResult>,
+}
+
+impl fmt::Display for BenchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", "Index upsert throughput (WAL)".bold().blue())?;
+        writeln!(
+            f,
+            "  {:>8}  {:<15} {:>10} {:>12} {:>10} {:>12}",
+            "rows", "strategy", "insert", "rows/s", "update", "rows/s"
+        )?;
+        for r in &self.results {
+            writeln!(
+                f,
+                "  {:>8}  {:<15} {:>10} {:>12.0} {:>10} {:>12.0}",
+                r.rows,
+                r.strategy.name(),
+                format!("{:.1?}", r.insert),
+                r.insert_rate(),
+                format!("{:.1?}", r.update),
+                r.update_rate(),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Time every strategy at each of `sizes`, with the databases created in a
+/// temporary directory under `dir` (so on the disk being measured)
+pub async fn run(dir: &Path, sizes: &[usize]) -> Result<BenchReport> {
+    let scratch = tempfile::tempdir_in(dir)?;
+    let permissions = std::fs::metadata(scratch.path())?.permissions();
+    let mut report = BenchReport::default();
+
+    for &rows in sizes {
+        let sample = SampleRows::new(rows, permissions.clone());
+        for strategy in UpsertStrategy::ALL {
+            let db = Db::new(&scratch.path().join(format!("{}-{}.db", strategy, rows))).await?;
+
+            let start = Instant::now();
+            sample.upsert(&db, strategy).await?;
+            let insert = start.elapsed();
+
+            let start = Instant::now();
+            sample.upsert(&db, strategy).await?;
+            let update = start.elapsed();
+
+            tracing::debug!(target: "bench", %strategy, rows, ?insert, ?update, "Measured upserts");
+            report.results.push(BenchResult { strategy, rows, insert, update });
+        }
+    }
+    Ok(report)
+}
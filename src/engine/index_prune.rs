@@ -0,0 +1,153 @@
+//! Keeping the index from growing forever. An entry is forgotten once its
+//! file is gone from both its destination and its original path, so there is
+//! nothing left to revert, and it was last updated longer ago than the
+//! retention age. Entries whose destination is missing are orphans; they are
+//! reported whatever their age. A file only counts as missing when the
+//! directory it was in is still there: without it, the file may be on a
+//! drive that isn't mounted, and the entry is left alone.
+
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use chrono::{DateTime, Local};
+use colored::*;
+
+use crate::{errors::Result, index::Db, snapshot::now_unix};
+
+/// `stash db prune` without `--older-than` or an `index_retention`: 180 days
+pub const DEFAULT_INDEX_RETENTION: Duration = Duration::from_secs(180 * 24 * 60 * 60);
+
+/// Orphans listed by name before summarizing the rest
+const MAX_ORPHANS_SHOWN: usize = 10;
+
+/// An index entry whose destination is missing on disk
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Orphan {
+    pub path: PathBuf,
+    pub dest_path: PathBuf,
+    /// When the entry was last written, Unix seconds
+    pub updated_at: i64,
+}
+
+/// What a prune did, or would do
+#[derive(Debug, Default)]
+pub struct PruneReport {
+    pub dry_run: bool,
+    /// Entries looked at
+    pub checked: usize,
+    /// Original paths of the entries forgotten
+    pub pruned: Vec<PathBuf>,
+    /// Orphans that were kept
+    pub orphans: Vec<Orphan>,
+    /// Entries left alone because a directory they need is missing, e.g.
+    /// on a volume that isn't mounted
+    pub unreachable: usize,
+}
+
+/// What is at a recorded path
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Presence {
+    Present,
+    /// Nothing there, though the directory it was in is
+    Missing,
+    /// The directory it was in is missing too
+    Unreachable,
+}
+
+/// Forget entries older than `older_than` whose file is gone. With
+/// `orphans`, every entry whose destination is missing goes too, whatever
+/// its age.
+pub async fn prune_index(db: &Db, older_than: Duration, orphans: bool, dry_run: bool) -> Result<PruneReport> {
+    let cutoff = now_unix().saturating_sub(older_than.as_secs() as i64);
+    let mut report = PruneReport { dry_run, ..Default::default() };
+
+    for (entry, updated_at) in db.files_updated_at().await? {
+        report.checked += 1;
+        match presence(&entry.dest_path).await {
+            Presence::Present => continue,
+            Presence::Unreachable => {
+                report.unreachable += 1;
+                continue;
+            }
+            Presence::Missing => {}
+        }
+        let original = presence(&entry.path).await;
+        if original == Presence::Unreachable {
+            report.unreachable += 1;
+            continue;
+        }
+        let gone = original == Presence::Missing;
+        if orphans || (gone && updated_at < cutoff) {
+            report.pruned.push(entry.path);
+        } else {
+            report.orphans.push(Orphan { path: entry.path, dest_path: entry.dest_path, updated_at });
+        }
+    }
+
+    if !dry_run {
+        let paths: Vec<&Path> = report.pruned.iter().map(PathBuf::as_path).collect();
+        db.forget_files(&paths).await?;
+        tracing::info!(
+            target: "index",
+            "Pruned {} of {} index entries ({} orphans kept)",
+            report.pruned.len(),
+            report.checked,
+            report.orphans.len()
+        );
+    }
+    Ok(report)
+}
+
+/// Whether anything is at `path`, a dangling symlink included
+async fn exists(path: &Path) -> bool {
+    tokio::fs::symlink_metadata(path).await.is_ok()
+}
+
+async fn presence(path: &Path) -> Presence {
+    if exists(path).await {
+        Presence::Present
+    } else if path.parent().is_some_and(|dir| dir.is_dir()) {
+        Presence::Missing
+    } else {
+        Presence::Unreachable
+    }
+}
+
+impl fmt::Display for PruneReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let verb = if self.dry_run { "would be forgotten" } else { "forgotten" };
+        writeln!(
+            f,
+            "🧹 Index pruned: {} of {} entries {}, {} orphans kept.",
+            self.pruned.len(),
+            self.checked,
+            verb,
+            self.orphans.len()
+        )?;
+        if self.unreachable > 0 {
+            writeln!(
+                f,
+                "{} entries left alone: their folder is missing, maybe on a drive that isn't mounted.",
+                self.unreachable.to_string().yellow()
+            )?;
+        }
+        if self.orphans.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(f, "{} entries point at a destination that is missing:", self.orphans.len().to_string().yellow())?;
+        for orphan in self.orphans.iter().take(MAX_ORPHANS_SHOWN) {
+            let indexed = DateTime::from_timestamp(orphan.updated_at, 0)
+                .map(|t| t.with_timezone(&Local).format("%Y-%m-%d").to_string())
+                .unwrap_or_default();
+            writeln!(f, "  {} -> {} (indexed {})", orphan.path.display(), orphan.dest_path.display(), indexed)?;
+        }
+        if self.orphans.len() > MAX_ORPHANS_SHOWN {
+            writeln!(f, "  … and {} more", self.orphans.len() - MAX_ORPHANS_SHOWN)?;
+        }
+        writeln!(f, "Use --orphans to forget them too.")
+    }
+}
@@ -0,0 +1,228 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    io::{Read, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use colored::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    errors::{FileOrganizerError, Result},
+    index::{Db, DbFileEntry},
+    utils::{from_unix, to_unix},
+};
+
+/// File format of an index export
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexFormat {
+    Json,
+    Csv,
+}
+
+impl IndexFormat {
+    /// Guess from a file extension, defaulting to JSON
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("csv") => Self::Csv,
+            _ => Self::Json,
+        }
+    }
+}
+
+impl FromStr for IndexFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "json" => Ok(Self::Json),
+            "csv" => Ok(Self::Csv),
+            other => Err(format!("unknown index format `{}` (json, csv)", other)),
+        }
+    }
+}
+
+/// One row of the `files` table, with absolute paths and unix-second mtimes
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexRecord {
+    pub path: PathBuf,
+    pub dest_path: PathBuf,
+    pub size: u64,
+    pub modified: Option<i64>,
+    pub category: Option<String>,
+    pub hash: Option<String>,
+}
+
+impl From<DbFileEntry> for IndexRecord {
+    fn from(e: DbFileEntry) -> Self {
+        Self {
+            path: e.path,
+            dest_path: e.dest_path,
+            size: e.size,
+            modified: to_unix(e.modified),
+            category: e.category,
+            hash: e.hash,
+        }
+    }
+}
+
+impl From<IndexRecord> for DbFileEntry {
+    fn from(r: IndexRecord) -> Self {
+        Self {
+            path: r.path,
+            dest_path: r.dest_path,
+            size: r.size,
+            modified: from_unix(r.modified),
+            category: r.category.filter(|c| !c.is_empty()),
+            hash: r.hash.filter(|h| !h.is_empty()),
+        }
+    }
+}
+
+/// Write every indexed file to `out`, sorted by path. Returns how many were written.
+pub async fn export(db: &Db, format: IndexFormat, out: impl Write) -> Result<usize> {
+    let mut records: Vec<IndexRecord> = db.get_all_files().await?.into_iter().map(Into::into).collect();
+    records.sort_by(|a, b| a.path.cmp(&b.path));
+    write_records(&records, format, out)?;
+    Ok(records.len())
+}
+
+pub fn write_records(records: &[IndexRecord], format: IndexFormat, mut out: impl Write) -> Result<()> {
+    match format {
+        IndexFormat::Json => {
+            serde_json::to_writer_pretty(&mut out, records).map_err(|e| FileOrganizerError::Other(e.to_string()))?;
+            writeln!(out)?;
+        }
+        IndexFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(out);
+            for record in records {
+                writer.serialize(record).map_err(|e| FileOrganizerError::Other(e.to_string()))?;
+            }
+            writer.flush()?;
+        }
+    }
+    Ok(())
+}
+
+pub fn read_records(format: IndexFormat, input: impl Read) -> Result<Vec<IndexRecord>> {
+    match format {
+        IndexFormat::Json => serde_json::from_reader(input).map_err(|e| FileOrganizerError::Other(e.to_string())),
+        IndexFormat::Csv => csv::Reader::from_reader(input)
+            .deserialize()
+            .enumerate()
+            .map(|(idx, record)| {
+                record.map_err(|e| FileOrganizerError::Other(format!("row {}: {}", idx + 1, e)))
+            })
+            .collect(),
+    }
+}
+
+/// What merging an export into the index did
+#[derive(Debug, Default)]
+pub struct ImportReport {
+    /// Paths that were not indexed yet
+    pub added: usize,
+    /// Identical to what is already indexed
+    pub unchanged: usize,
+    /// Conflicting entries replaced (`overwrite` only)
+    pub replaced: usize,
+    /// Paths indexed with a different destination, size or hash; left as they are
+    pub conflicts: Vec<PathBuf>,
+    /// (record number, why it was rejected)
+    pub invalid: Vec<(usize, String)>,
+}
+
+impl fmt::Display for ImportReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", "Index import".bold().blue())?;
+        writeln!(f, "  Added:     {}", self.added.to_string().green())?;
+        writeln!(f, "  Unchanged: {}", self.unchanged)?;
+        writeln!(f, "  Replaced:  {}", self.replaced)?;
+        writeln!(f, "  Conflicts: {}", self.conflicts.len().to_string().yellow())?;
+        writeln!(f, "  Invalid:   {}", self.invalid.len().to_string().red())?;
+        for path in &self.conflicts {
+            writeln!(f, "    conflict: {}", path.display())?;
+        }
+        for (n, reason) in &self.invalid {
+            writeln!(f, "    record {}: {}", n, reason)?;
+        }
+        if !self.conflicts.is_empty() {
+            writeln!(f, "Use --overwrite to replace conflicting entries.")?;
+        }
+        Ok(())
+    }
+}
+
+/// Merge `records` into the index. An already indexed `path` with different
+/// data is a conflict: kept as is unless `overwrite`.
+pub async fn import(db: &Db, records: Vec<IndexRecord>, overwrite: bool) -> Result<ImportReport> {
+    let existing: HashMap<PathBuf, DbFileEntry> = db
+        .get_all_files()
+        .await?
+        .into_iter()
+        .map(|e| (e.path.clone(), e))
+        .collect();
+
+    let mut report = ImportReport::default();
+    let mut seen = HashSet::new();
+    let mut accepted = Vec::new();
+
+    for (idx, record) in records.into_iter().enumerate() {
+        if let Err(reason) = validate(&record) {
+            report.invalid.push((idx + 1, reason));
+            continue;
+        }
+        if !seen.insert(record.path.clone()) {
+            report.invalid.push((idx + 1, format!("duplicate path {:?}", record.path)));
+            continue;
+        }
+
+        let entry = DbFileEntry::from(record);
+        match existing.get(&entry.path) {
+            None => report.added += 1,
+            Some(current) if same_entry(current, &entry) => {
+                report.unchanged += 1;
+                continue;
+            }
+            Some(_) if overwrite => report.replaced += 1,
+            Some(_) => {
+                report.conflicts.push(entry.path);
+                continue;
+            }
+        }
+        accepted.push(entry);
+    }
+
+    db.upsert_file_entries(&accepted).await?;
+    tracing::info!(
+        target: "index_transfer",
+        "Imported {} entries ({} conflicts, {} invalid)",
+        accepted.len(),
+        report.conflicts.len(),
+        report.invalid.len()
+    );
+    Ok(report)
+}
+
+fn validate(record: &IndexRecord) -> std::result::Result<(), String> {
+    for (name, path) in [("path", &record.path), ("dest_path", &record.dest_path)] {
+        if path.as_os_str().is_empty() {
+            return Err(format!("empty {}", name));
+        }
+        if !path.is_absolute() {
+            return Err(format!("{} {:?} is not absolute", name, path));
+        }
+    }
+    if let Some(hash) = record.hash.as_deref()
+        && !hash.chars().all(|c| c.is_ascii_hexdigit())
+    {
+        return Err(format!("hash {:?} is not hex", hash));
+    }
+    Ok(())
+}
+
+fn same_entry(a: &DbFileEntry, b: &DbFileEntry) -> bool {
+    a.dest_path == b.dest_path && a.size == b.size && a.hash == b.hash
+}
@@ -0,0 +1,200 @@
+//! `stash index`: record a whole tree (size, timestamps, hash, category)
+//! without moving anything, so organize, dedupe and query work from the
+//! index instead of reading terabytes again. Files are recorded where they
+//! are, as rows whose destination is their own path.
+//!
+//! Runs resume: a file already indexed with the same size and modification
+//! time (and a hash of the chosen algorithm) is not hashed again, and rows
+//! are written in batches, so an interrupted run loses little.
+
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use colored::*;
+use futures::stream::{self, StreamExt};
+use tokio::sync::mpsc;
+
+use crate::{
+    cancel::CancelToken,
+    errors::{FileOrganizerError, Result},
+    exclusions::{ExcludeGlobs, Exclusions},
+    hasher::HashAlgo,
+    index::Db,
+    progress::Progress,
+    registry::ClassifierRegistry,
+    scanner::{RawFileMetadata, ScanConfig, Scanner},
+    stats::format_size,
+    throttle::Throttle,
+};
+
+/// Rows written to the index at a time
+pub const INDEX_BATCH: usize = 500;
+
+/// Files hashed at the same time
+const CONCURRENCY: usize = 4;
+
+/// Scanned files waiting to be hashed
+const QUEUE: usize = 1024;
+
+/// How to index a tree
+#[derive(Debug, Clone, Default)]
+pub struct IndexOptions {
+    pub hash_algo: HashAlgo,
+    /// Cap on bytes per second read for hashing
+    pub max_throughput: Option<u64>,
+    /// Paths below the root left out, on top of stash's own files
+    pub exclude: ExcludeGlobs,
+    pub progress: Progress,
+    /// Stops the run; files hashed so far are still written
+    pub cancel: CancelToken,
+}
+
+/// What [`index_tree`] did
+#[derive(Debug, Default)]
+pub struct IndexReport {
+    /// Files hashed and recorded
+    pub indexed: usize,
+    pub bytes: u64,
+    /// Files already in the index and unchanged, or already organized
+    pub unchanged: usize,
+    pub errors: Vec<(PathBuf, FileOrganizerError)>,
+    /// Stopped before the whole tree was seen
+    pub cancelled: bool,
+}
+
+impl fmt::Display for IndexReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", "Index".bold().blue())?;
+        writeln!(f, "  Indexed:    {} files, {}", self.indexed.to_string().green(), format_size(self.bytes))?;
+        writeln!(f, "  Unchanged:  {} files", self.unchanged)?;
+        writeln!(f, "  Errors:     {} files", self.errors.len().to_string().red())?;
+        for (path, error) in &self.errors {
+            writeln!(f, "    {}: {}", path.display(), error)?;
+        }
+        if self.cancelled {
+            writeln!(f, "  {}", "Stopped early; run it again to carry on".yellow())?;
+        }
+        Ok(())
+    }
+}
+
+enum Indexed {
+    Unchanged,
+    Row(RawFileMetadata, String, String),
+    Failed(PathBuf, FileOrganizerError),
+}
+
+/// Record every file under `root` in `db`, hashing only what changed since
+/// the last run
+pub async fn index_tree(db: &Db, registry: &ClassifierRegistry, root: &Path, options: &IndexOptions) -> Result<IndexReport> {
+    let organized = db.organized_paths().await?;
+    let throttle = options.max_throughput.map(|rate| Arc::new(Throttle::new(rate)));
+    let config = ScanConfig {
+        exclude: Exclusions::new(root).globs(options.exclude.clone()),
+        cancel: options.cancel.clone(),
+        ..Default::default()
+    };
+
+    // The walk blocks, so it runs on its own thread and waits when hashing
+    // falls behind
+    let (tx, rx) = mpsc::channel(QUEUE);
+    let scan_root = root.to_path_buf();
+    let scan = tokio::task::spawn_blocking(move || {
+        for result in Scanner::new(&scan_root, config) {
+            let item = match result {
+                Ok(raw) if raw.is_file => Ok(raw),
+                Ok(_) | Err(FileOrganizerError::Skipped { .. }) => continue,
+                Err(e) => Err(e),
+            };
+            if tx.blocking_send(item).is_err() {
+                break;
+            }
+        }
+    });
+
+    options.progress.start("Indexing", 0);
+    let files = stream::unfold(rx, |mut rx| async move { rx.recv().await.map(|item| (item, rx)) });
+    let results = files
+        .map(|item| {
+            let (organized, throttle) = (&organized, throttle.clone());
+            async move {
+                let raw = match item {
+                    Ok(raw) => raw,
+                    Err(e) => return Indexed::Failed(root.to_path_buf(), e),
+                };
+                if organized.contains(&raw.path) {
+                    return Indexed::Unchanged;
+                }
+                index_file(db, registry, raw, options.hash_algo, throttle.as_deref()).await
+            }
+        })
+        .buffer_unordered(CONCURRENCY);
+    tokio::pin!(results);
+
+    let mut report = IndexReport::default();
+    let mut batch = Vec::with_capacity(INDEX_BATCH);
+    while let Some(result) = results.next().await {
+        match result {
+            Indexed::Unchanged => report.unchanged += 1,
+            Indexed::Row(raw, category, hash) => {
+                report.indexed += 1;
+                report.bytes += raw.size;
+                let dest = raw.path.clone();
+                batch.push((raw, category, dest, hash));
+                if batch.len() == INDEX_BATCH {
+                    db.update_files_batch(&batch).await?;
+                    batch.clear();
+                }
+            }
+            Indexed::Failed(path, e) => report.errors.push((path, e)),
+        }
+        options.progress.inc(1);
+    }
+    db.update_files_batch(&batch).await?;
+    scan.await?;
+    options.progress.finish();
+
+    report.cancelled = options.cancel.is_cancelled();
+    tracing::info!(
+        target: "indexing",
+        "Indexed {:?}: {} files hashed ({} bytes), {} unchanged, {} errors",
+        root,
+        report.indexed,
+        report.bytes,
+        report.unchanged,
+        report.errors.len()
+    );
+    Ok(report)
+}
+
+async fn index_file(
+    db: &Db,
+    registry: &ClassifierRegistry,
+    raw: RawFileMetadata,
+    algo: HashAlgo,
+    throttle: Option<&Throttle>,
+) -> Indexed {
+    match db.cached_hash(&raw.path, raw.size, raw.modified).await {
+        Ok(Some(hash)) if HashAlgo::of(&hash) == Some(algo) => return Indexed::Unchanged,
+        Ok(_) => {}
+        Err(e) => return Indexed::Failed(raw.path, e),
+    }
+    if let Some(throttle) = throttle {
+        throttle.take(raw.size).await;
+    }
+    let hash = match algo.hash_file(&raw.path).await {
+        Ok(hash) => hash,
+        Err(e) => return Indexed::Failed(raw.path, e),
+    };
+    let category = match registry.classify(&raw).await {
+        Ok(classified) => classified.category.to_string(),
+        Err(e) => {
+            tracing::debug!(target: "indexing", "Could not classify {:?}: {}", raw.path, e);
+            String::new()
+        }
+    };
+    Indexed::Row(raw, category, hash)
+}
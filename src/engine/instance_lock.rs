@@ -0,0 +1,136 @@
+//! Keeps two stash runs off the same directories, where they would race on
+//! the same files. Organize locks each root and its destination; revert
+//! locks the directories it moves files out of and back into. Each lock is
+//! an advisory lock on a file in the lock directory (`<index directory>/locks`
+//! unless given), one per directory, so it goes away with the process
+//! however that ends. The file is removed again when the run finishes.
+
+use std::{
+    fs::{File, OpenOptions, TryLockError},
+    io::Write,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use crate::{
+    errors::{FileOrganizerError, Result},
+    utils::default_db_path,
+};
+
+/// How often a run waiting for a lock tries again
+const LOCK_POLL: Duration = Duration::from_millis(250);
+
+/// Locks held on directories until dropped
+#[derive(Debug)]
+pub struct InstanceLock {
+    /// Each lock file and the open handle holding its lock
+    files: Vec<(PathBuf, File)>,
+}
+
+impl InstanceLock {
+    /// Lock `dirs` for this run with lock files in `lock_dir`. When another
+    /// run holds one of them, fail with [`FileOrganizerError::AlreadyRunning`],
+    /// or with `wait` wait for it to finish.
+    pub async fn acquire<'a>(lock_dir: &Path, dirs: impl IntoIterator<Item = &'a Path>, wait: bool) -> Result<Self> {
+        tokio::fs::create_dir_all(lock_dir).await?;
+
+        // Always in the same order, so two waiting runs can't deadlock
+        let mut dirs: Vec<PathBuf> = dirs.into_iter().map(normalize).collect();
+        dirs.sort();
+        dirs.dedup();
+
+        let mut files = Vec::with_capacity(dirs.len());
+        for dir in dirs {
+            let path = lock_path(lock_dir, &dir);
+            let file = lock(&path, &dir, wait).await?;
+            files.push((path, file));
+        }
+        Ok(Self { files })
+    }
+}
+
+impl Drop for InstanceLock {
+    fn drop(&mut self) {
+        // Removed while still locked: a run that opened it meanwhile finds it
+        // gone once it gets the lock, and starts over on a new file
+        for (path, file) in self.files.drain(..) {
+            let _ = std::fs::remove_file(&path);
+            drop(file);
+        }
+    }
+}
+
+/// `<index directory>/locks`, where lock files go unless told otherwise
+pub async fn default_lock_dir() -> Result<PathBuf> {
+    let db_path = default_db_path().await?;
+    Ok(db_path.parent().unwrap_or(&db_path).join("locks"))
+}
+
+/// The lock file for `dir`, named after a hash of its path
+fn lock_path(lock_dir: &Path, dir: &Path) -> PathBuf {
+    let hash = blake3::hash(dir.as_os_str().as_encoded_bytes()).to_hex();
+    lock_dir.join(format!("{}.lock", &hash[..16]))
+}
+
+async fn lock(path: &Path, dir: &Path, wait: bool) -> Result<File> {
+    let mut waiting = false;
+    let mut file = loop {
+        let file = OpenOptions::new().read(true).write(true).create(true).truncate(false).open(path)?;
+        match file.try_lock() {
+            Ok(()) if is_current(&file, path) => break file,
+            // Locked after its holder removed it; the next run uses a new one
+            Ok(()) => continue,
+            Err(TryLockError::WouldBlock) if wait => {
+                if !waiting {
+                    tracing::warn!(target: "lock", "Waiting for another stash run on {:?} to finish", dir);
+                    waiting = true;
+                }
+                tokio::time::sleep(LOCK_POLL).await;
+            }
+            Err(TryLockError::WouldBlock) => {
+                return Err(FileOrganizerError::AlreadyRunning { dir: dir.to_path_buf(), pid: holder(path) });
+            }
+            Err(TryLockError::Error(e)) => return Err(e.into()),
+        }
+    };
+    // Tells a run turned away who holds the lock
+    file.set_len(0)?;
+    writeln!(file, "{}\n{}", std::process::id(), dir.display())?;
+    Ok(file)
+}
+
+/// Whether `file` is still the one at `path`, not one removed since it was opened
+#[cfg(unix)]
+fn is_current(file: &File, path: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    match (file.metadata(), std::fs::metadata(path)) {
+        (Ok(open), Ok(named)) => open.dev() == named.dev() && open.ino() == named.ino(),
+        _ => false,
+    }
+}
+
+/// Whether `file` is still the one at `path`. Windows can't open a removed
+/// file that is still held, so one that exists is the same.
+#[cfg(not(unix))]
+fn is_current(_file: &File, path: &Path) -> bool {
+    path.exists()
+}
+
+/// The process id the holder of the lock at `path` wrote into it. Windows
+/// locks keep others from reading it.
+fn holder(path: &Path) -> Option<u32> {
+    std::fs::read_to_string(path).ok()?.lines().next()?.trim().parse().ok()
+}
+
+/// `dir` as an absolute path with symlinks resolved as far as it exists, so
+/// each directory has one lock however it is named
+fn normalize(dir: &Path) -> PathBuf {
+    let absolute = std::path::absolute(dir).unwrap_or_else(|_| dir.to_path_buf());
+    for ancestor in absolute.ancestors() {
+        if let Ok(real) = ancestor.canonicalize() {
+            return real.join(absolute.strip_prefix(ancestor).unwrap_or(Path::new("")));
+        }
+    }
+    absolute
+}
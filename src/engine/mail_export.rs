@@ -0,0 +1,239 @@
+//! Attachments saved out of mail clients, e.g. Thunderbird's ImportExportTools
+//! NG or Outlook's "Save All Attachments" dumped into an `Attachments`
+//! folder. With `--mail-attachments` they go to
+//! `Documents/Attachments/{year}/{sender}`, with the sender and date taken
+//! from companion metadata when the export left any: a `<file>.json` sidecar,
+//! or the headers of the `.eml` saved next to the folder. The companions
+//! themselves stay where they are.
+
+use std::{
+    collections::{HashMap, HashSet},
+    io::{BufRead, BufReader},
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Datelike, FixedOffset};
+use serde::Deserialize;
+
+use crate::path_safety::sanitize_component;
+
+/// Sender and date of the message a file was attached to
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MailInfo {
+    /// Display name, or the address when there is none
+    pub sender: Option<String>,
+    pub date: Option<DateTime<FixedOffset>>,
+}
+
+impl MailInfo {
+    /// Directory named after the sender, `Unknown` without one
+    pub fn sender_folder(&self) -> String {
+        sanitize_component(self.sender.as_deref().unwrap_or_default())
+    }
+
+    pub fn year(&self) -> Option<i32> {
+        self.date.map(|date| date.year())
+    }
+
+    /// Read the `From` and `Date` headers of a message, stopping at the body.
+    /// Folded header lines are joined; missing or unreadable ones stay `None`.
+    pub fn from_headers(text: &str) -> Self {
+        let mut headers: Vec<String> = Vec::new();
+        for line in text.lines() {
+            if line.is_empty() {
+                break;
+            }
+            match headers.last_mut() {
+                Some(last) if line.starts_with([' ', '\t']) => {
+                    last.push(' ');
+                    last.push_str(line.trim());
+                }
+                _ => headers.push(line.to_string()),
+            }
+        }
+
+        let mut info = MailInfo::default();
+        for header in &headers {
+            let Some((name, value)) = header.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+            if name.eq_ignore_ascii_case("from") {
+                info.sender = sender_name(value);
+            } else if name.eq_ignore_ascii_case("date") {
+                info.date = parse_date(value);
+            }
+        }
+        info
+    }
+}
+
+/// `"Jane Doe" <jane@example.com>` → `Jane Doe`; a bare or bracketed
+/// address, or an encoded (`=?utf-8?…?=`) name, gives the address
+pub fn sender_name(from: &str) -> Option<String> {
+    let (name, address) = match from.rsplit_once('<') {
+        Some((name, rest)) => (name, rest.split('>').next().unwrap_or_default()),
+        None => ("", from),
+    };
+    let name = name.trim().trim_matches('"').trim();
+    let address = address.trim();
+    if !name.is_empty() && !name.contains("=?") {
+        Some(name.to_string())
+    } else if !address.is_empty() {
+        Some(address.to_string())
+    } else {
+        None
+    }
+}
+
+/// RFC 2822 as in mail headers, or RFC 3339 as written by export scripts
+fn parse_date(value: &str) -> Option<DateTime<FixedOffset>> {
+    let value = value.trim();
+    DateTime::parse_from_rfc2822(value)
+        .or_else(|_| DateTime::parse_from_rfc3339(value))
+        .ok()
+}
+
+/// `<attachment>.json` written by export scripts
+#[derive(Debug, Deserialize)]
+struct Sidecar {
+    #[serde(alias = "sender")]
+    from: Option<String>,
+    date: Option<String>,
+}
+
+/// Whether a directory holds saved attachments, judging by its name
+/// (`Attachments`, `Invoice_attachments`)
+fn is_attachment_dir(dir: &Path) -> bool {
+    dir.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.to_lowercase().contains("attachment"))
+}
+
+fn has_extension(path: &Path, ext: &str) -> bool {
+    path.extension().and_then(|e| e.to_str()).is_some_and(|e| e.eq_ignore_ascii_case(ext))
+}
+
+/// Mail exports found among the files of one run
+#[derive(Debug, Clone, Default)]
+pub struct MailExports {
+    attachments: HashMap<PathBuf, MailInfo>,
+    companions: HashSet<PathBuf>,
+}
+
+impl MailExports {
+    /// Find the attachments among `files` (every file in a folder named like
+    /// an attachment folder) and read what their companions say about them
+    pub fn detect<'a>(files: impl IntoIterator<Item = &'a Path>) -> Self {
+        let files: HashSet<&Path> = files.into_iter().collect();
+        let mut exports = MailExports::default();
+        let mut messages: HashMap<PathBuf, MailInfo> = HashMap::new();
+
+        for &file in &files {
+            let Some(dir) = file.parent().filter(|dir| is_attachment_dir(dir)) else {
+                continue;
+            };
+            // Messages saved with the attachments, and sidecars of other files
+            if has_extension(file, "eml") || Self::sidecar_of(file).is_some_and(|of| files.contains(of.as_path())) {
+                exports.companions.insert(file.to_path_buf());
+                continue;
+            }
+
+            let mut sidecar = file.as_os_str().to_owned();
+            sidecar.push(".json");
+            let info = read_sidecar(Path::new(&sidecar))
+                .or_else(|| {
+                    let message = find_message(dir)?;
+                    exports.companions.insert(message.clone());
+                    Some(
+                        messages
+                            .entry(message)
+                            .or_insert_with_key(|message| read_message(message).unwrap_or_default())
+                            .clone(),
+                    )
+                })
+                .unwrap_or_default();
+            exports.attachments.insert(file.to_path_buf(), info);
+        }
+        exports
+    }
+
+    /// The file `path` is the `.json` sidecar of
+    fn sidecar_of(path: &Path) -> Option<PathBuf> {
+        has_extension(path, "json").then(|| path.with_extension(""))
+    }
+
+    /// Sender and date for an attachment, `None` for files not in an export
+    pub fn get(&self, path: &Path) -> Option<&MailInfo> {
+        self.attachments.get(path)
+    }
+
+    /// Whether `path` is a message or sidecar the attachments were described by
+    pub fn is_companion(&self, path: &Path) -> bool {
+        self.companions.contains(path)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.attachments.is_empty()
+    }
+}
+
+fn read_sidecar(path: &Path) -> Option<MailInfo> {
+    let sidecar: Sidecar = serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()?;
+    Some(MailInfo {
+        sender: sidecar.from.as_deref().and_then(sender_name),
+        date: sidecar.date.as_deref().and_then(parse_date),
+    })
+}
+
+/// The message an attachment folder belongs to: an `.eml` inside it, or
+/// next to it named like the folder (`Invoice.eml` for
+/// `Invoice_attachments`), or the only `.eml` next to it
+fn find_message(dir: &Path) -> Option<PathBuf> {
+    let emls = |dir: &Path| -> Vec<PathBuf> {
+        let mut found: Vec<PathBuf> = std::fs::read_dir(dir)
+            .into_iter()
+            .flatten()
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|path| has_extension(path, "eml") && path.is_file())
+            .collect();
+        found.sort();
+        found
+    };
+
+    if let Some(inside) = emls(dir).into_iter().next() {
+        return Some(inside);
+    }
+    let folder = dir.file_name()?.to_str()?.to_lowercase();
+    let beside = emls(dir.parent()?);
+    let named = beside.iter().find(|eml| {
+        eml.file_stem()
+            .and_then(|stem| stem.to_str())
+            .is_some_and(|stem| folder.starts_with(&stem.to_lowercase()))
+    });
+    match (named, beside.as_slice()) {
+        (Some(eml), _) => Some(eml.clone()),
+        (None, [only]) => Some(only.clone()),
+        _ => None,
+    }
+}
+
+/// Headers of a saved message, without reading its (possibly large) body
+fn read_message(path: &Path) -> Option<MailInfo> {
+    let mut reader = BufReader::new(std::fs::File::open(path).ok()?);
+    let mut headers = String::new();
+    let mut line = Vec::new();
+    // Headers should be ASCII, but old clients wrote raw Latin-1 names
+    while reader.read_until(b'\n', &mut line).ok()? > 0 {
+        let text = String::from_utf8_lossy(&line);
+        let text = text.trim_end_matches(['\r', '\n']);
+        if text.is_empty() {
+            break;
+        }
+        headers.push_str(text);
+        headers.push('\n');
+        line.clear();
+    }
+    Some(MailInfo::from_headers(&headers))
+}
@@ -0,0 +1,150 @@
+//! `.stash-manifest.json`: a small summary written into each category folder
+//! of the destination (`--manifests`), so the organized tree describes itself
+//! to anyone browsing it without the index. After a run only the folders it
+//! organized files into are counted again.
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use walkdir::WalkDir;
+
+use crate::{
+    errors::{FileOrganizerError, Result},
+    exclusions::IN_PLACE_MARKER,
+    path_builder::FolderNames,
+    stats::FileOutcome,
+};
+
+/// File name of a manifest inside its category folder
+pub const MANIFEST_NAME: &str = ".stash-manifest.json";
+
+/// What a category folder holds
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+    /// Top-level category, e.g. `Images`
+    pub category: String,
+    pub files: u64,
+    pub bytes: u64,
+    /// Modification time of the least recently modified file, RFC 3339
+    pub oldest: Option<String>,
+    /// Modification time of the most recently modified file, RFC 3339
+    pub newest: Option<String>,
+    /// Run that last organized files into the folder
+    pub run_id: String,
+    /// When the manifest was written, RFC 3339
+    pub updated_at: String,
+}
+
+impl Manifest {
+    /// Count what is in `dir` now, in all its subfolders
+    pub fn build(dir: &Path, category: &str, run_id: &str) -> Result<Self> {
+        let (mut files, mut bytes) = (0, 0);
+        let mut range: Option<(SystemTime, SystemTime)> = None;
+        for entry in WalkDir::new(dir).min_depth(1) {
+            let entry = entry.map_err(std::io::Error::other)?;
+            if !entry.file_type().is_file() || (entry.depth() == 1 && entry.file_name() == MANIFEST_NAME) {
+                continue;
+            }
+            let metadata = entry.metadata().map_err(std::io::Error::other)?;
+            files += 1;
+            bytes += metadata.len();
+            if let Ok(modified) = metadata.modified() {
+                range = Some(match range {
+                    Some((oldest, newest)) => (oldest.min(modified), newest.max(modified)),
+                    None => (modified, modified),
+                });
+            }
+        }
+        let rfc3339 = |time: SystemTime| DateTime::<Local>::from(time).to_rfc3339();
+        Ok(Self {
+            category: category.to_string(),
+            files,
+            bytes,
+            oldest: range.map(|(oldest, _)| rfc3339(oldest)),
+            newest: range.map(|(_, newest)| rfc3339(newest)),
+            run_id: run_id.to_string(),
+            updated_at: Local::now().to_rfc3339(),
+        })
+    }
+
+    /// The manifest in `dir`, if it has one
+    pub fn read(dir: &Path) -> Result<Option<Self>> {
+        let path = dir.join(MANIFEST_NAME);
+        match std::fs::read(&path) {
+            Ok(json) => serde_json::from_slice(&json)
+                .map(Some)
+                .map_err(|source| FileOrganizerError::Json { path, source }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Replace the manifest in `dir` in one step. Returns its path.
+    pub fn write(&self, dir: &Path) -> Result<PathBuf> {
+        let path = dir.join(MANIFEST_NAME);
+        let json = serde_json::to_vec_pretty(self)
+            .map_err(|source| FileOrganizerError::Json { path: path.clone(), source })?;
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, json)?;
+        std::fs::rename(&tmp, &path)?;
+        Ok(path)
+    }
+}
+
+/// Category folders files were organized into, with their category: the
+/// nearest folder above each file named after its top-level category, as
+/// `names` calls it
+pub fn touched_folders(outcomes: &[FileOutcome], names: &FolderNames) -> BTreeMap<PathBuf, &'static str> {
+    let mut folders = BTreeMap::new();
+    for outcome in outcomes {
+        let (dest, category) = match outcome {
+            FileOutcome::Moved(report) => (&report.dest, report.category),
+            FileOutcome::Renamed { report, new_path } => (new_path, report.category),
+            _ => continue,
+        };
+        let folder_name = names.category(category);
+        if let Some(folder) = dest.ancestors().skip(1).find(|dir| dir.file_name().is_some_and(|name| *name == *folder_name)) {
+            folders.insert(folder.to_path_buf(), category);
+        }
+    }
+    folders
+}
+
+/// Rewrite the manifest of each of `folders` (from [`touched_folders`]).
+/// Returns the manifests written; a folder that cannot be counted or written
+/// to is only logged.
+pub fn update(folders: BTreeMap<PathBuf, &'static str>, run_id: &str) -> Vec<PathBuf> {
+    folders
+        .into_iter()
+        .filter_map(|(dir, category)| match Manifest::build(&dir, category, run_id).and_then(|m| m.write(&dir)) {
+            Ok(path) => Some(path),
+            Err(e) => {
+                tracing::warn!(?dir, error = %e, "Could not write manifest");
+                None
+            }
+        })
+        .collect()
+}
+
+/// Whether `dir` holds nothing but a manifest or an [`IN_PLACE_MARKER`],
+/// which are then removed so the folder can be; used when cleaning up after
+/// a revert
+pub async fn only_own_files_left(dir: &Path) -> Result<bool> {
+    let mut entries = tokio::fs::read_dir(dir).await?;
+    let mut own = Vec::new();
+    while let Some(entry) = entries.next_entry().await? {
+        if entry.file_name() != MANIFEST_NAME && entry.file_name() != IN_PLACE_MARKER {
+            return Ok(false);
+        }
+        own.push(entry.path());
+    }
+    for path in own {
+        tokio::fs::remove_file(path).await?;
+    }
+    Ok(true)
+}
@@ -0,0 +1,91 @@
+//! Text from scanned documents, read with external tools: `pdftotext` for
+//! PDFs with a text layer, `pdftoppm` and `tesseract` for PDFs without one
+//! and for images. A missing tool just means no text.
+
+use std::{
+    ffi::OsStr,
+    path::{Path, PathBuf},
+    process::Stdio,
+};
+
+use tokio::process::Command;
+
+use crate::doc_naming::{looks_generic, suggest_name};
+
+/// Less text than this from `pdftotext` means the PDF is a scan
+const MIN_TEXT_LAYER: usize = 20;
+
+/// Images `tesseract` reads directly
+const IMAGE_EXTENSIONS: [&str; 6] = ["png", "jpg", "jpeg", "tif", "tiff", "bmp"];
+
+/// `destination` with its file name replaced by one read from `source`'s
+/// text, when the name `source` has is a scanner's counter rather than a
+/// person's choice. Otherwise `destination` unchanged.
+pub async fn rename(source: &Path, destination: PathBuf) -> PathBuf {
+    let Some(stem) = source.file_stem().and_then(OsStr::to_str) else {
+        return destination;
+    };
+    if !looks_generic(stem) {
+        return destination;
+    }
+    let Some(text) = extract_text(source).await else {
+        return destination;
+    };
+    match suggest_name(&text) {
+        Some(name) => {
+            let name = name.file_name(source.extension().and_then(OsStr::to_str));
+            tracing::debug!(target: "ocr", "Naming {:?} {:?} from its text", source, name);
+            destination.with_file_name(name)
+        }
+        None => destination,
+    }
+}
+
+/// The text of the first page of a PDF or an image of a document
+pub async fn extract_text(path: &Path) -> Option<String> {
+    let ext = path.extension()?.to_str()?.to_lowercase();
+    if ext == "pdf" {
+        let text = run("pdftotext", [OsStr::new("-l"), OsStr::new("1"), OsStr::new("-q"), path.as_os_str(), OsStr::new("-")]).await;
+        match text {
+            Some(text) if text.trim().len() >= MIN_TEXT_LAYER => Some(text),
+            _ => ocr_pdf(path).await,
+        }
+    } else if IMAGE_EXTENSIONS.contains(&ext.as_str()) {
+        run("tesseract", [path.as_os_str(), OsStr::new("stdout")]).await
+    } else {
+        None
+    }
+}
+
+/// Render the first page and read it with tesseract
+async fn ocr_pdf(path: &Path) -> Option<String> {
+    let dir = tempfile::tempdir().ok()?;
+    let prefix = dir.path().join("page");
+    let args = [
+        OsStr::new("-r"), OsStr::new("300"), OsStr::new("-f"), OsStr::new("1"), OsStr::new("-l"), OsStr::new("1"),
+        OsStr::new("-png"), OsStr::new("-singlefile"), path.as_os_str(), prefix.as_os_str(),
+    ];
+    run("pdftoppm", args).await?;
+    run("tesseract", [prefix.with_extension("png").as_os_str(), OsStr::new("stdout")]).await
+}
+
+/// Stdout of `program`, or `None` if it is missing or fails
+async fn run<'a>(program: &str, args: impl IntoIterator<Item = &'a OsStr>) -> Option<String> {
+    let output = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stderr(Stdio::piped())
+        .output()
+        .await;
+    match output {
+        Ok(output) if output.status.success() => Some(String::from_utf8_lossy(&output.stdout).into_owned()),
+        Ok(output) => {
+            tracing::debug!(target: "ocr", "{} failed ({}): {}", program, output.status, String::from_utf8_lossy(&output.stderr).trim());
+            None
+        }
+        Err(e) => {
+            tracing::debug!(target: "ocr", "Cannot run {}: {}", program, e);
+            None
+        }
+    }
+}
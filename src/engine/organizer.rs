@@ -1,25 +1,975 @@
-use std::{path::{Path, PathBuf}, sync::Arc};
+use std::{collections::{BTreeMap, BTreeSet, HashMap, HashSet}, path::{Path, PathBuf}, str::FromStr, sync::{Arc, Mutex}, time::{Duration, Instant}};
+use chrono::Datelike;
 use tokio::sync::{OwnedSemaphorePermit, Semaphore};
 use futures::stream::{FuturesUnordered, StreamExt};
+use tracing::{field, Instrument};
 
 use crate::{
-    conflict_resolver::resolve_conflict, 
-    errors::{FileOrganizerError, Result}, 
+    cancel::CancelToken,
+    chunking::{ChunkSignature, MIN_SIGNED_SIZE},
+    control::RunControl,
+    config::disabled_categories,
+    conflict_resolver::resolve_conflict_in, 
+    dedupe::{same_file, DedupeAction},
+    errors::{FileOrganizerError, Result, SkipReason}, 
+    exclusions::{ExcludeGlobs, Exclusions, IN_PLACE_MARKER},
+    filename_date::DatePatterns,
+    guard::Guard,
     file_mover::FileMover, 
+    instance_lock::{default_lock_dir, InstanceLock},
+    fs::{Preserve, ThrottledFs},
     hasher::{create_hasher, FileHasher, HashAlgo}, 
-    index::Db, 
-    metadata::FileCategory, 
-    path_builder::PathBuilder, 
+    index::{Db, RunMove, RunStats}, 
+    index_prune::prune_index,
+    mail_export::MailExports,
+    manifest,
+    metadata::{ClassifiedFileMetadata, CodeSubcategory, DocumentSubcategory, ExecutableSubcategory, FileCategory}, 
+    path_builder::{DateFolders, FolderNames, PathBuilder}, 
+    post_process::{PostCommands, PostProcessor},
+    progress::Progress,
+    provenance::{GroupTemplate, Provenance},
+    permissions::DestPermissions,
+    quarantine,
     registry::ClassifierRegistry, 
-    scanner::{RawFileMetadata, Scanner, ScannerExt}, 
-    utils::{create_classifier_registry, default_db_path, make_progress}
+    repair,
+    reverter::verify_recorded,
+    rule_classifier::{ClassifierRule, ExtensionOverrides, RuleClassifier},
+    run_report::{RunReport, RunReportFormat},
+    run_status::RunStatus,
+    scanner::{BundleKind, RawFileMetadata, ScanConfig, Scanner, SymlinkPolicy}, 
+    snapshot,
+    space::{self, Unusable},
+    stats::{CategoryLegend, FileErrorReport, FileOutcome, FileReport, MoveAction, Stage, StageStats, Summary},
+    timed_stage,
+    trash,
+    utils::{create_classifier_registry_with, current_uid, default_db_path, default_trash_dir, portable_db_path, user_name},
+    volumes::{Device, Routes, Volumes, DEFAULT_TRANSFERS_PER_DEVICE},
+    win_path,
+    workspace::Workspace,
 };
 
+/// Files processed at once unless `--max-concurrency` says otherwise
+pub const DEFAULT_CONCURRENCY: usize = 32;
+
+/// Row written to the index for an organized file: (metadata, category, destination, hash)
+pub type IndexEntry = (RawFileMetadata, String, PathBuf, String);
+
+/// What to do when a different file already has a file's destination name
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConflictStrategy {
+    /// Move it under a numbered name (`report_1.pdf`)
+    #[default]
+    Rename,
+    /// Leave it where it is
+    Skip,
+    /// Replace the file at the destination
+    Overwrite,
+}
+
+impl FromStr for ConflictStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "rename" => Ok(Self::Rename),
+            "skip" => Ok(Self::Skip),
+            "overwrite" => Ok(Self::Overwrite),
+            other => Err(format!("unknown conflict strategy `{}` (rename, skip, overwrite)", other)),
+        }
+    }
+}
+
+/// What to do with a file whose content was organized before (see
+/// [`OrganizeOptions::dedupe_on_organize`])
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicateAction {
+    /// Remove it for good
+    Delete,
+    /// Move it into stash's trash, where `stash trash restore` can bring it back
+    Trash,
+}
+
+impl FromStr for DuplicateAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "delete" => Ok(Self::Delete),
+            "trash" | "quarantine" => Ok(Self::Trash),
+            other => Err(format!("unknown duplicate action `{}` (delete, trash)", other)),
+        }
+    }
+}
+
+/// What to do with project folders and app bundles (see [`BundleKind`])
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BundlePolicy {
+    /// Leave them where they are
+    #[default]
+    Skip,
+    /// Move each one whole, to `Code/Projects` or `Executables/MacApp`
+    Move,
+}
+
+impl FromStr for BundlePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "skip" => Ok(Self::Skip),
+            "move" => Ok(Self::Move),
+            other => Err(format!("unknown bundle policy `{}` (skip, move)", other)),
+        }
+    }
+}
+
+/// Order in which scanned files are handed to the workers
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ProcessingOrder {
+    /// As the scan found them
+    #[default]
+    Scan,
+    /// Smallest first, so most files are done early and large ones finish last
+    SmallFirst,
+    LargeFirst,
+    /// Least recently modified first; files without an mtime go last
+    OldestFirst,
+}
+
+impl ProcessingOrder {
+    /// Sort `files` into this order, keeping the scan order among equals
+    pub fn sort(self, files: &mut [RawFileMetadata]) {
+        match self {
+            Self::Scan => {}
+            Self::SmallFirst => files.sort_by_key(|f| f.size),
+            Self::LargeFirst => files.sort_by_key(|f| std::cmp::Reverse(f.size)),
+            Self::OldestFirst => files.sort_by_key(|f| (f.modified.is_none(), f.modified)),
+        }
+    }
+}
+
+impl FromStr for ProcessingOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "scan" => Ok(Self::Scan),
+            "small-first" => Ok(Self::SmallFirst),
+            "large-first" => Ok(Self::LargeFirst),
+            "oldest-first" => Ok(Self::OldestFirst),
+            other => Err(format!("unknown order `{}` (scan, small-first, large-first, oldest-first)", other)),
+        }
+    }
+}
+
+/// Options controlling an organize run
+#[derive(Debug, Clone, Default)]
+pub struct OrganizeOptions {
+    /// Perform a dry run without moving files
+    pub dry_run: bool,
+    /// Store the index inside the organized root with paths relative to it
+    pub portable: bool,
+    /// Directory to organize into (defaults to `<root_dir>/Organized`)
+    pub dest: Option<PathBuf>,
+    /// Destination roots per category used instead of `dest`, preferring
+    /// one on the same filesystem as each file (see [`Routes`])
+    pub volumes: Volumes,
+    /// Sort files into category folders directly under the root instead of
+    /// a separate destination. Each folder gets an [`IN_PLACE_MARKER`], and
+    /// marked folders are left out of later scans.
+    pub in_place: bool,
+    /// Record a compressed listing of `root_dir` in the index before organizing
+    pub snapshot: bool,
+    /// Directory levels scanned below the root; 1 (the default) takes only
+    /// the files directly in it. The destination is never scanned.
+    pub max_depth: Option<usize>,
+    /// Skip files smaller than this many bytes
+    pub min_size: Option<u64>,
+    /// Skip files larger than this many bytes
+    pub max_size: Option<u64>,
+    /// Only organize files last modified at least this long ago
+    pub older_than: Option<Duration>,
+    /// Only organize files last modified within this long
+    pub newer_than: Option<Duration>,
+    /// Put each file under a directory named after its owner (`<dest>/<owner>/Documents/...`)
+    pub by_owner: bool,
+    /// Only organize files owned by the user running stash (Unix only)
+    pub only_mine: bool,
+    /// Files whose destination is taken by a different file
+    pub on_conflict: ConflictStrategy,
+    /// Files with the same content as one organized before (anywhere, by
+    /// the index) are not organized again but deleted or trashed
+    pub dedupe_on_organize: Option<DuplicateAction>,
+    /// stash's trash, for [`DuplicateAction::Trash`] (defaults to [`default_trash_dir`])
+    pub trash_dir: Option<PathBuf>,
+    /// Also record a content-defined chunk signature of files of at least
+    /// [`MIN_SIGNED_SIZE`], for `stash dedupe similar` to find near-duplicates
+    /// (re-encodes, downloads cut short)
+    pub chunk_signatures: bool,
+    /// Hash used to spot duplicates and recorded in the index, tagged with
+    /// its algorithm so `stash verify` and `stash revert` check each file
+    /// with the one it was recorded with
+    pub hash_algo: HashAlgo,
+    /// Metadata kept when files are copied to another filesystem
+    pub preserve: Preserve,
+    /// Commands run on each file once organized, per category
+    pub post_process: PostCommands,
+    /// Name scanned documents with generic names (`SCAN_0042.pdf`) after their
+    /// text, e.g. `2024-03-12 Electric Bill.pdf`. Only with the `ocr` feature.
+    pub ocr_names: bool,
+    /// Send attachments saved out of mail clients (files in `Attachments`
+    /// folders) to `Documents/Attachments/{year}/{sender}`, see [`MailExports`]
+    pub mail_attachments: bool,
+    /// Leave symbolic links alone, organize what they point to, or move them as links
+    pub symlinks: SymlinkPolicy,
+    /// Project folders (holding `.git`, `Cargo.toml`, `package.json`, ...) and
+    /// `.app` bundles are never split up: they are left alone or moved whole
+    pub bundles: BundlePolicy,
+    /// Directories from provenance tokens, e.g. `{source_domain}`, placed
+    /// before the category (`<dest>/github.com/Archives/...`)
+    pub group: Option<GroupTemplate>,
+    /// Classifiers from config, tried alongside the built-in ones
+    pub classifiers: Vec<Arc<RuleClassifier>>,
+    /// Extensions moved to a fixed folder under the destination, whatever
+    /// the classifiers say (the config's `overrides`)
+    pub overrides: ExtensionOverrides,
+    /// Weighted score a classifier needs for its category; files below it
+    /// for every classifier go to `Others` (see [`ClassifierRegistry::min_score`])
+    pub min_classifier_score: u16,
+    /// Read the date from file names matching these instead of using
+    /// timestamps (e.g. `SCN_20240131_0001.pdf` → `.../2024/01`)
+    pub date_patterns: DatePatterns,
+    /// Date directories under each category. Unset: the year, plus the
+    /// month when one of `date_patterns` gives one.
+    pub date_folders: Option<DateFolders>,
+    /// Paths below each root left alone, on top of stash's own files
+    pub exclude: ExcludeGlobs,
+    /// Top-level categories (e.g. `Code`) whose files are left in place
+    pub disabled_categories: BTreeSet<&'static str>,
+    /// Top-level categories whose download quarantine marker (`Zone.Identifier`,
+    /// `com.apple.quarantine`) is removed once moved; other files keep it
+    pub strip_quarantine: BTreeSet<&'static str>,
+    /// Mode and group given to organized files per top-level category
+    pub permissions: BTreeMap<&'static str, DestPermissions>,
+    /// Icons, colors and names used in the summary's category breakdown
+    pub legend: CategoryLegend,
+    /// Names of the category folders, when not the built-in English ones
+    pub folder_names: FolderNames,
+    /// Files processed at once (default [`DEFAULT_CONCURRENCY`])
+    pub max_concurrency: Option<usize>,
+    /// Order files start in within each root, e.g. smallest first
+    pub order: ProcessingOrder,
+    /// Cap on bytes per second copied to other filesystems, shared by all files
+    pub max_throughput: Option<u64>,
+    /// Files copied onto one destination device at once (default
+    /// [`DEFAULT_TRANSFERS_PER_DEVICE`]); classifying and hashing others
+    /// goes on meanwhile
+    pub transfers_per_device: Option<usize>,
+    /// Organize even when the preflight check finds too little free space
+    /// for files copied to another filesystem (only warn)
+    pub force: bool,
+    /// Roots and destinations refused, e.g. `/` or the home directory
+    pub guard: Guard,
+    /// When another run is working on a root or destination, wait for it
+    /// instead of failing with [`FileOrganizerError::AlreadyRunning`]
+    pub wait_for_lock: bool,
+    /// Where the lock files of runs go (defaults to [`default_lock_dir`])
+    pub lock_dir: Option<PathBuf>,
+    /// Write the outcome of each run here (see [`RunStatus`])
+    pub status_file: Option<PathBuf>,
+    /// Write a `.stash-manifest.json` summary into each category folder files
+    /// were organized into (see [`manifest`])
+    pub manifests: bool,
+    /// Directory that gets a timestamped report of every run: each move,
+    /// skip and error plus the summary (see [`RunReport`])
+    pub report_dir: Option<PathBuf>,
+    pub report_format: RunReportFormat,
+    /// After each run, forget index entries this old whose files are gone
+    /// (see [`prune_index`])
+    pub index_retention: Option<Duration>,
+    /// Where runs that move files get a [`Workspace`] for the files they
+    /// stage; none when unset. The CLI uses [`crate::workspace::default_work_root`].
+    pub work_root: Option<PathBuf>,
+    /// Where per-file progress goes (silent by default)
+    pub progress: Progress,
+    /// Print nothing to the terminal: failed files, the summary and the
+    /// report's path are left to the caller, from [`Summary::failures`] and
+    /// [`Summary::report_path`]. For front ends that own the screen.
+    pub quiet: bool,
+    /// Stops the run early: files being moved finish and are indexed, the
+    /// rest are left in place and counted in [`Summary::not_started`]
+    pub cancel: CancelToken,
+    /// Pauses the run or skips categories while it goes (see [`RunControl`])
+    pub control: RunControl,
+}
+
+impl OrganizeOptions {
+    /// Base directory that organized files are placed under
+    pub fn dest_root(&self, root_dir: &Path) -> PathBuf {
+        if self.in_place {
+            return root_dir.to_path_buf();
+        }
+        self.dest
+            .clone()
+            .unwrap_or_else(|| root_dir.join("Organized"))
+    }
+
+    /// Scanner settings: entries down to [`max_depth`](Self::max_depth) (top-level
+    /// only by default), with the size and age filters applied
+    pub fn scan_config(&self) -> ScanConfig {
+        ScanConfig {
+            min_depth: 1,
+            max_depth: self.max_depth.unwrap_or(1).max(1),
+            min_size: self.min_size,
+            max_size: self.max_size,
+            older_than: self.older_than,
+            newer_than: self.newer_than,
+            owner: if self.only_mine { current_uid() } else { None },
+            symlinks: self.symlinks,
+            bundles: true,
+            cancel: self.cancel.clone(),
+            ..Default::default()
+        }
+    }
+
+    /// What is left alone under `root_dir`: the destination, stash's own
+    /// files (index, logs, status file, reports) and [`exclude`](Self::exclude)
+    pub fn exclusions(&self, root_dir: &Path) -> Exclusions {
+        let mut exclusions = Exclusions::new(root_dir).dir(self.dest_root(root_dir)).globs(self.exclude.clone());
+        if self.in_place {
+            exclusions = exclusions.marked();
+        }
+        for volume in self.volumes.values().flatten() {
+            exclusions = exclusions.dir(volume);
+        }
+        if let Some(dir) = &self.report_dir {
+            exclusions = exclusions.dir(dir);
+        }
+        match &self.status_file {
+            Some(file) => exclusions.file(file),
+            None => exclusions,
+        }
+    }
+}
+
+/// An organize run configured in code, for programs that embed stash
+/// instead of running the `stash` binary. Build one with [`Organizer::builder`]:
+/// `Organizer::builder("/home/me/Downloads").dest(sorted).max_depth(3).build()?.run().await?`
+/// returns the run's [`Summary`].
+#[derive(Debug, Clone)]
+pub struct Organizer {
+    roots: Vec<PathBuf>,
+    options: OrganizeOptions,
+}
+
+impl Organizer {
+    pub fn builder(root: impl Into<PathBuf>) -> OrganizerBuilder {
+        OrganizerBuilder::new(root)
+    }
+
+    pub fn roots(&self) -> &[PathBuf] {
+        &self.roots
+    }
+
+    pub fn options(&self) -> &OrganizeOptions {
+        &self.options
+    }
+
+    /// Organize every root; see [`organise_roots`]
+    pub async fn run(&self) -> Result<Summary> {
+        organise_roots(&self.roots, &self.options).await
+    }
+}
+
+/// Sets up an [`Organizer`]. Anything not set keeps the `stash organize` default.
+#[derive(Debug, Clone)]
+pub struct OrganizerBuilder {
+    roots: Vec<PathBuf>,
+    options: OrganizeOptions,
+    rules: Vec<ClassifierRule>,
+    overrides: HashMap<String, String>,
+    disabled: Vec<String>,
+    exclude: Vec<String>,
+}
+
+impl OrganizerBuilder {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            roots: vec![root.into()],
+            options: OrganizeOptions::default(),
+            rules: Vec::new(),
+            overrides: HashMap::new(),
+            disabled: Vec::new(),
+            exclude: Vec::new(),
+        }
+    }
+
+    /// Start from existing options, e.g. ones read from a config file
+    pub fn options(mut self, options: OrganizeOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Organize another root in the same run
+    pub fn root(mut self, root: impl Into<PathBuf>) -> Self {
+        self.roots.push(root.into());
+        self
+    }
+
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.options.dry_run = dry_run;
+        self
+    }
+
+    /// Print nothing; see [`OrganizeOptions::quiet`]
+    pub fn quiet(mut self, quiet: bool) -> Self {
+        self.options.quiet = quiet;
+        self
+    }
+
+    /// Organize into `dest` instead of `<root>/Organized`
+    pub fn dest(mut self, dest: impl Into<PathBuf>) -> Self {
+        self.options.dest = Some(dest.into());
+        self
+    }
+
+    /// Sort into category folders directly under the root
+    pub fn in_place(mut self, in_place: bool) -> Self {
+        self.options.in_place = in_place;
+        self
+    }
+
+    /// Which roots are refused; [`Guard::disabled`] allows any
+    pub fn guard(mut self, guard: Guard) -> Self {
+        self.options.guard = guard;
+        self
+    }
+
+    /// Keep the index inside the organized root
+    pub fn portable(mut self, portable: bool) -> Self {
+        self.options.portable = portable;
+        self
+    }
+
+    /// Directory levels scanned below each root; see [`OrganizeOptions::max_depth`]
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.options.max_depth = Some(depth);
+        self
+    }
+
+    pub fn on_conflict(mut self, strategy: ConflictStrategy) -> Self {
+        self.options.on_conflict = strategy;
+        self
+    }
+
+    /// Project folders and app bundles; see [`OrganizeOptions::bundles`]
+    pub fn bundles(mut self, policy: BundlePolicy) -> Self {
+        self.options.bundles = policy;
+        self
+    }
+
+    pub fn order(mut self, order: ProcessingOrder) -> Self {
+        self.options.order = order;
+        self
+    }
+
+    pub fn hash_algo(mut self, algo: HashAlgo) -> Self {
+        self.options.hash_algo = algo;
+        self
+    }
+
+    pub fn date_folders(mut self, date_folders: DateFolders) -> Self {
+        self.options.date_folders = Some(date_folders);
+        self
+    }
+
+    /// Files processed at once
+    pub fn concurrency(mut self, files: usize) -> Self {
+        self.options.max_concurrency = Some(files);
+        self
+    }
+
+    /// Only organize files of at least `bytes`
+    pub fn min_size(mut self, bytes: u64) -> Self {
+        self.options.min_size = Some(bytes);
+        self
+    }
+
+    /// Only organize files of at most `bytes`
+    pub fn max_size(mut self, bytes: u64) -> Self {
+        self.options.max_size = Some(bytes);
+        self
+    }
+
+    /// Only organize files last modified at least `age` ago
+    pub fn older_than(mut self, age: Duration) -> Self {
+        self.options.older_than = Some(age);
+        self
+    }
+
+    /// Only organize files last modified within `age`
+    pub fn newer_than(mut self, age: Duration) -> Self {
+        self.options.newer_than = Some(age);
+        self
+    }
+
+    /// Add a classifier for a category stash does not know, as in the config's `classifiers`
+    pub fn rule(mut self, rule: ClassifierRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Move files with `extension` to `folder` under the destination, as in the config's `overrides`
+    pub fn override_extension(mut self, extension: impl Into<String>, folder: impl Into<String>) -> Self {
+        self.overrides.insert(extension.into(), folder.into());
+        self
+    }
+
+    /// Leave files of a top-level category (e.g. `"code"`) in place
+    pub fn disable_category(mut self, name: impl Into<String>) -> Self {
+        self.disabled.push(name.into());
+        self
+    }
+
+    /// Leave paths matching `glob` alone; see [`ExcludeGlobs`]
+    pub fn exclude(mut self, glob: impl Into<String>) -> Self {
+        self.exclude.push(glob.into());
+        self
+    }
+
+    pub fn progress(mut self, progress: Progress) -> Self {
+        self.options.progress = progress;
+        self
+    }
+
+    /// Stop the run early when `token` is cancelled
+    pub fn cancel(mut self, token: CancelToken) -> Self {
+        self.options.cancel = token;
+        self
+    }
+
+    /// Pause the run or skip categories through `control` while it goes
+    pub fn control(mut self, control: RunControl) -> Self {
+        self.options.control = control;
+        self
+    }
+
+    /// Check the rules, category names and globs and build the organizer
+    pub fn build(self) -> Result<Organizer> {
+        let Self { roots, mut options, rules, overrides, disabled, exclude } = self;
+        options.exclude.append(ExcludeGlobs::new(&exclude)?);
+        if !overrides.is_empty() {
+            options.overrides = ExtensionOverrides::new(&overrides).map_err(FileOrganizerError::InvalidRule)?;
+        }
+        for rule in &rules {
+            let classifier = RuleClassifier::from_rule(rule).map_err(FileOrganizerError::InvalidRule)?;
+            options.classifiers.push(Arc::new(classifier));
+        }
+        let disabled: HashMap<String, bool> = disabled.into_iter().map(|name| (name, false)).collect();
+        let custom: Vec<&str> = options.classifiers.iter().map(|classifier| classifier.category().top_level()).collect();
+        options.disabled_categories.extend(disabled_categories(&disabled, &custom)?);
+        Ok(Organizer { roots, options })
+    }
+}
+
 /// Organize files in `root_dir` asynchronously and efficiently.
 pub async fn organise_files(
     root_dir: &Path,
-    dry_run: bool
-) -> Result<()> {
+    options: &OrganizeOptions,
+) -> Result<Summary> {
+    organise(&[root_dir.to_path_buf()], options, None).await
+}
+
+/// Organize several roots in one run with a single progress bar and a
+/// combined summary. Each root keeps its own destination, index and run record.
+pub async fn organise_roots(
+    roots: &[PathBuf],
+    options: &OrganizeOptions,
+) -> Result<Summary> {
+    let mut unique: Vec<PathBuf> = Vec::with_capacity(roots.len());
+    for root in roots {
+        if !unique.contains(root) {
+            unique.push(root.clone());
+        }
+    }
+    organise(&unique, options, None).await
+}
+
+/// The roots no run was ever recorded for in the index organizing them
+/// would use, e.g. to preview a first run before moving anything. The index
+/// is not created if it doesn't exist yet.
+pub async fn first_runs(roots: &[PathBuf], options: &OrganizeOptions) -> Result<Vec<PathBuf>> {
+    let mut first = Vec::new();
+    for root in roots {
+        let (portable_root, index_path) = index_location(root, options).await?;
+        let seen = index_path.exists() && {
+            let db = if options.portable { Db::open_portable(portable_root).await? } else { Db::new(&index_path).await? };
+            db.has_run_for(root).await?
+        };
+        if !seen && !first.contains(root) {
+            first.push(root.clone());
+        }
+    }
+    Ok(first)
+}
+
+/// Organize only the given files of `root_dir` (used by watch mode).
+/// Scan filters still apply; snapshots are not taken.
+pub async fn organise_paths(
+    root_dir: &Path,
+    paths: &[PathBuf],
+    options: &OrganizeOptions,
+) -> Result<Summary> {
+    organise(&[root_dir.to_path_buf()], options, Some(paths)).await
+}
+
+async fn organise(
+    roots: &[PathBuf],
+    options: &OrganizeOptions,
+    only: Option<&[PathBuf]>,
+) -> Result<Summary> {
+    let run_id = new_run_id();
+    let span = tracing::info_span!(target: "organizer", "run", id = %run_id);
+    let result = run(roots, options, only, &run_id).instrument(span).await;
+
+    if let Some(path) = &options.status_file {
+        let status = match &result {
+            Ok(summary) => RunStatus::from_summary(roots, options.dry_run, summary),
+            Err(e) => RunStatus::failed(roots, options.dry_run, e),
+        };
+        if let Err(e) = status.write(path) {
+            tracing::warn!(target: "organizer", "Cannot write run status to {:?}: {}", path, e);
+        }
+    }
+
+    result
+}
+
+/// Short random id tying together the log lines of one run
+fn new_run_id() -> String {
+    format!("{:08x}", rand::random::<u32>())
+}
+
+async fn run(
+    roots: &[PathBuf],
+    options: &OrganizeOptions,
+    only: Option<&[PathBuf]>,
+    run_id: &str,
+) -> Result<Summary> {
+    let start = Instant::now();
+    let dry_run = options.dry_run;
+
+    // Held until the run ends; a dry run moves nothing, so it needs none
+    let _lock = if dry_run {
+        None
+    } else {
+        let mut dirs: Vec<PathBuf> = roots.iter().flat_map(|root| [root.clone(), options.dest_root(root)]).collect();
+        if !options.in_place {
+            dirs.extend(options.volumes.values().flatten().cloned());
+        }
+        let lock_dir = match &options.lock_dir {
+            Some(dir) => dir.clone(),
+            None => default_lock_dir().await?,
+        };
+        Some(InstanceLock::acquire(&lock_dir, dirs.iter().map(PathBuf::as_path), options.wait_for_lock).await?)
+    };
+
+    let registry = Arc::new(
+        create_classifier_registry_with(&options.classifiers)
+            .with_min_score(options.min_classifier_score)
+            .with_overrides(options.overrides.clone()),
+    );
+
+    // Validate and scan every root before moving anything
+    let mut stages = StageStats::default();
+    let mut prepared = Vec::with_capacity(roots.len());
+    for root_dir in roots {
+        prepared.push(prepare_root(root_dir, options, only, &registry, &mut stages).await?);
+    }
+
+    check_space(&prepared, options)?;
+
+    // Removed when the run ends, however it ends
+    let _workspace = match &options.work_root {
+        Some(work_root) if !dry_run => Some(Workspace::create(work_root, run_id)?),
+        _ => None,
+    };
+
+    let discovered: usize = prepared.iter().map(|p| p.files.len() + p.outcomes.len()).sum();
+    let total: usize = prepared.iter().map(|p| p.files.len()).sum();
+    let label = if dry_run { "Organizing (dry-run)" } else { "Organizing" };
+    options.progress.start(label, total as u64);
+
+    let mover = match options.max_throughput {
+        Some(rate) => FileMover::with_fs(Arc::new(ThrottledFs::new(rate))),
+        None => FileMover::new(),
+    };
+    let mover = Arc::new(
+        mover
+            .with_symlinks(options.symlinks)
+            .with_preserve(options.preserve),
+    );
+    let hasher = create_hasher(options.hash_algo);
+    let post = if dry_run { None } else { PostProcessor::new(&options.post_process).map(Arc::new) };
+    // Shared by every root, so copies onto one device queue together
+    let volumes = if options.in_place { Volumes::new() } else { options.volumes.clone() };
+    let routes = Arc::new(
+        Routes::new(volumes).with_transfers_per_device(options.transfers_per_device.unwrap_or(DEFAULT_TRANSFERS_PER_DEVICE)),
+    );
+    let mut outcomes = Vec::with_capacity(discovered);
+    let mut not_started = 0;
+    // Only a run that may trash duplicates takes a folder in the trash
+    let trash_run = match options.dedupe_on_organize {
+        Some(DuplicateAction::Trash) if !dry_run => {
+            Some(trash::run_dir(&options.trash_dir.clone().unwrap_or_else(default_trash_dir))?)
+        }
+        _ => None,
+    };
+
+    for root in prepared {
+        let root_start = Instant::now();
+        let first_outcome = outcomes.len();
+        outcomes.extend(root.outcomes);
+
+        // Process files with concurrency control
+        let ctx = Arc::new(FileContext {
+            db: root.db.clone(),
+            registry: registry.clone(),
+            mover: mover.clone(),
+            hasher: hasher.clone(),
+            hash_algo: options.hash_algo,
+            dest_root: root.dest_root.clone(),
+            routes: routes.clone(),
+            in_place: options.in_place,
+            folder_names: options.folder_names.clone(),
+            dry_run,
+            quiet: options.quiet,
+            date_patterns: options.date_patterns.clone(),
+            date_folders: options.date_folders,
+            disabled_categories: options.disabled_categories.clone(),
+            strip_quarantine: options.strip_quarantine.clone(),
+            permissions: options.permissions.clone(),
+            owners: options.by_owner.then(Default::default),
+            group: options.group.clone(),
+            progress: options.progress.clone(),
+            post: post.clone(),
+            ocr_names: options.ocr_names,
+            chunk_signatures: options.chunk_signatures,
+            mail: root.mail,
+            on_conflict: options.on_conflict,
+            dedupe: options.dedupe_on_organize.map(|action| (action, trash_run.clone().unwrap_or_default())),
+            cancel: options.cancel.clone(),
+            control: options.control.clone(),
+            concurrency: options.max_concurrency.unwrap_or(DEFAULT_CONCURRENCY).max(1),
+        });
+        let (processed, moves, processed_stages, skipped) = match process_files_concurrently(root.files, ctx).await {
+            Ok(result) => result,
+            Err(e) => {
+                options.progress.finish();
+                return Err(e);
+            }
+        };
+        outcomes.extend(processed);
+        stages.merge(&processed_stages);
+        not_started += skipped;
+
+        // Commit DB checkpoint once all files are processed
+        root.db.save().await?;
+
+        let mut recorded = None;
+        if !moves.is_empty() {
+            let run_id = root.db.record_run(&root.root, &root.dest_root, &moves).await?;
+            tracing::info!(target: "organizer", "Recorded run #{} for {:?} ({} moves)", run_id, root.root, moves.len());
+            if !options.quiet {
+                println!("📝 Run #{} recorded for {:?} ({} files moved)", run_id, root.root, moves.len());
+            }
+            recorded = Some(run_id);
+        }
+        if !dry_run && outcomes.len() > first_outcome {
+            let totals = Summary::from_outcomes(0, &outcomes[first_outcome..], root_start);
+            root.db.record_run_stats(&RunStats {
+                run_id: recorded,
+                root: root.root.clone(),
+                finished_at: snapshot::now_unix(),
+                processed: totals.processed as u64,
+                moved: (totals.moved + totals.renamed) as u64,
+                skipped: totals.skip_counts.iter().sum::<usize>() as u64,
+                duplicates: totals.duplicates as u64,
+                errors: totals.errors as u64,
+                bytes: totals.bytes_moved + totals.bytes_renamed,
+                duration_ms: Some(totals.duration.as_millis() as u64),
+            }).await?;
+        }
+        if !dry_run
+            && let Some(age) = options.index_retention
+            && let Err(e) = prune_index(&root.db, age, false, false).await
+        {
+            tracing::warn!(target: "organizer", "Index retention failed: {}", e);
+        }
+    }
+    options.progress.finish();
+    if let Some(trash_run) = &trash_run {
+        trash::discard_if_empty(trash_run).await;
+    }
+
+    if let Some(post) = post {
+        let report = post.finish().await;
+        tracing::info!(target: "organizer", "Post-processing: {} commands, {} failed", report.ran, report.failed);
+        if report.ran > 0 && !options.quiet {
+            println!("⚙️ Ran {} post-processing commands ({} failed)", report.ran, report.failed);
+        }
+    }
+
+    let mut summary = Summary::from_outcomes(discovered, &outcomes, start);
+    summary.run_id = run_id.to_string();
+    summary.add_stage_stats(&stages);
+    summary.legend = options.legend.clone();
+    summary.cancelled = options.cancel.is_cancelled();
+    summary.not_started = not_started;
+
+    if options.manifests && !dry_run {
+        let folders = manifest::touched_folders(&outcomes, &options.folder_names);
+        let run_id = run_id.to_string();
+        let written = tokio::task::spawn_blocking(move || manifest::update(folders, &run_id)).await?;
+        tracing::debug!(target: "organizer", "Updated {} manifests", written.len());
+    }
+
+    summary.failures = outcomes
+        .iter()
+        .filter(|o| matches!(o, FileOutcome::Err(_)))
+        .map(ToString::to_string)
+        .collect();
+    if !options.quiet {
+        for failure in &summary.failures {
+            eprintln!("{}", failure);
+        }
+        println!("{}", summary);
+    }
+
+    if let Some(dir) = &options.report_dir {
+        match RunReport::new(roots, dry_run, &outcomes, &summary).write_to(dir, options.report_format) {
+            Ok(path) => {
+                if !options.quiet {
+                    println!("🧾 Report written to {:?}", path);
+                }
+                summary.report_path = Some(path);
+            }
+            Err(e) => tracing::warn!(target: "organizer", "Cannot write run report to {:?}: {}", dir, e),
+        }
+    }
+
+    if summary.cancelled {
+        tracing::info!(target: "organizer", "Cancelled after {} files; {} not started", summary.processed, not_started);
+    } else if dry_run {
+        tracing::info!(target: "organizer", "Dry-run completed with {} files analyzed", summary.processed);
+    } else {
+        tracing::info!(target: "organizer", "Organize completed with {} files processed", summary.processed);
+    }
+    
+    Ok(summary)
+}
+
+/// A root that has been validated and scanned, ready to process
+struct PreparedRoot {
+    root: PathBuf,
+    dest_root: PathBuf,
+    db: Arc<Db>,
+    files: Vec<RawFileMetadata>,
+    /// Entries already settled during the scan (skipped or unreadable)
+    outcomes: Vec<FileOutcome>,
+    /// Mail attachments among `files`, when organizing them
+    mail: Option<MailExports>,
+}
+
+/// Before moving anything, make sure every destination filesystem has room
+/// for the files that will be copied onto it from another filesystem. Counts
+/// every scanned file, so duplicates and disabled categories only make it
+/// more cautious. Dry runs and `force` only warn.
+fn check_space(prepared: &[PreparedRoot], options: &OrganizeOptions) -> Result<()> {
+    let transfers = prepared.iter().map(|p| {
+        let bytes = p.files.iter().map(|f| f.size).sum();
+        (p.root.as_path(), p.dest_root.as_path(), bytes)
+    });
+    let shortfalls = match space::check(transfers) {
+        Ok(shortfalls) => shortfalls,
+        Err(e) => {
+            tracing::debug!(target: "organizer", error = %e, "Could not check free space");
+            return Ok(());
+        }
+    };
+
+    for shortfall in shortfalls {
+        let error = FileOrganizerError::InsufficientSpace {
+            path: shortfall.dest,
+            needed: shortfall.needed,
+            available: shortfall.available,
+        };
+        if !(options.dry_run || options.force) {
+            return Err(error);
+        }
+        tracing::warn!(target: "organizer", "{}", error);
+    }
+    Ok(())
+}
+
+/// Refuse a root or destination the guard protects, like the home directory.
+/// A dry run moves nothing, so it only warns.
+fn check_guard(guard: &Guard, root_dir: &Path, dest_root: &Path, dry_run: bool) -> Result<()> {
+    for path in [root_dir, dest_root] {
+        match guard.check(path) {
+            Err(e) if dry_run => tracing::warn!(target: "organizer", "{}", e),
+            result => result?,
+        }
+    }
+    Ok(())
+}
+
+/// Fail before scanning when the root or its destination is on a read-only
+/// mount, which would otherwise give one EROFS error per file, or on a
+/// pseudo-filesystem like `/proc`. Dry runs write nothing, so a read-only
+/// mount only gets a warning there.
+fn check_filesystems(root_dir: &Path, dest_root: &Path, dry_run: bool) -> Result<()> {
+    for path in [root_dir, dest_root] {
+        let reason = match space::unusable(path) {
+            Ok(Some(reason)) => reason,
+            Ok(None) => continue,
+            Err(e) => {
+                tracing::debug!(target: "organizer", error = %e, "Could not check the filesystem of {:?}", path);
+                continue;
+            }
+        };
+        let error = FileOrganizerError::UnusableFilesystem { path: path.to_path_buf(), reason };
+        if dry_run && reason == Unusable::ReadOnly {
+            tracing::warn!(target: "organizer", "{}", error);
+            continue;
+        }
+        return Err(error);
+    }
+    Ok(())
+}
+
+/// The root a portable index for `root_dir` lives in, and the index file
+/// organizing `root_dir` uses
+async fn index_location<'a>(root_dir: &'a Path, options: &'a OrganizeOptions) -> Result<(&'a Path, PathBuf)> {
+    let portable_root = options.dest.as_deref().unwrap_or(root_dir);
+    let index_path = if options.portable {
+        portable_db_path(portable_root)
+    } else {
+        default_db_path().await?
+    };
+    Ok((portable_root, index_path))
+}
+
+/// Validate `root_dir`, open its index, take a snapshot if asked and scan it
+async fn prepare_root(
+    root_dir: &Path,
+    options: &OrganizeOptions,
+    only: Option<&[PathBuf]>,
+    registry: &ClassifierRegistry,
+    stages: &mut StageStats,
+) -> Result<PreparedRoot> {
+    let dry_run = options.dry_run;
+
     if !root_dir.exists() {
         return Err(FileOrganizerError::from(std::io::Error::new(
             std::io::ErrorKind::NotFound,
@@ -34,206 +984,806 @@ pub async fn organise_files(
         )));
     }
     
-    let db_path = if dry_run {
-        PathBuf::from(":memory:")
-    } else {
-        let path = default_db_path().await?;
-        tracing::debug!(target: "organizer", "Using database path: {:?}", path);
-        if let Some(parent) = path.parent() {
-            tracing::debug!(target: "organizer", "Database directory exists: {}", parent.exists());
+    let dest_root = options.dest_root(root_dir);
+    check_filesystems(root_dir, &dest_root, dry_run)?;
+    check_guard(&options.guard, root_dir, &dest_root, dry_run)?;
+    if !options.in_place {
+        for volume in options.volumes.values().flatten() {
+            check_filesystems(root_dir, volume, dry_run)?;
+            check_guard(&options.guard, root_dir, volume, dry_run)?;
+        }
+    }
+    if let Some(dest) = &options.dest {
+        if dest.exists() && !dest.is_dir() {
+            return Err(FileOrganizerError::from(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Destination {:?} is not a directory", dest),
+            )));
+        }
+        if !dry_run {
+            tokio::fs::create_dir_all(dest).await?;
         }
-        path
+    }
+
+    // The persistent index; a dry run only reads ignore entries from it, if it exists yet
+    let (portable_root, index_path) = index_location(root_dir, options).await?;
+    tracing::debug!(target: "organizer", "Using database path: {:?}", index_path);
+
+    let index = if !dry_run || index_path.exists() {
+        Some(if options.portable {
+            Db::open_portable(portable_root).await?
+        } else {
+            Db::new(&index_path).await?
+        })
+    } else {
+        None
+    };
+    let ignored = match &index {
+        Some(db) => db.ignored_paths().await?,
+        None => HashSet::new(),
+    };
+    let db = match index {
+        Some(db) if !dry_run => db,
+        _ => Db::new(Path::new(":memory:")).await?,
     };
 
-    let db = Arc::new(Db::new(&db_path).await?);
-    let registry = Arc::new(create_classifier_registry());
-    let mover = Arc::new(FileMover::new());
-    let hasher = create_hasher(HashAlgo::Blake3);
+    // Settle moves a previous run left half done before scanning again
+    if !dry_run {
+        let report = repair::repair(&db, registry).await?;
+        if !report.is_empty() {
+            tracing::info!(target: "organizer", "Repaired an interrupted run in {:?}", root_dir);
+            if !options.quiet {
+                print!("{}", report);
+            }
+        }
+    }
 
-    let files = scan_files(root_dir).await?;
-    
-    // Process files with concurrency control
-    process_files_concurrently(files, db.clone(), registry, mover, hasher, root_dir, dry_run).await?;
-    
-    // Commit DB checkpoint once all files are processed
-    db.save().await?;
-    
-    Ok(())
+    if options.snapshot && !dry_run && only.is_none() {
+        let entries = snapshot::capture_async(root_dir).await?;
+        let id = db.insert_snapshot(root_dir, &entries).await?;
+        tracing::info!(target: "organizer", "Recorded snapshot #{} ({} files)", id, entries.len());
+        if !options.quiet {
+            println!("📸 Snapshot #{} recorded ({} files)", id, entries.len());
+        }
+    }
+
+    let scan_start = Instant::now();
+    let (targets, config) = match only {
+        None => (
+            vec![root_dir.to_path_buf()],
+            ScanConfig { exclude: options.exclusions(root_dir), ..options.scan_config() },
+        ),
+        Some(paths) => (
+            paths.to_vec(),
+            ScanConfig { min_depth: 0, max_depth: 0, exclude: options.exclusions(root_dir), ..options.scan_config() },
+        ),
+    };
+    let (mut files, mut outcomes) = scan_files(targets, config).await?;
+    files.retain(|raw| {
+        let reason = if ignored.contains(&raw.path) {
+            SkipReason::Ignored
+        } else if raw.is_dir && options.bundles == BundlePolicy::Skip {
+            SkipReason::Bundle
+        } else {
+            return true;
+        };
+        outcomes.push(FileOutcome::Skipped {
+            src: raw.path.clone(),
+            reason,
+            size: raw.size,
+        });
+        false
+    });
+    // Read every export's messages before any attachment moves away from them
+    let mail = options.mail_attachments.then(|| MailExports::detect(files.iter().map(|raw| raw.path.as_path())));
+    if let Some(mail) = &mail {
+        files.retain(|raw| {
+            if !mail.is_companion(&raw.path) {
+                return true;
+            }
+            outcomes.push(FileOutcome::Skipped {
+                src: raw.path.clone(),
+                reason: SkipReason::Excluded,
+                size: raw.size,
+            });
+            false
+        });
+    }
+    options.order.sort(&mut files);
+    stages.add(Stage::Scan, scan_start.elapsed(), files.len() + outcomes.len());
+
+    Ok(PreparedRoot {
+        root: root_dir.to_path_buf(),
+        dest_root,
+        db: Arc::new(db),
+        files,
+        outcomes,
+        mail,
+    })
 }
 
-/// Scans only top-level files from the root directory (ignores subdirs)
-async fn scan_files(root_dir: &Path) -> Result<Vec<RawFileMetadata>> {
-    let root_dir = root_dir.to_path_buf();
-    
+/// Scans each target with `config` (the root directory's top-level entries by default).
+/// Returns the files to process and outcomes for entries that were skipped or unreadable.
+async fn scan_files(
+    targets: Vec<PathBuf>,
+    config: ScanConfig,
+) -> Result<(Vec<RawFileMetadata>, Vec<FileOutcome>)> {
     let result = tokio::task::spawn_blocking(move || {
-        Scanner::new(root_dir.clone(), Default::default())
-            .filter_ok()
-            .filter(|raw| {
-                // Keep only files directly under `root_dir`
-                raw.path.is_file() &&
-                raw.path.parent() == Some(&root_dir)
-            })
-            .collect::<Vec<_>>()
+        let mut files = Vec::new();
+        let mut outcomes = Vec::new();
+        for target in targets {
+            for res in Scanner::new(target.clone(), config.clone()) {
+                match res {
+                    // Directories only come out of the scan as bundles
+                    Ok(raw) if raw.path.is_file() || raw.is_dir => files.push(raw),
+                    Ok(_) => {}
+                    Err(FileOrganizerError::Skipped { path, reason, size }) => {
+                        outcomes.push(FileOutcome::Skipped { src: path, reason, size });
+                    }
+                    Err(error) => outcomes.push(FileOutcome::Err(FileErrorReport {
+                        path: target.clone(),
+                        stage: Stage::Scan,
+                        error,
+                    })),
+                }
+            }
+        }
+        (files, outcomes)
     })
     .await?;
     
     Ok(result)
 }
 
-/// Processes files concurrently with a semaphore for rate limiting
-async fn process_files_concurrently(
-    files: Vec<RawFileMetadata>,
+/// Everything a per-file task needs, shared across tasks
+struct FileContext {
     db: Arc<Db>,
     registry: Arc<ClassifierRegistry>,
     mover: Arc<FileMover>,
     hasher: Arc<dyn FileHasher + Send + Sync>,
-    root_dir: &Path,
-    dry_run: bool
-) -> Result<()> {
-    let semaphore = Arc::new(Semaphore::new(32)); // Max concurrent files
-    let mut tasks = FuturesUnordered::new();
+    hash_algo: HashAlgo,
+    dest_root: PathBuf,
+    routes: Arc<Routes>,
+    /// Mark the folders files land in at the top of the root
+    in_place: bool,
+    folder_names: FolderNames,
+    dry_run: bool,
+    /// Don't list what a dry run would move
+    quiet: bool,
+    date_patterns: DatePatterns,
+    date_folders: Option<DateFolders>,
+    disabled_categories: BTreeSet<&'static str>,
+    strip_quarantine: BTreeSet<&'static str>,
+    permissions: BTreeMap<&'static str, DestPermissions>,
+    /// Owner directory names by uid, when organizing by owner
+    owners: Option<Mutex<HashMap<u32, String>>>,
+    group: Option<GroupTemplate>,
+    progress: Progress,
+    post: Option<Arc<PostProcessor>>,
+    ocr_names: bool,
+    chunk_signatures: bool,
+    mail: Option<MailExports>,
+    on_conflict: ConflictStrategy,
+    /// What to do with copies of organized files, and the trash folder of
+    /// this run (empty unless trashing)
+    dedupe: Option<(DuplicateAction, PathBuf)>,
+    cancel: CancelToken,
+    control: RunControl,
+    /// Files processed at once
+    concurrency: usize,
+}
 
-    let root_dir = root_dir.to_path_buf();
+impl FileContext {
+    /// The destination root of a file of top-level `category`, and the
+    /// device it is copied onto if that is on another filesystem
+    fn route(&self, category: &str, source: &Path) -> (PathBuf, Option<Arc<Device>>) {
+        let root = self.routes.root_for(category, source, &self.dest_root);
+        let device = self.routes.transfer(source, &root);
+        (root, device)
+    }
 
+    /// Directories before and after the category: `root`, then the owner
+    /// and group layout, split at its `{category}` token
+    fn dirs_for(&self, raw: &RawFileMetadata, root: &Path) -> (PathBuf, PathBuf) {
+        let mut base = root.to_path_buf();
+        if let Some(owners) = &self.owners {
+            let name = match raw.owner {
+                Some(uid) => owners
+                    .lock()
+                    .unwrap()
+                    .entry(uid)
+                    .or_insert_with(|| user_name(uid).unwrap_or_else(|| uid.to_string()))
+                    .clone(),
+                None => "unknown".to_string(),
+            };
+            base.push(name);
+        }
+        let mut after = PathBuf::new();
+        if let Some(group) = &self.group {
+            let provenance = Provenance::detect(&raw.path);
+            base.push(group.expand(&provenance));
+            after = group.expand_after(&provenance);
+        }
+        (base, after)
+    }
+
+    /// Move `raw` to `dest` under a journal entry that stays open until the
+    /// file is indexed, so `stash repair` can settle a run cut short. A copy
+    /// onto another `device` waits for a slot on it, giving up the file's
+    /// processing `permit` meanwhile so other files are classified and hashed.
+    async fn journaled_move(
+        &self,
+        raw: &RawFileMetadata,
+        dest: &Path,
+        hash: &str,
+        device: Option<&Device>,
+        permit: OwnedSemaphorePermit,
+    ) -> Result<i64> {
+        if self.in_place {
+            self.mark_folder(dest).await?;
+        }
+        let id = self.db.journal_move(&raw.path, dest, hash, raw.size).await?;
+        let _slot = match device {
+            Some(device) => {
+                drop(permit);
+                Some(device.queue().await)
+            }
+            None => None,
+        };
+        if let Err(e) = self.mover.move_file(&raw.path, dest).await {
+            self.db.clear_journal(&[id]).await?;
+            return Err(e);
+        }
+        Ok(id)
+    }
+
+    /// Leave an [`IN_PLACE_MARKER`] in the folder at the top of the root that
+    /// `dest` is in, before the file arrives, so a run cut short still keeps
+    /// the folder out of the next scan
+    async fn mark_folder(&self, dest: &Path) -> Result<()> {
+        let Some(top) = dest.strip_prefix(&self.dest_root).ok().and_then(|rel| rel.iter().next()) else {
+            return Ok(());
+        };
+        let folder = self.dest_root.join(top);
+        if folder == dest {
+            return Ok(());
+        }
+        let marker = folder.join(IN_PLACE_MARKER);
+        if !tokio::fs::try_exists(&marker).await? {
+            tokio::fs::create_dir_all(&folder).await?;
+            tokio::fs::write(&marker, "Sorted by stash --in-place; left out of later scans\n").await?;
+        }
+        Ok(())
+    }
+
+    /// Queue the category's post-processing command, if any, on a file just moved to `dest`
+    fn after_move(&self, category: &FileCategory, src: &Path, dest: &Path) {
+        if let Some(post) = &self.post {
+            post.after_move(category.top_level(), src, dest);
+        }
+    }
+
+    /// Give a file just moved from `src` to `dest` its category's mode and
+    /// group, recording what it had for revert. Failing to is only logged.
+    async fn apply_permissions(&self, category: &FileCategory, src: &Path, dest: &Path) -> Result<()> {
+        let Some(permissions) = self.permissions.get(category.top_level()) else {
+            return Ok(());
+        };
+        match permissions.apply(dest) {
+            Ok(Some(original)) => self.db.record_permissions(src, &original).await,
+            Ok(None) => Ok(()),
+            Err(e) => {
+                tracing::warn!(?dest, error = %e, "Could not set permissions");
+                Ok(())
+            }
+        }
+    }
+
+    /// An organized file `raw` is a copy of, checked against its recorded
+    /// hash so a file changed since is never taken for the original
+    async fn organized_copy(&self, raw: &RawFileMetadata, hash: &str) -> Result<Option<PathBuf>> {
+        for entry in self.db.find_by_hash(hash, &raw.path).await? {
+            // The organized file itself, reached through a symlinked folder
+            // or under a name differing in case, is no copy
+            if entry.dest_path == raw.path
+                || !self.mover.fs().exists(&entry.dest_path).await?
+                || same_file(&entry.dest_path, &raw.path)
+            {
+                continue;
+            }
+            match verify_recorded(&entry, &entry.dest_path).await {
+                Ok(()) => return Ok(Some(entry.dest_path)),
+                Err(FileOrganizerError::HashMismatch { .. }) => continue,
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(None)
+    }
+
+    /// Delete or trash `raw`, a copy of the organized file `existing`
+    async fn remove_duplicate(
+        &self,
+        action: DuplicateAction,
+        trash_run: &Path,
+        raw: &RawFileMetadata,
+        existing: PathBuf,
+        hash: &str,
+    ) -> Result<FileOutcome> {
+        let trashed = match action {
+            DuplicateAction::Delete => {
+                self.mover.fs().remove_file(&raw.path).await?;
+                self.db.forget_files(&[&raw.path]).await?;
+                None
+            }
+            DuplicateAction::Trash => {
+                let target = trash::move_to(&self.mover, &raw.path, trash_run).await?;
+                self.db.record_action(DedupeAction::Trash.as_str(), &raw.path, &target, Some(hash), raw.size).await?;
+                Some(target)
+            }
+        };
+        tracing::info!(target: "organizer", "{:?} is a copy of {:?}; {}", raw.path, existing, if trashed.is_some() { "trashed" } else { "deleted" });
+        Ok(FileOutcome::DuplicateSkipped { src: raw.path.clone(), existing, trashed, size: raw.size })
+    }
+
+    /// Record the chunk signature of a large file just organized from `src`
+    /// to `dest`. Failing to is only logged.
+    async fn record_signature(&self, src: &Path, dest: &Path) {
+        let path = dest.to_path_buf();
+        let signed = match tokio::task::spawn_blocking(move || ChunkSignature::of_file(&path)).await {
+            Ok(Ok(signature)) => self.db.record_chunk_signature(src, &signature).await,
+            Ok(Err(e)) => Err(e.into()),
+            Err(e) => Err(e.into()),
+        };
+        if let Err(e) = signed {
+            tracing::warn!(?dest, error = %e, "Could not record chunk signature");
+        }
+    }
+
+    /// Drop the quarantine marker of a file just moved to `dest` if its category is trusted
+    fn release_quarantine(&self, category: &FileCategory, dest: &Path) {
+        if !self.strip_quarantine.contains(category.top_level()) {
+            return;
+        }
+        match quarantine::strip(dest) {
+            Ok(true) => tracing::debug!(?dest, "Removed quarantine marker"),
+            Ok(false) => {}
+            Err(e) => tracing::warn!(?dest, error = %e, "Could not remove quarantine marker"),
+        }
+    }
+}
+
+/// Processes files concurrently with a semaphore for rate limiting.
+/// Advances the caller's progress once per file.
+/// Also returns the moves made, for the run record (empty on dry runs).
+async fn process_files_concurrently(
+    files: Vec<RawFileMetadata>,
+    ctx: Arc<FileContext>,
+) -> Result<(Vec<FileOutcome>, Vec<RunMove>, StageStats, usize)> {
+    let semaphore = Arc::new(Semaphore::new(ctx.concurrency));
+    let mut tasks = FuturesUnordered::new();
+    let dry_run = ctx.dry_run;
     let total = files.len();
-    let label = if dry_run { "Organizing (dry-run)" } else { "Organizing" };
-    let pb = make_progress( total as u64, label);
-
-    for raw_file in files {
-        let permit = semaphore.clone().acquire_owned().await.map_err(|e| {
-            // Convert AcquireError to your error type
-            crate::errors::FileOrganizerError::from(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                format!("Failed to acquire semaphore: {}", e),
-            ))
-        })?;
+    let mut not_started = 0;
+
+    for (started, raw_file) in files.into_iter().enumerate() {
+        // Once cancelled, start nothing new; tasks already running finish.
+        // While paused, wait here before taking the next file.
+        let permit = tokio::select! {
+            biased;
+            _ = ctx.cancel.cancelled() => {
+                not_started = total - started;
+                break;
+            }
+            permit = async {
+                let permit = semaphore.clone().acquire_owned().await;
+                ctx.control.resumed().await;
+                permit
+            } => permit?,
+        };
         
-        let registry_clone = registry.clone();
-        let mover_clone = mover.clone();
-        let hasher_clone = hasher.clone();
-        let root_dir_clone = root_dir.clone();
-        let pb_clone = pb.clone();
+        let ctx_clone = ctx.clone();
+        // Inside the run's span, so every line about the file carries both
+        let span = tracing::info_span!(
+            target: "organizer",
+            "file",
+            path = %raw_file.path.display(),
+            category = field::Empty,
+            classify_ms = field::Empty,
+            move_ms = field::Empty,
+        );
 
         tasks.push(tokio::spawn(async move {
-            let res = process_file(
-                raw_file,
-                registry_clone,
-                mover_clone,
-                hasher_clone,
-                &root_dir_clone,
-                permit,
-                dry_run,
-            ).await;
-
-            pb_clone.inc(1);
+            ctx_clone.progress.file_started(&raw_file.path);
+            let res = process_file(raw_file, &ctx_clone, permit).await;
+
+            let span = tracing::Span::current();
+            for (stage, name) in [(Stage::Classify, "classify_ms"), (Stage::Move, "move_ms")] {
+                if let Some(timing) = res.stages.get(stage) {
+                    span.record(name, timing.duration.as_millis() as u64);
+                }
+            }
+            tracing::debug!(target: "organizer", outcome = res.outcome.kind(), "Processed");
+
+            ctx_clone.progress.file_done(&res.outcome);
+            ctx_clone.progress.inc(1);
             res
-        }));
+        }.instrument(span)));
     }
 
-    let mut results = Vec::new();
+    let mut outcomes = Vec::with_capacity(total);
+    let mut entries = Vec::new();
+    let mut moves = Vec::new();
+    let mut journal = Vec::new();
+    let mut stages = StageStats::default();
 
-    // Await all tasks and propagate errors
+    // Await all tasks; per-file failures are reported as outcomes
     while let Some(join_res) = tasks.next().await {
         match join_res {
-            Ok(Ok(Some(entry))) => results.push(entry),
-            Ok(Ok(_)) => {}
-            Ok(Err(e)) => return Err(e),
+            Ok(processed) => {
+                if !dry_run
+                    && matches!(processed.outcome, FileOutcome::Moved(_) | FileOutcome::Renamed { .. })
+                    && let Some((raw, _, dest, hash)) = &processed.entry
+                {
+                    moves.push(RunMove {
+                        source: raw.path.clone(),
+                        dest: dest.clone(),
+                        hash: Some(hash.clone()),
+                        size: raw.size,
+                    });
+                }
+                outcomes.push(processed.outcome);
+                entries.extend(processed.entry);
+                journal.extend(processed.journal);
+                stages.merge(&processed.stages);
+            }
             Err(join_err) => {
-                pb.finish_and_clear();
                 return Err(FileOrganizerError::from(join_err));
             }
         }
     }
-
     if dry_run {
-        for (raw, category, dest, _) in &results {
-            println!("Would move {:?} (category: {}) → {:?}", raw.path, category, dest);
-       } 
+        if !ctx.quiet {
+            for (raw, category, dest, _) in &entries {
+                println!("Would move {:?} (category: {}) → {:?}", raw.path, category, dest);
+            }
+        }
     } else {
-        db.update_files_batch(&results).await?;
+        let index_start = Instant::now();
+        ctx.db.update_files_batch(&entries).await?;
+        ctx.db.clear_journal(&journal).await?;
+        stages.add(Stage::Index, index_start.elapsed(), entries.len());
     }
 
-    let summary = if dry_run {
-        format!("✅ Dry-run completed: {} files analyzed, {} planned moves", total, results.len())
-    } else {
-        format!("✅ Organize completed: {} files processed", total)
+    Ok((outcomes, moves, stages, not_started))
+}
+
+/// Outcome of one file, the row to index for it and time spent per stage
+struct Processed {
+    outcome: FileOutcome,
+    entry: Option<IndexEntry>,
+    /// Journal entry of the move, closed once `entry` is indexed
+    journal: Option<i64>,
+    stages: StageStats,
+}
+
+impl Processed {
+    fn failed(path: PathBuf, stage: Stage, error: FileOrganizerError, stages: StageStats) -> Self {
+        tracing::warn!(target: "organizer", "{:?} failed at {:?} stage: {}", path, stage, error);
+        Self {
+            outcome: FileOutcome::Err(FileErrorReport { path, stage, error }),
+            entry: None,
+            journal: None,
+            stages,
+        }
+    }
+}
+
+/// Process a single file: classify → resolve conflicts → move
+async fn process_file(
+    raw: RawFileMetadata,
+    ctx: &FileContext,
+    permit: OwnedSemaphorePermit,
+) -> Processed {
+    if raw.is_dir {
+        return process_bundle(raw, ctx, permit).await;
+    }
+    let mut stages = StageStats::default();
+
+    let mut classified = match timed_stage!(async stages, Stage::Classify, { ctx.registry.classify(&raw) }) {
+        Ok(classified) => classified,
+        Err(e) => return Processed::failed(raw.path, Stage::Classify, e, stages),
     };
-    pb.finish_with_message(summary.clone());
 
-    if dry_run {
-        tracing::info!(target: "organizer", "Dry-run completed with {} files analyzed", total);
+    let mail = ctx.mail.as_ref().and_then(|mail| mail.get(&raw.path));
+    if mail.is_some() {
+        classified.category = FileCategory::Documents(DocumentSubcategory::Attachments);
+    }
+    tracing::Span::current().record("category", field::display(&classified.category));
+
+    let top_level = classified.category.top_level();
+    if ctx.disabled_categories.contains(top_level) || ctx.control.is_skipped(top_level) {
+        tracing::debug!(target: "organizer", "Leaving {:?} in place ({} is disabled)", raw.path, classified.category);
+        let outcome = FileOutcome::Skipped {
+            src: raw.path,
+            reason: SkipReason::CategoryDisabled,
+            size: raw.size,
+        };
+        return Processed { outcome, entry: None, journal: None, stages };
+    }
+
+    let mut date_folders = ctx.date_folders.unwrap_or_default();
+    if let Some(date) = raw.path.file_name().and_then(|n| n.to_str()).and_then(|n| ctx.date_patterns.extract(n)) {
+        classified.year = Some(date.year);
+        classified.month = date.month;
+        classified.day = date.day;
+        if ctx.date_folders.is_none() && date.month.is_some() {
+            date_folders = DateFolders::YearMonth;
+        }
+    }
+
+    let (root, device) = ctx.route(top_level, &raw.path);
+    let (base, mut after) = ctx.dirs_for(&raw, &root);
+    if let Some(mail) = mail {
+        // The message's date beats both the timestamps and the file name
+        if let Some(date) = mail.date {
+            classified.year = Some(date.year());
+            classified.month = Some(date.month());
+            classified.day = Some(date.day());
+        }
+        after = Path::new(&mail.sender_folder()).join(after);
+    }
+    let mut builder = PathBuilder::new(&classified).base(&base).after(&after).date_folders(date_folders).names(&ctx.folder_names);
+    if let Some(folder) = ctx.registry.overrides.folder_for(&raw.path) {
+        builder = builder.literal(folder);
+    }
+    let Some(destination) = builder.destination_for(&raw.path) else {
+        let error = FileOrganizerError::InvalidPath(raw.path.clone());
+        return Processed::failed(raw.path, Stage::Classify, error, stages);
+    };
+    let destination = if ctx.ocr_names {
+        ocr_destination(&raw.path, destination).await
     } else {
-        tracing::info!(target: "organizer", "Organize completed with {} files processed", total);
+        destination
+    };
+    let destination = win_path::native_destination(&destination).into_owned();
+
+    if ctx.dry_run {
+        tracing::info!(target: "organizer", "Would move {:?} to {:?}", raw.path, destination);
+        let outcome = FileOutcome::Moved(FileReport {
+            src: raw.path.clone(),
+            dest: destination.clone(),
+            action: MoveAction::Moved,
+            size: raw.size,
+            category: classified.category.top_level(),
+            device: device.map(|device| device.root.clone()),
+        });
+        let entry = (raw, classified.category.to_string(), destination, "dry-run".into());
+        return Processed { outcome, entry: Some(entry), journal: None, stages };
     }
 
-    Ok(())
+    let path = raw.path.clone();
+    let moved = timed_stage!(async stages, Stage::Move, {
+        handle_file_movement(raw, &classified.category, destination, device, permit, ctx)
+    });
+
+    match moved {
+        Ok((outcome, entry, journal)) => {
+            if ctx.chunk_signatures
+                && let Some((raw, _, dest, _)) = &entry
+                && raw.size >= MIN_SIGNED_SIZE
+            {
+                ctx.record_signature(&raw.path, dest).await;
+            }
+            Processed { outcome, entry, journal, stages }
+        }
+        Err(e) => Processed::failed(path, Stage::Move, e, stages),
+    }
 }
 
-/// Process a single file: classify → resolve conflicts → move → update DB
-async fn process_file(
-    raw: RawFileMetadata,
-    registry: Arc<ClassifierRegistry>,
-    mover: Arc<FileMover>,
-    hasher: Arc<dyn FileHasher + Send + Sync>,
-    root_dir: &Path,
-    _permit: OwnedSemaphorePermit,
-    dry_run: bool,
-) -> Result<Option<(RawFileMetadata, String, PathBuf, String)>> {
-    let classified = registry.classify(&raw).await?;
-    let mut destination = PathBuilder::new(&classified)
-        .base(&root_dir.join("Organized"))
-        .build();
+/// Move a project folder or app bundle as one unit, to `Code/Projects` or
+/// `Executables/MacApp` without date directories. It is indexed without a
+/// hash and never replaces or merges into an existing directory.
+async fn process_bundle(raw: RawFileMetadata, ctx: &FileContext, permit: OwnedSemaphorePermit) -> Processed {
+    let mut stages = StageStats::default();
+    let category = match BundleKind::detect(&raw.path) {
+        Some(BundleKind::Project) => FileCategory::Code(CodeSubcategory::Other("Projects".into())),
+        Some(BundleKind::App) => FileCategory::Executables(ExecutableSubcategory::MacApp),
+        None => {
+            let outcome = FileOutcome::Skipped { src: raw.path, reason: SkipReason::IsDir, size: raw.size };
+            return Processed { outcome, entry: None, journal: None, stages };
+        }
+    };
+    tracing::Span::current().record("category", field::display(&category));
 
-    destination.push(raw.path.file_name().unwrap());
+    let top_level = category.top_level();
+    if ctx.disabled_categories.contains(top_level) || ctx.control.is_skipped(top_level) {
+        let outcome = FileOutcome::Skipped { src: raw.path, reason: SkipReason::CategoryDisabled, size: raw.size };
+        return Processed { outcome, entry: None, journal: None, stages };
+    }
 
-    if dry_run {
+    let (root, device) = ctx.route(top_level, &raw.path);
+    let (base, after) = ctx.dirs_for(&raw, &root);
+    let classified = ClassifiedFileMetadata::new(raw.path.clone(), category);
+    let builder = PathBuilder::new(&classified).base(&base).after(&after).date_folders(DateFolders::None).names(&ctx.folder_names);
+    let Some(destination) = builder.destination_for(&raw.path) else {
+        let error = FileOrganizerError::InvalidPath(raw.path.clone());
+        return Processed::failed(raw.path, Stage::Classify, error, stages);
+    };
+    let destination = win_path::native_destination(&destination).into_owned();
+
+    let report = |dest: &Path| FileReport {
+        src: raw.path.clone(),
+        dest: dest.to_path_buf(),
+        action: MoveAction::Moved,
+        size: raw.size,
+        category: top_level,
+        device: device.as_ref().map(|device| device.root.clone()),
+    };
+    if ctx.dry_run {
         tracing::info!(target: "organizer", "Would move {:?} to {:?}", raw.path, destination);
-        return Ok(Some(
-            (raw, classified.category.to_string(), destination, "dry-run".into())
-        ));
+        let outcome = FileOutcome::Moved(report(&destination));
+        let entry = (raw, classified.category.to_string(), destination, "dry-run".into());
+        return Processed { outcome, entry: Some(entry), journal: None, stages };
+    }
+
+    let taken = match ctx.mover.fs().exists(&destination).await {
+        Ok(taken) => taken,
+        Err(e) => return Processed::failed(raw.path, Stage::Move, e.into(), stages),
+    };
+    let final_path = match (taken, ctx.on_conflict) {
+        (false, _) => destination.clone(),
+        (true, ConflictStrategy::Rename) => match resolve_conflict_in(ctx.mover.fs(), &destination, false).await {
+            Ok(path) => path,
+            Err(e) => return Processed::failed(raw.path, Stage::Move, e, stages),
+        },
+        (true, ConflictStrategy::Skip | ConflictStrategy::Overwrite) => {
+            tracing::info!(target: "organizer", "Leaving {:?} in place, {:?} exists", raw.path, destination);
+            let outcome = FileOutcome::Skipped { src: raw.path, reason: SkipReason::Conflict, size: raw.size };
+            return Processed { outcome, entry: None, journal: None, stages };
+        }
+    };
+
+    let moved = timed_stage!(async stages, Stage::Move, {
+        async {
+            let _slot = match &device {
+                Some(device) => {
+                    drop(permit);
+                    Some(device.queue().await)
+                }
+                None => None,
+            };
+            ctx.mover.move_dir(&raw.path, &final_path).await
+        }
+    });
+    if let Err(e) = moved {
+        return Processed::failed(raw.path, Stage::Move, e, stages);
     }
+    tracing::info!(target: "organizer", "Moved {:?} to {:?} whole", raw.path, final_path);
+    let outcome = if final_path == destination {
+        FileOutcome::Moved(report(&final_path))
+    } else {
+        let report = FileReport { action: MoveAction::Renamed(final_path.clone()), ..report(&destination) };
+        FileOutcome::Renamed { report, new_path: final_path.clone() }
+    };
+    let entry = (raw, classified.category.to_string(), final_path, String::new());
+    Processed { outcome, entry: Some(entry), journal: None, stages }
+}
 
-    let entry = handle_file_movement(raw, &classified.category, destination, mover, hasher).await?;
-    Ok(Some(entry))
+/// `destination` renamed after the document's text, see [`crate::ocr::rename`]
+#[cfg(feature = "ocr")]
+async fn ocr_destination(source: &Path, destination: PathBuf) -> PathBuf {
+    crate::ocr::rename(source, destination).await
+}
+
+#[cfg(not(feature = "ocr"))]
+async fn ocr_destination(_source: &Path, destination: PathBuf) -> PathBuf {
+    destination
 }
 
 /// Checks if a file should be skipped (unchanged since last processing)
 #[allow(dead_code)]
 async fn should_skip_file(raw: &RawFileMetadata, db: &Db) -> Result<bool> {
-    if let Some(existing) = db.lookup(&raw.path).await? {
-        if !raw.is_newer_than(&existing) {
+    if let Some(existing) = db.lookup(&raw.path).await?
+        && !raw.is_newer_than(&existing) {
             return Ok(true);
         }
-    }
     Ok(false)
 }
 
-/// Handles file movement with conflict resolution
+/// Handles file movement with conflict resolution. Also returns the index
+/// row for the file, unless it was left in place, and the journal entry of
+/// the move, if one was made.
 async fn handle_file_movement(
     raw: RawFileMetadata,
     category: &FileCategory,
     destination: PathBuf,
-    mover: Arc<FileMover>,
-    hasher: Arc<dyn FileHasher + Send + Sync>,
-) -> Result<(RawFileMetadata, String, PathBuf, String)> {
-    let source_hash = hex::encode(hasher.hash_file(&raw.path).await?);
+    device: Option<Arc<Device>>,
+    permit: OwnedSemaphorePermit,
+    ctx: &FileContext,
+) -> Result<(FileOutcome, Option<IndexEntry>, Option<i64>)> {
+    let (mover, hasher) = (&ctx.mover, &ctx.hasher);
+
+    // Unchanged since the last run (same size and mtime): reuse the stored
+    // hash, unless it was made with another algorithm
+    let cached = ctx.db.cached_hash(&raw.path, raw.size, raw.modified).await?;
+    let source_hash = match cached.filter(|hash| HashAlgo::of(hash) == Some(ctx.hash_algo)) {
+        Some(hash) => hash,
+        None => ctx.hash_algo.encode(&hasher.hash_file(&raw.path).await?),
+    };
     let category_str = category.to_string();
 
-    let destination_exists = tokio::fs::try_exists(&destination).await?;
+    if let Some((action, trash_run)) = &ctx.dedupe
+        && let Some(existing) = ctx.organized_copy(&raw, &source_hash).await?
+    {
+        let outcome = ctx.remove_duplicate(*action, trash_run, &raw, existing, &source_hash).await?;
+        return Ok((outcome, None, None));
+    }
+
+    let destination_exists = mover.fs().exists(&destination).await?;
 
     if !destination_exists {
-        mover.move_file(&raw.path, &destination).await?;
-        Ok((raw, category_str, destination, source_hash))
+        let journal = ctx.journaled_move(&raw, &destination, &source_hash, device.as_deref(), permit).await?;
+        ctx.apply_permissions(category, &raw.path, &destination).await?;
+        ctx.release_quarantine(category, &destination);
+        ctx.after_move(category, &raw.path, &destination);
+        let outcome = FileOutcome::Moved(FileReport {
+            src: raw.path.clone(),
+            dest: destination.clone(),
+            action: MoveAction::Moved,
+            size: raw.size,
+            category: category.top_level(),
+            device: device.as_ref().map(|device| device.root.clone()),
+        });
+        Ok((outcome, Some((raw, category_str, destination, source_hash)), Some(journal)))
     } else {
-        let dest_hash = hex::encode(hasher.hash_file(&destination).await?);
+        let dest_hash = ctx.hash_algo.encode(&hasher.hash_file(&destination).await?);
 
         if source_hash == dest_hash {
             tracing::debug!("Skipping identical file: {:?}", raw.path);
-            Ok((raw, category_str, destination, source_hash))
+            let outcome = FileOutcome::Skipped {
+                src: raw.path.clone(),
+                reason: SkipReason::Duplicate,
+                size: raw.size,
+            };
+            Ok((outcome, Some((raw, category_str, destination, source_hash)), None))
+        } else if ctx.on_conflict == ConflictStrategy::Skip {
+            tracing::debug!("Skipping {:?}: {:?} is taken", raw.path, destination);
+            let outcome = FileOutcome::Skipped {
+                src: raw.path.clone(),
+                reason: SkipReason::Conflict,
+                size: raw.size,
+            };
+            Ok((outcome, None, None))
         } else {
-            let resolved_path = resolve_conflict(&destination, false).await?;
-            mover.move_file(&raw.path, &resolved_path).await?;
-            Ok((raw, category_str, resolved_path, source_hash))
+            let overwrite = ctx.on_conflict == ConflictStrategy::Overwrite;
+            let resolved_path = resolve_conflict_in(mover.fs(), &destination, overwrite).await?;
+            let journal = ctx.journaled_move(&raw, &resolved_path, &source_hash, device.as_deref(), permit).await?;
+            ctx.apply_permissions(category, &raw.path, &resolved_path).await?;
+            ctx.release_quarantine(category, &resolved_path);
+            ctx.after_move(category, &raw.path, &resolved_path);
+            let outcome = if overwrite {
+                FileOutcome::Moved(FileReport {
+                    src: raw.path.clone(),
+                    dest: destination,
+                    action: MoveAction::Moved,
+                    size: raw.size,
+                    category: category.top_level(),
+                    device: device.as_ref().map(|device| device.root.clone()),
+                })
+            } else {
+                FileOutcome::Renamed {
+                    report: FileReport {
+                        src: raw.path.clone(),
+                        dest: destination,
+                        action: MoveAction::Renamed(resolved_path.clone()),
+                        size: raw.size,
+                        category: category.top_level(),
+                        device: device.as_ref().map(|device| device.root.clone()),
+                    },
+                    new_path: resolved_path.clone(),
+                }
+            };
+            Ok((outcome, Some((raw, category_str, resolved_path, source_hash)), Some(journal)))
         }
     }
 }
@@ -254,7 +1804,7 @@ async fn handle_conflict(
     if source_hash == destination_hash {
         Ok((raw, category_str, destination, source_hash))
     } else {
-        let resolved_path = resolve_conflict(&destination, false).await?;
+        let resolved_path = resolve_conflict_in(mover.fs(), &destination, false).await?;
         mover.move_file(&raw.path, &resolved_path).await?;
         Ok((raw, category_str, resolved_path, source_hash))
     }
@@ -263,15 +1813,14 @@ async fn handle_conflict(
 /// Gets the hash of the destination file, checking database first for optimization
 #[allow(dead_code)]
 async fn get_destination_hash(
-    destination: &std::path::PathBuf,
+    destination: &Path,
     db: &Db,
     hasher: &Arc<dyn FileHasher + Send + Sync>,
 ) -> Result<String> {
-    if let Some(metadata) = db.lookup_full(destination).await? {
-        if let Some(h) = metadata.hash {
+    if let Some(metadata) = db.lookup_full(destination).await?
+        && let Some(h) = metadata.hash {
             return Ok(h);
         }
-    }
 
     Ok(hex::encode(hasher.hash_file(destination).await?))
 }
\ No newline at end of file
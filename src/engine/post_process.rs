@@ -0,0 +1,135 @@
+//! Commands run on files once they are organized, per category: tag photos
+//! with exiftool, feed documents to an OCR indexer, and so on
+
+use std::{
+    collections::BTreeMap,
+    ffi::OsString,
+    path::Path,
+    process::Stdio,
+    sync::{Arc, Mutex},
+};
+
+use tokio::{process::Command, sync::Semaphore, task::JoinSet};
+
+/// Commands running at once when the config does not say
+pub const DEFAULT_JOBS: usize = 4;
+
+/// A command line per top-level category, e.g. `Images` →
+/// `["exiftool", "-P", "-overwrite_original", "-Artist=Me", "{dest}"]`
+#[derive(Debug, Clone, Default)]
+pub struct PostCommands {
+    pub commands: BTreeMap<&'static str, Vec<String>>,
+    /// Commands running at once; 0 means [`DEFAULT_JOBS`]
+    pub jobs: usize,
+}
+
+/// Arguments for one file: `{dest}`, `{src}` and `{category}` are replaced,
+/// and the destination is appended when no argument mentions `{dest}`
+pub fn expand_args(args: &[String], src: &Path, dest: &Path, category: &str) -> Vec<OsString> {
+    let mut expanded: Vec<OsString> = args
+        .iter()
+        .map(|arg| match arg.as_str() {
+            "{dest}" => dest.as_os_str().to_os_string(),
+            "{src}" => src.as_os_str().to_os_string(),
+            _ => arg
+                .replace("{dest}", &dest.to_string_lossy())
+                .replace("{src}", &src.to_string_lossy())
+                .replace("{category}", category)
+                .into(),
+        })
+        .collect();
+    if !args.iter().any(|arg| arg.contains("{dest}")) {
+        expanded.push(dest.as_os_str().to_os_string());
+    }
+    expanded
+}
+
+/// Commands started and how many of them failed
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PostReport {
+    pub ran: usize,
+    pub failed: usize,
+}
+
+/// Starts the configured command for each organized file in the background,
+/// a bounded number at a time
+#[derive(Debug)]
+pub struct PostProcessor {
+    commands: BTreeMap<&'static str, Vec<String>>,
+    limit: Arc<Semaphore>,
+    tasks: Mutex<JoinSet<bool>>,
+}
+
+impl PostProcessor {
+    /// `None` when no category has a command
+    pub fn new(config: &PostCommands) -> Option<Self> {
+        let commands: BTreeMap<_, _> = config
+            .commands
+            .iter()
+            .filter(|(_, args)| !args.is_empty())
+            .map(|(category, args)| (*category, args.clone()))
+            .collect();
+        if commands.is_empty() {
+            return None;
+        }
+        let jobs = if config.jobs == 0 { DEFAULT_JOBS } else { config.jobs };
+        Some(Self {
+            commands,
+            limit: Arc::new(Semaphore::new(jobs)),
+            tasks: Mutex::new(JoinSet::new()),
+        })
+    }
+
+    /// Queue the command for `category`, if it has one, on a file just moved to `dest`
+    pub fn after_move(&self, category: &'static str, src: &Path, dest: &Path) {
+        let Some(args) = self.commands.get(category) else {
+            return;
+        };
+        let mut args = expand_args(args, src, dest, category).into_iter();
+        let Some(program) = args.next() else {
+            return;
+        };
+        let mut command = Command::new(program);
+        command.args(args).stdin(Stdio::null()).kill_on_drop(true);
+
+        let limit = self.limit.clone();
+        let dest = dest.to_path_buf();
+        self.tasks.lock().unwrap_or_else(|e| e.into_inner()).spawn(async move {
+            let Ok(_permit) = limit.acquire_owned().await else {
+                return false;
+            };
+            match command.output().await {
+                Ok(output) => {
+                    let stdout = String::from_utf8_lossy(&output.stdout);
+                    let stderr = String::from_utf8_lossy(&output.stderr);
+                    tracing::debug!(
+                        target: "post_process",
+                        "{} on {:?}: {}\nstdout: {}\nstderr: {}",
+                        category, dest, output.status, stdout.trim(), stderr.trim()
+                    );
+                    if !output.status.success() {
+                        tracing::warn!(target: "post_process", "{} command failed on {:?}: {}", category, dest, output.status);
+                    }
+                    output.status.success()
+                }
+                Err(e) => {
+                    tracing::warn!(target: "post_process", "Cannot run {} command on {:?}: {}", category, dest, e);
+                    false
+                }
+            }
+        });
+    }
+
+    /// Wait for every queued command
+    pub async fn finish(&self) -> PostReport {
+        let mut tasks = std::mem::take(&mut *self.tasks.lock().unwrap_or_else(|e| e.into_inner()));
+        let mut report = PostReport::default();
+        while let Some(result) = tasks.join_next().await {
+            report.ran += 1;
+            if !matches!(result, Ok(true)) {
+                report.failed += 1;
+            }
+        }
+        report
+    }
+}
@@ -0,0 +1,269 @@
+use std::{
+    fmt,
+    io::Write,
+    path::Path,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use indicatif::{ProgressBar, ProgressStyle};
+use serde::Serialize;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+
+use crate::stats::FileOutcome;
+
+/// Something happening to a long-running operation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ProgressEvent {
+    Started { label: String, total: u64 },
+    Advanced { done: u64, total: u64 },
+    Finished { done: u64, total: u64 },
+}
+
+/// Receives progress from the engine. Called from many tasks at once.
+pub trait ProgressReporter: Send + Sync {
+    /// A new operation of `total` steps begins
+    fn start(&self, label: &str, total: u64);
+
+    /// `n` more steps are done
+    fn inc(&self, n: u64);
+
+    /// The current operation ended (completed or aborted)
+    fn finish(&self);
+
+    /// Work on the file at `path` begins
+    fn file_started(&self, _path: &Path) {}
+
+    /// A file is done, with what happened to it; called before [`inc`](Self::inc)
+    fn file_done(&self, _outcome: &FileOutcome) {}
+}
+
+/// Cheaply cloneable handle the engine reports progress through
+#[derive(Clone)]
+pub struct Progress(Arc<dyn ProgressReporter>);
+
+impl Progress {
+    pub fn new(reporter: impl ProgressReporter + 'static) -> Self {
+        Self(Arc::new(reporter))
+    }
+
+    /// Styled bar on the terminal
+    pub fn terminal() -> Self {
+        Self::new(TerminalBar::default())
+    }
+
+    /// JSON lines on stderr
+    pub fn json() -> Self {
+        Self::new(Json::new(std::io::stderr()))
+    }
+
+    pub fn silent() -> Self {
+        Self::new(Silent)
+    }
+
+    /// Progress as events on a channel, e.g. for a GUI embedding the library
+    pub fn channel() -> (Self, UnboundedReceiver<ProgressEvent>) {
+        let (tx, rx) = unbounded_channel();
+        (Self::new(Channel::new(tx)), rx)
+    }
+}
+
+impl Default for Progress {
+    fn default() -> Self {
+        Self::silent()
+    }
+}
+
+impl fmt::Debug for Progress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Progress")
+    }
+}
+
+impl std::ops::Deref for Progress {
+    type Target = dyn ProgressReporter;
+
+    fn deref(&self) -> &Self::Target {
+        self.0.as_ref()
+    }
+}
+
+/// How the CLI shows progress
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ProgressMode {
+    #[default]
+    Bar,
+    Json,
+    None,
+}
+
+impl ProgressMode {
+    pub fn reporter(self) -> Progress {
+        match self {
+            ProgressMode::Bar => Progress::terminal(),
+            ProgressMode::Json => Progress::json(),
+            ProgressMode::None => Progress::silent(),
+        }
+    }
+}
+
+impl FromStr for ProgressMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "bar" => Ok(Self::Bar),
+            "json" => Ok(Self::Json),
+            "none" => Ok(Self::None),
+            other => Err(format!("unknown progress mode `{}` (bar, json, none)", other)),
+        }
+    }
+}
+
+/// Step counter shared by the event-based reporters
+#[derive(Debug, Default)]
+struct Counter {
+    done: AtomicU64,
+    total: AtomicU64,
+}
+
+impl Counter {
+    fn reset(&self, total: u64) {
+        self.total.store(total, Ordering::Relaxed);
+        self.done.store(0, Ordering::Relaxed);
+    }
+
+    /// Add `n` and return (done, total)
+    fn add(&self, n: u64) -> (u64, u64) {
+        let done = self.done.fetch_add(n, Ordering::Relaxed) + n;
+        (done, self.total.load(Ordering::Relaxed))
+    }
+
+    fn get(&self) -> (u64, u64) {
+        (self.done.load(Ordering::Relaxed), self.total.load(Ordering::Relaxed))
+    }
+}
+
+/// indicatif bar, drawn while an operation runs
+#[derive(Default)]
+pub struct TerminalBar {
+    bar: Mutex<Option<ProgressBar>>,
+}
+
+impl ProgressReporter for TerminalBar {
+    fn start(&self, label: &str, total: u64) {
+        let pb = ProgressBar::new(total);
+        pb.set_style(
+            ProgressStyle::with_template("[{elapsed_precise}] [{bar:40.magenta/bright_magenta}] {pos}/{len} {msg}")
+                .unwrap()
+        );
+        pb.set_message(label.to_string());
+        pb.enable_steady_tick(Duration::from_millis(200));
+
+        if let Some(old) = self.bar.lock().unwrap().replace(pb) {
+            old.finish_and_clear();
+        }
+    }
+
+    fn inc(&self, n: u64) {
+        if let Some(pb) = self.bar.lock().unwrap().as_ref() {
+            pb.inc(n);
+        }
+    }
+
+    fn finish(&self) {
+        if let Some(pb) = self.bar.lock().unwrap().take() {
+            pb.finish_and_clear();
+        }
+    }
+}
+
+/// One JSON object per line; `advanced` events are throttled
+pub struct Json {
+    out: Mutex<(Box<dyn Write + Send>, Option<Instant>)>,
+    counter: Counter,
+}
+
+impl Json {
+    /// Minimum time between two `advanced` lines
+    const INTERVAL: Duration = Duration::from_millis(100);
+
+    pub fn new(out: impl Write + Send + 'static) -> Self {
+        Self { out: Mutex::new((Box::new(out), None)), counter: Counter::default() }
+    }
+
+    fn emit(&self, event: &ProgressEvent, force: bool) {
+        let mut guard = self.out.lock().unwrap();
+        let (out, last) = &mut *guard;
+        if !force && last.is_some_and(|t| t.elapsed() < Self::INTERVAL) {
+            return;
+        }
+        *last = Some(Instant::now());
+
+        if let Ok(line) = serde_json::to_string(event) {
+            let _ = writeln!(out, "{}", line);
+            let _ = out.flush();
+        }
+    }
+}
+
+impl ProgressReporter for Json {
+    fn start(&self, label: &str, total: u64) {
+        self.counter.reset(total);
+        self.emit(&ProgressEvent::Started { label: label.to_string(), total }, true);
+    }
+
+    fn inc(&self, n: u64) {
+        let (done, total) = self.counter.add(n);
+        self.emit(&ProgressEvent::Advanced { done, total }, done >= total);
+    }
+
+    fn finish(&self) {
+        let (done, total) = self.counter.get();
+        self.emit(&ProgressEvent::Finished { done, total }, true);
+    }
+}
+
+/// Reports nothing
+#[derive(Debug, Default)]
+pub struct Silent;
+
+impl ProgressReporter for Silent {
+    fn start(&self, _label: &str, _total: u64) {}
+    fn inc(&self, _n: u64) {}
+    fn finish(&self) {}
+}
+
+/// Sends every event to a channel; a dropped receiver is ignored
+pub struct Channel {
+    tx: UnboundedSender<ProgressEvent>,
+    counter: Counter,
+}
+
+impl Channel {
+    pub fn new(tx: UnboundedSender<ProgressEvent>) -> Self {
+        Self { tx, counter: Counter::default() }
+    }
+}
+
+impl ProgressReporter for Channel {
+    fn start(&self, label: &str, total: u64) {
+        self.counter.reset(total);
+        let _ = self.tx.send(ProgressEvent::Started { label: label.to_string(), total });
+    }
+
+    fn inc(&self, n: u64) {
+        let (done, total) = self.counter.add(n);
+        let _ = self.tx.send(ProgressEvent::Advanced { done, total });
+    }
+
+    fn finish(&self) {
+        let (done, total) = self.counter.get();
+        let _ = self.tx.send(ProgressEvent::Finished { done, total });
+    }
+}
@@ -0,0 +1,315 @@
+use std::{
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use crate::path_safety::sanitize_component;
+
+/// Where a file came from, as far as the OS or its name tells
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Provenance {
+    /// URL the file was downloaded from
+    pub source_url: Option<String>,
+    /// Page that linked to it
+    pub referrer: Option<String>,
+    /// Application that fetched or created it (e.g. `Safari`, `WhatsApp`)
+    pub source_app: Option<String>,
+    /// Host of the download URL without `www.`
+    pub source_domain: Option<String>,
+}
+
+impl Provenance {
+    /// Read every hint available for `path`: extended attributes on Unix,
+    /// the `Zone.Identifier` stream on Windows, then well-known file names
+    pub fn detect(path: &Path) -> Self {
+        let mut p = platform_hints(path);
+        if p.source_app.is_none() {
+            p.source_app = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .and_then(app_from_name)
+                .map(str::to_string);
+        }
+        if p.source_app.is_none() && p.source_url.is_some() {
+            p.source_app = Some("Browser".to_string());
+        }
+        if p.source_domain.is_none() {
+            p.source_domain = p.source_url.as_deref().or(p.referrer.as_deref()).and_then(domain_of);
+        }
+        p
+    }
+}
+
+/// Host part of a URL, lowercased and without a leading `www.`
+pub fn domain_of(url: &str) -> Option<String> {
+    let rest = url.split_once("://").map(|(_, r)| r)?;
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let host = authority.rsplit('@').next()?;
+    let host = match host.strip_prefix('[') {
+        Some(v6) => v6.split(']').next()?,
+        None => host.split(':').next()?,
+    };
+    let host = host.trim_end_matches('.').to_ascii_lowercase();
+    let host = host.strip_prefix("www.").unwrap_or(&host);
+    (!host.is_empty()).then(|| host.to_string())
+}
+
+/// Parse a Windows `Zone.Identifier` stream: (`HostUrl`, `ReferrerUrl`)
+pub fn parse_zone_identifier(text: &str) -> (Option<String>, Option<String>) {
+    let mut host = None;
+    let mut referrer = None;
+    for line in text.lines() {
+        match line.trim().split_once('=') {
+            Some(("HostUrl", v)) if !v.is_empty() && v != "about:internet" => host = Some(v.to_string()),
+            Some(("ReferrerUrl", v)) if !v.is_empty() => referrer = Some(v.to_string()),
+            _ => {}
+        }
+    }
+    (host, referrer)
+}
+
+/// Agent name from a macOS `com.apple.quarantine` value (`flags;time;agent;uuid`)
+pub fn parse_quarantine(value: &str) -> Option<String> {
+    let agent = value.split(';').nth(2)?.trim();
+    (!agent.is_empty()).then(|| agent.to_string())
+}
+
+/// URLs stored in `kMDItemWhereFroms` (a binary plist array of strings):
+/// download URL first, then the referring page
+pub fn urls_from_where_froms(data: &[u8]) -> Vec<String> {
+    let mut urls = Vec::new();
+    let mut i = 0;
+    while i < data.len() {
+        // ASCII string objects: marker 0x5N with N the length, or 0x5F
+        // followed by an int object (0x1K, then 2^K big-endian bytes)
+        if data[i] & 0xF0 != 0x50 {
+            i += 1;
+            continue;
+        }
+        let (len, start) = match data[i] & 0x0F {
+            0x0F => match data.get(i + 1) {
+                Some(&int) if int & 0xF0 == 0x10 => {
+                    let width = 1usize << (int & 0x0F);
+                    let Some(bytes) = data.get(i + 2..i + 2 + width) else { break };
+                    let len = bytes.iter().fold(0usize, |n, b| (n << 8) | *b as usize);
+                    (len, i + 2 + width)
+                }
+                _ => {
+                    i += 1;
+                    continue;
+                }
+            },
+            n => (n as usize, i + 1),
+        };
+        match data.get(start..start + len).and_then(|s| std::str::from_utf8(s).ok()) {
+            Some(text) if text.starts_with("http") && text.contains("://") => {
+                urls.push(text.to_string());
+                i = start + len;
+            }
+            _ => i += 1,
+        }
+    }
+    urls
+}
+
+/// Apps whose saved files carry a recognisable name prefix
+const NAME_PATTERNS: &[(&str, &str)] = &[
+    ("WhatsApp Image ", "WhatsApp"),
+    ("WhatsApp Video ", "WhatsApp"),
+    ("WhatsApp Audio ", "WhatsApp"),
+    ("signal-", "Signal"),
+    ("telegram-cloud-", "Telegram"),
+    ("photo_", "Telegram"),
+    ("Screenshot ", "Screenshots"),
+    ("Screenshot_", "Screenshots"),
+    ("Screen Shot ", "Screenshots"),
+    ("Screen Recording ", "Screenshots"),
+    ("Zoom_", "Zoom"),
+    ("IMG_", "Camera"),
+    ("PXL_", "Camera"),
+    ("VID_", "Camera"),
+];
+
+/// Guess the app behind a file from its name alone
+pub fn app_from_name(name: &str) -> Option<&'static str> {
+    NAME_PATTERNS
+        .iter()
+        .find(|(prefix, _)| name.starts_with(prefix))
+        .map(|(_, app)| *app)
+}
+
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "macos"))]
+fn xattr(path: &Path, name: &str) -> Option<Vec<u8>> {
+    use std::{ffi::CString, os::unix::ffi::OsStrExt};
+
+    let c_path = CString::new(path.as_os_str().as_bytes()).ok()?;
+    let c_name = CString::new(name).ok()?;
+    let mut buf = vec![0u8; 4096];
+
+    // SAFETY: both strings are NUL-terminated and `buf` is valid for `buf.len()` bytes
+    #[cfg(target_os = "macos")]
+    let len = unsafe {
+        libc::getxattr(c_path.as_ptr(), c_name.as_ptr(), buf.as_mut_ptr().cast(), buf.len(), 0, 0)
+    };
+    #[cfg(not(target_os = "macos"))]
+    let len = unsafe { libc::getxattr(c_path.as_ptr(), c_name.as_ptr(), buf.as_mut_ptr().cast(), buf.len()) };
+
+    if len < 0 {
+        return None;
+    }
+    buf.truncate(len as usize);
+    Some(buf)
+}
+
+#[cfg(target_os = "macos")]
+fn platform_hints(path: &Path) -> Provenance {
+    let mut urls = xattr(path, "com.apple.metadata:kMDItemWhereFroms")
+        .map(|data| urls_from_where_froms(&data))
+        .unwrap_or_default()
+        .into_iter();
+    Provenance {
+        source_url: urls.next(),
+        referrer: urls.next(),
+        source_app: xattr(path, "com.apple.quarantine")
+            .and_then(|v| parse_quarantine(&String::from_utf8_lossy(&v))),
+        source_domain: None,
+    }
+}
+
+/// Chrome, Firefox and wget record the freedesktop `user.xdg.*` attributes
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn platform_hints(path: &Path) -> Provenance {
+    let text = |name| xattr(path, name).map(|v| String::from_utf8_lossy(&v).into_owned());
+    Provenance {
+        source_url: text("user.xdg.origin.url"),
+        referrer: text("user.xdg.referrer.url"),
+        source_app: None,
+        source_domain: None,
+    }
+}
+
+#[cfg(windows)]
+fn platform_hints(path: &Path) -> Provenance {
+    let mut stream = path.as_os_str().to_os_string();
+    stream.push(":Zone.Identifier");
+    let (source_url, referrer) = std::fs::read_to_string(&stream)
+        .map(|text| parse_zone_identifier(&text))
+        .unwrap_or_default();
+    Provenance { source_url, referrer, source_app: None, source_domain: None }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android", target_os = "macos", windows)))]
+fn platform_hints(_path: &Path) -> Provenance {
+    Provenance::default()
+}
+
+/// Directory layout inserted between the destination root and the category,
+/// e.g. `{source_domain}` or `{source_app}/{source_domain}`. A `{category}`
+/// token places the category (with its subcategory and date directories)
+/// elsewhere, e.g. `{category}/{domain}`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupTemplate {
+    parts: Vec<Part>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part {
+    Literal(String),
+    Token(Token),
+}
+
+impl Part {
+    /// Literal text may only separate directories with `/`; anything that
+    /// could name a drive or escape the destination is refused
+    fn literal(text: &str) -> Result<Self, String> {
+        if let Some(c) = text.chars().find(|&c| c.is_control() || ":*?\"<>|".contains(c)) {
+            return Err(format!("`{}` may not be used in a group template", c.escape_default()));
+        }
+        Ok(Part::Literal(text.to_string()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token {
+    SourceApp,
+    SourceDomain,
+    Category,
+}
+
+impl FromStr for GroupTemplate {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = Vec::new();
+        let mut rest = s;
+        while let Some(start) = rest.find('{') {
+            if start > 0 {
+                parts.push(Part::literal(&rest[..start])?);
+            }
+            let end = rest[start..]
+                .find('}')
+                .ok_or_else(|| format!("unclosed `{{` in `{}`", s))?;
+            let token = match &rest[start + 1..start + end] {
+                "source_app" | "app" => Token::SourceApp,
+                "source_domain" | "domain" => Token::SourceDomain,
+                "category" => Token::Category,
+                other => {
+                    return Err(format!("unknown token `{{{}}}` (source_app, source_domain, category)", other))
+                }
+            };
+            if token == Token::Category && parts.contains(&Part::Token(Token::Category)) {
+                return Err(format!("`{{category}}` appears more than once in `{}`", s));
+            }
+            parts.push(Part::Token(token));
+            rest = &rest[start + end + 1..];
+        }
+        if !rest.is_empty() {
+            parts.push(Part::literal(rest)?);
+        }
+        if parts.is_empty() {
+            return Err("empty group template".into());
+        }
+        Ok(Self { parts })
+    }
+}
+
+impl GroupTemplate {
+    /// Relative directory placed before the category for a file with this
+    /// provenance (the whole template without `{category}`); unknown values become `Unknown`
+    pub fn expand(&self, provenance: &Provenance) -> PathBuf {
+        Self::expand_parts(&self.parts[..self.category().unwrap_or(self.parts.len())], provenance)
+    }
+
+    /// Relative directory placed after the category: what follows `{category}`
+    pub fn expand_after(&self, provenance: &Provenance) -> PathBuf {
+        match self.category() {
+            Some(idx) => Self::expand_parts(&self.parts[idx + 1..], provenance),
+            None => PathBuf::new(),
+        }
+    }
+
+    /// Index of the `{category}` token
+    fn category(&self) -> Option<usize> {
+        self.parts.iter().position(|p| *p == Part::Token(Token::Category))
+    }
+
+    fn expand_parts(parts: &[Part], provenance: &Provenance) -> PathBuf {
+        let mut out = String::new();
+        for part in parts {
+            match part {
+                Part::Literal(text) => out.push_str(text),
+                Part::Token(token) => {
+                    let value = match token {
+                        Token::SourceApp => provenance.source_app.as_deref(),
+                        Token::SourceDomain => provenance.source_domain.as_deref(),
+                        Token::Category => continue,
+                    };
+                    out.push_str(&sanitize_component(value.unwrap_or("Unknown")));
+                }
+            }
+        }
+        out.split(['/', '\\'])
+            .filter(|c| !c.is_empty() && *c != "." && *c != "..")
+            .collect()
+    }
+}
@@ -0,0 +1,190 @@
+//! `stash query`: read-only filters over the files the index knows about,
+//! answering "what did you do with my tax PDFs?" without opening sqlite3.
+//! The filters become a single `SELECT` on the `files` table, see
+//! [`Db::query_files`].
+
+use std::{fmt, path::{Path, PathBuf}, str::FromStr};
+
+use chrono::{DateTime, Local, NaiveDate, TimeZone};
+use colored::*;
+use serde::Serialize;
+
+use crate::{
+    errors::Result,
+    index::{Db, DbFileEntry},
+    stats::format_size,
+    utils::{remap_path, PathMapping},
+};
+
+/// Which indexed files to list; unset filters match everything
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileQuery {
+    /// Category, or top-level category with everything under it, ignoring case
+    pub category: Option<String>,
+    /// Any of these extensions, without the dot, ignoring case
+    pub extensions: Vec<String>,
+    /// Glob or substring of the original or organized path, as in [`Db::search`]
+    pub name: Option<String>,
+    pub min_size: Option<u64>,
+    pub max_size: Option<u64>,
+    /// Modified on or after this local day
+    pub since: Option<NaiveDate>,
+    /// Modified on or before this local day
+    pub until: Option<NaiveDate>,
+    /// Most files listed, largest first when set
+    pub limit: Option<usize>,
+    pub scope: IndexScope,
+}
+
+/// Whether a file was moved by stash or only indexed where it is (`stash index`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileState {
+    Organized,
+    Unorganized,
+}
+
+/// Which part of the index to look at; the default is all of it
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IndexScope {
+    /// Only files whose current location is under this directory
+    pub under: Option<PathBuf>,
+    pub state: Option<FileState>,
+    /// Prefix rewrites for recorded paths, for when the drive is mounted
+    /// elsewhere now; `under` and the paths listed use the new mount point
+    pub mappings: Vec<PathMapping>,
+}
+
+impl IndexScope {
+    /// Files stash moved, anywhere
+    pub fn organized() -> Self {
+        Self { state: Some(FileState::Organized), ..Default::default() }
+    }
+
+    /// `under` as the index recorded it, before the drive was remounted
+    pub fn recorded_under(&self) -> Option<PathBuf> {
+        let recorded: Vec<PathMapping> = self.mappings.iter().map(PathMapping::reversed).collect();
+        self.under.as_deref().map(|dir| remap_path(dir, &recorded))
+    }
+
+    /// A recorded `path` where it is now
+    pub fn current_path(&self, path: &Path) -> PathBuf {
+        remap_path(path, &self.mappings)
+    }
+
+    /// What `stash stats` and `stash query` print about the scope, if it is not everything
+    pub fn describe(&self) -> Option<String> {
+        let state = self.state.map(|state| match state {
+            FileState::Organized => "organized files",
+            FileState::Unorganized => "unorganized files",
+        });
+        match (&self.under, state) {
+            (Some(dir), state) => Some(format!("{} under {}", state.unwrap_or("files"), dir.display())),
+            (None, state) => state.map(str::to_string),
+        }
+    }
+}
+
+impl FileQuery {
+    /// Matching files, by original path (largest first with a limit)
+    pub async fn run(&self, db: &Db) -> Result<Vec<QueryRow>> {
+        Ok(db
+            .query_files(self)
+            .await?
+            .into_iter()
+            .map(|entry| QueryRow {
+                path: self.scope.current_path(&entry.path),
+                dest: self.scope.current_path(&entry.dest_path),
+                ..QueryRow::from(entry)
+            })
+            .collect())
+    }
+}
+
+/// Unix time at the local midnight starting `day`
+pub(crate) fn day_start(day: NaiveDate) -> i64 {
+    let midnight = day.and_hms_opt(0, 0, 0).unwrap_or_default();
+    Local
+        .from_local_datetime(&midnight)
+        .earliest()
+        .map_or_else(|| midnight.and_utc().timestamp(), |time| time.timestamp())
+}
+
+/// Parse a `YYYY-MM-DD` day
+pub fn parse_day(input: &str) -> std::result::Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(input.trim(), "%Y-%m-%d").map_err(|_| format!("invalid date `{}` (expected YYYY-MM-DD)", input))
+}
+
+/// How `stash query` prints its results
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QueryFormat {
+    #[default]
+    Table,
+    Json,
+}
+
+impl FromStr for QueryFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "table" => Ok(Self::Table),
+            "json" => Ok(Self::Json),
+            other => Err(format!("unknown query format `{}` (table, json)", other)),
+        }
+    }
+}
+
+/// One indexed file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct QueryRow {
+    pub path: PathBuf,
+    pub dest: PathBuf,
+    pub size: u64,
+    pub category: Option<String>,
+    /// RFC 3339, local time
+    pub modified: Option<String>,
+    pub hash: Option<String>,
+}
+
+impl From<DbFileEntry> for QueryRow {
+    fn from(entry: DbFileEntry) -> Self {
+        Self {
+            path: entry.path,
+            dest: entry.dest_path,
+            size: entry.size,
+            category: entry.category,
+            modified: entry.modified.map(|time| DateTime::<Local>::from(time).to_rfc3339()),
+            hash: entry.hash,
+        }
+    }
+}
+
+/// Rows printed as a table with a total at the end
+pub struct QueryTable<'a>(pub &'a [QueryRow]);
+
+impl fmt::Display for QueryTable<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.is_empty() {
+            return writeln!(f, "No matching files.");
+        }
+        writeln!(f, "{}", format!("{:>10}  {:<10}  {:<18}  {}", "Size", "Modified", "Category", "File").bold().blue())?;
+        for row in self.0 {
+            let day = row.modified.as_deref().and_then(|time| time.get(..10)).unwrap_or("");
+            let file = if row.dest == row.path {
+                row.path.display().to_string()
+            } else {
+                format!("{} → {}", row.path.display(), row.dest.display())
+            };
+            writeln!(
+                f,
+                "{:>10}  {:<10}  {:<18}  {}",
+                format_size(row.size),
+                day,
+                row.category.as_deref().unwrap_or("-"),
+                file
+            )?;
+        }
+        let bytes: u64 = self.0.iter().map(|row| row.size).sum();
+        writeln!(f, "{} files, {}", self.0.len().to_string().green(), format_size(bytes))
+    }
+}
@@ -0,0 +1,125 @@
+//! Finish or undo the work of a run that stopped part way (crash, power loss,
+//! kill -9). Organize moves are journaled before they happen and dropped from
+//! the journal once the file is indexed, so anything left in it was cut short.
+
+use std::{fmt, path::PathBuf};
+
+use colored::*;
+
+use crate::{
+    errors::Result,
+    index::{ActionRecord, Db},
+    metadata::FileCategory,
+    organizer::IndexEntry,
+    registry::ClassifierRegistry,
+    reverter::recover_journal,
+    scanner::{ScanConfig, Scanner, SymlinkPolicy},
+};
+
+/// What [`repair`] did
+#[derive(Debug, Default)]
+pub struct RepairReport {
+    /// Files that reached their destination but were never indexed; now indexed
+    pub completed: Vec<PathBuf>,
+    /// Partial copies removed from the destination; the original stays put
+    pub rolled_back: Vec<PathBuf>,
+    /// Journaled moves that never started
+    pub abandoned: usize,
+    /// Journaled files found in neither place
+    pub missing: Vec<PathBuf>,
+    /// Interrupted revert moves settled, see [`recover_journal`]
+    pub reverts: usize,
+}
+
+impl RepairReport {
+    /// True when there was nothing to settle
+    pub fn is_empty(&self) -> bool {
+        self.completed.is_empty()
+            && self.rolled_back.is_empty()
+            && self.abandoned == 0
+            && self.missing.is_empty()
+            && self.reverts == 0
+    }
+}
+
+impl fmt::Display for RepairReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", "Repair".bold().blue())?;
+        writeln!(f, "  Completed:   {}", self.completed.len().to_string().green())?;
+        writeln!(f, "  Rolled back: {}", self.rolled_back.len().to_string().yellow())?;
+        writeln!(f, "  Not started: {}", self.abandoned)?;
+        writeln!(f, "  Reverts:     {}", self.reverts)?;
+        writeln!(f, "  Missing:     {}", self.missing.len().to_string().red())?;
+        for path in &self.completed {
+            writeln!(f, "    indexed: {}", path.display())?;
+        }
+        for path in &self.rolled_back {
+            writeln!(f, "    kept in place: {}", path.display())?;
+        }
+        for path in &self.missing {
+            writeln!(f, "    missing: {}", path.display())?;
+        }
+        Ok(())
+    }
+}
+
+/// Settle every journaled move. A file found only at its destination is
+/// indexed there; one still at its source keeps it, and any partial copy at
+/// the destination is removed.
+pub async fn repair(db: &Db, registry: &ClassifierRegistry) -> Result<RepairReport> {
+    let mut report = RepairReport::default();
+    let pending = db.pending_moves().await?;
+    let mut entries = Vec::new();
+
+    for record in &pending {
+        let source_exists = tokio::fs::try_exists(&record.path).await?;
+        let dest_exists = tokio::fs::try_exists(&record.target).await?;
+        match (source_exists, dest_exists) {
+            (false, true) => {
+                entries.push(index_entry(record, registry).await?);
+                report.completed.push(record.path.clone());
+            }
+            (true, true) => {
+                tokio::fs::remove_file(&record.target).await?;
+                tracing::info!(target: "repair", "Removed partial copy {:?} of {:?}", record.target, record.path);
+                report.rolled_back.push(record.path.clone());
+            }
+            (true, false) => report.abandoned += 1,
+            (false, false) => {
+                tracing::warn!(target: "repair", "{:?} is in neither {:?} nor its source", record.path, record.target);
+                report.missing.push(record.path.clone());
+            }
+        }
+    }
+
+    db.update_files_batch(&entries).await?;
+    let ids: Vec<i64> = pending.iter().map(|r| r.id).collect();
+    db.clear_journal(&ids).await?;
+
+    report.reverts = recover_journal(db).await?;
+    Ok(report)
+}
+
+/// The index row the interrupted run would have written: the destination's
+/// metadata under the original path
+async fn index_entry(
+    record: &ActionRecord,
+    registry: &ClassifierRegistry,
+) -> Result<IndexEntry> {
+    let config = ScanConfig {
+        max_depth: 0,
+        include_hidden: true,
+        symlinks: SymlinkPolicy::MoveLink,
+        ..Default::default()
+    };
+    let mut raw = Scanner::new(&record.target, config)
+        .next()
+        .transpose()?
+        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, format!("{:?} vanished", record.target)))?;
+    let category = match registry.classify(&raw).await {
+        Ok(classified) => classified.category.to_string(),
+        Err(_) => FileCategory::Others.to_string(),
+    };
+    raw.path = record.path.clone();
+    Ok((raw, category, record.target.clone(), record.hash.clone().unwrap_or_default()))
+}
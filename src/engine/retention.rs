@@ -0,0 +1,110 @@
+//! Age limits per category, e.g. backups older than 90 days. `stash prune`
+//! moves organized files past their limit to the trash, where
+//! `stash dedupe undo` can bring them back.
+
+use std::{
+    collections::BTreeMap,
+    fmt,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use colored::*;
+
+use crate::{
+    dedupe::DedupeAction,
+    errors::{FileOrganizerError, Result},
+    file_mover::FileMover,
+    index::Db,
+    stats::format_size,
+    trash,
+};
+
+/// What [`prune`] did
+#[derive(Debug, Default)]
+pub struct PruneReport {
+    /// Files trashed (or that would be, in a dry run)
+    pub pruned: Vec<PathBuf>,
+    pub bytes: u64,
+    /// Files still within their category's limit
+    pub kept: usize,
+    pub errors: Vec<(PathBuf, FileOrganizerError)>,
+}
+
+impl fmt::Display for PruneReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", "Prune".bold().blue())?;
+        writeln!(f, "  Pruned:  {} files, {}", self.pruned.len().to_string().green(), format_size(self.bytes))?;
+        writeln!(f, "  Kept:    {} files", self.kept)?;
+        writeln!(f, "  Errors:  {} files", self.errors.len().to_string().red())?;
+        for (path, error) in &self.errors {
+            writeln!(f, "    {}: {}", path.display(), error)?;
+        }
+        Ok(())
+    }
+}
+
+/// Trash every indexed file whose top-level category has a limit in
+/// `max_age` and whose modification time is older than it. Trashed files go
+/// to a per-run folder under `trash_root` and are recorded like dedupe's.
+pub async fn prune(
+    db: &Db,
+    max_age: &BTreeMap<&'static str, Duration>,
+    dry_run: bool,
+    trash_root: &Path,
+) -> Result<PruneReport> {
+    let mut report = PruneReport::default();
+    if max_age.is_empty() {
+        return Ok(report);
+    }
+    let mover = FileMover::new();
+    let trash_dir = if dry_run { None } else { Some(trash::run_dir(trash_root)?) };
+    let now = SystemTime::now();
+
+    for entry in db.get_all_files().await? {
+        let Some(limit) = entry
+            .category
+            .as_deref()
+            .map(|category| category.split("::").next().unwrap_or(category))
+            .and_then(|top| max_age.get(top))
+        else {
+            continue;
+        };
+        // Already pruned, deduplicated or moved by hand
+        let Ok(metadata) = tokio::fs::metadata(&entry.dest_path).await else {
+            continue;
+        };
+        let age = metadata
+            .modified()
+            .ok()
+            .and_then(|modified| now.duration_since(modified).ok())
+            .unwrap_or_default();
+        if age <= *limit {
+            report.kept += 1;
+            continue;
+        }
+
+        if let Some(trash_dir) = &trash_dir {
+            match trash::move_to(&mover, &entry.dest_path, trash_dir).await {
+                Ok(target) => {
+                    let kind = DedupeAction::Trash.as_str();
+                    db.record_action(kind, &entry.dest_path, &target, entry.hash.as_deref(), metadata.len()).await?;
+                    tracing::info!(target: "retention", "Trashed {:?} ({} days old)", entry.dest_path, age.as_secs() / 86_400);
+                }
+                Err(e) => {
+                    report.errors.push((entry.dest_path, e));
+                    continue;
+                }
+            }
+        } else {
+            println!("Would trash {:?} ({} days old)", entry.dest_path, age.as_secs() / 86_400);
+        }
+        report.bytes += metadata.len();
+        report.pruned.push(entry.dest_path);
+    }
+
+    if let Some(trash_dir) = &trash_dir {
+        trash::discard_if_empty(trash_dir).await;
+    }
+    Ok(report)
+}
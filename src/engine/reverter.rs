@@ -1,8 +1,10 @@
-use std::{collections::HashSet, path::Path, sync::Arc};
+use std::{collections::HashSet, fmt, io::Write, path::{Path, PathBuf}, str::FromStr, sync::Arc};
+
+use colored::*;
 use tokio::fs;
 
 use crate::{
-    conflict_resolver::resolve_conflict, errors::{FileOrganizerError, Result}, file_mover::FileMover, hasher::{create_hasher, FileHasher, HashAlgo}, index::{Db, DbFileEntry}, utils::{default_db_path, make_progress}
+    conflict_resolver::{resolve_conflict_claimed, resolve_conflict_in}, errors::{FileOrganizerError, Result}, file_mover::FileMover, exclusions::ExcludeGlobs, guard::Guard, instance_lock::{default_lock_dir, InstanceLock}, hasher::{create_hasher, rehash_like, FileHasher, HashAlgo}, index::{Db, DbFileEntry}, manifest::only_own_files_left, permissions, progress::Progress, utils::{default_db_path, remap_path, to_unix, PathMapping}
 };
 
 /// Iteratively remove empty directories under `root` (post-order).
@@ -25,13 +27,11 @@ pub async fn cleanup_empty_dirs(root: &Path) -> Result<()> {
                 }
             }
         } else {
-            // Second time: check if empty and remove
-            let mut entries = fs::read_dir(&dir).await?;
-            if entries.next_entry().await?.is_none() && dir != root {
-                if fs::remove_dir(&dir).await.is_ok() {
+            // Second time: check if empty (stale manifest or marker aside) and remove
+            if dir != root && only_own_files_left(&dir).await?
+                && fs::remove_dir(&dir).await.is_ok() {
                     tracing::info!("Removed empty dir: {:?}", dir);
                 }
-            }
         }
     }
 
@@ -39,94 +39,544 @@ pub async fn cleanup_empty_dirs(root: &Path) -> Result<()> {
 }
 
 
-/// Reverts previously organized files back to their original locations.
+/// Which recorded files a revert run touches
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum RevertScope {
+    /// Files whose destination is inside the root directory
+    #[default]
+    Destination,
+    /// Every recorded file, wherever its destination lives
+    All,
+    /// Files that originally lived under this directory
+    OriginalRoot(PathBuf),
+}
+
+/// Single files or globs to revert instead of everything in scope. A file
+/// matches by its recorded destination or original path; a directory takes
+/// every file below it. Globs work like `exclude` ones, on either path.
+#[derive(Debug, Clone, Default)]
+pub struct RevertSelection {
+    pub files: Vec<PathBuf>,
+    pub globs: ExcludeGlobs,
+}
+
+impl RevertSelection {
+    /// Nothing selected: every file in scope is reverted
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty() && self.globs.is_empty()
+    }
+
+    pub fn matches(&self, original: &Path, dest: &Path) -> bool {
+        self.is_empty()
+            || [original, dest].iter().any(|path| {
+                self.files.iter().any(|file| path.starts_with(file)) || self.globs.matches(path)
+            })
+    }
+}
+
+/// What to do with a file whose content changed since it was organized
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MismatchPolicy {
+    /// Leave it where it is and report it
+    #[default]
+    Skip,
+    /// Move it back anyway
+    Restore,
+    /// Ask on the terminal for each such file
+    Ask,
+}
+
+/// What to do when a different file already exists at a file's original
+/// path; the same choices as organize's `--on-conflict`, plus asking
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    /// Restore it under a numbered name (`report_1.pdf`)
+    #[default]
+    Rename,
+    /// Leave it where it was organized to
+    Skip,
+    /// Replace the file at the original path
+    Overwrite,
+    /// Ask on the terminal for each such file
+    Prompt,
+}
+
+impl FromStr for ConflictPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "rename" => Ok(Self::Rename),
+            "skip" => Ok(Self::Skip),
+            "overwrite" => Ok(Self::Overwrite),
+            "prompt" => Ok(Self::Prompt),
+            other => Err(format!("unknown conflict strategy `{}` (rename, skip, overwrite, prompt)", other)),
+        }
+    }
+}
+
+impl FromStr for MismatchPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "skip" => Ok(Self::Skip),
+            "restore" => Ok(Self::Restore),
+            "ask" => Ok(Self::Ask),
+            other => Err(format!("unknown mismatch policy `{}` (skip, restore, ask)", other)),
+        }
+    }
+}
+
+/// Options controlling a revert run
+#[derive(Debug, Clone)]
+pub struct RevertOptions {
+    /// Remove directories left empty after reverting
+    pub cleanup: bool,
+    /// Read the portable database stored inside `root_dir`
+    pub portable: bool,
+    /// Prefix rewrites for recorded paths, for when the drive is mounted elsewhere now
+    pub mappings: Vec<PathMapping>,
+    pub scope: RevertScope,
+    /// Only these files within the scope
+    pub select: RevertSelection,
+    /// Files whose content no longer matches the recorded hash
+    pub on_mismatch: MismatchPolicy,
+    /// Files whose original path is taken by a different file
+    pub on_conflict: ConflictPolicy,
+    /// Roots refused, e.g. `/` or the home directory
+    pub guard: Guard,
+    /// When another run is working on the directories, wait for it instead
+    /// of failing with [`FileOrganizerError::AlreadyRunning`]
+    pub wait_for_lock: bool,
+    /// Where the lock files of runs go (defaults to [`default_lock_dir`])
+    pub lock_dir: Option<PathBuf>,
+    /// Where per-file progress goes (silent by default)
+    pub progress: Progress,
+}
+
+impl Default for RevertOptions {
+    fn default() -> Self {
+        Self {
+            cleanup: true,
+            portable: false,
+            mappings: Vec::new(),
+            scope: RevertScope::Destination,
+            select: RevertSelection::default(),
+            on_mismatch: MismatchPolicy::Skip,
+            on_conflict: ConflictPolicy::Rename,
+            guard: Guard::default(),
+            wait_for_lock: false,
+            lock_dir: None,
+            progress: Progress::default(),
+        }
+    }
+}
+
+/// What a revert run did
+#[derive(Debug, Default)]
+pub struct RevertReport {
+    /// Recorded files in scope
+    pub candidates: usize,
+    pub moved: usize,
+    /// Recorded destination no longer exists
+    pub missing: usize,
+    /// Destinations of recorded files that were outside the scope
+    pub out_of_scope: Vec<PathBuf>,
+    /// [`FileOrganizerError::HashMismatch`] for each changed file left in place
+    pub changed: Vec<FileOrganizerError>,
+    /// Changed files moved back anyway
+    pub changed_restored: usize,
+    /// Files restored under a new name, as (original path, restored as)
+    pub renamed: Vec<(PathBuf, PathBuf)>,
+    /// Files left in place because their original path was taken
+    pub conflicts_skipped: usize,
+    /// Files that replaced the one at their original path
+    pub overwritten: usize,
+}
+
+/// Out-of-scope or changed files listed by name before summarizing the rest
+const MAX_OUT_OF_SCOPE_SHOWN: usize = 10;
+
+impl fmt::Display for RevertReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "♻️ Revert completed: {} moved, {} skipped ({} missing), {} candidates.",
+            self.moved,
+            self.candidates - self.moved,
+            self.missing,
+            self.candidates
+        )?;
+        if !self.changed.is_empty() {
+            writeln!(f, "{} files changed since they were organized and were left alone:",
+                self.changed.len().to_string().yellow()
+            )?;
+            for error in self.changed.iter().take(MAX_OUT_OF_SCOPE_SHOWN) {
+                writeln!(f, "  {}", error)?;
+            }
+            if self.changed.len() > MAX_OUT_OF_SCOPE_SHOWN {
+                writeln!(f, "  … and {} more", self.changed.len() - MAX_OUT_OF_SCOPE_SHOWN)?;
+            }
+            writeln!(f, "Use --on-mismatch restore (or ask) to move them back anyway.")?;
+        }
+        if self.changed_restored > 0 {
+            writeln!(f, "{} changed files were restored anyway.", self.changed_restored)?;
+        }
+        if !self.renamed.is_empty() {
+            writeln!(f, "{} files were restored under a new name, their original name being taken:",
+                self.renamed.len().to_string().yellow()
+            )?;
+            for (original, restored) in self.renamed.iter().take(MAX_OUT_OF_SCOPE_SHOWN) {
+                writeln!(f, "  {} -> {}", original.display(), restored.display())?;
+            }
+            if self.renamed.len() > MAX_OUT_OF_SCOPE_SHOWN {
+                writeln!(f, "  … and {} more", self.renamed.len() - MAX_OUT_OF_SCOPE_SHOWN)?;
+            }
+        }
+        if self.conflicts_skipped > 0 {
+            writeln!(f, "{} files were left in place, their original name being taken.", self.conflicts_skipped)?;
+        }
+        if self.overwritten > 0 {
+            writeln!(f, "{} files replaced the file at their original path.", self.overwritten)?;
+        }
+        if self.out_of_scope.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(f, "{} recorded files are outside the revert scope and were left alone:",
+            self.out_of_scope.len().to_string().yellow()
+        )?;
+        for path in self.out_of_scope.iter().take(MAX_OUT_OF_SCOPE_SHOWN) {
+            writeln!(f, "  {}", path.display())?;
+        }
+        if self.out_of_scope.len() > MAX_OUT_OF_SCOPE_SHOWN {
+            writeln!(f, "  … and {} more", self.out_of_scope.len() - MAX_OUT_OF_SCOPE_SHOWN)?;
+        }
+        writeln!(f, "Use --all or --original-root <DIR> to include them.")
+    }
+}
+
+/// Reverts previously organized files in `root_dir` back to their original locations.
 pub async fn revert_files(
     root_dir: &Path, 
-    cleanup: bool,
-) -> Result<()> {
-    validate_dir(&root_dir).await?;
+    options: &RevertOptions,
+) -> Result<RevertReport> {
+    revert(Some(root_dir), options).await
+}
+
+/// Reverts recorded files selected by `options.scope`. `root_dir` is where a
+/// portable index lives and what gets cleaned up; only
+/// [`RevertScope::Destination`] and portable indexes require it.
+pub async fn revert(
+    root_dir: Option<&Path>,
+    options: &RevertOptions,
+) -> Result<RevertReport> {
+    if let Some(root_dir) = root_dir {
+        validate_dir(root_dir).await?;
+        options.guard.check(root_dir)?;
+    }
+    if let RevertScope::OriginalRoot(dir) = &options.scope {
+        options.guard.check(dir)?;
+    }
+    // The directories files are moved out of and back into
+    let locked = root_dir.into_iter().chain(match &options.scope {
+        RevertScope::OriginalRoot(dir) => Some(dir.as_path()),
+        _ => None,
+    });
+    let lock_dir = match &options.lock_dir {
+        Some(dir) => dir.clone(),
+        None => default_lock_dir().await?,
+    };
+    let _lock = InstanceLock::acquire(&lock_dir, locked, options.wait_for_lock).await?;
 
-    let db_path = default_db_path().await?; 
-    let db = Arc::new(Db::new(&db_path).await?);
+    let db = match root_dir {
+        Some(root_dir) if options.portable => Db::open_portable(root_dir).await?,
+        None if options.portable => {
+            return Err(FileOrganizerError::Other("A portable revert needs the root directory".into()));
+        }
+        _ => Db::new(&default_db_path().await?).await?,
+    };
+    let db = Arc::new(db);
     let mover = Arc::new(FileMover::new());
     let hasher = create_hasher(HashAlgo::Blake3);
 
+    let in_scope = |original: &Path, dest: &Path| match (&options.scope, root_dir) {
+        (RevertScope::All, _) => true,
+        (RevertScope::OriginalRoot(dir), _) => original.starts_with(dir),
+        (RevertScope::Destination, Some(root_dir)) => dest.starts_with(root_dir),
+        (RevertScope::Destination, None) => false,
+    };
+
+    // Files an earlier revert restored under a new name are where they belong
+    let restored = db.revert_targets().await?;
+
     // Deduplicate by dest_path
     let mut seen = HashSet::new();
-    let files: Vec<DbFileEntry> = db.get_all_files()
-        .await?
-        .into_iter()
-        .filter(|f| f.dest_path.starts_with(root_dir)) // Only revert inside root_dir
-        .filter(|f| seen.insert(f.dest_path.clone()))
-        .collect();
+    let mut report = RevertReport::default();
+    let mut files: Vec<DbFileEntry> = Vec::new();
+    for mut f in db.get_all_files().await? {
+        f.dest_path = remap_path(&f.dest_path, &options.mappings);
+        if !seen.insert(f.dest_path.clone()) {
+            continue;
+        }
+        let original = remap_path(&f.path, &options.mappings);
+        if !options.select.matches(&original, &f.dest_path) {
+            continue;
+        }
+        if in_scope(&original, &f.dest_path) {
+            files.push(f);
+        } else if f.dest_path != original && !restored.contains(&f.dest_path) {
+            // Already-reverted records are not worth reporting
+            report.out_of_scope.push(f.dest_path);
+        }
+    }
 
     let total = files.len();
-    let pb = make_progress(total as u64, "Reverting");
+    report.candidates = total;
+    let progress = &options.progress;
 
-    let mut moved: usize = 0;
+    recover_journal(&db).await?;
+    let permissions = db.original_permissions().await?;
 
-    for file in &files {
-        let source = file.dest_path.clone();
-        let original = file.path.clone();
+    progress.start("Reverting", total as u64);
+    let mut reverted_dirs = HashSet::new();
+    let mut planned = Vec::with_capacity(REVERT_BATCH);
+    let mut claimed = HashSet::new();
+    let mut pending = files.iter().peekable();
 
-        if !tokio::fs::try_exists(&source).await? {
-            tracing::warn!("Missing file at destination, skipping: {:?}", source);
-            pb.inc(1);
-            continue;
-        }
+    while pending.peek().is_some() {
+        // Decide what this chunk moves before touching anything
+        planned.clear();
+        claimed.clear();
+        for file in pending.by_ref() {
+            let source = file.dest_path.clone();
+            let original = remap_path(&file.path, &options.mappings);
 
-        if source == original {
-            tracing::debug!("Already at original path, skipping: {:?}", source);
-            pb.inc(1);
-            continue;
-        }
+            if !mover.fs().exists(&source).await? {
+                tracing::warn!("Missing file at destination, skipping: {:?}", source);
+                report.missing += 1;
+                progress.inc(1);
+                continue;
+            }
 
-        if should_skip_file(&source, &original, hasher.clone(), &pb).await? {
-            continue;
-        }
+            if source == original || restored.contains(&source) {
+                tracing::debug!("Already at original path, skipping: {:?}", source);
+                progress.inc(1);
+                continue;
+            }
 
-        // If original already exists, resolve conflict
-        let final_path = if tokio::fs::try_exists(&original).await? {
-            resolve_conflict(&original, true).await?
-        } else {
-            original
-        };
+            match verify_recorded(file, &source).await {
+                Ok(()) => {}
+                Err(mismatch @ FileOrganizerError::HashMismatch { .. }) => {
+                    tracing::warn!(target: "reverter", "{}", mismatch);
+                    let restore = match options.on_mismatch {
+                        MismatchPolicy::Skip => false,
+                        MismatchPolicy::Restore => true,
+                        MismatchPolicy::Ask => confirm_restore(&mismatch).await?,
+                    };
+                    if !restore {
+                        report.changed.push(mismatch);
+                        progress.inc(1);
+                        continue;
+                    }
+                    report.changed_restored += 1;
+                }
+                Err(e) => return Err(e),
+            }
+
+            // A project folder or app bundle moved whole has nothing to hash
+            if !source.is_dir() && should_skip_file(&source, &original, hasher.clone()).await? {
+                progress.inc(1);
+                continue;
+            }
+
+            // Names earlier files of this chunk will take count as taken
+            let mut overwrite = false;
+            let final_path = if claimed.contains(&original) || mover.fs().exists(&original).await? {
+                let policy = match options.on_conflict {
+                    ConflictPolicy::Prompt => ask_conflict(&original).await?,
+                    policy => policy,
+                };
+                match policy {
+                    ConflictPolicy::Skip => {
+                        tracing::info!(target: "reverter", "Leaving {:?} in place, {:?} exists", source, original);
+                        report.conflicts_skipped += 1;
+                        progress.inc(1);
+                        continue;
+                    }
+                    // A folder is never deleted to make room; the file there is
+                    // only replaced once the chunk is journaled and the move runs
+                    ConflictPolicy::Overwrite if !source.is_dir() && !original.is_dir() && !claimed.contains(&original) => {
+                        overwrite = true;
+                        original
+                    }
+                    _ => resolve_conflict_claimed(mover.fs(), &original, &claimed).await?,
+                }
+            } else {
+                original
+            };
 
-        if let Some(parent) = final_path.parent() {
-            tokio::fs::create_dir_all(parent).await?;
+            claimed.insert(final_path.clone());
+            planned.push((file, source, final_path, overwrite));
+            if planned.len() == REVERT_BATCH {
+                break;
+            }
         }
 
-        // Move file back
-        mover.move_file(&source, &final_path).await?;
-        tracing::debug!(target: "reverter", "Reverted {:?} -> {:?}", source, final_path);
+        let intents: Vec<_> = planned
+            .iter()
+            .map(|(file, _, final_path, _)| (file.path.as_path(), final_path.as_path(), file.size))
+            .collect();
+        let ids = db.journal_reverts(&intents).await?;
 
-        let mut tx = db.begin().await?;
-        db.update_dest_path_tx(&mut tx, &file.path, &final_path).await?;
-        tx.commit().await?;
+        let mut done = Vec::with_capacity(planned.len());
+        let mut failure = None;
+        for ((file, source, final_path, overwrite), id) in planned.iter().zip(ids) {
+            let moved = if source.is_dir() {
+                mover.move_dir(source, final_path).await
+            } else if *overwrite {
+                move_replacing(&mover, source, final_path).await
+            } else {
+                mover.move_file(source, final_path).await
+            };
+            if let Err(e) = moved {
+                failure = Some(e);
+                break;
+            }
+            tracing::debug!(target: "reverter", "Reverted {:?} -> {:?}", source, final_path);
+            if *overwrite {
+                report.overwritten += 1;
+            }
+            if let Some(original) = permissions.get(&file.path)
+                && let Err(e) = permissions::restore(final_path, original)
+            {
+                tracing::warn!(target: "reverter", "Could not restore permissions of {:?}: {}", final_path, e);
+            }
+            if let Some(parent) = source.parent() {
+                reverted_dirs.insert(parent.to_path_buf());
+            }
+            let original = remap_path(&file.path, &options.mappings);
+            if *final_path != original {
+                report.renamed.push((original, final_path.clone()));
+            }
+            done.push((id, file.path.as_path(), final_path.as_path()));
+            progress.inc(1);
+        }
 
-        moved += 1;
-        pb.inc(1);
+        // One commit per chunk; the journal covers a crash before this point
+        db.commit_reverts(&done).await?;
+        let restored: Vec<&Path> = done
+            .iter()
+            .map(|(_, path, _)| *path)
+            .filter(|path| permissions.contains_key(*path))
+            .collect();
+        db.forget_permissions(&restored).await?;
+        report.moved += done.len();
+        if let Some(e) = failure {
+            progress.finish();
+            return Err(e);
+        }
     }
 
-    pb.finish_with_message(format!(
-        "♻️ Revert completed: {} moved, {} skipped, {} candidates.",
-        moved,
-        total - moved,
-        total
-    ));
+    progress.finish();
+    print!("{}", report);
     tracing::info!(
-        target: "reverter", "Revert completed: {} moved, {} skipped, {} candidates.", 
-        moved, 
-        total - moved,
-        total
+        target: "reverter", "Revert completed: {} moved, {} skipped, {} candidates, {} out of scope.", 
+        report.moved, 
+        total - report.moved,
+        total,
+        report.out_of_scope.len()
     );
 
-    if cleanup {
-        if let Err(e) = cleanup_empty_dirs(root_dir).await {
+    if options.cleanup {
+        let result = match root_dir {
+            Some(root_dir) => cleanup_empty_dirs(root_dir).await,
+            None => prune_empty_parents(reverted_dirs).await,
+        };
+        if let Err(e) = result {
             tracing::warn!(target: "reverter", "Failed to fully cleanup dirs: {:?}", e);
         }
     }
 
+    Ok(report)
+}
+
+/// Files moved back per index transaction
+pub const REVERT_BATCH: usize = 200;
+
+/// Settle revert moves journaled by an interrupted run: a file that reached
+/// its target gets its index entry updated, otherwise the entry is dropped
+pub async fn recover_journal(db: &Db) -> Result<usize> {
+    let mut recovered = Vec::new();
+    let mut abandoned = Vec::new();
+
+    for record in db.pending_reverts().await? {
+        let dest = db.lookup_full(&record.path).await?.map(|e| e.dest_path);
+        let arrived = tokio::fs::try_exists(&record.target).await?
+            && match &dest {
+                Some(dest) => dest == &record.target || !tokio::fs::try_exists(dest).await?,
+                None => false,
+            };
+        if arrived {
+            recovered.push(record);
+        } else {
+            abandoned.push(record);
+        }
+    }
+
+    let done: Vec<_> = recovered
+        .iter()
+        .map(|r| (r.id, r.path.as_path(), r.target.as_path()))
+        .collect();
+    db.commit_reverts(&done).await?;
+    for record in &abandoned {
+        db.mark_action_reverted(record.id).await?;
+    }
+
+    if !recovered.is_empty() {
+        tracing::info!(target: "reverter", "Recovered {} reverted files from an interrupted run", recovered.len());
+    }
+    Ok(recovered.len())
+}
+
+/// Move `source` onto the file at `target`, which is renamed aside first and
+/// only deleted once the move succeeded; a failed move puts it back
+async fn move_replacing(mover: &FileMover, source: &Path, target: &Path) -> Result<()> {
+    let fs = mover.fs();
+    if !fs.exists(target).await? {
+        return mover.move_file(source, target).await;
+    }
+    let name = target.file_name().unwrap_or_default().to_string_lossy();
+    let aside = resolve_conflict_in(fs, &target.with_file_name(format!(".{}.stash-replaced", name)), false).await?;
+    fs.rename(target, &aside).await?;
+
+    match mover.move_file(source, target).await {
+        Ok(()) => {
+            if let Err(e) = fs.remove_file(&aside).await {
+                tracing::warn!(target: "reverter", "Could not remove replaced file {:?}: {}", aside, e);
+            }
+            Ok(())
+        }
+        Err(e) => {
+            if let Err(undo) = fs.rename(&aside, target).await {
+                tracing::error!(target: "reverter", "Could not put {:?} back at {:?}: {}", aside, target, undo);
+            }
+            Err(e)
+        }
+    }
+}
+
+/// Remove each directory and its ancestors for as long as they are empty
+async fn prune_empty_parents(dirs: HashSet<PathBuf>) -> Result<()> {
+    for dir in dirs {
+        let mut current = Some(dir.as_path());
+        while let Some(dir) = current {
+            if !only_own_files_left(dir).await.unwrap_or(false) || fs::remove_dir(dir).await.is_err() {
+                break;
+            }
+            tracing::info!("Removed empty dir: {:?}", dir);
+            current = dir.parent();
+        }
+    }
     Ok(())
 }
 
@@ -149,13 +599,73 @@ pub async fn validate_dir(path: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Checks that `dest` still holds what was organized for `entry`. A matching
+/// size and mtime is trusted; otherwise the content is hashed with the
+/// algorithm the recorded hash was made with. Entries without a recorded hash
+/// always pass.
+pub async fn verify_recorded(entry: &DbFileEntry, dest: &Path) -> Result<()> {
+    let Some(expected) = entry.hash.as_deref().filter(|h| !h.is_empty()) else {
+        return Ok(());
+    };
+
+    let meta = fs::metadata(dest).await?;
+    if meta.len() == entry.size
+        && entry.modified.is_some()
+        && to_unix(meta.modified().ok()) == to_unix(entry.modified)
+    {
+        return Ok(());
+    }
+
+    let actual = rehash_like(expected, dest).await?;
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(FileOrganizerError::HashMismatch {
+            path: dest.to_path_buf(),
+            expected: expected.to_string(),
+            actual,
+        })
+    }
+}
+
+/// Ask on the terminal whether to restore a changed file
+async fn confirm_restore(mismatch: &FileOrganizerError) -> Result<bool> {
+    let prompt = format!("{}\nRestore it anyway? [y/N] ", mismatch);
+    tokio::task::spawn_blocking(move || {
+        eprint!("{}", prompt);
+        std::io::stderr().flush()?;
+        let mut answer = String::new();
+        std::io::stdin().read_line(&mut answer)?;
+        Ok(matches!(answer.trim().to_ascii_lowercase().as_str(), "y" | "yes"))
+    })
+    .await?
+}
+
+/// Ask on the terminal what to do about a file already at `original`. An
+/// "overwrite" answer is planned like [`ConflictPolicy::Overwrite`]: nothing
+/// is deleted until the file's own move runs.
+async fn ask_conflict(original: &Path) -> Result<ConflictPolicy> {
+    let prompt = format!("{:?} already exists. [R]ename, [s]kip or [o]verwrite? ", original);
+    tokio::task::spawn_blocking(move || {
+        eprint!("{}", prompt);
+        std::io::stderr().flush()?;
+        let mut answer = String::new();
+        std::io::stdin().read_line(&mut answer)?;
+        Ok(match answer.trim().to_ascii_lowercase().as_str() {
+            "s" | "skip" => ConflictPolicy::Skip,
+            "o" | "overwrite" => ConflictPolicy::Overwrite,
+            _ => ConflictPolicy::Rename,
+        })
+    })
+    .await?
+}
+
 /// Checks if source and original files are identical based on their hashes.
 /// Returns true if the file should be skipped (identical).
 pub async fn should_skip_file(
     source: &Path,
     original: &Path,
     hasher: Arc<dyn FileHasher + Send + Sync>,
-    pb: &indicatif::ProgressBar,
 ) -> Result<bool> {
     if !tokio::fs::try_exists(original).await? {
         return Ok(false);
@@ -166,7 +676,6 @@ pub async fn should_skip_file(
 
     if source_hash == original_hash {
         tracing::debug!("Skipping identical file: {:?}", source);
-        pb.inc(1);
         Ok(true)
     } else {
         Ok(false)
@@ -0,0 +1,228 @@
+//! An audit trail of one organize run: every move, skip and error together
+//! with the summary, written to its own timestamped file (`--report DIR`)
+//! so it outlives the rolling debug log.
+
+use std::{
+    fmt::Write as _,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use chrono::Local;
+use serde::Serialize;
+
+use crate::{
+    errors::{FileOrganizerError, Result},
+    stats::{format_size, FileOutcome, Summary},
+};
+
+/// File format of a run report
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RunReportFormat {
+    #[default]
+    Json,
+    /// A standalone page with tables, for reading in a browser
+    Html,
+}
+
+impl RunReportFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            RunReportFormat::Json => "json",
+            RunReportFormat::Html => "html",
+        }
+    }
+}
+
+impl FromStr for RunReportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "json" => Ok(Self::Json),
+            "html" => Ok(Self::Html),
+            other => Err(format!("unknown report format `{}` (json, html)", other)),
+        }
+    }
+}
+
+/// What happened to one file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum ReportEntry {
+    Moved { src: PathBuf, dest: PathBuf, category: &'static str, size: u64 },
+    Renamed { src: PathBuf, dest: PathBuf, category: &'static str, size: u64 },
+    Skipped { src: PathBuf, reason: String, size: u64 },
+    /// Deleted, or moved to `trashed`, as a copy of `existing`
+    Duplicate { src: PathBuf, existing: PathBuf, trashed: Option<PathBuf>, size: u64 },
+    Failed { src: PathBuf, stage: String, error: String },
+}
+
+impl From<&FileOutcome> for ReportEntry {
+    fn from(outcome: &FileOutcome) -> Self {
+        match outcome {
+            FileOutcome::Moved(report) => ReportEntry::Moved {
+                src: report.src.clone(),
+                dest: report.dest.clone(),
+                category: report.category,
+                size: report.size,
+            },
+            FileOutcome::Renamed { report, new_path } => ReportEntry::Renamed {
+                src: report.src.clone(),
+                dest: new_path.clone(),
+                category: report.category,
+                size: report.size,
+            },
+            FileOutcome::Skipped { src, reason, size } => ReportEntry::Skipped {
+                src: src.clone(),
+                reason: reason.to_string(),
+                size: *size,
+            },
+            FileOutcome::DuplicateSkipped { src, existing, trashed, size } => ReportEntry::Duplicate {
+                src: src.clone(),
+                existing: existing.clone(),
+                trashed: trashed.clone(),
+                size: *size,
+            },
+            FileOutcome::Err(err) => ReportEntry::Failed {
+                src: err.path.clone(),
+                stage: format!("{:?}", err.stage),
+                error: err.error.to_string(),
+            },
+        }
+    }
+}
+
+/// Everything about a finished run
+#[derive(Debug, Serialize)]
+pub struct RunReport<'a> {
+    /// When the run ended, RFC 3339
+    pub finished_at: String,
+    pub dry_run: bool,
+    pub roots: Vec<PathBuf>,
+    pub summary: &'a Summary,
+    pub files: Vec<ReportEntry>,
+}
+
+impl<'a> RunReport<'a> {
+    pub fn new(roots: &[PathBuf], dry_run: bool, outcomes: &[FileOutcome], summary: &'a Summary) -> Self {
+        Self {
+            finished_at: Local::now().to_rfc3339(),
+            dry_run,
+            roots: roots.to_vec(),
+            summary,
+            files: outcomes.iter().map(ReportEntry::from).collect(),
+        }
+    }
+
+    /// Write the report into `dir` as `stash-<date>-<time>.<ext>`, with a
+    /// number added if two runs end within the same second. Returns the file.
+    pub fn write_to(&self, dir: &Path, format: RunReportFormat) -> Result<PathBuf> {
+        std::fs::create_dir_all(dir)?;
+        let stamp = Local::now().format("%Y%m%d-%H%M%S");
+        let mut path = dir.join(format!("stash-{}.{}", stamp, format.extension()));
+        let mut n = 1;
+        while path.exists() {
+            n += 1;
+            path = dir.join(format!("stash-{}-{}.{}", stamp, n, format.extension()));
+        }
+
+        let contents = match format {
+            RunReportFormat::Json => serde_json::to_string_pretty(self)
+                .map_err(|source| FileOrganizerError::Json { path: path.clone(), source })?,
+            RunReportFormat::Html => self.to_html(),
+        };
+        std::fs::write(&path, contents)?;
+        Ok(path)
+    }
+
+    /// Standalone HTML page: the summary counts, then one table row per file
+    pub fn to_html(&self) -> String {
+        let s = self.summary;
+        let roots: Vec<String> = self.roots.iter().map(|root| root.display().to_string()).collect();
+        let mut html = String::new();
+        let _ = write!(
+            html,
+            "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>stash run {}</title>\n<style>\
+             body{{font-family:sans-serif;margin:2em}}table{{border-collapse:collapse}}\
+             td,th{{border:1px solid #ccc;padding:.25em .5em;text-align:left}}\
+             .moved{{color:#060}}.renamed{{color:#069}}.skipped{{color:#960}}.duplicate{{color:#909}}.failed{{color:#a00}}\
+             </style></head><body>\n<h1>stash run {}{}</h1>\n<p>Roots: {}</p>\n",
+            escape(&self.finished_at),
+            escape(&self.finished_at),
+            if self.dry_run { " (dry run)" } else { "" },
+            escape(&roots.join(", ")),
+        );
+        let _ = write!(
+            html,
+            "<table>\n<tr><th>Discovered</th><td>{}</td></tr>\n<tr><th>Processed</th><td>{}</td></tr>\n\
+             <tr><th>Moved</th><td>{} ({})</td></tr>\n<tr><th>Renamed</th><td>{} ({})</td></tr>\n\
+             <tr><th>Skipped</th><td>{} ({})</td></tr>\n<tr><th>Errors</th><td>{}</td></tr>\n\
+             <tr><th>Duration</th><td>{:.1?}</td></tr>\n</table>\n",
+            s.discovered,
+            s.processed,
+            s.moved,
+            format_size(s.bytes_moved),
+            s.renamed,
+            format_size(s.bytes_renamed),
+            s.skip_counts.iter().sum::<usize>(),
+            format_size(s.bytes_skipped),
+            s.errors,
+            s.duration,
+        );
+
+        if !s.error_groups.is_empty() {
+            html.push_str("<h2>Errors</h2>\n<table>\n<tr><th>Stage</th><th>Kind</th><th>Files</th><th>For example</th></tr>\n");
+            for group in &s.error_groups {
+                let examples: Vec<String> = group.examples.iter().map(|path| escape(&path.display().to_string())).collect();
+                let _ = writeln!(
+                    html,
+                    "<tr class=\"failed\"><td>{:?}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                    group.stage,
+                    group.kind,
+                    group.count,
+                    examples.join("<br>"),
+                );
+            }
+            html.push_str("</table>\n");
+        }
+
+        html.push_str("<h2>Files</h2>\n<table>\n<tr><th>Outcome</th><th>File</th><th>Destination or reason</th><th>Size</th></tr>\n");
+        for entry in &self.files {
+            let (class, src, detail, size) = match entry {
+                ReportEntry::Moved { src, dest, size, .. } => ("moved", src, dest.display().to_string(), Some(*size)),
+                ReportEntry::Renamed { src, dest, size, .. } => ("renamed", src, dest.display().to_string(), Some(*size)),
+                ReportEntry::Skipped { src, reason, size } => ("skipped", src, reason.clone(), Some(*size)),
+                ReportEntry::Duplicate { src, existing, size, .. } => {
+                    ("duplicate", src, format!("copy of {}", existing.display()), Some(*size))
+                }
+                ReportEntry::Failed { src, stage, error } => ("failed", src, format!("{} ({})", error, stage), None),
+            };
+            let _ = writeln!(
+                html,
+                "<tr class=\"{}\"><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                class,
+                class,
+                escape(&src.display().to_string()),
+                escape(&detail),
+                size.map(format_size).unwrap_or_default(),
+            );
+        }
+        html.push_str("</table>\n</body></html>\n");
+        html
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,112 @@
+//! A small JSON file describing the last organize run, cheap for status bars
+//! (waybar, xbar) and monitoring scripts to poll
+
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    errors::{FileOrganizerError, Result},
+    stats::Summary,
+    utils::write_json_atomically,
+};
+
+/// How the run ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunResult {
+    /// Every file was handled
+    Ok,
+    /// The run finished, but some files failed
+    Errors,
+    /// The run stopped before organizing (e.g. too little free space)
+    Failed,
+    /// The run was stopped part way; files it did not reach are untouched
+    Cancelled,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunStatus {
+    /// When the run ended, RFC 3339
+    pub finished_at: String,
+    /// The same instant as Unix seconds
+    pub timestamp: i64,
+    pub result: RunResult,
+    pub dry_run: bool,
+    pub roots: Vec<PathBuf>,
+    pub processed: usize,
+    pub moved: usize,
+    pub renamed: usize,
+    pub skipped: usize,
+    pub errors: usize,
+    pub duration_ms: u64,
+    /// Why the run failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl RunStatus {
+    fn new(roots: &[PathBuf], dry_run: bool, result: RunResult) -> Self {
+        let now = Utc::now();
+        Self {
+            finished_at: now.to_rfc3339(),
+            timestamp: now.timestamp(),
+            result,
+            dry_run,
+            roots: roots.to_vec(),
+            processed: 0,
+            moved: 0,
+            renamed: 0,
+            skipped: 0,
+            errors: 0,
+            duration_ms: 0,
+            error: None,
+        }
+    }
+
+    pub fn from_summary(roots: &[PathBuf], dry_run: bool, summary: &Summary) -> Self {
+        let result = if summary.cancelled {
+            RunResult::Cancelled
+        } else if summary.errors == 0 {
+            RunResult::Ok
+        } else {
+            RunResult::Errors
+        };
+        Self {
+            processed: summary.processed,
+            moved: summary.moved,
+            renamed: summary.renamed,
+            skipped: summary.skip_counts.iter().sum(),
+            errors: summary.errors,
+            duration_ms: summary.duration.as_millis() as u64,
+            ..Self::new(roots, dry_run, result)
+        }
+    }
+
+    pub fn failed(roots: &[PathBuf], dry_run: bool, error: &FileOrganizerError) -> Self {
+        Self {
+            error: Some(error.to_string()),
+            ..Self::new(roots, dry_run, RunResult::Failed)
+        }
+    }
+
+    /// Replace the file at `path` in one step, so a reader never sees half of it
+    pub fn write(&self, path: &Path) -> Result<()> {
+        write_json_atomically(path, self)
+    }
+
+    pub fn read(path: &Path) -> Result<Self> {
+        serde_json::from_slice(&std::fs::read(path)?)
+            .map_err(|source| FileOrganizerError::Json { path: path.to_path_buf(), source })
+    }
+}
+
+/// `~/.local/state/stash/last_run.json` on Linux; the local data dir elsewhere
+pub fn default_status_path() -> PathBuf {
+    dirs::state_dir()
+        .or_else(dirs::data_local_dir)
+        .unwrap_or_else(std::env::temp_dir)
+        .join("stash")
+        .join("last_run.json")
+}
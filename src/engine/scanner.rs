@@ -1,20 +1,100 @@
 use std::fs::Permissions;
 use std::io;
-use std::path::PathBuf;
-use std::time::SystemTime;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, SystemTime};
 use walkdir::{DirEntry, WalkDir};
 
+use crate::cancel::CancelToken;
+use crate::exclusions::Exclusions;
 use crate::errors::{FileOrganizerError, Result, SkipReason};
 
+/// What to do with symbolic links found while scanning
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SymlinkPolicy {
+    /// Leave links where they are
+    #[default]
+    Skip,
+    /// Organize what a link points to: the link is replaced by a copy of it
+    Follow,
+    /// Move the link itself, still pointing at the same file
+    MoveLink,
+}
+
+impl FromStr for SymlinkPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "skip" => Ok(Self::Skip),
+            "follow" => Ok(Self::Follow),
+            "move-link" => Ok(Self::MoveLink),
+            other => Err(format!("unknown symlink policy `{}` (skip, follow, move-link)", other)),
+        }
+    }
+}
+
+/// Extensions browsers and download managers give a file while it is being
+/// downloaded (`.crdownload`, `.part`, Safari's `.download` bundle, ...).
+/// The finished file is renamed to its real name.
+const INCOMPLETE_EXTENSIONS: [&str; 6] = ["crdownload", "part", "partial", "download", "opdownload", "!qb"];
+
+/// Whether `path` is named like a download still in progress
+pub fn is_incomplete_download(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|ext| INCOMPLETE_EXTENSIONS.iter().any(|known| ext.eq_ignore_ascii_case(known)))
+}
+
+/// Entries whose presence makes a directory a project: repositories and
+/// build manifests
+const PROJECT_MARKERS: [&str; 7] = [".git", ".hg", "Cargo.toml", "package.json", "pyproject.toml", "go.mod", "pom.xml"];
+
+/// A directory that only makes sense whole, so it is never organized file by file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BundleKind {
+    /// A source tree, holding one of the project markers (`.git`, `Cargo.toml`, `package.json`, ...)
+    Project,
+    /// A macOS application (`Name.app`)
+    App,
+}
+
+impl BundleKind {
+    /// What kind of bundle the directory `dir` is, if any
+    pub fn detect(dir: &Path) -> Option<Self> {
+        if dir.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("app")) {
+            return Some(Self::App);
+        }
+        PROJECT_MARKERS
+            .iter()
+            .any(|marker| std::fs::symlink_metadata(dir.join(marker)).is_ok())
+            .then_some(Self::Project)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ScanConfig {
     pub include_hidden: bool,
     pub include_dirs: bool,
+    pub min_depth: usize,
     pub max_depth: usize,
     pub allowed_extensions: Option<Vec<String>>,
     pub min_size: Option<u64>,
     pub max_size: Option<u64>,
-    pub follow_symlinks: bool,
+    /// Only files last modified at least this long ago
+    pub older_than: Option<Duration>,
+    /// Only files last modified within this long
+    pub newer_than: Option<Duration>,
+    /// Only files owned by this user id (Unix only)
+    pub owner: Option<u32>,
+    pub symlinks: SymlinkPolicy,
+    /// Yield project folders and app bundles (see [`BundleKind`]) as one
+    /// entry, sized by their contents, instead of walking into them
+    pub bundles: bool,
+    /// Files and directories left alone, e.g. the destination inside the root
+    pub exclude: Exclusions,
+    /// Stops the scan early when cancelled
+    pub cancel: CancelToken,
 }
 
 impl Default for ScanConfig {
@@ -22,11 +102,18 @@ impl Default for ScanConfig {
         Self {
             include_hidden: false,
             include_dirs: false,
+            min_depth: 0,
             max_depth: usize::MAX,
             allowed_extensions: None,
             min_size: None,
             max_size: None,
-            follow_symlinks: false,
+            older_than: None,
+            newer_than: None,
+            owner: None,
+            symlinks: SymlinkPolicy::Skip,
+            bundles: false,
+            exclude: Exclusions::default(),
+            cancel: CancelToken::default(),
         }
     }
 }
@@ -42,6 +129,8 @@ pub struct RawFileMetadata {
     pub is_file: bool,
     pub is_dir: bool,
     pub is_symlink: bool,
+    /// Owning user id (Unix only)
+    pub owner: Option<u32>,
 }
 
 impl RawFileMetadata {
@@ -72,6 +161,8 @@ impl RawFileMetadata {
 pub struct Scanner {
     inner: walkdir::IntoIter,
     config: ScanConfig,
+    /// Reference point for age filters, fixed for the whole scan
+    now: SystemTime,
 }
 
 impl Scanner {
@@ -82,62 +173,115 @@ impl Scanner {
         }
 
         let walker = WalkDir::new(root.into())
+            .min_depth(config.min_depth)
             .max_depth(config.max_depth)
-            .follow_links(config.follow_symlinks);
+            .follow_links(config.symlinks == SymlinkPolicy::Follow);
 
         Self {
             inner: walker.into_iter(),
             config,
+            now: SystemTime::now(),
         }
     }
 
-    fn process_entry(&self, entry: &DirEntry) -> Result<RawFileMetadata> {
+    /// Check `entry` against the filters; `bundle` when it is a directory
+    /// kept whole
+    fn process_entry(&self, entry: &DirEntry, bundle: bool) -> Result<RawFileMetadata> {
+        let skip = |reason: SkipReason, size: u64| FileOrganizerError::Skipped {
+            path: entry.path().to_path_buf(),
+            reason,
+            size,
+        };
+
         // hidden
         if !self.config.include_hidden && is_hidden(entry) {
-            return Err(FileOrganizerError::Skipped(SkipReason::Hidden));
+            let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+            return Err(skip(SkipReason::Hidden, size));
+        }
+
+        if self.config.exclude.excludes(entry.path()) {
+            let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+            return Err(skip(SkipReason::Excluded, size));
+        }
+
+        if is_incomplete_download(entry.path()) {
+            let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+            return Err(skip(SkipReason::Incomplete, size));
+        }
+
+        let is_symlink = entry.path_is_symlink();
+        if is_symlink && self.config.symlinks == SymlinkPolicy::Skip {
+            return Err(skip(SkipReason::Symlink, 0));
         }
 
-        let metadata = entry.metadata().map_err(|_| FileOrganizerError::Skipped(SkipReason::MetadataUnreadable))?;
+        // A link moved as a link is filtered by what it points to
+        let metadata = match self.config.symlinks {
+            SymlinkPolicy::MoveLink if is_symlink => std::fs::metadata(entry.path()).ok(),
+            _ => entry.metadata().ok(),
+        }
+        .ok_or_else(|| skip(SkipReason::MetadataUnreadable, 0))?;
 
         // skip dirs
-        if metadata.is_dir() && !self.config.include_dirs {
-            return Err(FileOrganizerError::Skipped(SkipReason::IsDir));
+        if metadata.is_dir() && !self.config.include_dirs && !bundle {
+            return Err(skip(SkipReason::IsDir, 0));
         }
 
         // skip by extension
-        if let Some(ref exts) = self.config.allowed_extensions {
-            if let Some(ext) = entry.path().extension().and_then(|e| e.to_str()) {
-                if !exts.contains(&ext.to_lowercase()) {
-                    return Err(FileOrganizerError::Skipped(SkipReason::WrongExtension));
-                }
-            }
+        if let Some(ref exts) = self.config.allowed_extensions
+            && let Some(ext) = entry.path().extension().and_then(|e| e.to_str())
+            && !exts.contains(&ext.to_lowercase())
+        {
+            return Err(skip(SkipReason::WrongExtension, metadata.len()));
         }
 
         // size filtering
         if metadata.is_file() {
             let size = metadata.len();
-            if let Some(min) = self.config.min_size {
-                if size < min {
-                    return Err(FileOrganizerError::Skipped(SkipReason::TooSmall));
-                }
+            if let Some(min) = self.config.min_size
+                && size < min
+            {
+                return Err(skip(SkipReason::TooSmall, size));
+            }
+            if let Some(max) = self.config.max_size
+                && size > max
+            {
+                return Err(skip(SkipReason::TooLarge, size));
             }
-            if let Some(max) = self.config.max_size {
-                if size > max {
-                    return Err(FileOrganizerError::Skipped(SkipReason::TooLarge));
+
+            // age filtering (files with an unreadable mtime pass)
+            if let Ok(modified) = metadata.modified() {
+                let age = self.now.duration_since(modified).unwrap_or_default();
+                if let Some(older_than) = self.config.older_than
+                    && age < older_than
+                {
+                    return Err(skip(SkipReason::TooNew, size));
+                }
+                if let Some(newer_than) = self.config.newer_than
+                    && age > newer_than
+                {
+                    return Err(skip(SkipReason::TooOld, size));
                 }
             }
         }
 
+        let owner = file_owner(&metadata);
+        if let Some(uid) = self.config.owner
+            && owner.is_some_and(|owner| owner != uid)
+        {
+            return Err(skip(SkipReason::NotOwned, metadata.len()));
+        }
+
         Ok(RawFileMetadata {
             path: entry.path().to_path_buf(),
-            size: metadata.len(),
+            size: if bundle { dir_size(entry.path()) } else { metadata.len() },
             created: metadata.created().ok(),
             modified: metadata.modified().ok(),
             accessed: metadata.accessed().ok(),
             permissions: metadata.permissions(),
             is_file: metadata.is_file(),
             is_dir: metadata.is_dir(),
-            is_symlink: metadata.file_type().is_symlink(),
+            is_symlink,
+            owner,
         })
     }
 }
@@ -146,16 +290,57 @@ impl Iterator for Scanner {
     type Item = Result<RawFileMetadata>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        while let Some(entry) = self.inner.next() {
-            match entry {
-                Ok(e) => return Some(self.process_entry(&e)),
-                Err(err) => return Some(Err(FileOrganizerError::Io(io::Error::new(io::ErrorKind::Other, err)))),
-            }
+        if self.config.cancel.is_cancelled() {
+            return None;
         }
-        None
+        self.inner.next().map(|entry| match entry {
+            Ok(e) => {
+                let bundle = self.config.bundles
+                    && e.depth() > 0
+                    && e.file_type().is_dir()
+                    && !e.path_is_symlink()
+                    && BundleKind::detect(e.path()).is_some();
+                // Files inside hidden or excluded directories (or a Safari
+                // `.download` bundle) are not wanted either, and those inside
+                // a bundle go with it
+                if e.depth() > 0
+                    && e.file_type().is_dir()
+                    && (bundle
+                        || (!self.config.include_hidden && is_hidden(&e))
+                        || self.config.exclude.excludes(e.path())
+                        || is_incomplete_download(e.path()))
+                {
+                    self.inner.skip_current_dir();
+                }
+                self.process_entry(&e, bundle)
+            }
+            Err(err) => Err(FileOrganizerError::Io(io::Error::other(err))),
+        })
     }
 }
 
+/// Bytes in the files below `dir`, links not followed
+fn dir_size(dir: &Path) -> u64 {
+    WalkDir::new(dir)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| entry.metadata().ok())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+#[cfg(unix)]
+pub(crate) fn file_owner(metadata: &std::fs::Metadata) -> Option<u32> {
+    use std::os::unix::fs::MetadataExt;
+    Some(metadata.uid())
+}
+
+#[cfg(not(unix))]
+pub(crate) fn file_owner(_metadata: &std::fs::Metadata) -> Option<u32> {
+    None
+}
+
 /// UNIX hidden detection (dotfiles)
 #[cfg(unix)]
 fn is_hidden(entry: &DirEntry) -> bool {
@@ -189,15 +374,12 @@ where
     I: Iterator<Item = Result<RawFileMetadata>>,
 {
     fn filter_ok(self) -> impl Iterator<Item = RawFileMetadata> {
-        self.filter_map(|res| match res {
-            Ok(file) => Some(file),
-            _ => None,
-        })
+        self.filter_map(|res| res.ok())
     }
 
     fn filter_skipped(self) -> impl Iterator<Item = SkipReason> {
         self.filter_map(|res| match res {
-            Err(FileOrganizerError::Skipped(reason)) => Some(reason),
+            Err(FileOrganizerError::Skipped { reason, .. }) => Some(reason),
             _ => None,
         })
     }
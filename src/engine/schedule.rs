@@ -0,0 +1,314 @@
+//! When background organizing may run. The daemon and watch mode check the
+//! configured [`Schedule`] before each batch and hold off while it says to
+//! pause: outside the active hours, inside quiet hours, on battery or with
+//! the battery low, under high CPU load, on a metered connection, or when
+//! the destination is short of space. Power, load and network state that
+//! can't be read never pauses anything.
+
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use chrono::{Local, NaiveTime};
+use serde::{Deserialize, Deserializer};
+
+use crate::{errors::FileOrganizerError, space, utils::parse_size};
+
+/// A daily time range like `02:00-06:00`; it wraps past midnight when the end
+/// is before the start (`22:00-07:00`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct TimeWindow {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl TimeWindow {
+    /// Whether `time` falls in the window; the start is inside, the end isn't
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start < self.end {
+            self.start <= time && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+}
+
+impl TryFrom<String> for TimeWindow {
+    type Error = FileOrganizerError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl std::str::FromStr for TimeWindow {
+    type Err = FileOrganizerError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = |why: &str| FileOrganizerError::InvalidRule(format!("time window `{}` {}", s, why));
+        let (start, end) = s.split_once(['-', '–']).ok_or_else(|| invalid("is not `HH:MM-HH:MM`"))?;
+        let parse = |time: &str| NaiveTime::parse_from_str(time.trim(), "%H:%M").map_err(|_| invalid("is not `HH:MM-HH:MM`"));
+        let window = Self { start: parse(start)?, end: parse(end)? };
+        if window.start == window.end {
+            return Err(invalid("is empty"));
+        }
+        Ok(window)
+    }
+}
+
+impl fmt::Display for TimeWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.start.format("%H:%M"), self.end.format("%H:%M"))
+    }
+}
+
+/// Why organizing is held off
+#[derive(Debug, Clone, PartialEq)]
+pub enum Pause {
+    OutsideActiveHours,
+    QuietHours(TimeWindow),
+    OnBattery,
+    /// On battery with this much charge left, in percent
+    LowBattery(u8),
+    /// One-minute load average per CPU
+    HighLoad(f64),
+    Metered,
+    /// Bytes left on the filesystem `path` is organized into
+    LowDiskSpace { path: PathBuf, available: u64 },
+}
+
+impl fmt::Display for Pause {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Pause::OutsideActiveHours => write!(f, "outside the active hours"),
+            Pause::QuietHours(window) => write!(f, "in quiet hours ({})", window),
+            Pause::OnBattery => write!(f, "running on battery"),
+            Pause::LowBattery(percent) => write!(f, "battery low ({}%)", percent),
+            Pause::HighLoad(load) => write!(f, "CPU load high ({:.0}%)", load * 100.0),
+            Pause::Metered => write!(f, "on a metered connection"),
+            Pause::LowDiskSpace { path, available } => {
+                write!(f, "low disk space ({} MB left for {:?})", available / (1 << 20), path)
+            }
+        }
+    }
+}
+
+/// The `schedule` config key. The default never pauses.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct Schedule {
+    /// Only organize inside one of these windows; empty means any time
+    #[serde(default)]
+    pub active_hours: Vec<TimeWindow>,
+
+    /// Never organize inside these windows, even when active
+    #[serde(default)]
+    pub quiet_hours: Vec<TimeWindow>,
+
+    /// Hold off while the machine runs on battery
+    #[serde(default)]
+    pub pause_on_battery: bool,
+
+    /// Hold off on a metered network connection (Linux with NetworkManager)
+    #[serde(default)]
+    pub pause_on_metered: bool,
+
+    /// Hold off on battery once the charge drops below this percentage
+    #[serde(default)]
+    pub min_battery_percent: Option<u8>,
+
+    /// Hold off while the one-minute load average per CPU is above this,
+    /// e.g. `0.8` for 80% (Unix only)
+    #[serde(default)]
+    pub max_cpu_load: Option<f64>,
+
+    /// Hold off while the destination has less free space than this, e.g. `"5GB"`
+    #[serde(default, deserialize_with = "deserialize_size")]
+    pub min_free_space: Option<u64>,
+}
+
+/// A size as a number of bytes or a string [`parse_size`] reads
+fn deserialize_size<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u64>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Size {
+        Bytes(u64),
+        Text(String),
+    }
+    match Option::<Size>::deserialize(deserializer)? {
+        None => Ok(None),
+        Some(Size::Bytes(bytes)) => Ok(Some(bytes)),
+        Some(Size::Text(text)) => parse_size(&text).map(Some).map_err(serde::de::Error::custom),
+    }
+}
+
+impl Schedule {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Why organizing should wait at `time` of day, going by the windows only
+    pub fn pause_at(&self, time: NaiveTime) -> Option<Pause> {
+        if let Some(window) = self.quiet_hours.iter().find(|window| window.contains(time)) {
+            return Some(Pause::QuietHours(*window));
+        }
+        if !self.active_hours.is_empty() && !self.active_hours.iter().any(|window| window.contains(time)) {
+            return Some(Pause::OutsideActiveHours);
+        }
+        None
+    }
+
+    /// Why organizing should wait right now, if it should
+    pub fn pause(&self) -> Option<Pause> {
+        if let Some(pause) = self.pause_at(Local::now().time()) {
+            return Some(pause);
+        }
+        if (self.pause_on_battery || self.min_battery_percent.is_some())
+            && let Some(power) = power_state()
+            && power.on_battery
+        {
+            if self.pause_on_battery {
+                return Some(Pause::OnBattery);
+            }
+            if let (Some(min), Some(percent)) = (self.min_battery_percent, power.percent)
+                && percent < min
+            {
+                return Some(Pause::LowBattery(percent));
+            }
+        }
+        if let Some(max) = self.max_cpu_load
+            && let Some(load) = cpu_load()
+            && load > max
+        {
+            return Some(Pause::HighLoad(load));
+        }
+        if self.pause_on_metered && metered_connection() == Some(true) {
+            return Some(Pause::Metered);
+        }
+        None
+    }
+
+    /// [`Schedule::pause`], and also whether the filesystem `dest` is on is
+    /// short of space
+    pub fn pause_for(&self, dest: &Path) -> Option<Pause> {
+        self.pause().or_else(|| {
+            let min = self.min_free_space?;
+            let available = space::available_space(dest).ok()?;
+            (available < min).then(|| Pause::LowDiskSpace { path: dest.to_path_buf(), available })
+        })
+    }
+}
+
+/// Whether the machine runs on battery, and how charged that is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PowerState {
+    pub on_battery: bool,
+    pub percent: Option<u8>,
+}
+
+/// The machine's power state; `None` when it can't be read
+#[cfg(target_os = "linux")]
+pub fn power_state() -> Option<PowerState> {
+    let read = |path: PathBuf| std::fs::read_to_string(path).map(|s| s.trim().to_string()).unwrap_or_default();
+    let mut state = PowerState { on_battery: false, percent: None };
+    let mut plugged_in = false;
+    for supply in std::fs::read_dir("/sys/class/power_supply").ok()?.flatten() {
+        let dir = supply.path();
+        match read(dir.join("type")).as_str() {
+            "Mains" | "USB" => plugged_in |= read(dir.join("online")) == "1",
+            // Mice and keyboards report their batteries with scope `Device`
+            "Battery" if read(dir.join("scope")) != "Device" => {
+                state.on_battery |= read(dir.join("status")) == "Discharging";
+                state.percent = state.percent.or(read(dir.join("capacity")).parse().ok());
+            }
+            _ => {}
+        }
+    }
+    // An adapter that is plugged in settles it
+    state.on_battery &= !plugged_in;
+    Some(state)
+}
+
+#[cfg(target_os = "macos")]
+pub fn power_state() -> Option<PowerState> {
+    // Now drawing from 'Battery Power'
+    //  -InternalBattery-0 (id=1234)	87%; discharging; 4:12 remaining present: true
+    let output = Command::new("pmset").args(["-g", "batt"]).output().ok()?;
+    let text = String::from_utf8_lossy(&output.stdout);
+    let on_battery = text.lines().next()?.contains("'Battery Power'");
+    let percent = text.split_once('%').and_then(|(before, _)| {
+        let digits = before.rsplit(|c: char| !c.is_ascii_digit()).next()?;
+        digits.parse().ok()
+    });
+    Some(PowerState { on_battery, percent })
+}
+
+#[cfg(windows)]
+pub fn power_state() -> Option<PowerState> {
+    use windows_sys::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+    let mut status: SYSTEM_POWER_STATUS = unsafe { std::mem::zeroed() };
+    if unsafe { GetSystemPowerStatus(&mut status) } == 0 {
+        return None;
+    }
+    // 0 offline, 1 online, 255 unknown
+    let on_battery = match status.ACLineStatus {
+        0 => true,
+        1 => false,
+        _ => return None,
+    };
+    let percent = (status.BatteryLifePercent <= 100).then_some(status.BatteryLifePercent);
+    Some(PowerState { on_battery, percent })
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+pub fn power_state() -> Option<PowerState> {
+    None
+}
+
+/// One-minute load average divided by the number of CPUs; `None` off Unix
+pub fn cpu_load() -> Option<f64> {
+    #[cfg(unix)]
+    {
+        let mut load = [0.0f64; 1];
+        if unsafe { libc::getloadavg(load.as_mut_ptr(), 1) } != 1 {
+            return None;
+        }
+        Some(load[0] / num_cpus::get().max(1) as f64)
+    }
+
+    #[cfg(not(unix))]
+    {
+        None
+    }
+}
+
+/// Whether NetworkManager considers the connection metered (its `yes` and
+/// `guess-yes`); `None` without NetworkManager
+pub fn metered_connection() -> Option<bool> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    let output = Command::new("busctl")
+        .args([
+            "--system",
+            "get-property",
+            "org.freedesktop.NetworkManager",
+            "/org/freedesktop/NetworkManager",
+            "org.freedesktop.NetworkManager",
+            "Metered",
+        ])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    // `u 4`: 0 unknown, 1 yes, 2 no, 3 guess-yes, 4 guess-no
+    match String::from_utf8_lossy(&output.stdout).trim().strip_prefix("u ")? {
+        "1" | "3" => Some(true),
+        "0" => None,
+        _ => Some(false),
+    }
+}
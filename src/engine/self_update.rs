@@ -0,0 +1,261 @@
+use std::{
+    io::{Cursor, Read},
+    path::{Path, PathBuf},
+};
+
+use flate2::read::GzDecoder;
+use semver::Version;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::errors::{FileOrganizerError, Result};
+
+/// GitHub repository releases are published to
+pub const REPO: &str = "chineduCoded/file_organizer";
+
+/// Largest archive or binary we are willing to download or unpack
+const MAX_DOWNLOAD: u64 = 256 * 1024 * 1024;
+
+/// A downloadable file attached to a release
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Asset {
+    pub name: String,
+    pub url: String,
+}
+
+/// The parts of a GitHub release an update needs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Release {
+    /// The tag as pushed, e.g. `v0.3.1`, which asset names start with
+    pub tag: String,
+    pub version: Version,
+    pub assets: Vec<Asset>,
+}
+
+impl Release {
+    /// Parse the GitHub "get latest release" response
+    pub fn from_json(json: &Value) -> Result<Self> {
+        let tag = json["tag_name"]
+            .as_str()
+            .ok_or_else(|| FileOrganizerError::Other("Release has no tag_name".into()))?;
+        let version = Version::parse(tag.trim_start_matches('v')).map_err(|e| {
+            FileOrganizerError::Other(format!("Release tag `{}` is not a version: {}", tag, e))
+        })?;
+
+        let assets = json["assets"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|a| {
+                Some(Asset {
+                    name: a["name"].as_str()?.to_string(),
+                    url: a["browser_download_url"].as_str()?.to_string(),
+                })
+            })
+            .collect();
+
+        Ok(Self { tag: tag.to_string(), version, assets })
+    }
+
+    pub fn asset(&self, name: &str) -> Option<&Asset> {
+        self.assets.iter().find(|a| a.name == name)
+    }
+}
+
+/// Target triple this binary was built for, e.g. `x86_64-unknown-linux-gnu`,
+/// which its release archive is named after
+pub fn target_triple() -> String {
+    let arch = std::env::consts::ARCH;
+    match std::env::consts::OS {
+        "linux" if cfg!(target_env = "musl") => format!("{}-unknown-linux-musl", arch),
+        "linux" => format!("{}-unknown-linux-gnu", arch),
+        "windows" if cfg!(target_env = "gnu") => format!("{}-pc-windows-gnu", arch),
+        "windows" => format!("{}-pc-windows-msvc", arch),
+        "macos" => format!("{}-apple-darwin", arch),
+        os => format!("{}-unknown-{}", arch, os),
+    }
+}
+
+/// Release archive for `target`, named like `.github/workflows/release.yml`
+/// does: `stash-v0.3.1-x86_64-unknown-linux-gnu.tar.gz`, a `.zip` on Windows
+pub fn asset_name(tag: &str, target: &str) -> String {
+    let ext = if target.contains("windows") { "zip" } else { "tar.gz" };
+    format!("stash-{}-{}.{}", tag, target, ext)
+}
+
+/// The `.sha256` asset with the digest of the binary in [`asset_name`]'s archive
+pub fn checksum_name(tag: &str, target: &str) -> String {
+    format!("stash-{}-{}.sha256", tag, target)
+}
+
+/// Take the stash binary out of a release archive: `stash-<tag>/stash` in a
+/// `.tar.gz`, or `stash-<tag>/stash.exe` in a `.zip`
+pub fn unpack_binary(archive_name: &str, data: &[u8]) -> Result<Vec<u8>> {
+    let binary = format!("stash{}", std::env::consts::EXE_SUFFIX);
+    let is_binary = |path: &Path| path.file_name().is_some_and(|name| name == binary.as_str());
+    let unreadable = |e: &dyn std::fmt::Display| FileOrganizerError::Other(format!("Can't unpack {}: {}", archive_name, e));
+    let mut unpacked = Vec::new();
+
+    if archive_name.ends_with(".zip") {
+        let mut archive = zip::ZipArchive::new(Cursor::new(data)).map_err(|e| unreadable(&e))?;
+        for i in 0..archive.len() {
+            let entry = archive.by_index(i).map_err(|e| unreadable(&e))?;
+            if entry.is_file() && entry.enclosed_name().is_some_and(|path| is_binary(&path)) {
+                entry.take(MAX_DOWNLOAD).read_to_end(&mut unpacked)?;
+                return Ok(unpacked);
+            }
+        }
+    } else {
+        let mut archive = tar::Archive::new(GzDecoder::new(data));
+        for entry in archive.entries().map_err(|e| unreadable(&e))? {
+            let entry = entry.map_err(|e| unreadable(&e))?;
+            if entry.header().entry_type().is_file() && entry.path().is_ok_and(|path| is_binary(&path)) {
+                entry.take(MAX_DOWNLOAD).read_to_end(&mut unpacked)?;
+                return Ok(unpacked);
+            }
+        }
+    }
+    Err(FileOrganizerError::Other(format!("{} has no {} in it", archive_name, binary)))
+}
+
+/// Check `data` against a `.sha256` file: either a bare hex digest or
+/// `sha256sum` output with one `<digest>  <name>` line per asset
+pub fn verify_sha256(data: &[u8], checksums: &str, asset: &str) -> Result<()> {
+    let expected = checksums
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let digest = parts.next()?;
+            match parts.next() {
+                None => Some(digest),
+                Some(name) if name.trim_start_matches('*') == asset => Some(digest),
+                Some(_) => None,
+            }
+        })
+        .next()
+        .ok_or_else(|| FileOrganizerError::Other(format!("No checksum listed for {}", asset)))?;
+
+    let actual = hex::encode(Sha256::digest(data));
+    if !actual.eq_ignore_ascii_case(expected) {
+        return Err(FileOrganizerError::Other(format!(
+            "Checksum mismatch for {}: expected {}, got {}",
+            asset, expected, actual
+        )));
+    }
+    Ok(())
+}
+
+/// Write `new` next to `exe` and swap it into place
+pub fn replace_exe(new: &[u8], exe: &Path) -> Result<()> {
+    let staged = sibling(exe, ".new");
+    std::fs::write(&staged, new)?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&staged, std::fs::Permissions::from_mode(0o755))?;
+    }
+
+    // A running executable can't be overwritten on Windows, but it can be renamed
+    #[cfg(windows)]
+    let old = {
+        let old = sibling(exe, ".old");
+        let _ = std::fs::remove_file(&old);
+        if let Err(e) = std::fs::rename(exe, &old) {
+            let _ = std::fs::remove_file(&staged);
+            return Err(e.into());
+        }
+        old
+    };
+
+    if let Err(e) = std::fs::rename(&staged, exe) {
+        let _ = std::fs::remove_file(&staged);
+        // Put the running binary back rather than leave no `exe` at all
+        #[cfg(windows)]
+        let _ = std::fs::rename(&old, exe);
+        return Err(e.into());
+    }
+    Ok(())
+}
+
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+/// What `self_update` found or did
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UpdateStatus {
+    UpToDate(Version),
+    /// A newer release exists (check-only mode)
+    Available { current: Version, latest: Version },
+    Updated { from: Version, to: Version },
+}
+
+/// Fetch the latest release and, unless `check_only`, replace the running
+/// binary with its verified asset for this platform. Blocks on network I/O.
+pub fn self_update(check_only: bool) -> Result<UpdateStatus> {
+    let current = Version::parse(env!("CARGO_PKG_VERSION"))
+        .map_err(|e| FileOrganizerError::Other(e.to_string()))?;
+
+    let url = format!("https://api.github.com/repos/{}/releases/latest", REPO);
+    let json: Value = ureq::get(&url)
+        .header("User-Agent", concat!("stash/", env!("CARGO_PKG_VERSION")))
+        .header("Accept", "application/vnd.github+json")
+        .call()
+        .and_then(|mut r| r.body_mut().read_json())
+        .map_err(http_error)?;
+    let release = Release::from_json(&json)?;
+
+    if release.version <= current {
+        return Ok(UpdateStatus::UpToDate(current));
+    }
+    if check_only {
+        return Ok(UpdateStatus::Available { current, latest: release.version });
+    }
+
+    let target = target_triple();
+    let name = asset_name(&release.tag, &target);
+    let archive = release
+        .asset(&name)
+        .ok_or_else(|| FileOrganizerError::Other(format!(
+            "Release {} has no build for this platform ({})",
+            release.version, name
+        )))?;
+    let checksum = release
+        .asset(&checksum_name(&release.tag, &target))
+        .ok_or_else(|| FileOrganizerError::Other(format!(
+            "Release {} has no checksum for {}; refusing to install",
+            release.version, name
+        )))?;
+
+    tracing::info!(target: "self_update", "Downloading {} {}", name, release.version);
+    let data = unpack_binary(&name, &download(&archive.url)?)?;
+    let checksums = String::from_utf8_lossy(&download(&checksum.url)?).into_owned();
+    verify_sha256(&data, &checksums, &name)?;
+
+    replace_exe(&data, &std::env::current_exe()?)?;
+    tracing::info!(target: "self_update", "Updated {} -> {}", current, release.version);
+
+    Ok(UpdateStatus::Updated { from: current, to: release.version })
+}
+
+fn download(url: &str) -> Result<Vec<u8>> {
+    let mut response = ureq::get(url)
+        .header("User-Agent", concat!("stash/", env!("CARGO_PKG_VERSION")))
+        .call()
+        .map_err(http_error)?;
+
+    let mut data = Vec::new();
+    response
+        .body_mut()
+        .as_reader()
+        .take(MAX_DOWNLOAD)
+        .read_to_end(&mut data)?;
+    Ok(data)
+}
+
+fn http_error(e: ureq::Error) -> FileOrganizerError {
+    FileOrganizerError::Other(format!("Update check failed: {}", e))
+}
@@ -0,0 +1,401 @@
+//! "Organize with Stash" in file manager context menus: Explorer on Windows,
+//! Nautilus and Dolphin on Linux. Each entry opens a terminal running
+//! `stash organize <folder> --interactive`, which previews the run and asks
+//! before moving anything. On macOS a Finder Quick Action organizes the
+//! selected folders directly and posts a notification for each.
+
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use crate::errors::{FileOrganizerError, Result};
+
+pub const MENU_LABEL: &str = "Organize with Stash";
+
+/// Explorer menus under the current user's classes: one on folders, one on a
+/// folder's background. No administrator rights needed.
+const WINDOWS_KEYS: [(&str, &str); 2] = [
+    (r"HKCU\Software\Classes\Directory\shell\Stash", "%1"),
+    (r"HKCU\Software\Classes\Directory\Background\shell\Stash", "%V"),
+];
+
+/// One piece of a shell integration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Integration {
+    /// A file written into a file manager's script or menu directory
+    File { path: PathBuf, contents: String, executable: bool },
+    /// A directory of files, e.g. a macOS `.workflow` bundle; removed as a whole
+    Bundle { path: PathBuf, files: Vec<(PathBuf, String)> },
+    /// A Windows registry key with a default value and a command
+    Registry { key: String, command: String },
+}
+
+impl fmt::Display for Integration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Integration::File { path, .. } | Integration::Bundle { path, .. } => write!(f, "{}", path.display()),
+            Integration::Registry { key, .. } => write!(f, "{}", key),
+        }
+    }
+}
+
+/// Registry keys for Explorer, running `exe`
+pub fn windows_entries(exe: &Path) -> Vec<Integration> {
+    WINDOWS_KEYS
+        .iter()
+        .map(|(key, folder)| Integration::Registry {
+            key: key.to_string(),
+            command: format!("\"{}\" organize \"{}\" --interactive", exe.display(), folder),
+        })
+        .collect()
+}
+
+/// A Nautilus script; Nautilus passes the selected folders as arguments
+pub fn nautilus_script(exe: &Path) -> String {
+    format!(
+        "#!/bin/sh\n\
+         # {label}: installed by `stash shell-integration install`\n\
+         [ $# -eq 0 ] && set -- \"$(pwd)\"\n\
+         for dir in \"$@\"; do\n    \
+             [ -d \"$dir\" ] || continue\n    \
+             gnome-terminal --wait -- {exe} organize \"$dir\" --interactive\n\
+         done\n",
+        label = MENU_LABEL,
+        exe = shell_quote(&exe.to_string_lossy()),
+    )
+}
+
+/// A Dolphin service menu on folders
+pub fn dolphin_service_menu(exe: &Path) -> String {
+    format!(
+        "[Desktop Entry]\n\
+         Type=Service\n\
+         MimeType=inode/directory;\n\
+         Actions=organizeWithStash;\n\
+         X-KDE-ServiceTypes=KonqPopupMenu/Plugin\n\
+         X-KDE-Priority=TopLevel\n\
+         \n\
+         [Desktop Action organizeWithStash]\n\
+         Name={label}\n\
+         Icon=folder-sync\n\
+         Exec=konsole -e \"{exe}\" organize %f --interactive\n",
+        label = MENU_LABEL,
+        exe = exe.display().to_string().replace('\\', "\\\\").replace('"', "\\\""),
+    )
+}
+
+/// Files for Nautilus and Dolphin under `data_dir` (`~/.local/share`)
+pub fn linux_entries(exe: &Path, data_dir: &Path) -> Vec<Integration> {
+    vec![
+        Integration::File {
+            path: data_dir.join("nautilus").join("scripts").join(MENU_LABEL),
+            contents: nautilus_script(exe),
+            executable: true,
+        },
+        // Dolphin 6 only loads service menus that are executable
+        Integration::File {
+            path: data_dir.join("kio").join("servicemenus").join("stash-organize.desktop"),
+            contents: dolphin_service_menu(exe),
+            executable: true,
+        },
+    ]
+}
+
+/// Shell script run by the Quick Action; Finder passes the selected folders
+/// as arguments. It has no terminal to preview a first run in.
+pub fn finder_script(exe: &Path, config: Option<&Path>) -> String {
+    let config = config
+        .map(|c| format!(" --config {}", shell_quote(&c.to_string_lossy())))
+        .unwrap_or_default();
+    format!(
+        "notify() {{\n    \
+             osascript -e 'on run argv' -e 'display notification (item 1 of argv) with title \"Stash\"' -e 'end run' \"$1\"\n\
+         }}\n\
+         for dir in \"$@\"; do\n    \
+             if {exe} organize \"$dir\" --yes --progress none{config}; then\n        \
+                 notify \"Organized $(basename \"$dir\")\"\n    \
+             else\n        \
+                 notify \"Could not organize $(basename \"$dir\")\"\n    \
+             fi\n\
+         done\n",
+        exe = shell_quote(&exe.to_string_lossy()),
+    )
+}
+
+/// The Quick Action bundle under `services_dir` (`~/Library/Services`)
+pub fn finder_entries(exe: &Path, services_dir: &Path, config: Option<&Path>) -> Vec<Integration> {
+    let contents = Path::new("Contents");
+    vec![Integration::Bundle {
+        path: services_dir.join(format!("{}.workflow", MENU_LABEL)),
+        files: vec![
+            (contents.join("Info.plist"), INFO_PLIST.replace("{label}", MENU_LABEL)),
+            (contents.join("document.wflow"), DOCUMENT_WFLOW.replace("{script}", &xml_escape(&finder_script(exe, config)))),
+        ],
+    }]
+}
+
+/// The entries for this platform. `finder` asks for the macOS Quick Action,
+/// which runs stash with `config`.
+pub fn entries(exe: &Path, finder: bool, config: Option<&Path>) -> Result<Vec<Integration>> {
+    if finder {
+        if !cfg!(target_os = "macos") {
+            return Err(FileOrganizerError::Other("Finder Quick Actions are only available on macOS".into()));
+        }
+        let home = dirs::home_dir().ok_or_else(|| FileOrganizerError::Other("no home directory found".into()))?;
+        Ok(finder_entries(exe, &home.join("Library").join("Services"), config))
+    } else if cfg!(target_os = "macos") {
+        Err(FileOrganizerError::Other("on macOS, pass --finder to add a Finder Quick Action".into()))
+    } else if cfg!(windows) {
+        Ok(windows_entries(exe))
+    } else if cfg!(target_os = "linux") {
+        let data_dir = dirs::data_dir()
+            .ok_or_else(|| FileOrganizerError::Other("no data directory found".into()))?;
+        Ok(linux_entries(exe, &data_dir))
+    } else {
+        Err(FileOrganizerError::Other("shell integration is available on Windows and Linux".into()))
+    }
+}
+
+/// Add every entry, replacing earlier ones
+pub fn install(entries: &[Integration]) -> Result<()> {
+    for entry in entries {
+        match entry {
+            Integration::File { path, contents, executable } => {
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                std::fs::write(path, contents)?;
+                if *executable {
+                    make_executable(path)?;
+                }
+            }
+            Integration::Bundle { path, files } => {
+                // Start clean so files from an older version do not linger
+                remove_dir_if_exists(path)?;
+                for (file, contents) in files {
+                    let file = path.join(file);
+                    if let Some(parent) = file.parent() {
+                        std::fs::create_dir_all(parent)?;
+                    }
+                    std::fs::write(file, contents)?;
+                }
+            }
+            Integration::Registry { key, command } => {
+                reg(&["add", key, "/ve", "/d", MENU_LABEL, "/f"])?;
+                reg(&["add", key, "/v", "Icon", "/d", command.split('"').nth(1).unwrap_or_default(), "/f"])?;
+                reg(&["add", &format!(r"{}\command", key), "/ve", "/d", command, "/f"])?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Remove every entry; ones already gone are fine
+pub fn uninstall(entries: &[Integration]) -> Result<()> {
+    for entry in entries {
+        match entry {
+            Integration::File { path, .. } => match std::fs::remove_file(path) {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(e.into()),
+            },
+            Integration::Bundle { path, .. } => remove_dir_if_exists(path)?,
+            Integration::Registry { key, .. } => {
+                let exists = Command::new("reg").args(["query", key]).output().is_ok_and(|o| o.status.success());
+                if exists {
+                    reg(&["delete", key, "/f"])?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Run `reg.exe` with `args`
+fn reg(args: &[&str]) -> Result<()> {
+    let output = Command::new("reg").args(args).output()?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(FileOrganizerError::Other(format!(
+            "reg {} failed: {}",
+            args.first().unwrap_or(&""),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )))
+    }
+}
+
+fn remove_dir_if_exists(path: &Path) -> Result<()> {
+    match std::fs::remove_dir_all(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+/// Text safe inside a plist `<string>`
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// `'...'` for `sh`, with embedded quotes kept
+fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755))?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn make_executable(_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+/// Registers the workflow as a Finder service on folders
+const INFO_PLIST: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+	<key>NSServices</key>
+	<array>
+		<dict>
+			<key>NSMenuItem</key>
+			<dict>
+				<key>default</key>
+				<string>{label}</string>
+			</dict>
+			<key>NSMessage</key>
+			<string>runWorkflowAsService</string>
+			<key>NSRequiredContext</key>
+			<dict>
+				<key>NSApplicationIdentifier</key>
+				<string>com.apple.finder</string>
+			</dict>
+			<key>NSSendFileTypes</key>
+			<array>
+				<string>public.folder</string>
+			</array>
+		</dict>
+	</array>
+</dict>
+</plist>
+"#;
+
+/// A Quick Action with one "Run Shell Script" step, taking the folders as arguments
+const DOCUMENT_WFLOW: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+	<key>AMApplicationBuild</key>
+	<string>523</string>
+	<key>AMApplicationVersion</key>
+	<string>2.10</string>
+	<key>AMDocumentVersion</key>
+	<string>2</string>
+	<key>actions</key>
+	<array>
+		<dict>
+			<key>action</key>
+			<dict>
+				<key>AMAccepts</key>
+				<dict>
+					<key>Container</key>
+					<string>List</string>
+					<key>Optional</key>
+					<true/>
+					<key>Types</key>
+					<array>
+						<string>com.apple.cocoa.path</string>
+					</array>
+				</dict>
+				<key>AMActionVersion</key>
+				<string>2.0.3</string>
+				<key>AMApplication</key>
+				<array>
+					<string>Automator</string>
+				</array>
+				<key>AMParameterProperties</key>
+				<dict>
+					<key>COMMAND_STRING</key>
+					<dict/>
+					<key>CheckedForUserDefaultShell</key>
+					<dict/>
+					<key>inputMethod</key>
+					<dict/>
+					<key>shell</key>
+					<dict/>
+					<key>source</key>
+					<dict/>
+				</dict>
+				<key>AMProvides</key>
+				<dict>
+					<key>Container</key>
+					<string>List</string>
+					<key>Types</key>
+					<array>
+						<string>com.apple.cocoa.string</string>
+					</array>
+				</dict>
+				<key>ActionBundlePath</key>
+				<string>/System/Library/Automator/Run Shell Script.action</string>
+				<key>ActionName</key>
+				<string>Run Shell Script</string>
+				<key>ActionParameters</key>
+				<dict>
+					<key>COMMAND_STRING</key>
+					<string>{script}</string>
+					<key>CheckedForUserDefaultShell</key>
+					<true/>
+					<key>inputMethod</key>
+					<integer>1</integer>
+					<key>shell</key>
+					<string>/bin/sh</string>
+					<key>source</key>
+					<string></string>
+				</dict>
+				<key>BundleIdentifier</key>
+				<string>com.apple.RunShellScript</string>
+				<key>CFBundleVersion</key>
+				<string>2.0.3</string>
+				<key>CanShowSelectedItemsWhenRun</key>
+				<false/>
+				<key>CanShowWhenRun</key>
+				<true/>
+				<key>Category</key>
+				<array>
+					<string>AMCategoryUtilities</string>
+				</array>
+				<key>Class Name</key>
+				<string>RunShellScriptAction</string>
+				<key>InputUUID</key>
+				<string>5A0D3A9E-4C4B-4E0F-9D4A-6E2B1F0C7A11</string>
+				<key>OutputUUID</key>
+				<string>8F6C2B7D-1E3A-4B59-A0C8-3D9E4F5B6C22</string>
+				<key>UUID</key>
+				<string>C3B1E8F4-7A2D-4E6B-9F1C-2D8A5B4E3F33</string>
+				<key>isViewVisible</key>
+				<integer>1</integer>
+			</dict>
+		</dict>
+	</array>
+	<key>connectors</key>
+	<dict/>
+	<key>workflowMetaData</key>
+	<dict>
+		<key>serviceInputTypeIdentifier</key>
+		<string>com.apple.Automator.fileSystemObject.folder</string>
+		<key>serviceOutputTypeIdentifier</key>
+		<string>com.apple.Automator.nothing</string>
+		<key>serviceProcessesInput</key>
+		<integer>0</integer>
+		<key>workflowTypeIdentifier</key>
+		<string>com.apple.Automator.servicesMenu</string>
+	</dict>
+</dict>
+</plist>
+"#;
@@ -0,0 +1,194 @@
+use std::{
+    collections::BTreeMap,
+    fmt,
+    io::{Read, Write},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use colored::*;
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use serde::{Deserialize, Serialize};
+use walkdir::WalkDir;
+
+use crate::{
+    errors::{FileOrganizerError, Result},
+    stats::format_size,
+    utils::{to_unix, PORTABLE_DB_DIR},
+};
+
+/// One file in a directory snapshot
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotEntry {
+    /// Path relative to the snapshot root, `/`-separated
+    pub path: String,
+    pub size: u64,
+    /// Modification time as unix seconds
+    pub modified: Option<i64>,
+}
+
+/// A recorded listing of a directory at a point in time
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub id: i64,
+    pub root: PathBuf,
+    /// Unix seconds
+    pub created_at: i64,
+    pub entries: Vec<SnapshotEntry>,
+}
+
+/// Snapshot row without its listing, for `snapshot list`
+#[derive(Debug, Clone)]
+pub struct SnapshotInfo {
+    pub id: i64,
+    pub root: PathBuf,
+    pub created_at: i64,
+    pub file_count: usize,
+}
+
+/// Files that differ between two snapshots, keyed by relative path
+#[derive(Debug, Clone, Default)]
+pub struct SnapshotDiff {
+    pub added: Vec<SnapshotEntry>,
+    pub removed: Vec<SnapshotEntry>,
+    /// (before, after) pairs whose size or mtime changed
+    pub changed: Vec<(SnapshotEntry, SnapshotEntry)>,
+}
+
+impl SnapshotDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Compare two listings. Output is sorted by path.
+pub fn diff(old: &[SnapshotEntry], new: &[SnapshotEntry]) -> SnapshotDiff {
+    let old: BTreeMap<&str, &SnapshotEntry> = old.iter().map(|e| (e.path.as_str(), e)).collect();
+    let new: BTreeMap<&str, &SnapshotEntry> = new.iter().map(|e| (e.path.as_str(), e)).collect();
+
+    let mut result = SnapshotDiff::default();
+    for (path, after) in &new {
+        match old.get(path) {
+            None => result.added.push((*after).clone()),
+            Some(before) if before.size != after.size || before.modified != after.modified => {
+                result.changed.push(((*before).clone(), (*after).clone()));
+            }
+            Some(_) => {}
+        }
+    }
+    result.removed = old
+        .iter()
+        .filter(|(path, _)| !new.contains_key(*path))
+        .map(|(_, e)| (*e).clone())
+        .collect();
+
+    result
+}
+
+impl fmt::Display for SnapshotDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "No differences");
+        }
+
+        for e in &self.added {
+            writeln!(f, "{} {} ({})", "+".green(), e.path, format_size(e.size))?;
+        }
+        for e in &self.removed {
+            writeln!(f, "{} {} ({})", "-".red(), e.path, format_size(e.size))?;
+        }
+        for (before, after) in &self.changed {
+            writeln!(f, "{} {} ({} → {})",
+                "~".yellow(),
+                after.path,
+                format_size(before.size),
+                format_size(after.size)
+            )?;
+        }
+
+        writeln!(f, "\n{} added, {} removed, {} changed",
+            self.added.len().to_string().green(),
+            self.removed.len().to_string().red(),
+            self.changed.len().to_string().yellow()
+        )
+    }
+}
+
+/// Walk `root` recursively and record every file (hidden ones included).
+/// The portable index directory is left out since it changes on every run.
+pub fn capture(root: &Path) -> Result<Vec<SnapshotEntry>> {
+    let mut entries = Vec::new();
+
+    let walker = WalkDir::new(root)
+        .min_depth(1)
+        .follow_links(false)
+        .into_iter()
+        .filter_entry(|e| e.file_name() != PORTABLE_DB_DIR);
+
+    for entry in walker {
+        let entry = match entry {
+            Ok(e) => e,
+            Err(e) => {
+                tracing::warn!(target: "snapshot", "Unreadable entry: {}", e);
+                continue;
+            }
+        };
+        if !entry.file_type().is_file() {
+            continue;
+        }
+
+        let metadata = match entry.metadata() {
+            Ok(m) => m,
+            Err(e) => {
+                tracing::warn!(target: "snapshot", "No metadata for {:?}: {}", entry.path(), e);
+                continue;
+            }
+        };
+
+        let rel = entry.path().strip_prefix(root).unwrap_or(entry.path());
+        entries.push(SnapshotEntry {
+            path: rel
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/"),
+            size: metadata.len(),
+            modified: to_unix(metadata.modified().ok()),
+        });
+    }
+
+    entries.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(entries)
+}
+
+/// Capture `root` on a blocking thread
+pub async fn capture_async(root: &Path) -> Result<Vec<SnapshotEntry>> {
+    let root = root.to_path_buf();
+    tokio::task::spawn_blocking(move || capture(&root)).await?
+}
+
+/// Serialize entries as gzip-compressed JSON
+pub fn compress(entries: &[SnapshotEntry]) -> Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    let json = serde_json::to_vec(entries).map_err(|source| FileOrganizerError::Json {
+        path: PathBuf::from("<snapshot>"),
+        source,
+    })?;
+    encoder.write_all(&json)?;
+    Ok(encoder.finish()?)
+}
+
+/// Inverse of [`compress`]
+pub fn decompress(data: &[u8]) -> Result<Vec<SnapshotEntry>> {
+    let mut json = Vec::new();
+    GzDecoder::new(data).read_to_end(&mut json)?;
+    serde_json::from_slice(&json).map_err(|source| FileOrganizerError::Json {
+        path: PathBuf::from("<snapshot>"),
+        source,
+    })
+}
+
+/// Current time as unix seconds
+pub(crate) fn now_unix() -> i64 {
+    to_unix(Some(SystemTime::now())).unwrap_or_default()
+}
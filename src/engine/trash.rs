@@ -0,0 +1,293 @@
+//! stash's own trash: dedupe and retention never delete, they move files to
+//! `<trash>/<run-id>/` (the run id is the unix time the run started, or the
+//! next free second), where
+//! `stash trash restore` (or `stash dedupe undo`) can bring them back.
+//! Nothing leaves the trash until `stash trash empty`, so there is a grace
+//! period independent of the OS trash.
+
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use colored::*;
+
+use crate::{
+    conflict_resolver::resolve_conflict_in,
+    errors::{FileOrganizerError, Result},
+    file_mover::FileMover,
+    dedupe::DedupeAction,
+    index::{ActionRecord, Db},
+    snapshot::now_unix,
+    stats::format_size,
+};
+
+/// Create the folder for a new run's trashed files under `trash_root`. It is
+/// named after the current unix time, or the next second no other run has
+/// taken, so two runs started in the same second never share one.
+pub fn run_dir(trash_root: &Path) -> Result<PathBuf> {
+    std::fs::create_dir_all(trash_root)?;
+    let mut id = now_unix();
+    loop {
+        let dir = trash_root.join(id.to_string());
+        match std::fs::create_dir(&dir) {
+            Ok(()) => return Ok(dir),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => id += 1,
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+/// Remove the folder [`run_dir`] made if the run trashed nothing into it
+pub(crate) async fn discard_if_empty(run_dir: &Path) {
+    // Only succeeds once nothing is in it
+    let _ = tokio::fs::remove_dir(run_dir).await;
+}
+
+/// Move `path` into `run_dir`, returning where it went
+pub(crate) async fn move_to(mover: &FileMover, path: &Path, run_dir: &Path) -> Result<PathBuf> {
+    let name = path.file_name().ok_or_else(|| FileOrganizerError::InvalidPath(path.to_path_buf()))?;
+    let dest = resolve_conflict_in(mover.fs(), &run_dir.join(name), false).await?;
+    mover.move_file(path, &dest).await?;
+    Ok(dest)
+}
+
+/// Move the trashed file of `record` back to where it was, next to it
+/// under a free name if that is taken again. Returns where it went.
+pub(crate) async fn move_back(mover: &FileMover, record: &ActionRecord) -> Result<PathBuf> {
+    // A portable index read without its root; never resolve it against the
+    // working directory
+    if record.path.is_relative() {
+        return Err(FileOrganizerError::InvalidPath(record.path.clone()));
+    }
+    let dest = resolve_conflict_in(mover.fs(), &record.path, false).await?;
+    mover.move_file(&record.target, &dest).await?;
+    Ok(dest)
+}
+
+/// One run's folder in the trash
+#[derive(Debug, Clone)]
+pub struct TrashRun {
+    /// Unix time the run started, also the folder name
+    pub id: i64,
+    pub dir: PathBuf,
+    pub files: usize,
+    pub bytes: u64,
+    /// Files without an undo record in the index the run was listed with
+    /// (see [`list_in`]), which [`restore`] can't put back from it
+    pub untracked: Option<usize>,
+}
+
+impl TrashRun {
+    /// How long ago the files were trashed
+    pub fn age(&self) -> Duration {
+        Duration::from_secs(now_unix().saturating_sub(self.id).max(0) as u64)
+    }
+}
+
+impl fmt::Display for TrashRun {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "#{}  {} days ago  {} files, {}  {}",
+            self.id,
+            self.age().as_secs() / 86_400,
+            self.files,
+            format_size(self.bytes),
+            self.dir.display()
+        )?;
+        match self.untracked {
+            Some(untracked) if untracked > 0 => write!(f, "  ({} untracked)", untracked.to_string().yellow()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Runs in the trash, oldest first. Folders not named like a run are left out.
+pub fn list(trash_root: &Path) -> Result<Vec<TrashRun>> {
+    let entries = match std::fs::read_dir(trash_root) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut runs = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        let Some(id) = entry.file_name().to_str().and_then(|name| name.parse::<i64>().ok()) else {
+            continue;
+        };
+        if !entry.file_type()?.is_dir() {
+            continue;
+        }
+        let (files, bytes) = walkdir::WalkDir::new(entry.path())
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+            .fold((0, 0), |(files, bytes), e| (files + 1, bytes + e.metadata().map(|m| m.len()).unwrap_or(0)));
+        runs.push(TrashRun { id, dir: entry.path(), files, bytes, untracked: None });
+    }
+    runs.sort_by_key(|run| run.id);
+    Ok(runs)
+}
+
+/// [`list`], counting the files of each run that have no undo record in `db`
+pub async fn list_in(db: &Db, trash_root: &Path) -> Result<Vec<TrashRun>> {
+    let records = db.list_actions(false).await?;
+    let mut runs = list(trash_root)?;
+    for run in &mut runs {
+        let trashed = trashed_in(&records, &run.dir);
+        run.untracked = Some(untracked_in(&run.dir, &trashed).count());
+    }
+    Ok(runs)
+}
+
+/// Open undo records of files trashed into `run_dir`
+fn trashed_in<'a>(records: &'a [ActionRecord], run_dir: &Path) -> Vec<&'a ActionRecord> {
+    records
+        .iter()
+        .filter(|record| record.kind == DedupeAction::Trash.as_str() && record.target.starts_with(run_dir))
+        .collect()
+}
+
+/// Files in `run_dir` none of `trashed` accounts for
+fn untracked_in<'a>(run_dir: &Path, trashed: &'a [&ActionRecord]) -> impl Iterator<Item = PathBuf> + 'a {
+    walkdir::WalkDir::new(run_dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .map(|e| e.into_path())
+        .filter(|path| !trashed.iter().any(|record| &record.target == path))
+}
+
+/// What [`empty`] did
+#[derive(Debug, Default)]
+pub struct EmptyReport {
+    /// Runs purged (or that would be, in a dry run)
+    pub purged: Vec<TrashRun>,
+    /// Runs younger than the cutoff
+    pub kept: usize,
+    pub errors: Vec<(PathBuf, FileOrganizerError)>,
+}
+
+impl fmt::Display for EmptyReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let files: usize = self.purged.iter().map(|run| run.files).sum();
+        let bytes: u64 = self.purged.iter().map(|run| run.bytes).sum();
+        writeln!(f, "{}", "Empty trash".bold().blue())?;
+        writeln!(f, "  Purged:  {} runs, {} files, {}", self.purged.len(), files.to_string().green(), format_size(bytes))?;
+        writeln!(f, "  Kept:    {} runs", self.kept)?;
+        writeln!(f, "  Errors:  {} runs", self.errors.len().to_string().red())?;
+        for (path, error) in &self.errors {
+            writeln!(f, "    {}: {}", path.display(), error)?;
+        }
+        Ok(())
+    }
+}
+
+/// Permanently delete every run trashed longer than `older_than` ago (all of
+/// them when `None`), and drop their actions from `db` so `stash dedupe
+/// undo` no longer offers them
+pub async fn empty(db: &Db, trash_root: &Path, older_than: Option<Duration>, dry_run: bool) -> Result<EmptyReport> {
+    let mut report = EmptyReport::default();
+    for run in list(trash_root)? {
+        if older_than.is_some_and(|limit| run.age() < limit) {
+            report.kept += 1;
+            continue;
+        }
+        if dry_run {
+            println!("Would purge {:?} ({} files, {} days old)", run.dir, run.files, run.age().as_secs() / 86_400);
+            report.purged.push(run);
+            continue;
+        }
+
+        if let Err(e) = tokio::fs::remove_dir_all(&run.dir).await {
+            report.errors.push((run.dir, e.into()));
+            continue;
+        }
+        let ids: Vec<i64> = db
+            .list_actions(false)
+            .await?
+            .into_iter()
+            .filter(|action| action.target.starts_with(&run.dir))
+            .map(|action| action.id)
+            .collect();
+        db.delete_actions(&ids).await?;
+        tracing::info!(target: "trash", "Purged {:?} ({} files, {} undo records)", run.dir, run.files, ids.len());
+        report.purged.push(run);
+    }
+    Ok(report)
+}
+
+/// What [`restore`] did
+#[derive(Debug, Default)]
+pub struct RestoreReport {
+    /// Trashed files and where they went back to (or would, in a dry run)
+    pub restored: Vec<(PathBuf, PathBuf)>,
+    /// Files in the runs without an undo record in this index, e.g. trashed
+    /// through a portable index
+    pub untracked: Vec<PathBuf>,
+    pub errors: Vec<(PathBuf, FileOrganizerError)>,
+}
+
+impl fmt::Display for RestoreReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", "Restore from trash".bold().blue())?;
+        writeln!(f, "  Restored:   {} files", self.restored.len().to_string().green())?;
+        for (_, path) in &self.restored {
+            writeln!(f, "    {}", path.display())?;
+        }
+        writeln!(f, "  Untracked:  {} files", self.untracked.len().to_string().yellow())?;
+        for path in &self.untracked {
+            writeln!(f, "    {}", path.display())?;
+        }
+        writeln!(f, "  Errors:     {} files", self.errors.len().to_string().red())?;
+        for (path, error) in &self.errors {
+            writeln!(f, "    {}: {}", path.display(), error)?;
+        }
+        Ok(())
+    }
+}
+
+/// Put every file of the given runs (all of them when `runs` is empty) back
+/// where it was trashed from. The undo records are closed, and an organized
+/// file that has to take a new name is re-pointed in the index, so `stash
+/// revert` still finds it. Emptied run folders are removed.
+pub async fn restore(db: &Db, trash_root: &Path, runs: &[i64], dry_run: bool) -> Result<RestoreReport> {
+    let mover = FileMover::new();
+    let records = db.list_actions(false).await?;
+    let mut report = RestoreReport::default();
+
+    for run in list(trash_root)? {
+        if !runs.is_empty() && !runs.contains(&run.id) {
+            continue;
+        }
+        let trashed = trashed_in(&records, &run.dir);
+        report.untracked.extend(untracked_in(&run.dir, &trashed));
+
+        for record in trashed {
+            if dry_run {
+                println!("Would restore {:?} → {:?}", record.target, record.path);
+                report.restored.push((record.target.clone(), record.path.clone()));
+                continue;
+            }
+            match move_back(&mover, record).await {
+                Ok(dest) => {
+                    db.mark_action_reverted(record.id).await?;
+                    if dest != record.path {
+                        db.replace_dest_path(&record.path, &dest).await?;
+                    }
+                    tracing::info!(target: "trash", "Restored {:?} -> {:?}", record.target, dest);
+                    report.restored.push((record.target.clone(), dest));
+                }
+                Err(e) => report.errors.push((record.target.clone(), e)),
+            }
+        }
+        if !dry_run {
+            // Only succeeds once nothing is left in it
+            let _ = tokio::fs::remove_dir(&run.dir).await;
+        }
+    }
+    Ok(report)
+}
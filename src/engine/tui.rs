@@ -0,0 +1,375 @@
+//! `stash tui`: an organize run behind a live terminal dashboard. The
+//! engine reports into a shared [`DashboardState`] through
+//! [`ProgressReporter`], which is redrawn a few times a second next to a
+//! scrolling log of outcomes. Keys steer the run through its
+//! [`RunControl`] and [`CancelToken`](crate::cancel::CancelToken).
+
+use std::{
+    collections::{BTreeMap, VecDeque},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    layout::{Constraint, Layout},
+    style::{Color, Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Gauge, List, ListItem, Paragraph, Row, Table, TableState},
+    Frame,
+};
+
+use crate::{
+    control::RunControl,
+    errors::Result,
+    metadata::FileCategory,
+    organizer::{organise_roots, OrganizeOptions},
+    progress::{Progress, ProgressReporter},
+    stats::{format_size, CategoryTotal, FileOutcome, Summary},
+};
+
+/// How often the dashboard is redrawn
+const TICK: Duration = Duration::from_millis(100);
+
+/// Outcomes kept for the log
+const LOG_LINES: usize = 500;
+
+/// Where the run is
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum Phase {
+    /// Walking the roots; nothing is known about the files yet
+    #[default]
+    Scanning,
+    /// Classifying and moving, under the engine's label
+    Organizing(String),
+    Done,
+}
+
+/// One line of the outcome log
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogLine {
+    pub kind: LogKind,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogKind {
+    Moved,
+    Renamed,
+    Skipped,
+    Failed,
+}
+
+/// Everything the dashboard shows, updated from the engine's tasks
+#[derive(Debug, Default)]
+pub struct DashboardState {
+    pub phase: Phase,
+    pub total: u64,
+    pub done: u64,
+    /// Files being classified or moved right now
+    pub in_flight: usize,
+    /// Moved and renamed files per top-level category
+    pub categories: BTreeMap<&'static str, CategoryTotal>,
+    pub skipped: usize,
+    pub failed: usize,
+    /// Newest last, at most [`LOG_LINES`]
+    pub log: VecDeque<LogLine>,
+}
+
+impl DashboardState {
+    /// Count `outcome` and add it to the log
+    pub fn record(&mut self, outcome: &FileOutcome) {
+        let line = match outcome {
+            FileOutcome::Moved(report) => {
+                self.add_category(report.category, report.size);
+                LogLine { kind: LogKind::Moved, text: format!("{} → {}", report.src.display(), report.dest.display()) }
+            }
+            FileOutcome::Renamed { report, new_path } => {
+                self.add_category(report.category, report.size);
+                LogLine { kind: LogKind::Renamed, text: format!("{} → {}", report.src.display(), new_path.display()) }
+            }
+            FileOutcome::Skipped { src, reason, .. } => {
+                self.skipped += 1;
+                LogLine { kind: LogKind::Skipped, text: format!("{} ({})", src.display(), reason) }
+            }
+            FileOutcome::DuplicateSkipped { src, existing, .. } => {
+                self.skipped += 1;
+                LogLine { kind: LogKind::Skipped, text: format!("{} (copy of {})", src.display(), existing.display()) }
+            }
+            FileOutcome::Err(err) => {
+                self.failed += 1;
+                LogLine {
+                    kind: LogKind::Failed,
+                    text: format!("{} at {:?} stage: {}", err.path.display(), err.stage, err.error),
+                }
+            }
+        };
+        if self.log.len() == LOG_LINES {
+            self.log.pop_front();
+        }
+        self.log.push_back(line);
+    }
+
+    fn add_category(&mut self, category: &'static str, size: u64) {
+        let total = self.categories.entry(category).or_default();
+        total.files += 1;
+        total.bytes += size;
+    }
+
+    /// Rows of the category table: the built-in categories, then any rule
+    /// categories seen so far
+    pub fn category_names(&self) -> Vec<&'static str> {
+        let mut names = FileCategory::TOP_LEVEL.to_vec();
+        names.extend(self.categories.keys().filter(|name| !FileCategory::TOP_LEVEL.contains(name)));
+        names
+    }
+}
+
+/// Reports engine progress into a shared [`DashboardState`]
+#[derive(Debug, Clone, Default)]
+pub struct Dashboard {
+    state: Arc<Mutex<DashboardState>>,
+}
+
+impl Dashboard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn state(&self) -> Arc<Mutex<DashboardState>> {
+        self.state.clone()
+    }
+}
+
+impl ProgressReporter for Dashboard {
+    fn start(&self, label: &str, total: u64) {
+        let mut state = self.state.lock().unwrap();
+        state.phase = Phase::Organizing(label.to_string());
+        state.total = total;
+        state.done = 0;
+    }
+
+    fn inc(&self, n: u64) {
+        self.state.lock().unwrap().done += n;
+    }
+
+    fn finish(&self) {
+        self.state.lock().unwrap().phase = Phase::Done;
+    }
+
+    fn file_started(&self, _path: &Path) {
+        self.state.lock().unwrap().in_flight += 1;
+    }
+
+    fn file_done(&self, outcome: &FileOutcome) {
+        let mut state = self.state.lock().unwrap();
+        state.in_flight = state.in_flight.saturating_sub(1);
+        state.record(outcome);
+    }
+}
+
+/// Organize `roots` with `options` behind the dashboard, until the run is
+/// done and the dashboard closed. The run's own progress reporter is
+/// replaced by the dashboard, and the run is [`quiet`](OrganizeOptions::quiet):
+/// failed files and the report's path are in the returned [`Summary`], for
+/// showing once the terminal is restored.
+///
+/// Keys: `space`/`p` pause and resume, `↑`/`↓` pick a category and
+/// `s`/`enter` skip it or organize it again, `q`/`esc` cancel the run (or
+/// close the dashboard once it is done).
+pub async fn organize_with_dashboard(roots: &[PathBuf], options: &OrganizeOptions) -> Result<Summary> {
+    let dashboard = Dashboard::new();
+    let options = OrganizeOptions { progress: Progress::new(dashboard.clone()), quiet: true, ..options.clone() };
+    let mut ui = Ui::new(dashboard.state(), options.control.clone());
+
+    let roots = roots.to_vec();
+    let cancel = options.cancel.clone();
+    let mut run = tokio::spawn(async move { organise_roots(&roots, &options).await });
+
+    let mut keys = KeyReader::spawn();
+    let mut terminal = ratatui::init();
+    let mut ticker = tokio::time::interval(TICK);
+    let mut result = None;
+
+    let ui_result = loop {
+        if let Err(e) = terminal.draw(|frame| ui.draw(frame, result.is_some())) {
+            break Err(e);
+        }
+        tokio::select! {
+            finished = &mut run, if result.is_none() => {
+                result = Some(finished);
+                // Lines printed by the run are not part of the frame
+                if let Err(e) = terminal.clear() {
+                    break Err(e);
+                }
+            }
+            _ = ticker.tick() => {}
+            key = keys.rx.recv() => match key {
+                Some(key) if ui.handle_key(key) => {
+                    if result.is_some() {
+                        break Ok(());
+                    }
+                    cancel.cancel();
+                }
+                Some(_) => {}
+                None => break Ok(()),
+            },
+        }
+    };
+    ratatui::restore();
+    drop(keys);
+    ui_result?;
+
+    match result {
+        Some(finished) => finished?,
+        // Closed by a read error before the run ended: let it stop cleanly
+        None => {
+            cancel.cancel();
+            run.await?
+        }
+    }
+}
+
+/// Reads key presses on a thread of its own, so waiting for input never
+/// holds up the runtime. The channel closes once input can't be read.
+struct KeyReader {
+    rx: tokio::sync::mpsc::UnboundedReceiver<KeyEvent>,
+    stop: Arc<AtomicBool>,
+}
+
+impl KeyReader {
+    fn spawn() -> Self {
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = stop.clone();
+        std::thread::spawn(move || {
+            while !stopped.load(Ordering::Relaxed) {
+                match event::poll(TICK) {
+                    Ok(false) => continue,
+                    Ok(true) => {}
+                    Err(_) => break,
+                }
+                match event::read() {
+                    Ok(Event::Key(key)) if key.kind == KeyEventKind::Press => {
+                        if tx.send(key).is_err() {
+                            break;
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(_) => break,
+                }
+            }
+        });
+        Self { rx, stop }
+    }
+}
+
+impl Drop for KeyReader {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+struct Ui {
+    state: Arc<Mutex<DashboardState>>,
+    control: RunControl,
+    table: TableState,
+}
+
+impl Ui {
+    fn new(state: Arc<Mutex<DashboardState>>, control: RunControl) -> Self {
+        Self { state, control, table: TableState::default().with_selected(0) }
+    }
+
+    /// Act on `key`; returns true when it asks to quit
+    fn handle_key(&mut self, key: KeyEvent) -> bool {
+        let rows = self.state.lock().unwrap().category_names();
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return true,
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return true,
+            KeyCode::Char(' ') | KeyCode::Char('p') => {
+                self.control.toggle_pause();
+            }
+            KeyCode::Up | KeyCode::Char('k') => self.table.select_previous(),
+            KeyCode::Down | KeyCode::Char('j') => {
+                let next = self.table.selected().map_or(0, |i| (i + 1).min(rows.len() - 1));
+                self.table.select(Some(next));
+            }
+            KeyCode::Char('s') | KeyCode::Enter => {
+                if let Some(&name) = self.table.selected().and_then(|i| rows.get(i)) {
+                    self.control.toggle_category(name);
+                }
+            }
+            _ => {}
+        }
+        false
+    }
+
+    fn draw(&mut self, frame: &mut Frame, finished: bool) {
+        let state = self.state.lock().unwrap();
+        let [header, body, footer] =
+            Layout::vertical([Constraint::Length(3), Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+        let [table_area, log_area] =
+            Layout::horizontal([Constraint::Length(44), Constraint::Min(0)]).areas(body);
+
+        let status = match &state.phase {
+            Phase::Scanning => "Scanning".to_string(),
+            Phase::Organizing(label) if self.control.is_paused() => format!("{} (paused)", label),
+            Phase::Organizing(label) => label.clone(),
+            Phase::Done => "Done".to_string(),
+        };
+        let ratio = if state.total > 0 { (state.done as f64 / state.total as f64).min(1.0) } else { 0.0 };
+        let gauge = Gauge::default()
+            .block(Block::bordered().title(" stash ".bold()))
+            .gauge_style(Style::default().fg(Color::Magenta))
+            .ratio(ratio)
+            .label(format!(
+                "{}  {}/{}  {} in progress  {} skipped  {} failed",
+                status, state.done, state.total, state.in_flight, state.skipped, state.failed
+            ));
+        frame.render_widget(gauge, header);
+
+        let skipped = self.control.skipped();
+        let rows = state.category_names().into_iter().map(|name| {
+            let total = state.categories.get(name).copied().unwrap_or_default();
+            let row = Row::new([
+                name.to_string(),
+                total.files.to_string(),
+                format_size(total.bytes),
+                if skipped.contains(name) { "skip".to_string() } else { String::new() },
+            ]);
+            if skipped.contains(name) { row.dark_gray() } else { row }
+        });
+        let table = Table::new(
+            rows,
+            [Constraint::Length(14), Constraint::Length(8), Constraint::Length(12), Constraint::Length(5)],
+        )
+        .header(Row::new(["Category", "Files", "Size", ""]).bold())
+        .block(Block::bordered().title(" Categories "))
+        .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(table, table_area, &mut self.table);
+
+        let visible = log_area.height.saturating_sub(2) as usize;
+        let items = state.log.iter().skip(state.log.len().saturating_sub(visible)).map(|line| {
+            let (tag, color) = match line.kind {
+                LogKind::Moved => ("moved   ", Color::Green),
+                LogKind::Renamed => ("renamed ", Color::Cyan),
+                LogKind::Skipped => ("skipped ", Color::Yellow),
+                LogKind::Failed => ("failed  ", Color::Red),
+            };
+            ListItem::new(Line::from(vec![Span::styled(tag, Style::default().fg(color)), Span::raw(line.text.as_str())]))
+        });
+        frame.render_widget(List::new(items).block(Block::bordered().title(" Log ")), log_area);
+
+        let help = if finished {
+            "q close"
+        } else {
+            "space pause/resume  ↑↓ select  s skip category  q cancel"
+        };
+        frame.render_widget(Paragraph::new(help).dark_gray(), footer);
+    }
+}
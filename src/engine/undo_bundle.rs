@@ -0,0 +1,277 @@
+use std::{
+    fmt,
+    fs::File,
+    path::{Component, Path, PathBuf},
+};
+
+use colored::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    archive,
+    conflict_resolver::resolve_conflict_in,
+    errors::{FileOrganizerError, Result},
+    file_mover::FileMover,
+    guard::Guard,
+    hasher::rehash_like,
+    index::{Db, RunMove},
+    instance_lock::{default_lock_dir, InstanceLock},
+    reverter::cleanup_empty_dirs,
+    snapshot::now_unix,
+    utils::{remap_path, PathMapping},
+};
+
+/// Name of the manifest inside an undo bundle
+pub const MANIFEST_NAME: &str = "manifest.json";
+
+/// Bundle layout version; bumped on incompatible manifest changes
+pub const BUNDLE_VERSION: u32 = 1;
+
+/// Largest manifest read back, well above what any real run records
+const MAX_MANIFEST_SIZE: u64 = 1 << 30;
+
+/// Everything needed to revert one organize run, with absolute paths as
+/// they were on the exporting machine
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UndoManifest {
+    pub version: u32,
+    pub run_id: i64,
+    pub root: PathBuf,
+    pub dest_root: PathBuf,
+    /// Unix seconds when the run happened
+    pub created_at: i64,
+    /// Unix seconds when the bundle was written
+    pub exported_at: i64,
+    pub moves: Vec<RunMove>,
+}
+
+impl UndoManifest {
+    /// Build the manifest for run `run_id` from the index
+    pub async fn from_run(db: &Db, run_id: i64) -> Result<Self> {
+        let info = db
+            .get_run(run_id)
+            .await?
+            .ok_or_else(|| FileOrganizerError::Other(format!("Run #{} not found", run_id)))?;
+
+        Ok(Self {
+            version: BUNDLE_VERSION,
+            run_id,
+            root: info.root,
+            dest_root: info.dest_root,
+            created_at: info.created_at,
+            exported_at: now_unix(),
+            moves: db.run_moves(run_id).await?,
+        })
+    }
+}
+
+/// Write the undo bundle for run `run_id` to `out` (a tar archive)
+pub async fn export(db: &Db, run_id: i64, out: &Path) -> Result<UndoManifest> {
+    let manifest = UndoManifest::from_run(db, run_id).await?;
+    write_bundle(&manifest, out)?;
+    Ok(manifest)
+}
+
+/// Write `manifest` as a tar archive holding a single `manifest.json`
+pub fn write_bundle(manifest: &UndoManifest, out: &Path) -> Result<()> {
+    let json = serde_json::to_vec_pretty(manifest).map_err(|source| FileOrganizerError::Json {
+        path: out.to_path_buf(),
+        source,
+    })?;
+
+    let mut header = tar::Header::new_gnu();
+    header.set_size(json.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(manifest.exported_at.max(0) as u64);
+    header.set_cksum();
+
+    let mut builder = tar::Builder::new(File::create(out)?);
+    builder.append_data(&mut header, MANIFEST_NAME, json.as_slice())?;
+    builder.into_inner()?.sync_all()?;
+    Ok(())
+}
+
+/// Read the manifest back out of an undo bundle
+pub fn read_bundle(path: &Path) -> Result<UndoManifest> {
+    let json = archive::read_entry(path, MANIFEST_NAME, MAX_MANIFEST_SIZE)?
+        .ok_or_else(|| FileOrganizerError::Other(format!("{:?} has no {}", path, MANIFEST_NAME)))?;
+    let manifest: UndoManifest = serde_json::from_slice(&json)
+        .map_err(|source| FileOrganizerError::Json { path: path.to_path_buf(), source })?;
+    if manifest.version > BUNDLE_VERSION {
+        return Err(FileOrganizerError::Other(format!(
+            "{:?} uses bundle version {}; this stash understands up to {}",
+            path, manifest.version, BUNDLE_VERSION
+        )));
+    }
+    Ok(manifest)
+}
+
+/// Options controlling an imported revert
+#[derive(Debug, Clone)]
+pub struct ImportOptions {
+    /// Prefix rewrites applied to every recorded path
+    pub mappings: Vec<PathMapping>,
+    /// Show what would happen without changing anything
+    pub dry_run: bool,
+    /// Remove directories left empty under the destination root
+    pub cleanup: bool,
+    /// Roots refused, e.g. `/` or the home directory
+    pub guard: Guard,
+    /// When another run is working on the run's roots, wait for it instead
+    /// of failing with [`FileOrganizerError::AlreadyRunning`]
+    pub wait_for_lock: bool,
+    /// Where the lock files of runs go (defaults to [`default_lock_dir`])
+    pub lock_dir: Option<PathBuf>,
+}
+
+impl Default for ImportOptions {
+    fn default() -> Self {
+        Self {
+            mappings: Vec::new(),
+            dry_run: false,
+            cleanup: true,
+            guard: Guard::default(),
+            wait_for_lock: false,
+            lock_dir: None,
+        }
+    }
+}
+
+/// Result of reverting from a bundle
+#[derive(Debug, Default)]
+pub struct ImportReport {
+    pub run_id: i64,
+    pub reverted: usize,
+    /// Recorded destination no longer exists (after remapping)
+    pub missing: Vec<PathBuf>,
+    /// Destination content differs from what was moved; left alone
+    pub changed: Vec<PathBuf>,
+    /// (original, where it went instead) when the original path was taken
+    pub renamed: Vec<(PathBuf, PathBuf)>,
+    pub errors: Vec<(PathBuf, FileOrganizerError)>,
+}
+
+impl fmt::Display for ImportReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", format!("Undo run #{}", self.run_id).bold().blue())?;
+        writeln!(f, "  Reverted:  {} files", self.reverted.to_string().green())?;
+        writeln!(f, "  Renamed:   {} files (original path was taken)", self.renamed.len())?;
+        writeln!(f, "  Missing:   {} files", self.missing.len().to_string().yellow())?;
+        writeln!(f, "  Changed:   {} files (content differs, left alone)", self.changed.len())?;
+        writeln!(f, "  Errors:    {} files", self.errors.len().to_string().red())?;
+        for (original, path) in &self.renamed {
+            writeln!(f, "    {} → {}", original.display(), path.display())?;
+        }
+        for path in &self.missing {
+            writeln!(f, "    missing: {}", path.display())?;
+        }
+        for (path, error) in &self.errors {
+            writeln!(f, "    {}: {}", path.display(), error)?;
+        }
+        Ok(())
+    }
+}
+
+/// Move every file of the bundled run back to its original location,
+/// rewriting paths with `options.mappings` first
+pub async fn import(bundle: &Path, options: &ImportOptions) -> Result<ImportReport> {
+    let manifest = read_bundle(bundle)?;
+    apply(&manifest, options).await
+}
+
+/// Revert the moves listed in `manifest`. A bundle may come from anywhere,
+/// so only files recorded below its (remapped) roots are touched, and only
+/// when their recorded hash still matches.
+pub async fn apply(manifest: &UndoManifest, options: &ImportOptions) -> Result<ImportReport> {
+    let root = remap_path(&manifest.root, &options.mappings);
+    let dest_root = remap_path(&manifest.dest_root, &options.mappings);
+    options.guard.check(&root)?;
+    options.guard.check(&dest_root)?;
+    let lock_dir = match &options.lock_dir {
+        Some(dir) => dir.clone(),
+        None => default_lock_dir().await?,
+    };
+    let _lock = InstanceLock::acquire(&lock_dir, [root.as_path(), dest_root.as_path()], options.wait_for_lock).await?;
+
+    let mover = FileMover::new();
+    let mut report = ImportReport { run_id: manifest.run_id, ..Default::default() };
+
+    // Undo in reverse so a file moved twice in one run ends up where it started
+    for mv in manifest.moves.iter().rev() {
+        let dest = remap_path(&mv.dest, &options.mappings);
+        let original = remap_path(&mv.source, &options.mappings);
+
+        if !is_below(&dest, &dest_root) || !is_below(&original, &root) {
+            let error = FileOrganizerError::Other(format!(
+                "recorded outside the run's roots ({:?} → {:?}); left alone",
+                original, dest
+            ));
+            report.errors.push((dest, error));
+            continue;
+        }
+
+        if !tokio::fs::try_exists(&dest).await? {
+            report.missing.push(dest);
+            continue;
+        }
+
+        // Without a hash there is no telling whether this is still the file that was moved
+        let Some(expected) = &mv.hash else {
+            let error = FileOrganizerError::Other("no recorded hash; left alone".into());
+            report.errors.push((dest, error));
+            continue;
+        };
+        match rehash_like(expected, &dest).await {
+            Ok(hash) if hash == *expected => {}
+            Ok(_) => {
+                report.changed.push(dest);
+                continue;
+            }
+            Err(e) => {
+                report.errors.push((dest, e));
+                continue;
+            }
+        }
+
+        if options.dry_run {
+            println!("Would move {:?} → {:?}", dest, original);
+            report.reverted += 1;
+            continue;
+        }
+
+        match revert_one(&mover, &dest, &original).await {
+            Ok(target) => {
+                tracing::debug!(target: "undo_bundle", "Reverted {:?} -> {:?}", dest, target);
+                if target != original {
+                    report.renamed.push((original, target));
+                }
+                report.reverted += 1;
+            }
+            Err(e) => report.errors.push((dest, e)),
+        }
+    }
+
+    if options.cleanup && !options.dry_run {
+        let dest_root = remap_path(&manifest.dest_root, &options.mappings);
+        if let Err(e) = cleanup_empty_dirs(&dest_root).await {
+            tracing::warn!(target: "undo_bundle", "Failed to fully cleanup dirs: {:?}", e);
+        }
+    }
+
+    Ok(report)
+}
+
+/// Whether `path` lies below `root`, with no `..` to climb back out
+fn is_below(path: &Path, root: &Path) -> bool {
+    path.starts_with(root) && !path.components().any(|c| c == Component::ParentDir)
+}
+
+/// Move `dest` back to `original`, never overwriting a file already there
+async fn revert_one(mover: &FileMover, dest: &Path, original: &Path) -> Result<PathBuf> {
+    let target = resolve_conflict_in(mover.fs(), original, false).await?;
+    if let Some(parent) = target.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    mover.move_file(dest, &target).await?;
+    Ok(target)
+}
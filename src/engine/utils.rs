@@ -1,21 +1,14 @@
-use std::{path::PathBuf, sync::Arc, time::{Duration, SystemTime, UNIX_EPOCH}};
+use std::{path::{Path, PathBuf}, sync::Arc, time::{Duration, SystemTime, UNIX_EPOCH}};
 
 use chrono::{DateTime, Utc, Datelike};
-use indicatif::{ProgressBar, ProgressStyle};
+use serde::Serialize;
 use tracing_subscriber::{fmt, EnvFilter, prelude::*};
 use tracing_appender::rolling;
 
 use crate::{
-    archive_classifier::ArchiveClassifier, 
-    audio_classifier::AudioClassifier, 
-    code_classifier::CodeClassifier, 
-    docs_classifier::DocumentClassifier,
     errors::{FileOrganizerError, Result}, 
-    executable_classifier::ExecutableClassifier, 
-    generic::GenericClassifier, 
-    image_classifier::ImageClassifier, 
-    registry::ClassifierRegistry, 
-    video_classifier::VideoClassifier
+    registry::{BuiltinClassifier, ClassifierRegistry}, 
+    rule_classifier::RuleClassifier,
 };
 
 /// Initialize tracing
@@ -49,18 +42,6 @@ pub fn init_tracing() {
         .init();
 }
 
-/// Create a styled progress bar
-pub fn make_progress(total: u64, msg: &str) -> ProgressBar {
-    let pb = ProgressBar::new(total);
-    pb.set_style(
-        ProgressStyle::with_template("[{elapsed_precise}] [{bar:40.magenta/bright_magenta}] {pos}/{len} {msg}")
-            .unwrap()
-    );
-    pb.set_message(msg.to_string());
-    pb.enable_steady_tick(Duration::from_millis(200));
-    pb
-}
-
 pub async fn default_db_path() -> Result<PathBuf> {
     // Candidate directories in order of preference
     let candidates = [
@@ -81,7 +62,7 @@ pub async fn default_db_path() -> Result<PathBuf> {
         let test_file = path.join(".write_test_tmp");
         match tokio::fs::File::create(&test_file).await {
             Ok(_) => {
-                let _ = tokio::fs::remove_file(&test_file); // clean up
+                let _ = tokio::fs::remove_file(&test_file).await; // clean up
                 let db_path = path.join("file_organizer.db");
                 tracing::debug!("Using database path: {:?}", db_path);
                 return Ok(db_path);
@@ -101,6 +82,58 @@ pub async fn default_db_path() -> Result<PathBuf> {
     )))
 }
 
+/// Name of stash's trash directory; see [`crate::trash`]
+pub const TRASH_DIR: &str = ".stash-trash";
+
+/// Where trashed files are kept until restored or purged: `~/.stash-trash`
+pub fn default_trash_dir() -> PathBuf {
+    dirs::home_dir()
+        .or_else(dirs::data_local_dir)
+        .unwrap_or_else(std::env::temp_dir)
+        .join(TRASH_DIR)
+}
+
+/// Whether process `pid` is running; `None` where that can't be checked cheaply
+#[cfg(unix)]
+pub fn process_alive(pid: u32) -> Option<bool> {
+    // 0 and negative ids address process groups, not a process
+    let pid = match libc::pid_t::try_from(pid) {
+        Ok(pid) if pid > 0 => pid,
+        _ => return Some(false),
+    };
+    // Signal 0 only checks that the process exists; EPERM means it exists
+    // but belongs to another user
+    let rc = unsafe { libc::kill(pid, 0) };
+    Some(rc == 0 || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM))
+}
+
+#[cfg(not(unix))]
+pub fn process_alive(_pid: u32) -> Option<bool> {
+    None
+}
+
+/// Write `value` as pretty JSON to `path`, creating its directory, and
+/// replace the file in one step so a reader never sees half of it
+pub fn write_json_atomically<T: Serialize>(path: &Path, value: &T) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let json = serde_json::to_vec_pretty(value)
+        .map_err(|source| FileOrganizerError::Json { path: path.to_path_buf(), source })?;
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, json)?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+/// Directory (inside the organized root) that holds the portable database
+pub const PORTABLE_DB_DIR: &str = ".file_organizer";
+
+/// Location of the portable database for `root`
+pub fn portable_db_path(root: &Path) -> PathBuf {
+    root.join(PORTABLE_DB_DIR).join("file_organizer.db")
+}
+
 /// Expands `~` and environment variables in paths, then returns an absolute path.
 pub fn expand_tilde<P: AsRef<str>>(path: P) -> PathBuf {
     // Expand tilde (~) to home directory
@@ -112,11 +145,10 @@ pub fn expand_tilde<P: AsRef<str>>(path: P) -> PathBuf {
     let mut path_buf = PathBuf::from(expanded_env.to_string());
 
     // If relative, make it absolute relative to current working dir
-    if !path_buf.is_absolute() {
-        if let Ok(current_dir) = std::env::current_dir() {
+    if !path_buf.is_absolute()
+        && let Ok(current_dir) = std::env::current_dir() {
             path_buf = current_dir.join(path_buf);
         }
-    }
 
     path_buf
 }
@@ -152,34 +184,201 @@ pub fn from_unix(ts: Option<i64>) -> Option<SystemTime> {
     })
 }
 
-/// Creates and configures the classifier registry with priorities
+/// Effective user id of this process (Unix only)
+#[cfg(unix)]
+pub fn current_uid() -> Option<u32> {
+    // SAFETY: geteuid has no preconditions and cannot fail
+    Some(unsafe { libc::geteuid() })
+}
+
+#[cfg(not(unix))]
+pub fn current_uid() -> Option<u32> {
+    None
+}
+
+/// Login name for `uid` from the user database, if it has one
+#[cfg(unix)]
+pub fn user_name(uid: u32) -> Option<String> {
+    let mut buf = vec![0 as libc::c_char; 1024];
+    loop {
+        // SAFETY: passwd is plain data; getpwuid_r writes only into `pwd` and `buf`
+        let mut pwd: libc::passwd = unsafe { std::mem::zeroed() };
+        let mut result = std::ptr::null_mut();
+        let rc = unsafe { libc::getpwuid_r(uid, &mut pwd, buf.as_mut_ptr(), buf.len(), &mut result) };
+        if rc == libc::ERANGE && buf.len() < 1 << 20 {
+            buf.resize(buf.len() * 2, 0);
+            continue;
+        }
+        if rc != 0 || result.is_null() || pwd.pw_name.is_null() {
+            return None;
+        }
+        // SAFETY: on success pw_name points at a NUL-terminated string inside `buf`
+        let name = unsafe { std::ffi::CStr::from_ptr(pwd.pw_name) };
+        return Some(name.to_string_lossy().into_owned());
+    }
+}
+
+#[cfg(not(unix))]
+pub fn user_name(_uid: u32) -> Option<String> {
+    None
+}
+
+/// Group id for a group name or a numeric id, if the group exists
+#[cfg(unix)]
+pub fn group_id(name: &str) -> Option<u32> {
+    if let Ok(gid) = name.parse() {
+        return Some(gid);
+    }
+    let c_name = std::ffi::CString::new(name).ok()?;
+    let mut buf = vec![0 as libc::c_char; 1024];
+    loop {
+        // SAFETY: group is plain data; getgrnam_r writes only into `grp` and `buf`
+        let mut grp: libc::group = unsafe { std::mem::zeroed() };
+        let mut result = std::ptr::null_mut();
+        let rc = unsafe { libc::getgrnam_r(c_name.as_ptr(), &mut grp, buf.as_mut_ptr(), buf.len(), &mut result) };
+        if rc == libc::ERANGE && buf.len() < 1 << 20 {
+            buf.resize(buf.len() * 2, 0);
+            continue;
+        }
+        if rc != 0 || result.is_null() {
+            return None;
+        }
+        return Some(grp.gr_gid);
+    }
+}
+
+#[cfg(not(unix))]
+pub fn group_id(name: &str) -> Option<u32> {
+    name.parse().ok()
+}
+
+/// Creates the classifier registry with every built-in classifier at its priority.
+/// Higher priority = more specific/specialized classifiers; the generic
+/// fallback comes last and handles everything.
 pub fn create_classifier_registry() -> ClassifierRegistry {
     let mut registry = ClassifierRegistry::new();
-    // Register classifiers with appropriate base priorities
-    // Higher priority = more specific/specialized classifiers
-    // Lower priority = more general/fallback classifiers
+    for builtin in BuiltinClassifier::ALL {
+        registry.register_builtin(builtin);
+    }
+    registry
+}
 
-    // Media classifiers (very specific, high confidence)
-    registry.register_with_priority(100, Arc::new(ImageClassifier));
-    registry.register_with_priority(95, Arc::new(AudioClassifier));
-    registry.register_with_priority(90, Arc::new(VideoClassifier));
+/// The built-in classifiers plus rule classifiers from config
+pub fn create_classifier_registry_with(rules: &[Arc<RuleClassifier>]) -> ClassifierRegistry {
+    let mut registry = create_classifier_registry();
+    for rule in rules {
+        registry.register_with_priority(rule.priority(), rule.clone());
+    }
+    registry
+}
 
-    // Document classifier (specific but may overlap with code)
-    registry.register_with_priority(85, Arc::new(DocumentClassifier));
+/// Parse a human-friendly size such as `500`, `10KB`, `1.5 GB` (binary units, case-insensitive)
+pub fn parse_size(input: &str) -> std::result::Result<u64, String> {
+    let input = input.trim();
+    let split = input
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(input.len());
+    let (number, unit) = input.split_at(split);
 
-    // Code classifier (specific but may overlap with documents/executables)
-    registry.register_with_priority(80, Arc::new(CodeClassifier));
+    let value: f64 = number
+        .parse()
+        .map_err(|_| format!("invalid size `{}`", input))?;
+    let multiplier: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" | "kib" => 1 << 10,
+        "m" | "mb" | "mib" => 1 << 20,
+        "g" | "gb" | "gib" => 1 << 30,
+        "t" | "tb" | "tib" => 1 << 40,
+        other => return Err(format!("unknown size unit `{}` (use B, KB, MB, GB or TB)", other)),
+    };
 
-    // Archive classifier (specific but may overlap with executables)
-    registry.register_with_priority(75, Arc::new(ArchiveClassifier));
+    Ok((value * multiplier as f64) as u64)
+}
 
-    // Executable classifier (broader category, may overlap with others)
-    registry.register_with_priority(70, Arc::new(ExecutableClassifier));
+/// Parse a rate in bytes per second: a bare number is MB/s (`20`, `2.5`),
+/// or give a unit, optionally with `/s` (`500KB`, `1GB/s`)
+pub fn parse_throughput(input: &str) -> std::result::Result<u64, String> {
+    let input = input.trim();
+    let input = input.strip_suffix("/s").unwrap_or(input);
+    let bytes = if input.parse::<f64>().is_ok() {
+        parse_size(&format!("{}MB", input))?
+    } else {
+        parse_size(input)?
+    };
+    if bytes == 0 {
+        return Err("throughput must be above zero".to_string());
+    }
+    Ok(bytes)
+}
 
-    // Generic fallback (lowest priority, handles everything)
-    registry.register_with_priority(10, Arc::new(GenericClassifier));
+/// Parse a duration such as `90s`, `30m`, `12h`, `7d` or `2w`
+pub fn parse_duration(input: &str) -> std::result::Result<Duration, String> {
+    let input = input.trim();
+    let split = input
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(input.len());
+    let (number, unit) = input.split_at(split);
 
-    registry
+    let value: u64 = number
+        .parse()
+        .map_err(|_| format!("invalid duration `{}`", input))?;
+    let seconds: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "s" | "sec" | "secs" => 1,
+        "m" | "min" | "mins" => 60,
+        "h" | "hour" | "hours" => 60 * 60,
+        "d" | "day" | "days" => 24 * 60 * 60,
+        "w" | "week" | "weeks" => 7 * 24 * 60 * 60,
+        other => return Err(format!("unknown duration unit `{}` (use s, m, h, d or w)", other)),
+    };
+
+    Ok(Duration::from_secs(value.saturating_mul(seconds)))
+}
+
+/// Prefix rewrite for paths recorded on another mount point, written `OLD=NEW`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathMapping {
+    pub from: PathBuf,
+    pub to: PathBuf,
+}
+
+impl PathMapping {
+    /// The same rewrite the other way round, `NEW=OLD`
+    pub fn reversed(&self) -> Self {
+        Self { from: self.to.clone(), to: self.from.clone() }
+    }
+
+    /// `path` with the `from` prefix replaced, if it starts with it
+    pub fn apply(&self, path: &Path) -> Option<PathBuf> {
+        path.strip_prefix(&self.from).ok().map(|rest| {
+            if rest.as_os_str().is_empty() {
+                self.to.clone()
+            } else {
+                self.to.join(rest)
+            }
+        })
+    }
+}
+
+impl std::str::FromStr for PathMapping {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.split_once('=') {
+            Some((from, to)) if !from.is_empty() && !to.is_empty() => Ok(Self {
+                from: PathBuf::from(from),
+                to: PathBuf::from(to),
+            }),
+            _ => Err(format!("invalid mapping `{}` (expected OLD=NEW)", s)),
+        }
+    }
+}
+
+/// Rewrite `path` with the first mapping whose prefix matches
+pub fn remap_path(path: &Path, mappings: &[PathMapping]) -> PathBuf {
+    mappings
+        .iter()
+        .find_map(|m| m.apply(path))
+        .unwrap_or_else(|| path.to_path_buf())
 }
 
 pub fn humanize(e: &FileOrganizerError) -> String {
@@ -0,0 +1,134 @@
+use std::{fmt, path::{Path, PathBuf}};
+
+use colored::*;
+use futures::stream::{self, StreamExt};
+
+use crate::{
+    errors::{FileOrganizerError, Result},
+    hasher::rehash_like,
+    index::{Db, DbFileEntry},
+    progress::Progress,
+};
+
+/// Files hashed at the same time
+const CONCURRENCY: usize = 8;
+
+/// Result of re-hashing indexed files
+#[derive(Debug, Default)]
+pub struct VerifyReport {
+    /// Entries with a recorded hash that were checked
+    pub checked: usize,
+    pub ok: usize,
+    /// Recorded location no longer exists
+    pub missing: Vec<PathBuf>,
+    /// [`FileOrganizerError::HashMismatch`] for each file whose content changed
+    pub mismatched: Vec<FileOrganizerError>,
+    /// Entries in scope without a recorded hash
+    pub unhashed: usize,
+    pub errors: Vec<(PathBuf, FileOrganizerError)>,
+}
+
+impl VerifyReport {
+    /// True when every checked file still matches its recorded hash
+    pub fn is_clean(&self) -> bool {
+        self.missing.is_empty() && self.mismatched.is_empty() && self.errors.is_empty()
+    }
+}
+
+impl fmt::Display for VerifyReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", "Verify".bold().blue())?;
+        writeln!(f, "  Checked:    {} files", self.checked)?;
+        writeln!(f, "  OK:         {}", self.ok.to_string().green())?;
+        writeln!(f, "  Mismatched: {}", self.mismatched.len().to_string().red())?;
+        writeln!(f, "  Missing:    {}", self.missing.len().to_string().yellow())?;
+        writeln!(f, "  Errors:     {}", self.errors.len().to_string().red())?;
+        if self.unhashed > 0 {
+            writeln!(f, "  Not hashed: {} (nothing to compare against)", self.unhashed)?;
+        }
+        for mismatch in &self.mismatched {
+            writeln!(f, "    {}", mismatch)?;
+        }
+        for path in &self.missing {
+            writeln!(f, "    missing: {}", path.display())?;
+        }
+        for (path, error) in &self.errors {
+            writeln!(f, "    {}: {}", path.display(), error)?;
+        }
+        Ok(())
+    }
+}
+
+enum Check {
+    Ok,
+    Missing(PathBuf),
+    Mismatch(FileOrganizerError),
+    Failed(PathBuf, FileOrganizerError),
+}
+
+/// Re-hash the current location of every indexed file (only those under
+/// `scope` when given) and compare it with the recorded hash
+pub async fn verify(db: &Db, scope: Option<&Path>, progress: &Progress) -> Result<VerifyReport> {
+    let mut report = VerifyReport::default();
+    let mut entries = Vec::new();
+    for entry in db.get_all_files().await? {
+        if scope.is_some_and(|dir| !entry.dest_path.starts_with(dir)) {
+            continue;
+        }
+        if entry.hash.as_deref().is_none_or(str::is_empty) {
+            report.unhashed += 1;
+            continue;
+        }
+        entries.push(entry);
+    }
+
+    report.checked = entries.len();
+    progress.start("Verifying", entries.len() as u64);
+    let mut checks = stream::iter(entries)
+        .map(|entry| async move { check(&entry).await })
+        .buffer_unordered(CONCURRENCY);
+
+    while let Some(result) = checks.next().await {
+        match result {
+            Check::Ok => report.ok += 1,
+            Check::Missing(path) => report.missing.push(path),
+            Check::Mismatch(e) => report.mismatched.push(e),
+            Check::Failed(path, e) => report.errors.push((path, e)),
+        }
+        progress.inc(1);
+    }
+    progress.finish();
+
+    report.missing.sort();
+    tracing::info!(
+        target: "verify",
+        "Verified {} files: {} ok, {} mismatched, {} missing",
+        report.checked,
+        report.ok,
+        report.mismatched.len(),
+        report.missing.len()
+    );
+    Ok(report)
+}
+
+/// Hashes with the algorithm the entry's hash was recorded with, so an index
+/// organized with different `--hash-algo` settings verifies as a whole
+async fn check(entry: &DbFileEntry) -> Check {
+    let path = &entry.dest_path;
+    match tokio::fs::try_exists(path).await {
+        Ok(true) => {}
+        Ok(false) => return Check::Missing(path.clone()),
+        Err(e) => return Check::Failed(path.clone(), e.into()),
+    }
+
+    let expected = entry.hash.clone().unwrap_or_default();
+    match rehash_like(&expected, path).await {
+        Ok(hash) if hash == expected => Check::Ok,
+        Ok(actual) => Check::Mismatch(FileOrganizerError::HashMismatch {
+            path: path.clone(),
+            expected,
+            actual,
+        }),
+        Err(e) => Check::Failed(path.clone(), e),
+    }
+}
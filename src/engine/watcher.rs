@@ -0,0 +1,292 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    future::Future,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+#[cfg(target_os = "linux")]
+use notify::event::{AccessKind, AccessMode, ModifyKind, RenameMode};
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+
+use crate::{
+    daemon::shutdown_signal,
+    errors::{FileOrganizerError, Result},
+    organizer::{organise_files, organise_paths, OrganizeOptions},
+    scanner::is_incomplete_download,
+    schedule::{Pause, Schedule},
+};
+
+/// How often settled files are collected and organized
+const TICK: Duration = Duration::from_millis(250);
+
+/// How often a paused watch asks the schedule again
+const PAUSE_RECHECK: Duration = Duration::from_secs(30);
+
+/// Decides when a file that is being written is finished and safe to move
+pub trait SettleStrategy: Send {
+    /// Record a filesystem event for a top-level file
+    fn observe(&mut self, event: &Event);
+
+    /// Files that have settled since the last call
+    fn settled(&mut self) -> Vec<PathBuf>;
+}
+
+/// Linux: a file is complete once its writer closes it (`IN_CLOSE_WRITE`)
+/// or when it is renamed into the directory (e.g. a browser's `.part` file).
+#[cfg(target_os = "linux")]
+#[derive(Debug, Default)]
+pub struct CloseWriteSettle {
+    ready: BTreeSet<PathBuf>,
+}
+
+#[cfg(target_os = "linux")]
+impl SettleStrategy for CloseWriteSettle {
+    fn observe(&mut self, event: &Event) {
+        match event.kind {
+            EventKind::Access(AccessKind::Close(AccessMode::Write))
+            | EventKind::Modify(ModifyKind::Name(RenameMode::To)) => {
+                self.ready.extend(event.paths.iter().cloned());
+            }
+            EventKind::Modify(ModifyKind::Name(RenameMode::Both)) => {
+                // paths = [from, to]
+                if let Some(to) = event.paths.last() {
+                    self.ready.insert(to.clone());
+                }
+            }
+            EventKind::Remove(_) | EventKind::Modify(ModifyKind::Name(RenameMode::From)) => {
+                for path in &event.paths {
+                    self.ready.remove(path);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn settled(&mut self) -> Vec<PathBuf> {
+        // Closed by one writer but still held by another: try again next tick
+        let (busy, ready): (BTreeSet<_>, BTreeSet<_>) =
+            std::mem::take(&mut self.ready).into_iter().partition(|path| is_locked(path));
+        self.ready = busy;
+        ready.into_iter().collect()
+    }
+}
+
+/// Portable fallback: a file is complete once its size has stopped changing
+/// for `quiet` after the last event touching it and no other process holds
+/// it locked.
+#[derive(Debug)]
+pub struct SizeStabilitySettle {
+    quiet: Duration,
+    /// Last observed size and when the file last changed
+    pending: HashMap<PathBuf, (Option<u64>, Instant)>,
+}
+
+impl SizeStabilitySettle {
+    pub fn new(quiet: Duration) -> Self {
+        Self { quiet, pending: HashMap::new() }
+    }
+}
+
+impl Default for SizeStabilitySettle {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(2))
+    }
+}
+
+impl SettleStrategy for SizeStabilitySettle {
+    fn observe(&mut self, event: &Event) {
+        match event.kind {
+            EventKind::Create(_) | EventKind::Modify(_) | EventKind::Access(_) => {
+                let now = Instant::now();
+                for path in &event.paths {
+                    self.pending
+                        .entry(path.clone())
+                        .and_modify(|(_, changed)| *changed = now)
+                        .or_insert((None, now));
+                }
+            }
+            EventKind::Remove(_) => {
+                for path in &event.paths {
+                    self.pending.remove(path);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn settled(&mut self) -> Vec<PathBuf> {
+        let now = Instant::now();
+        let mut ready = Vec::new();
+
+        self.pending.retain(|path, (size, changed)| {
+            let Ok(metadata) = std::fs::metadata(path) else {
+                return false; // gone (moved away or deleted)
+            };
+            if *size != Some(metadata.len()) || is_locked(path) {
+                *size = Some(metadata.len());
+                *changed = now;
+                return true;
+            }
+            if now.duration_since(*changed) >= self.quiet {
+                ready.push(path.clone());
+                return false;
+            }
+            true
+        });
+
+        ready.sort();
+        ready
+    }
+}
+
+/// Whether another process has `path` open exclusively or holds a lock on
+/// it, e.g. a download manager still writing. Windows refuses to open such
+/// a file; elsewhere only advisory (`flock`) locks are seen.
+pub fn is_locked(path: &Path) -> bool {
+    match std::fs::File::open(path) {
+        Ok(file) => matches!(file.try_lock(), Err(std::fs::TryLockError::WouldBlock)),
+        // ERROR_SHARING_VIOLATION / ERROR_LOCK_VIOLATION
+        Err(e) => cfg!(windows) && matches!(e.raw_os_error(), Some(32 | 33)),
+    }
+}
+
+/// Strategy for `--settle`: with a quiet period, wait for the size to stay
+/// the same that long on every platform; otherwise the platform's best
+pub fn settle_strategy(quiet: Option<Duration>) -> Box<dyn SettleStrategy> {
+    match quiet {
+        Some(quiet) => Box::new(SizeStabilitySettle::new(quiet)),
+        None => default_strategy(),
+    }
+}
+
+/// Best strategy for the current platform
+pub fn default_strategy() -> Box<dyn SettleStrategy> {
+    #[cfg(target_os = "linux")]
+    {
+        Box::new(CloseWriteSettle::default())
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        Box::new(SizeStabilitySettle::default())
+    }
+}
+
+/// Organize `root_dir`, then keep organizing new top-level files as they
+/// settle (see [`settle_strategy`]) until SIGTERM / Ctrl-C. While `schedule`
+/// says to pause, settled files are held and organized once it allows.
+pub async fn watch_and_organize(
+    root_dir: &Path,
+    options: &OrganizeOptions,
+    settle: Option<Duration>,
+    schedule: &Schedule,
+) -> Result<()> {
+    watch_until(root_dir, options, settle_strategy(settle), schedule, shutdown_signal()).await
+}
+
+/// Watch loop with an explicit settle strategy and shutdown future
+pub async fn watch_until<F: Future<Output = ()>>(
+    root_dir: &Path,
+    options: &OrganizeOptions,
+    mut strategy: Box<dyn SettleStrategy>,
+    schedule: &Schedule,
+    shutdown: F,
+) -> Result<()> {
+    // The first pass waits for the schedule like any other batch
+    let dest_root = options.dest_root(root_dir);
+    let mut paused: Option<(Pause, Instant)> = schedule.pause_for(&dest_root).map(|pause| (pause, Instant::now()));
+    let mut full_pass_pending = paused.is_some();
+    match &paused {
+        Some((pause, _)) => tracing::info!(target: "watcher", reason = %pause, "Organizing paused by the schedule"),
+        None => organise_files(root_dir, options).await.map(drop)?,
+    }
+    let mut held = BTreeSet::new();
+    let exclusions = options.exclusions(root_dir);
+
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let mut watcher = notify::recommended_watcher(move |res| {
+        let _ = tx.send(res);
+    })
+    .map_err(|e| FileOrganizerError::Watch(e.to_string()))?;
+    watcher
+        .watch(root_dir, RecursiveMode::NonRecursive)
+        .map_err(|e| FileOrganizerError::Watch(e.to_string()))?;
+    tracing::info!(target: "watcher", "Watching {:?}", root_dir);
+
+    tokio::pin!(shutdown);
+    let mut ticker = tokio::time::interval(TICK);
+
+    loop {
+        tokio::select! {
+            _ = &mut shutdown => break,
+            Some(res) = rx.recv() => match res {
+                Ok(mut event) => {
+                    // Only files directly in the root are organized, and never
+                    // stash's own writes (index, logs, status file) or
+                    // downloads in progress, which settle under their final name
+                    event.paths.retain(|p| {
+                        p.parent() == Some(root_dir) && !exclusions.excludes(p) && !is_incomplete_download(p)
+                    });
+                    if !event.paths.is_empty() {
+                        strategy.observe(&event);
+                    }
+                }
+                Err(e) => tracing::warn!(target: "watcher", "Watch error: {}", e),
+            },
+            _ = ticker.tick() => {
+                held.extend(strategy.settled());
+                if held.is_empty() && !full_pass_pending {
+                    continue;
+                }
+
+                if let Some((pause, checked)) = &paused {
+                    if checked.elapsed() < PAUSE_RECHECK {
+                        continue;
+                    }
+                    match schedule.pause_for(&dest_root) {
+                        Some(still) => {
+                            if still != *pause {
+                                tracing::info!(target: "watcher", reason = %still, "Organizing paused by the schedule");
+                            }
+                            paused = Some((still, Instant::now()));
+                            continue;
+                        }
+                        None => {
+                            tracing::info!(target: "watcher", "Schedule allows organizing again");
+                            paused = None;
+                        }
+                    }
+                } else if let Some(pause) = schedule.pause_for(&dest_root) {
+                    tracing::info!(target: "watcher", reason = %pause, "Organizing paused by the schedule");
+                    paused = Some((pause, Instant::now()));
+                    continue;
+                }
+
+                if std::mem::take(&mut full_pass_pending) {
+                    held.clear();
+                    if let Err(e) = organise_files(root_dir, options).await {
+                        tracing::error!(target: "watcher", error = %e, "Organize pass failed");
+                    }
+                    continue;
+                }
+
+                let ready: Vec<PathBuf> = std::mem::take(&mut held)
+                    .into_iter()
+                    .filter(|p| p.is_file())
+                    .collect();
+                if ready.is_empty() {
+                    continue;
+                }
+
+                tracing::debug!(target: "watcher", "{} settled file(s)", ready.len());
+                if let Err(e) = organise_paths(root_dir, &ready, options).await {
+                    tracing::error!(target: "watcher", error = %e, "Organize pass failed");
+                }
+            }
+        }
+    }
+
+    tracing::info!(target: "watcher", "Stopped watching {:?}", root_dir);
+    Ok(())
+}
@@ -0,0 +1,151 @@
+//! Scratch space for one run: `~/.local/state/stash/work/<run-id>` on Linux,
+//! the local data dir elsewhere. Anything a run stages before it is final
+//! (plans, manifests, partial downloads) goes there rather than next to the
+//! user's files. Each workspace names its process in an owner file, so runs
+//! in parallel never share one, and the workspaces of runs that died are
+//! removed the next time stash starts.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use crate::{
+    errors::{FileOrganizerError, Result},
+    utils::process_alive,
+};
+
+/// File in each workspace holding the id of the process using it
+pub const OWNER_FILE: &str = ".owner";
+
+/// Workspaces without an owner file are left this long, since their process
+/// may still be about to write it
+const OWNERLESS_GRACE: Duration = Duration::from_secs(60);
+
+/// Where it can't be told whether the owner still runs, workspaces older
+/// than this are taken as abandoned
+const STALE_AFTER: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// `~/.local/state/stash/work` on Linux; the local data dir elsewhere
+pub fn default_work_root() -> PathBuf {
+    dirs::state_dir()
+        .or_else(dirs::data_local_dir)
+        .unwrap_or_else(std::env::temp_dir)
+        .join("stash")
+        .join("work")
+}
+
+/// One run's directory under a work root, removed when dropped unless kept
+#[derive(Debug)]
+pub struct Workspace {
+    dir: PathBuf,
+    keep: bool,
+}
+
+impl Workspace {
+    /// Create `<root>/<run_id>` for this process. Fails if the directory
+    /// already exists, so two runs never share a workspace.
+    pub fn create(root: &Path, run_id: &str) -> Result<Self> {
+        if run_id.is_empty() || run_id.contains(['/', '\\']) || run_id.starts_with('.') {
+            return Err(FileOrganizerError::Other(format!("invalid workspace name `{}`", run_id)));
+        }
+        fs::create_dir_all(root)?;
+        let dir = root.join(run_id);
+        fs::create_dir(&dir).map_err(|e| match e.kind() {
+            std::io::ErrorKind::AlreadyExists => {
+                FileOrganizerError::Other(format!("workspace {:?} is already in use", dir))
+            }
+            _ => e.into(),
+        })?;
+        let workspace = Self { dir, keep: false };
+        fs::write(workspace.dir.join(OWNER_FILE), format!("{}\n", std::process::id()))?;
+        tracing::debug!(target: "workspace", "Created workspace {:?}", workspace.dir);
+        Ok(workspace)
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// `name` inside the workspace, e.g. for a staged file
+    pub fn path(&self, name: impl AsRef<Path>) -> PathBuf {
+        self.dir.join(name)
+    }
+
+    /// A directory inside the workspace, created if needed
+    pub fn subdir(&self, name: impl AsRef<Path>) -> Result<PathBuf> {
+        let dir = self.dir.join(name);
+        fs::create_dir_all(&dir)?;
+        Ok(dir)
+    }
+
+    /// Leave the workspace on disk when dropped, e.g. to look into a failed
+    /// run; the next start of stash still removes it once this process exits
+    pub fn keep(mut self) -> PathBuf {
+        self.keep = true;
+        self.dir.clone()
+    }
+}
+
+impl Drop for Workspace {
+    fn drop(&mut self) {
+        if self.keep {
+            return;
+        }
+        if let Err(e) = fs::remove_dir_all(&self.dir) {
+            tracing::warn!(target: "workspace", "Failed to remove workspace {:?}: {}", self.dir, e);
+        }
+    }
+}
+
+/// Process id in a workspace's owner file
+fn owner(dir: &Path) -> Option<u32> {
+    fs::read_to_string(dir.join(OWNER_FILE)).ok()?.trim().parse().ok()
+}
+
+fn older_than(dir: &Path, age: Duration) -> bool {
+    fs::metadata(dir)
+        .and_then(|meta| meta.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .is_some_and(|elapsed| elapsed > age)
+}
+
+/// Whether the run owning `dir` is gone
+fn is_abandoned(dir: &Path) -> bool {
+    match owner(dir) {
+        Some(pid) if pid == std::process::id() => false,
+        Some(pid) => match process_alive(pid) {
+            Some(alive) => !alive,
+            None => older_than(dir, STALE_AFTER),
+        },
+        None => older_than(dir, OWNERLESS_GRACE),
+    }
+}
+
+/// Remove the workspaces under `root` whose process is no longer running.
+/// Returns the directories removed; a missing root is not an error.
+pub fn clean_abandoned(root: &Path) -> Result<Vec<PathBuf>> {
+    let entries = match fs::read_dir(root) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut removed = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        let dir = entry.path();
+        if !entry.file_type()?.is_dir() || !is_abandoned(&dir) {
+            continue;
+        }
+        match fs::remove_dir_all(&dir) {
+            Ok(()) => {
+                tracing::info!(target: "workspace", "Removed abandoned workspace {:?}", dir);
+                removed.push(dir);
+            }
+            Err(e) => tracing::warn!(target: "workspace", "Failed to remove abandoned workspace {:?}: {}", dir, e),
+        }
+    }
+    Ok(removed)
+}
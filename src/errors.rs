@@ -4,7 +4,7 @@ use serde::Serialize;
 use thiserror::Error;
 use tokio::task::JoinError;
 
-use crate::utils::humanize;
+use crate::{guard::Danger, space::Unusable, stats::format_size, utils::humanize};
 
 pub type Result<T, E = FileOrganizerError> = std::result::Result<T, E>;
 
@@ -52,8 +52,30 @@ pub enum FileOrganizerError {
     #[error("Database error: {0}")]
     Database(#[from] sqlx::Error),
 
-    #[error("Skipped: {0}")]
-    Skipped(SkipReason),
+    #[error("Skipped {path:?}: {reason}")]
+    Skipped { path: PathBuf, reason: SkipReason, size: u64 },
+
+    #[error("{path:?} changed since it was organized (recorded hash {expected}, found {actual})")]
+    HashMismatch { path: PathBuf, expected: String, actual: String },
+
+    #[error("Not enough space at {path:?}: {} to copy, {} free", format_size(*.needed), format_size(*.available))]
+    InsufficientSpace { path: PathBuf, needed: u64, available: u64 },
+
+    #[error("Refusing to unpack {path:?}: {reason}")]
+    UnsafeArchive { path: PathBuf, reason: String },
+
+    #[error("{path:?} {reason}")]
+    UnusableFilesystem { path: PathBuf, reason: Unusable },
+
+    #[error("Refusing to move files in {path:?}: it {danger} (pass --i-know-what-im-doing to go ahead anyway)")]
+    DangerousRoot { path: PathBuf, danger: Danger },
+
+    #[error(
+        "Another stash run is working on {dir:?}{}; wait for it to finish or pass --wait",
+        .pid.map(|pid| format!(" (pid {})", pid)).unwrap_or_default()
+    )]
+    AlreadyRunning { dir: PathBuf, pid: Option<u32> },
+
     #[error("Task join error: {0}")]
     Join(#[from] JoinError),
 
@@ -82,10 +104,52 @@ impl FileOrganizerError {
             Database(_) => 13,
             InvalidRule(_) => 14,
             MimeDetection(_) => 15,
-            Skipped(_) => 16,
+            Skipped { .. } => 16,
             Join(_) => 17,
             Concurrency(_) => 18,
             Other(_) => 19,
+            HashMismatch { .. } => 20,
+            InsufficientSpace { .. } => 21,
+            UnsafeArchive { .. } => 22,
+            UnusableFilesystem { .. } => 23,
+            DangerousRoot { .. } => 24,
+            AlreadyRunning { .. } => 25,
+        }
+    }
+
+    /// What went wrong in a word or two, to group failures by: a pile of
+    /// `permission denied` points at ownership, `I/O error` at the disk,
+    /// `classifier` at a bug
+    pub fn kind(&self) -> &'static str {
+        use FileOrganizerError::*;
+        match self {
+            Io(e) => match e.kind() {
+                io::ErrorKind::PermissionDenied => "permission denied",
+                io::ErrorKind::NotFound => "not found",
+                io::ErrorKind::StorageFull | io::ErrorKind::QuotaExceeded => "disk full",
+                io::ErrorKind::ReadOnlyFilesystem => "read-only filesystem",
+                io::ErrorKind::InvalidFilename => "invalid name",
+                io::ErrorKind::TimedOut | io::ErrorKind::StaleNetworkFileHandle => "unreachable",
+                _ => "I/O error",
+            },
+            Config(_) => "config",
+            Index(_) | Database(_) => "index",
+            Move(_) => "move",
+            Scan(_) => "scan",
+            Watch(_) => "watch",
+            InvalidPath(_) => "invalid path",
+            Classify(_) | MimeDetection(_) | NoMatchingRule(_) => "classifier",
+            Json { .. } => "json",
+            Regex { .. } | InvalidRule(_) => "rule",
+            Skipped { .. } => "skipped",
+            HashMismatch { .. } => "changed since indexed",
+            InsufficientSpace { .. } => "disk full",
+            UnsafeArchive { .. } => "unsafe archive",
+            UnusableFilesystem { .. } => "unusable filesystem",
+            DangerousRoot { .. } => "dangerous root",
+            AlreadyRunning { .. } => "already running",
+            Join(_) | Concurrency(_) => "internal",
+            Other(_) => "other",
         }
     }
 }
@@ -112,16 +176,38 @@ pub enum SkipReason {
     TooSmall,
     TooLarge,
     MetadataUnreadable,
+    Duplicate,
+    TooOld,
+    TooNew,
+    Ignored,
+    CategoryDisabled,
+    NotOwned,
+    Symlink,
+    Conflict,
+    Excluded,
+    Incomplete,
+    Bundle,
 }
 
 impl SkipReason {
-    pub const VARIANTS: [SkipReason; 6] = [
+    pub const VARIANTS: [SkipReason; 17] = [
         SkipReason::Hidden,
         SkipReason::IsDir,
         SkipReason::WrongExtension,
         SkipReason::TooSmall,
         SkipReason::TooLarge,
         SkipReason::MetadataUnreadable,
+        SkipReason::Duplicate,
+        SkipReason::TooOld,
+        SkipReason::TooNew,
+        SkipReason::Ignored,
+        SkipReason::CategoryDisabled,
+        SkipReason::NotOwned,
+        SkipReason::Symlink,
+        SkipReason::Conflict,
+        SkipReason::Excluded,
+        SkipReason::Incomplete,
+        SkipReason::Bundle,
     ];
 
     #[inline]
@@ -133,6 +219,17 @@ impl SkipReason {
             SkipReason::TooSmall => 3,
             SkipReason::TooLarge => 4,
             SkipReason::MetadataUnreadable => 5,
+            SkipReason::Duplicate => 6,
+            SkipReason::TooOld => 7,
+            SkipReason::TooNew => 8,
+            SkipReason::Ignored => 9,
+            SkipReason::CategoryDisabled => 10,
+            SkipReason::NotOwned => 11,
+            SkipReason::Symlink => 12,
+            SkipReason::Conflict => 13,
+            SkipReason::Excluded => 14,
+            SkipReason::Incomplete => 15,
+            SkipReason::Bundle => 16,
         }
     }
 }
@@ -146,6 +243,17 @@ impl std::fmt::Display for SkipReason {
             SkipReason::TooSmall => "File skipped because it is smaller than minimum size",
             SkipReason::TooLarge => "File skipped because it is larger than maximum size",
             SkipReason::MetadataUnreadable => "File skipped because metadata could not be read",
+            SkipReason::Duplicate => "File skipped because an identical copy already exists at the destination",
+            SkipReason::TooOld => "File skipped because it was modified before the newer-than cutoff",
+            SkipReason::TooNew => "File skipped because it was modified after the older-than cutoff",
+            SkipReason::Ignored => "File skipped because it is on the ignore list",
+            SkipReason::CategoryDisabled => "File skipped because its category is disabled",
+            SkipReason::NotOwned => "File skipped because it belongs to another user",
+            SkipReason::Symlink => "File skipped because it is a symbolic link",
+            SkipReason::Conflict => "File skipped because a different file already exists at the destination",
+            SkipReason::Excluded => "File skipped because it is stash's own output or matches an exclude glob",
+            SkipReason::Incomplete => "File skipped because it is a download still in progress",
+            SkipReason::Bundle => "Directory skipped because it is a project folder or app bundle",
         };
         write!(f, "{}", msg)
     }
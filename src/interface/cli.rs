@@ -1,6 +1,27 @@
-use std::path::PathBuf;
+use std::{
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
-use clap::{Parser, Subcommand};
+use chrono::NaiveDate;
+use clap::{CommandFactory, Parser, Subcommand};
+use clap_complete::Shell;
+
+use crate::{
+    dedupe::{DedupeAction, ReportFormat},
+    hasher::HashAlgo,
+    index_transfer::IndexFormat,
+    organizer::{BundlePolicy, ConflictStrategy, DuplicateAction, ProcessingOrder},
+    path_builder::DateFolders,
+    progress::ProgressMode,
+    provenance::GroupTemplate,
+    query::{parse_day, QueryFormat},
+    reverter::{ConflictPolicy, MismatchPolicy},
+    run_report::RunReportFormat,
+    scanner::SymlinkPolicy,
+    utils::{parse_duration, parse_size, parse_throughput, PathMapping},
+};
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
@@ -9,39 +30,822 @@ pub struct Args {
     pub cmd: Commands
 }
 
+/// Completion script for `shell`
+pub fn write_completions(shell: Shell, out: &mut dyn Write) {
+    clap_complete::generate(shell, &mut Args::command(), "stash", out);
+}
+
+/// The `stash(1)` man page, covering the top-level options and commands
+pub fn write_man_page(out: &mut dyn Write) -> io::Result<()> {
+    clap_mangen::Man::new(Args::command()).render(out)
+}
+
+/// `stash.1` plus a page for every command and subcommand
+/// (`stash-organize.1`, `stash-db-bench.1`, …) in `dir`
+pub fn write_man_pages(dir: &Path) -> io::Result<()> {
+    clap_mangen::generate_to(Args::command(), dir)
+}
+
+// Parsed once per process; `Organize` carries every option and is far larger than the rest
+#[allow(clippy::large_enum_variant)]
 #[derive(Subcommand, Debug, Clone)]
 pub enum Commands {
     Organize {
-        /// Root directory to organize
-        #[arg(default_value = ".")]
-        path: PathBuf,
+        /// Root directories to organize, processed together in one run
+        #[arg(default_value = ".", num_args = 1..)]
+        paths: Vec<PathBuf>,
 
-        /// Enable watch mode
+        /// Enable watch mode (single root only)
         #[arg(short, long)]
         watch: bool,
 
-        /// Perform a dry run without moving files
-        #[arg(short, long)]
-        dry_run: bool,
+        /// In watch mode, move a file only once its size has stayed the same
+        /// this long (e.g. 5s) and no other program holds it locked. By
+        /// default Linux waits for the writer to close the file, other
+        /// systems for 2s without change.
+        #[arg(long, value_name = "DURATION", value_parser = parse_duration, requires = "watch")]
+        settle: Option<Duration>,
+
+        #[command(flatten)]
+        args: OrganizeArgs,
+
+        /// How to show progress: bar, json (lines on stderr) or none
+        #[arg(long, default_value = "bar", value_name = "MODE")]
+        progress: ProgressMode,
+
+        /// Show a dry run first and ask before moving anything, then wait for
+        /// Enter before exiting (used by the file manager context menu)
+        #[arg(short, long, conflicts_with_all = ["watch", "dry_run"])]
+        interactive: bool,
     },
     Revert {
         /// Root directory to revert to
-        root_dir: PathBuf,
+        #[arg(required_unless_present_any = ["all", "original_root", "file", "glob"])]
+        root_dir: Option<PathBuf>,
+
+        /// Revert every recorded file, wherever it was moved to
+        #[arg(long, conflicts_with = "original_root")]
+        all: bool,
+
+        /// Revert files that originally lived under this directory
+        #[arg(long, value_name = "DIR")]
+        original_root: Option<PathBuf>,
+
+        /// Revert only this file, looked up by where it was moved to or where
+        /// it came from; a directory takes every file below it (repeatable)
+        #[arg(long, value_name = "PATH")]
+        file: Vec<PathBuf>,
+
+        /// Revert only files whose destination or original path matches, e.g.
+        /// '*.pdf' or 'Documents/**' (repeatable)
+        #[arg(long, value_name = "GLOB")]
+        glob: Vec<String>,
 
         /// Skip cleaning up empty directories
         #[arg(long, default_value_t = false)]
         no_cleanup: bool,
+
+        /// Use the portable index stored inside the root directory
+        #[arg(long)]
+        portable: bool,
+
+        /// Rewrite recorded paths starting with OLD to start with NEW, e.g. after
+        /// the drive was remounted (repeatable)
+        #[arg(long = "map-prefix", value_name = "OLD=NEW")]
+        map_prefix: Vec<PathMapping>,
+
+        /// Files changed since they were organized: skip (report them), restore, or ask
+        #[arg(long, default_value = "skip", value_name = "POLICY")]
+        on_mismatch: MismatchPolicy,
+
+        /// When a different file already has the original name: rename
+        /// (`report_1.pdf`), skip, overwrite it, or prompt for each
+        #[arg(long, default_value = "rename", value_name = "STRATEGY")]
+        on_conflict: ConflictPolicy,
+
+        /// Revert into `/`, the home directory, system directories or the
+        /// config's `protected` directories, which are refused otherwise
+        #[arg(long)]
+        i_know_what_im_doing: bool,
+
+        /// When another stash run is working on these directories, wait for
+        /// it to finish instead of failing
+        #[arg(long)]
+        wait: bool,
+
+        /// How to show progress: bar, json (lines on stderr) or none
+        #[arg(long, default_value = "bar", value_name = "MODE")]
+        progress: ProgressMode,
+    },
+    /// Periodically organize the directories listed in a config file
+    Daemon {
+        /// Daemon config (defaults to `<config dir>/file_organizer/daemon.json`)
+        #[arg(short, long, value_name = "FILE", global = true)]
+        config: Option<PathBuf>,
+
+        /// Run a single pass and exit
+        #[arg(long)]
+        once: bool,
+
+        #[command(subcommand)]
+        action: Option<DaemonCommands>,
+    },
+    /// Record a listing of a directory, or compare recorded listings
+    #[command(args_conflicts_with_subcommands = true)]
+    Snapshot {
+        /// Directory to record
+        path: Option<PathBuf>,
+
+        /// Use the portable index stored inside this root
+        #[arg(long, value_name = "ROOT", global = true)]
+        portable: Option<PathBuf>,
+
+        #[command(subcommand)]
+        action: Option<SnapshotCommands>,
+    },
+    /// Show how files would be classified, or score the classifiers against a labeled list
+    Classify {
+        /// Files to classify
+        #[arg(required_unless_present_any = ["eval", "explain_ext"])]
+        paths: Vec<PathBuf>,
+
+        /// CSV of `path,category` rows; reports per-classifier precision and recall
+        #[arg(long, value_name = "CSV", conflicts_with = "paths")]
+        eval: Option<PathBuf>,
+
+        /// Show every classifier's confidence and weighted score, and where organizing would move the file
+        #[arg(long, conflicts_with = "eval")]
+        explain: bool,
+
+        /// Explain how files with this extension are classified, without a file at hand (repeatable)
+        #[arg(long, value_name = "EXT", conflicts_with = "eval")]
+        explain_ext: Vec<String>,
+    },
+    /// Act on duplicate sets found by other tools (fdupes, jdupes, czkawka)
+    Dedupe {
+        /// Use the portable index stored inside this root
+        #[arg(long, value_name = "ROOT", global = true)]
+        portable: Option<PathBuf>,
+
+        #[command(subcommand)]
+        action: DedupeCommands,
+    },
+    /// Manage files that are never organized
+    Ignore {
+        /// Operate on the portable index stored inside this root
+        #[arg(long, value_name = "ROOT", global = true)]
+        portable: Option<PathBuf>,
+
+        #[command(subcommand)]
+        action: IgnoreCommands,
+    },
+    /// List recorded organize runs
+    Runs {
+        /// Use the portable index stored inside this root
+        #[arg(long, value_name = "ROOT")]
+        portable: Option<PathBuf>,
+    },
+    /// Finish or roll back moves left half done by an interrupted run
+    Repair {
+        /// Use the portable index stored inside this root
+        #[arg(long, value_name = "ROOT")]
+        portable: Option<PathBuf>,
+    },
+    /// Trash organized files older than their category's `retention` limit in the config
+    Prune {
+        /// Use the portable index stored inside this root
+        #[arg(long, value_name = "ROOT")]
+        portable: Option<PathBuf>,
+
+        /// Show what would be trashed without changing anything
+        #[arg(short, long)]
+        dry_run: bool,
+    },
+    /// List old, large organized installers and archives (.dmg, .exe, .iso) that could go
+    SuggestCleanup {
+        /// Use the portable index stored inside this root
+        #[arg(long, value_name = "ROOT")]
+        portable: Option<PathBuf>,
+
+        /// Only files not modified for this long (e.g. 90d, 26w)
+        #[arg(long, value_name = "AGE", default_value = "180d", value_parser = parse_duration)]
+        older_than: Duration,
+
+        /// Only files at least this big (e.g. 500MB)
+        #[arg(long, value_name = "SIZE", default_value = "50MB", value_parser = parse_size)]
+        min_size: u64,
+
+        /// Move the listed files to stash's trash
+        #[arg(long)]
+        delete: bool,
+
+        /// Delete without asking first
+        #[arg(short, long, requires = "delete")]
+        yes: bool,
+
+        /// Print as JSON
+        #[arg(long, conflicts_with = "delete")]
+        json: bool,
+    },
+    /// Check the organizer config
+    Config {
+        #[command(subcommand)]
+        action: ConfigCommands,
+    },
+    /// Look into, restore from or empty stash's trash (~/.stash-trash), where dedupe and prune move files
+    Trash {
+        /// Use the undo records in the portable index inside this root
+        #[arg(long, value_name = "ROOT", global = true)]
+        portable: Option<PathBuf>,
+
+        #[command(subcommand)]
+        action: TrashCommands,
+    },
+    /// Re-hash organized files and report any whose content no longer matches the index
+    Verify {
+        /// Only check files whose current location is under this directory
+        dir: Option<PathBuf>,
+
+        /// Use the portable index stored inside DIR
+        #[arg(long, requires = "dir")]
+        portable: bool,
+
+        /// How to show progress: bar, json (lines on stderr) or none
+        #[arg(long, default_value = "bar", value_name = "MODE")]
+        progress: ProgressMode,
+    },
+    /// Hash and record every file under a directory without moving anything; run again to resume
+    Index {
+        /// Directory to index
+        dir: PathBuf,
+
+        /// Use the portable index stored inside DIR
+        #[arg(long)]
+        portable: bool,
+
+        /// Hash recorded for each file; use the one later organize runs use
+        #[arg(long, default_value = "blake3", value_name = "ALGO")]
+        hash_algo: HashAlgo,
+
+        /// Read files for hashing at most this fast, in MB/s (or e.g. 500KB/s)
+        #[arg(long, value_name = "MB/s", value_parser = parse_throughput)]
+        max_throughput: Option<u64>,
+
+        /// Leave paths matching this glob alone (repeatable); added to the config's `exclude`
+        #[arg(long, value_name = "GLOB")]
+        exclude: Vec<String>,
+
+        /// How to show progress: bar, json (lines on stderr) or none
+        #[arg(long, default_value = "bar", value_name = "MODE")]
+        progress: ProgressMode,
+    },
+    /// Show aggregate numbers from the index: categories, moves per day, extensions, largest files
+    Stats {
+        /// Use the portable index stored inside this root
+        #[arg(long, value_name = "ROOT")]
+        portable: Option<PathBuf>,
+
+        /// How many days, extensions and files to list
+        #[arg(long, default_value_t = 10)]
+        limit: usize,
+
+        /// Only files whose current location is under this directory
+        #[arg(long, value_name = "DIR")]
+        under: Option<PathBuf>,
+
+        /// Rewrite recorded paths starting with OLD to start with NEW, e.g. after
+        /// the drive was remounted (repeatable)
+        #[arg(long = "map-prefix", value_name = "OLD=NEW")]
+        map_prefix: Vec<PathMapping>,
+
+        /// Count files recorded by `stash index` but not organized, instead of organized ones
+        #[arg(long, conflicts_with = "all")]
+        unorganized: bool,
+
+        /// Count every indexed file, organized or not
+        #[arg(long)]
+        all: bool,
+
+        /// Sum what every recorded organize run did, per month and overall, instead
+        #[arg(long, conflicts_with_all = ["under", "map_prefix", "unorganized", "all"])]
+        all_runs: bool,
+
+        /// With --all-runs, also write the monthly totals to this CSV file
+        #[arg(long, value_name = "FILE", requires = "all_runs")]
+        csv: Option<PathBuf>,
+
+        /// Print as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// List indexed files matching every filter given, e.g. where the tax PDFs went
+    Query {
+        /// Use the portable index stored inside this root
+        #[arg(long, value_name = "ROOT")]
+        portable: Option<PathBuf>,
+
+        /// Category, or top-level category with everything under it (`Images`, `Documents::Pdf`)
+        #[arg(long)]
+        category: Option<String>,
+
+        /// Extension, without the dot (repeatable)
+        #[arg(long = "ext", value_name = "EXT")]
+        extensions: Vec<String>,
+
+        /// Glob over the original or organized path (`*tax*.pdf`), or any part of it
+        #[arg(long, value_name = "PATTERN")]
+        name: Option<String>,
+
+        /// Only files at least this big (e.g. 500KB, 5MB)
+        #[arg(long, value_name = "SIZE", value_parser = parse_size)]
+        min_size: Option<u64>,
+
+        /// Only files at most this big
+        #[arg(long, value_name = "SIZE", value_parser = parse_size)]
+        max_size: Option<u64>,
+
+        /// Only files modified on or after this day (YYYY-MM-DD)
+        #[arg(long, value_name = "DATE", value_parser = parse_day)]
+        since: Option<NaiveDate>,
+
+        /// Only files modified on or before this day (YYYY-MM-DD)
+        #[arg(long, value_name = "DATE", value_parser = parse_day)]
+        until: Option<NaiveDate>,
+
+        /// List at most this many files, largest first
+        #[arg(long)]
+        limit: Option<usize>,
+
+        /// Only files whose current location is under this directory
+        #[arg(long, value_name = "DIR")]
+        under: Option<PathBuf>,
+
+        /// Rewrite recorded paths starting with OLD to start with NEW, e.g. after
+        /// the drive was remounted (repeatable)
+        #[arg(long = "map-prefix", value_name = "OLD=NEW")]
+        map_prefix: Vec<PathMapping>,
+
+        /// Only files stash moved
+        #[arg(long, conflicts_with = "unorganized")]
+        organized: bool,
+
+        /// Only files recorded by `stash index` and left where they are
+        #[arg(long)]
+        unorganized: bool,
+
+        /// Output: table or json
+        #[arg(long, default_value = "table", value_name = "FORMAT")]
+        format: QueryFormat,
+    },
+    /// Package what is needed to revert a run into a bundle for another machine
+    ExportUndo {
+        /// Run id (see `stash runs`)
+        run_id: i64,
+
+        /// Bundle to write
+        #[arg(short, long, value_name = "FILE")]
+        out: PathBuf,
+
+        /// Use the portable index stored inside this root
+        #[arg(long, value_name = "ROOT")]
+        portable: Option<PathBuf>,
+    },
+    /// Revert a run from a bundle written by `export-undo`
+    ImportUndo {
+        /// Bundle to read
+        bundle: PathBuf,
+
+        /// Rewrite recorded paths starting with OLD to start with NEW (repeatable)
+        #[arg(long = "map-prefix", value_name = "OLD=NEW")]
+        map_prefix: Vec<PathMapping>,
+
+        /// Show what would happen without changing anything
+        #[arg(short, long)]
+        dry_run: bool,
+
+        /// Skip cleaning up empty directories
+        #[arg(long)]
+        no_cleanup: bool,
+
+        /// Revert into `/`, the home directory, system directories or the
+        /// config's `protected` directories, which are refused otherwise
+        #[arg(long)]
+        i_know_what_im_doing: bool,
+
+        /// When another stash run is working on the run's directories, wait
+        /// for it to finish instead of failing
+        #[arg(long)]
+        wait: bool,
+    },
+    /// Organize with a live dashboard: progress, per-category counts and a
+    /// log of every file, with keys to pause and to skip categories
+    #[cfg(feature = "tui")]
+    Tui {
+        /// Root directories to organize, processed together in one run
+        #[arg(default_value = ".", num_args = 1..)]
+        paths: Vec<PathBuf>,
+
+        #[command(flatten)]
+        args: OrganizeArgs,
+    },
+    /// Replace this binary with the latest GitHub release
+    #[cfg(feature = "self-update")]
+    SelfUpdate {
+        /// Only report whether a newer release exists
+        #[arg(long)]
+        check: bool,
+    },
+    /// Add or remove "Organize with Stash" in the Explorer (Windows),
+    /// Nautilus and Dolphin (Linux) or Finder (macOS) folder context menu
+    ShellIntegration {
+        #[command(subcommand)]
+        action: ShellIntegrationCommands,
+    },
+    /// Print the completion script for a shell, e.g.
+    /// `stash completions bash > /etc/bash_completion.d/stash`
+    Completions {
+        #[arg(value_enum)]
+        shell: Shell,
+    },
+    /// Print the man page, or write one page per command into a directory
+    Man {
+        /// Write `stash.1`, `stash-organize.1`, … here instead
+        #[arg(long, value_name = "DIR")]
+        out: Option<PathBuf>,
     },
     Db {
+        /// Operate on the portable index stored inside this root
+        #[arg(long, value_name = "ROOT", global = true)]
+        portable: Option<PathBuf>,
+
         #[command(subcommand)]
         action: DbCommands,
     }
 }
 
+/// Options `stash organize` and `stash tui` share
+#[derive(clap::Args, Debug, Clone)]
+pub struct OrganizeArgs {
+    /// Perform a dry run without moving files
+    #[arg(short, long)]
+    pub dry_run: bool,
+
+    /// Keep the index inside the organized root with relative paths (for external drives)
+    #[arg(long)]
+    pub portable: bool,
+
+    /// Organize into this directory instead of `<path>/Organized`
+    #[arg(long, value_name = "DIR")]
+    pub dest: Option<PathBuf>,
+
+    /// Create the category folders directly in the directory instead of
+    /// `<path>/Organized`; they are marked so later runs leave them alone
+    #[arg(long, conflicts_with = "dest")]
+    pub in_place: bool,
+
+    /// Record a listing of the directory before organizing
+    #[arg(long)]
+    pub snapshot: bool,
+
+    /// Also organize files this many directory levels down (1, the default,
+    /// is only the files directly in the directory)
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u16).range(1..))]
+    pub max_depth: Option<u16>,
+
+    /// Date directories under each category: year (`2024`), year-month
+    /// (`2024/03`), year-month-day (`2024/03/12`) or none. By default the
+    /// year, plus the month when a `date_patterns` pattern gives one.
+    #[arg(long, value_name = "GRANULARITY")]
+    pub date_folders: Option<DateFolders>,
+
+    /// When a different file already has the destination name: rename
+    /// (`report_1.pdf`), skip, or overwrite it
+    #[arg(long, default_value = "rename", value_name = "STRATEGY")]
+    pub on_conflict: ConflictStrategy,
+
+    /// Files with the same content as a file organized before are not
+    /// organized again: delete them, or trash them (undo with `stash trash restore`)
+    #[arg(long, value_name = "ACTION")]
+    pub dedupe_on_organize: Option<DuplicateAction>,
+
+    /// Hash recorded for each file and used to spot duplicates: blake3,
+    /// sha256, or xxh3 (much faster, but not cryptographic)
+    #[arg(long, default_value = "blake3", value_name = "ALGO")]
+    pub hash_algo: HashAlgo,
+
+    /// Also record a chunk signature of files of 8 MB and more, so
+    /// `stash dedupe similar` can find re-encodes and cut-short downloads
+    #[arg(long)]
+    pub chunk_signatures: bool,
+
+    /// Skip files smaller than this (e.g. 500KB, 10MB)
+    #[arg(long, value_name = "SIZE", value_parser = parse_size)]
+    pub min_size: Option<u64>,
+
+    /// Skip files larger than this (e.g. 2GB)
+    #[arg(long, value_name = "SIZE", value_parser = parse_size)]
+    pub max_size: Option<u64>,
+
+    /// Only organize files last modified at least this long ago (e.g. 30d, 2w)
+    #[arg(long, value_name = "AGE", value_parser = parse_duration)]
+    pub older_than: Option<Duration>,
+
+    /// Only organize files last modified within this long (e.g. 12h, 7d)
+    #[arg(long, value_name = "AGE", value_parser = parse_duration)]
+    pub newer_than: Option<Duration>,
+
+    /// Put files under a directory named after their owner (`Organized/<owner>/Documents/...`)
+    #[arg(long)]
+    pub by_owner: bool,
+
+    /// Only organize files owned by the current user
+    #[arg(long)]
+    pub mine: bool,
+
+    /// Group by where files came from: a layout of `{source_app}` and
+    /// `{source_domain}` tokens, e.g. `{source_domain}` or `{source_app}/{source_domain}`.
+    /// `{category}` places the category elsewhere, e.g. `{category}/{domain}`.
+    #[arg(long, value_name = "TEMPLATE")]
+    pub group_by: Option<GroupTemplate>,
+
+    /// Remove the download quarantine marker (Windows `Zone.Identifier`,
+    /// macOS `com.apple.quarantine`) from files of these trusted categories,
+    /// e.g. `images,documents`. Everything else keeps it.
+    #[arg(long, value_name = "CATEGORIES", value_delimiter = ',')]
+    pub strip_quarantine: Vec<String>,
+
+    /// Leave paths matching this glob alone (repeatable), e.g. `*.part` or
+    /// `Projects/**`; added to the config's `exclude`
+    #[arg(long, value_name = "GLOB")]
+    pub exclude: Vec<String>,
+
+    /// Organize even if a destination on another drive looks too full (only warn)
+    #[arg(long)]
+    pub force: bool,
+
+    /// Organize `/`, the home directory, system directories or the
+    /// config's `protected` directories, which are refused otherwise
+    #[arg(long)]
+    pub i_know_what_im_doing: bool,
+
+    /// When another stash run is working on a root, wait for it to
+    /// finish instead of failing
+    #[arg(long)]
+    pub wait: bool,
+
+    /// Process at most this many files at once (default 32)
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u16).range(1..))]
+    pub max_concurrency: Option<u16>,
+
+    /// Order files are started in: scan (as found), small-first (most
+    /// files done early, large ones finish last), large-first or oldest-first
+    #[arg(long, default_value = "scan", value_name = "ORDER")]
+    pub order: ProcessingOrder,
+
+    /// Copy to other drives at most this fast, in MB/s (or e.g. 500KB/s),
+    /// so organizing can run in the background
+    #[arg(long, value_name = "MB/s", value_parser = parse_throughput)]
+    pub max_throughput: Option<u64>,
+
+    /// Copy at most this many files onto each drive at once (default 1,
+    /// or the config's `transfers_per_device`)
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u16).range(1..))]
+    pub transfers_per_device: Option<u16>,
+
+    /// Rename scanned documents with generic names (SCAN_0042.pdf) after
+    /// the title and date in their text (needs the `ocr` build feature)
+    #[arg(long)]
+    pub ocr_names: bool,
+
+    /// Send attachments saved out of mail clients (files in folders named
+    /// like `Attachments`) to Documents/Attachments/<year>/<sender>, using
+    /// the `.eml` or `.json` saved with them for the sender and date
+    #[arg(long)]
+    pub mail_attachments: bool,
+
+    /// After the run, write its result and counts as JSON for status bars
+    /// and scripts (default `~/.local/state/stash/last_run.json`)
+    #[arg(long, value_name = "FILE", num_args = 0..=1)]
+    pub status_file: Option<Option<PathBuf>>,
+
+    /// After the run, write a timestamped report of every move, skip and
+    /// error plus the summary into this directory, as an audit trail
+    #[arg(long, value_name = "DIR")]
+    pub report: Option<PathBuf>,
+
+    /// Write a `.stash-manifest.json` into each category folder files went
+    /// into: file count, bytes, oldest and newest file, and the run id
+    #[arg(long)]
+    pub manifests: bool,
+
+    /// Format of the --report file: json or html
+    #[arg(long, default_value = "json", value_name = "FORMAT", requires = "report")]
+    pub report_format: RunReportFormat,
+
+    /// Symbolic links: skip them, follow them (organize a copy of what they
+    /// point to), or move-link (move the link, still pointing at its target)
+    #[arg(long, default_value = "skip", value_name = "POLICY")]
+    pub symlinks: SymlinkPolicy,
+
+    /// Project folders (with `.git`, `Cargo.toml`, `package.json`, ...) and
+    /// `.app` bundles: skip them, or move each one whole
+    #[arg(long, default_value = "skip", value_name = "POLICY")]
+    pub bundles: BundlePolicy,
+
+    /// Organize a root stash has not organized before right away, without
+    /// the preview and question a first run gets. Needed for a first run
+    /// without a terminal, e.g. from cron
+    #[arg(short, long)]
+    pub yes: bool,
+
+    /// Organizer config (defaults to `<config dir>/file_organizer/config.json` if it exists)
+    #[arg(short, long, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum ShellIntegrationCommands {
+    /// Add the context menu entries for this stash binary
+    Install {
+        /// Only list what would be added
+        #[arg(short, long)]
+        dry_run: bool,
+
+        /// Add a Finder Quick Action instead (macOS)
+        #[arg(long)]
+        finder: bool,
+
+        /// Organizer config the Quick Action runs with
+        #[arg(short, long, value_name = "FILE", requires = "finder")]
+        config: Option<PathBuf>,
+    },
+    /// Remove the context menu entries
+    Uninstall {
+        /// Remove the Finder Quick Action (macOS)
+        #[arg(long)]
+        finder: bool,
+    },
+}
+
 #[derive(Subcommand, Debug, Clone)]
 pub enum DbCommands {
     /// Optimize the database (VACUUM + ANALYZE)
     Vacuum,
     /// Show database information (path, size, modified_dt, tables, counts)
     Status,
+    /// Write every indexed file (original path, destination, size, hash) for backup or inspection
+    Export {
+        /// json or csv (defaults to the output file's extension, else json)
+        #[arg(long, value_name = "FORMAT")]
+        format: Option<IndexFormat>,
+
+        /// File to write (defaults to stdout)
+        #[arg(short, long, value_name = "FILE")]
+        out: Option<PathBuf>,
+    },
+    /// Merge an export into the index; paths indexed with different data are reported as conflicts
+    Import {
+        /// Export to read
+        file: PathBuf,
+
+        /// json or csv (defaults to the file's extension)
+        #[arg(long, value_name = "FORMAT")]
+        format: Option<IndexFormat>,
+
+        /// Replace conflicting entries instead of keeping them
+        #[arg(long)]
+        overwrite: bool,
+    },
+    /// Forget entries of files that are gone, and list entries whose destination is missing
+    Prune {
+        /// Only entries last updated this long ago (e.g. 180d; defaults to the
+        /// config's `index_retention`, else 180d)
+        #[arg(long, value_name = "AGE", value_parser = parse_duration)]
+        older_than: Option<Duration>,
+
+        /// Also forget every entry whose destination is missing, whatever its age
+        #[arg(long)]
+        orphans: bool,
+
+        /// Show what would be forgotten without changing anything
+        #[arg(short, long)]
+        dry_run: bool,
+    },
+    /// Measure upsert throughput of the index with each write strategy, on scratch databases
+    Bench {
+        /// Row counts to measure, repeatable (default: 1000, 10000 and 100000)
+        #[arg(long, value_name = "N")]
+        rows: Vec<usize>,
+
+        /// Directory for the scratch databases, i.e. the disk measured (default: the temp directory)
+        #[arg(long, value_name = "DIR")]
+        dir: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum ConfigCommands {
+    /// Report errors, unknown keys and deprecated keys without changing the file
+    Validate {
+        /// Config file (defaults to `<config dir>/file_organizer/config.json`)
+        path: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum TrashCommands {
+    /// List trashed runs with their file count, size and age
+    List,
+    /// Put trashed files back where they were, so organized ones can be reverted again
+    Restore {
+        /// Runs to restore, as shown by `stash trash list` (all when none are given)
+        runs: Vec<i64>,
+
+        /// Show what would be restored without moving anything
+        #[arg(short, long)]
+        dry_run: bool,
+    },
+    /// Permanently delete trashed files; they can no longer be undone
+    Empty {
+        /// Only runs trashed at least this long ago (e.g. 30d)
+        #[arg(long, value_name = "AGE", value_parser = parse_duration)]
+        older_than: Option<Duration>,
+
+        /// Show what would be purged without deleting anything
+        #[arg(short, long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum DedupeCommands {
+    /// Import a duplicate report and hardlink or trash every duplicate but the first of each set
+    Import {
+        /// Report file (`fdupes -r`, `jdupes -r [-j]`, czkawka JSON export)
+        report: PathBuf,
+
+        /// Report layout: auto, fdupes, jdupes-json, czkawka-json
+        #[arg(long, default_value = "auto")]
+        format: ReportFormat,
+
+        /// What to do with duplicates: hardlink or trash
+        #[arg(long, default_value = "trash")]
+        action: DedupeAction,
+
+        /// Show what would happen without changing anything
+        #[arg(short, long)]
+        dry_run: bool,
+    },
+    /// List organized files that are probably the same content with a
+    /// different tail (needs `organize --chunk-signatures`)
+    Similar {
+        /// Least share of the smaller file found in the other, 0 to 1
+        #[arg(long, default_value_t = 0.5, value_name = "RATIO")]
+        min_shared: f64,
+    },
+    /// List recorded dedupe actions
+    List {
+        /// Include actions that were already undone
+        #[arg(long)]
+        all: bool,
+    },
+    /// Undo dedupe actions (all pending ones if no ids are given)
+    Undo {
+        ids: Vec<i64>,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum IgnoreCommands {
+    /// Never organize these files
+    Add {
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+    },
+    /// List ignored files
+    List,
+    /// Allow these files to be organized again
+    Remove {
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum DaemonCommands {
+    /// Show whether the daemon is running, and whether and why it is paused
+    Status,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum SnapshotCommands {
+    /// List recorded snapshots
+    List,
+    /// Show files added, removed or changed between two snapshots
+    Diff {
+        /// Older snapshot id
+        from: i64,
+        /// Newer snapshot id
+        to: i64,
+    },
 }
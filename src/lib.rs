@@ -1,17 +1,35 @@
+#[cfg(feature = "native")]
 mod engine;
+#[cfg(feature = "native")]
 mod interface;
 mod classifiers;
+#[cfg(feature = "native")]
 mod mover;
 
+#[cfg(feature = "native")]
 pub mod errors;
 
+#[cfg(feature = "native")]
 pub use engine::{
-    config, index, scanner, utils, watcher, hasher, organizer, reverter,
+    config, config_schema, index, scanner, utils, watcher, hasher, organizer, reverter, snapshot, daemon, calibration, dedupe,
+    undo_bundle, progress, history, index_transfer, verify, provenance, filename_date,
+    run_status, repair, post_process, retention, cancel, shell_integration, exclusions,
+    index_bench, index_prune, trash, control, run_report, mail_export, query, chunking, manifest,
+    cleanup, explain, indexing, workspace, guard, schedule, instance_lock,
 };
+#[cfg(feature = "self-update")]
+pub use engine::self_update;
+#[cfg(feature = "ocr")]
+pub use engine::ocr;
+#[cfg(feature = "tui")]
+pub use engine::tui;
+#[cfg(feature = "native")]
 pub use interface::cli;
+#[cfg(feature = "native")]
+pub use classifiers::registry;
 pub use classifiers::{
     metadata,
-    registry,
+    builtin,
     generic,
     docs_classifier, 
     image_classifier, 
@@ -20,14 +38,31 @@ pub use classifiers::{
     archive_classifier,
     executable_classifier,
     code_classifier,
+    design_classifier,
+    font_classifier,
+    backup_classifier,
+    rule_classifier,
     path_builder,
+    path_safety,
+    doc_naming,
     code_const,
+    preview,
 };
 
+#[cfg(feature = "native")]
 pub use mover::{
+    archive,
     file_mover,
+    fs,
     file_operator,
     directory_manager,
     stats,
     conflict_resolver,
-};
\ No newline at end of file
+    permissions,
+    quarantine,
+    space,
+    throttle,
+    xattr,
+    win_path,
+    volumes,
+};
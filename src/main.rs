@@ -1,53 +1,799 @@
-use std::path::Path;
+use std::{
+    io::{IsTerminal, Write},
+    path::{Path, PathBuf},
+};
 
 use clap::Parser;
-use stash::{cli::{Args, Commands, DbCommands}, index::Db, organizer::organise_files, reverter::revert_files, utils::{default_db_path, expand_tilde, init_tracing}};
+use stash::{
+    cli::{self, Args, Commands, ConfigCommands, DaemonCommands, DbCommands, DedupeCommands, IgnoreCommands, OrganizeArgs, ShellIntegrationCommands, SnapshotCommands, TrashCommands},
+    calibration::{evaluate, load_labels},
+    cancel::CancelToken,
+    chunking,
+    cleanup::{self, CleanupCriteria},
+    control::RunControl,
+    config::{DaemonConfig, OrganizerConfig},
+    config_schema,
+    daemon::{default_pid_path, Daemon, DaemonStatus},
+    dedupe,
+    exclusions::ExcludeGlobs,
+    explain,
+    guard::Guard,
+    history::{IndexStats, RunHistory},
+    index::Db,
+    indexing::{self, IndexOptions},
+    index_bench,
+    index_prune::{prune_index, DEFAULT_INDEX_RETENTION},
+    index_transfer::{self, IndexFormat},
+    organizer::{first_runs, organise_roots, OrganizeOptions},
+    progress::Progress,
+    query::{FileQuery, FileState, IndexScope, QueryFormat, QueryTable},
+    repair,
+    retention,
+    reverter::{revert, RevertOptions, RevertScope, RevertSelection},
+    run_status::default_status_path,
+    snapshot,
+    scanner::{ScanConfig, Scanner, SymlinkPolicy},
+    shell_integration,
+    trash,
+    undo_bundle::{self, ImportOptions},
+    verify,
+    utils::{create_classifier_registry_with, current_uid, default_db_path, default_trash_dir, expand_tilde, init_tracing, portable_db_path},
+    watcher::watch_and_organize,
+    workspace,
+};
 
 fn main() -> anyhow::Result<()> {
     init_tracing();
     let args = Args::parse();
+    // Scratch space of runs that were killed
+    if let Err(e) = workspace::clean_abandoned(&workspace::default_work_root()) {
+        tracing::warn!("Could not clean up abandoned workspaces: {}", e);
+    }
 
     tokio::runtime::Runtime::new()?.block_on(async {
         match args.cmd {
-            Commands::Organize { path, watch, dry_run } => {
+            Commands::Organize { paths, watch, settle, args, progress, interactive } => {
+                let paths = paths.iter().map(|p| expand_path(p)).collect::<anyhow::Result<Vec<_>>>()?;
+                let config = load_config(args.config.as_deref())?;
+                let options = OrganizeOptions { progress: progress.reporter(), ..organize_options(&args, &config)? };
+                options.cancel.cancel_on_signal();
+                if !(interactive || args.dry_run || args.yes || confirm_first_runs(&paths, &options, watch).await?) {
+                    return Ok(());
+                }
                 if watch {
-                    println!("Watch mode not yet implemented");
+                    let [path] = paths.as_slice() else {
+                        anyhow::bail!("--watch takes a single directory");
+                    };
+                    watch_and_organize(path, &options, settle, &config.schedule).await?;
+                } else if interactive {
+                    // Opened from a context menu: keep the window up until read
+                    let result = organize_interactively(&paths, &options).await;
+                    if let Err(e) = &result {
+                        eprintln!("❌ {:#}", e);
+                    }
+                    prompt("Press Enter to close ").await?;
+                    result?;
                 } else {
-                    let path_str = path.to_str()
-                        .ok_or_else(|| anyhow::anyhow!("Path contains invalid UTF-8"))?;
-                    let path = expand_tilde(path_str);
-                    organise_files(Path::new(&path), dry_run).await?;
+                    let summary = organise_roots(&paths, &options).await?;
+                    if summary.cancelled {
+                        anyhow::bail!("Cancelled; {} files were left where they are", summary.not_started);
+                    }
 
                     // Every Nth run, vacuum the DB
-                    let db_path = default_db_path().await?;
-                    let db = Db::new(&db_path).await?;
-                    if rand::random::<u8>() % 20 == 0  {
+                    if rand::random::<u8>().is_multiple_of(20) {
+                        let db = if args.portable {
+                            Db::open_portable(options.dest.as_deref().unwrap_or(&paths[0])).await?
+                        } else {
+                            Db::new(&default_db_path().await?).await?
+                        };
                         if let Err(e) = db.vacuum().await {
                             tracing::warn!(%e, "Auto-vacuum failed");
                         }
                     }
                 }
             }
-            Commands::Revert { root_dir, no_cleanup } => {
-                let root_dir_str = root_dir.to_str()
-                    .ok_or_else(|| anyhow::anyhow!("Root directory path contains invalid UTF-8"))?;
-                let root_dir = expand_tilde(root_dir_str);
-                revert_files(&root_dir, !no_cleanup).await?;
+            Commands::Revert { root_dir, all, original_root, file, glob, no_cleanup, portable, map_prefix, on_mismatch, on_conflict, i_know_what_im_doing, wait, progress } => {
+                let root_dir = root_dir.as_deref().map(expand_path).transpose()?;
+                let select = RevertSelection {
+                    files: file
+                        .iter()
+                        .map(|path| Ok(std::path::absolute(expand_path(path)?)?))
+                        .collect::<anyhow::Result<_>>()?,
+                    globs: ExcludeGlobs::new(&glob)?,
+                };
+                let scope = match original_root {
+                    Some(dir) => RevertScope::OriginalRoot(expand_path(&dir)?),
+                    // Single files are looked up wherever they were moved to
+                    None if all || root_dir.is_none() => RevertScope::All,
+                    None => RevertScope::Destination,
+                };
+                let options = RevertOptions {
+                    cleanup: !no_cleanup,
+                    portable,
+                    mappings: map_prefix,
+                    scope,
+                    select,
+                    on_mismatch,
+                    on_conflict,
+                    guard: if i_know_what_im_doing { Guard::disabled() } else { OrganizerConfig::load_default()?.guard() },
+                    wait_for_lock: wait,
+                    lock_dir: None,
+                    progress: progress.reporter(),
+                };
+                revert(root_dir.as_deref(), &options).await?;
             }
-            Commands::Db { action } => {
+            Commands::Daemon { config, once, action } => {
+                let config_path = match config {
+                    Some(path) => expand_path(&path)?,
+                    None => DaemonConfig::default_path()
+                        .ok_or_else(|| anyhow::anyhow!("No config directory found; pass --config"))?,
+                };
+                let config = DaemonConfig::load_from_file(&config_path)?;
+                match action {
+                    Some(DaemonCommands::Status) => {
+                        let pid_path = config.pid_file.clone().unwrap_or_else(default_pid_path);
+                        print!("{}", DaemonStatus::read(&pid_path));
+                    }
+                    None => {
+                        let daemon = Daemon::new(config).with_organizer_config(OrganizerConfig::load_default()?);
+                        if once {
+                            daemon.run_once().await;
+                        } else {
+                            daemon.run().await?;
+                        }
+                    }
+                }
+            }
+            Commands::Snapshot { path, portable, action } => {
+                let db = match &portable {
+                    Some(root) => Db::open_portable(&expand_path(root)?).await?,
+                    None => Db::new(&default_db_path().await?).await?,
+                };
+                match (action, path) {
+                    (Some(SnapshotCommands::List), _) => {
+                        for info in db.list_snapshots().await? {
+                            let created = chrono::DateTime::from_timestamp(info.created_at, 0)
+                                .map(|t| t.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S").to_string())
+                                .unwrap_or_default();
+                            println!("#{:<4} {}  {:>6} files  {:?}", info.id, created, info.file_count, info.root);
+                        }
+                    }
+                    (Some(SnapshotCommands::Diff { from, to }), _) => {
+                        let load = async |id: i64| -> anyhow::Result<snapshot::Snapshot> {
+                            db.get_snapshot(id)
+                                .await?
+                                .ok_or_else(|| anyhow::anyhow!("Snapshot #{} not found", id))
+                        };
+                        let (old, new) = (load(from).await?, load(to).await?);
+                        if old.root != new.root {
+                            eprintln!("⚠️ Comparing snapshots of different roots: {:?} and {:?}", old.root, new.root);
+                        }
+                        print!("{}", snapshot::diff(&old.entries, &new.entries));
+                    }
+                    (None, path) => {
+                        let root = path.or(portable).unwrap_or_else(|| PathBuf::from("."));
+                        let root = expand_path(&root)?;
+                        let entries = snapshot::capture_async(&root).await?;
+                        let id = db.insert_snapshot(&root, &entries).await?;
+                        println!("📸 Snapshot #{} recorded ({} files)", id, entries.len());
+                    }
+                }
+            }
+            Commands::Classify { paths, eval, explain, explain_ext } => {
+                let organizer_config = OrganizerConfig::load_default()?;
+                let registry = create_classifier_registry_with(&organizer_config.rule_classifiers())
+                    .with_min_score(organizer_config.min_classifier_score)
+                    .with_overrides(organizer_config.extension_overrides());
+                if let Some(csv_path) = eval {
+                    let labels = load_labels(&expand_path(&csv_path)?)?;
+                    print!("{}", evaluate(&registry, &labels).await);
+                } else {
+                    // Classifying only reads, so show what a link points to
+                    let config = ScanConfig {
+                        max_depth: 0,
+                        include_hidden: true,
+                        symlinks: SymlinkPolicy::Follow,
+                        ..Default::default()
+                    };
+                    let date_patterns = organizer_config.date_patterns();
+                    let folder_names = organizer_config.folder_names();
+                    for ext in explain_ext {
+                        print!("{}", explain::explain_extension(&registry, &ext, &date_patterns, &folder_names).await?);
+                    }
+                    for path in paths {
+                        let path = expand_path(&path)?;
+                        match Scanner::new(&path, config.clone()).next() {
+                            Some(Ok(raw)) if explain => print!("{}", explain::explain(&registry, &raw, &date_patterns, &folder_names).await),
+                            Some(Ok(raw)) => match registry.classify_named(&raw).await {
+                                Ok((name, metadata)) => {
+                                    println!("{}: {} ({})", path.display(), metadata.category, name)
+                                }
+                                Err(e) => eprintln!("{}: {}", path.display(), e),
+                            },
+                            Some(Err(e)) => eprintln!("{}: {}", path.display(), e),
+                            None => eprintln!("{}: not found", path.display()),
+                        }
+                    }
+                }
+            }
+            Commands::Dedupe { portable, action } => {
+                let db = match &portable {
+                    Some(root) => Db::open_portable(&expand_path(root)?).await?,
+                    None => Db::new(&default_db_path().await?).await?,
+                };
+                match action {
+                    DedupeCommands::Import { report, format, action, dry_run } => {
+                        let text = std::fs::read_to_string(expand_path(&report)?)?;
+                        let sets = dedupe::parse_report(&text, format)?;
+                        print!("{}", dedupe::apply(&db, &sets, action, dry_run, &default_trash_dir()).await?);
+                    }
+                    DedupeCommands::Similar { min_shared } => {
+                        let pairs = chunking::find_similar(&db.chunk_signatures().await?, min_shared);
+                        print!("{}", dedupe::SimilarReport(&pairs));
+                    }
+                    DedupeCommands::List { all } => {
+                        for record in db.list_actions(all).await? {
+                            let state = if record.reverted_at.is_some() { " (undone)" } else { "" };
+                            println!("#{:<5} {:<8} {:?} -> {:?}{}", record.id, record.kind, record.path, record.target, state);
+                        }
+                    }
+                    DedupeCommands::Undo { ids } => {
+                        let restored = dedupe::undo(&db, &ids).await?;
+                        println!("♻️ Restored {} files", restored);
+                    }
+                }
+            }
+            Commands::Ignore { portable, action } => {
+                let db = match &portable {
+                    Some(root) => Db::open_portable(&expand_path(root)?).await?,
+                    None => Db::new(&default_db_path().await?).await?,
+                };
+                match action {
+                    IgnoreCommands::Add { paths } => {
+                        for path in paths {
+                            let path = expand_path(&path)?;
+                            if db.add_ignored(&path).await? {
+                                println!("🚫 Ignoring {:?}", path);
+                            } else {
+                                println!("Already ignored: {:?}", path);
+                            }
+                        }
+                    }
+                    IgnoreCommands::List => {
+                        for (path, _) in db.list_ignored().await? {
+                            println!("{}", path.display());
+                        }
+                    }
+                    IgnoreCommands::Remove { paths } => {
+                        for path in paths {
+                            let path = expand_path(&path)?;
+                            if db.remove_ignored(&path).await? {
+                                println!("✅ No longer ignoring {:?}", path);
+                            } else {
+                                println!("Not ignored: {:?}", path);
+                            }
+                        }
+                    }
+                }
+            }
+            Commands::Runs { portable } => {
+                let db = match &portable {
+                    Some(root) => Db::open_portable(&expand_path(root)?).await?,
+                    None => Db::new(&default_db_path().await?).await?,
+                };
+                for run in db.list_runs().await? {
+                    let created = chrono::DateTime::from_timestamp(run.created_at, 0)
+                        .map(|t| t.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S").to_string())
+                        .unwrap_or_default();
+                    println!("#{:<4} {}  {:>6} files  {:?} → {:?}", run.id, created, run.file_count, run.root, run.dest_root);
+                }
+            }
+            Commands::Repair { portable } => {
+                let db = match &portable {
+                    Some(root) => Db::open_portable(&expand_path(root)?).await?,
+                    None => Db::new(&default_db_path().await?).await?,
+                };
+                let config = OrganizerConfig::load_default()?;
+                let registry = create_classifier_registry_with(&config.rule_classifiers())
+                    .with_min_score(config.min_classifier_score)
+                    .with_overrides(config.extension_overrides());
+                let report = repair::repair(&db, &registry).await?;
+                if report.is_empty() {
+                    println!("✅ Nothing to repair");
+                } else {
+                    print!("{}", report);
+                }
+            }
+            Commands::Prune { portable, dry_run } => {
+                let limits = OrganizerConfig::load_default()?.retention_limits();
+                if limits.is_empty() {
+                    anyhow::bail!("No retention limits configured; add e.g. \"retention\": {{ \"backups\": \"90d\" }} to the config");
+                }
+                let db = match &portable {
+                    Some(root) => Db::open_portable(&expand_path(root)?).await?,
+                    None => Db::new(&default_db_path().await?).await?,
+                };
+                print!("{}", retention::prune(&db, &limits, dry_run, &default_trash_dir()).await?);
+            }
+            Commands::SuggestCleanup { portable, older_than, min_size, delete, yes, json } => {
+                let db = match &portable {
+                    Some(root) => Db::open_portable(&expand_path(root)?).await?,
+                    None => Db::new(&default_db_path().await?).await?,
+                };
+                let suggestions = cleanup::suggest(&db, &CleanupCriteria { older_than, min_size }).await?;
+                if json {
+                    println!("{}", serde_json::to_string_pretty(&suggestions.0)?);
+                } else {
+                    print!("{}", suggestions);
+                    if delete && !suggestions.0.is_empty() {
+                        let answer = if yes { "y".to_string() } else { prompt("Move these files to the trash? [y/N] ").await? };
+                        if matches!(answer.trim().to_ascii_lowercase().as_str(), "y" | "yes") {
+                            let report = cleanup::delete(&db, &suggestions, &default_trash_dir()).await?;
+                            print!("{}", report);
+                            if !report.errors.is_empty() {
+                                anyhow::bail!("{} files could not be trashed", report.errors.len());
+                            }
+                        } else {
+                            println!("Nothing was deleted");
+                        }
+                    }
+                }
+            }
+            Commands::Config { action } => match action {
+                ConfigCommands::Validate { path } => {
+                    let path = match path {
+                        Some(path) => expand_path(&path)?,
+                        None => OrganizerConfig::default_path().ok_or_else(|| anyhow::anyhow!("No config directory on this system"))?,
+                    };
+                    if !path.exists() {
+                        anyhow::bail!("No config file at {:?}", path);
+                    }
+                    let report = config_schema::validate(&path)?;
+                    print!("{}", report);
+                    if !report.is_valid() {
+                        anyhow::bail!("{:?} has {} errors", path, report.errors.len());
+                    }
+                }
+            },
+            Commands::Trash { portable, action } => {
+                let trash_root = default_trash_dir();
+                let db = match &portable {
+                    Some(root) => Db::open_portable(&expand_path(root)?).await?,
+                    None => Db::new(&default_db_path().await?).await?,
+                };
+                match action {
+                    TrashCommands::List => {
+                        let runs = trash::list_in(&db, &trash_root).await?;
+                        if runs.is_empty() {
+                            println!("🗑️ Trash is empty ({:?})", trash_root);
+                        }
+                        for run in runs {
+                            println!("{}", run);
+                        }
+                    }
+                    TrashCommands::Restore { runs, dry_run } => {
+                        let report = trash::restore(&db, &trash_root, &runs, dry_run).await?;
+                        print!("{}", report);
+                        if !report.errors.is_empty() {
+                            anyhow::bail!("{} files could not be restored", report.errors.len());
+                        }
+                    }
+                    TrashCommands::Empty { older_than, dry_run } => {
+                        print!("{}", trash::empty(&db, &trash_root, older_than, dry_run).await?);
+                    }
+                }
+            }
+            Commands::Verify { dir, portable, progress } => {
+                let dir = dir.as_deref().map(expand_path).transpose()?;
+                let db = match &dir {
+                    Some(dir) if portable => Db::open_portable(dir).await?,
+                    _ => Db::new(&default_db_path().await?).await?,
+                };
+                let report = verify::verify(&db, dir.as_deref(), &progress.reporter()).await?;
+                print!("{}", report);
+                if !report.is_clean() {
+                    anyhow::bail!("{} files failed verification", report.checked - report.ok);
+                }
+            }
+            Commands::Index { dir, portable, hash_algo, max_throughput, exclude: exclude_globs, progress } => {
+                let dir = expand_path(&dir)?;
+                let db = if portable { Db::open_portable(&dir).await? } else { Db::new(&default_db_path().await?).await? };
+                let config = OrganizerConfig::load_default()?;
+                let registry = create_classifier_registry_with(&config.rule_classifiers())
+                    .with_min_score(config.min_classifier_score)
+                    .with_overrides(config.extension_overrides());
+                let mut exclude = config.exclude_globs();
+                exclude.append(ExcludeGlobs::new(&exclude_globs)?);
+                let options = IndexOptions {
+                    hash_algo,
+                    max_throughput,
+                    exclude,
+                    progress: progress.reporter(),
+                    cancel: CancelToken::new(),
+                };
+                options.cancel.cancel_on_signal();
+                let report = indexing::index_tree(&db, &registry, &dir, &options).await?;
+                print!("{}", report);
+            }
+            Commands::Stats { portable, limit, under, map_prefix, unorganized, all, all_runs, csv, json } => {
+                let db = match &portable {
+                    Some(root) => Db::open_portable(&expand_path(root)?).await?,
+                    None => Db::new(&default_db_path().await?).await?,
+                };
+                if all_runs {
+                    let history = RunHistory::collect(&db).await?;
+                    if let Some(path) = csv {
+                        let path = expand_path(&path)?;
+                        history.write_csv(std::fs::File::create(&path)?)?;
+                        eprintln!("📊 Wrote {} months of run totals to {:?}", history.months.len(), path);
+                    }
+                    if json {
+                        println!("{}", serde_json::to_string_pretty(&history)?);
+                    } else {
+                        print!("{}", history);
+                    }
+                } else {
+                    let state = match (unorganized, all) {
+                        (true, _) => Some(FileState::Unorganized),
+                        (_, true) => None,
+                        _ => Some(FileState::Organized),
+                    };
+                    let scope = IndexScope { under: under.as_deref().map(expand_path).transpose()?, state, mappings: map_prefix };
+                    let stats = IndexStats::collect_scoped(&db, limit, &scope).await?;
+                    if json {
+                        println!("{}", serde_json::to_string_pretty(&stats)?);
+                    } else {
+                        print!("{}", stats);
+                    }
+                }
+            }
+            Commands::Query { portable, category, extensions, name, min_size, max_size, since, until, limit, under, map_prefix, organized, unorganized, format } => {
+                let db = match &portable {
+                    Some(root) => Db::open_portable(&expand_path(root)?).await?,
+                    None => Db::new(&default_db_path().await?).await?,
+                };
+                let state = match (organized, unorganized) {
+                    (true, _) => Some(FileState::Organized),
+                    (_, true) => Some(FileState::Unorganized),
+                    _ => None,
+                };
+                let scope = IndexScope { under: under.as_deref().map(expand_path).transpose()?, state, mappings: map_prefix };
+                let query = FileQuery { category, extensions, name, min_size, max_size, since, until, limit, scope };
+                let rows = query.run(&db).await?;
+                match format {
+                    QueryFormat::Table => print!("{}", QueryTable(&rows)),
+                    QueryFormat::Json => println!("{}", serde_json::to_string_pretty(&rows)?),
+                }
+            }
+            Commands::ExportUndo { run_id, out, portable } => {
+                let db = match &portable {
+                    Some(root) => Db::open_portable(&expand_path(root)?).await?,
+                    None => Db::new(&default_db_path().await?).await?,
+                };
+                let out = expand_path(&out)?;
+                let manifest = undo_bundle::export(&db, run_id, &out).await?;
+                println!("📦 Wrote undo bundle for run #{} ({} files) to {:?}", run_id, manifest.moves.len(), out);
+            }
+            Commands::ImportUndo { bundle, map_prefix, dry_run, no_cleanup, i_know_what_im_doing, wait } => {
+                let options = ImportOptions {
+                    mappings: map_prefix,
+                    dry_run,
+                    cleanup: !no_cleanup,
+                    guard: if i_know_what_im_doing { Guard::disabled() } else { OrganizerConfig::load_default()?.guard() },
+                    wait_for_lock: wait,
+                    lock_dir: None,
+                };
+                print!("{}", undo_bundle::import(&expand_path(&bundle)?, &options).await?);
+            }
+            #[cfg(feature = "tui")]
+            Commands::Tui { paths, args } => {
+                use stash::tui::organize_with_dashboard;
+
+                let paths = paths.iter().map(|p| expand_path(p)).collect::<anyhow::Result<Vec<_>>>()?;
+                let config = load_config(args.config.as_deref())?;
+                let options = organize_options(&args, &config)?;
+                if !(args.dry_run || args.yes || confirm_first_runs(&paths, &options, false).await?) {
+                    return Ok(());
+                }
+                // The dashboard's terminal is restored before the summary is shown
+                let summary = organize_with_dashboard(&paths, &options).await?;
+                for failure in &summary.failures {
+                    eprintln!("{}", failure);
+                }
+                println!("{}", summary);
+                if let Some(path) = &summary.report_path {
+                    println!("🧾 Report written to {:?}", path);
+                }
+                if summary.cancelled {
+                    anyhow::bail!("Cancelled; {} files were left where they are", summary.not_started);
+                }
+            }
+            #[cfg(feature = "self-update")]
+            Commands::SelfUpdate { check } => {
+                use stash::self_update::{self_update, UpdateStatus};
+
+                match tokio::task::spawn_blocking(move || self_update(check)).await?? {
+                    UpdateStatus::UpToDate(version) => println!("✅ stash {} is up to date", version),
+                    UpdateStatus::Available { current, latest } => {
+                        println!("⬆️ stash {} is available (installed: {}); run `stash self-update`", latest, current)
+                    }
+                    UpdateStatus::Updated { from, to } => println!("✅ Updated stash {} → {}", from, to),
+                }
+            }
+            Commands::Completions { shell } => cli::write_completions(shell, &mut std::io::stdout()),
+            Commands::Man { out } => match out {
+                Some(dir) => {
+                    let dir = expand_path(&dir)?;
+                    std::fs::create_dir_all(&dir)?;
+                    cli::write_man_pages(&dir)?;
+                    println!("📖 Wrote man pages to {:?}", dir);
+                }
+                None => cli::write_man_page(&mut std::io::stdout())?,
+            },
+            Commands::ShellIntegration { action } => {
+                let exe = std::env::current_exe()?;
+                match action {
+                    ShellIntegrationCommands::Install { dry_run, finder, config } => {
+                        let config = config.as_deref().map(expand_path).transpose()?.map(std::path::absolute).transpose()?;
+                        let entries = shell_integration::entries(&exe, finder, config.as_deref())?;
+                        if !dry_run {
+                            shell_integration::install(&entries)?;
+                        }
+                        let verb = if dry_run { "Would add" } else { "Added" };
+                        for entry in &entries {
+                            println!("{} {}", verb, entry);
+                        }
+                    }
+                    ShellIntegrationCommands::Uninstall { finder } => {
+                        let entries = shell_integration::entries(&exe, finder, None)?;
+                        shell_integration::uninstall(&entries)?;
+                        for entry in &entries {
+                            println!("Removed {}", entry);
+                        }
+                    }
+                }
+            }
+            Commands::Db { portable, action } => {
+                let db_path = match &portable {
+                    Some(root) => portable_db_path(&expand_path(root)?),
+                    None => default_db_path().await?,
+                };
                 match action {
                     DbCommands::Vacuum => {
-                        let db_path = default_db_path().await?;
                         let db = Db::new(&db_path).await?;
                         db.vacuum().await?;
                     }
                     DbCommands::Status => {
-                        let db_path = default_db_path().await?;
                         Db::status(&db_path).await?;
                     }
+                    DbCommands::Export { format, out } => {
+                        let db = match &portable {
+                            Some(root) => Db::open_portable(&expand_path(root)?).await?,
+                            None => Db::new(&db_path).await?,
+                        };
+                        match out {
+                            Some(out) => {
+                                let out = expand_path(&out)?;
+                                let format = format.unwrap_or_else(|| IndexFormat::from_path(&out));
+                                let file = std::io::BufWriter::new(std::fs::File::create(&out)?);
+                                let count = index_transfer::export(&db, format, file).await?;
+                                eprintln!("📤 Exported {} entries to {:?}", count, out);
+                            }
+                            None => {
+                                let format = format.unwrap_or(IndexFormat::Json);
+                                index_transfer::export(&db, format, std::io::stdout().lock()).await?;
+                            }
+                        }
+                    }
+                    DbCommands::Prune { older_than, orphans, dry_run } => {
+                        let db = match &portable {
+                            Some(root) => Db::open_portable(&expand_path(root)?).await?,
+                            None => Db::new(&db_path).await?,
+                        };
+                        let older_than = older_than
+                            .or_else(|| OrganizerConfig::load_default().ok()?.index_retention())
+                            .unwrap_or(DEFAULT_INDEX_RETENTION);
+                        print!("{}", prune_index(&db, older_than, orphans, dry_run).await?);
+                    }
+                    DbCommands::Import { file, format, overwrite } => {
+                        let db = match &portable {
+                            Some(root) => Db::open_portable(&expand_path(root)?).await?,
+                            None => Db::new(&db_path).await?,
+                        };
+                        let file = expand_path(&file)?;
+                        let format = format.unwrap_or_else(|| IndexFormat::from_path(&file));
+                        let records = index_transfer::read_records(format, std::fs::File::open(&file)?)?;
+                        print!("{}", index_transfer::import(&db, records, overwrite).await?);
+                    }
+                    DbCommands::Bench { rows, dir } => {
+                        let dir = match dir {
+                            Some(dir) => expand_path(&dir)?,
+                            None => std::env::temp_dir(),
+                        };
+                        let sizes = if rows.is_empty() { index_bench::DEFAULT_SIZES.to_vec() } else { rows };
+                        print!("{}", index_bench::run(&dir, &sizes).await?);
+                    }
                 }
             }
         }
         Ok(())
     })
 }
+
+/// The organizer config at `file`, or the default one
+fn load_config(file: Option<&Path>) -> anyhow::Result<OrganizerConfig> {
+    Ok(match file {
+        Some(file) => OrganizerConfig::load_from_file(expand_path(file)?)?,
+        None => OrganizerConfig::load_default()?,
+    })
+}
+
+/// What `stash organize` and `stash tui` run with: their shared flags on top
+/// of the organizer config. Progress is silent; callers pick their own.
+fn organize_options(args: &OrganizeArgs, config: &OrganizerConfig) -> anyhow::Result<OrganizeOptions> {
+    if args.mine && current_uid().is_none() {
+        anyhow::bail!("--mine needs file ownership, which is only available on Unix");
+    }
+    if args.ocr_names && !cfg!(feature = "ocr") {
+        anyhow::bail!("--ocr-names needs stash built with `--features ocr`");
+    }
+    let mut exclude = config.exclude_globs();
+    exclude.append(ExcludeGlobs::new(&args.exclude)?);
+    Ok(OrganizeOptions {
+        dry_run: args.dry_run,
+        portable: args.portable,
+        dest: args.dest.as_deref().map(expand_path).transpose()?,
+        volumes: config.volumes(),
+        in_place: args.in_place,
+        snapshot: args.snapshot,
+        max_depth: args.max_depth.map(usize::from),
+        date_folders: args.date_folders,
+        on_conflict: args.on_conflict,
+        dedupe_on_organize: args.dedupe_on_organize,
+        trash_dir: None,
+        hash_algo: args.hash_algo,
+        chunk_signatures: args.chunk_signatures,
+        min_size: args.min_size,
+        max_size: args.max_size,
+        older_than: args.older_than,
+        newer_than: args.newer_than,
+        by_owner: args.by_owner,
+        only_mine: args.mine,
+        symlinks: args.symlinks,
+        bundles: args.bundles,
+        group: args.group_by.clone(),
+        date_patterns: config.date_patterns(),
+        exclude,
+        disabled_categories: config.disabled_categories(),
+        strip_quarantine: config.trusted_categories(&args.strip_quarantine)?,
+        permissions: config.dest_permissions(),
+        legend: config.legend(),
+        folder_names: config.folder_names(),
+        classifiers: config.rule_classifiers(),
+        overrides: config.extension_overrides(),
+        min_classifier_score: config.min_classifier_score,
+        preserve: config.preserve,
+        post_process: config.post_commands(),
+        ocr_names: args.ocr_names,
+        mail_attachments: args.mail_attachments,
+        max_concurrency: args.max_concurrency.map(usize::from),
+        order: args.order,
+        max_throughput: args.max_throughput,
+        transfers_per_device: args.transfers_per_device.map(usize::from).or(config.transfers_per_device),
+        force: args.force,
+        guard: if args.i_know_what_im_doing { Guard::disabled() } else { config.guard() },
+        wait_for_lock: args.wait,
+        lock_dir: None,
+        status_file: args.status_file
+            .as_ref()
+            .map(|file| file.as_deref().map(expand_path).transpose())
+            .transpose()?
+            .map(|file| file.unwrap_or_else(default_status_path)),
+        report_dir: args.report.as_deref().map(expand_path).transpose()?,
+        report_format: args.report_format,
+        manifests: args.manifests,
+        index_retention: config.index_retention(),
+        work_root: Some(workspace::default_work_root()),
+        progress: Progress::silent(),
+        quiet: false,
+        cancel: CancelToken::new(),
+        control: RunControl::new(),
+    })
+}
+
+/// Preview and confirm the run when a root in `paths` was never organized
+/// before. Returns whether to go ahead; fails when there is no terminal to
+/// ask on. `watch` goes ahead with nothing to organize yet.
+async fn confirm_first_runs(paths: &[PathBuf], options: &OrganizeOptions, watch: bool) -> anyhow::Result<bool> {
+    let first_runs = first_runs(paths, options).await?;
+    let Some(first) = first_runs.first() else {
+        return Ok(true);
+    };
+    // Nobody is there to answer the preview (cron, CI, a pipe)
+    if !std::io::stdin().is_terminal() {
+        anyhow::bail!(
+            "stash has not organized {:?} before and can't ask first without a terminal; pass --yes to organize it",
+            first
+        );
+    }
+    // A first run surprises least as a preview, watched or not
+    for root in &first_runs {
+        println!("👋 stash has not organized {:?} before, so here is what it would do (--yes skips this)", root);
+    }
+    match preview_and_confirm(paths, options).await? {
+        Some(true) => Ok(true),
+        // An empty directory is still worth watching
+        None if watch => Ok(true),
+        None => {
+            println!("Nothing to organize");
+            Ok(false)
+        }
+        Some(false) => {
+            println!("Nothing was moved");
+            Ok(false)
+        }
+    }
+}
+
+/// Preview the run, then organize only if the user agrees
+async fn organize_interactively(paths: &[PathBuf], options: &OrganizeOptions) -> anyhow::Result<()> {
+    match preview_and_confirm(paths, options).await? {
+        Some(true) => {}
+        None => {
+            println!("Nothing to organize");
+            return Ok(());
+        }
+        Some(false) => {
+            println!("Nothing was moved");
+            return Ok(());
+        }
+    }
+    let summary = organise_roots(paths, options).await?;
+    if summary.cancelled {
+        anyhow::bail!("Cancelled; {} files were left where they are", summary.not_started);
+    }
+    Ok(())
+}
+
+/// Show what organizing `paths` would do and ask whether to go ahead; `None`
+/// when there is nothing to organize, so nothing to ask
+async fn preview_and_confirm(paths: &[PathBuf], options: &OrganizeOptions) -> anyhow::Result<Option<bool>> {
+    // The preview is a dry run, which would only warn
+    for path in paths {
+        options.guard.check(path)?;
+    }
+    let preview = OrganizeOptions {
+        dry_run: true,
+        snapshot: false,
+        status_file: None,
+        report_dir: None,
+        progress: Progress::silent(),
+        ..options.clone()
+    };
+    let summary = organise_roots(paths, &preview).await?;
+    if summary.discovered == 0 {
+        return Ok(None);
+    }
+    let answer = prompt("Organize these files? [y/N] ").await?;
+    Ok(Some(matches!(answer.trim().to_ascii_lowercase().as_str(), "y" | "yes")))
+}
+
+/// Print `message` on stderr and read a line from stdin
+async fn prompt(message: &str) -> anyhow::Result<String> {
+    let message = message.to_string();
+    tokio::task::spawn_blocking(move || {
+        eprint!("{}", message);
+        std::io::stderr().flush()?;
+        let mut answer = String::new();
+        std::io::stdin().read_line(&mut answer)?;
+        Ok(answer)
+    })
+    .await?
+}
+
+/// Expand `~` and environment variables in a CLI path argument
+fn expand_path(path: &Path) -> anyhow::Result<PathBuf> {
+    let path_str = path.to_str()
+        .ok_or_else(|| anyhow::anyhow!("Path {:?} contains invalid UTF-8", path))?;
+    Ok(expand_tilde(path_str))
+}
@@ -1,5 +1,14 @@
+pub mod archive;
 pub mod directory_manager;
 pub mod conflict_resolver;
 pub mod file_operator;
 pub mod stats;
-pub mod file_mover;
\ No newline at end of file
+pub mod file_mover;
+pub mod fs;
+pub mod permissions;
+pub mod quarantine;
+pub mod space;
+pub mod throttle;
+pub mod xattr;
+pub mod win_path;
+pub mod volumes;
\ No newline at end of file
@@ -0,0 +1,306 @@
+//! Looking inside archives found while organizing. Entry names, sizes and
+//! link targets are written by whoever built the archive, so everything here
+//! treats them as hostile: names that would land outside the destination
+//! are refused (zip-slip), links are reported but never created, and sizes
+//! are checked both as declared and as actually read against
+//! [`ArchiveLimits`]. Classifiers that want to peek into an archive go
+//! through this module instead of opening it themselves.
+
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, Read},
+    ops::ControlFlow,
+    path::{Path, PathBuf},
+};
+
+use flate2::read::GzDecoder;
+
+use crate::{
+    errors::{FileOrganizerError, Result},
+    path_safety::archive_entry_path,
+};
+
+/// Archive formats that can be read, told apart by their magic bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveKind {
+    Zip,
+    Tar,
+    /// A gzip-compressed tar (`.tar.gz`, `.tgz`)
+    TarGz,
+}
+
+impl ArchiveKind {
+    /// What kind of archive `path` is, `None` for anything else (including
+    /// a plain `.gz` of a single file). Tars are recognized by their `ustar`
+    /// magic, so pre-POSIX tars are not.
+    pub fn detect(path: &Path) -> Result<Option<Self>> {
+        let mut head = Vec::with_capacity(TAR_BLOCK);
+        File::open(path)?.take(TAR_BLOCK as u64).read_to_end(&mut head)?;
+
+        if head.starts_with(b"PK\x03\x04") || head.starts_with(b"PK\x05\x06") {
+            return Ok(Some(ArchiveKind::Zip));
+        }
+        if is_tar_header(&head) {
+            return Ok(Some(ArchiveKind::Tar));
+        }
+        if head.starts_with(&[0x1f, 0x8b]) {
+            let mut inner = Vec::with_capacity(TAR_BLOCK);
+            // A truncated or corrupt stream is just not a tar.gz
+            let _ = GzDecoder::new(File::open(path)?).take(TAR_BLOCK as u64).read_to_end(&mut inner);
+            if is_tar_header(&inner) {
+                return Ok(Some(ArchiveKind::TarGz));
+            }
+        }
+        Ok(None)
+    }
+}
+
+const TAR_BLOCK: usize = 512;
+
+fn is_tar_header(block: &[u8]) -> bool {
+    block.get(257..262) == Some(b"ustar".as_slice())
+}
+
+/// How much an archive may hold before it is treated as a bomb
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArchiveLimits {
+    pub max_entries: usize,
+    /// Uncompressed bytes of any one entry
+    pub max_entry_size: u64,
+    /// Uncompressed bytes of all entries together
+    pub max_total_size: u64,
+    /// Uncompressed size over compressed size of a zip entry
+    pub max_ratio: u64,
+}
+
+impl Default for ArchiveLimits {
+    fn default() -> Self {
+        Self {
+            max_entries: 10_000,
+            max_entry_size: 1 << 30,
+            max_total_size: 4 << 30,
+            max_ratio: 100,
+        }
+    }
+}
+
+/// What an entry is
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EntryKind {
+    File,
+    Dir,
+    /// Target as written in the archive, unchecked
+    Symlink(PathBuf),
+    /// Target as written in the archive, unchecked
+    Hardlink(PathBuf),
+    /// Devices, fifos, sparse files and the like
+    Other,
+}
+
+/// One entry of an archive
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchiveEntry {
+    /// Name as written in the archive
+    pub name: String,
+    /// Where the entry would go relative to the extraction directory, `None`
+    /// when the name is absolute or climbs out of it
+    pub path: Option<PathBuf>,
+    pub kind: EntryKind,
+    /// Declared uncompressed size
+    pub size: u64,
+    /// Declared compressed size, for zip entries
+    pub compressed_size: Option<u64>,
+}
+
+impl ArchiveEntry {
+    /// Why extracting this entry under `limits` would be unsafe, if it would
+    fn check(&self, limits: &ArchiveLimits) -> Option<String> {
+        if matches!(self.kind, EntryKind::File | EntryKind::Dir) && self.path.is_none() {
+            return Some(format!("entry {:?} would be written outside the destination", self.name));
+        }
+        if self.size > limits.max_entry_size {
+            return Some(format!("entry {:?} declares {} bytes", self.name, self.size));
+        }
+        if let Some(compressed) = self.compressed_size
+            && self.size / compressed.max(1) > limits.max_ratio
+        {
+            return Some(format!(
+                "entry {:?} expands {}x",
+                self.name,
+                self.size / compressed.max(1)
+            ));
+        }
+        None
+    }
+}
+
+/// Every entry of the archive at `path`, unsafe ones included (with `path`
+/// unset), without reading their contents. Fails once there are more than
+/// `limits.max_entries`.
+pub fn list(path: &Path, limits: &ArchiveLimits) -> Result<Vec<ArchiveEntry>> {
+    let mut entries = Vec::new();
+    for_each_entry(path, |entry, _| {
+        if entries.len() == limits.max_entries {
+            return Err(unsafe_archive(path, format!("more than {} entries", limits.max_entries)));
+        }
+        entries.push(entry);
+        Ok(ControlFlow::Continue(()))
+    })?;
+    Ok(entries)
+}
+
+/// Contents of the file entry called `name`, at most `max_bytes` of it;
+/// `None` when there is no such file
+pub fn read_entry(path: &Path, name: &str, max_bytes: u64) -> Result<Option<Vec<u8>>> {
+    let mut contents = None;
+    for_each_entry(path, |entry, reader| {
+        if entry.kind != EntryKind::File || entry.name != name {
+            return Ok(ControlFlow::Continue(()));
+        }
+        let mut buf = Vec::new();
+        reader.take(max_bytes).read_to_end(&mut buf)?;
+        contents = Some(buf);
+        Ok(ControlFlow::Break(()))
+    })?;
+    Ok(contents)
+}
+
+/// What [`extract`] did
+#[derive(Debug, Default)]
+pub struct ExtractReport {
+    /// Files written, under the destination
+    pub files: Vec<PathBuf>,
+    pub dirs: usize,
+    pub bytes: u64,
+    /// Links and special entries left out, by name
+    pub skipped: Vec<String>,
+}
+
+/// Unpack the archive at `path` into `dest`. The whole archive is checked
+/// against `limits` first and nothing is written if any entry is unsafe;
+/// while writing, entries that turn out bigger than they declared stop the
+/// extraction. Links and special files are skipped, modes are not kept, and
+/// no existing file is replaced, so `dest` should be a fresh directory.
+pub fn extract(path: &Path, dest: &Path, limits: &ArchiveLimits) -> Result<ExtractReport> {
+    let entries = list(path, limits)?;
+    let mut declared: u64 = 0;
+    for entry in &entries {
+        if let Some(reason) = entry.check(limits) {
+            return Err(unsafe_archive(path, reason));
+        }
+        declared = declared.saturating_add(entry.size);
+    }
+    if declared > limits.max_total_size {
+        return Err(unsafe_archive(path, format!("entries declare {} bytes in total", declared)));
+    }
+
+    std::fs::create_dir_all(dest)?;
+    let mut report = ExtractReport::default();
+    for_each_entry(path, |entry, reader| {
+        let target = match (&entry.kind, &entry.path) {
+            (EntryKind::File | EntryKind::Dir, Some(relative)) => dest.join(relative),
+            _ => {
+                report.skipped.push(entry.name);
+                return Ok(ControlFlow::Continue(()));
+            }
+        };
+        if entry.kind == EntryKind::Dir {
+            std::fs::create_dir_all(&target)?;
+            report.dirs += 1;
+            return Ok(ControlFlow::Continue(()));
+        }
+
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let limit = entry.size.min(limits.max_entry_size).min(limits.max_total_size.saturating_sub(report.bytes));
+        let mut file = OpenOptions::new().write(true).create_new(true).open(&target)?;
+        let written = io::copy(&mut reader.take(limit + 1), &mut file)?;
+        if written > limit {
+            drop(file);
+            let _ = std::fs::remove_file(&target);
+            return Err(unsafe_archive(path, format!("entry {:?} holds more than it declares", entry.name)));
+        }
+        report.bytes += written;
+        report.files.push(target);
+        Ok(ControlFlow::Continue(()))
+    })?;
+    Ok(report)
+}
+
+fn unsafe_archive(path: &Path, reason: String) -> FileOrganizerError {
+    FileOrganizerError::UnsafeArchive { path: path.to_path_buf(), reason }
+}
+
+fn unreadable(path: &Path, error: impl std::fmt::Display) -> FileOrganizerError {
+    FileOrganizerError::Other(format!("{:?} is not a readable archive: {}", path, error))
+}
+
+/// Call `f` with every entry of the archive at `path` and a reader over its
+/// contents, until it breaks
+fn for_each_entry(
+    path: &Path,
+    mut f: impl FnMut(ArchiveEntry, &mut dyn Read) -> Result<ControlFlow<()>>,
+) -> Result<()> {
+    match ArchiveKind::detect(path)? {
+        Some(ArchiveKind::Zip) => {
+            let mut archive = zip::ZipArchive::new(File::open(path)?).map_err(|e| unreadable(path, e))?;
+            for i in 0..archive.len() {
+                let mut file = archive.by_index(i).map_err(|e| unreadable(path, e))?;
+                let name = file.name().to_string();
+                let kind = if file.is_symlink() {
+                    let mut target = String::new();
+                    (&mut file).take(4096).read_to_string(&mut target)?;
+                    EntryKind::Symlink(PathBuf::from(target))
+                } else if file.is_dir() {
+                    EntryKind::Dir
+                } else {
+                    EntryKind::File
+                };
+                let entry = ArchiveEntry {
+                    path: archive_entry_path(&name),
+                    name,
+                    kind,
+                    size: file.size(),
+                    compressed_size: Some(file.compressed_size()),
+                };
+                if f(entry, &mut file)?.is_break() {
+                    break;
+                }
+            }
+        }
+        Some(kind @ (ArchiveKind::Tar | ArchiveKind::TarGz)) => {
+            let file = File::open(path)?;
+            let reader: Box<dyn Read> =
+                if kind == ArchiveKind::TarGz { Box::new(GzDecoder::new(file)) } else { Box::new(file) };
+            let mut archive = tar::Archive::new(reader);
+            for entry in archive.entries()? {
+                let mut entry = entry?;
+                let name = String::from_utf8_lossy(&entry.path_bytes()).into_owned();
+                let target = || {
+                    let target = entry.link_name_bytes().unwrap_or_default();
+                    PathBuf::from(String::from_utf8_lossy(&target).into_owned())
+                };
+                let kind = match entry.header().entry_type() {
+                    tar::EntryType::Regular | tar::EntryType::Continuous => EntryKind::File,
+                    tar::EntryType::Directory => EntryKind::Dir,
+                    tar::EntryType::Symlink => EntryKind::Symlink(target()),
+                    tar::EntryType::Link => EntryKind::Hardlink(target()),
+                    _ => EntryKind::Other,
+                };
+                let archive_entry = ArchiveEntry {
+                    path: archive_entry_path(&name),
+                    name,
+                    kind,
+                    size: entry.size(),
+                    compressed_size: None,
+                };
+                if f(archive_entry, &mut entry)?.is_break() {
+                    break;
+                }
+            }
+        }
+        None => return Err(unreadable(path, "unknown format")),
+    }
+    Ok(())
+}
@@ -1,33 +1,48 @@
-use std::path::{Path, PathBuf};
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
 
-use tokio::fs;
-
-use crate::errors::Result;
+use crate::{errors::Result, mover::fs::{FileSystem, RealFs}, path_safety::conflict_candidate};
 
 /// Renames conflicting destination by appending counter (file.txt → file_1.txt).
 pub async fn resolve_conflict(path: &Path, overwrite: bool) -> Result<PathBuf> {
+    resolve_conflict_in(&RealFs, path, overwrite).await
+}
+
+/// [`resolve_conflict`] against the given filesystem
+pub async fn resolve_conflict_in(fs: &dyn FileSystem, path: &Path, overwrite: bool) -> Result<PathBuf> {
     if overwrite {
-        if tokio::fs::try_exists(path).await? {
-            tokio::fs::remove_file(path).await?;
+        if fs.exists(path).await? {
+            fs.remove_file(path).await?;
         }
         return Ok(path.to_path_buf())
     }
 
     // Non-overwrite: keep original if free
-    if !tokio::fs::try_exists(path).await? {
+    if !fs.exists(path).await? {
         return Ok(path.to_path_buf());
     }
-    
-    let parent = path.parent().unwrap_or_else(|| Path::new("."));
-    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
-    let ext = path.extension().map(|e| format!(".{}", e.to_string_lossy())).unwrap_or_default();
 
     let mut counter = 1;
     loop {
-        let candidate = parent.join(format!("{}_{}{}", stem, counter, ext));
-        if !fs::try_exists(&candidate).await? {
+        let candidate = conflict_candidate(path, counter);
+        if !fs.exists(&candidate).await? {
             return Ok(candidate);
         }
         counter += 1;
     }
-}
\ No newline at end of file
+}
+
+/// A free name for `path` like [`resolve_conflict_in`] without overwriting,
+/// also passing over the names in `claimed` that other files are about to
+/// be moved to
+pub async fn resolve_conflict_claimed(fs: &dyn FileSystem, path: &Path, claimed: &HashSet<PathBuf>) -> Result<PathBuf> {
+    let mut candidate = path.to_path_buf();
+    let mut counter = 0;
+    while claimed.contains(&candidate) || fs.exists(&candidate).await? {
+        counter += 1;
+        candidate = conflict_candidate(path, counter);
+    }
+    Ok(candidate)
+}
@@ -1,23 +1,64 @@
-use std::{collections::HashSet, path::Path, sync::Arc};
-use tokio::{
-    fs, io::{self, AsyncWriteExt}, sync::RwLock, task
-};
+use std::{collections::HashSet, fmt, path::{self, Path}, sync::Arc};
+use tokio::{io, sync::RwLock};
 use tracing::{debug, instrument};
 
-use crate::errors::Result;
+use crate::{errors::Result, mover::fs::{FileSystem, Preserve, RealFs}, scanner::SymlinkPolicy};
 
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct FileMover {
+    fs: Arc<dyn FileSystem>,
+    /// `Follow` replaces a moved link by a copy of what it points to; with
+    /// any other policy the link itself is moved
+    symlinks: SymlinkPolicy,
+    /// Metadata kept when a move falls back to copying
+    preserve: Preserve,
     created_dirs: Arc<RwLock<HashSet<String>>>,
 }
 
+impl fmt::Debug for FileMover {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FileMover").field("fs", &self.fs).field("symlinks", &self.symlinks).finish_non_exhaustive()
+    }
+}
+
+impl Default for FileMover {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl FileMover {
     pub fn new() -> Self {
+        Self::with_fs(Arc::new(RealFs))
+    }
+
+    /// Mover working on `fs` instead of the real filesystem
+    pub fn with_fs(fs: Arc<dyn FileSystem>) -> Self {
         Self {
+            fs,
+            symlinks: SymlinkPolicy::default(),
+            preserve: Preserve::default(),
             created_dirs: Arc::new(RwLock::new(HashSet::new())),
         }
     }
 
+    /// Which metadata copies keep (all by default)
+    pub fn with_preserve(mut self, preserve: Preserve) -> Self {
+        self.preserve = preserve;
+        self
+    }
+
+    /// How symbolic links handed to [`FileMover::move_file`] are moved
+    pub fn with_symlinks(mut self, policy: SymlinkPolicy) -> Self {
+        self.symlinks = policy;
+        self
+    }
+
+    /// The filesystem files are moved on
+    pub fn fs(&self) -> &dyn FileSystem {
+        self.fs.as_ref()
+    }
+
     /// Ensure parent dir exists (creates once, cache result)
     pub async fn ensure_parent_dir(&self, dest: &Path) -> Result<()> {
         if let Some(parent) = dest.parent() {
@@ -30,7 +71,7 @@ impl FileMover {
                 }
             }
 
-            fs::create_dir_all(parent).await?;
+            self.fs.create_dir_all(parent).await?;
 
             let mut cache = self.created_dirs.write().await;
             cache.insert(dir_str);
@@ -38,20 +79,15 @@ impl FileMover {
         Ok(())
     }
 
-    #[cfg(unix)]
-    fn is_cross_device_error(e: &io::Error) -> bool {
-        e.kind() == io::ErrorKind::CrossesDevices
-    }
-
     #[cfg(windows)]
     fn is_cross_device_error(e: &io::Error) -> bool {
         // Windows returns ERROR_NOT_SAME_DEVICE (17) for cross-device moves
-        e.raw_os_error() == Some(17)
+        e.raw_os_error() == Some(17) || e.kind() == io::ErrorKind::CrossesDevices
     }
 
-    #[cfg(not(any(unix, windows)))]
-    fn is_cross_device_error(_e: &io::Error) -> bool {
-        false
+    #[cfg(not(windows))]
+    fn is_cross_device_error(e: &io::Error) -> bool {
+        e.kind() == io::ErrorKind::CrossesDevices
     }
 
     /// Move file, falling back to copy+delete if across devices
@@ -59,7 +95,11 @@ impl FileMover {
     pub async fn move_file(&self, src: &Path, dest: &Path) -> Result<()> {
         self.ensure_parent_dir(dest).await?;
 
-        match fs::rename(src, dest).await {
+        if let Some(target) = self.fs.read_link(src).await? {
+            return self.move_link(src, &target, dest).await;
+        }
+
+        match self.fs.rename(src, dest).await {
             Ok(_) => {
                 debug!(?src, ?dest, "File moved with rename");
                 Ok(())
@@ -67,146 +107,106 @@ impl FileMover {
             Err(e) if Self::is_cross_device_error(&e) => {
                 tracing::debug!(?src, ?dest, "Cross-device move, falling back to copy+delete");
                 self.copy_file(src, dest).await?;
-                fs::remove_file(src).await?;
+                self.fs.remove_file(src).await?;
                 Ok(())
             }
             Err(e) => Err(e.into()),
         }
     }
 
-    /// Copy file efficiently (platform-specific fast path, buffered fallback)
+    /// Move the directory `src` to `dest` as one unit: a rename on the same
+    /// filesystem. Across filesystems the tree is copied next to `dest` under
+    /// a temporary name, renamed into place once complete and only then
+    /// removed from `src`, so `dest` never holds half of it.
     #[instrument(skip(self), level = "debug")]
-    pub async fn copy_file(&self, src: &Path, dest: &Path) -> Result<()> {
+    pub async fn move_dir(&self, src: &Path, dest: &Path) -> Result<()> {
         self.ensure_parent_dir(dest).await?;
-        
-        #[cfg(target_os = "linux")]
-        {
-            if let Err(e) = self.copy_file_unix(src, dest).await {
-                debug!(error = ?e, "sendfile failed, falling back to buffered copy");
-                self.buffered_copy(src, dest).await
-            } else {
+
+        match self.fs.rename(src, dest).await {
+            Ok(_) => {
+                debug!(?src, ?dest, "Directory moved with rename");
                 Ok(())
             }
-        }
-
-        #[cfg(target_os = "macos")]
-        {
-            // macOS doesn’t support rustix::sendfile
-            self.buffered_copy(src, dest).await
-        }
-
-        #[cfg(windows)]
-        {
-            if let Err(e) = self.copy_file_windows(src, dest).await {
-                debug!(error = ?e, "CopyFileExW failed, falling back to buffered copy");
-                self.buffered_copy(src, dest).await
-            } else {
+            Err(e) if Self::is_cross_device_error(&e) => {
+                let name = dest.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+                let staging = dest.with_file_name(format!(".{}.stash-partial", name));
+                debug!(?src, ?dest, ?staging, "Cross-device directory move, copying the tree first");
+
+                let (from, to) = (src.to_path_buf(), staging.clone());
+                let copied = tokio::task::spawn_blocking(move || copy_tree(&from, &to)).await?;
+                if let Err(e) = copied.and_then(|_| std::fs::rename(&staging, dest)) {
+                    let _ = std::fs::remove_dir_all(&staging);
+                    return Err(e.into());
+                }
+                tokio::fs::remove_dir_all(src).await?;
                 Ok(())
             }
+            Err(e) => Err(e.into()),
         }
+    }
 
-        // Fallback for other platforms (e.g., WASM, embedded)
-        #[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
-        {
-            self.buffered_copy(src, dest).await
+    /// Move the symbolic link `src` (pointing at `target`) to `dest`
+    async fn move_link(&self, src: &Path, target: &Path, dest: &Path) -> Result<()> {
+        if self.symlinks == SymlinkPolicy::Follow {
+            debug!(?src, ?dest, "Replacing link with a copy of its target");
+            self.fs.copy(src, dest).await?;
+        } else {
+            // A relative target would point elsewhere from the new location
+            let target = match src.parent() {
+                Some(parent) if target.is_relative() => path::absolute(parent.join(target))?,
+                _ => target.to_path_buf(),
+            };
+            debug!(?src, ?dest, ?target, "Recreating link");
+            self.fs.symlink(&target, dest).await?;
         }
+        self.fs.remove_file(src).await?;
+        Ok(())
     }
 
-    /// Buffered async copy fallback
-    async fn buffered_copy(&self, src: &Path, dest: &Path) -> Result<()> {
-        let mut src_file = fs::File::open(src).await?;
-        let mut dest_file = fs::File::create(dest).await?;
-        tokio::io::copy(&mut src_file, &mut dest_file).await?;
-        dest_file.flush().await?;
-
-        let metadata = fs::metadata(src).await?;
-        fs::set_permissions(dest, metadata.permissions()).await?;
+    /// Copy file efficiently, keeping its download quarantine marker and, as
+    /// configured, its timestamps and extended attributes. Metadata that
+    /// cannot be kept (e.g. on a FAT drive) is only warned about.
+    #[instrument(skip(self), level = "debug")]
+    pub async fn copy_file(&self, src: &Path, dest: &Path) -> Result<()> {
+        self.ensure_parent_dir(dest).await?;
+        self.fs.copy(src, dest).await?;
+        if let Err(e) = self.fs.copy_metadata(src, dest, self.preserve).await {
+            tracing::warn!(?src, ?dest, "Copied without all of its metadata: {}", e);
+        }
         Ok(())
     }
 
     /// Get file size
     #[instrument(skip(self), level = "debug")]
     pub async fn get_file_size(&self, path: &Path) -> Result<u64> {
-        let metadata = fs::metadata(path).await?;
-        Ok(metadata.len())
-    }
-
-    // ----------- Platform-specific fast paths -----------
-
-    /// Unix: use rustix::fs::sendfile
-    #[cfg(target_os = "linux")]
-    async fn copy_file_unix(&self, src: &Path, dest: &Path) -> Result<()> {
-        use std::os::fd::AsFd;
-        use rustix::fs::sendfile;
-
-        // Take ownership of paths (so they can move into the 'static closure)
-        let src = src.to_path_buf();
-        let dest = dest.to_path_buf();
-
-        let src_file = fs::File::open(&src).await?;
-        let dest_file = fs::File::create(&dest).await?;
-
-        let std_src = src_file.into_std().await;
-        let std_dest = dest_file.into_std().await;
-
-        task::spawn_blocking(move || {
-            let len = std::fs::metadata(&src)?.len();
-            let mut offset: u64 = 0;
-            let mut remaining = len;
-
-            while remaining > 0 {
-                let written = sendfile(
-                    std_dest.as_fd(),
-                    std_src.as_fd(),
-                    Some(&mut offset),
-                    remaining as usize,
-                )?;
-                if written == 0 {
-                    if remaining > 0 {
-                        return Err(std::io::Error::new(
-                            std::io::ErrorKind::UnexpectedEof,
-                            "sendfile returned 0 before copying all data"
-                        ));
-                    }
-                    break;
-                }
-                remaining -= written as u64;
-            }
-            Ok::<_, std::io::Error>(())
-        })
-        .await??; // join + IO error propagation
-
-        Ok(())
+        Ok(self.fs.metadata(path).await?.len)
     }
+}
 
+/// Copy the directory tree `src` to `dest`, which must not exist yet.
+/// Links are recreated as links rather than followed.
+fn copy_tree(src: &Path, dest: &Path) -> io::Result<()> {
+    for entry in walkdir::WalkDir::new(src) {
+        let entry = entry.map_err(io::Error::other)?;
+        let target = dest.join(entry.path().strip_prefix(src).map_err(io::Error::other)?);
+        let file_type = entry.file_type();
+        if file_type.is_dir() {
+            std::fs::create_dir(&target)?;
+        } else if file_type.is_symlink() {
+            copy_link(entry.path(), &target)?;
+        } else {
+            std::fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
 
-    /// Windows: use CopyFileExW
-    #[cfg(windows)]
-    async fn copy_file_windows(&self, src: &Path, dest: &Path) -> Result<()> {
-        use std::os::windows::ffi::OsStrExt;
-        use windows_sys::Win32::Storage::FileSystem::CopyFileExW;
-
-        let src_w: Vec<u16> = src.as_os_str().encode_wide().chain(Some(0)).collect();
-        let dest_w: Vec<u16> = dest.as_os_str().encode_wide().chain(Some(0)).collect();
-
-        task::spawn_blocking(move || unsafe {
-            let success = CopyFileExW(
-                src_w.as_ptr(),
-                dest_w.as_ptr(),
-                None,
-                std::ptr::null_mut(),
-                std::ptr::null_mut(),
-                0,
-            );
-            if success == 0 {
-                Err(std::io::Error::last_os_error())
-            } else {
-                Ok(())
-            }
-        })
-        .await??;
+#[cfg(unix)]
+fn copy_link(src: &Path, dest: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(std::fs::read_link(src)?, dest)
+}
 
-        debug!(?src, ?dest, "Copied with CopyFileExW");
-        Ok(())
-    }
+#[cfg(not(unix))]
+fn copy_link(src: &Path, _dest: &Path) -> io::Result<()> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, format!("cannot copy the link {:?}", src)))
 }
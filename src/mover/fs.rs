@@ -0,0 +1,663 @@
+//! The filesystem operations moving files relies on, behind a trait so the
+//! placement logic (renames, cross-device fallback, conflict naming) can run
+//! against [`MemoryFs`] in tests instead of the real disk.
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashSet},
+    fmt::Debug,
+    io,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use tokio::{fs, io::{AsyncReadExt, AsyncWriteExt}, task};
+
+use crate::mover::{quarantine, throttle::{self, Throttle}, win_path::extended_length, xattr};
+
+/// What callers need to know about a path
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FsMetadata {
+    pub len: u64,
+    pub is_dir: bool,
+    pub modified: Option<SystemTime>,
+}
+
+/// Metadata carried over when a move has to copy (across devices)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct Preserve {
+    /// Modification and access times
+    pub timestamps: bool,
+    /// Extended attributes (macOS Finder tags, Linux `user.*`) and Windows
+    /// alternate data streams
+    pub xattrs: bool,
+}
+
+impl Default for Preserve {
+    fn default() -> Self {
+        Self { timestamps: true, xattrs: true }
+    }
+}
+
+#[async_trait]
+pub trait FileSystem: Debug + Send + Sync {
+    async fn metadata(&self, path: &Path) -> io::Result<FsMetadata>;
+
+    async fn exists(&self, path: &Path) -> io::Result<bool>;
+
+    /// Whole contents of a file
+    async fn read(&self, path: &Path) -> io::Result<Vec<u8>>;
+
+    /// Create or replace a file
+    async fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()>;
+
+    /// Rename within one filesystem. Fails with
+    /// [`io::ErrorKind::CrossesDevices`] (or the OS equivalent) otherwise.
+    async fn rename(&self, from: &Path, to: &Path) -> io::Result<()>;
+
+    /// Copy contents and permissions, replacing `to`
+    async fn copy(&self, from: &Path, to: &Path) -> io::Result<()>;
+
+    /// Give the copy `to` the metadata of `from` that `preserve` asks for
+    async fn copy_metadata(&self, from: &Path, to: &Path, preserve: Preserve) -> io::Result<()>;
+
+    async fn remove_file(&self, path: &Path) -> io::Result<()>;
+
+    async fn create_dir_all(&self, path: &Path) -> io::Result<()>;
+
+    /// Target of `path` if it is a symbolic link
+    async fn read_link(&self, path: &Path) -> io::Result<Option<PathBuf>>;
+
+    /// Create a symbolic link at `link` pointing to `target`
+    async fn symlink(&self, target: &Path, link: &Path) -> io::Result<()>;
+
+    /// Every file and symbolic link below `root`, sorted
+    async fn list_files(&self, root: &Path) -> io::Result<Vec<PathBuf>>;
+}
+
+/// The operating system's filesystem. Paths too long for the plain Windows
+/// calls get the extended-length prefix on the way in.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RealFs;
+
+#[async_trait]
+impl FileSystem for RealFs {
+    async fn metadata(&self, path: &Path) -> io::Result<FsMetadata> {
+        let metadata = fs::metadata(extended_length(path)).await?;
+        Ok(FsMetadata {
+            len: metadata.len(),
+            is_dir: metadata.is_dir(),
+            modified: metadata.modified().ok(),
+        })
+    }
+
+    async fn exists(&self, path: &Path) -> io::Result<bool> {
+        fs::try_exists(extended_length(path)).await
+    }
+
+    async fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        fs::read(extended_length(path)).await
+    }
+
+    async fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        fs::write(extended_length(path), contents).await
+    }
+
+    async fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        fs::rename(extended_length(from), extended_length(to)).await
+    }
+
+    /// Platform fast path with a buffered fallback, keeping the download
+    /// quarantine marker of `from`
+    async fn copy(&self, from: &Path, to: &Path) -> io::Result<()> {
+        let (from, to) = (extended_length(from), extended_length(to));
+        let marker = quarantine::read(&from);
+        copy_contents(&from, &to).await?;
+        if let Some(marker) = marker {
+            quarantine::write(&to, &marker)?;
+        }
+        Ok(())
+    }
+
+    async fn copy_metadata(&self, from: &Path, to: &Path, preserve: Preserve) -> io::Result<()> {
+        let (from, to) = (extended_length(from).into_owned(), extended_length(to).into_owned());
+        task::spawn_blocking(move || {
+            if preserve.xattrs {
+                xattr::copy(&from, &to)?;
+            }
+            // Last, since writing attributes or streams can touch the mtime
+            if preserve.timestamps {
+                copy_times(&from, &to)?;
+            }
+            Ok(())
+        })
+        .await?
+    }
+
+    async fn remove_file(&self, path: &Path) -> io::Result<()> {
+        fs::remove_file(extended_length(path)).await
+    }
+
+    async fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        fs::create_dir_all(extended_length(path)).await
+    }
+
+    async fn read_link(&self, path: &Path) -> io::Result<Option<PathBuf>> {
+        if !fs::symlink_metadata(path).await?.is_symlink() {
+            return Ok(None);
+        }
+        fs::read_link(path).await.map(Some)
+    }
+
+    async fn symlink(&self, target: &Path, link: &Path) -> io::Result<()> {
+        #[cfg(unix)]
+        {
+            fs::symlink(target, link).await
+        }
+
+        // Windows has separate links for files and directories
+        #[cfg(windows)]
+        {
+            if fs::metadata(target).await.is_ok_and(|m| m.is_dir()) {
+                fs::symlink_dir(target, link).await
+            } else {
+                fs::symlink_file(target, link).await
+            }
+        }
+
+        #[cfg(not(any(unix, windows)))]
+        {
+            let _ = (target, link);
+            Err(io::Error::from(io::ErrorKind::Unsupported))
+        }
+    }
+
+    async fn list_files(&self, root: &Path) -> io::Result<Vec<PathBuf>> {
+        let root = root.to_path_buf();
+        task::spawn_blocking(move || {
+            let mut files = Vec::new();
+            for entry in walkdir::WalkDir::new(root).sort_by_file_name() {
+                let entry = entry.map_err(io::Error::other)?;
+                if entry.file_type().is_file() || entry.path_is_symlink() {
+                    files.push(entry.into_path());
+                }
+            }
+            Ok(files)
+        })
+        .await?
+    }
+}
+
+/// The real filesystem with copies held to a byte rate; everything else is
+/// [`RealFs`]. Renames on one filesystem move no data and are not throttled.
+#[derive(Debug, Clone)]
+pub struct ThrottledFs {
+    throttle: Arc<Throttle>,
+}
+
+impl ThrottledFs {
+    pub fn new(bytes_per_sec: u64) -> Self {
+        Self { throttle: Arc::new(Throttle::new(bytes_per_sec)) }
+    }
+}
+
+#[async_trait]
+impl FileSystem for ThrottledFs {
+    async fn metadata(&self, path: &Path) -> io::Result<FsMetadata> {
+        RealFs.metadata(path).await
+    }
+
+    async fn exists(&self, path: &Path) -> io::Result<bool> {
+        RealFs.exists(path).await
+    }
+
+    async fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        RealFs.read(path).await
+    }
+
+    async fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        RealFs.write(path, contents).await
+    }
+
+    async fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        RealFs.rename(from, to).await
+    }
+
+    /// Chunked copy, waiting on the throttle before each chunk, keeping the
+    /// download quarantine marker of `from`
+    async fn copy(&self, from: &Path, to: &Path) -> io::Result<()> {
+        let (from, to) = (extended_length(from), extended_length(to));
+        let marker = quarantine::read(&from);
+        throttled_copy(&from, &to, &self.throttle).await?;
+        if let Some(marker) = marker {
+            quarantine::write(&to, &marker)?;
+        }
+        Ok(())
+    }
+
+    async fn copy_metadata(&self, from: &Path, to: &Path, preserve: Preserve) -> io::Result<()> {
+        RealFs.copy_metadata(from, to, preserve).await
+    }
+
+    async fn remove_file(&self, path: &Path) -> io::Result<()> {
+        RealFs.remove_file(path).await
+    }
+
+    async fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        RealFs.create_dir_all(path).await
+    }
+
+    async fn read_link(&self, path: &Path) -> io::Result<Option<PathBuf>> {
+        RealFs.read_link(path).await
+    }
+
+    async fn symlink(&self, target: &Path, link: &Path) -> io::Result<()> {
+        RealFs.symlink(target, link).await
+    }
+
+    async fn list_files(&self, root: &Path) -> io::Result<Vec<PathBuf>> {
+        RealFs.list_files(root).await
+    }
+}
+
+async fn throttled_copy(src: &Path, dest: &Path, throttle: &Throttle) -> io::Result<()> {
+    let mut src_file = fs::File::open(src).await?;
+    let mut dest_file = fs::File::create(dest).await?;
+    let mut buf = vec![0u8; throttle::CHUNK];
+    loop {
+        let n = src_file.read(&mut buf).await?;
+        if n == 0 {
+            break;
+        }
+        throttle.take(n as u64).await;
+        dest_file.write_all(&buf[..n]).await?;
+    }
+    dest_file.flush().await?;
+
+    let metadata = fs::metadata(src).await?;
+    fs::set_permissions(dest, metadata.permissions()).await?;
+    Ok(())
+}
+
+async fn copy_contents(src: &Path, dest: &Path) -> io::Result<()> {
+    #[cfg(target_os = "linux")]
+    {
+        if let Err(e) = copy_file_unix(src, dest).await {
+            tracing::debug!(error = ?e, "sendfile failed, falling back to buffered copy");
+            buffered_copy(src, dest).await
+        } else {
+            Ok(())
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        // macOS doesn’t support rustix::sendfile
+        buffered_copy(src, dest).await
+    }
+
+    #[cfg(windows)]
+    {
+        if let Err(e) = copy_file_windows(src, dest).await {
+            tracing::debug!(error = ?e, "CopyFileExW failed, falling back to buffered copy");
+            buffered_copy(src, dest).await
+        } else {
+            Ok(())
+        }
+    }
+
+    // Fallback for other platforms (e.g., WASM, embedded)
+    #[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+    {
+        buffered_copy(src, dest).await
+    }
+}
+
+fn copy_times(from: &Path, to: &Path) -> io::Result<()> {
+    let metadata = std::fs::metadata(from)?;
+    let mut times = std::fs::FileTimes::new().set_modified(metadata.modified()?);
+    if let Ok(accessed) = metadata.accessed() {
+        times = times.set_accessed(accessed);
+    }
+    std::fs::File::options().write(true).open(to)?.set_times(times)
+}
+
+/// Buffered async copy fallback
+async fn buffered_copy(src: &Path, dest: &Path) -> io::Result<()> {
+    let mut src_file = fs::File::open(src).await?;
+    let mut dest_file = fs::File::create(dest).await?;
+    tokio::io::copy(&mut src_file, &mut dest_file).await?;
+    dest_file.flush().await?;
+
+    let metadata = fs::metadata(src).await?;
+    fs::set_permissions(dest, metadata.permissions()).await?;
+    Ok(())
+}
+
+// ----------- Platform-specific fast paths -----------
+
+/// Unix: use rustix::fs::sendfile
+#[cfg(target_os = "linux")]
+async fn copy_file_unix(src: &Path, dest: &Path) -> io::Result<()> {
+    use std::os::fd::AsFd;
+    use rustix::fs::sendfile;
+
+    // Take ownership of paths (so they can move into the 'static closure)
+    let src = src.to_path_buf();
+    let dest = dest.to_path_buf();
+
+    let src_file = fs::File::open(&src).await?;
+    let dest_file = fs::File::create(&dest).await?;
+
+    let std_src = src_file.into_std().await;
+    let std_dest = dest_file.into_std().await;
+
+    task::spawn_blocking(move || {
+        let len = std::fs::metadata(&src)?.len();
+        let mut offset: u64 = 0;
+        let mut remaining = len;
+
+        while remaining > 0 {
+            let written = sendfile(
+                std_dest.as_fd(),
+                std_src.as_fd(),
+                Some(&mut offset),
+                remaining as usize,
+            )?;
+            if written == 0 {
+                if remaining > 0 {
+                    return Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "sendfile returned 0 before copying all data"
+                    ));
+                }
+                break;
+            }
+            remaining -= written as u64;
+        }
+        Ok(())
+    })
+    .await? // join error propagation
+}
+
+/// Windows: use CopyFileExW
+#[cfg(windows)]
+async fn copy_file_windows(src: &Path, dest: &Path) -> io::Result<()> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Storage::FileSystem::CopyFileExW;
+
+    let src_w: Vec<u16> = src.as_os_str().encode_wide().chain(Some(0)).collect();
+    let dest_w: Vec<u16> = dest.as_os_str().encode_wide().chain(Some(0)).collect();
+
+    task::spawn_blocking(move || unsafe {
+        let success = CopyFileExW(
+            src_w.as_ptr(),
+            dest_w.as_ptr(),
+            None,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            0,
+        );
+        if success == 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(())
+        }
+    })
+    .await??;
+
+    tracing::debug!(?src, ?dest, "Copied with CopyFileExW");
+    Ok(())
+}
+
+/// A deterministic filesystem held in memory. Paths are used as given (no
+/// normalization), parent directories must exist before a file is written,
+/// and any path can be marked as its own device or made to fail.
+#[derive(Debug, Default)]
+pub struct MemoryFs {
+    state: Mutex<MemoryState>,
+}
+
+#[derive(Debug, Default)]
+struct MemoryState {
+    files: BTreeMap<PathBuf, MemoryFile>,
+    dirs: BTreeSet<PathBuf>,
+    /// Symbolic links and their targets, as written
+    links: BTreeMap<PathBuf, PathBuf>,
+    /// Roots of simulated mount points
+    devices: Vec<PathBuf>,
+    /// Paths whose writes, renames and removals fail
+    failing: HashSet<PathBuf>,
+    /// Seconds after the epoch given to the next write, so every write has
+    /// a distinct, predictable mtime
+    clock: u64,
+}
+
+#[derive(Debug, Clone)]
+struct MemoryFile {
+    contents: Vec<u8>,
+    modified: SystemTime,
+}
+
+impl MemoryFs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a file (and its parent directories) without going through the trait
+    pub fn insert(&self, path: impl Into<PathBuf>, contents: impl Into<Vec<u8>>) {
+        let path = path.into();
+        let mut state = self.lock();
+        if let Some(parent) = path.parent() {
+            state.add_dirs(parent);
+        }
+        let modified = state.tick();
+        state.files.insert(path, MemoryFile { contents: contents.into(), modified });
+    }
+
+    /// Add a symbolic link (and its parent directories) pointing to `target`
+    pub fn insert_link(&self, link: impl Into<PathBuf>, target: impl Into<PathBuf>) {
+        let link = link.into();
+        let mut state = self.lock();
+        if let Some(parent) = link.parent() {
+            state.add_dirs(parent);
+        }
+        state.links.insert(link, target.into());
+    }
+
+    /// Treat everything below `root` as a separate device, so renames in or
+    /// out of it fail like a move across drives
+    pub fn mount(&self, root: impl Into<PathBuf>) {
+        let root = root.into();
+        let mut state = self.lock();
+        state.add_dirs(&root);
+        state.devices.push(root);
+    }
+
+    /// Make every write, rename or removal touching `path` fail
+    pub fn fail_on(&self, path: impl Into<PathBuf>) {
+        self.lock().failing.insert(path.into());
+    }
+
+    /// Contents of a file, if there is one at `path` (not following links)
+    pub fn contents(&self, path: &Path) -> Option<Vec<u8>> {
+        self.lock().files.get(path).map(|f| f.contents.clone())
+    }
+
+    /// Every file and link path, sorted
+    pub fn paths(&self) -> Vec<PathBuf> {
+        let state = self.lock();
+        let mut paths: Vec<_> = state.files.keys().chain(state.links.keys()).cloned().collect();
+        paths.sort();
+        paths
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, MemoryState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl MemoryState {
+    fn tick(&mut self) -> SystemTime {
+        self.clock += 1;
+        SystemTime::UNIX_EPOCH + Duration::from_secs(self.clock)
+    }
+
+    fn add_dirs(&mut self, dir: &Path) {
+        for ancestor in dir.ancestors().filter(|a| !a.as_os_str().is_empty()) {
+            self.dirs.insert(ancestor.to_path_buf());
+        }
+    }
+
+    fn device(&self, path: &Path) -> Option<&Path> {
+        self.devices
+            .iter()
+            .filter(|root| path.starts_with(root))
+            .max_by_key(|root| root.components().count())
+            .map(PathBuf::as_path)
+    }
+
+    fn check_writable(&self, path: &Path) -> io::Result<()> {
+        if self.failing.contains(path) {
+            return Err(io::Error::new(io::ErrorKind::PermissionDenied, format!("{} is not writable", path.display())));
+        }
+        match path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            Some(parent) if !self.dirs.contains(parent) => Err(not_found(parent)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Where `path` leads: the target of a link (one level), or itself
+    fn resolve(&self, path: &Path) -> PathBuf {
+        match self.links.get(path) {
+            Some(target) => path.parent().unwrap_or(Path::new("")).join(target),
+            None => path.to_path_buf(),
+        }
+    }
+
+    /// The file at `path`, following a link
+    fn file(&self, path: &Path) -> io::Result<&MemoryFile> {
+        let path = self.resolve(path);
+        self.files.get(&path).ok_or_else(|| not_found(&path))
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.files.contains_key(path) || self.dirs.contains(path) || self.links.contains_key(path)
+    }
+}
+
+fn not_found(path: &Path) -> io::Error {
+    io::Error::new(io::ErrorKind::NotFound, format!("{} does not exist", path.display()))
+}
+
+#[async_trait]
+impl FileSystem for MemoryFs {
+    async fn metadata(&self, path: &Path) -> io::Result<FsMetadata> {
+        let state = self.lock();
+        if state.dirs.contains(path) {
+            return Ok(FsMetadata { len: 0, is_dir: true, modified: None });
+        }
+        let file = state.file(path)?;
+        Ok(FsMetadata { len: file.contents.len() as u64, is_dir: false, modified: Some(file.modified) })
+    }
+
+    async fn exists(&self, path: &Path) -> io::Result<bool> {
+        Ok(self.lock().exists(path))
+    }
+
+    async fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        Ok(self.lock().file(path)?.contents.clone())
+    }
+
+    async fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        let mut state = self.lock();
+        state.check_writable(path)?;
+        let modified = state.tick();
+        state.files.insert(path.to_path_buf(), MemoryFile { contents: contents.to_vec(), modified });
+        Ok(())
+    }
+
+    async fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        let mut state = self.lock();
+        if !state.files.contains_key(from) && !state.links.contains_key(from) {
+            return Err(not_found(from));
+        }
+        if state.device(from) != state.device(to) {
+            return Err(io::Error::from(io::ErrorKind::CrossesDevices));
+        }
+        state.check_writable(from)?;
+        state.check_writable(to)?;
+        // Like rename(2), this moves a link itself, not what it points to
+        if let Some(target) = state.links.remove(from) {
+            state.links.insert(to.to_path_buf(), target);
+        } else if let Some(file) = state.files.remove(from) {
+            state.files.insert(to.to_path_buf(), file);
+        }
+        Ok(())
+    }
+
+    async fn copy(&self, from: &Path, to: &Path) -> io::Result<()> {
+        let mut state = self.lock();
+        let contents = state.file(from)?.contents.clone();
+        state.check_writable(to)?;
+        let modified = state.tick();
+        state.files.insert(to.to_path_buf(), MemoryFile { contents, modified });
+        Ok(())
+    }
+
+    /// Only timestamps exist here
+    async fn copy_metadata(&self, from: &Path, to: &Path, preserve: Preserve) -> io::Result<()> {
+        let mut state = self.lock();
+        let modified = state.file(from)?.modified;
+        state.check_writable(to)?;
+        let to = state.resolve(to);
+        let file = state.files.get_mut(&to).ok_or_else(|| not_found(&to))?;
+        if preserve.timestamps {
+            file.modified = modified;
+        }
+        Ok(())
+    }
+
+    async fn remove_file(&self, path: &Path) -> io::Result<()> {
+        let mut state = self.lock();
+        state.check_writable(path)?;
+        if state.links.remove(path).is_some() {
+            return Ok(());
+        }
+        state.files.remove(path).map(drop).ok_or_else(|| not_found(path))
+    }
+
+    async fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        let mut state = self.lock();
+        if state.files.contains_key(path) {
+            return Err(io::Error::new(io::ErrorKind::AlreadyExists, format!("{} is a file", path.display())));
+        }
+        state.add_dirs(path);
+        Ok(())
+    }
+
+    async fn read_link(&self, path: &Path) -> io::Result<Option<PathBuf>> {
+        let state = self.lock();
+        if !state.exists(path) {
+            return Err(not_found(path));
+        }
+        Ok(state.links.get(path).cloned())
+    }
+
+    async fn symlink(&self, target: &Path, link: &Path) -> io::Result<()> {
+        let mut state = self.lock();
+        state.check_writable(link)?;
+        if state.exists(link) {
+            return Err(io::Error::new(io::ErrorKind::AlreadyExists, format!("{} exists", link.display())));
+        }
+        state.links.insert(link.to_path_buf(), target.to_path_buf());
+        Ok(())
+    }
+
+    async fn list_files(&self, root: &Path) -> io::Result<Vec<PathBuf>> {
+        Ok(self.paths().into_iter().filter(|p| p.starts_with(root)).collect())
+    }
+}
@@ -0,0 +1,106 @@
+//! Permissions given to organized files per category, for shared servers
+//! where the umask alone isn't enough: e.g. documents `0640`, executables
+//! `0750` owned by a group. What a file had before is kept in the index so
+//! `stash revert` can put it back. Unix only; elsewhere nothing is changed.
+
+use std::{io, path::Path};
+
+/// Mode and group for files of one category; unset parts are left alone
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DestPermissions {
+    /// Permission bits, e.g. `0o640`
+    pub mode: Option<u32>,
+    pub gid: Option<u32>,
+}
+
+/// Mode and group a file had before [`DestPermissions::apply`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OriginalPermissions {
+    pub mode: u32,
+    pub gid: u32,
+}
+
+impl DestPermissions {
+    pub fn is_empty(&self) -> bool {
+        self.mode.is_none() && self.gid.is_none()
+    }
+
+    /// Give `path` this mode and group. Returns what it had before, or
+    /// `None` when nothing changed.
+    pub fn apply(&self, path: &Path) -> io::Result<Option<OriginalPermissions>> {
+        platform::apply(self, path)
+    }
+}
+
+/// Put back the mode and group recorded by [`DestPermissions::apply`]
+pub fn restore(path: &Path, original: &OriginalPermissions) -> io::Result<()> {
+    platform::restore(path, original)
+}
+
+/// Parse an octal mode such as `640` or `0o2775`
+pub fn parse_mode(input: &str) -> Result<u32, String> {
+    let digits = input.trim();
+    let digits = digits.strip_prefix("0o").unwrap_or(digits);
+    match u32::from_str_radix(digits, 8) {
+        Ok(mode) if !digits.is_empty() && mode <= 0o7777 => Ok(mode),
+        _ => Err(format!("invalid mode `{}` (expected octal, e.g. 0640)", input)),
+    }
+}
+
+#[cfg(unix)]
+mod platform {
+    use std::{
+        fs::{self, Permissions},
+        io,
+        os::unix::fs::{MetadataExt, PermissionsExt},
+        path::Path,
+    };
+
+    use super::{DestPermissions, OriginalPermissions};
+
+    pub fn apply(wanted: &DestPermissions, path: &Path) -> io::Result<Option<OriginalPermissions>> {
+        let metadata = fs::symlink_metadata(path)?;
+        // A link's own mode means nothing; the file it points at is not ours
+        if metadata.file_type().is_symlink() {
+            return Ok(None);
+        }
+        let original = OriginalPermissions { mode: metadata.mode() & 0o7777, gid: metadata.gid() };
+        let gid = wanted.gid.filter(|&gid| gid != original.gid);
+        let mode = wanted.mode.filter(|&mode| mode != original.mode);
+        if gid.is_none() && mode.is_none() {
+            return Ok(None);
+        }
+
+        // Group first: chown clears setgid bits the mode may ask for
+        if let Some(gid) = gid {
+            std::os::unix::fs::lchown(path, None, Some(gid))?;
+        }
+        if let Some(mode) = wanted.mode {
+            fs::set_permissions(path, Permissions::from_mode(mode))?;
+        }
+        Ok(Some(original))
+    }
+
+    pub fn restore(path: &Path, original: &OriginalPermissions) -> io::Result<()> {
+        let metadata = fs::symlink_metadata(path)?;
+        if metadata.gid() != original.gid {
+            std::os::unix::fs::lchown(path, None, Some(original.gid))?;
+        }
+        fs::set_permissions(path, Permissions::from_mode(original.mode))
+    }
+}
+
+#[cfg(not(unix))]
+mod platform {
+    use std::{io, path::Path};
+
+    use super::{DestPermissions, OriginalPermissions};
+
+    pub fn apply(_wanted: &DestPermissions, _path: &Path) -> io::Result<Option<OriginalPermissions>> {
+        Ok(None)
+    }
+
+    pub fn restore(_path: &Path, _original: &OriginalPermissions) -> io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,122 @@
+//! The marker an OS attaches to downloaded files so they are treated with
+//! suspicion: the `Zone.Identifier` stream on Windows and the
+//! `com.apple.quarantine` attribute on macOS. Other platforms have none.
+//!
+//! A rename keeps the marker. The copy fallback used across devices carries it
+//! over explicitly, so organizing never silently makes a download trusted.
+
+use std::{io, path::Path};
+
+/// Name of the NTFS stream holding the Windows mark of the web
+pub const ZONE_IDENTIFIER: &str = "Zone.Identifier";
+/// Extended attribute holding the macOS quarantine flag
+pub const QUARANTINE_XATTR: &str = "com.apple.quarantine";
+
+/// Raw marker of `path`, if it has one
+pub fn read(path: &Path) -> Option<Vec<u8>> {
+    platform::read(path)
+}
+
+/// Attach `marker` (as returned by [`read`]) to `path`
+pub fn write(path: &Path, marker: &[u8]) -> io::Result<()> {
+    platform::write(path, marker)
+}
+
+/// Remove the marker from `path`. Returns whether there was one.
+pub fn strip(path: &Path) -> io::Result<bool> {
+    platform::strip(path)
+}
+
+#[cfg(windows)]
+mod platform {
+    use std::{ffi::OsString, fs, io, path::Path};
+
+    use super::ZONE_IDENTIFIER;
+
+    fn stream(path: &Path) -> OsString {
+        let mut stream = path.as_os_str().to_os_string();
+        stream.push(":");
+        stream.push(ZONE_IDENTIFIER);
+        stream
+    }
+
+    pub fn read(path: &Path) -> Option<Vec<u8>> {
+        fs::read(stream(path)).ok()
+    }
+
+    pub fn write(path: &Path, marker: &[u8]) -> io::Result<()> {
+        fs::write(stream(path), marker)
+    }
+
+    pub fn strip(path: &Path) -> io::Result<bool> {
+        match fs::remove_file(stream(path)) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::{ffi::CString, io, os::unix::ffi::OsStrExt, path::Path};
+
+    use super::QUARANTINE_XATTR;
+
+    fn c_strings(path: &Path) -> io::Result<(CString, CString)> {
+        let c_path = CString::new(path.as_os_str().as_bytes())?;
+        let c_name = CString::new(QUARANTINE_XATTR)?;
+        Ok((c_path, c_name))
+    }
+
+    pub fn read(path: &Path) -> Option<Vec<u8>> {
+        let (c_path, c_name) = c_strings(path).ok()?;
+        let mut buf = vec![0u8; 1024];
+        // SAFETY: both strings are NUL-terminated and `buf` is valid for `buf.len()` bytes
+        let len = unsafe {
+            libc::getxattr(c_path.as_ptr(), c_name.as_ptr(), buf.as_mut_ptr().cast(), buf.len(), 0, 0)
+        };
+        if len < 0 {
+            return None;
+        }
+        buf.truncate(len as usize);
+        Some(buf)
+    }
+
+    pub fn write(path: &Path, marker: &[u8]) -> io::Result<()> {
+        let (c_path, c_name) = c_strings(path)?;
+        // SAFETY: both strings are NUL-terminated and `marker` is valid for its length
+        let rc = unsafe {
+            libc::setxattr(c_path.as_ptr(), c_name.as_ptr(), marker.as_ptr().cast(), marker.len(), 0, 0)
+        };
+        if rc < 0 { Err(io::Error::last_os_error()) } else { Ok(()) }
+    }
+
+    pub fn strip(path: &Path) -> io::Result<bool> {
+        let (c_path, c_name) = c_strings(path)?;
+        // SAFETY: both strings are NUL-terminated
+        let rc = unsafe { libc::removexattr(c_path.as_ptr(), c_name.as_ptr(), 0) };
+        if rc == 0 {
+            return Ok(true);
+        }
+        let e = io::Error::last_os_error();
+        if e.raw_os_error() == Some(libc::ENOATTR) { Ok(false) } else { Err(e) }
+    }
+}
+
+#[cfg(not(any(windows, target_os = "macos")))]
+mod platform {
+    use std::{io, path::Path};
+
+    pub fn read(_path: &Path) -> Option<Vec<u8>> {
+        None
+    }
+
+    pub fn write(_path: &Path, _marker: &[u8]) -> io::Result<()> {
+        Ok(())
+    }
+
+    pub fn strip(_path: &Path) -> io::Result<bool> {
+        Ok(false)
+    }
+}
@@ -0,0 +1,222 @@
+use std::{
+    collections::HashMap,
+    fmt, io,
+    path::{Path, PathBuf},
+};
+
+/// Identifies the filesystem (Unix device, Windows volume) a path lives on
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FsId(platform::Id);
+
+/// A destination filesystem without room for what is about to be copied onto it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Shortfall {
+    /// First destination planned on that filesystem
+    pub dest: PathBuf,
+    pub needed: u64,
+    pub available: u64,
+}
+
+/// Nearest ancestor of `path` (or `path` itself) that exists, so a
+/// destination can be checked before it is created
+fn existing_ancestor(path: &Path) -> &Path {
+    path.ancestors().find(|p| p.exists()).unwrap_or(path)
+}
+
+/// Filesystem holding `path`, or the nearest existing ancestor of it
+pub fn filesystem_id(path: &Path) -> io::Result<FsId> {
+    platform::id(existing_ancestor(path)).map(FsId)
+}
+
+/// Bytes available to the current user on the filesystem holding `path`
+pub fn available_space(path: &Path) -> io::Result<u64> {
+    platform::available(existing_ancestor(path))
+}
+
+/// Why files can't be organized from or into a filesystem
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unusable {
+    ReadOnly,
+    /// A kernel filesystem such as `proc` or `sysfs`, by type name
+    Pseudo(&'static str),
+}
+
+impl fmt::Display for Unusable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Unusable::ReadOnly => write!(f, "is on a read-only filesystem"),
+            Unusable::Pseudo(name) => write!(f, "is on a `{}` pseudo-filesystem", name),
+        }
+    }
+}
+
+/// Whether the filesystem holding `path`, or the nearest existing ancestor
+/// of it, is mounted read-only or is a pseudo-filesystem. Unix only;
+/// elsewhere every filesystem counts as usable.
+pub fn unusable(path: &Path) -> io::Result<Option<Unusable>> {
+    platform::unusable(existing_ancestor(path))
+}
+
+/// Sum what each planned `(source dir, destination dir, bytes)` transfer
+/// copies across filesystems, per destination filesystem, and report those
+/// without enough free space. Moves within one filesystem are renames and
+/// need no space.
+pub fn check<'a>(transfers: impl IntoIterator<Item = (&'a Path, &'a Path, u64)>) -> io::Result<Vec<Shortfall>> {
+    let mut needed: HashMap<FsId, (PathBuf, u64)> = HashMap::new();
+    for (src, dest, bytes) in transfers {
+        let dest_fs = filesystem_id(dest)?;
+        if bytes == 0 || filesystem_id(src)? == dest_fs {
+            continue;
+        }
+        needed.entry(dest_fs).or_insert_with(|| (dest.to_path_buf(), 0)).1 += bytes;
+    }
+
+    let mut shortfalls = Vec::new();
+    for (dest, needed) in needed.into_values() {
+        let available = available_space(&dest)?;
+        if needed > available {
+            shortfalls.push(Shortfall { dest, needed, available });
+        }
+    }
+    shortfalls.sort_by(|a, b| a.dest.cmp(&b.dest));
+    Ok(shortfalls)
+}
+
+#[cfg(unix)]
+mod platform {
+    use std::{ffi::CString, io, os::unix::{ffi::OsStrExt, fs::MetadataExt}, path::Path};
+
+    use super::Unusable;
+
+    pub type Id = u64;
+
+    pub fn id(path: &Path) -> io::Result<Id> {
+        Ok(std::fs::metadata(path)?.dev())
+    }
+
+    fn statvfs(path: &Path) -> io::Result<libc::statvfs> {
+        let c_path = CString::new(path.as_os_str().as_bytes())?;
+        let mut stat = std::mem::MaybeUninit::<libc::statvfs>::uninit();
+        // SAFETY: `c_path` is NUL-terminated and `stat` is valid for writes
+        if unsafe { libc::statvfs(c_path.as_ptr(), stat.as_mut_ptr()) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY: statvfs succeeded, so it filled `stat`
+        Ok(unsafe { stat.assume_init() })
+    }
+
+    pub fn available(path: &Path) -> io::Result<u64> {
+        let stat = statvfs(path)?;
+        #[allow(clippy::useless_conversion)]
+        Ok(u64::from(stat.f_bavail) * u64::from(stat.f_frsize))
+    }
+
+    pub fn unusable(path: &Path) -> io::Result<Option<Unusable>> {
+        if let Some(name) = pseudo_fs(path)? {
+            return Ok(Some(Unusable::Pseudo(name)));
+        }
+        Ok((statvfs(path)?.f_flag & libc::ST_RDONLY != 0).then_some(Unusable::ReadOnly))
+    }
+
+    /// Kernel filesystems that only look like files, by `statfs` magic
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    const PSEUDO: [(libc::__fsword_t, &str); 9] = [
+        (libc::PROC_SUPER_MAGIC as _, "proc"),
+        (libc::SYSFS_MAGIC as _, "sysfs"),
+        (libc::DEVPTS_SUPER_MAGIC as _, "devpts"),
+        (libc::DEBUGFS_MAGIC as _, "debugfs"),
+        (libc::TRACEFS_MAGIC as _, "tracefs"),
+        (libc::SECURITYFS_MAGIC as _, "securityfs"),
+        (libc::CGROUP_SUPER_MAGIC as _, "cgroup"),
+        (libc::CGROUP2_SUPER_MAGIC as _, "cgroup2"),
+        (libc::BPF_FS_MAGIC as _, "bpf"),
+    ];
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn pseudo_fs(path: &Path) -> io::Result<Option<&'static str>> {
+        let c_path = CString::new(path.as_os_str().as_bytes())?;
+        let mut stat = std::mem::MaybeUninit::<libc::statfs>::uninit();
+        // SAFETY: `c_path` is NUL-terminated and `stat` is valid for writes
+        if unsafe { libc::statfs(c_path.as_ptr(), stat.as_mut_ptr()) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY: statfs succeeded, so it filled `stat`
+        let stat = unsafe { stat.assume_init() };
+        Ok(PSEUDO.iter().find(|(magic, _)| *magic == stat.f_type).map(|(_, name)| *name))
+    }
+
+    #[cfg(target_os = "macos")]
+    fn pseudo_fs(path: &Path) -> io::Result<Option<&'static str>> {
+        let c_path = CString::new(path.as_os_str().as_bytes())?;
+        let mut stat = std::mem::MaybeUninit::<libc::statfs>::uninit();
+        // SAFETY: `c_path` is NUL-terminated and `stat` is valid for writes
+        if unsafe { libc::statfs(c_path.as_ptr(), stat.as_mut_ptr()) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY: statfs succeeded, so it filled `stat`
+        let stat = unsafe { stat.assume_init() };
+        // SAFETY: the kernel NUL-terminates the type name
+        let name = unsafe { std::ffi::CStr::from_ptr(stat.f_fstypename.as_ptr()) };
+        Ok((name.to_bytes() == b"devfs").then_some("devfs"))
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "android", target_os = "macos")))]
+    fn pseudo_fs(_path: &Path) -> io::Result<Option<&'static str>> {
+        Ok(None)
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use std::{
+        ffi::OsString,
+        io,
+        os::windows::ffi::OsStrExt,
+        path::{Component, Path},
+    };
+    use windows_sys::Win32::Storage::FileSystem::GetDiskFreeSpaceExW;
+
+    /// Drive letter or UNC share
+    pub type Id = OsString;
+
+    pub fn id(path: &Path) -> io::Result<Id> {
+        let path = std::path::absolute(path)?;
+        match path.components().next() {
+            Some(Component::Prefix(prefix)) => Ok(prefix.as_os_str().to_ascii_uppercase()),
+            _ => Err(io::Error::new(io::ErrorKind::InvalidInput, "path has no volume")),
+        }
+    }
+
+    pub fn available(path: &Path) -> io::Result<u64> {
+        let wide: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+        let mut free: u64 = 0;
+        // SAFETY: `wide` is NUL-terminated; the out-pointer is valid and the others may be null
+        let ok = unsafe {
+            GetDiskFreeSpaceExW(wide.as_ptr(), &mut free, std::ptr::null_mut(), std::ptr::null_mut())
+        };
+        if ok == 0 { Err(io::Error::last_os_error()) } else { Ok(free) }
+    }
+
+    pub fn unusable(_path: &Path) -> io::Result<Option<super::Unusable>> {
+        Ok(None)
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+mod platform {
+    use std::{io, path::Path};
+
+    pub type Id = ();
+
+    pub fn id(_path: &Path) -> io::Result<Id> {
+        Ok(())
+    }
+
+    pub fn available(_path: &Path) -> io::Result<u64> {
+        Ok(u64::MAX)
+    }
+
+    pub fn unusable(_path: &Path) -> io::Result<Option<super::Unusable>> {
+        Ok(None)
+    }
+}
@@ -1,9 +1,10 @@
 use std::{
+    collections::{BTreeMap, HashMap},
     path::PathBuf,
     time::{Duration, Instant},
 };
 
-use serde::{Serialize, Serializer};
+use serde::{Deserialize, Serialize, Serializer};
 use colored::*;
 
 use crate::errors::{FileOrganizerError, SkipReason};
@@ -20,6 +21,10 @@ pub enum FileOutcome {
     /// File was skipped with a reason
     Skipped { src: PathBuf, reason: SkipReason, size: u64 },
 
+    /// File was a copy of `existing`, organized before, and was deleted or,
+    /// with `trashed` set, moved to the trash instead of organized again
+    DuplicateSkipped { src: PathBuf, existing: PathBuf, trashed: Option<PathBuf>, size: u64 },
+
     /// File failed due to an error
     Err(FileErrorReport),
 }
@@ -30,6 +35,11 @@ pub struct FileReport {
     pub dest: PathBuf,
     pub action: MoveAction,
     pub size: u64,
+    /// Top-level category, e.g. `Images`
+    pub category: &'static str,
+    /// Names the device the file was copied onto from another filesystem;
+    /// `None` when the move was a rename
+    pub device: Option<PathBuf>,
 }
 
 #[derive(Debug)]
@@ -46,7 +56,7 @@ pub struct FileErrorReport {
     pub error: FileOrganizerError,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
 pub enum Stage {
     Scan,
     Classify,
@@ -105,7 +115,7 @@ impl SkipStats {
 
 #[derive(Debug, Default, Clone, Serialize)]
 pub struct StageStats {
-    timings: [Option<StageTiming>; Stage::VARIANTS.len()],
+    pub timings: [Option<StageTiming>; Stage::VARIANTS.len()],
 }
 
 impl StageStats {
@@ -116,11 +126,114 @@ impl StageStats {
     pub fn get(&self, stage: Stage) -> Option<&StageTiming> {
         self.timings[stage.as_index()].as_ref()
     }
+
+    /// Accumulate time spent in `stage` across `files` files
+    pub fn add(&mut self, stage: Stage, duration: Duration, files: usize) {
+        let entry = self.timings[stage.as_index()].get_or_insert_with(Default::default);
+        entry.duration += duration;
+        entry.files += files;
+    }
+
+    /// Merge another set of timings into this one
+    pub fn merge(&mut self, other: &StageStats) {
+        for (i, timing) in other.timings.iter().enumerate() {
+            if let Some(timing) = timing {
+                self.add(Stage::VARIANTS[i], timing.duration, timing.files);
+            }
+        }
+    }
+}
+
+
+/// Example paths kept per group of errors
+const ERROR_EXAMPLES: usize = 3;
+
+/// Files that failed at the same stage with the same kind of error
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ErrorGroup {
+    pub stage: Stage,
+    /// See [`FileOrganizerError::kind`]
+    pub kind: &'static str,
+    pub count: usize,
+    /// The first few files that failed this way
+    pub examples: Vec<PathBuf>,
+}
+
+/// Files and bytes organized into one category
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct CategoryTotal {
+    pub files: usize,
+    pub bytes: u64,
+}
+
+/// How a category is shown in summaries; unset fields keep the built-in look
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct CategoryStyle {
+    /// Color name understood by the terminal, e.g. `magenta` or `bright blue`
+    pub color: Option<String>,
+    pub icon: Option<String>,
+    /// Label shown instead of the category name
+    pub name: Option<String>,
+}
+
+/// Built-in icon and color per top-level category
+fn builtin_style(category: &str) -> (&'static str, Color) {
+    match category {
+        "Documents" => ("📄", Color::Blue),
+        "Images" => ("🖼️", Color::Magenta),
+        "Videos" => ("🎬", Color::Red),
+        "Audio" => ("🎵", Color::Cyan),
+        "Archives" => ("📦", Color::Yellow),
+        "Executables" => ("⚙️", Color::BrightRed),
+        "Code" => ("💻", Color::Green),
+        "Fonts" => ("🔤", Color::BrightMagenta),
+        "Design" => ("🎨", Color::BrightCyan),
+        "Backups" => ("🗄️", Color::BrightBlue),
+        _ => ("📁", Color::White),
+    }
+}
+
+/// Per-category presentation used by the summary's category legend
+#[derive(Debug, Clone, Default)]
+pub struct CategoryLegend {
+    overrides: HashMap<String, CategoryStyle>,
 }
 
+impl CategoryLegend {
+    /// Override how `category` (a top-level name such as `Images`) is shown
+    pub fn with_style(mut self, category: &str, style: CategoryStyle) -> Self {
+        self.overrides.insert(category.to_string(), style);
+        self
+    }
+
+    fn style(&self, category: &str) -> Option<&CategoryStyle> {
+        self.overrides.get(category)
+    }
+
+    pub fn icon<'a>(&'a self, category: &'a str) -> &'a str {
+        self.style(category)
+            .and_then(|s| s.icon.as_deref())
+            .unwrap_or(builtin_style(category).0)
+    }
+
+    pub fn color(&self, category: &str) -> Color {
+        self.style(category)
+            .and_then(|s| s.color.as_deref())
+            .and_then(|c| c.parse().ok())
+            .unwrap_or(builtin_style(category).1)
+    }
+
+    pub fn name<'a>(&'a self, category: &'a str) -> &'a str {
+        self.style(category)
+            .and_then(|s| s.name.as_deref())
+            .unwrap_or(category)
+    }
+}
 
 #[derive(Debug, Default, Serialize)]
 pub struct Summary {
+    /// Id of the run, also on its `run` tracing span, to find its log lines
+    pub run_id: String,
     pub discovered: usize,
     pub processed: usize, 
     pub moved: usize,
@@ -129,20 +242,48 @@ pub struct Summary {
     pub bytes_moved: u64,
     pub bytes_renamed: u64,
     pub bytes_skipped: u64,
+    /// Copies of files organized before, removed instead of organized
+    pub duplicates: usize,
+    pub bytes_deduplicated: u64,
+
+    /// The run was cancelled before every file was handled
+    pub cancelled: bool,
+    /// Files left untouched because of the cancellation
+    pub not_started: usize,
 
     pub skip_counts: [usize; SkipReason::VARIANTS.len()],
     pub skip_bytes: [u64; SkipReason::VARIANTS.len()],
+
+    /// Moved and renamed files per top-level category
+    pub categories: BTreeMap<&'static str, CategoryTotal>,
+    /// Moved and renamed files copied from another filesystem, per
+    /// destination device (see [`FileReport::device`])
+    pub transfers: BTreeMap<PathBuf, CategoryTotal>,
+    /// Presentation of the category breakdown
+    #[serde(skip)]
+    pub legend: CategoryLegend,
     
     #[serde(serialize_with = "serialize_duration")]
     pub duration: Duration,
 
     pub timings: [Option<StageTiming>; Stage::VARIANTS.len()],
+
+    /// Errors by stage, then most common kind first
+    pub error_groups: Vec<ErrorGroup>,
+    /// Each failed file, as listed after the run
+    #[serde(skip)]
+    pub failures: Vec<String>,
+    /// Where the run's report was written, when one was asked for
+    #[serde(skip)]
+    pub report_path: Option<PathBuf>,
 }
 
 impl Summary {
     pub fn from_outcomes(discovered: usize, outcomes: &[FileOutcome], start: Instant) -> Self {
-        let mut summary = Summary::default();
-        summary.discovered = discovered;
+        let mut summary = Summary {
+            discovered,
+            ..Default::default()
+        };
 
         for outcome in outcomes {
             match outcome {
@@ -150,11 +291,13 @@ impl Summary {
                     summary.moved += 1;
                     summary.processed += 1;
                     summary.bytes_moved += report.size;
+                    summary.add_category(report);
                 }
                 FileOutcome::Renamed { report, .. } => {
                     summary.renamed += 1;
                     summary.processed += 1;
                     summary.bytes_renamed += report.size;
+                    summary.add_category(report);
                 }
                 FileOutcome::Skipped { reason, size, .. } => {
                     let idx = reason.as_index();
@@ -163,22 +306,81 @@ impl Summary {
                     summary.bytes_skipped += *size;
                     summary.processed += 1;
                 }
-                FileOutcome::Err(_) => {
+                FileOutcome::DuplicateSkipped { size, .. } => {
+                    summary.duplicates += 1;
+                    summary.bytes_deduplicated += *size;
+                    summary.processed += 1;
+                }
+                FileOutcome::Err(err) => {
                     summary.errors += 1;
                     summary.processed += 1;
+                    summary.add_error(err);
                 }
             }
         }
+        summary.error_groups.sort_by(|a, b| {
+            a.stage.as_index().cmp(&b.stage.as_index()).then(b.count.cmp(&a.count)).then(a.kind.cmp(b.kind))
+        });
 
         summary.duration = start.elapsed();
         summary
     }
 
+    fn add_category(&mut self, report: &FileReport) {
+        let total = self.categories.entry(report.category).or_default();
+        total.files += 1;
+        total.bytes += report.size;
+        if let Some(device) = &report.device {
+            let transfer = self.transfers.entry(device.clone()).or_default();
+            transfer.files += 1;
+            transfer.bytes += report.size;
+        }
+    }
+
+    fn add_error(&mut self, err: &FileErrorReport) {
+        let kind = err.error.kind();
+        let group = match self.error_groups.iter_mut().position(|g| g.stage == err.stage && g.kind == kind) {
+            Some(i) => &mut self.error_groups[i],
+            None => {
+                self.error_groups.push(ErrorGroup { stage: err.stage, kind, count: 0, examples: Vec::new() });
+                self.error_groups.last_mut().unwrap()
+            }
+        };
+        group.count += 1;
+        if group.examples.len() < ERROR_EXAMPLES {
+            group.examples.push(err.path.clone());
+        }
+    }
+
+    /// Fold stage timings into the run totals
+    pub fn add_stage_stats(&mut self, stats: &StageStats) {
+        for (total, timing) in self.timings.iter_mut().zip(&stats.timings) {
+            if let Some(timing) = timing {
+                let entry = total.get_or_insert_with(Default::default);
+                entry.duration += timing.duration;
+                entry.files += timing.files;
+            }
+        }
+    }
+
     pub fn to_json(&self) -> serde_json::Result<String> {
         serde_json::to_string_pretty(self)
     }
 }
 
+impl FileOutcome {
+    /// One word for what happened, as in run reports
+    pub fn kind(&self) -> &'static str {
+        match self {
+            FileOutcome::Moved(_) => "moved",
+            FileOutcome::Renamed { .. } => "renamed",
+            FileOutcome::Skipped { .. } => "skipped",
+            FileOutcome::DuplicateSkipped { .. } => "duplicate",
+            FileOutcome::Err(_) => "failed",
+        }
+    }
+}
+
 impl std::fmt::Display for FileOutcome {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -203,6 +405,14 @@ impl std::fmt::Display for FileOutcome {
                 src.display(),
                 reason
             ),
+            FileOutcome::DuplicateSkipped { src, existing, trashed, .. } => write!(
+                f,
+                "{} {} (already organized as {}{})",
+                "♻ Duplicate".magenta().bold(),
+                src.display(),
+                existing.display(),
+                if trashed.is_some() { ", trashed" } else { ", deleted" }
+            ),
             FileOutcome::Err(err) => write!(
                 f,
                 "{} at {:?} stage for {}: {}",
@@ -228,8 +438,37 @@ impl std::fmt::Display for Summary {
         writeln!(f, "  Processed:   {} files ({:.1}%)", self.processed.to_string().green(), pct)?;
         writeln!(f, "  Moved:       {} files, {}", self.moved.to_string().green(), format_size(self.bytes_moved))?;
         writeln!(f, "  Renamed:     {} files, {}", self.renamed.to_string().cyan(), format_size(self.bytes_renamed))?;
+        if self.duplicates > 0 {
+            writeln!(f, "  Duplicates:  {} files, {}", self.duplicates.to_string().magenta(), format_size(self.bytes_deduplicated))?;
+        }
         writeln!(f, "  Errors:      {} files", self.errors.to_string().red())?;
+        if self.cancelled {
+            writeln!(f, "  Cancelled:   {} files not started", self.not_started.to_string().yellow())?;
+        }
+
+
+        // Category legend, largest first
+        if !self.categories.is_empty() {
+            writeln!(f, "\n{}", "Categories:".bold().blue())?;
+            let mut rows: Vec<_> = self.categories.iter().collect();
+            rows.sort_by(|a, b| b.1.files.cmp(&a.1.files).then(a.0.cmp(b.0)));
+            for (category, total) in rows {
+                let label = format!("{} {}", self.legend.icon(category), self.legend.name(category));
+                writeln!(f, "  {:<16} {:<4} files ({})",
+                    label.color(self.legend.color(category)),
+                    total.files,
+                    format_size(total.bytes)
+                )?;
+            }
+        }
 
+        // Copies onto other devices, the slow part of a run
+        if !self.transfers.is_empty() {
+            writeln!(f, "\n{}", "Transfers by device:".bold().blue())?;
+            for (device, total) in &self.transfers {
+                writeln!(f, "  {} {:<4} files ({})", device.display(), total.files, format_size(total.bytes))?;
+            }
+        }
 
         // Skips
         if self.skip_counts.iter().any(|&c| c > 0) {
@@ -247,6 +486,24 @@ impl std::fmt::Display for Summary {
             }
         }
 
+        // Errors, so a pile of them points at a cause
+        if !self.error_groups.is_empty() {
+            writeln!(f, "\n{}", "Errors:".bold().blue())?;
+            for group in &self.error_groups {
+                writeln!(f, "  - {:<9} {:<22} {:<4} files",
+                    format!("{:?}", group.stage),
+                    group.kind,
+                    group.count.to_string().red()
+                )?;
+                for path in &group.examples {
+                    writeln!(f, "      {}", path.display())?;
+                }
+                if group.count > group.examples.len() {
+                    writeln!(f, "      … and {} more", group.count - group.examples.len())?;
+                }
+            }
+        }
+
         // Stage timings
         writeln!(f, "\n{}", "Stage timings:".bold().blue())?;
         for (i, opt) in self.timings.iter().enumerate() {
@@ -273,7 +530,7 @@ impl std::fmt::Display for Summary {
 }
 
 // --- Helpers ---
-fn format_size(bytes: u64) -> String {
+pub(crate) fn format_size(bytes: u64) -> String {
     const KB: u64 = 1024;
     const MB: u64 = KB * 1024;
     const GB: u64 = MB * 1024;
@@ -289,7 +546,7 @@ fn format_size(bytes: u64) -> String {
     }
 }
 
-fn format_duration(d: Duration) -> String {
+pub(crate) fn format_duration(d: Duration) -> String {
     let secs = d.as_secs_f64();
     if secs < 1.0 {
         format!("{:.2} ms", secs * 1000.0)
@@ -0,0 +1,40 @@
+//! A byte-rate limit shared by every copy in a run, so organizing in the
+//! background leaves the disk usable
+
+use std::sync::Mutex;
+
+use tokio::time::{sleep_until, Duration, Instant};
+
+/// Bytes copied per throttled read
+pub const CHUNK: usize = 256 * 1024;
+
+/// Caps the combined rate of the copies sharing it. Each chunk books the next
+/// free slot on a virtual clock, so concurrent copies split the budget.
+#[derive(Debug)]
+pub struct Throttle {
+    bytes_per_sec: u64,
+    next: Mutex<Option<Instant>>,
+}
+
+impl Throttle {
+    /// `bytes_per_sec` must be above zero
+    pub fn new(bytes_per_sec: u64) -> Self {
+        Self { bytes_per_sec: bytes_per_sec.max(1), next: Mutex::new(None) }
+    }
+
+    pub fn bytes_per_sec(&self) -> u64 {
+        self.bytes_per_sec
+    }
+
+    /// Wait until `bytes` more may be copied
+    pub async fn take(&self, bytes: u64) {
+        let start = {
+            let mut next = self.next.lock().unwrap_or_else(|e| e.into_inner());
+            let now = Instant::now();
+            let start = next.map_or(now, |next| next.max(now));
+            *next = Some(start + Duration::from_secs_f64(bytes as f64 / self.bytes_per_sec as f64));
+            start
+        };
+        sleep_until(start).await;
+    }
+}
@@ -0,0 +1,108 @@
+//! Destination roots on other volumes, per category. A category can list
+//! several (`"videos": ["/mnt/media/Organized", "/mnt/usb/Organized"]`). Each
+//! file goes to the first one on its own filesystem, where the move is a
+//! rename, and to the first listed when none is. Files copied onto a
+//! filesystem from another queue up per destination device, and at most
+//! [`DEFAULT_TRANSFERS_PER_DEVICE`] (or the configured number) copy onto one
+//! device at once: parallel copies onto a spinning disk are slower than
+//! sequential ones.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+use crate::space::{filesystem_id, FsId};
+
+/// Copies onto one device at once unless configured: one after another
+pub const DEFAULT_TRANSFERS_PER_DEVICE: usize = 1;
+
+/// Candidate destination roots per top-level category, in order of preference
+pub type Volumes = BTreeMap<&'static str, Vec<PathBuf>>;
+
+/// Picks the destination root of each file and the device queue it copies through
+#[derive(Debug)]
+pub struct Routes {
+    volumes: Volumes,
+    transfers_per_device: usize,
+    /// Filesystem of each directory looked at; `None` when it can't be read
+    filesystems: Mutex<HashMap<PathBuf, Option<FsId>>>,
+    devices: Mutex<HashMap<FsId, Arc<Device>>>,
+}
+
+/// A destination filesystem that files are copied onto
+#[derive(Debug)]
+pub struct Device {
+    /// The first destination root seen on it, naming it in summaries
+    pub root: PathBuf,
+    slots: Semaphore,
+}
+
+impl Device {
+    /// Wait for a free transfer slot on this device, in the order asked;
+    /// the copy runs while the permit is held
+    pub async fn queue(&self) -> SemaphorePermit<'_> {
+        self.slots.acquire().await.expect("device queues are never closed")
+    }
+}
+
+impl Routes {
+    pub fn new(volumes: Volumes) -> Self {
+        Self {
+            volumes,
+            transfers_per_device: DEFAULT_TRANSFERS_PER_DEVICE,
+            filesystems: Default::default(),
+            devices: Default::default(),
+        }
+    }
+
+    /// Let up to `transfers` files copy onto each device at once
+    pub fn with_transfers_per_device(mut self, transfers: usize) -> Self {
+        self.transfers_per_device = transfers.max(1);
+        self
+    }
+
+    /// Where a file of `category` at `source` is organized under: the first
+    /// of the category's volumes on the same filesystem as the file, else
+    /// the first listed; `default` for categories without volumes
+    pub fn root_for(&self, category: &str, source: &Path, default: &Path) -> PathBuf {
+        let Some(candidates) = self.volumes.get(category).filter(|roots| !roots.is_empty()) else {
+            return default.to_path_buf();
+        };
+        let source_fs = self.filesystem(parent(source));
+        source_fs
+            .and_then(|fs| candidates.iter().find(|root| self.filesystem(root) == Some(fs.clone())))
+            .unwrap_or(&candidates[0])
+            .clone()
+    }
+
+    /// The device a file at `source` is copied onto when organized under
+    /// `root`; `None` when it stays on its filesystem or either can't be read
+    pub fn transfer(&self, source: &Path, root: &Path) -> Option<Arc<Device>> {
+        let dest_fs = self.filesystem(root)?;
+        if self.filesystem(parent(source))? == dest_fs {
+            return None;
+        }
+        let mut devices = self.devices.lock().unwrap();
+        let device = devices.entry(dest_fs).or_insert_with(|| {
+            Arc::new(Device { root: root.to_path_buf(), slots: Semaphore::new(self.transfers_per_device) })
+        });
+        Some(device.clone())
+    }
+
+    fn filesystem(&self, dir: &Path) -> Option<FsId> {
+        let mut filesystems = self.filesystems.lock().unwrap();
+        filesystems
+            .entry(dir.to_path_buf())
+            .or_insert_with(|| filesystem_id(dir).ok())
+            .clone()
+    }
+}
+
+/// The directory a file is in, which shares its filesystem
+fn parent(path: &Path) -> &Path {
+    path.parent().unwrap_or(path)
+}
@@ -0,0 +1,98 @@
+//! Windows rules for destination paths. Names like `CON` or `nul.txt`, and
+//! names ending in a dot or space, can't be created there. Paths past
+//! `MAX_PATH` need the `\\?\` extended-length prefix. The rewrites are plain
+//! string functions, so they build and behave the same everywhere.
+//! [`native_destination`] and [`extended_length`] only apply them on Windows.
+
+use std::{
+    borrow::Cow,
+    path::{Component, Path, PathBuf},
+};
+
+/// Device names Windows reserves, with or without an extension
+const RESERVED_NAMES: [&str; 32] = [
+    "CON", "PRN", "AUX", "NUL", "CONIN$", "CONOUT$", "COM0", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6",
+    "COM7", "COM8", "COM9", "COM¹", "COM²", "COM³", "LPT0", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6",
+    "LPT7", "LPT8", "LPT9", "LPT¹", "LPT²", "LPT³",
+];
+
+/// Longest path the plain Win32 calls accept, counting the final NUL
+pub const MAX_PATH: usize = 260;
+
+/// Directories get 12 characters less, to leave room for an 8.3 file name
+const MAX_DIR_PATH: usize = MAX_PATH - 12;
+
+/// `name` as Windows can create it: trailing dots and spaces dropped and
+/// `_` added after a reserved device name (`CON.txt` → `CON_.txt`). A name
+/// of only dots and spaces becomes `_`.
+pub fn sanitize_name(name: &str) -> Cow<'_, str> {
+    let trimmed = name.trim_end_matches(['.', ' ']);
+    if trimmed.is_empty() {
+        return Cow::Owned("_".to_string());
+    }
+    // `CON .txt` and `con.tar.gz` are the device too
+    let stem_end = trimmed.find('.').unwrap_or(trimmed.len());
+    let stem = trimmed[..stem_end].trim_end_matches(' ');
+    if RESERVED_NAMES.iter().any(|device| device.eq_ignore_ascii_case(stem)) {
+        Cow::Owned(format!("{}_{}", &trimmed[..stem_end], &trimmed[stem_end..]))
+    } else {
+        Cow::Borrowed(trimmed)
+    }
+}
+
+/// `path` with [`sanitize_name`] applied to each of its folder and file
+/// names. Names that aren't UTF-8 are left alone.
+pub fn sanitize_path(path: &Path) -> PathBuf {
+    path.components()
+        .map(|component| match component {
+            Component::Normal(name) => match name.to_str() {
+                Some(name) => PathBuf::from(sanitize_name(name).as_ref()),
+                None => PathBuf::from(name),
+            },
+            other => PathBuf::from(other.as_os_str()),
+        })
+        .collect()
+}
+
+/// The `\\?\` form of an absolute Windows path (`C:\...` or `\\server\share\...`)
+/// too long for the plain calls; `None` when it needs no prefix or can't
+/// take one. Extended-length paths are passed to the filesystem as they
+/// are, so `/` becomes `\` and paths with `.` or `..` folders are left alone.
+pub fn extended_length_str(path: &str) -> Option<String> {
+    if path.encode_utf16().count() < MAX_DIR_PATH || path.starts_with(r"\\?\") || path.starts_with(r"\\.\") {
+        return None;
+    }
+    let path = path.replace('/', "\\");
+    if path.split('\\').any(|part| part == "." || part == "..") {
+        return None;
+    }
+    if let Some(share) = path.strip_prefix(r"\\") {
+        return Some(format!(r"\\?\UNC\{}", share));
+    }
+    let bytes = path.as_bytes();
+    let is_drive_path = bytes.len() > 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' && bytes[2] == b'\\';
+    is_drive_path.then(|| format!(r"\\?\{}", path))
+}
+
+/// `path` as the destination of a move: sanitized on Windows (see
+/// [`sanitize_path`]), unchanged elsewhere
+pub fn native_destination(path: &Path) -> Cow<'_, Path> {
+    if cfg!(windows) {
+        Cow::Owned(sanitize_path(path))
+    } else {
+        Cow::Borrowed(path)
+    }
+}
+
+/// `path` in the form to hand the operating system: with the extended-length
+/// prefix on Windows when it is too long (see [`extended_length_str`]),
+/// unchanged elsewhere
+pub fn extended_length(path: &Path) -> Cow<'_, Path> {
+    if !cfg!(windows) {
+        return Cow::Borrowed(path);
+    }
+    match path.to_str().and_then(extended_length_str) {
+        Some(extended) => Cow::Owned(PathBuf::from(extended)),
+        None => Cow::Borrowed(path),
+    }
+}
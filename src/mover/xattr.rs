@@ -0,0 +1,173 @@
+//! Metadata stored beside a file's contents that a plain copy drops: extended
+//! attributes on macOS (Finder tags, comments) and Linux (the `user.`
+//! namespace), alternate data streams on Windows. Other platforms have none.
+
+use std::{io, path::Path};
+
+/// Copy every attribute (stream) of `from` onto `to`. Returns how many were copied.
+pub fn copy(from: &Path, to: &Path) -> io::Result<usize> {
+    platform::copy(from, to)
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+mod platform {
+    use std::{
+        ffi::{c_char, c_void, CString},
+        io,
+        os::unix::ffi::OsStrExt,
+        path::Path,
+    };
+
+    #[cfg(target_os = "linux")]
+    mod sys {
+        use std::ffi::{c_char, c_void};
+
+        pub unsafe fn list(path: *const c_char, buf: *mut c_char, size: usize) -> isize {
+            unsafe { libc::listxattr(path, buf, size) }
+        }
+
+        pub unsafe fn get(path: *const c_char, name: *const c_char, buf: *mut c_void, size: usize) -> isize {
+            unsafe { libc::getxattr(path, name, buf, size) }
+        }
+
+        pub unsafe fn set(path: *const c_char, name: *const c_char, value: *const c_void, size: usize) -> i32 {
+            unsafe { libc::setxattr(path, name, value, size, 0) }
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    mod sys {
+        use std::ffi::{c_char, c_void};
+
+        pub unsafe fn list(path: *const c_char, buf: *mut c_char, size: usize) -> isize {
+            unsafe { libc::listxattr(path, buf, size, 0) }
+        }
+
+        pub unsafe fn get(path: *const c_char, name: *const c_char, buf: *mut c_void, size: usize) -> isize {
+            unsafe { libc::getxattr(path, name, buf, size, 0, 0) }
+        }
+
+        pub unsafe fn set(path: *const c_char, name: *const c_char, value: *const c_void, size: usize) -> i32 {
+            unsafe { libc::setxattr(path, name, value, size, 0, 0) }
+        }
+    }
+
+    /// Attributes an unprivileged copy can recreate: all of them on macOS,
+    /// the `user.` namespace on Linux
+    fn wanted(name: &[u8]) -> bool {
+        cfg!(target_os = "macos") || name.starts_with(b"user.")
+    }
+
+    /// Ask for the size, then fetch; retry if the value grew in between
+    fn read_sized(mut call: impl FnMut(*mut u8, usize) -> isize) -> io::Result<Vec<u8>> {
+        loop {
+            let len = call(std::ptr::null_mut(), 0);
+            if len < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            let mut buf = vec![0u8; len as usize];
+            let got = call(buf.as_mut_ptr(), buf.len());
+            if got < 0 {
+                let e = io::Error::last_os_error();
+                if e.raw_os_error() == Some(libc::ERANGE) {
+                    continue;
+                }
+                return Err(e);
+            }
+            buf.truncate(got as usize);
+            return Ok(buf);
+        }
+    }
+
+    pub fn copy(from: &Path, to: &Path) -> io::Result<usize> {
+        let from = CString::new(from.as_os_str().as_bytes())?;
+        let to = CString::new(to.as_os_str().as_bytes())?;
+
+        // SAFETY: `from` is NUL-terminated and `buf` is valid for `size` bytes
+        let names = match read_sized(|buf, size| unsafe { sys::list(from.as_ptr(), buf.cast::<c_char>(), size) }) {
+            Ok(names) => names,
+            // The source filesystem has no attributes at all
+            Err(e) if [Some(libc::ENOTSUP), Some(libc::EOPNOTSUPP)].contains(&e.raw_os_error()) => return Ok(0),
+            Err(e) => return Err(e),
+        };
+
+        let mut copied = 0;
+        for name in names.split(|&b| b == 0).filter(|n| !n.is_empty() && wanted(n)) {
+            let name = CString::new(name)?;
+            // SAFETY: both strings are NUL-terminated and `buf` is valid for `size` bytes
+            let value = read_sized(|buf, size| unsafe {
+                sys::get(from.as_ptr(), name.as_ptr(), buf.cast::<c_void>(), size)
+            })?;
+            // SAFETY: both strings are NUL-terminated and `value` is valid for its length
+            let rc = unsafe { sys::set(to.as_ptr(), name.as_ptr(), value.as_ptr().cast::<c_void>(), value.len()) };
+            if rc != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            copied += 1;
+        }
+        Ok(copied)
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use std::{
+        ffi::OsString,
+        fs, io,
+        os::windows::ffi::{OsStrExt, OsStringExt},
+        path::Path,
+    };
+    use windows_sys::Win32::{
+        Foundation::{ERROR_HANDLE_EOF, INVALID_HANDLE_VALUE},
+        Storage::FileSystem::{FindClose, FindFirstStreamW, FindNextStreamW, FindStreamInfoStandard, WIN32_FIND_STREAM_DATA},
+    };
+
+    /// Names of every stream of `path`, e.g. `::$DATA`, `:Zone.Identifier:$DATA`
+    fn streams(path: &Path) -> io::Result<Vec<OsString>> {
+        let wide: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+        let mut data = WIN32_FIND_STREAM_DATA::default();
+        let data_ptr = (&mut data as *mut WIN32_FIND_STREAM_DATA).cast();
+        // SAFETY: `wide` is NUL-terminated and `data_ptr` is valid for writes
+        let handle = unsafe { FindFirstStreamW(wide.as_ptr(), FindStreamInfoStandard, data_ptr, 0) };
+        if handle == INVALID_HANDLE_VALUE {
+            let e = io::Error::last_os_error();
+            return if e.raw_os_error() == Some(ERROR_HANDLE_EOF as i32) { Ok(Vec::new()) } else { Err(e) };
+        }
+
+        let mut names = Vec::new();
+        loop {
+            let len = data.cStreamName.iter().position(|&c| c == 0).unwrap_or(data.cStreamName.len());
+            names.push(OsString::from_wide(&data.cStreamName[..len]));
+            // SAFETY: `handle` is an open find handle and `data_ptr` is valid for writes
+            if unsafe { FindNextStreamW(handle, data_ptr) } == 0 {
+                break;
+            }
+        }
+        // SAFETY: `handle` came from FindFirstStreamW and is closed once
+        unsafe { FindClose(handle) };
+        Ok(names)
+    }
+
+    pub fn copy(from: &Path, to: &Path) -> io::Result<usize> {
+        let mut copied = 0;
+        // `::$DATA` is the file's own contents, already copied
+        for name in streams(from)?.into_iter().filter(|n| n != "::$DATA") {
+            let mut src = from.as_os_str().to_os_string();
+            src.push(&name);
+            let mut dest = to.as_os_str().to_os_string();
+            dest.push(&name);
+            fs::write(dest, fs::read(src)?)?;
+            copied += 1;
+        }
+        Ok(copied)
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+mod platform {
+    use std::{io, path::Path};
+
+    pub fn copy(_from: &Path, _to: &Path) -> io::Result<usize> {
+        Ok(0)
+    }
+}
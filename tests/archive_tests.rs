@@ -0,0 +1,199 @@
+use std::{
+    fs::File,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use flate2::{write::GzEncoder, Compression};
+use stash::{
+    archive::{self, ArchiveKind, ArchiveLimits, EntryKind},
+    errors::FileOrganizerError,
+};
+use tempfile::tempdir;
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
+
+/// Write a zip at `path` with `(name, contents)` files
+fn write_zip(path: &Path, files: &[(&str, &[u8])]) {
+    let mut zip = ZipWriter::new(File::create(path).unwrap());
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    for (name, contents) in files {
+        zip.start_file(*name, options).unwrap();
+        zip.write_all(contents).unwrap();
+    }
+    zip.finish().unwrap();
+}
+
+/// Append a regular file to a tar under `name` exactly as given, bypassing
+/// the checks `Header::set_path` would make
+fn append_raw(builder: &mut tar::Builder<impl Write>, name: &str, contents: &[u8]) {
+    let mut header = tar::Header::new_gnu();
+    header.as_old_mut().name[..name.len()].copy_from_slice(name.as_bytes());
+    header.set_size(contents.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    builder.append(&header, contents).unwrap();
+}
+
+fn assert_unsafe(result: stash::errors::Result<archive::ExtractReport>) {
+    match result {
+        Err(FileOrganizerError::UnsafeArchive { .. }) => {}
+        other => panic!("expected UnsafeArchive, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_detect_kinds() {
+    let dir = tempdir().unwrap();
+    let zip = dir.path().join("a.zip");
+    write_zip(&zip, &[("a.txt", b"a")]);
+
+    let tar_path = dir.path().join("a.tar");
+    let mut builder = tar::Builder::new(File::create(&tar_path).unwrap());
+    append_raw(&mut builder, "a.txt", b"a");
+    builder.finish().unwrap();
+
+    let tgz = dir.path().join("a.tgz");
+    let mut builder = tar::Builder::new(GzEncoder::new(File::create(&tgz).unwrap(), Compression::default()));
+    append_raw(&mut builder, "a.txt", b"a");
+    builder.into_inner().unwrap().finish().unwrap();
+
+    let gz = dir.path().join("notes.txt.gz");
+    let mut encoder = GzEncoder::new(File::create(&gz).unwrap(), Compression::default());
+    encoder.write_all(b"just some text").unwrap();
+    encoder.finish().unwrap();
+
+    let text = dir.path().join("zip.txt");
+    std::fs::write(&text, b"not an archive").unwrap();
+
+    assert_eq!(ArchiveKind::detect(&zip).unwrap(), Some(ArchiveKind::Zip));
+    assert_eq!(ArchiveKind::detect(&tar_path).unwrap(), Some(ArchiveKind::Tar));
+    assert_eq!(ArchiveKind::detect(&tgz).unwrap(), Some(ArchiveKind::TarGz));
+    assert_eq!(ArchiveKind::detect(&gz).unwrap(), None);
+    assert_eq!(ArchiveKind::detect(&text).unwrap(), None);
+}
+
+#[test]
+fn test_extract_nested_zip() {
+    let dir = tempdir().unwrap();
+    let zip = dir.path().join("photos.zip");
+    write_zip(&zip, &[("album/2024/beach.jpg", b"jpeg"), ("readme.txt", b"hello")]);
+
+    let dest = dir.path().join("out");
+    let report = archive::extract(&zip, &dest, &ArchiveLimits::default()).unwrap();
+    assert_eq!(report.files.len(), 2);
+    assert_eq!(report.bytes, 9);
+    assert_eq!(std::fs::read(dest.join("album/2024/beach.jpg")).unwrap(), b"jpeg");
+    assert_eq!(std::fs::read(dest.join("readme.txt")).unwrap(), b"hello");
+}
+
+#[test]
+fn test_zip_slip_is_refused_before_writing() {
+    let dir = tempdir().unwrap();
+    let zip = dir.path().join("evil.zip");
+    write_zip(&zip, &[("fine.txt", b"ok"), ("../../escaped.txt", b"gotcha"), ("/etc/evil", b"x")]);
+
+    let entries = archive::list(&zip, &ArchiveLimits::default()).unwrap();
+    let paths: Vec<Option<PathBuf>> = entries.iter().map(|entry| entry.path.clone()).collect();
+    assert_eq!(paths, vec![Some(PathBuf::from("fine.txt")), None, None]);
+
+    let dest = dir.path().join("a/b/out");
+    assert_unsafe(archive::extract(&zip, &dest, &ArchiveLimits::default()));
+    assert!(!dest.exists(), "nothing may be written when an entry is unsafe");
+    assert!(!dir.path().join("a/escaped.txt").exists());
+}
+
+#[test]
+fn test_tar_traversal_is_refused() {
+    let dir = tempdir().unwrap();
+    let tgz = dir.path().join("evil.tar.gz");
+    let mut builder = tar::Builder::new(GzEncoder::new(File::create(&tgz).unwrap(), Compression::default()));
+    append_raw(&mut builder, "ok/fine.txt", b"ok");
+    append_raw(&mut builder, "ok/../../escaped.txt", b"gotcha");
+    builder.into_inner().unwrap().finish().unwrap();
+
+    let entries = archive::list(&tgz, &ArchiveLimits::default()).unwrap();
+    assert_eq!(entries[0].path, Some(PathBuf::from("ok/fine.txt")));
+    assert_eq!(entries[1].path, None);
+
+    let dest = dir.path().join("out");
+    assert_unsafe(archive::extract(&tgz, &dest, &ArchiveLimits::default()));
+    assert!(!dir.path().join("escaped.txt").exists());
+}
+
+#[test]
+fn test_links_are_listed_but_never_created() {
+    let dir = tempdir().unwrap();
+    let zip_path = dir.path().join("links.zip");
+    let mut zip = ZipWriter::new(File::create(&zip_path).unwrap());
+    zip.add_symlink("passwd", "/etc/passwd", SimpleFileOptions::default()).unwrap();
+    zip.start_file("data.txt", SimpleFileOptions::default()).unwrap();
+    zip.write_all(b"data").unwrap();
+    zip.finish().unwrap();
+
+    let tar_path = dir.path().join("links.tar");
+    let mut builder = tar::Builder::new(File::create(&tar_path).unwrap());
+    let mut header = tar::Header::new_gnu();
+    header.set_entry_type(tar::EntryType::Symlink);
+    header.set_size(0);
+    builder.append_link(&mut header, "up", "../..").unwrap();
+    // Written through the link if it were followed
+    append_raw(&mut builder, "up/escaped.txt", b"gotcha");
+    builder.finish().unwrap();
+
+    let entries = archive::list(&zip_path, &ArchiveLimits::default()).unwrap();
+    assert_eq!(entries[0].kind, EntryKind::Symlink(PathBuf::from("/etc/passwd")));
+
+    let dest = dir.path().join("from_zip");
+    let report = archive::extract(&zip_path, &dest, &ArchiveLimits::default()).unwrap();
+    assert_eq!(report.skipped, vec!["passwd".to_string()]);
+    assert!(std::fs::symlink_metadata(dest.join("passwd")).is_err());
+    assert_eq!(std::fs::read(dest.join("data.txt")).unwrap(), b"data");
+
+    let entries = archive::list(&tar_path, &ArchiveLimits::default()).unwrap();
+    assert_eq!(entries[0].kind, EntryKind::Symlink(PathBuf::from("../..")));
+
+    let dest = dir.path().join("nested/from_tar");
+    let report = archive::extract(&tar_path, &dest, &ArchiveLimits::default()).unwrap();
+    assert_eq!(report.skipped, vec!["up".to_string()]);
+    assert!(std::fs::symlink_metadata(dest.join("up")).unwrap().is_dir());
+    assert!(dest.join("up/escaped.txt").is_file());
+    assert!(!dir.path().join("escaped.txt").exists());
+}
+
+#[test]
+fn test_size_bombs_are_refused() {
+    let dir = tempdir().unwrap();
+    let zip = dir.path().join("bomb.zip");
+    write_zip(&zip, &[("zeros.bin", &vec![0u8; 10 << 20])]);
+
+    let entries = archive::list(&zip, &ArchiveLimits::default()).unwrap();
+    assert_eq!(entries[0].size, 10 << 20);
+    assert!(entries[0].compressed_size.unwrap() < 100 << 10);
+    assert_unsafe(archive::extract(&zip, &dir.path().join("ratio"), &ArchiveLimits::default()));
+
+    let plain = dir.path().join("plain.zip");
+    write_zip(&plain, &[("a.txt", b"0123456789abcdef"), ("b.txt", b"0123456789abcdef")]);
+    let lenient = ArchiveLimits { max_ratio: u64::MAX, ..Default::default() };
+    let small_entries = ArchiveLimits { max_entry_size: 8, ..lenient };
+    assert_unsafe(archive::extract(&plain, &dir.path().join("entry"), &small_entries));
+    let small_total = ArchiveLimits { max_total_size: 24, ..lenient };
+    assert_unsafe(archive::extract(&plain, &dir.path().join("total"), &small_total));
+    let few_entries = ArchiveLimits { max_entries: 1, ..lenient };
+    assert!(matches!(
+        archive::list(&plain, &few_entries),
+        Err(FileOrganizerError::UnsafeArchive { .. })
+    ));
+    assert_eq!(archive::extract(&plain, &dir.path().join("ok"), &lenient).unwrap().bytes, 32);
+}
+
+#[test]
+fn test_read_entry() {
+    let dir = tempdir().unwrap();
+    let zip = dir.path().join("book.epub");
+    write_zip(&zip, &[("META-INF/container.xml", b"<container/>"), ("big.bin", &[7u8; 4096])]);
+
+    assert_eq!(archive::read_entry(&zip, "META-INF/container.xml", 1 << 20).unwrap(), Some(b"<container/>".to_vec()));
+    assert_eq!(archive::read_entry(&zip, "big.bin", 100).unwrap().unwrap().len(), 100);
+    assert_eq!(archive::read_entry(&zip, "missing.xml", 100).unwrap(), None);
+    assert!(archive::read_entry(&dir.path().join("missing.zip"), "a", 1).is_err());
+}
@@ -0,0 +1,130 @@
+mod test_utils;
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::BTreeMap,
+        path::Path,
+        time::{Duration, SystemTime},
+    };
+
+    use stash::{backup_classifier::BackupClassifier, path_builder::PathBuilder, registry::Classifier};
+    use stash::config::OrganizerConfig;
+    use stash::index::Db;
+    use stash::metadata::{BackupSubcategory, FileCategory};
+    use stash::organizer::{organise_files, OrganizeOptions};
+    use stash::preview::classify_name;
+    use stash::retention::prune;
+    use stash::scanner::{ScanConfig, Scanner};
+    use stash::utils::create_classifier_registry;
+    use tempfile::tempdir;
+
+    use crate::test_utils::create_test_file_with_ext;
+
+    #[test]
+    fn test_confidence_levels() {
+        let clf = BackupClassifier;
+
+        assert_eq!(clf.confidence("bak", "application/octet-stream"), 100);
+        assert_eq!(clf.confidence("txt~", "application/octet-stream"), 100);
+        assert_eq!(clf.confidence("jsonlz4", "application/octet-stream"), 90);
+        assert_eq!(clf.confidence("zip", "application/zip"), 0);
+
+        assert_eq!(BackupClassifier::score_name("backup-2024-03-12.zip", "zip", "application/zip"), 100);
+        assert_eq!(BackupClassifier::score_name("site_backup.tar.gz", "gz", "application/gzip"), 100);
+        assert_eq!(BackupClassifier::score_name("bookmarks_3_12_24.html", "html", "text/html"), 100);
+        assert_eq!(BackupClassifier::score_name("sms-20240312101500.xml", "xml", "text/xml"), 100);
+        assert_eq!(BackupClassifier::score_name("photos.zip", "zip", "application/zip"), 0);
+        assert_eq!(BackupClassifier::score_name("index.html", "html", "text/html"), 0);
+    }
+
+    #[test]
+    fn test_subcategories_from_names() {
+        for (name, ext, expected) in [
+            ("thesis.docx.bak", "bak", BackupSubcategory::Copies),
+            ("config.old", "old", BackupSubcategory::Copies),
+            ("backup-2024.zip", "zip", BackupSubcategory::Archives),
+            ("bookmarks-2024-03-12.json", "json", BackupSubcategory::Bookmarks),
+            ("bookmarks-2024-03-12_1234.jsonlz4", "jsonlz4", BackupSubcategory::Bookmarks),
+            ("backup.ab", "ab", BackupSubcategory::Phone),
+            ("calls-20240312.xml", "xml", BackupSubcategory::Phone),
+        ] {
+            assert_eq!(BackupClassifier::subcategory(name, ext), expected, "{name}");
+        }
+    }
+
+    #[test]
+    fn test_preview_prefers_backups_over_archives() {
+        let meta = classify_name("Backup-2024.zip", 10, None);
+        assert_eq!(meta.category.to_string(), "Backups::Archives");
+
+        let meta = classify_name("holiday.zip", 10, None);
+        assert_eq!(meta.category.top_level(), "Archives");
+    }
+
+    #[tokio::test]
+    async fn test_registry_sends_backups_to_backups_folder() {
+        let (dir, _) = create_test_file_with_ext("txt");
+        let path = dir.path().join("backup_2024.zip");
+        std::fs::write(&path, "zip").unwrap();
+        let registry = create_classifier_registry();
+
+        let config = ScanConfig { max_depth: 0, include_hidden: true, ..Default::default() };
+        let raw = Scanner::new(&path, config).next().unwrap().unwrap();
+        let mut meta = registry.classify(&raw).await.unwrap();
+        meta.year = None;
+
+        assert!(matches!(meta.category, FileCategory::Backups(BackupSubcategory::Archives)));
+        assert_eq!(PathBuilder::new(&meta).build(), Path::new("Organized/Backups/Archives"));
+    }
+
+    #[test]
+    fn test_retention_config_is_validated() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        std::fs::write(&path, r#"{ "retention": { "backups": "90d" } }"#).unwrap();
+
+        let limits = OrganizerConfig::load_from_file(&path).unwrap().retention_limits();
+        assert_eq!(limits["Backups"], Duration::from_secs(90 * 24 * 60 * 60));
+
+        std::fs::write(&path, r#"{ "retention": { "backupz": "90d" } }"#).unwrap();
+        assert!(OrganizerConfig::load_from_file(&path).is_err());
+        std::fs::write(&path, r#"{ "retention": { "backups": "soon" } }"#).unwrap();
+        assert!(OrganizerConfig::load_from_file(&path).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_prune_trashes_only_expired_backups() {
+        let dir = tempdir().unwrap();
+        let trash = tempdir().unwrap();
+        let year_ago = SystemTime::now() - Duration::from_secs(365 * 24 * 60 * 60);
+        for name in ["old.bak", "new.bak", "report.pdf"] {
+            std::fs::write(dir.path().join(name), name).unwrap();
+        }
+        for name in ["old.bak", "report.pdf"] {
+            std::fs::File::options()
+                .write(true)
+                .open(dir.path().join(name))
+                .unwrap()
+                .set_modified(year_ago)
+                .unwrap();
+        }
+        organise_files(dir.path(), &OrganizeOptions { portable: true, ..Default::default() }).await.unwrap();
+
+        let db = Db::open_portable(dir.path()).await.unwrap();
+        let limits = BTreeMap::from([("Backups", Duration::from_secs(30 * 24 * 60 * 60))]);
+
+        let report = prune(&db, &limits, true, trash.path()).await.unwrap();
+        assert_eq!((report.pruned.len(), report.kept), (1, 1));
+        assert!(report.pruned[0].exists());
+
+        let report = prune(&db, &limits, false, trash.path()).await.unwrap();
+        assert_eq!(report.pruned.len(), 1);
+        assert!(report.pruned[0].ends_with("old.bak"));
+        assert!(!report.pruned[0].exists());
+        assert_eq!(db.list_actions(false).await.unwrap().len(), 1);
+
+        let report = prune(&db, &limits, false, trash.path()).await.unwrap();
+        assert!(report.pruned.is_empty());
+    }
+}
@@ -0,0 +1,67 @@
+use stash::{
+    calibration::{evaluate, load_labels, normalize_category},
+    utils::create_classifier_registry,
+};
+use tempfile::tempdir;
+
+#[test]
+fn test_normalize_category() {
+    assert_eq!(normalize_category("Images"), Some("Images"));
+    assert_eq!(normalize_category("documents"), Some("Documents"));
+    assert_eq!(normalize_category("Code::Rust"), Some("Code"));
+    assert_eq!(normalize_category("Spreadsheets"), None);
+}
+
+#[test]
+fn test_load_labels_resolves_relative_paths_and_skips_header() {
+    let dir = tempdir().unwrap();
+    let csv = dir.path().join("labels.csv");
+    std::fs::write(&csv, "path,category\n# comment\nphoto.jpg, images\n/abs/notes.pdf,Documents::Pdf\n").unwrap();
+
+    let labels = load_labels(&csv).unwrap();
+    assert_eq!(labels.len(), 2);
+    assert_eq!(labels[0].path, dir.path().join("photo.jpg"));
+    assert_eq!(labels[0].expected, "Images");
+    assert_eq!(labels[1].path, std::path::Path::new("/abs/notes.pdf"));
+    assert_eq!(labels[1].expected, "Documents");
+}
+
+#[test]
+fn test_load_labels_rejects_unknown_category() {
+    let dir = tempdir().unwrap();
+    let csv = dir.path().join("labels.csv");
+    std::fs::write(&csv, "a.txt,Spreadsheets\n").unwrap();
+
+    assert!(load_labels(&csv).is_err());
+}
+
+#[tokio::test]
+async fn test_evaluate_reports_precision_and_recall() {
+    let dir = tempdir().unwrap();
+    for name in ["a.jpg", "b.pdf", "c.zip"] {
+        std::fs::write(dir.path().join(name), b"x").unwrap();
+    }
+    let csv = dir.path().join("labels.csv");
+    // c.zip is deliberately mislabeled as a document
+    std::fs::write(&csv, "a.jpg,Images\nb.pdf,Documents\nc.zip,Documents\nmissing.png,Images\n").unwrap();
+
+    let registry = create_classifier_registry();
+    let report = evaluate(&registry, &load_labels(&csv).unwrap()).await;
+
+    assert_eq!(report.evaluated, 3);
+    assert_eq!(report.correct, 2);
+    assert_eq!(report.missing, vec![dir.path().join("missing.png")]);
+
+    let docs = &report.per_category["Documents"];
+    assert_eq!(docs.classifier, Some("DocumentClassifier"));
+    assert_eq!(docs.precision(), Some(1.0));
+    assert_eq!(docs.recall(), Some(0.5));
+
+    let archives = &report.per_category["Archives"];
+    assert_eq!(archives.false_positives, 1);
+    assert_eq!(archives.precision(), Some(0.0));
+    assert_eq!(archives.recall(), None);
+
+    assert_eq!(report.misclassified.len(), 1);
+    assert_eq!(report.misclassified[0].2, "Archives");
+}
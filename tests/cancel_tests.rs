@@ -0,0 +1,88 @@
+use std::time::Duration;
+
+use stash::{
+    cancel::CancelToken,
+    index::Db,
+    organizer::{organise_files, OrganizeOptions},
+    progress::{Progress, ProgressReporter},
+    run_status::{RunResult, RunStatus},
+};
+use tempfile::tempdir;
+
+/// Cancels the run as soon as the first file is done
+struct CancelAfterFirst(CancelToken);
+
+impl ProgressReporter for CancelAfterFirst {
+    fn start(&self, _label: &str, _total: u64) {}
+
+    fn inc(&self, _n: u64) {
+        self.0.cancel();
+    }
+
+    fn finish(&self) {}
+}
+
+#[tokio::test]
+async fn test_cancelled_resolves_for_every_clone() {
+    let token = CancelToken::new();
+    let waiter = token.clone();
+    let handle = tokio::spawn(async move { waiter.cancelled().await });
+
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    assert!(!token.is_cancelled());
+    token.cancel();
+    tokio::time::timeout(Duration::from_secs(1), handle).await.unwrap().unwrap();
+
+    // Already cancelled: resolves at once
+    tokio::time::timeout(Duration::from_secs(1), token.cancelled()).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_cancelled_before_start_scans_nothing() {
+    let dir = tempdir().unwrap();
+    std::fs::write(dir.path().join("a.pdf"), "a").unwrap();
+    let cancel = CancelToken::new();
+    cancel.cancel();
+
+    let options = OrganizeOptions { portable: true, cancel, ..Default::default() };
+    let summary = organise_files(dir.path(), &options).await.unwrap();
+
+    assert!(summary.cancelled);
+    assert_eq!((summary.discovered, summary.moved), (0, 0));
+    assert!(dir.path().join("a.pdf").exists());
+}
+
+#[tokio::test]
+async fn test_cancel_mid_run_finishes_started_files_and_indexes_them() {
+    let dir = tempdir().unwrap();
+    for i in 0..5 {
+        std::fs::write(dir.path().join(format!("file{i}.pdf")), format!("file {i}")).unwrap();
+    }
+    let cancel = CancelToken::new();
+    let options = OrganizeOptions {
+        portable: true,
+        max_concurrency: Some(1),
+        progress: Progress::new(CancelAfterFirst(cancel.clone())),
+        cancel,
+        ..Default::default()
+    };
+    let summary = organise_files(dir.path(), &options).await.unwrap();
+
+    assert!(summary.cancelled);
+    assert!(summary.moved >= 1);
+    assert_eq!(summary.moved + summary.not_started, 5);
+
+    let left = std::fs::read_dir(dir.path())
+        .unwrap()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().extension().is_some_and(|ext| ext == "pdf"))
+        .count();
+    assert_eq!(left, summary.not_started);
+
+    let db = Db::open_portable(dir.path()).await.unwrap();
+    assert_eq!(db.get_all_files().await.unwrap().len(), summary.moved);
+    assert!(db.pending_moves().await.unwrap().is_empty());
+
+    let status = RunStatus::from_summary(&[dir.path().to_path_buf()], false, &summary);
+    assert_eq!(status.result, RunResult::Cancelled);
+}
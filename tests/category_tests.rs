@@ -0,0 +1,384 @@
+use std::collections::BTreeSet;
+
+use stash::{
+    config::OrganizerConfig,
+    errors::SkipReason,
+    manifest::Manifest,
+    organizer::{organise_files, OrganizeOptions},
+};
+use tempfile::tempdir;
+
+#[test]
+fn test_organizer_config_disabled_categories() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("config.json");
+    std::fs::write(&path, r#"{ "categories": { "code": false, "Executables": false, "images": true } }"#).unwrap();
+
+    let config = OrganizerConfig::load_from_file(&path).unwrap();
+    assert_eq!(config.disabled_categories(), BTreeSet::from(["Code", "Executables"]));
+
+    std::fs::write(&path, r#"{ "categories": { "kode": false } }"#).unwrap();
+    assert!(OrganizerConfig::load_from_file(&path).is_err());
+}
+
+#[test]
+fn test_organizer_config_preserve() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("config.json");
+    std::fs::write(&path, r#"{ "preserve": { "xattrs": false } }"#).unwrap();
+
+    let preserve = OrganizerConfig::load_from_file(&path).unwrap().preserve;
+    assert!(preserve.timestamps && !preserve.xattrs);
+    assert_eq!(OrganizerConfig::default().preserve, Default::default());
+    assert!(OrganizerConfig::default().preserve.xattrs);
+}
+
+#[tokio::test]
+async fn test_disabled_category_files_stay_in_place() {
+    let dir = tempdir().unwrap();
+    let script = dir.path().join("main.rs");
+    let doc = dir.path().join("report.pdf");
+    std::fs::write(&script, "fn main() {}\n").unwrap();
+    std::fs::write(&doc, b"pdf bytes").unwrap();
+
+    let options = OrganizeOptions {
+        portable: true,
+        disabled_categories: BTreeSet::from(["Code"]),
+        ..Default::default()
+    };
+    let summary = organise_files(dir.path(), &options).await.unwrap();
+
+    assert_eq!(summary.moved, 1);
+    assert_eq!(summary.skip_counts[SkipReason::CategoryDisabled.as_index()], 1);
+    assert!(script.exists());
+    assert!(!doc.exists());
+    assert!(!dir.path().join("Organized").join("Code").exists());
+}
+
+#[test]
+fn test_display_config_builds_legend() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("config.json");
+    std::fs::write(&path, r#"{ "display": { "images": { "color": "bright blue", "icon": "📷", "name": "Pictures" } } }"#).unwrap();
+
+    let legend = OrganizerConfig::load_from_file(&path).unwrap().legend();
+    assert_eq!(legend.name("Images"), "Pictures");
+    assert_eq!(legend.icon("Images"), "📷");
+    assert_eq!(legend.color("Images"), colored::Color::BrightBlue);
+    // Categories without overrides keep the built-in look
+    assert_eq!(legend.name("Documents"), "Documents");
+    assert_eq!(legend.icon("Documents"), "📄");
+
+    std::fs::write(&path, r#"{ "display": { "images": { "color": "chartreuse" } } }"#).unwrap();
+    assert!(OrganizerConfig::load_from_file(&path).is_err());
+}
+
+#[tokio::test]
+async fn test_summary_counts_files_per_category() {
+    let dir = tempdir().unwrap();
+    std::fs::write(dir.path().join("a.pdf"), b"pdf one").unwrap();
+    std::fs::write(dir.path().join("b.pdf"), b"pdf two!").unwrap();
+    std::fs::write(dir.path().join("main.rs"), "fn main() {}\n").unwrap();
+
+    let options = OrganizeOptions { dry_run: true, ..Default::default() };
+    let summary = organise_files(dir.path(), &options).await.unwrap();
+
+    assert_eq!(summary.categories["Documents"].files, 2);
+    assert_eq!(summary.categories["Documents"].bytes, 15);
+    assert_eq!(summary.categories["Code"].files, 1);
+    assert!(summary.to_string().contains("Categories:"));
+}
+
+#[test]
+fn test_config_classifiers_are_validated() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("config.json");
+    std::fs::write(
+        &path,
+        r#"{ "classifiers": [{ "name": "cad", "extensions": ["dwg", ".DXF"], "category": "CAD", "subfolder": "Drawings" }] }"#,
+    )
+    .unwrap();
+
+    let classifiers = OrganizerConfig::load_from_file(&path).unwrap().rule_classifiers();
+    assert_eq!(classifiers.len(), 1);
+    assert_eq!(classifiers[0].score("dxf", "application/octet-stream"), 90);
+    assert_eq!(classifiers[0].score("pdf", "application/pdf"), 0);
+    assert_eq!(classifiers[0].category().to_string(), "CAD::Drawings");
+
+    for bad in [
+        r#"{ "classifiers": [{ "name": "empty", "category": "CAD" }] }"#,
+        r#"{ "classifiers": [{ "name": "escape", "extensions": ["dwg"], "category": ".." }] }"#,
+        r#"{ "classifiers": [{ "name": "nested", "extensions": ["dwg"], "category": "CAD/2d" }] }"#,
+        r#"{ "classifiers": [{ "name": "sure", "extensions": ["dwg"], "category": "CAD", "confidence": 0 }] }"#,
+    ] {
+        std::fs::write(&path, bad).unwrap();
+        assert!(OrganizerConfig::load_from_file(&path).is_err(), "{bad}");
+    }
+}
+
+#[test]
+fn test_classifier_mime_prefix_pattern() {
+    use stash::rule_classifier::{ClassifierRule, RuleClassifier};
+
+    let rule: ClassifierRule = serde_json::from_str(
+        r#"{ "name": "fonts", "mime_patterns": ["font/*"], "category": "Fonts", "confidence": 70 }"#,
+    )
+    .unwrap();
+    let fonts = RuleClassifier::from_rule(&rule).unwrap();
+    assert_eq!(fonts.score("woff2", "font/woff2"), 70);
+    assert_eq!(fonts.score("txt", "text/plain"), 0);
+    assert_eq!(fonts.category().to_string(), "Fonts");
+}
+
+#[tokio::test]
+async fn test_config_classifier_routes_files_to_custom_category() {
+    let dir = tempdir().unwrap();
+    let config_path = dir.path().join("config.json");
+    std::fs::write(
+        &config_path,
+        r#"{ "classifiers": [{ "name": "cad", "extensions": ["dwg"], "category": "CAD", "subfolder": "Drawings" }] }"#,
+    )
+    .unwrap();
+    let src = dir.path().join("src");
+    std::fs::create_dir(&src).unwrap();
+    std::fs::write(src.join("plan.dwg"), b"AC1032").unwrap();
+
+    let options = OrganizeOptions {
+        portable: true,
+        classifiers: OrganizerConfig::load_from_file(&config_path).unwrap().rule_classifiers(),
+        ..Default::default()
+    };
+    let summary = organise_files(&src, &options).await.unwrap();
+
+    assert_eq!(summary.moved, 1);
+    let drawings = src.join("Organized").join("CAD").join("Drawings");
+    let moved: Vec<_> = walkdir::WalkDir::new(&drawings)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_name() == "plan.dwg")
+        .collect();
+    assert_eq!(moved.len(), 1);
+}
+
+#[test]
+fn test_trusted_categories_for_quarantine_stripping() {
+    use stash::config::trusted_categories;
+
+    assert_eq!(trusted_categories(&["images", " Documents"], &[]).unwrap(), BTreeSet::from(["Images", "Documents"]));
+    assert!(trusted_categories(&["executables"], &[]).is_err());
+    assert!(trusted_categories(&["pictures"], &[]).is_err());
+}
+
+#[test]
+fn test_date_patterns_read_dates_from_names() {
+    use stash::filename_date::{DatePatterns, NameDate};
+
+    let patterns = DatePatterns::new(&[
+        r"^SCN_(?P<year>\d{4})(?P<month>\d{2})(?P<day>\d{2})_",
+        r"(?P<year>\d{4})-(?P<month>\d{2})",
+    ])
+    .unwrap();
+    assert_eq!(
+        patterns.extract("SCN_20240131_0001.pdf"),
+        Some(NameDate { year: 2024, month: Some(1), day: Some(31) })
+    );
+    assert_eq!(patterns.extract("invoice 2023-11.pdf"), Some(NameDate { year: 2023, month: Some(11), day: None }));
+    assert_eq!(patterns.extract("SCN_20241399_0001.pdf"), None);
+    assert_eq!(patterns.extract("notes.pdf"), None);
+
+    assert!(DatePatterns::new(&[r"(\d{4})"]).is_err());
+    assert!(DatePatterns::new(&[r"(?P<year>\d{4}"]).is_err());
+}
+
+#[tokio::test]
+async fn test_filename_date_overrides_mtime() {
+    use stash::filename_date::DatePatterns;
+
+    let dir = tempdir().unwrap();
+    std::fs::write(dir.path().join("SCN_20190305_0001.pdf"), b"pdf bytes").unwrap();
+
+    let options = OrganizeOptions {
+        portable: true,
+        date_patterns: DatePatterns::new(&[r"^SCN_(?P<year>\d{4})(?P<month>\d{2})"]).unwrap(),
+        ..Default::default()
+    };
+    organise_files(dir.path(), &options).await.unwrap();
+
+    let expected = dir.path().join("Organized/Documents/Pdf/2019/03/SCN_20190305_0001.pdf");
+    assert!(expected.exists());
+}
+
+#[tokio::test]
+async fn test_date_folders_use_timestamps_and_file_names() {
+    use stash::{filename_date::DatePatterns, path_builder::DateFolders};
+
+    let dir = tempdir().unwrap();
+    let march = chrono::NaiveDate::from_ymd_opt(2023, 3, 7).unwrap().and_hms_opt(12, 0, 0).unwrap().and_utc();
+    std::fs::write(dir.path().join("photo.jpg"), b"jpg bytes").unwrap();
+    std::fs::File::options()
+        .write(true)
+        .open(dir.path().join("photo.jpg"))
+        .unwrap()
+        .set_modified(march.into())
+        .unwrap();
+    std::fs::write(dir.path().join("SCN_2019.pdf"), b"pdf bytes").unwrap();
+
+    let options = OrganizeOptions {
+        portable: true,
+        date_patterns: DatePatterns::new(&[r"^SCN_(?P<year>\d{4})"]).unwrap(),
+        date_folders: Some(DateFolders::YearMonthDay),
+        ..Default::default()
+    };
+    organise_files(dir.path(), &options).await.unwrap();
+
+    assert!(dir.path().join("Organized/Images/Jpeg/2023/03/07/photo.jpg").exists());
+    // The name only gives the year
+    assert!(dir.path().join("Organized/Documents/Pdf/2019/SCN_2019.pdf").exists());
+}
+
+#[tokio::test]
+async fn test_config_names_category_folders() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("config.json");
+    std::fs::write(&path, r#"{ "names": { "images": "Bilder", "Documents": "Dokumente" }, "name_style": "lowercase" }"#).unwrap();
+    let config = OrganizerConfig::load_from_file(&path).unwrap();
+    let names = config.folder_names();
+    assert_eq!((names.category("Images"), names.category("Videos")), ("Bilder".to_string(), "videos".to_string()));
+
+    let root = dir.path().join("inbox");
+    std::fs::create_dir(&root).unwrap();
+    std::fs::write(root.join("holiday.jpg"), "jpeg bytes").unwrap();
+    std::fs::write(root.join("report.pdf"), "pdf").unwrap();
+    let options = OrganizeOptions { portable: true, manifests: true, folder_names: names, ..Default::default() };
+    assert_eq!(organise_files(&root, &options).await.unwrap().moved, 2);
+    assert!(root.join("Organized/Bilder/jpeg").is_dir());
+    assert!(root.join("Organized/Dokumente/pdf").is_dir());
+    assert_eq!(Manifest::read(&root.join("Organized/Bilder")).unwrap().unwrap().category, "Images");
+
+    for bad in [
+        r#"{ "names": { "pictures": "Bilder" } }"#,
+        r#"{ "names": { "images": "Medien/Bilder" } }"#,
+        r#"{ "names": { "images": " " } }"#,
+        r#"{ "names": { "images": "Videos" } }"#,
+        r#"{ "name_style": "camelcase" }"#,
+    ] {
+        std::fs::write(&path, bad).unwrap();
+        assert!(OrganizerConfig::load_from_file(&path).is_err(), "{bad}");
+    }
+}
+
+#[tokio::test]
+async fn test_custom_categories_from_config() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("config.json");
+    let config = r#"{
+        "custom_categories": [{
+            "name": "Work",
+            "layout": "{subfolder}/{year}",
+            "rules": [
+                { "extensions": ["dwg"], "subfolder": "Drawings/CAD" },
+                { "name": "plans", "extensions": ["mpp"] }
+            ]
+        }],
+        "categories": { "work": true },
+        "retention": { "Work": "30d" },
+        "display": { "work": { "icon": "💼" } }
+    }"#;
+    std::fs::write(&path, config).unwrap();
+    let config = OrganizerConfig::load_from_file(&path).unwrap();
+    assert_eq!(config.retention_limits().keys().copied().collect::<Vec<_>>(), vec!["Work"]);
+    let classifiers = config.rule_classifiers();
+    assert_eq!(classifiers.iter().map(|c| c.name()).collect::<Vec<_>>(), vec!["Work/Drawings/CAD", "plans"]);
+
+    let root = dir.path().join("inbox");
+    std::fs::create_dir(&root).unwrap();
+    std::fs::write(root.join("plan.dwg"), b"AC1032").unwrap();
+    std::fs::write(root.join("roadmap.mpp"), b"mpp").unwrap();
+    let options = OrganizeOptions { portable: true, classifiers, ..Default::default() };
+    let summary = organise_files(&root, &options).await.unwrap();
+    assert_eq!(summary.moved, 2);
+
+    // Just written, so dated this year
+    let year = chrono::Utc::now().format("%Y").to_string();
+    assert!(root.join("Organized/Work/Drawings/CAD").join(&year).join("plan.dwg").exists());
+    assert!(root.join("Organized/Work").join(&year).join("roadmap.mpp").exists());
+
+    // The index records the category with its levels
+    let db = stash::index::Db::open_portable(&root).await.unwrap();
+    let mut categories: Vec<_> = db.get_all_files().await.unwrap().into_iter().filter_map(|f| f.category).collect();
+    categories.sort();
+    assert_eq!(categories, vec!["Work".to_string(), "Work::Drawings::CAD".to_string()]);
+
+    // Disabled like a built-in category
+    std::fs::write(root.join("other.dwg"), b"AC1032").unwrap();
+    let mut config = OrganizerConfig::load_from_file(&path).unwrap();
+    config.categories.insert("WORK".into(), false);
+    let options = OrganizeOptions {
+        portable: true,
+        classifiers: config.rule_classifiers(),
+        disabled_categories: config.disabled_categories(),
+        ..Default::default()
+    };
+    assert_eq!(organise_files(&root, &options).await.unwrap().moved, 0);
+    assert!(root.join("other.dwg").exists());
+
+    for bad in [
+        r#"{ "custom_categories": [{ "name": "Images", "rules": [{ "extensions": ["dwg"] }] }] }"#,
+        r#"{ "custom_categories": [{ "name": "Work", "rules": [] }] }"#,
+        r#"{ "custom_categories": [{ "name": "Work", "layout": "{week}", "rules": [{ "extensions": ["dwg"] }] }] }"#,
+        r#"{ "custom_categories": [{ "name": "Work", "rules": [{ "extensions": ["dwg"], "subfolder": "a/../b" }] }] }"#,
+        r#"{ "custom_categories": [{ "name": "Work", "rules": [{ "extensions": ["dwg"] }] }, { "name": "work", "rules": [{ "extensions": ["x"] }] }] }"#,
+        r#"{ "retention": { "work": "30d" } }"#,
+    ] {
+        std::fs::write(&path, bad).unwrap();
+        assert!(OrganizerConfig::load_from_file(&path).is_err(), "{bad}");
+    }
+}
+
+#[tokio::test]
+async fn test_extension_overrides_from_config() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("config.json");
+    let config = r#"{
+        "overrides": { "ai": "Design/Illustrator", ".KDBX": "Vault" },
+        "name_style": "lowercase",
+        "retention": { "Vault": "30d" }
+    }"#;
+    std::fs::write(&path, config).unwrap();
+    let config = OrganizerConfig::load_from_file(&path).unwrap();
+    assert_eq!(config.retention_limits().keys().copied().collect::<Vec<_>>(), vec!["Vault"]);
+
+    let root = dir.path().join("inbox");
+    std::fs::create_dir(&root).unwrap();
+    std::fs::write(root.join("logo.AI"), b"%PDF-1.5").unwrap();
+    std::fs::write(root.join("passwords.kdbx"), b"\x03\xd9\xa2\x9a").unwrap();
+    std::fs::write(root.join("notes.txt"), b"notes").unwrap();
+    let options = OrganizeOptions {
+        portable: true,
+        overrides: config.extension_overrides(),
+        folder_names: config.folder_names(),
+        ..Default::default()
+    };
+    assert_eq!(organise_files(&root, &options).await.unwrap().moved, 3);
+
+    // Used as written, without date folders or the name style
+    assert!(root.join("Organized/Design/Illustrator/logo.AI").exists());
+    assert!(root.join("Organized/Vault/passwords.kdbx").exists());
+    let year = chrono::Utc::now().format("%Y").to_string();
+    assert!(root.join("Organized/documents/text").join(&year).join("notes.txt").exists());
+
+    let db = stash::index::Db::open_portable(&root).await.unwrap();
+    let mut categories: Vec<_> = db.get_all_files().await.unwrap().into_iter().filter_map(|f| f.category).collect();
+    categories.sort();
+    assert_eq!(categories, vec!["Design::Illustrator", "Documents::Text", "Vault"]);
+
+    for bad in [
+        r#"{ "overrides": { "ai": "../Design" } }"#,
+        r#"{ "overrides": { "ai": "/abs/Design" } }"#,
+        r#"{ "overrides": { "": "Design" } }"#,
+        r#"{ "overrides": { "ai": "Design", ".AI": "Art" } }"#,
+    ] {
+        std::fs::write(&path, bad).unwrap();
+        assert!(OrganizerConfig::load_from_file(&path).is_err(), "{bad}");
+    }
+}
@@ -0,0 +1,98 @@
+use std::path::PathBuf;
+
+use stash::{
+    chunking::{find_similar, ChunkParams, ChunkSignature, MIN_SIGNED_SIZE},
+    index::Db,
+    organizer::{organise_files, OrganizeOptions},
+};
+use tempfile::tempdir;
+
+const SMALL: ChunkParams = ChunkParams { min: 1 << 10, avg: 4 << 10, max: 16 << 10 };
+
+/// `len` bytes of deterministic noise (xorshift64)
+fn noise(seed: u64, len: usize) -> Vec<u8> {
+    let mut state = seed.max(1);
+    (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as u8
+        })
+        .collect()
+}
+
+fn sign(data: &[u8]) -> ChunkSignature {
+    ChunkSignature::of_reader(data, SMALL).unwrap()
+}
+
+#[test]
+fn test_chunks_follow_content() {
+    let data = noise(1, 1 << 20);
+    let signature = sign(&data);
+    assert_eq!(signature.len(), data.len() as u64);
+    assert!(signature.chunks.len() > 64, "{} chunks", signature.chunks.len());
+    assert!(signature.chunks.iter().all(|chunk| chunk.len as usize <= SMALL.max));
+    assert_eq!(signature, sign(&data));
+
+    // A cut-short copy shares everything but its last chunk, all at the start
+    let partial = sign(&data[..700 << 10]);
+    let similarity = signature.compare(&partial);
+    assert!(similarity.same_start());
+    assert!(similarity.ratio() > 0.95, "{}", similarity.ratio());
+
+    // Bytes put in front shift the content but the boundaries find it again
+    let mut shifted = b"a new header".to_vec();
+    shifted.extend_from_slice(&data);
+    let similarity = signature.compare(&sign(&shifted));
+    assert!(!similarity.same_start());
+    assert!(similarity.ratio() > 0.95, "{}", similarity.ratio());
+
+    assert_eq!(signature.compare(&sign(&noise(2, 1 << 20))).shared, 0);
+}
+
+#[test]
+fn test_signature_bytes_round_trip() {
+    let signature = sign(&noise(3, 100 << 10));
+    assert_eq!(ChunkSignature::from_bytes(&signature.to_bytes()), Some(signature.clone()));
+    assert_eq!(ChunkSignature::from_bytes(&signature.to_bytes()[1..]), None);
+    assert!(ChunkSignature::from_bytes(&[]).unwrap().is_empty());
+}
+
+#[test]
+fn test_find_similar_leaves_out_exact_and_unrelated_files() {
+    let data = noise(4, 512 << 10);
+    let files = vec![
+        (PathBuf::from("movie.mkv"), sign(&data)),
+        (PathBuf::from("movie (copy).mkv"), sign(&data)),
+        (PathBuf::from("movie.mkv.part"), sign(&data[..300 << 10])),
+        (PathBuf::from("other.mkv"), sign(&noise(5, 512 << 10))),
+    ];
+    let pairs = find_similar(&files, 0.5);
+    let names: Vec<(&str, &str)> =
+        pairs.iter().map(|pair| (pair.a.to_str().unwrap(), pair.b.to_str().unwrap())).collect();
+    assert_eq!(names, vec![("movie (copy).mkv", "movie.mkv.part"), ("movie.mkv", "movie.mkv.part")]);
+    assert!(pairs[0].similarity.same_start());
+    assert!(find_similar(&files, 1.01).is_empty());
+}
+
+#[tokio::test]
+async fn test_organize_records_signatures_of_large_files() {
+    let dir = tempdir().unwrap();
+    let size = MIN_SIGNED_SIZE as usize + (1 << 20);
+    let data = noise(6, size);
+    std::fs::write(dir.path().join("talk.mp4"), &data).unwrap();
+    std::fs::write(dir.path().join("talk-reencoded.mp4"), &data[..size - (512 << 10)]).unwrap();
+    std::fs::write(dir.path().join("small.mp4"), &data[..1 << 20]).unwrap();
+
+    let options = OrganizeOptions { portable: true, chunk_signatures: true, ..Default::default() };
+    assert_eq!(organise_files(dir.path(), &options).await.unwrap().moved, 3);
+
+    let db = Db::open_portable(dir.path()).await.unwrap();
+    let signed = db.chunk_signatures().await.unwrap();
+    assert_eq!(signed.len(), 2);
+    assert!(signed.iter().all(|(path, _)| path.exists()));
+    let pairs = find_similar(&signed, 0.9);
+    assert_eq!(pairs.len(), 1);
+    assert!(pairs[0].similarity.same_start());
+}
@@ -0,0 +1,76 @@
+use std::{
+    path::Path,
+    time::{Duration, SystemTime},
+};
+
+use stash::{
+    cleanup::{delete, suggest, CleanupCriteria},
+    index::{Db, DbFileEntry},
+    trash::{list, restore},
+};
+use tempfile::tempdir;
+
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Write an organized file of `size` bytes last modified `age` ago, and index it
+async fn organized(db: &Db, path: &Path, category: &str, size: usize, age: Duration) {
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, vec![0u8; size]).unwrap();
+    std::fs::File::options()
+        .write(true)
+        .open(path)
+        .unwrap()
+        .set_modified(SystemTime::now() - age)
+        .unwrap();
+    let entry = DbFileEntry {
+        path: path.with_file_name(format!("original-{}", path.file_name().unwrap().to_string_lossy())),
+        size: size as u64,
+        modified: None,
+        hash: None,
+        category: Some(category.to_string()),
+        dest_path: path.to_path_buf(),
+    };
+    db.upsert_file_entries(std::slice::from_ref(&entry)).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_suggests_old_large_installers_and_trashes_them() {
+    let dir = tempdir().unwrap();
+    let trash = tempdir().unwrap();
+    let root = dir.path().join("Organized");
+    let db = Db::open_portable(dir.path()).await.unwrap();
+
+    let dmg = root.join("Executables/MacApp/Tool.dmg");
+    let iso = root.join("Archives/Other/distro.iso");
+    organized(&db, &dmg, "Executables::MacApp", 4096, 400 * DAY).await;
+    organized(&db, &iso, "Archives::Other", 8192, 200 * DAY).await;
+    // Too new, too small, not an installer, gone
+    organized(&db, &root.join("Executables/WindowsApp/new.exe"), "Executables::WindowsApp", 8192, DAY).await;
+    organized(&db, &root.join("Archives/Zip/tiny.zip"), "Archives::Zip", 10, 400 * DAY).await;
+    organized(&db, &root.join("Videos/Movies/film.mkv"), "Videos::Movies", 8192, 400 * DAY).await;
+    let gone = root.join("Executables/LinuxApp/gone.deb");
+    organized(&db, &gone, "Executables::LinuxApp", 8192, 400 * DAY).await;
+    std::fs::remove_file(&gone).unwrap();
+
+    let criteria = CleanupCriteria { older_than: 180 * DAY, min_size: 1024 };
+    let suggestions = suggest(&db, &criteria).await.unwrap();
+    let paths: Vec<&Path> = suggestions.0.iter().map(|candidate| candidate.path.as_path()).collect();
+    assert_eq!(paths, vec![iso.as_path(), dmg.as_path()]);
+    assert_eq!(suggestions.reclaimable(), 12288);
+    assert!(suggestions.0[1].age_days >= 399);
+    assert!(suggestions.to_string().contains("reclaimable"));
+
+    let stricter = CleanupCriteria { older_than: 365 * DAY, ..criteria };
+    assert_eq!(suggest(&db, &stricter).await.unwrap().0.len(), 1);
+
+    // Deleting moves them to the trash, where they can be restored from
+    let report = delete(&db, &suggestions, trash.path()).await.unwrap();
+    assert!(report.errors.is_empty());
+    assert_eq!((report.trashed.len(), report.bytes), (2, 12288));
+    assert!(!dmg.exists() && !iso.exists());
+    assert_eq!(list(trash.path()).unwrap()[0].files, 2);
+    assert!(suggest(&db, &criteria).await.unwrap().0.is_empty());
+
+    restore(&db, trash.path(), &[], false).await.unwrap();
+    assert!(dmg.exists() && iso.exists());
+}
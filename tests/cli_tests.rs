@@ -0,0 +1,80 @@
+use clap::Parser;
+use clap_complete::Shell;
+use stash::cli::{write_completions, write_man_page, write_man_pages, Args, Commands};
+use tempfile::tempdir;
+
+#[test]
+fn test_completions_cover_subcommands() {
+    for shell in [Shell::Bash, Shell::Zsh, Shell::Fish, Shell::PowerShell] {
+        let mut out = Vec::new();
+        write_completions(shell, &mut out);
+        let script = String::from_utf8(out).unwrap();
+        assert!(script.contains("organize"), "{shell} completions miss `organize`");
+        assert!(script.contains("dry-run"), "{shell} completions miss `--dry-run`");
+    }
+
+    let args = Args::try_parse_from(["stash", "completions", "fish"]).unwrap();
+    assert!(matches!(args.cmd, Commands::Completions { shell: Shell::Fish }));
+    assert!(Args::try_parse_from(["stash", "completions", "tcsh"]).is_err());
+}
+
+#[test]
+fn test_man_pages() {
+    let mut out = Vec::new();
+    write_man_page(&mut out).unwrap();
+    let page = String::from_utf8(out).unwrap();
+    assert!(page.contains(".TH stash 1"));
+    assert!(page.contains("organize"));
+
+    let dir = tempdir().unwrap();
+    write_man_pages(dir.path()).unwrap();
+    for name in ["stash.1", "stash-organize.1", "stash-db-bench.1", "stash-trash-empty.1"] {
+        assert!(dir.path().join(name).is_file(), "{} was not written", name);
+    }
+}
+
+#[test]
+fn test_first_organize_without_a_terminal_needs_yes() {
+    use std::process::{Command, Stdio};
+
+    let (home, dir) = (tempdir().unwrap(), tempdir().unwrap());
+    std::fs::write(dir.path().join("notes.pdf"), b"pdf bytes").unwrap();
+    let organize = |extra: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_stash"))
+            .args(["organize", "--portable"])
+            .args(extra)
+            .arg(dir.path())
+            .current_dir(home.path())
+            .env("HOME", home.path())
+            .env("XDG_DATA_HOME", home.path())
+            .stdin(Stdio::null())
+            .output()
+            .unwrap()
+    };
+
+    // Watching starts with a full pass, so it asks first too
+    for extra in [&[][..], &["--watch"]] {
+        let output = organize(extra);
+        assert!(!output.status.success());
+        assert!(String::from_utf8_lossy(&output.stderr).contains("--yes"));
+        assert!(dir.path().join("notes.pdf").exists());
+    }
+
+    assert!(organize(&["--yes"]).status.success());
+    assert!(!dir.path().join("notes.pdf").exists());
+}
+
+#[cfg(feature = "tui")]
+#[test]
+fn test_tui_takes_the_organize_flags() {
+    let args = Args::try_parse_from([
+        "stash", "tui", "--exclude", "*.part", "--strip-quarantine", "images", "--transfers-per-device", "2", "--yes", "dir",
+    ])
+    .unwrap();
+    let Commands::Tui { paths, args } = args.cmd else { panic!("not a tui command") };
+    assert_eq!(paths.len(), 1);
+    assert_eq!(args.exclude, ["*.part"]);
+    assert_eq!(args.transfers_per_device, Some(2));
+    assert_eq!(args.strip_quarantine, ["images"]);
+    assert!(args.yes);
+}
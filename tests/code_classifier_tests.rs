@@ -91,7 +91,7 @@ mod tests {
         let _guard = TestFile(path);
 
         let classifier = CodeClassifier;
-        let metadata = classifier.extract_metadata(&path).await.unwrap();
+        let metadata = classifier.extract_metadata(path).await.unwrap();
 
         assert!(matches!(metadata.category, FileCategory::Code(CodeSubcategory::Dockerfile)));
     }
@@ -0,0 +1,64 @@
+use stash::{
+    config::OrganizerConfig,
+    config_schema::{backup_path, validate, CONFIG_VERSION},
+};
+use tempfile::tempdir;
+
+#[test]
+fn test_deprecated_keys_are_migrated_on_load() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("config.json");
+    let original = r#"{ "filename_dates": ["^SCN_(?P<year>\\d{4})"], "exclude": ["*.part"] }"#;
+    std::fs::write(&path, original).unwrap();
+
+    let config = OrganizerConfig::load_from_file(&path).unwrap();
+    assert_eq!(config.date_patterns, vec![r"^SCN_(?P<year>\d{4})".to_string()]);
+    assert_eq!(config.exclude, vec!["*.part".to_string()]);
+
+    // The file now uses the current keys; the original is kept
+    assert_eq!(std::fs::read_to_string(backup_path(&path, 0)).unwrap(), original);
+    let rewritten: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(rewritten["version"], CONFIG_VERSION);
+    assert!(rewritten.get("filename_dates").is_none());
+    assert_eq!(rewritten["date_patterns"][0], r"^SCN_(?P<year>\d{4})");
+
+    // Nothing left to migrate
+    let migrated = std::fs::read_to_string(&path).unwrap();
+    let config = OrganizerConfig::load_from_file(&path).unwrap();
+    assert_eq!(config.version, CONFIG_VERSION);
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), migrated);
+
+    // Both keys set: the current one wins
+    std::fs::write(&path, r#"{ "filename_dates": ["old"], "date_patterns": ["^(?P<year>\\d{4})"] }"#).unwrap();
+    let config = OrganizerConfig::load_from_file(&path).unwrap();
+    assert_eq!(config.date_patterns, vec![r"^(?P<year>\d{4})".to_string()]);
+}
+
+#[test]
+fn test_validate_reports_without_changing_the_file() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("config.json");
+
+    std::fs::write(&path, r#"{ "version": 1, "exclude": ["*.part"], "retention": { "backups": "90d" } }"#).unwrap();
+    let report = validate(&path).unwrap();
+    assert!(report.is_valid() && report.deprecated.is_empty() && report.unknown.is_empty());
+    assert!(report.to_string().contains("valid"));
+
+    let text = r#"{ "filename_dates": [], "exlude": ["*.part"], "categories": { "pictures": false } }"#;
+    std::fs::write(&path, text).unwrap();
+    let report = validate(&path).unwrap();
+    assert_eq!(report.version, 0);
+    assert_eq!(report.deprecated.len(), 1);
+    assert_eq!(report.unknown, vec!["exlude".to_string()]);
+    assert_eq!(report.errors.len(), 1);
+    assert!(report.errors[0].contains("pictures"), "{}", report.errors[0]);
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), text);
+    assert!(!backup_path(&path, 0).exists());
+
+    std::fs::write(&path, r#"{ "version": 99 }"#).unwrap();
+    assert!(!validate(&path).unwrap().is_valid());
+    std::fs::write(&path, r#"{ "exclude": "*.part" }"#).unwrap();
+    assert!(!validate(&path).unwrap().is_valid());
+    std::fs::write(&path, "not json").unwrap();
+    assert!(!validate(&path).unwrap().is_valid());
+}
@@ -0,0 +1,73 @@
+use std::time::Duration;
+
+use stash::{
+    control::RunControl,
+    errors::SkipReason,
+    organizer::{organise_files, OrganizeOptions},
+};
+use tempfile::tempdir;
+
+#[tokio::test]
+async fn test_resumed_waits_while_paused() {
+    let control = RunControl::new();
+    // Running: resolves at once
+    tokio::time::timeout(Duration::from_secs(1), control.resumed()).await.unwrap();
+
+    assert!(control.toggle_pause());
+    let waiter = control.clone();
+    let handle = tokio::spawn(async move { waiter.resumed().await });
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    assert!(!handle.is_finished());
+
+    assert!(!control.toggle_pause());
+    tokio::time::timeout(Duration::from_secs(1), handle).await.unwrap().unwrap();
+}
+
+#[test]
+fn test_toggle_category() {
+    let control = RunControl::new();
+    assert!(control.toggle_category("Images"));
+    assert!(control.is_skipped("Images"));
+    assert!(!control.is_skipped("Documents"));
+    assert!(!control.toggle_category("Images"));
+    assert!(control.skipped().is_empty());
+}
+
+#[tokio::test]
+async fn test_skipped_category_is_left_in_place() {
+    let dir = tempdir().unwrap();
+    std::fs::write(dir.path().join("photo.jpg"), b"jpeg").unwrap();
+    std::fs::write(dir.path().join("notes.txt"), b"text").unwrap();
+
+    let control = RunControl::new();
+    control.skip_category("Images");
+    let options = OrganizeOptions { portable: true, control, ..Default::default() };
+    let summary = organise_files(dir.path(), &options).await.unwrap();
+
+    assert_eq!(summary.moved, 1);
+    assert_eq!(summary.skip_counts[SkipReason::CategoryDisabled.as_index()], 1);
+    assert!(dir.path().join("photo.jpg").exists());
+    assert!(!dir.path().join("notes.txt").exists());
+}
+
+#[tokio::test]
+async fn test_paused_run_starts_no_files_until_resumed() {
+    let dir = tempdir().unwrap();
+    for i in 0..3 {
+        std::fs::write(dir.path().join(format!("doc{}.txt", i)), b"text").unwrap();
+    }
+
+    let control = RunControl::new();
+    control.pause();
+    let options = OrganizeOptions { portable: true, control: control.clone(), ..Default::default() };
+    let root = dir.path().to_path_buf();
+    let run = tokio::spawn(async move { organise_files(&root, &options).await });
+
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    assert!(!run.is_finished());
+    assert!(dir.path().join("doc0.txt").exists());
+
+    control.resume();
+    let summary = tokio::time::timeout(Duration::from_secs(5), run).await.unwrap().unwrap().unwrap();
+    assert_eq!(summary.moved, 3);
+}
@@ -0,0 +1,176 @@
+use std::time::Duration;
+
+use stash::{
+    config::{DaemonConfig, OrganizerConfig},
+    daemon::{state_path, Daemon, DaemonState, DaemonStatus, PidFile},
+};
+use tempfile::tempdir;
+
+#[test]
+fn test_load_daemon_config_with_defaults() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("daemon.json");
+    std::fs::write(&path, r#"{ "directories": [{ "path": "/tmp/in", "portable": true }] }"#).unwrap();
+
+    let config = DaemonConfig::load_from_file(&path).unwrap();
+    assert_eq!(config.interval_secs, 3600);
+    assert!(config.pid_file.is_none());
+    assert_eq!(config.directories.len(), 1);
+    let options = config.directories[0].options(&OrganizerConfig::default());
+    assert!(options.portable);
+    assert!(!options.dry_run);
+}
+
+#[test]
+fn test_zero_interval_is_rejected() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("daemon.json");
+    std::fs::write(&path, r#"{ "interval_secs": 0, "directories": [] }"#).unwrap();
+
+    assert!(DaemonConfig::load_from_file(&path).is_err());
+}
+
+#[test]
+fn test_daemon_schedule_is_read_and_checked() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("daemon.json");
+    std::fs::write(
+        &path,
+        r#"{ "directories": [], "schedule": { "active_hours": ["02:00-06:00"], "pause_on_battery": true } }"#,
+    )
+    .unwrap();
+    let config = DaemonConfig::load_from_file(&path).unwrap();
+    assert_eq!(config.schedule.active_hours.len(), 1);
+    assert!(config.schedule.pause_on_battery);
+
+    std::fs::write(&path, r#"{ "directories": [], "schedule": { "quiet_hours": ["9-5"] } }"#).unwrap();
+    assert!(DaemonConfig::load_from_file(&path).is_err());
+}
+
+#[test]
+fn test_pid_file_lifecycle() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("run").join("stash.pid");
+
+    let pid_file = PidFile::create(&path).unwrap();
+    let written = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(written.trim(), std::process::id().to_string());
+
+    drop(pid_file);
+    assert!(!path.exists());
+
+    // A stale PID (no such process) is overwritten
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(&path, "999999999").unwrap();
+    let _pid_file = PidFile::create(&path).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn test_pid_file_refuses_live_process() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("stash.pid");
+    // PID 1 always exists
+    std::fs::write(&path, "1").unwrap();
+
+    assert!(PidFile::create(&path).is_err());
+    assert!(path.exists());
+}
+
+#[tokio::test]
+async fn test_daemon_organizes_until_shutdown() {
+    let root = tempdir().unwrap();
+    std::fs::write(root.path().join("notes.pdf"), b"pdf bytes").unwrap();
+
+    let config_dir = tempdir().unwrap();
+    let config_path = config_dir.path().join("daemon.json");
+    let json = serde_json::json!({
+        "interval_secs": 3600,
+        "directories": [{ "path": root.path(), "portable": true }],
+    });
+    std::fs::write(&config_path, json.to_string()).unwrap();
+
+    let daemon = Daemon::new(DaemonConfig::load_from_file(&config_path).unwrap());
+    tokio::time::timeout(
+        Duration::from_secs(10),
+        daemon.run_until(tokio::time::sleep(Duration::from_millis(200))),
+    )
+    .await
+    .expect("daemon did not stop on shutdown")
+    .unwrap();
+
+    assert!(!root.path().join("notes.pdf").exists());
+    assert!(root.path().join("Organized").join("Documents").exists());
+}
+
+#[tokio::test]
+async fn test_daemon_pauses_and_reports_why() {
+    let root = tempdir().unwrap();
+    std::fs::write(root.path().join("notes.pdf"), b"pdf bytes").unwrap();
+
+    let config_dir = tempdir().unwrap();
+    let config_path = config_dir.path().join("daemon.json");
+    let json = serde_json::json!({
+        "interval_secs": 3600,
+        "directories": [{ "path": root.path(), "portable": true }],
+        "schedule": { "min_free_space": u64::MAX },
+    });
+    std::fs::write(&config_path, json.to_string()).unwrap();
+
+    let pid_path = config_dir.path().join("stash.pid");
+    let state_file = state_path(&pid_path);
+    assert_eq!(state_file, config_dir.path().join("stash.state.json"));
+    let daemon = Daemon::new(DaemonConfig::load_from_file(&config_path).unwrap()).with_state_file(&state_file);
+
+    let watched = state_file.clone();
+    let shutdown = async move {
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        let state = DaemonState::read(&watched).unwrap();
+        assert_eq!(state.pid, std::process::id());
+        assert!(state.paused.unwrap().starts_with("low disk space"));
+        assert!(state.paused_since.is_some());
+        assert!(state.last_pass.is_none());
+
+        // `daemon status` reads it while the PID file names a live process
+        std::fs::write(&pid_path, std::process::id().to_string()).unwrap();
+        let status = DaemonStatus::read(&pid_path).to_string();
+        assert!(status.contains("paused, low disk space"), "{}", status);
+    };
+    tokio::time::timeout(Duration::from_secs(10), daemon.run_until(shutdown)).await.unwrap().unwrap();
+
+    assert!(root.path().join("notes.pdf").exists());
+    assert!(!state_file.exists());
+    assert!(DaemonStatus::read(&config_dir.path().join("missing.pid")).to_string().contains("not running"));
+}
+
+#[tokio::test]
+async fn test_daemon_organizes_with_the_organizer_config() {
+    let (protected, excluding) = (tempdir().unwrap(), tempdir().unwrap());
+    for root in [&protected, &excluding] {
+        std::fs::write(root.path().join("notes.pdf"), b"pdf bytes").unwrap();
+        std::fs::write(root.path().join("photo.jpg"), b"jpg bytes").unwrap();
+    }
+
+    let config_dir = tempdir().unwrap();
+    let config_path = config_dir.path().join("daemon.json");
+    let json = serde_json::json!({
+        "directories": [
+            { "path": protected.path(), "portable": true },
+            { "path": excluding.path(), "portable": true },
+        ],
+    });
+    std::fs::write(&config_path, json.to_string()).unwrap();
+    let organizer_path = config_dir.path().join("config.json");
+    let json = serde_json::json!({ "protected": [protected.path()], "exclude": ["*.pdf"] });
+    std::fs::write(&organizer_path, json.to_string()).unwrap();
+
+    let daemon = Daemon::new(DaemonConfig::load_from_file(&config_path).unwrap())
+        .with_organizer_config(OrganizerConfig::load_from_file(&organizer_path).unwrap());
+    daemon.run_once().await;
+
+    // The guard refuses the protected directory outright
+    assert!(protected.path().join("notes.pdf").exists());
+    assert!(protected.path().join("photo.jpg").exists());
+    assert!(excluding.path().join("notes.pdf").exists());
+    assert!(!excluding.path().join("photo.jpg").exists());
+}
@@ -19,6 +19,7 @@ async fn test_update_and_lookup_file() {
         is_file: true,
         is_dir: false,
         is_symlink: false,
+        owner: None,
     };
 
     db.update_file(&meta, "text", Path::new("dest/testfile.txt"), "hash123")
@@ -59,6 +60,7 @@ async fn test_concurrent_writes_are_safe() {
                 is_file: true,
                 is_dir: false,
                 is_symlink: false,
+                owner: None,
             };
 
             db.update_file(&meta, "text", Path::new(&format!("dest_{i}.txt")), &format!("hash{i}"))
@@ -115,6 +117,7 @@ async fn test_concurrent_updates_on_same_file() {
                 is_file: true,
                 is_dir: false,
                 is_symlink: false,
+                owner: None,
             };
 
             let dest = Path::new(&format!("dest_{i}.bin")).to_path_buf();
@@ -146,3 +149,189 @@ async fn test_concurrent_updates_on_same_file() {
     tokio::fs::remove_file(path).await.unwrap();
 }
 
+
+#[tokio::test]
+async fn test_portable_db_survives_root_move() {
+    let parent = tempfile::tempdir().unwrap();
+    let old_root = parent.path().join("usb1");
+    let new_root = parent.path().join("usb2");
+    tokio::fs::create_dir_all(&old_root).await.unwrap();
+
+    let src = old_root.join("report.pdf");
+    tokio::fs::write(&src, "pdf").await.unwrap();
+
+    let db = Db::open_portable(&old_root).await.unwrap();
+    let meta = RawFileMetadata {
+        path: src.clone(),
+        size: 3,
+        created: None,
+        modified: Some(SystemTime::now()),
+        accessed: None,
+        permissions: tokio::fs::metadata(&src).await.unwrap().permissions(),
+        is_file: true,
+        is_dir: false,
+        is_symlink: false,
+        owner: None,
+    };
+    let dest = old_root.join("Organized/Documents/Pdf/report.pdf");
+    db.update_file(&meta, "Documents::Pdf", &dest, "hash").await.unwrap();
+    drop(db);
+
+    // Simulate the drive being mounted somewhere else
+    tokio::fs::rename(&old_root, &new_root).await.unwrap();
+
+    let db = Db::open_portable(&new_root).await.unwrap();
+    let entries = db.get_all_files().await.unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].path, new_root.join("report.pdf"));
+    assert_eq!(entries[0].dest_path, new_root.join("Organized/Documents/Pdf/report.pdf"));
+}
+
+#[tokio::test]
+async fn test_cached_hash_requires_matching_size_and_mtime() {
+    let db = Db::new(Path::new(":memory:")).await.unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("cached.txt");
+    tokio::fs::write(&path, "hello").await.unwrap();
+
+    let modified = SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_700_000_000);
+    let meta = RawFileMetadata {
+        path: path.clone(),
+        size: 5,
+        created: None,
+        modified: Some(modified),
+        accessed: None,
+        permissions: tokio::fs::metadata(&path).await.unwrap().permissions(),
+        is_file: true,
+        is_dir: false,
+        is_symlink: false,
+        owner: None,
+    };
+    db.update_file(&meta, "text", &path, "abc123").await.unwrap();
+
+    assert_eq!(db.cached_hash(&path, 5, Some(modified)).await.unwrap().as_deref(), Some("abc123"));
+
+    // Changed size, changed mtime or unknown mtime all force a re-hash
+    assert!(db.cached_hash(&path, 6, Some(modified)).await.unwrap().is_none());
+    let later = modified + std::time::Duration::from_secs(10);
+    assert!(db.cached_hash(&path, 5, Some(later)).await.unwrap().is_none());
+    assert!(db.cached_hash(&path, 5, None).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_index_stats_aggregate_organized_files() {
+    use stash::{history::IndexStats, organizer::{organise_files, OrganizeOptions}};
+
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("a.pdf"), b"small").unwrap();
+    std::fs::write(dir.path().join("b.PDF"), vec![b'x'; 4096]).unwrap();
+    std::fs::write(dir.path().join("c.txt"), b"some text").unwrap();
+    organise_files(dir.path(), &OrganizeOptions { portable: true, ..Default::default() }).await.unwrap();
+
+    let db = Db::open_portable(dir.path()).await.unwrap();
+    let stats = IndexStats::collect(&db, 10).await.unwrap();
+
+    assert_eq!(stats.categories.iter().map(|c| c.files).sum::<u64>(), 3);
+    assert_eq!(stats.categories.iter().map(|c| c.bytes).sum::<u64>(), 5 + 4096 + 9);
+    assert_eq!(stats.moves_per_day.len(), 1);
+    assert_eq!(stats.moves_per_day[0].moves, 3);
+    assert_eq!((stats.top_extensions[0].extension.as_str(), stats.top_extensions[0].files), ("pdf", 2));
+    assert_eq!(stats.largest[0].path, dir.path().join("b.PDF"));
+    assert_eq!(stats.largest.len(), 3);
+
+    let json = serde_json::to_value(&stats).unwrap();
+    assert_eq!(json["top_extensions"][0]["extension"], "pdf");
+
+    let limited = IndexStats::collect(&db, 1).await.unwrap();
+    assert_eq!(limited.largest.len(), 1);
+}
+
+#[tokio::test]
+async fn test_run_history_sums_every_run() {
+    use stash::{history::RunHistory, index::RunStats, organizer::{organise_files, OrganizeOptions}};
+
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("a.pdf"), b"small").unwrap();
+    std::fs::write(dir.path().join(".hidden"), b"x").unwrap();
+    let options = OrganizeOptions { portable: true, ..Default::default() };
+    organise_files(dir.path(), &options).await.unwrap();
+    std::fs::write(dir.path().join("c.txt"), b"some text").unwrap();
+    organise_files(dir.path(), &options).await.unwrap();
+    // Nothing left to move: still a run, without an entry in `runs`
+    organise_files(dir.path(), &options).await.unwrap();
+    organise_files(dir.path(), &OrganizeOptions { dry_run: true, ..options.clone() }).await.unwrap();
+
+    let db = Db::open_portable(dir.path()).await.unwrap();
+    let runs = db.all_run_stats().await.unwrap();
+    assert_eq!(runs.len(), 3);
+    assert!(runs[0].run_id.is_some() && runs[2].run_id.is_none());
+    assert!(runs.iter().all(|run| run.duration_ms.is_some() && run.skipped >= 1));
+
+    let history = RunHistory::collect(&db).await.unwrap();
+    assert_eq!((history.total.runs, history.total.moved, history.total.bytes), (3, 2, 5 + 9));
+    assert_eq!(history.months.len(), 1);
+    assert_eq!(history.untimed_runs, 0);
+
+    // Runs from different months, one recorded before durations were
+    let old = |finished_at, duration_ms| RunStats { finished_at, moved: 1, bytes: 10, duration_ms, ..Default::default() };
+    let history = RunHistory::from_runs(&[old(1_700_000_000, None), old(1_700_000_100, Some(500)), old(1_710_000_000, Some(1500))]);
+    assert_eq!(history.months.iter().map(|m| m.runs).collect::<Vec<_>>(), [2, 1]);
+    assert_eq!(history.total.duration_secs, 2.0);
+    assert_eq!(history.untimed_runs, 1);
+
+    let mut csv = Vec::new();
+    history.write_csv(&mut csv).unwrap();
+    let csv = String::from_utf8(csv).unwrap();
+    let mut lines = csv.lines();
+    assert_eq!(lines.next(), Some("period,runs,processed,moved,skipped,duplicates,errors,bytes,duration_secs"));
+    assert_eq!(lines.count(), 2);
+}
+
+/// An organized file `name` under `category`, from `/in` to `/out/<category>`
+fn organized(name: &str, category: &str, hash: &str) -> stash::index::DbFileEntry {
+    stash::index::DbFileEntry {
+        path: PathBuf::from("/in").join(name),
+        size: 1,
+        modified: None,
+        hash: Some(hash.to_string()),
+        category: Some(category.to_string()),
+        dest_path: PathBuf::from("/out").join(category.replace("::", "/")).join(name),
+    }
+}
+
+#[tokio::test]
+async fn test_query_by_hash_category_and_pattern() {
+    let db = Db::new(Path::new(":memory:")).await.unwrap();
+    db.upsert_file_entries(&[
+        organized("invoice-01.pdf", "Documents::Pdf", "aaa"),
+        organized("invoice-02.pdf", "Documents::Pdf", "bbb"),
+        organized("copy of invoice-01.pdf", "Documents::Pdf", "aaa"),
+        organized("notes_2024.txt", "Documents::Text", "ccc"),
+        organized("beach.jpg", "Images", "ddd"),
+        organized("Docs%.txt", "DocumentsExtra", "eee"),
+    ])
+    .await
+    .unwrap();
+
+    let paths = |entries: Vec<stash::index::DbFileEntry>| -> Vec<String> {
+        entries.iter().map(|e| e.path.file_name().unwrap().to_string_lossy().into_owned()).collect()
+    };
+
+    let copies = db.find_by_hash("aaa", Path::new("/in/invoice-01.pdf")).await.unwrap();
+    assert_eq!(paths(copies), ["copy of invoice-01.pdf"]);
+
+    let pdfs = db.find_by_category("documents::pdf").await.unwrap();
+    assert_eq!(pdfs.len(), 3);
+    let documents = db.find_by_category("Documents").await.unwrap();
+    assert_eq!(paths(documents), ["copy of invoice-01.pdf", "invoice-01.pdf", "invoice-02.pdf", "notes_2024.txt"]);
+    assert!(db.find_by_category("Document").await.unwrap().is_empty());
+
+    assert_eq!(paths(db.search("*/invoice-0?.pdf").await.unwrap()), ["invoice-01.pdf", "invoice-02.pdf"]);
+    assert_eq!(paths(db.search("BEACH").await.unwrap()), ["beach.jpg"]);
+    // Matches the organized path too
+    assert_eq!(db.search("/out/Images/*").await.unwrap().len(), 1);
+    // `%` and `_` are literal
+    assert_eq!(paths(db.search("s%").await.unwrap()), ["Docs%.txt"]);
+    assert_eq!(paths(db.search("s_2024").await.unwrap()), ["notes_2024.txt"]);
+    assert!(db.search("*.docx").await.unwrap().is_empty());
+}
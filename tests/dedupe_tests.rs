@@ -0,0 +1,243 @@
+use std::path::PathBuf;
+
+use stash::{
+    dedupe::{apply, parse_report, undo, DedupeAction, DuplicateSet, ReportFormat},
+    index::Db,
+};
+use tempfile::tempdir;
+
+fn set(paths: &[&str]) -> DuplicateSet {
+    DuplicateSet { files: paths.iter().map(PathBuf::from).collect() }
+}
+
+#[test]
+fn test_parse_fdupes_text() {
+    let report = "/a/one.txt\n/b/one.txt\n\n/a/two.txt\n/b/two.txt\n/c/two.txt\n\n/lonely.txt\n";
+    let sets = parse_report(report, ReportFormat::Auto).unwrap();
+    assert_eq!(sets, vec![
+        set(&["/a/one.txt", "/b/one.txt"]),
+        set(&["/a/two.txt", "/b/two.txt", "/c/two.txt"]),
+    ]);
+}
+
+#[test]
+fn test_parse_jdupes_json() {
+    let report = r#"{
+        "jdupesVersion": "1.27",
+        "matchSets": [
+            { "fileSize": 3, "fileList": [ { "filePath": "/a/x" }, { "filePath": "/b/x" } ] }
+        ]
+    }"#;
+    let sets = parse_report(report, ReportFormat::Auto).unwrap();
+    assert_eq!(sets, vec![set(&["/a/x", "/b/x"])]);
+}
+
+#[test]
+fn test_parse_czkawka_json() {
+    let report = r#"{
+        "3": [
+            [ { "path": "/a/x", "size": 3 }, { "path": "/b/x", "size": 3 } ],
+            [ { "path": "/a/y", "size": 3 }, { "path": "/b/y", "size": 3 } ]
+        ]
+    }"#;
+    let sets = parse_report(report, ReportFormat::CzkawkaJson).unwrap();
+    assert_eq!(sets, vec![set(&["/a/x", "/b/x"]), set(&["/a/y", "/b/y"])]);
+}
+
+#[test]
+fn test_parse_format_names() {
+    assert_eq!("jdupes".parse::<ReportFormat>().unwrap(), ReportFormat::Fdupes);
+    assert_eq!("czkawka-json".parse::<ReportFormat>().unwrap(), ReportFormat::CzkawkaJson);
+    assert!("dupeguru".parse::<ReportFormat>().is_err());
+}
+
+#[tokio::test]
+async fn test_trash_duplicates_and_undo() {
+    let dir = tempdir().unwrap();
+    let trash = tempdir().unwrap();
+    let keep = dir.path().join("keep.txt");
+    let dup = dir.path().join("dup.txt");
+    let changed = dir.path().join("changed.txt");
+    std::fs::write(&keep, b"same").unwrap();
+    std::fs::write(&dup, b"same").unwrap();
+    std::fs::write(&changed, b"different now").unwrap();
+
+    let db = Db::open_portable(dir.path()).await.unwrap();
+    let sets = vec![DuplicateSet {
+        files: vec![keep.clone(), dup.clone(), changed.clone(), dir.path().join("gone.txt")],
+    }];
+
+    let dry = apply(&db, &sets, DedupeAction::Trash, true, trash.path()).await.unwrap();
+    assert_eq!(dry.acted, 1);
+    assert!(dup.exists());
+    assert!(db.list_actions(true).await.unwrap().is_empty());
+
+    let outcome = apply(&db, &sets, DedupeAction::Trash, false, trash.path()).await.unwrap();
+    assert_eq!(outcome.acted, 1);
+    assert_eq!(outcome.bytes_freed, 4);
+    assert_eq!(outcome.changed, 1);
+    assert_eq!(outcome.missing, 1);
+    assert!(!dup.exists());
+    assert!(keep.exists() && changed.exists());
+
+    let actions = db.list_actions(false).await.unwrap();
+    assert_eq!(actions.len(), 1);
+    assert_eq!(actions[0].kind, "trash");
+    assert_eq!(actions[0].path, dup);
+    assert!(actions[0].target.starts_with(trash.path()));
+
+    assert_eq!(undo(&db, &[]).await.unwrap(), 1);
+    assert_eq!(std::fs::read(&dup).unwrap(), b"same");
+    assert!(db.list_actions(false).await.unwrap().is_empty());
+    assert!(db.list_actions(true).await.unwrap()[0].reverted_at.is_some());
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_hardlink_duplicates_and_undo() {
+    use std::os::unix::fs::MetadataExt;
+
+    let dir = tempdir().unwrap();
+    let keep = dir.path().join("keep.bin");
+    let dup = dir.path().join("dup.bin");
+    std::fs::write(&keep, b"payload").unwrap();
+    std::fs::write(&dup, b"payload").unwrap();
+
+    let db = Db::open_portable(dir.path()).await.unwrap();
+    let sets = vec![DuplicateSet { files: vec![keep.clone(), dup.clone()] }];
+
+    let outcome = apply(&db, &sets, DedupeAction::Hardlink, false, dir.path()).await.unwrap();
+    assert_eq!(outcome.acted, 1);
+    assert_eq!(std::fs::metadata(&keep).unwrap().ino(), std::fs::metadata(&dup).unwrap().ino());
+
+    // Already linked: a second import is a no-op
+    let again = apply(&db, &sets, DedupeAction::Hardlink, false, dir.path()).await.unwrap();
+    assert_eq!(again.acted, 0);
+
+    assert_eq!(undo(&db, &[]).await.unwrap(), 1);
+    assert_ne!(std::fs::metadata(&keep).unwrap().ino(), std::fs::metadata(&dup).unwrap().ino());
+    assert_eq!(std::fs::read(&dup).unwrap(), b"payload");
+}
+
+#[tokio::test]
+async fn test_organize_trashes_copies_of_organized_files() {
+    use stash::{
+        organizer::{organise_files, DuplicateAction, OrganizeOptions},
+        stats::FileOutcome,
+        trash,
+    };
+
+    let dir = tempdir().unwrap();
+    let trash_root = tempdir().unwrap();
+    let root = dir.path();
+    std::fs::write(root.join("report.pdf"), b"%PDF-1.4 quarterly report").unwrap();
+    organise_files(root, &OrganizeOptions { portable: true, ..Default::default() }).await.unwrap();
+
+    std::fs::write(root.join("report (1).pdf"), b"%PDF-1.4 quarterly report").unwrap();
+    std::fs::write(root.join("other.pdf"), b"%PDF-1.4 something else").unwrap();
+    let options = OrganizeOptions {
+        portable: true,
+        dedupe_on_organize: Some(DuplicateAction::Trash),
+        trash_dir: Some(trash_root.path().to_path_buf()),
+        ..Default::default()
+    };
+    let summary = organise_files(root, &options).await.unwrap();
+
+    assert_eq!(summary.duplicates, 1);
+    assert_eq!(summary.moved, 1);
+    assert!(!root.join("report (1).pdf").exists());
+    let runs = trash::list(trash_root.path()).unwrap();
+    assert_eq!(runs.len(), 1);
+    assert_eq!(runs[0].files, 1);
+
+    // Read without its root, the portable index only has relative paths;
+    // nothing is restored into the working directory
+    let db = Db::new(&stash::utils::portable_db_path(root)).await.unwrap();
+    let report = trash::restore(&db, trash_root.path(), &[], false).await.unwrap();
+    assert!(report.restored.is_empty());
+    assert_eq!(report.errors.len(), 1);
+    drop(db);
+
+    // Trashed copies come back like any other
+    let db = Db::open_portable(root).await.unwrap();
+    let report = trash::restore(&db, trash_root.path(), &[], false).await.unwrap();
+    assert_eq!(report.restored.len(), 1);
+    assert!(root.join("report (1).pdf").exists());
+
+    // The outcome names the file it was a copy of
+    let outcome = FileOutcome::DuplicateSkipped {
+        src: root.join("a.pdf"),
+        existing: root.join("b.pdf"),
+        trashed: None,
+        size: 1,
+    };
+    assert!(outcome.to_string().contains("b.pdf"));
+}
+
+#[tokio::test]
+async fn test_organize_deletes_copies_but_not_of_changed_files() {
+    use stash::organizer::{organise_files, DuplicateAction, OrganizeOptions};
+
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    std::fs::write(root.join("a.txt"), b"first notes").unwrap();
+    std::fs::write(root.join("b.txt"), b"second notes").unwrap();
+    organise_files(root, &OrganizeOptions { portable: true, ..Default::default() }).await.unwrap();
+
+    // The organized `b.txt` changed since, so it is no longer the original of anything
+    let organized_b = walkdir::WalkDir::new(root.join("Organized"))
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .find(|e| e.file_name() == "b.txt")
+        .unwrap()
+        .into_path();
+    std::fs::write(&organized_b, b"second notes, edited").unwrap();
+
+    std::fs::write(root.join("a copy.txt"), b"first notes").unwrap();
+    std::fs::write(root.join("b copy.txt"), b"second notes").unwrap();
+    let options = OrganizeOptions {
+        portable: true,
+        dedupe_on_organize: Some(DuplicateAction::Delete),
+        ..Default::default()
+    };
+    let summary = organise_files(root, &options).await.unwrap();
+
+    assert_eq!(summary.duplicates, 1);
+    assert!(!root.join("a copy.txt").exists());
+    assert!(organized_b.with_file_name("b copy.txt").exists());
+    assert_eq!("quarantine".parse::<DuplicateAction>().unwrap(), DuplicateAction::Trash);
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_organize_never_deletes_the_organized_file_through_a_link() {
+    use stash::{
+        organizer::{organise_files, DuplicateAction, OrganizeOptions},
+        scanner::SymlinkPolicy,
+    };
+
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    std::fs::write(root.join("notes.txt"), b"only copy").unwrap();
+    organise_files(root, &OrganizeOptions { portable: true, ..Default::default() }).await.unwrap();
+    let organized = walkdir::WalkDir::new(root.join("Organized"))
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .find(|e| e.file_name() == "notes.txt")
+        .unwrap()
+        .into_path();
+
+    // A folder linking into the organized tree
+    std::os::unix::fs::symlink(organized.parent().unwrap(), root.join("shortcut")).unwrap();
+    let options = OrganizeOptions {
+        portable: true,
+        max_depth: Some(2),
+        symlinks: SymlinkPolicy::Follow,
+        dedupe_on_organize: Some(DuplicateAction::Delete),
+        ..Default::default()
+    };
+    let summary = organise_files(root, &options).await.unwrap();
+
+    assert_eq!(summary.duplicates, 0);
+    assert_eq!(std::fs::read(&organized).unwrap(), b"only copy");
+}
@@ -0,0 +1,57 @@
+mod test_utils;
+
+#[cfg(test)]
+mod tests {
+    use stash::{design_classifier::DesignClassifier, registry::Classifier};
+    use stash::metadata::{DesignSubcategory, FileCategory};
+    use stash::scanner::{ScanConfig, Scanner};
+    use stash::utils::create_classifier_registry;
+
+    use crate::test_utils::create_test_file_with_ext;
+
+    #[tokio::test]
+    async fn test_confidence_levels() {
+        let clf = DesignClassifier;
+
+        assert_eq!(clf.confidence("psd", "image/vnd.adobe.photoshop"), 100);
+        assert_eq!(clf.confidence("blend", "application/octet-stream"), 100);
+        assert_eq!(clf.confidence("c4d", "application/octet-stream"), 80);
+        assert_eq!(clf.confidence("foo", "model/gltf-binary"), 90);
+        assert_eq!(clf.confidence("png", "image/png"), 0);
+    }
+
+    #[tokio::test]
+    async fn test_extract_metadata_subcategories() {
+        for (ext, expected) in [
+            ("psd", DesignSubcategory::Photoshop),
+            ("ai", DesignSubcategory::Illustrator),
+            ("xd", DesignSubcategory::AdobeXd),
+            ("fig", DesignSubcategory::Figma),
+            ("sketch", DesignSubcategory::Sketch),
+            ("blend", DesignSubcategory::Blender),
+            ("stl", DesignSubcategory::Models),
+            ("gltf", DesignSubcategory::Models),
+        ] {
+            let (_dir, path) = create_test_file_with_ext(ext);
+            let result = DesignClassifier.extract_metadata(&path).await.unwrap();
+
+            match result.category {
+                FileCategory::Design(sub) => assert_eq!(sub, expected, "{ext}"),
+                other => panic!("Expected design category for {ext}, got {other}"),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_registry_prefers_design_over_images_and_documents() {
+        let registry = create_classifier_registry();
+        for ext in ["psd", "ai", "obj", "fbx", "stl", "gltf", "fig"] {
+            let (_dir, path) = create_test_file_with_ext(ext);
+            let config = ScanConfig { max_depth: 0, ..Default::default() };
+            let raw = Scanner::new(&path, config).next().unwrap().unwrap();
+
+            let meta = registry.classify(&raw).await.unwrap();
+            assert_eq!(meta.category.top_level(), "Design", "{ext} classified as {}", meta.category);
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use chrono::NaiveDate;
+use stash::doc_naming::{looks_generic, suggest_name, DocName};
+
+#[test]
+fn test_generic_names() {
+    for stem in ["SCAN_0042", "IMG_20240312_101500", "00017", "Scanned Document 3", "document(1)", "PXL_20240101"] {
+        assert!(looks_generic(stem), "{}", stem);
+    }
+    for stem in ["Electric Bill March", "tax return", "scan of passport"] {
+        assert!(!looks_generic(stem), "{}", stem);
+    }
+}
+
+#[test]
+fn test_bill_gets_title_and_date() {
+    let text = "ACME POWER CO.\n123 Main St\n\nELECTRIC BILL\nStatement date: March 12, 2024\nAmount due: $84.20\n";
+    let name = suggest_name(text).unwrap();
+    assert_eq!(name.title, "Electric Bill");
+    assert_eq!(name.date, NaiveDate::from_ymd_opt(2024, 3, 12));
+    assert_eq!(name.file_name(Some("pdf")), "2024-03-12 Electric Bill.pdf");
+}
+
+#[test]
+fn test_date_formats() {
+    let date = |text: &str| suggest_name(&format!("Invoice\n{}", text)).unwrap().date;
+    assert_eq!(date("Issued 2023-11-05"), NaiveDate::from_ymd_opt(2023, 11, 5));
+    assert_eq!(date("Issued 2023/11/05"), NaiveDate::from_ymd_opt(2023, 11, 5));
+    assert_eq!(date("Issued 5 Nov 2023"), NaiveDate::from_ymd_opt(2023, 11, 5));
+    // Day and month order unknown
+    assert_eq!(date("Issued 05/11/2023"), None);
+    assert_eq!(date("Issued 2023-02-30"), None);
+}
+
+#[test]
+fn test_without_date_or_title() {
+    let name = DocName { date: None, title: "Lease Agreement".into() };
+    assert_eq!(name.file_name(Some("pdf")), "Lease Agreement.pdf");
+    assert_eq!(suggest_name("12/03 4.50\n$$$ ###\n"), None);
+    assert_eq!(suggest_name(""), None);
+}
+
+#[test]
+fn test_titles_are_safe_file_names() {
+    let name = suggest_name("Receipt: order 4/5 <copy>\n").unwrap();
+    assert!(!name.title.contains(['/', ':', '<', '>']), "{}", name.title);
+}
+
+#[cfg(feature = "ocr")]
+#[tokio::test]
+async fn test_named_files_keep_their_name() {
+    use std::path::{Path, PathBuf};
+
+    let dest = PathBuf::from("/out/Documents/Pdf/2024/Electric Bill March.pdf");
+    assert_eq!(stash::ocr::rename(Path::new("/in/Electric Bill March.pdf"), dest.clone()).await, dest);
+}
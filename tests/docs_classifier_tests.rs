@@ -95,6 +95,79 @@ mod tests {
         assert_eq!(meta.mime_type.unwrap(), "application/octet-stream");
     }
 
+    #[test]
+    fn test_ebook_and_comic_subcategories() {
+        for ext in ["epub", "mobi", "azw3", "fb2", "djvu"] {
+            assert_eq!(DocumentClassifier::score(ext, "application/octet-stream"), 100, "{ext}");
+            assert!(matches!(DocumentClassifier::subcategory(ext), DocumentSubcategory::Ebook), "{ext}");
+        }
+        for ext in ["cbz", "cbr", "cb7"] {
+            assert_eq!(DocumentClassifier::score(ext, "application/octet-stream"), 100, "{ext}");
+            assert!(matches!(DocumentClassifier::subcategory(ext), DocumentSubcategory::Comic), "{ext}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_registry_keeps_books_out_of_images_and_archives() {
+        use stash::{scanner::{ScanConfig, Scanner}, utils::create_classifier_registry};
+
+        let dir = tempfile::tempdir().unwrap();
+        let registry = create_classifier_registry();
+        for (name, expect_comic) in [("book.djvu", false), ("issue.cbz", true), ("issue.cbr", true)] {
+            let path = dir.path().join(name);
+            fs::write(&path, b"data").await.unwrap();
+            let config = ScanConfig { max_depth: 0, ..Default::default() };
+            let raw = Scanner::new(&path, config).next().unwrap().unwrap();
+
+            let meta = registry.classify(&raw).await.unwrap();
+            match meta.category {
+                FileCategory::Documents(DocumentSubcategory::Comic) => assert!(expect_comic, "{name}"),
+                FileCategory::Documents(DocumentSubcategory::Ebook) => assert!(!expect_comic, "{name}"),
+                other => panic!("{name} classified as {other}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_opf_metadata() {
+        use stash::docs_classifier::{opf_path, parse_opf};
+
+        let container = r#"<?xml version="1.0"?>
+            <container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+              <rootfiles><rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/></rootfiles>
+            </container>"#;
+        assert_eq!(opf_path(container).as_deref(), Some("OEBPS/content.opf"));
+
+        let opf = r#"<package><metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+              <dc:title id="t1">Pride &amp; Prejudice</dc:title>
+              <dc:creator opf:role="aut">Jane Austen</dc:creator>
+              <dc:creator>  </dc:creator>
+            </metadata></package>"#;
+        let info = parse_opf(opf);
+        assert_eq!(info.title.as_deref(), Some("Pride & Prejudice"));
+        assert_eq!(info.authors, vec!["Jane Austen".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_extract_metadata_epub_title_and_author() {
+        use std::io::Write;
+        use stash::metadata::ExtraMetadataValue;
+
+        let tmp = Builder::new().suffix(".epub").tempfile().unwrap();
+        let mut epub = zip::ZipWriter::new(std::fs::File::create(tmp.path()).unwrap());
+        let options = zip::write::SimpleFileOptions::default();
+        epub.start_file("META-INF/container.xml", options).unwrap();
+        epub.write_all(br#"<container><rootfiles><rootfile full-path="content.opf"/></rootfiles></container>"#).unwrap();
+        epub.start_file("content.opf", options).unwrap();
+        epub.write_all(br#"<package><metadata><dc:title>Dune</dc:title><dc:creator>Frank Herbert</dc:creator></metadata></package>"#).unwrap();
+        epub.finish().unwrap();
+
+        let meta = DocumentClassifier.extract_metadata(tmp.path()).await.unwrap();
+        assert!(matches!(meta.category, FileCategory::Documents(DocumentSubcategory::Ebook)));
+        assert!(matches!(meta.extra.get("title"), Some(ExtraMetadataValue::String(t)) if t == "Dune"));
+        assert!(matches!(meta.extra.get("author"), Some(ExtraMetadataValue::String(a)) if a == "Frank Herbert"));
+    }
+
     // ---------------------------
     // Optional property tests
     // ---------------------------
@@ -0,0 +1,137 @@
+use std::path::Path;
+
+use stash::{
+    config::OrganizerConfig,
+    errors::SkipReason,
+    exclusions::{ExcludeGlobs, Exclusions, IN_PLACE_MARKER},
+    organizer::{organise_files, OrganizeOptions, Organizer},
+    reverter::{revert_files, RevertOptions},
+};
+use tempfile::tempdir;
+
+#[test]
+fn test_globs_match_names_or_relative_paths() {
+    let globs = ExcludeGlobs::new(&["*.part", "node_modules", "Projects/**/*.psd", "?.tmp"]).unwrap();
+
+    assert!(globs.matches(Path::new("movie.mkv.part")));
+    assert!(globs.matches(Path::new("web/node_modules/react/index.js")));
+    assert!(globs.matches(Path::new("Projects/logo.psd")));
+    assert!(globs.matches(Path::new("Projects/2024/client/logo.psd")));
+    assert!(globs.matches(Path::new("a.tmp")));
+
+    assert!(!globs.matches(Path::new("movie.mkv")));
+    assert!(!globs.matches(Path::new("Archive/Projects/logo.psd")));
+    assert!(!globs.matches(Path::new("ab.tmp")));
+
+    assert!(ExcludeGlobs::new(&["/"]).is_err());
+}
+
+#[test]
+fn test_own_outputs_are_always_excluded() {
+    let root = Path::new("/home/ada/Downloads");
+    let exclusions = Exclusions::new(root).dir(root.join("Organized")).file(root.join("status.json"));
+
+    for path in [
+        "Organized/Documents/Pdf/report.pdf",
+        ".file_organizer/file_organizer.db",
+        "file_organizer.db-wal",
+        "file_organizer.db-journal",
+        "file_organizer.log.2025-01-31",
+        "status.json",
+    ] {
+        assert!(exclusions.excludes(&root.join(path)), "{path}");
+    }
+    assert!(!exclusions.excludes(&root.join("report.pdf")));
+    assert!(!exclusions.excludes(&root.join("notes.db")));
+
+    // A destination equal to the root does not exclude everything
+    let exclusions = Exclusions::new(root).dir(root);
+    assert!(!exclusions.excludes(&root.join("report.pdf")));
+}
+
+#[test]
+fn test_config_exclude_is_validated() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("config.json");
+    std::fs::write(&path, r#"{ "exclude": ["*.part", "Projects/**"] }"#).unwrap();
+    assert!(!OrganizerConfig::load_from_file(&path).unwrap().exclude_globs().is_empty());
+
+    std::fs::write(&path, r#"{ "exclude": [""] }"#).unwrap();
+    assert!(OrganizerConfig::load_from_file(&path).is_err());
+}
+
+#[tokio::test]
+async fn test_organize_leaves_own_files_and_globs_alone() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    std::fs::create_dir_all(root.join("logs")).unwrap();
+    std::fs::create_dir_all(root.join("node_modules/pkg")).unwrap();
+    for file in [
+        "report.pdf",
+        "movie.mkv.part",
+        "file_organizer.db",
+        "file_organizer.db-wal",
+        "logs/file_organizer.log.2025-01-31",
+        "node_modules/pkg/index.js",
+    ] {
+        std::fs::write(root.join(file), file).unwrap();
+    }
+
+    let status = root.join("last_run.json");
+    let summary = Organizer::builder(root)
+        .options(OrganizeOptions { status_file: Some(status.clone()), ..Default::default() })
+        .portable(true)
+        .max_depth(5)
+        .exclude("*.part")
+        .exclude("node_modules")
+        .build()
+        .unwrap()
+        .run()
+        .await
+        .unwrap();
+
+    assert_eq!(summary.moved, 1);
+    // The part file, the database and its WAL, the log and `node_modules` itself
+    assert_eq!(summary.skip_counts[SkipReason::Excluded.as_index()], 5);
+    for file in ["movie.mkv.part", "file_organizer.db-wal", "logs/file_organizer.log.2025-01-31", "node_modules/pkg/index.js"] {
+        assert!(root.join(file).exists(), "{file}");
+    }
+
+    // The status file written by the first run is not organized by the next
+    assert!(status.exists());
+    let options = OrganizeOptions { portable: true, status_file: Some(status.clone()), ..Default::default() };
+    organise_files(root, &options).await.unwrap();
+    assert!(status.exists());
+}
+
+#[tokio::test]
+async fn test_in_place_folders_are_not_organized_again() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    std::fs::create_dir_all(root.join("inbox")).unwrap();
+    std::fs::write(root.join("report.pdf"), "pdf").unwrap();
+    std::fs::write(root.join("holiday.jpg"), "jpeg bytes").unwrap();
+    std::fs::write(root.join("inbox/notes.txt"), "notes").unwrap();
+    let options = OrganizeOptions { portable: true, in_place: true, max_depth: Some(5), ..Default::default() };
+
+    let summary = organise_files(root, &options).await.unwrap();
+    assert_eq!(summary.moved, 3);
+    assert!(!root.join("Organized").exists());
+    for folder in ["Documents", "Images"] {
+        assert!(root.join(folder).join(IN_PLACE_MARKER).is_file(), "{folder}");
+    }
+    assert!(!root.join("inbox").join(IN_PLACE_MARKER).exists());
+
+    // Sorted folders are skipped as a whole; new files still get sorted
+    let sorted: Vec<_> = walkdir::WalkDir::new(root.join("Documents")).into_iter().map(|e| e.unwrap().into_path()).collect();
+    std::fs::write(root.join("invoice.pdf"), "invoice").unwrap();
+    let summary = organise_files(root, &options).await.unwrap();
+    assert_eq!(summary.moved, 1);
+    assert_eq!(summary.skip_counts[SkipReason::Excluded.as_index()], 2);
+    assert!(sorted.iter().all(|path| path.exists()));
+
+    // Reverting takes the markers with the emptied folders
+    revert_files(root, &RevertOptions { cleanup: true, portable: true, ..Default::default() }).await.unwrap();
+    assert!(root.join("report.pdf").exists() && root.join("inbox/notes.txt").exists());
+    assert!(!root.join("Documents").exists() && !root.join("Images").exists());
+}
@@ -34,7 +34,7 @@ mod tests {
         tokio::fs::write(&path, "dummy exe").await.unwrap();
 
         let classifier = ExecutableClassifier;
-        let metadata = classifier.extract_metadata(&path).await.unwrap();
+        let metadata = classifier.extract_metadata(path).await.unwrap();
 
         assert!(matches!(metadata.category, FileCategory::Executables(ExecutableSubcategory::WindowsApp)));
         assert_eq!(metadata.file_size, Some(9));
@@ -48,7 +48,7 @@ mod tests {
         tokio::fs::write(&path, "#!/bin/bash\necho hello").await.unwrap();
 
         let classifier = ExecutableClassifier;
-        let metadata = classifier.extract_metadata(&path).await.unwrap();
+        let metadata = classifier.extract_metadata(path).await.unwrap();
 
         assert!(matches!(metadata.category, FileCategory::Executables(ExecutableSubcategory::Script)));
         assert_eq!(metadata.file_size, Some(22));
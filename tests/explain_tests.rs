@@ -0,0 +1,64 @@
+use stash::{
+    explain::{explain, explain_extension},
+    filename_date::DatePatterns,
+    path_builder::FolderNames,
+    scanner::{ScanConfig, Scanner},
+    utils::create_classifier_registry,
+};
+use tempfile::tempdir;
+
+#[tokio::test]
+async fn test_explain_shows_scores_winner_and_destination() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("SCN_20240131_0001.pdf");
+    std::fs::write(&path, b"%PDF-1.4").unwrap();
+    let raw = Scanner::new(&path, ScanConfig { max_depth: 0, ..Default::default() }).next().unwrap().unwrap();
+    let patterns = DatePatterns::new(&[r"^SCN_(?P<year>\d{4})(?P<month>\d{2})"]).unwrap();
+    let registry = create_classifier_registry();
+
+    let explanation = explain(&registry, &raw, &patterns, &FolderNames::default()).await;
+    assert_eq!(explanation.mime, "application/pdf");
+    assert_eq!(explanation.scores[0].name, "DocumentClassifier");
+    assert_eq!(explanation.scores[0].weighted, 8500);
+    assert!(explanation.scores.windows(2).all(|pair| pair[0].weighted >= pair[1].weighted));
+    assert_eq!(explanation.winner, Some("DocumentClassifier"));
+    assert_eq!(explanation.category.as_deref(), Some("Documents::Pdf"));
+    assert_eq!(
+        explanation.destination,
+        Some(dir.path().join("Organized/Documents/Pdf/2024/01/SCN_20240131_0001.pdf"))
+    );
+    assert!(explanation.to_string().contains("← chosen"));
+
+    let names = FolderNames::default().rename("Documents", "Dokumente");
+    let explanation = explain(&registry, &raw, &patterns, &names).await;
+    assert_eq!(
+        explanation.destination,
+        Some(dir.path().join("Organized/Dokumente/Pdf/2024/01/SCN_20240131_0001.pdf"))
+    );
+
+    // Below the minimum score the generic classifier takes it
+    let explanation = explain(&registry.clone().with_min_score(9000), &raw, &DatePatterns::default(), &names).await;
+    assert_eq!(explanation.winner, Some("GenericClassifier"));
+    assert_eq!(explanation.category.as_deref(), Some("Others"));
+    assert!(explanation.to_string().contains("below 9000"));
+}
+
+#[tokio::test]
+async fn test_explain_extension_shows_the_collision() {
+    let registry = create_classifier_registry();
+    let explanation = explain_extension(&registry, ".MD", &DatePatterns::default(), &FolderNames::default()).await.unwrap();
+    assert_eq!(explanation.path, std::path::Path::new("*.md"));
+    let names: Vec<_> = explanation.scores.iter().map(|score| score.name).collect();
+    assert_eq!(names, ["DocumentClassifier", "CodeClassifier", "GenericClassifier"]);
+    assert_eq!(explanation.winner, Some("DocumentClassifier"));
+    assert_eq!(explanation.category.as_deref(), Some("Documents::Text"));
+    assert!(explanation.destination.as_ref().unwrap().starts_with("Organized/Documents/Text"));
+    colored::control::set_override(false);
+    assert!(explanation.to_string().contains(r#""overrides": { "md": "<folder>" }"#));
+
+    // Nothing to untangle when one classifier has it to itself
+    let explanation = explain_extension(&registry, "png", &DatePatterns::default(), &FolderNames::default()).await.unwrap();
+    assert!(!explanation.to_string().contains("To change the winner"));
+
+    assert!(explain_extension(&registry, ".", &DatePatterns::default(), &FolderNames::default()).await.is_err());
+}
@@ -0,0 +1,58 @@
+mod test_utils;
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use stash::{font_classifier::FontClassifier, path_builder::PathBuilder, registry::Classifier};
+    use stash::metadata::{FileCategory, FontSubcategory};
+    use stash::scanner::{ScanConfig, Scanner};
+    use stash::utils::create_classifier_registry;
+
+    use crate::test_utils::create_test_file_with_ext;
+
+    #[tokio::test]
+    async fn test_confidence_levels() {
+        let clf = FontClassifier;
+
+        assert_eq!(clf.confidence("ttf", "font/ttf"), 100);
+        assert_eq!(clf.confidence("woff2", "font/woff2"), 100);
+        assert_eq!(clf.confidence("eot", "application/vnd.ms-fontobject"), 100);
+        assert_eq!(clf.confidence("pfb", "application/octet-stream"), 80);
+        assert_eq!(clf.confidence("foo", "font/sfnt"), 90);
+        assert_eq!(clf.confidence("txt", "text/plain"), 0);
+    }
+
+    #[tokio::test]
+    async fn test_extract_metadata_subcategories() {
+        for (ext, expected) in [
+            ("ttf", FontSubcategory::TrueType),
+            ("otf", FontSubcategory::OpenType),
+            ("woff", FontSubcategory::Woff),
+            ("woff2", FontSubcategory::Woff2),
+            ("eot", FontSubcategory::Eot),
+        ] {
+            let (_dir, path) = create_test_file_with_ext(ext);
+            let result = FontClassifier.extract_metadata(&path).await.unwrap();
+
+            match result.category {
+                FileCategory::Fonts(sub) => assert_eq!(sub, expected, "{ext}"),
+                other => panic!("Expected font category for {ext}, got {other}"),
+            }
+            assert!(result.file_size.unwrap() > 0);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_registry_sends_fonts_to_fonts_folder() {
+        let (_dir, path) = create_test_file_with_ext("otf");
+        let registry = create_classifier_registry();
+
+        let config = ScanConfig { max_depth: 0, include_hidden: true, ..Default::default() };
+        let raw = Scanner::new(&path, config).next().unwrap().unwrap();
+        let mut meta = registry.classify(&raw).await.unwrap();
+        meta.year = None;
+
+        assert_eq!(PathBuilder::new(&meta).build(), Path::new("Organized/Fonts/OpenType"));
+    }
+}
@@ -0,0 +1,83 @@
+use std::{fs, path::Path};
+
+use stash::{
+    errors::FileOrganizerError,
+    guard::{Danger, Guard},
+    organizer::{organise_files, OrganizeOptions},
+    reverter::{revert, RevertOptions, RevertScope},
+};
+use tempfile::tempdir;
+
+#[test]
+fn test_dangerous_roots_are_refused() {
+    let guard = Guard::default();
+    let home = dirs::home_dir().unwrap();
+    assert_eq!(guard.danger(&home), Some(Danger::Home));
+    // `/root`'s parent is the filesystem root itself
+    assert!(matches!(guard.danger(&home.join("..")), Some(Danger::AboveHome | Danger::FilesystemRoot)));
+    #[cfg(unix)]
+    {
+        assert_eq!(guard.danger(Path::new("/")), Some(Danger::FilesystemRoot));
+        assert_eq!(guard.danger(Path::new("/usr")), Some(Danger::System));
+        assert_eq!(guard.danger(Path::new("/etc/ssh")), Some(Danger::System));
+        assert_eq!(guard.danger(Path::new("/tmp/../usr/lib")), Some(Danger::System));
+    }
+    #[cfg(windows)]
+    assert_eq!(guard.danger(Path::new(r"C:\Windows\System32")), Some(Danger::System));
+
+    // Directories below the home directory and temp directories are fine
+    let dir = tempdir().unwrap();
+    assert_eq!(guard.danger(dir.path()), None);
+    assert_eq!(guard.danger(&home.join("Downloads")), None);
+    assert!(guard.check(dir.path()).is_ok());
+
+    assert_eq!(Guard::disabled().danger(&home), None);
+}
+
+#[test]
+fn test_protected_globs_cover_directories_below() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    let guard = Guard::new(&[format!("{}/keep", root.display()), "Taxes".to_string()]).unwrap();
+
+    assert_eq!(guard.danger(&root.join("keep")), Some(Danger::Protected));
+    assert_eq!(guard.danger(&root.join("keep/2024")), Some(Danger::Protected));
+    assert_eq!(guard.danger(&root.join("papers/Taxes")), Some(Danger::Protected));
+    assert_eq!(guard.danger(&root.join("keeps")), None);
+    assert_eq!(guard.danger(root), None);
+
+    assert!(Guard::new(&["/"]).is_err());
+}
+
+#[tokio::test]
+async fn test_organize_and_revert_refuse_a_protected_root() {
+    let dir = tempdir().unwrap();
+    let root = dir.path().join("Taxes");
+    fs::create_dir(&root).unwrap();
+    fs::write(root.join("return.pdf"), "%PDF-1.4").unwrap();
+    let guard = Guard::new(&["Taxes"]).unwrap();
+
+    let options = OrganizeOptions { portable: true, guard: guard.clone(), ..Default::default() };
+    let error = organise_files(&root, &options).await.unwrap_err();
+    assert!(matches!(error, FileOrganizerError::DangerousRoot { danger: Danger::Protected, .. }), "{}", error);
+    assert!(error.to_string().contains("--i-know-what-im-doing"));
+    assert!(root.join("return.pdf").exists());
+
+    // A dry run only warns
+    let preview = OrganizeOptions { dry_run: true, ..options.clone() };
+    assert!(organise_files(&root, &preview).await.is_ok());
+    assert!(root.join("return.pdf").exists());
+
+    // A protected destination is refused too
+    let dest = OrganizeOptions { dest: Some(root.clone()), guard: guard.clone(), ..Default::default() };
+    assert!(organise_files(dir.path(), &dest).await.is_err());
+
+    let options = OrganizeOptions { portable: true, guard: Guard::disabled(), ..Default::default() };
+    assert_eq!(organise_files(&root, &options).await.unwrap().moved, 1);
+
+    let refused = RevertOptions { portable: true, guard: guard.clone(), ..Default::default() };
+    assert!(matches!(revert(Some(&root), &refused).await, Err(FileOrganizerError::DangerousRoot { .. })));
+    let by_origin = RevertOptions { scope: RevertScope::OriginalRoot(root.clone()), guard, ..Default::default() };
+    assert!(revert(None, &by_origin).await.is_err());
+    assert!(!root.join("return.pdf").exists());
+}
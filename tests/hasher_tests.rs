@@ -2,11 +2,11 @@
 mod tests {
     use std::io::Write;
 
-    use stash::hasher::{Blake3Hasher, FileHasher, Sha256Hasher};
+    use stash::hasher::{rehash_like, Blake3Hasher, FileHasher, HashAlgo, Sha256Hasher, Xxh3Hasher};
     use tokio::io::AsyncWriteExt;
     use tempfile::NamedTempFile;
     use sha2::Digest;
-    use blake3;
+    
 
     const BLOCKING_THRESHOLD: u64 = 50 * 1024 * 1024; // 50MB
 
@@ -70,4 +70,34 @@ mod tests {
 
         check_hasher(Blake3Hasher, &content, expected).await;
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn xxh3_small_file() {
+        let content = b"hello world";
+        let expected = xxhash_rust::xxh3::xxh3_128(content).to_be_bytes().to_vec();
+
+        check_hasher(Xxh3Hasher, content, expected).await;
+    }
+
+    #[test]
+    fn algo_round_trips_through_stored_hashes() {
+        assert_eq!(HashAlgo::Blake3.encode(&[0xab, 0x01]), "ab01");
+        assert_eq!(HashAlgo::Sha256.encode(&[0xab, 0x01]), "sha256:ab01");
+        assert_eq!(HashAlgo::of("ab01"), Some(HashAlgo::Blake3));
+        assert_eq!(HashAlgo::of("xxh3:ab01"), Some(HashAlgo::Xxh3));
+        assert_eq!(HashAlgo::of("md5:ab01"), None);
+        assert_eq!("SHA256".parse::<HashAlgo>(), Ok(HashAlgo::Sha256));
+        assert!("md5".parse::<HashAlgo>().unwrap_err().contains("blake3, sha256, xxh3"));
+    }
+
+    #[tokio::test]
+    async fn rehash_like_matches_the_stored_algorithm() {
+        let path = write_temp_file(b"same content").await;
+        for algo in [HashAlgo::Blake3, HashAlgo::Sha256, HashAlgo::Xxh3] {
+            let stored = algo.hash_file(&path).await.unwrap();
+            assert_eq!(rehash_like(&stored, &path).await.unwrap(), stored);
+        }
+        assert!(rehash_like("md5:00", &path).await.is_err());
+        tokio::fs::remove_file(path).await.unwrap();
+    }
+}
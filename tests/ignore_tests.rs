@@ -0,0 +1,55 @@
+use stash::{
+    errors::SkipReason,
+    index::Db,
+    organizer::{organise_files, OrganizeOptions},
+};
+use tempfile::tempdir;
+
+#[tokio::test]
+async fn test_ignore_list_roundtrip() {
+    let dir = tempdir().unwrap();
+    let db = Db::open_portable(dir.path()).await.unwrap();
+    let a = dir.path().join("a.txt");
+    let b = dir.path().join("b.txt");
+
+    assert!(db.add_ignored(&b).await.unwrap());
+    assert!(db.add_ignored(&a).await.unwrap());
+    assert!(!db.add_ignored(&a).await.unwrap());
+
+    let listed: Vec<_> = db.list_ignored().await.unwrap().into_iter().map(|(p, _)| p).collect();
+    assert_eq!(listed, vec![a.clone(), b.clone()]);
+
+    assert!(db.remove_ignored(&a).await.unwrap());
+    assert!(!db.remove_ignored(&a).await.unwrap());
+    assert_eq!(db.ignored_paths().await.unwrap().into_iter().collect::<Vec<_>>(), vec![b]);
+}
+
+#[tokio::test]
+async fn test_organize_skips_ignored_files() {
+    let dir = tempdir().unwrap();
+    let keep = dir.path().join("keep-here.pdf");
+    let other = dir.path().join("other.pdf");
+    std::fs::write(&keep, b"pdf one").unwrap();
+    std::fs::write(&other, b"pdf two").unwrap();
+
+    let db = Db::open_portable(dir.path()).await.unwrap();
+    db.add_ignored(&keep).await.unwrap();
+
+    // Dry runs honour the persisted list too
+    let dry = OrganizeOptions { portable: true, dry_run: true, ..Default::default() };
+    let summary = organise_files(dir.path(), &dry).await.unwrap();
+    assert_eq!(summary.moved, 1);
+    assert_eq!(summary.skip_counts[SkipReason::Ignored.as_index()], 1);
+
+    let options = OrganizeOptions { portable: true, ..Default::default() };
+    let summary = organise_files(dir.path(), &options).await.unwrap();
+    assert_eq!(summary.moved, 1);
+    assert_eq!(summary.skip_counts[SkipReason::Ignored.as_index()], 1);
+    assert!(keep.exists());
+    assert!(!other.exists());
+
+    db.remove_ignored(&keep).await.unwrap();
+    let summary = organise_files(dir.path(), &options).await.unwrap();
+    assert_eq!(summary.moved, 1);
+    assert!(!keep.exists());
+}
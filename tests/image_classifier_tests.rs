@@ -2,7 +2,11 @@
 mod tests {
     use tempfile::Builder;
     use tokio::fs;
-    use stash::{image_classifier::ImageClassifier, registry::Classifier, metadata::{FileCategory, ImageSubcategory}};
+    use stash::{
+        image_classifier::{is_screenshot_name, is_screenshot_png, year_in_name, ImageClassifier},
+        registry::Classifier,
+        metadata::{FileCategory, ImageSubcategory},
+    };
 
     // ---------------------------
     // Unit tests
@@ -77,6 +81,69 @@ mod tests {
         assert_eq!(meta.mime_type.unwrap(), "application/octet-stream");
     }
 
+    #[test]
+    fn test_screenshot_names() {
+        for name in [
+            "Screenshot 2024-03-12 at 10.15.02.png",
+            "Screen Shot 2018-07-01 at 9.00.00 AM.png",
+            "Screenshot_20240312-101502_Chrome.jpg",
+            "screenshot (3).png",
+            "Snip_2021-02-03.jpg",
+            "Bildschirmfoto 2023-01-05 um 12.00.00.png",
+            "Capture d’écran 2023-01-05 à 12.00.00.png",
+        ] {
+            assert!(is_screenshot_name(name), "{}", name);
+        }
+        for name in ["IMG_0042.jpg", "snippet.png", "my screenshot.png", "holiday.png"] {
+            assert!(!is_screenshot_name(name), "{}", name);
+        }
+
+        assert_eq!(year_in_name("Screenshot 2024-03-12 at 10.15.02.png"), Some(2024));
+        assert_eq!(year_in_name("Screenshot_20190312-101502.jpg"), Some(2019));
+        assert_eq!(year_in_name("Screenshot (3).png"), None);
+    }
+
+    /// A PNG header with one text chunk of `keyword` and `text`, then image data
+    fn png_with_text(keyword: &str, text: &str) -> Vec<u8> {
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        let mut chunk = |kind: &[u8], data: &[u8]| {
+            png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+            png.extend_from_slice(kind);
+            png.extend_from_slice(data);
+            png.extend_from_slice(&[0; 4]);
+        };
+        chunk(b"IHDR", &[0; 13]);
+        chunk(b"tEXt", format!("{}\0{}", keyword, text).as_bytes());
+        chunk(b"IDAT", b"Screenshot");
+        png
+    }
+
+    #[test]
+    fn test_screenshot_png_metadata() {
+        assert!(is_screenshot_png(&png_with_text("Software", "gnome-screenshot")));
+        assert!(is_screenshot_png(&png_with_text("XML:com.adobe.xmp", "<exif:UserComment>Screenshot</exif:UserComment>")));
+        // The image data says `Screenshot` too, but only text chunks count
+        assert!(!is_screenshot_png(&png_with_text("Software", "GIMP 2.10")));
+        assert!(!is_screenshot_png(&png_with_text("Software", "GIMP 2.10")[..20]));
+        assert!(!is_screenshot_png(b"\xff\xd8\xff Screenshot"));
+    }
+
+    #[tokio::test]
+    async fn test_extract_metadata_screenshot() {
+        let dir = tempfile::tempdir().unwrap();
+        let named = dir.path().join("Screenshot 2021-06-01 at 08.30.00.jpg");
+        fs::write(&named, b"fakejpegdata").await.unwrap();
+        let tagged = dir.path().join("image.png");
+        fs::write(&tagged, png_with_text("Software", "ShareX")).await.unwrap();
+
+        let meta = ImageClassifier.extract_metadata(&named).await.unwrap();
+        assert!(matches!(meta.category, FileCategory::Images(ImageSubcategory::Screenshots)));
+        assert_eq!(meta.year, Some(2021));
+
+        let meta = ImageClassifier.extract_metadata(&tagged).await.unwrap();
+        assert!(matches!(meta.category, FileCategory::Images(ImageSubcategory::Screenshots)));
+    }
+
     // ---------------------------
     // Optional property tests
     // ---------------------------
@@ -0,0 +1,52 @@
+use stash::{
+    index::Db,
+    index_bench::{run, SampleRows, UpsertStrategy},
+};
+use tempfile::tempdir;
+
+#[tokio::test]
+async fn test_bench_measures_every_strategy_and_size() {
+    let dir = tempdir().unwrap();
+    let report = run(dir.path(), &[10, 50]).await.unwrap();
+
+    let measured: Vec<_> = report.results.iter().map(|r| (r.rows, r.strategy)).collect();
+    assert_eq!(
+        measured,
+        vec![
+            (10, UpsertStrategy::ChunkedValues),
+            (10, UpsertStrategy::Prepared),
+            (50, UpsertStrategy::ChunkedValues),
+            (50, UpsertStrategy::Prepared),
+        ]
+    );
+    assert!(report.results.iter().all(|r| r.insert_rate() > 0.0 && r.update_rate() > 0.0));
+    assert!(report.to_string().contains("chunked-values"));
+
+    // Scratch databases are cleaned up
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+}
+
+#[tokio::test]
+async fn test_strategies_write_the_same_rows() {
+    let dir = tempdir().unwrap();
+    let sample = SampleRows::new(250, std::fs::metadata(dir.path()).unwrap().permissions());
+    assert_eq!(sample.len(), 250);
+
+    let mut indexed = Vec::new();
+    for strategy in UpsertStrategy::ALL {
+        let db = Db::new(&dir.path().join(format!("{}.db", strategy))).await.unwrap();
+        sample.upsert(&db, strategy).await.unwrap();
+        sample.upsert(&db, strategy).await.unwrap();
+        let mut files: Vec<_> = db
+            .get_all_files()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|e| (e.path, e.dest_path, e.size, e.hash, e.category))
+            .collect();
+        files.sort();
+        indexed.push(files);
+    }
+    assert_eq!(indexed[0].len(), 250);
+    assert_eq!(indexed[0], indexed[1]);
+}
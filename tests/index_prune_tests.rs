@@ -0,0 +1,91 @@
+use std::{path::PathBuf, time::Duration};
+
+use stash::{
+    config::OrganizerConfig,
+    index::Db,
+    index_prune::prune_index,
+    organizer::{organise_files, OrganizeOptions},
+    volumes::Volumes,
+};
+use tempfile::tempdir;
+
+async fn dest(db: &Db, path: PathBuf) -> Option<PathBuf> {
+    db.lookup_full(&path).await.unwrap().map(|entry| entry.dest_path)
+}
+
+#[tokio::test]
+async fn test_prune_forgets_old_entries_of_files_that_are_gone() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    for name in ["deleted.pdf", "moved_back.pdf", "kept.pdf"] {
+        std::fs::write(root.join(name), name).unwrap();
+    }
+    organise_files(root, &OrganizeOptions { portable: true, ..Default::default() }).await.unwrap();
+    let db = Db::open_portable(root).await.unwrap();
+
+    std::fs::remove_file(dest(&db, root.join("deleted.pdf")).await.unwrap()).unwrap();
+    std::fs::rename(dest(&db, root.join("moved_back.pdf")).await.unwrap(), root.join("moved_back.pdf")).unwrap();
+
+    // Too recent to forget; both missing destinations are orphans
+    let report = prune_index(&db, Duration::from_secs(180 * 24 * 60 * 60), false, false).await.unwrap();
+    assert_eq!(report.checked, 3);
+    assert!(report.pruned.is_empty());
+    assert_eq!(report.orphans.len(), 2);
+    assert!(report.to_string().contains("--orphans"));
+
+    // Entries are stamped in whole seconds
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+    let preview = prune_index(&db, Duration::ZERO, false, true).await.unwrap();
+    assert_eq!(preview.pruned, vec![root.join("deleted.pdf")]);
+    assert!(preview.to_string().contains("would be forgotten"));
+    assert!(dest(&db, root.join("deleted.pdf")).await.is_some());
+
+    // A file that is back at its original path is still an orphan, not gone
+    let report = prune_index(&db, Duration::ZERO, false, false).await.unwrap();
+    assert_eq!(report.pruned, vec![root.join("deleted.pdf")]);
+    assert_eq!(report.orphans[0].path, root.join("moved_back.pdf"));
+    assert!(dest(&db, root.join("deleted.pdf")).await.is_none());
+
+    let report = prune_index(&db, Duration::from_secs(180 * 24 * 60 * 60), true, false).await.unwrap();
+    assert_eq!(report.pruned, vec![root.join("moved_back.pdf")]);
+    assert!(dest(&db, root.join("moved_back.pdf")).await.is_none());
+    assert!(dest(&db, root.join("kept.pdf")).await.is_some());
+}
+
+#[tokio::test]
+async fn test_retention_leaves_entries_on_missing_volumes_alone() {
+    let dir = tempdir().unwrap();
+    let root = dir.path().join("Downloads");
+    let volume = dir.path().join("usb");
+    std::fs::create_dir(&root).unwrap();
+    std::fs::write(root.join("report.pdf"), "report").unwrap();
+    let options = OrganizeOptions {
+        portable: true,
+        volumes: Volumes::from([("Documents", vec![volume.join("Organized")])]),
+        index_retention: Some(Duration::ZERO),
+        ..Default::default()
+    };
+    organise_files(&root, &options).await.unwrap();
+
+    // The drive is unplugged: neither the file nor its folder is there
+    std::fs::rename(&volume, dir.path().join("unplugged")).unwrap();
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+    std::fs::write(root.join("notes.txt"), "notes").unwrap();
+    organise_files(&root, &OrganizeOptions { volumes: Volumes::new(), ..options.clone() }).await.unwrap();
+
+    let db = Db::open_portable(&root).await.unwrap();
+    assert!(dest(&db, root.join("report.pdf")).await.is_some());
+    let report = prune_index(&db, Duration::ZERO, true, true).await.unwrap();
+    assert_eq!(report.unreachable, 1);
+    assert!(report.pruned.is_empty());
+    assert!(report.to_string().contains("isn't mounted"));
+}
+
+#[test]
+fn test_index_retention_is_validated() {
+    let config: OrganizerConfig = serde_json::from_value(serde_json::json!({ "index_retention": "90d" })).unwrap();
+    assert_eq!(config.index_retention(), Some(Duration::from_secs(90 * 24 * 60 * 60)));
+
+    let config: OrganizerConfig = serde_json::from_value(serde_json::json!({ "index_retention": "soon" })).unwrap();
+    assert!(config.check().is_err());
+}
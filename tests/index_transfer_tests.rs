@@ -0,0 +1,69 @@
+use stash::{
+    index::Db,
+    index_transfer::{self, IndexFormat, IndexRecord},
+    organizer::{organise_files, OrganizeOptions},
+};
+use std::path::{Path, PathBuf};
+use tempfile::tempdir;
+
+async fn organized_db(root: &Path) -> Db {
+    std::fs::write(root.join("report.pdf"), b"pdf bytes").unwrap();
+    std::fs::write(root.join("notes.txt"), b"some notes").unwrap();
+    organise_files(root, &OrganizeOptions { portable: true, ..Default::default() }).await.unwrap();
+    Db::open_portable(root).await.unwrap()
+}
+
+#[tokio::test]
+async fn test_export_round_trips_through_json_and_csv() {
+    let dir = tempdir().unwrap();
+    let db = organized_db(dir.path()).await;
+
+    for format in [IndexFormat::Json, IndexFormat::Csv] {
+        let mut out = Vec::new();
+        assert_eq!(index_transfer::export(&db, format, &mut out).await.unwrap(), 2);
+        let records = index_transfer::read_records(format, out.as_slice()).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].path, dir.path().join("notes.txt"));
+        assert!(records[0].dest_path.starts_with(dir.path().join("Organized")));
+        assert!(records[0].hash.is_some());
+
+        // Importing into a fresh index adds everything; again, nothing changes
+        let fresh = Db::new(Path::new(":memory:")).await.unwrap();
+        let report = index_transfer::import(&fresh, records.clone(), false).await.unwrap();
+        assert_eq!(report.added, 2);
+        let report = index_transfer::import(&fresh, records, false).await.unwrap();
+        assert_eq!((report.added, report.unchanged), (0, 2));
+    }
+}
+
+#[tokio::test]
+async fn test_import_reports_conflicts_and_invalid_records() {
+    let dir = tempdir().unwrap();
+    let db = organized_db(dir.path()).await;
+
+    let mut out = Vec::new();
+    index_transfer::export(&db, IndexFormat::Json, &mut out).await.unwrap();
+    let mut records = index_transfer::read_records(IndexFormat::Json, out.as_slice()).unwrap();
+    records[0].dest_path = dir.path().join("elsewhere/notes.txt");
+    records.push(IndexRecord {
+        path: PathBuf::from("relative.txt"),
+        dest_path: dir.path().join("x.txt"),
+        size: 1,
+        modified: None,
+        category: None,
+        hash: None,
+    });
+    records.push(records[1].clone());
+
+    let report = index_transfer::import(&db, records.clone(), false).await.unwrap();
+    assert_eq!(report.conflicts, vec![dir.path().join("notes.txt")]);
+    assert_eq!((report.unchanged, report.added), (1, 0));
+    assert_eq!(report.invalid.iter().map(|(n, _)| *n).collect::<Vec<_>>(), vec![3, 4]);
+    let kept = db.lookup_full(&dir.path().join("notes.txt")).await.unwrap().unwrap();
+    assert_ne!(kept.dest_path, records[0].dest_path);
+
+    let report = index_transfer::import(&db, records.clone(), true).await.unwrap();
+    assert_eq!(report.replaced, 1);
+    let replaced = db.lookup_full(&dir.path().join("notes.txt")).await.unwrap().unwrap();
+    assert_eq!(replaced.dest_path, records[0].dest_path);
+}
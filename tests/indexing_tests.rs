@@ -0,0 +1,66 @@
+use std::fs;
+
+use stash::{
+    cancel::CancelToken,
+    exclusions::ExcludeGlobs,
+    index::Db,
+    indexing::{index_tree, IndexOptions},
+    organizer::{organise_files, OrganizeOptions},
+    query::{FileQuery, IndexScope},
+    utils::create_classifier_registry_with,
+};
+use tempfile::tempdir;
+
+#[tokio::test]
+async fn test_index_records_without_moving_and_resumes() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    fs::create_dir(root.join("photos")).unwrap();
+    fs::write(root.join("report.pdf"), "pdf").unwrap();
+    fs::write(root.join("photos/holiday.jpg"), "jpeg bytes").unwrap();
+    fs::write(root.join("scratch.tmp"), "scratch").unwrap();
+    let registry = create_classifier_registry_with(&[]);
+    let options = IndexOptions { exclude: ExcludeGlobs::new(&["*.tmp"]).unwrap(), ..Default::default() };
+
+    let db = Db::open_portable(root).await.unwrap();
+    let report = index_tree(&db, &registry, root, &options).await.unwrap();
+    assert!(report.errors.is_empty(), "{:?}", report.errors);
+    assert_eq!((report.indexed, report.bytes, report.unchanged), (2, 13, 0));
+    assert!(root.join("report.pdf").exists() && !root.join("Organized").exists());
+
+    let entry = db.lookup_full(&root.join("photos/holiday.jpg")).await.unwrap().unwrap();
+    assert_eq!(entry.dest_path, entry.path);
+    assert_eq!(entry.category.as_deref(), Some("Images::Jpeg"));
+    assert!(entry.hash.is_some());
+    assert!(db.lookup_full(&root.join("scratch.tmp")).await.unwrap().is_none());
+
+    // The index answers queries about the tree as it is
+    let scope = IndexScope { under: Some(root.join("photos")), state: None, ..Default::default() };
+    let rows = FileQuery { scope, ..Default::default() }.run(&db).await.unwrap();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0].path, root.join("photos/holiday.jpg"));
+    let everything = IndexScope { under: Some(root.to_path_buf()), state: None, ..Default::default() };
+    assert_eq!(FileQuery { scope: everything, ..Default::default() }.run(&db).await.unwrap().len(), 2);
+
+    // Only new or changed files are hashed again
+    fs::write(root.join("report.pdf"), "new pdf").unwrap();
+    fs::write(root.join("notes.txt"), "notes").unwrap();
+    let report = index_tree(&db, &registry, root, &options).await.unwrap();
+    assert_eq!((report.indexed, report.unchanged), (2, 1));
+
+    // A cancelled run stops without losing what was recorded
+    let cancel = CancelToken::new();
+    cancel.cancel();
+    let report = index_tree(&db, &registry, root, &IndexOptions { cancel, ..options.clone() }).await.unwrap();
+    assert!(report.cancelled);
+    assert_eq!(report.indexed, 0);
+    drop(db);
+
+    // Organizing afterwards moves the files; their new homes are not indexed again
+    organise_files(root, &OrganizeOptions { portable: true, ..Default::default() }).await.unwrap();
+    let db = Db::open_portable(root).await.unwrap();
+    assert!(root.join("Organized/Documents").exists());
+    let report = index_tree(&db, &registry, root, &options).await.unwrap();
+    assert_eq!(report.indexed, 0);
+    assert_eq!(report.unchanged, 3);
+}
@@ -0,0 +1,67 @@
+use std::{fs, time::Duration};
+
+use stash::{
+    errors::FileOrganizerError,
+    instance_lock::InstanceLock,
+    organizer::{organise_files, OrganizeOptions},
+    reverter::{revert, RevertOptions},
+};
+use tempfile::tempdir;
+
+#[tokio::test]
+async fn test_a_second_run_on_the_same_root_is_refused() {
+    let dir = tempdir().unwrap();
+    let locks = tempdir().unwrap();
+    let root = dir.path().join("Downloads");
+    fs::create_dir(&root).unwrap();
+    fs::write(root.join("notes.txt"), "notes").unwrap();
+
+    let held = InstanceLock::acquire(locks.path(), [root.as_path()], false).await.unwrap();
+    match InstanceLock::acquire(locks.path(), [root.join(".").as_path()], false).await {
+        Err(FileOrganizerError::AlreadyRunning { dir, pid }) => {
+            assert_eq!(dir, root.canonicalize().unwrap());
+            assert_eq!(pid, Some(std::process::id()));
+        }
+        other => panic!("expected AlreadyRunning, got {:?}", other.map(drop)),
+    }
+
+    let options = OrganizeOptions { portable: true, lock_dir: Some(locks.path().to_path_buf()), ..Default::default() };
+    let error = organise_files(&root, &options).await.unwrap_err();
+    assert!(matches!(error, FileOrganizerError::AlreadyRunning { .. }), "{}", error);
+    assert!(error.to_string().contains("--wait"));
+    assert!(root.join("notes.txt").exists());
+
+    // A dry run moves nothing and goes ahead
+    let preview = OrganizeOptions { dry_run: true, ..options.clone() };
+    assert_eq!(organise_files(&root, &preview).await.unwrap().moved, 1);
+
+    drop(held);
+    assert_eq!(organise_files(&root, &options).await.unwrap().moved, 1);
+
+    // Reverting waits its turn too
+    let revert_options = RevertOptions { portable: true, lock_dir: Some(locks.path().to_path_buf()), ..Default::default() };
+    let held = InstanceLock::acquire(locks.path(), [root.as_path()], false).await.unwrap();
+    assert!(matches!(revert(Some(&root), &revert_options).await, Err(FileOrganizerError::AlreadyRunning { .. })));
+    drop(held);
+    assert_eq!(revert(Some(&root), &revert_options).await.unwrap().moved, 1);
+
+    // Finished runs leave no lock files behind
+    assert_eq!(fs::read_dir(locks.path()).unwrap().count(), 0);
+}
+
+#[tokio::test]
+async fn test_waiting_runs_go_once_the_lock_is_released() {
+    let dir = tempdir().unwrap();
+    let locks = tempdir().unwrap();
+    let held = InstanceLock::acquire(locks.path(), [dir.path()], false).await.unwrap();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        drop(held);
+    });
+
+    let waited = tokio::time::timeout(Duration::from_secs(10), InstanceLock::acquire(locks.path(), [dir.path()], true)).await;
+    let waited = waited.unwrap().unwrap();
+    assert_eq!(fs::read_dir(locks.path()).unwrap().count(), 1);
+    drop(waited);
+    assert_eq!(fs::read_dir(locks.path()).unwrap().count(), 0);
+}
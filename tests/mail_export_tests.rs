@@ -0,0 +1,75 @@
+use std::path::Path;
+
+use stash::{
+    mail_export::{sender_name, MailExports, MailInfo},
+    organizer::{organise_files, OrganizeOptions},
+};
+use tempfile::tempdir;
+
+#[test]
+fn test_headers_give_sender_and_date() {
+    let info = MailInfo::from_headers(
+        "Received: from mx.example.com\n\tby mail.example.org\nFrom: \"ACME Billing\"\n <billing@acme.com>\nDate: Tue, 12 Mar 2024 10:00:00 +0000\n\nFrom: someone else\n",
+    );
+    assert_eq!(info.sender.as_deref(), Some("ACME Billing"));
+    assert_eq!(info.year(), Some(2024));
+    assert_eq!(info.sender_folder(), "ACME Billing");
+
+    assert_eq!(MailInfo::from_headers("Subject: hi\n").sender_folder(), "Unknown");
+}
+
+#[test]
+fn test_sender_name_falls_back_to_address() {
+    assert_eq!(sender_name("jane@example.com").as_deref(), Some("jane@example.com"));
+    assert_eq!(sender_name("<jane@example.com>").as_deref(), Some("jane@example.com"));
+    assert_eq!(sender_name("=?utf-8?q?J=C3=BCrgen?= <j@example.com>").as_deref(), Some("j@example.com"));
+    assert_eq!(sender_name(""), None);
+}
+
+fn write(path: &Path, contents: &str) {
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, contents).unwrap();
+}
+
+#[tokio::test]
+async fn test_attachments_are_filed_by_year_and_sender() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    write(
+        &root.join("Mail/Invoice.eml"),
+        "From: \"ACME Billing\" <billing@acme.com>\r\nDate: Tue, 12 Mar 2024 10:00:00 +0000\r\n\r\nSee attached.\r\n",
+    );
+    write(&root.join("Mail/Invoice_attachments/invoice.pdf"), "%PDF-1.4 invoice");
+    write(&root.join("Mail/Attachments/photo.jpg"), "jpeg bytes");
+    write(&root.join("Mail/Attachments/photo.jpg.json"), r#"{"sender": "Jane Doe <jane@example.com>", "date": "2023-05-01T08:00:00Z"}"#);
+    write(&root.join("Saved attachments/notes.txt"), "notes");
+    write(&root.join("Projects/readme.txt"), "not an attachment");
+
+    let exports = MailExports::detect(
+        ["Mail/Invoice.eml", "Mail/Invoice_attachments/invoice.pdf", "Projects/readme.txt"]
+            .map(|file| root.join(file))
+            .iter()
+            .map(|path| path.as_path()),
+    );
+    assert!(exports.get(&root.join("Projects/readme.txt")).is_none());
+    assert!(exports.is_companion(&root.join("Mail/Invoice.eml")));
+
+    let options = OrganizeOptions { portable: true, max_depth: Some(3), mail_attachments: true, ..Default::default() };
+    organise_files(root, &options).await.unwrap();
+
+    let attachments = root.join("Organized/Documents/Attachments");
+    assert!(attachments.join("2024/ACME Billing/invoice.pdf").exists());
+    assert!(attachments.join("2023/Jane Doe/photo.jpg").exists());
+    let unknown: Vec<_> = walkdir::WalkDir::new(&attachments)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_name() == "notes.txt")
+        .collect();
+    assert_eq!(unknown.len(), 1);
+    assert!(unknown[0].path().parent().unwrap().ends_with("Unknown"));
+
+    // The messages and sidecars describing them stay with the export
+    assert!(root.join("Mail/Invoice.eml").exists());
+    assert!(root.join("Mail/Attachments/photo.jpg.json").exists());
+    assert!(!root.join("Projects/readme.txt").exists());
+}
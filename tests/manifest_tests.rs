@@ -0,0 +1,48 @@
+use std::fs;
+
+use stash::{
+    manifest::{Manifest, MANIFEST_NAME},
+    organizer::{organise_files, OrganizeOptions},
+    reverter::{revert_files, RevertOptions},
+};
+use tempfile::tempdir;
+
+#[tokio::test]
+async fn test_manifests_follow_each_run() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    fs::write(root.join("report.pdf"), "pdf").unwrap();
+    fs::write(root.join("holiday.jpg"), "jpeg bytes").unwrap();
+    let options = OrganizeOptions { portable: true, manifests: true, ..Default::default() };
+
+    let first = organise_files(root, &options).await.unwrap();
+    let documents = root.join("Organized/Documents");
+    let images = root.join("Organized/Images");
+    let manifest = Manifest::read(&documents).unwrap().unwrap();
+    assert_eq!((manifest.category.as_str(), manifest.files, manifest.bytes), ("Documents", 1, 3));
+    assert_eq!(manifest.run_id, first.run_id);
+    assert!(manifest.oldest.is_some() && manifest.oldest == manifest.newest);
+    assert_eq!(Manifest::read(&images).unwrap().unwrap().files, 1);
+    assert!(!root.join("Organized").join(MANIFEST_NAME).exists());
+
+    // Only folders the run added to are counted again
+    fs::write(root.join("invoice.pdf"), "invoice").unwrap();
+    let second = organise_files(root, &options).await.unwrap();
+    let manifest = Manifest::read(&documents).unwrap().unwrap();
+    assert_eq!((manifest.files, manifest.bytes), (2, 10));
+    assert_eq!(manifest.run_id, second.run_id);
+    assert_eq!(Manifest::read(&images).unwrap().unwrap().run_id, first.run_id);
+
+    // A dry run or a run without the option leaves them alone
+    fs::write(root.join("more.pdf"), "more").unwrap();
+    organise_files(root, &OrganizeOptions { dry_run: true, ..options.clone() }).await.unwrap();
+    organise_files(root, &OrganizeOptions { portable: true, ..Default::default() }).await.unwrap();
+    assert_eq!(Manifest::read(&documents).unwrap().unwrap().files, 2);
+
+    // Folders emptied by a revert go, manifests and all
+    let options = RevertOptions { cleanup: true, portable: true, ..Default::default() };
+    revert_files(root, &options).await.unwrap();
+    assert!(root.join("report.pdf").exists());
+    assert!(!documents.exists());
+    assert!(!images.exists());
+}
@@ -0,0 +1,208 @@
+use std::{path::Path, sync::Arc};
+
+use stash::{
+    conflict_resolver::resolve_conflict_in,
+    file_mover::FileMover,
+    fs::{FileSystem, MemoryFs, Preserve, RealFs, ThrottledFs},
+    scanner::SymlinkPolicy,
+    throttle::Throttle,
+};
+use tempfile::tempdir;
+
+fn memory_mover() -> (Arc<MemoryFs>, FileMover) {
+    let fs = Arc::new(MemoryFs::new());
+    (fs.clone(), FileMover::with_fs(fs))
+}
+
+#[tokio::test]
+async fn test_move_creates_destination_dirs() {
+    let (fs, mover) = memory_mover();
+    fs.insert("/home/downloads/report.pdf", "pdf");
+
+    mover.move_file(Path::new("/home/downloads/report.pdf"), Path::new("/home/Organized/Documents/report.pdf")).await.unwrap();
+
+    assert_eq!(fs.paths(), vec![Path::new("/home/Organized/Documents/report.pdf")]);
+    assert_eq!(fs.contents(Path::new("/home/Organized/Documents/report.pdf")).unwrap(), b"pdf");
+}
+
+#[tokio::test]
+async fn test_move_across_devices_falls_back_to_copy() {
+    let (fs, mover) = memory_mover();
+    fs.insert("/home/photo.jpg", "jpeg");
+    fs.mount("/mnt/usb");
+
+    mover.move_file(Path::new("/home/photo.jpg"), Path::new("/mnt/usb/Images/photo.jpg")).await.unwrap();
+
+    assert_eq!(fs.paths(), vec![Path::new("/mnt/usb/Images/photo.jpg")]);
+    assert_eq!(mover.get_file_size(Path::new("/mnt/usb/Images/photo.jpg")).await.unwrap(), 4);
+}
+
+#[tokio::test]
+async fn test_copy_across_devices_keeps_mtime_unless_disabled() {
+    let fs = Arc::new(MemoryFs::new());
+    fs.insert("/home/a.txt", "a");
+    fs.insert("/home/b.txt", "b");
+    fs.mount("/mnt/usb");
+    let mtime = |path: &str| {
+        let fs = fs.clone();
+        let path = path.to_string();
+        async move { fs.metadata(Path::new(&path)).await.unwrap().modified }
+    };
+    let (a_mtime, b_mtime) = (mtime("/home/a.txt").await, mtime("/home/b.txt").await);
+
+    FileMover::with_fs(fs.clone()).move_file(Path::new("/home/a.txt"), Path::new("/mnt/usb/a.txt")).await.unwrap();
+    assert_eq!(mtime("/mnt/usb/a.txt").await, a_mtime);
+
+    let preserve = Preserve { timestamps: false, ..Default::default() };
+    FileMover::with_fs(fs.clone())
+        .with_preserve(preserve)
+        .move_file(Path::new("/home/b.txt"), Path::new("/mnt/usb/b.txt"))
+        .await
+        .unwrap();
+    assert_ne!(mtime("/mnt/usb/b.txt").await, b_mtime);
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn test_real_copy_keeps_mtime_and_user_xattrs() {
+    use std::{ffi::CString, os::unix::ffi::OsStrExt, time::{Duration, SystemTime}};
+
+    let dir = tempdir().unwrap();
+    let src = dir.path().join("tagged.txt");
+    std::fs::write(&src, "tagged").unwrap();
+    let old = SystemTime::UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+    std::fs::File::options().write(true).open(&src).unwrap().set_modified(old).unwrap();
+
+    let c_src = CString::new(src.as_os_str().as_bytes()).unwrap();
+    let name = CString::new("user.xdg.tags").unwrap();
+    // SAFETY: both strings are NUL-terminated and the value is valid for its length
+    let rc = unsafe { libc::setxattr(c_src.as_ptr(), name.as_ptr(), b"red".as_ptr().cast(), 3, 0) };
+    let has_xattrs = rc == 0;
+
+    let dest = dir.path().join("copy/tagged.txt");
+    FileMover::new().copy_file(&src, &dest).await.unwrap();
+    assert_eq!(std::fs::metadata(&dest).unwrap().modified().unwrap(), old);
+
+    if has_xattrs {
+        let c_dest = CString::new(dest.as_os_str().as_bytes()).unwrap();
+        let mut buf = [0u8; 16];
+        // SAFETY: both strings are NUL-terminated and `buf` is valid for its length
+        let len = unsafe { libc::getxattr(c_dest.as_ptr(), name.as_ptr(), buf.as_mut_ptr().cast(), buf.len()) };
+        assert_eq!(&buf[..len.max(0) as usize], b"red");
+    }
+}
+
+#[tokio::test]
+async fn test_failed_move_keeps_source() {
+    let (fs, mover) = memory_mover();
+    fs.insert("/home/notes.txt", "notes");
+    fs.fail_on("/home/Organized/notes.txt");
+
+    assert!(mover.move_file(Path::new("/home/notes.txt"), Path::new("/home/Organized/notes.txt")).await.is_err());
+    assert_eq!(fs.contents(Path::new("/home/notes.txt")).unwrap(), b"notes");
+}
+
+#[tokio::test]
+async fn test_failed_cross_device_cleanup_leaves_both_copies() {
+    let (fs, mover) = memory_mover();
+    fs.insert("/home/song.mp3", "mp3");
+    fs.mount("/mnt/music");
+    fs.fail_on("/home/song.mp3");
+
+    assert!(mover.move_file(Path::new("/home/song.mp3"), Path::new("/mnt/music/song.mp3")).await.is_err());
+    assert_eq!(fs.paths(), vec![Path::new("/home/song.mp3"), Path::new("/mnt/music/song.mp3")]);
+}
+
+#[tokio::test]
+async fn test_moved_link_keeps_its_target() {
+    let (fs, mover) = memory_mover();
+    fs.insert("/home/docs/real.txt", "real");
+    fs.insert_link("/home/docs/link.txt", "real.txt");
+
+    mover.move_file(Path::new("/home/docs/link.txt"), Path::new("/home/Organized/link.txt")).await.unwrap();
+
+    assert_eq!(fs.read_link(Path::new("/home/Organized/link.txt")).await.unwrap(), Some("/home/docs/real.txt".into()));
+    assert_eq!(fs.read(Path::new("/home/Organized/link.txt")).await.unwrap(), b"real");
+    assert!(!fs.exists(Path::new("/home/docs/link.txt")).await.unwrap());
+}
+
+#[tokio::test]
+async fn test_followed_link_becomes_a_copy() {
+    let fs = Arc::new(MemoryFs::new());
+    let mover = FileMover::with_fs(fs.clone()).with_symlinks(SymlinkPolicy::Follow);
+    fs.insert("/home/docs/real.txt", "real");
+    fs.insert_link("/home/docs/link.txt", "/home/docs/real.txt");
+
+    mover.move_file(Path::new("/home/docs/link.txt"), Path::new("/home/Organized/link.txt")).await.unwrap();
+
+    assert_eq!(fs.read_link(Path::new("/home/Organized/link.txt")).await.unwrap(), None);
+    assert_eq!(fs.contents(Path::new("/home/Organized/link.txt")).unwrap(), b"real");
+    assert_eq!(fs.paths(), vec![Path::new("/home/Organized/link.txt"), Path::new("/home/docs/real.txt")]);
+}
+
+#[tokio::test]
+async fn test_conflicts_get_numbered_names() {
+    let fs = MemoryFs::new();
+    fs.insert("/Organized/a.txt", "1");
+    fs.insert("/Organized/a_1.txt", "2");
+
+    let resolved = resolve_conflict_in(&fs, Path::new("/Organized/a.txt"), false).await.unwrap();
+    assert_eq!(resolved, Path::new("/Organized/a_2.txt"));
+
+    let free = resolve_conflict_in(&fs, Path::new("/Organized/b.txt"), false).await.unwrap();
+    assert_eq!(free, Path::new("/Organized/b.txt"));
+}
+
+#[tokio::test]
+async fn test_overwrite_removes_existing_file() {
+    let fs = MemoryFs::new();
+    fs.insert("/Organized/a.txt", "old");
+
+    let resolved = resolve_conflict_in(&fs, Path::new("/Organized/a.txt"), true).await.unwrap();
+    assert_eq!(resolved, Path::new("/Organized/a.txt"));
+    assert!(!fs.exists(&resolved).await.unwrap());
+}
+
+#[tokio::test]
+async fn test_memory_fs_requires_parent_dirs() {
+    let fs = MemoryFs::new();
+    assert!(fs.write(Path::new("/missing/file.txt"), b"x").await.is_err());
+
+    fs.create_dir_all(Path::new("/missing")).await.unwrap();
+    fs.write(Path::new("/missing/file.txt"), b"x").await.unwrap();
+    assert_eq!(fs.list_files(Path::new("/missing")).await.unwrap(), vec![Path::new("/missing/file.txt")]);
+}
+
+#[tokio::test]
+async fn test_real_fs_lists_nested_files() {
+    let dir = tempdir().unwrap();
+    std::fs::create_dir(dir.path().join("sub")).unwrap();
+    std::fs::write(dir.path().join("sub/b.txt"), "b").unwrap();
+    std::fs::write(dir.path().join("a.txt"), "a").unwrap();
+
+    let files = RealFs.list_files(dir.path()).await.unwrap();
+    assert_eq!(files, vec![dir.path().join("a.txt"), dir.path().join("sub/b.txt")]);
+}
+
+#[tokio::test]
+async fn test_throttle_spreads_bytes_over_time() {
+    let throttle = Throttle::new(1_000_000);
+    let start = std::time::Instant::now();
+    for _ in 0..3 {
+        throttle.take(100_000).await;
+    }
+    // The first chunk goes at once, the next two wait 0.1s each
+    assert!(start.elapsed() >= std::time::Duration::from_millis(190), "{:?}", start.elapsed());
+}
+
+#[tokio::test]
+async fn test_throttled_copy_keeps_contents() {
+    let dir = tempdir().unwrap();
+    let src = dir.path().join("big.bin");
+    let contents: Vec<u8> = (0..600_000u32).map(|i| i as u8).collect();
+    std::fs::write(&src, &contents).unwrap();
+
+    let dest = dir.path().join("copy.bin");
+    ThrottledFs::new(64 * 1024 * 1024).copy(&src, &dest).await.unwrap();
+    assert_eq!(std::fs::read(&dest).unwrap(), contents);
+}
@@ -0,0 +1,72 @@
+mod test_utils;
+
+use std::time::{Duration, SystemTime};
+
+use stash::{
+    index::Db,
+    organizer::{organise_files, OrganizeOptions, ProcessingOrder},
+};
+use tempfile::tempdir;
+use test_utils::create_test_file;
+
+fn names(files: &[stash::scanner::RawFileMetadata]) -> Vec<&str> {
+    files.iter().map(|f| f.path.to_str().unwrap()).collect()
+}
+
+#[test]
+fn test_sort_by_size_and_age() {
+    let at = |secs| Some(SystemTime::UNIX_EPOCH + Duration::from_secs(secs));
+    let mut files = vec![
+        create_test_file("b", 30),
+        create_test_file("a", 10),
+        create_test_file("c", 10),
+        create_test_file("d", 20),
+    ];
+    files[0].modified = at(200);
+    files[1].modified = at(300);
+    files[3].modified = at(100);
+
+    ProcessingOrder::Scan.sort(&mut files);
+    assert_eq!(names(&files), ["b", "a", "c", "d"]);
+
+    // Equal sizes keep the scan order
+    ProcessingOrder::SmallFirst.sort(&mut files);
+    assert_eq!(names(&files), ["a", "c", "d", "b"]);
+
+    ProcessingOrder::LargeFirst.sort(&mut files);
+    assert_eq!(names(&files), ["b", "d", "a", "c"]);
+
+    // `c` has no mtime and goes last
+    ProcessingOrder::OldestFirst.sort(&mut files);
+    assert_eq!(names(&files), ["d", "b", "a", "c"]);
+
+    assert_eq!("Small-First".parse::<ProcessingOrder>(), Ok(ProcessingOrder::SmallFirst));
+    assert!("random".parse::<ProcessingOrder>().unwrap_err().contains("oldest-first"));
+}
+
+#[tokio::test]
+async fn test_small_first_moves_small_files_first() {
+    let dir = tempdir().unwrap();
+    for (name, size) in [("big.pdf", 4000), ("tiny.pdf", 10), ("medium.pdf", 500)] {
+        std::fs::write(dir.path().join(name), vec![b'x'; size]).unwrap();
+    }
+
+    let options = OrganizeOptions {
+        portable: true,
+        max_concurrency: Some(1),
+        order: ProcessingOrder::SmallFirst,
+        ..Default::default()
+    };
+    organise_files(dir.path(), &options).await.unwrap();
+
+    let db = Db::open_portable(dir.path()).await.unwrap();
+    let run = db.list_runs().await.unwrap()[0].id;
+    let moved: Vec<_> = db
+        .run_moves(run)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|m| m.source.file_name().unwrap().to_string_lossy().into_owned())
+        .collect();
+    assert_eq!(moved, ["tiny.pdf", "medium.pdf", "big.pdf"]);
+}
@@ -0,0 +1,151 @@
+use std::fs;
+
+use stash::{
+    errors::SkipReason,
+    hasher::HashAlgo,
+    organizer::{BundlePolicy, ConflictStrategy, Organizer},
+    rule_classifier::ClassifierRule,
+};
+use tempfile::tempdir;
+
+#[test]
+fn test_conflict_strategy_names() {
+    assert_eq!("Skip".parse::<ConflictStrategy>().unwrap(), ConflictStrategy::Skip);
+    assert_eq!("overwrite".parse::<ConflictStrategy>().unwrap(), ConflictStrategy::Overwrite);
+    assert!("merge".parse::<ConflictStrategy>().is_err());
+}
+
+#[test]
+fn test_build_checks_rules_and_categories() {
+    let organizer = Organizer::builder("/tmp/in")
+        .root("/tmp/other")
+        .disable_category("CODE")
+        .hash_algo(HashAlgo::Sha256)
+        .build()
+        .unwrap();
+    assert_eq!(organizer.roots().len(), 2);
+    assert!(organizer.options().disabled_categories.contains("Code"));
+    assert_eq!(organizer.options().hash_algo, HashAlgo::Sha256);
+
+    assert!(Organizer::builder("/tmp/in").disable_category("cod").build().is_err());
+
+    let rule: ClassifierRule = serde_json::from_str(r#"{ "name": "cad", "category": "CAD" }"#).unwrap();
+    assert!(Organizer::builder("/tmp/in").rule(rule).build().is_err());
+}
+
+#[tokio::test]
+async fn test_max_depth_descends_but_not_into_dest_or_hidden_dirs() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    fs::create_dir_all(root.join("sub/deeper")).unwrap();
+    fs::create_dir_all(root.join(".git")).unwrap();
+    fs::write(root.join("top.pdf"), "top").unwrap();
+    fs::write(root.join("sub/inner.jpg"), "inner").unwrap();
+    fs::write(root.join("sub/deeper/deep.mp3"), "deep").unwrap();
+    fs::write(root.join(".git/config"), "git").unwrap();
+
+    let summary = Organizer::builder(root).portable(true).max_depth(2).build().unwrap().run().await.unwrap();
+    assert_eq!(summary.moved, 2);
+    assert!(root.join("sub/deeper/deep.mp3").exists());
+    assert!(root.join(".git/config").exists());
+
+    // Organized files are not picked up again
+    let summary = Organizer::builder(root).portable(true).max_depth(10).build().unwrap().run().await.unwrap();
+    assert_eq!(summary.moved, 1);
+    assert!(!root.join("sub/deeper/deep.mp3").exists());
+}
+
+#[tokio::test]
+async fn test_conflict_strategies() {
+    for (strategy, moved, renamed, kept) in [
+        (ConflictStrategy::Rename, 0, 1, "old"),
+        (ConflictStrategy::Skip, 0, 0, "old"),
+        (ConflictStrategy::Overwrite, 1, 0, "newer"),
+    ] {
+        let (src, dest) = (tempdir().unwrap(), tempdir().unwrap());
+        fs::write(src.path().join("notes.txt"), "old").unwrap();
+        let organizer = Organizer::builder(src.path())
+            .dest(dest.path())
+            .portable(true)
+            .on_conflict(strategy)
+            .build()
+            .unwrap();
+        organizer.run().await.unwrap();
+        let organized = walkdir::WalkDir::new(dest.path())
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .find(|e| e.file_name() == "notes.txt")
+            .unwrap()
+            .into_path();
+
+        fs::write(src.path().join("notes.txt"), "newer").unwrap();
+        let summary = organizer.run().await.unwrap();
+
+        assert_eq!((summary.moved, summary.renamed), (moved, renamed), "{strategy:?}");
+        assert_eq!(fs::read_to_string(&organized).unwrap(), kept, "{strategy:?}");
+        let skipped = summary.skip_counts[SkipReason::Conflict.as_index()];
+        assert_eq!(skipped, usize::from(strategy == ConflictStrategy::Skip), "{strategy:?}");
+        assert_eq!(src.path().join("notes.txt").exists(), strategy == ConflictStrategy::Skip, "{strategy:?}");
+    }
+}
+
+#[tokio::test]
+async fn test_projects_and_apps_are_kept_whole() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    fs::create_dir_all(root.join("tool/src")).unwrap();
+    fs::write(root.join("tool/Cargo.toml"), "[package]").unwrap();
+    fs::write(root.join("tool/src/main.rs"), "fn main() {}").unwrap();
+    fs::write(root.join("tool/notes.pdf"), "notes").unwrap();
+    fs::create_dir_all(root.join("site/.git")).unwrap();
+    fs::write(root.join("site/index.html"), "<html>").unwrap();
+    fs::create_dir_all(root.join("Viewer.app/Contents/MacOS")).unwrap();
+    fs::write(root.join("Viewer.app/Contents/MacOS/Viewer"), "binary").unwrap();
+    fs::create_dir_all(root.join("loose")).unwrap();
+    fs::write(root.join("loose/photo.jpg"), "jpeg").unwrap();
+
+    // Skipped by default, however deep the scan goes
+    let summary = Organizer::builder(root).portable(true).max_depth(4).build().unwrap().run().await.unwrap();
+    assert_eq!(summary.moved, 1);
+    assert_eq!(summary.skip_counts[SkipReason::Bundle.as_index()], 3);
+    assert!(root.join("tool/notes.pdf").exists());
+    assert!(root.join("site/index.html").exists());
+
+    let organize = || Organizer::builder(root).portable(true).max_depth(4).bundles(BundlePolicy::Move).build().unwrap();
+    let summary = organize().run().await.unwrap();
+    assert_eq!(summary.moved, 3);
+    assert!(!root.join("tool").exists());
+    let projects = root.join("Organized/Code/Projects");
+    assert_eq!(fs::read_to_string(projects.join("tool/src/main.rs")).unwrap(), "fn main() {}");
+    assert!(projects.join("tool/notes.pdf").exists());
+    assert!(projects.join("site/.git").is_dir());
+    assert!(root.join("Organized/Executables/MacApp/Viewer.app/Contents/MacOS/Viewer").exists());
+
+    // Never merged into a project of the same name
+    fs::create_dir_all(root.join("tool")).unwrap();
+    fs::write(root.join("tool/package.json"), "{}").unwrap();
+    let summary = organize().run().await.unwrap();
+    assert_eq!(summary.renamed, 1);
+    assert!(projects.join("tool_1/package.json").exists());
+    assert!(!projects.join("tool/package.json").exists());
+}
+
+#[tokio::test]
+async fn test_first_runs_are_roots_never_organized() {
+    use stash::organizer::{first_runs, organise_files, OrganizeOptions};
+
+    let dir = tempdir().unwrap();
+    let root = dir.path().to_path_buf();
+    let roots = vec![root.clone()];
+    let options = OrganizeOptions { portable: true, ..Default::default() };
+    assert_eq!(first_runs(&[root.clone(), root.clone()], &options).await.unwrap(), roots);
+    // Checking doesn't create the index
+    assert!(!stash::utils::portable_db_path(&root).exists());
+
+    // A preview doesn't count, a run that moved nothing does
+    organise_files(&root, &OrganizeOptions { dry_run: true, ..options.clone() }).await.unwrap();
+    assert_eq!(first_runs(&roots, &options).await.unwrap(), roots);
+    fs::write(root.join(".hidden"), "x").unwrap();
+    organise_files(&root, &options).await.unwrap();
+    assert!(first_runs(&roots, &options).await.unwrap().is_empty());
+}
@@ -0,0 +1,62 @@
+use std::path::{Path, PathBuf};
+
+use stash::{
+    metadata::{ClassifiedFileMetadata, FileCategory},
+    path_builder::PathBuilder,
+    path_safety::{archive_entry_path, conflict_candidate, is_contained, is_valid_folder_name, sanitize_component},
+};
+
+#[test]
+fn test_sanitize_component() {
+    assert_eq!(sanitize_component("Fire/fox"), "Fire_fox");
+    assert_eq!(sanitize_component("a:b*c?"), "a_b_c_");
+    assert_eq!(sanitize_component(".."), "Unknown");
+    assert_eq!(sanitize_component(" . evil . "), "evil");
+    assert_eq!(sanitize_component("\u{0}"), "_");
+    assert_eq!(sanitize_component(""), "Unknown");
+}
+
+#[test]
+fn test_sanitized_names_are_valid_folders() {
+    for hostile in ["..", "../../etc", "C:\\Windows", " ", "\n", "a/b\\c:d", "...", "con."] {
+        assert!(is_valid_folder_name(&sanitize_component(hostile)), "{:?}", hostile);
+    }
+}
+
+#[test]
+fn test_is_contained() {
+    assert!(is_contained(Path::new("Documents/Pdf/a.pdf")));
+    assert!(is_contained(Path::new("./a")));
+    assert!(!is_contained(Path::new("../a")));
+    assert!(!is_contained(Path::new("/etc/passwd")));
+    assert!(!is_contained(Path::new("a/../../b")));
+}
+
+#[test]
+fn test_conflict_candidate() {
+    assert_eq!(conflict_candidate(Path::new("dir/file.txt"), 1), PathBuf::from("dir/file_1.txt"));
+    assert_eq!(conflict_candidate(Path::new("dir/archive.tar.gz"), 2), PathBuf::from("dir/archive.tar_2.gz"));
+    assert_eq!(conflict_candidate(Path::new("dir/.bashrc"), 1), PathBuf::from("dir/.bashrc_1"));
+    assert_eq!(conflict_candidate(Path::new("/"), 1), PathBuf::from("./_1"));
+}
+
+#[test]
+fn test_destination_for_rejects_paths_without_file_name() {
+    let meta = ClassifiedFileMetadata::new(PathBuf::from("a.bin"), FileCategory::Others);
+    assert_eq!(
+        PathBuilder::new(&meta).destination_for(Path::new("downloads/a.bin")),
+        Some(PathBuf::from("Organized/Others/a.bin"))
+    );
+    assert_eq!(PathBuilder::new(&meta).destination_for(Path::new("downloads/..")), None);
+    assert_eq!(PathBuilder::new(&meta).destination_for(Path::new("/")), None);
+}
+
+#[test]
+fn test_archive_entry_path() {
+    assert_eq!(archive_entry_path("a/b.txt"), Some(PathBuf::from("a/b.txt")));
+    assert_eq!(archive_entry_path("./a//b\\c.txt"), Some(PathBuf::from("a/b/c.txt")));
+    assert_eq!(archive_entry_path("dir/"), Some(PathBuf::from("dir")));
+    for hostile in ["", ".", "./", "../a", "a/../../b", "/etc/passwd", "\\\\server\\share", "C:\\x", "C:x", "a\0b", "a\\..\\..\\b"] {
+        assert_eq!(archive_entry_path(hostile), None, "{:?}", hostile);
+    }
+}
@@ -5,7 +5,7 @@ mod tests {
         ArchiveSubcategory, AudioSubcategory, ClassifiedFileMetadata, CodeSubcategory,
         DocumentSubcategory, ExecutableSubcategory, FileCategory, ImageSubcategory, VideoSubcategory,
     };
-    use stash::path_builder::PathBuilder;
+    use stash::path_builder::{DateFolders, FolderNames, NameStyle, PathBuilder};
 
     #[test]
     fn test_document_subcategory_as_ref() {
@@ -72,6 +72,46 @@ mod tests {
         assert_eq!(path, Path::new("Organized/Documents/Pdf/2024"));
     }
 
+    #[test]
+    fn test_pathbuilder_month_follows_year() {
+        let meta = ClassifiedFileMetadata {
+            category: FileCategory::Documents(DocumentSubcategory::Pdf),
+            year: Some(2024),
+            month: Some(1),
+            ..Default::default()
+        };
+
+        let path = PathBuilder::new(&meta).date_folders(DateFolders::YearMonth).build();
+        assert_eq!(path, Path::new("Organized/Documents/Pdf/2024/01"));
+    }
+
+    #[test]
+    fn test_pathbuilder_date_folders() {
+        let meta = ClassifiedFileMetadata {
+            category: FileCategory::Images(ImageSubcategory::Jpeg),
+            year: Some(2024),
+            month: Some(3),
+            day: Some(7),
+            ..Default::default()
+        };
+
+        for (folders, expected) in [
+            ("none", "Organized/Images/Jpeg"),
+            ("year", "Organized/Images/Jpeg/2024"),
+            ("year-month", "Organized/Images/Jpeg/2024/03"),
+            ("Year-Month-Day", "Organized/Images/Jpeg/2024/03/07"),
+        ] {
+            let folders: DateFolders = folders.parse().unwrap();
+            assert_eq!(PathBuilder::new(&meta).date_folders(folders).build(), Path::new(expected));
+        }
+        assert!("month".parse::<DateFolders>().is_err());
+
+        // Only as deep as the date is known
+        let meta = ClassifiedFileMetadata { day: None, ..meta };
+        let path = PathBuilder::new(&meta).date_folders(DateFolders::YearMonthDay).build();
+        assert_eq!(path, Path::new("Organized/Images/Jpeg/2024/03"));
+    }
+
     #[test]
     fn test_pathbuilder_images_custom_base() {
         let meta = ClassifiedFileMetadata {
@@ -97,4 +137,50 @@ mod tests {
         let path = PathBuilder::new(&meta).build();
         assert_eq!(path, Path::new("Organized/Others"));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_pathbuilder_folder_names() {
+        let meta = ClassifiedFileMetadata {
+            category: FileCategory::Images(ImageSubcategory::Screenshots),
+            year: Some(2024),
+            ..Default::default()
+        };
+
+        let names = FolderNames::default().rename("Images", "Bilder");
+        assert_eq!(PathBuilder::new(&meta).names(&names).build(), Path::new("Organized/Bilder/Screenshots/2024"));
+
+        // Renamed folders are kept as written; the style applies to the rest
+        let names = FolderNames::new(NameStyle::Lowercase).rename("Images", "Bilder");
+        assert_eq!(PathBuilder::new(&meta).names(&names).build(), Path::new("Organized/Bilder/screenshots/2024"));
+        let meta = ClassifiedFileMetadata { category: FileCategory::Code(CodeSubcategory::Cpp), ..meta };
+        assert_eq!(PathBuilder::new(&meta).names(&names).build(), Path::new("Organized/code/c++/2024"));
+        let names = FolderNames::new(NameStyle::Uppercase);
+        assert_eq!(PathBuilder::new(&meta).names(&names).build(), Path::new("Organized/CODE/C++/2024"));
+    }
+
+    #[test]
+    fn test_pathbuilder_custom_category_layout() {
+        use stash::metadata::CustomCategory;
+        use stash::path_builder::validate_layout;
+
+        let work = CustomCategory { name: "Work", subfolder: Some("Invoices/Acme"), layout: None };
+        let mut meta = ClassifiedFileMetadata::new("bill.pdf".into(), FileCategory::Custom(work));
+        meta.year = Some(2024);
+        meta.month = Some(3);
+        assert_eq!(meta.category.to_string(), "Work::Invoices::Acme");
+        assert_eq!(PathBuilder::new(&meta).build(), Path::new("Organized/Work/Invoices/Acme/2024"));
+
+        let layout = CustomCategory { layout: Some("FY{year}/{month}/{subfolder}/{day}"), ..work };
+        meta.category = FileCategory::Custom(layout);
+        let names = FolderNames::new(NameStyle::Lowercase);
+        assert_eq!(
+            PathBuilder::new(&meta).date_folders(DateFolders::None).names(&names).build(),
+            Path::new("Organized/work/FY2024/03/invoices/acme")
+        );
+
+        assert!(validate_layout("{year}/{subfolder}").is_ok());
+        for bad in ["", "{year}/../x", "{week}", "x{subfolder}", "{year}//{month}"] {
+            assert!(validate_layout(bad).is_err(), "{bad}");
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use std::collections::HashMap;
+
+use stash::{
+    config::{dest_permissions, PermissionRule},
+    permissions::{parse_mode, DestPermissions},
+};
+
+#[test]
+fn test_parse_mode() {
+    assert_eq!(parse_mode("0640"), Ok(0o640));
+    assert_eq!(parse_mode("750"), Ok(0o750));
+    assert_eq!(parse_mode("0o2775"), Ok(0o2775));
+    assert!(parse_mode("0999").is_err());
+    assert!(parse_mode("17777").is_err());
+    assert!(parse_mode("").unwrap_err().contains("octal"));
+}
+
+#[test]
+fn test_config_permissions() {
+    let rule = |mode: &str, group: Option<&str>| PermissionRule {
+        mode: Some(mode.to_string()),
+        group: group.map(str::to_string),
+    };
+    let config = HashMap::from([
+        ("documents".to_string(), rule("0640", None)),
+        ("Executables".to_string(), rule("0750", Some("0"))),
+    ]);
+    let resolved = dest_permissions(&config, &[]).unwrap();
+    assert_eq!(resolved["Documents"], DestPermissions { mode: Some(0o640), gid: None });
+    assert_eq!(resolved["Executables"], DestPermissions { mode: Some(0o750), gid: Some(0) });
+
+    let bad_mode = HashMap::from([("images".to_string(), rule("rw-r--r--", None))]);
+    assert!(dest_permissions(&bad_mode, &[]).is_err());
+    let bad_group = HashMap::from([("images".to_string(), rule("0644", Some("no-such-group-here")))]);
+    assert!(dest_permissions(&bad_group, &[]).unwrap_err().to_string().contains("no-such-group-here"));
+    let bad_category = HashMap::from([("pictures".to_string(), rule("0644", None))]);
+    assert!(dest_permissions(&bad_category, &[]).is_err());
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_permissions_are_applied_and_reverted() {
+    use std::{collections::BTreeMap, os::unix::fs::PermissionsExt};
+
+    use stash::{
+        organizer::{organise_files, OrganizeOptions},
+        reverter::{revert_files, RevertOptions},
+    };
+    use tempfile::tempdir;
+    use walkdir::WalkDir;
+
+    let dir = tempdir().unwrap();
+    let report = dir.path().join("report.pdf");
+    let photo = dir.path().join("photo.jpg");
+    for file in [&report, &photo] {
+        std::fs::write(file, b"data").unwrap();
+        std::fs::set_permissions(file, std::fs::Permissions::from_mode(0o664)).unwrap();
+    }
+    let mode = |path: &std::path::Path| std::fs::metadata(path).unwrap().permissions().mode() & 0o7777;
+
+    let options = OrganizeOptions {
+        portable: true,
+        permissions: BTreeMap::from([("Documents", DestPermissions { mode: Some(0o640), gid: None })]),
+        ..Default::default()
+    };
+    organise_files(dir.path(), &options).await.unwrap();
+
+    let organized = |name: &str| {
+        WalkDir::new(dir.path().join("Organized"))
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .find(|e| e.file_name() == name)
+            .unwrap()
+            .into_path()
+    };
+    assert_eq!(mode(&organized("report.pdf")), 0o640);
+    // Categories without settings keep what they had
+    assert_eq!(mode(&organized("photo.jpg")), 0o664);
+
+    let options = RevertOptions { portable: true, ..Default::default() };
+    revert_files(dir.path(), &options).await.unwrap();
+    assert_eq!(mode(&report), 0o664);
+    assert_eq!(mode(&photo), 0o664);
+}
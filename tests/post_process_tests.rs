@@ -0,0 +1,96 @@
+use std::{ffi::OsString, path::Path};
+
+use stash::{
+    config::OrganizerConfig,
+    post_process::{expand_args, PostCommands, PostProcessor},
+};
+use tempfile::tempdir;
+
+fn args(list: &[&str]) -> Vec<String> {
+    list.iter().map(|s| s.to_string()).collect()
+}
+
+#[test]
+fn test_expand_args_fills_placeholders() {
+    let (src, dest) = (Path::new("/in/a.jpg"), Path::new("/out/Images/a.jpg"));
+
+    let expanded = expand_args(&args(&["exiftool", "-P", "{dest}"]), src, dest, "Images");
+    assert_eq!(expanded, vec![OsString::from("exiftool"), "-P".into(), "/out/Images/a.jpg".into()]);
+
+    let expanded = expand_args(&args(&["index", "--from={src}", "--tag={category}"]), src, dest, "Images");
+    assert_eq!(
+        expanded,
+        vec![OsString::from("index"), "--from=/in/a.jpg".into(), "--tag=Images".into(), "/out/Images/a.jpg".into()]
+    );
+}
+
+#[test]
+fn test_after_move_config_is_validated() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("config.json");
+    std::fs::write(&path, r#"{ "after_move": { "images": ["exiftool", "-P", "{dest}"] }, "after_move_jobs": 2 }"#).unwrap();
+
+    let commands = OrganizerConfig::load_from_file(&path).unwrap().post_commands();
+    assert_eq!(commands.commands["Images"], args(&["exiftool", "-P", "{dest}"]));
+    assert_eq!(commands.jobs, 2);
+
+    std::fs::write(&path, r#"{ "after_move": { "imagez": ["exiftool"] } }"#).unwrap();
+    assert!(OrganizerConfig::load_from_file(&path).is_err());
+    std::fs::write(&path, r#"{ "after_move": { "images": [] } }"#).unwrap();
+    assert!(OrganizerConfig::load_from_file(&path).is_err());
+}
+
+#[test]
+fn test_no_commands_means_no_processor() {
+    assert!(PostProcessor::new(&PostCommands::default()).is_none());
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_commands_run_per_category_and_failures_are_counted() {
+    use std::collections::BTreeMap;
+
+    let dir = tempdir().unwrap();
+    let (photo, doc) = (dir.path().join("a.jpg"), dir.path().join("b.pdf"));
+    std::fs::write(&photo, "jpg").unwrap();
+    std::fs::write(&doc, "pdf").unwrap();
+
+    let config = PostCommands {
+        commands: BTreeMap::from([("Images", args(&["touch", "{dest}.seen"])), ("Documents", args(&["false"]))]),
+        jobs: 1,
+    };
+    let post = PostProcessor::new(&config).unwrap();
+    post.after_move("Images", &photo, &photo);
+    post.after_move("Documents", &doc, &doc);
+    post.after_move("Audio", &doc, &doc);
+
+    let report = post.finish().await;
+    assert_eq!((report.ran, report.failed), (2, 1));
+    assert!(dir.path().join("a.jpg.seen").exists());
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_organize_runs_command_on_destination() {
+    use std::collections::BTreeMap;
+
+    use stash::organizer::{organise_files, OrganizeOptions};
+
+    let dir = tempdir().unwrap();
+    std::fs::write(dir.path().join("report.pdf"), "pdf").unwrap();
+    let options = OrganizeOptions {
+        portable: true,
+        post_process: PostCommands {
+            commands: BTreeMap::from([("Documents", args(&["touch", "{dest}.indexed"]))]),
+            jobs: 0,
+        },
+        ..Default::default()
+    };
+    organise_files(dir.path(), &options).await.unwrap();
+
+    let marked = walkdir::WalkDir::new(dir.path().join("Organized"))
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .any(|e| e.file_name() == "report.pdf.indexed");
+    assert!(marked);
+}
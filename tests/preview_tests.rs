@@ -0,0 +1,82 @@
+mod test_utils;
+
+#[cfg(test)]
+mod tests {
+    use stash::{
+        metadata::{FileCategory, ImageSubcategory},
+        preview::{classify_name, classify_name_with, preview, ListingEntry},
+        utils::create_classifier_registry,
+    };
+
+    use crate::test_utils::create_test_file;
+
+    // 2024-03-12T00:00:00Z
+    const MARCH_2024: i64 = 1_710_201_600;
+
+    #[test]
+    fn test_classify_name_uses_extension() {
+        let meta = classify_name("Holiday.JPG", 2048, Some(MARCH_2024));
+        assert!(matches!(meta.category, FileCategory::Images(ImageSubcategory::Jpeg)));
+        assert_eq!(meta.year, Some(2024));
+        assert_eq!(meta.file_size, Some(2048));
+        assert_eq!(meta.mime_type.as_deref(), Some("image/jpeg"));
+    }
+
+    #[test]
+    fn test_preview_destinations() {
+        let entries = vec![
+            ListingEntry { name: "Holiday.JPG".into(), size: 10, modified: Some(MARCH_2024) },
+            ListingEntry { name: "main.rs".into(), size: 10, modified: Some(MARCH_2024) },
+            ListingEntry { name: "mystery.qqq".into(), size: 10, modified: Some(MARCH_2024) },
+        ];
+
+        let planned = preview(&entries);
+        let destinations: Vec<_> = planned.iter().map(|p| p.destination.as_str()).collect();
+        assert_eq!(destinations, vec![
+            "Organized/Images/Jpeg/2024/Holiday.JPG",
+            "Organized/Code/Rust/2024/main.rs",
+            "Organized/Others/mystery.qqq",
+        ]);
+        assert_eq!(planned[0].category, "Images::Jpeg");
+    }
+
+    #[test]
+    fn test_listing_entry_deserializes_with_defaults() {
+        let entry: ListingEntry = serde_json::from_str(r#"{"name": "a.pdf"}"#).unwrap();
+        assert_eq!(entry.size, 0);
+        assert!(entry.modified.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_preview_matches_registry() {
+        let registry = create_classifier_registry();
+        let dir = tempfile::tempdir().unwrap();
+
+        for name in [
+            "a.pdf", "b.md", "c.conf", "d.apk", "e.tar", "f.mp3", "g.mkv", "h.csv", "Dockerfile",
+            "Screenshot 2024-03-12 at 10.15.02.png", "backup-2024.zip", "notes.txt~",
+        ] {
+            let path = dir.path().join(name);
+            std::fs::write(&path, b"x").unwrap();
+            let raw = create_test_file(path.to_str().unwrap(), 1);
+
+            let native = registry.classify(&raw).await.unwrap();
+            let pure = classify_name(name, 1, None);
+            assert_eq!(native.category.to_string(), pure.category.to_string(), "mismatch for {name}");
+        }
+
+        // A minimum score leaves the same files to `Others` in both
+        let registry = create_classifier_registry().with_min_score(9000);
+        let path = dir.path().join("a.pdf");
+        let native = registry.classify(&create_test_file(path.to_str().unwrap(), 1)).await.unwrap();
+        let pure = classify_name_with("a.pdf", 1, None, 9000);
+        assert_eq!(native.category.to_string(), pure.category.to_string());
+    }
+
+    #[test]
+    fn test_screenshots_are_dated_by_their_name() {
+        let meta = classify_name("Screenshot 2023-11-02 at 09.41.10.png", 10, Some(MARCH_2024));
+        assert!(matches!(meta.category, FileCategory::Images(ImageSubcategory::Screenshots)));
+        assert_eq!(meta.year, Some(2023));
+    }
+}
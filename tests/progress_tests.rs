@@ -0,0 +1,75 @@
+use std::{
+    io::Write,
+    sync::{Arc, Mutex},
+};
+
+use stash::{
+    organizer::{organise_files, OrganizeOptions},
+    progress::{Json, Progress, ProgressEvent, ProgressMode},
+};
+use tempfile::tempdir;
+
+/// Writer whose contents can be read after the reporter is done with it
+#[derive(Clone, Default)]
+struct SharedBuf(Arc<Mutex<Vec<u8>>>);
+
+impl Write for SharedBuf {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_channel_progress_from_organize() {
+    let dir = tempdir().unwrap();
+    for name in ["a.txt", "b.pdf", "c.png"] {
+        std::fs::write(dir.path().join(name), name).unwrap();
+    }
+
+    let (progress, mut rx) = Progress::channel();
+    let options = OrganizeOptions { dry_run: true, progress, ..Default::default() };
+    organise_files(dir.path(), &options).await.unwrap();
+
+    let mut events = Vec::new();
+    while let Ok(event) = rx.try_recv() {
+        events.push(event);
+    }
+
+    assert!(matches!(&events[0], ProgressEvent::Started { total: 3, .. }));
+    let advanced = events.iter().filter(|e| matches!(e, ProgressEvent::Advanced { .. })).count();
+    assert_eq!(advanced, 3);
+    assert_eq!(events.last(), Some(&ProgressEvent::Finished { done: 3, total: 3 }));
+}
+
+#[test]
+fn test_json_progress_lines() {
+    let buf = SharedBuf::default();
+    let progress = Progress::new(Json::new(buf.clone()));
+
+    progress.start("Organizing", 2);
+    progress.inc(1);
+    progress.inc(1);
+    progress.finish();
+
+    let text = String::from_utf8(buf.0.lock().unwrap().clone()).unwrap();
+    let lines: Vec<serde_json::Value> = text.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+
+    assert_eq!(lines[0]["event"], "started");
+    assert_eq!(lines[0]["label"], "Organizing");
+    // Intermediate steps may be throttled, the final step never is
+    let last_advanced = lines.iter().rev().find(|l| l["event"] == "advanced").unwrap();
+    assert_eq!(last_advanced["done"], 2);
+    assert_eq!(lines.last().unwrap()["event"], "finished");
+}
+
+#[test]
+fn test_progress_mode_parse() {
+    assert_eq!("json".parse::<ProgressMode>().unwrap(), ProgressMode::Json);
+    assert_eq!("NONE".parse::<ProgressMode>().unwrap(), ProgressMode::None);
+    assert!("fancy".parse::<ProgressMode>().is_err());
+}
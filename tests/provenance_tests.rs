@@ -0,0 +1,129 @@
+use stash::provenance::{
+    app_from_name, domain_of, parse_quarantine, parse_zone_identifier, urls_from_where_froms, GroupTemplate,
+    Provenance,
+};
+use std::path::PathBuf;
+
+#[test]
+fn test_domain_of() {
+    assert_eq!(domain_of("https://www.GitHub.com/owner/repo/archive.zip").as_deref(), Some("github.com"));
+    assert_eq!(domain_of("http://user@files.example.org:8080/x?y#z").as_deref(), Some("files.example.org"));
+    assert_eq!(domain_of("https://[::1]:8000/file").as_deref(), Some("::1"));
+    assert_eq!(domain_of("not a url"), None);
+}
+
+#[test]
+fn test_platform_hint_parsers() {
+    let zone = "[ZoneTransfer]\r\nZoneId=3\r\nReferrerUrl=https://example.com/page\r\nHostUrl=https://cdn.example.com/a.zip\r\n";
+    assert_eq!(
+        parse_zone_identifier(zone),
+        (Some("https://cdn.example.com/a.zip".into()), Some("https://example.com/page".into()))
+    );
+    assert_eq!(parse_zone_identifier("ZoneId=3\nHostUrl=about:internet"), (None, None));
+
+    assert_eq!(parse_quarantine("0083;65a1b2c3;Safari;F1E2D3C4").as_deref(), Some("Safari"));
+    assert_eq!(parse_quarantine("0083;65a1b2c3;;"), None);
+
+    // bplist00 array with two ASCII strings
+    let mut plist = b"bplist00\xa2\x01\x02\x5f\x10\x1c".to_vec();
+    plist.extend_from_slice(b"https://dl.example.com/x.dmg");
+    plist.extend_from_slice(b"\x5f\x10\x14https://example.com/\x00\x08");
+    assert_eq!(
+        urls_from_where_froms(&plist),
+        vec!["https://dl.example.com/x.dmg".to_string(), "https://example.com/".to_string()]
+    );
+
+    assert_eq!(app_from_name("WhatsApp Image 2024-05-01 at 10.00.00.jpeg"), Some("WhatsApp"));
+    assert_eq!(app_from_name("report.pdf"), None);
+}
+
+#[test]
+fn test_group_template() {
+    assert!("{nope}".parse::<GroupTemplate>().is_err());
+    assert!("{source_app".parse::<GroupTemplate>().is_err());
+    assert!("".parse::<GroupTemplate>().is_err());
+    assert!("C:/{source_app}".parse::<GroupTemplate>().is_err());
+
+    let template: GroupTemplate = "From {source_app}/{source_domain}".parse().unwrap();
+    let provenance = Provenance {
+        source_app: Some("Fire/fox".into()),
+        source_domain: Some("example.com".into()),
+        ..Default::default()
+    };
+    assert_eq!(template.expand(&provenance), PathBuf::from("From Fire_fox/example.com"));
+    assert_eq!(template.expand(&Provenance::default()), PathBuf::from("From Unknown/Unknown"));
+}
+
+#[test]
+fn test_group_template_category_placement() {
+    assert!("{category}/{domain}/{category}".parse::<GroupTemplate>().is_err());
+
+    let provenance = Provenance { source_domain: Some("github.com".into()), ..Default::default() };
+    let template: GroupTemplate = "{domain}/{category}".parse().unwrap();
+    assert_eq!(template.expand(&provenance), PathBuf::from("github.com"));
+    assert_eq!(template.expand_after(&provenance), PathBuf::new());
+
+    let template: GroupTemplate = "Downloads/{category}/{domain}".parse().unwrap();
+    assert_eq!(template.expand(&provenance), PathBuf::from("Downloads"));
+    assert_eq!(template.expand_after(&provenance), PathBuf::from("github.com"));
+}
+
+#[tokio::test]
+async fn test_organize_with_category_before_app() {
+    use stash::organizer::{organise_files, OrganizeOptions};
+
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("WhatsApp Image 2024-05-01 at 10.00.00.pdf"), b"pdf").unwrap();
+
+    let options = OrganizeOptions {
+        portable: true,
+        date_folders: Some(stash::path_builder::DateFolders::None),
+        group: Some("{category}/{app}".parse().unwrap()),
+        ..Default::default()
+    };
+    organise_files(dir.path(), &options).await.unwrap();
+    assert!(dir.path().join("Organized/Documents/Pdf/WhatsApp/WhatsApp Image 2024-05-01 at 10.00.00.pdf").exists());
+}
+
+#[tokio::test]
+async fn test_organize_grouped_by_source_app() {
+    use stash::organizer::{organise_files, OrganizeOptions};
+
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("WhatsApp Image 2024-05-01 at 10.00.00.pdf"), b"pdf").unwrap();
+    std::fs::write(dir.path().join("plain.pdf"), b"pdf too").unwrap();
+
+    let options = OrganizeOptions {
+        portable: true,
+        group: Some("{source_app}".parse().unwrap()),
+        ..Default::default()
+    };
+    organise_files(dir.path(), &options).await.unwrap();
+    assert!(dir.path().join("Organized/WhatsApp/Documents").is_dir());
+    assert!(dir.path().join("Organized/Unknown/Documents").is_dir());
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_detect_reads_xdg_origin_xattr() {
+    use std::{ffi::CString, os::unix::ffi::OsStrExt};
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("archive.zip");
+    std::fs::write(&path, b"zip").unwrap();
+
+    let c_path = CString::new(path.as_os_str().as_bytes()).unwrap();
+    let url = b"https://www.example.com/archive.zip";
+    let rc = unsafe {
+        libc::setxattr(c_path.as_ptr(), c"user.xdg.origin.url".as_ptr(), url.as_ptr().cast(), url.len(), 0)
+    };
+    if rc != 0 {
+        eprintln!("user xattrs unsupported here, skipping");
+        return;
+    }
+
+    let provenance = Provenance::detect(&path);
+    assert_eq!(provenance.source_url.as_deref(), Some("https://www.example.com/archive.zip"));
+    assert_eq!(provenance.source_domain.as_deref(), Some("example.com"));
+    assert_eq!(provenance.source_app.as_deref(), Some("Browser"));
+}
@@ -0,0 +1,204 @@
+use std::{
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use chrono::{Local, NaiveDate, TimeZone};
+use clap::Parser;
+use stash::{
+    cli::{Args, Commands},
+    history::IndexStats,
+    index::{Db, DbFileEntry},
+    query::{parse_day, FileQuery, FileState, IndexScope, QueryFormat, QueryTable},
+};
+
+/// Local noon of a day, as an mtime
+fn noon(y: i32, m: u32, d: u32) -> SystemTime {
+    Local.with_ymd_and_hms(y, m, d, 12, 0, 0).unwrap().into()
+}
+
+fn entry(name: &str, category: &str, size: u64, modified: SystemTime) -> DbFileEntry {
+    DbFileEntry {
+        path: PathBuf::from("/home/me/Downloads").join(name),
+        size,
+        modified: Some(modified),
+        hash: None,
+        category: Some(category.to_string()),
+        dest_path: PathBuf::from("/home/me/Downloads/Organized").join(category.replace("::", "/")).join(name),
+    }
+}
+
+async fn indexed() -> Db {
+    let db = Db::new(Path::new(":memory:")).await.unwrap();
+    db.upsert_file_entries(&[
+        entry("tax-2023.pdf", "Documents::Pdf", 200_000, noon(2024, 2, 10)),
+        entry("tax-2022.PDF", "Documents::Pdf", 150_000, noon(2023, 3, 1)),
+        entry("letter.docx", "Documents::Word", 30_000, noon(2024, 5, 1)),
+        entry("beach.jpg", "Images::Jpeg", 6 << 20, noon(2024, 7, 4)),
+        entry("thumb.jpg", "Images::Jpeg", 40_000, noon(2024, 7, 4)),
+        entry("sunset.png", "Images::Png", 8 << 20, noon(2023, 12, 31)),
+    ])
+    .await
+    .unwrap();
+    db
+}
+
+fn names(rows: &[stash::query::QueryRow]) -> Vec<String> {
+    rows.iter().map(|row| row.path.file_name().unwrap().to_string_lossy().into_owned()).collect()
+}
+
+#[tokio::test]
+async fn test_filters_combine() {
+    let db = indexed().await;
+
+    let query = FileQuery {
+        category: Some("images".into()),
+        extensions: vec!["jpg".into()],
+        min_size: Some(5 << 20),
+        since: Some(parse_day("2024-01-01").unwrap()),
+        ..Default::default()
+    };
+    assert_eq!(names(&query.run(&db).await.unwrap()), ["beach.jpg"]);
+
+    let tax = FileQuery { name: Some("*tax*".into()), extensions: vec![".pdf".into()], ..Default::default() };
+    let rows = tax.run(&db).await.unwrap();
+    assert_eq!(names(&rows), ["tax-2022.PDF", "tax-2023.pdf"]);
+    assert_eq!(rows[1].dest, Path::new("/home/me/Downloads/Organized/Documents/Pdf/tax-2023.pdf"));
+    assert_eq!(rows[1].category.as_deref(), Some("Documents::Pdf"));
+
+    // `until` includes the whole day
+    let old = FileQuery { until: NaiveDate::from_ymd_opt(2023, 12, 31), ..Default::default() };
+    assert_eq!(names(&old.run(&db).await.unwrap()), ["sunset.png", "tax-2022.PDF"]);
+
+    let small = FileQuery { max_size: Some(100_000), category: Some("Documents".into()), ..Default::default() };
+    assert_eq!(names(&small.run(&db).await.unwrap()), ["letter.docx"]);
+
+    let largest = FileQuery { limit: Some(2), ..Default::default() };
+    assert_eq!(names(&largest.run(&db).await.unwrap()), ["sunset.png", "beach.jpg"]);
+
+    let none = FileQuery { category: Some("Videos".into()), ..Default::default() };
+    assert!(none.run(&db).await.unwrap().is_empty());
+}
+
+/// A file recorded by `stash index` on a share, left where it is
+fn inventoried(path: &str, category: &str, size: u64) -> DbFileEntry {
+    DbFileEntry {
+        path: PathBuf::from(path),
+        size,
+        modified: Some(noon(2024, 1, 1)),
+        hash: Some("blake3:00".into()),
+        category: Some(category.to_string()),
+        dest_path: PathBuf::from(path),
+    }
+}
+
+#[tokio::test]
+async fn test_scope_covers_indexed_but_unorganized_trees() {
+    let db = indexed().await;
+    db.upsert_file_entries(&[
+        inventoried("/mnt/nas/archive/scans/tax-2019.pdf", "Documents::Pdf", 90_000),
+        inventoried("/mnt/nas/archive/video.mkv", "Videos::Movies", 700 << 20),
+        inventoried("/mnt/nas/archive-old/notes.txt", "Documents::Text", 100),
+    ])
+    .await
+    .unwrap();
+    let nas = IndexScope { under: Some("/mnt/nas/archive".into()), state: None, ..Default::default() };
+
+    let query = FileQuery { scope: nas.clone(), ..Default::default() };
+    assert_eq!(names(&query.run(&db).await.unwrap()), ["tax-2019.pdf", "video.mkv"]);
+    let tax = FileQuery { name: Some("*tax*".into()), scope: nas.clone(), ..Default::default() };
+    assert_eq!(names(&tax.run(&db).await.unwrap()), ["tax-2019.pdf"]);
+
+    // Scoped by current location: organized files count under their destination
+    let organized = IndexScope { under: Some("/home/me/Downloads/Organized/Images/".into()), state: None, ..Default::default() };
+    let query = FileQuery { scope: organized, ..Default::default() };
+    assert_eq!(names(&query.run(&db).await.unwrap()), ["beach.jpg", "sunset.png", "thumb.jpg"]);
+
+    let unorganized = IndexScope { state: Some(FileState::Unorganized), ..Default::default() };
+    let query = FileQuery { category: Some("Documents".into()), scope: unorganized.clone(), ..Default::default() };
+    assert_eq!(names(&query.run(&db).await.unwrap()), ["notes.txt", "tax-2019.pdf"]);
+    let query = FileQuery { name: Some("*tax*".into()), scope: IndexScope::organized(), ..Default::default() };
+    assert_eq!(names(&query.run(&db).await.unwrap()), ["tax-2022.PDF", "tax-2023.pdf"]);
+
+    // Stats count organized files unless asked otherwise
+    assert_eq!(IndexStats::collect(&db, 10).await.unwrap().largest.len(), 6);
+    let stats = IndexStats::collect_scoped(&db, 10, &nas).await.unwrap();
+    let categories: Vec<_> = stats.categories.iter().map(|c| (c.category.as_str(), c.files)).collect();
+    assert_eq!(categories, [("Documents", 1), ("Videos", 1)]);
+    assert_eq!(stats.largest[0].path, Path::new("/mnt/nas/archive/video.mkv"));
+    assert!(stats.moves_per_day.is_empty());
+    let stats = IndexStats::collect_scoped(&db, 10, &unorganized).await.unwrap();
+    assert_eq!(stats.categories.iter().map(|c| c.files).sum::<u64>(), 3);
+    colored::control::set_override(false);
+    let text = stats.to_string();
+    assert!(text.starts_with("Scope: unorganized files\n") && text.contains("Largest files:"), "{}", text);
+    assert!(!text.contains("Moves per day"), "{}", text);
+}
+
+#[tokio::test]
+async fn test_scope_follows_a_remounted_drive() {
+    let db = indexed().await;
+    db.upsert_file_entries(&[
+        inventoried("/mnt/nas/archive/scans/tax-2019.pdf", "Documents::Pdf", 90_000),
+        inventoried("/mnt/nas/archive-old/notes.txt", "Documents::Text", 100),
+    ])
+    .await
+    .unwrap();
+    // The NAS is mounted at /media/nas now
+    let nas = IndexScope {
+        under: Some("/media/nas/archive".into()),
+        mappings: vec!["/mnt/nas=/media/nas".parse().unwrap()],
+        ..Default::default()
+    };
+
+    let rows = FileQuery { scope: nas.clone(), ..Default::default() }.run(&db).await.unwrap();
+    assert_eq!(names(&rows), ["tax-2019.pdf"]);
+    assert_eq!(rows[0].path, Path::new("/media/nas/archive/scans/tax-2019.pdf"));
+    assert_eq!(rows[0].dest, rows[0].path);
+    let stats = IndexStats::collect_scoped(&db, 10, &nas).await.unwrap();
+    assert_eq!(stats.largest.len(), 1);
+    assert_eq!(stats.largest[0].dest, Path::new("/media/nas/archive/scans/tax-2019.pdf"));
+}
+
+#[tokio::test]
+async fn test_output_formats() {
+    let db = indexed().await;
+    let rows = FileQuery { name: Some("beach".into()), ..Default::default() }.run(&db).await.unwrap();
+
+    colored::control::set_override(false);
+    let table = QueryTable(&rows).to_string();
+    assert!(table.contains("6.0 MB  2024-07-04  Images::Jpeg"), "{}", table);
+    assert!(table.contains("beach.jpg → /home/me/Downloads/Organized/Images/Jpeg/beach.jpg"), "{}", table);
+    assert!(table.ends_with("1 files, 6.0 MB\n"), "{}", table);
+    assert_eq!(QueryTable(&[]).to_string(), "No matching files.\n");
+
+    let json: serde_json::Value = serde_json::to_value(&rows).unwrap();
+    assert_eq!(json[0]["size"], 6 << 20);
+    assert!(json[0]["modified"].as_str().unwrap().starts_with("2024-07-04T12:00:00"));
+}
+
+#[test]
+fn test_query_arguments() {
+    let args = Args::try_parse_from([
+        "stash", "query", "--category", "Images", "--ext", "jpg", "--ext", "png", "--min-size", "5MB", "--since",
+        "2024-01-01", "--format", "json",
+    ])
+    .unwrap();
+    match args.cmd {
+        Commands::Query { category, extensions, min_size, since, format, .. } => {
+            assert_eq!(category.as_deref(), Some("Images"));
+            assert_eq!(extensions, ["jpg", "png"]);
+            assert_eq!(min_size, Some(5 << 20));
+            assert_eq!(since, NaiveDate::from_ymd_opt(2024, 1, 1));
+            assert_eq!(format, QueryFormat::Json);
+        }
+        other => panic!("parsed as {:?}", other),
+    }
+    assert!(Args::try_parse_from(["stash", "query", "--since", "01/02/2024"]).is_err());
+    assert!(Args::try_parse_from(["stash", "query", "--format", "csv"]).is_err());
+    assert!(Args::try_parse_from(["stash", "query", "--organized", "--unorganized"]).is_err());
+    assert!(Args::try_parse_from(["stash", "stats", "--unorganized", "--all"]).is_err());
+    assert!(Args::try_parse_from(["stash", "stats", "--all-runs", "--map-prefix", "/a=/b"]).is_err());
+    let args = Args::try_parse_from(["stash", "query", "--map-prefix", "/mnt/usb1=/mnt/usb2"]).unwrap();
+    assert!(matches!(args.cmd, Commands::Query { map_prefix, .. } if map_prefix.len() == 1));
+}
@@ -17,10 +17,12 @@ mod tests {
 
     // =============== Dynamic Mock Classifier ===============
 
+    type MetadataFn = Arc<dyn Fn(&Path) -> Result<ClassifiedFileMetadata> + Send + Sync>;
+
     pub struct MockClassifier {
         pub name: &'static str,
         pub confidence_score: u8,
-        pub metadata_fn: Arc<dyn Fn(&Path) -> Result<ClassifiedFileMetadata> + Send + Sync>,
+        pub metadata_fn: MetadataFn,
     }
 
     #[async_trait]
@@ -232,5 +234,64 @@ mod tests {
             assert_eq!(classified.mime_type, Some("text/plain".to_string()));
         }
     }
-}
 
+    #[tokio::test]
+    async fn test_builtins_and_plugins_share_one_registry() {
+        use stash::{registry::BuiltinClassifier, utils::create_classifier_registry};
+
+        let mut registry = create_classifier_registry();
+        registry.register_with_priority(250, Arc::new(MockClassifier {
+            name: "FailingPlugin",
+            confidence_score: 100,
+            metadata_fn: Arc::new(|_| Err(FileOrganizerError::Other("Failed to extract".into()))),
+        }));
+
+        let names: Vec<_> = registry.classifiers.iter().map(|(_, c)| c.name()).collect();
+        let builtins: Vec<_> = BuiltinClassifier::ALL.iter().map(|b| b.name()).collect();
+        assert_eq!(names[0], "FailingPlugin");
+        assert_eq!(names[1..], builtins[..]);
+
+        // The plugin and the image classifier fail (the file does not exist),
+        // so the generic fallback answers
+        let file = create_test_file("missing.png", 10);
+        let (name, _) = registry.classify_named(&file).await.unwrap();
+        assert_eq!(name, "GenericClassifier");
+    }
+
+    #[tokio::test]
+    async fn test_min_score_sends_borderline_files_to_others() {
+        let mut registry = ClassifierRegistry::new();
+        registry.register_with_priority(50, Arc::new(MockClassifier {
+            name: "Borderline",
+            confidence_score: 10,
+            metadata_fn: Arc::new(|path| Ok(create_test_metadata(path))),
+        }));
+        let file = create_test_file("notes.txt", 10);
+        assert_eq!(registry.classify_named(&file).await.unwrap().0, "Borderline");
+
+        // 50 × 10 is below the threshold, so nothing specific claims the file
+        let registry = registry.with_min_score(1000);
+        let (name, metadata) = registry.classify_named(&file).await.unwrap();
+        assert_eq!(name, "GenericClassifier");
+        assert!(matches!(metadata.category, stash::metadata::FileCategory::Others));
+        assert_eq!(metadata.file_size, Some(10));
+
+        assert_eq!(registry.with_min_score(500).classify_named(&file).await.unwrap().0, "Borderline");
+    }
+
+    #[tokio::test]
+    async fn test_equal_scores_are_broken_by_name() {
+        let mut registry = ClassifierRegistry::new();
+        for name in ["Zeta", "Alpha", "Mid"] {
+            registry.register_with_priority(50, Arc::new(MockClassifier {
+                name,
+                confidence_score: 80,
+                metadata_fn: Arc::new(|path| Ok(create_test_metadata(path))),
+            }));
+        }
+        let names: Vec<_> = registry.classifiers.iter().map(|(_, c)| c.name()).collect();
+        assert_eq!(names, ["Alpha", "Mid", "Zeta"]);
+        let file = create_test_file("tie.txt", 1);
+        assert_eq!(registry.classify_named(&file).await.unwrap().0, "Alpha");
+    }
+}
@@ -0,0 +1,57 @@
+use stash::{
+    index::Db,
+    organizer::{organise_files, OrganizeOptions},
+    repair::repair,
+    utils::create_classifier_registry,
+};
+use tempfile::tempdir;
+use tokio::fs;
+
+#[tokio::test]
+async fn test_organize_leaves_no_pending_moves() {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("report.pdf"), b"pdf bytes").await.unwrap();
+    organise_files(dir.path(), &OrganizeOptions { portable: true, ..Default::default() }).await.unwrap();
+
+    let db = Db::open_portable(dir.path()).await.unwrap();
+    assert!(db.pending_moves().await.unwrap().is_empty());
+    assert!(db.list_actions(true).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_repair_settles_interrupted_moves() {
+    let dir = tempdir().unwrap();
+    let organized = dir.path().join("Organized");
+    fs::create_dir_all(&organized).await.unwrap();
+    let db = Db::open_portable(dir.path()).await.unwrap();
+
+    // Moved, then the run died before indexing it
+    let (moved, moved_dest) = (dir.path().join("moved.pdf"), organized.join("moved.pdf"));
+    fs::write(&moved_dest, b"moved").await.unwrap();
+    // Copy to another filesystem cut short: both copies exist
+    let (copied, copied_dest) = (dir.path().join("copied.pdf"), organized.join("copied.pdf"));
+    fs::write(&copied, b"copied in full").await.unwrap();
+    fs::write(&copied_dest, b"copied").await.unwrap();
+    // Journaled, never started
+    let (waiting, waiting_dest) = (dir.path().join("waiting.pdf"), organized.join("waiting.pdf"));
+    fs::write(&waiting, b"waiting").await.unwrap();
+
+    for (path, dest) in [(&moved, &moved_dest), (&copied, &copied_dest), (&waiting, &waiting_dest)] {
+        db.journal_move(path, dest, "abc", 5).await.unwrap();
+    }
+
+    let report = repair(&db, &create_classifier_registry()).await.unwrap();
+    assert_eq!(report.completed, vec![moved.clone()]);
+    assert_eq!(report.rolled_back, vec![copied.clone()]);
+    assert_eq!(report.abandoned, 1);
+
+    let entry = db.lookup_full(&moved).await.unwrap().unwrap();
+    assert_eq!(entry.dest_path, moved_dest);
+    assert_eq!(entry.hash.as_deref(), Some("abc"));
+    assert!(copied.exists() && !copied_dest.exists());
+    assert!(waiting.exists());
+    assert!(db.lookup_full(&waiting).await.unwrap().is_none());
+    assert!(db.pending_moves().await.unwrap().is_empty());
+
+    assert!(repair(&db, &create_classifier_registry()).await.unwrap().is_empty());
+}
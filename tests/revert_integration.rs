@@ -0,0 +1,409 @@
+use tempfile::tempdir;
+use tokio::fs;
+use walkdir::WalkDir;
+
+use stash::{
+    organizer::{organise_files, BundlePolicy, OrganizeOptions},
+    exclusions::ExcludeGlobs,
+    reverter::{revert_files, RevertOptions, RevertSelection},
+};
+
+#[tokio::test]
+async fn test_organize_into_external_dest_and_revert() {
+    let src = tempdir().unwrap();
+    let dest = tempdir().unwrap();
+    let file = src.path().join("notes.pdf");
+    fs::write(&file, b"pdf bytes").await.unwrap();
+
+    let options = OrganizeOptions {
+        portable: true,
+        dest: Some(dest.path().to_path_buf()),
+        ..Default::default()
+    };
+    organise_files(src.path(), &options).await.unwrap();
+
+    assert!(!file.exists());
+    let moved: Vec<_> = WalkDir::new(dest.path().join("Documents"))
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_name() == "notes.pdf")
+        .collect();
+    assert_eq!(moved.len(), 1);
+    assert!(!src.path().join("Organized").exists());
+
+    let options = RevertOptions { cleanup: true, portable: true, ..Default::default() };
+    revert_files(dest.path(), &options).await.unwrap();
+
+    assert!(file.exists());
+    assert!(!dest.path().join("Documents").exists());
+}
+
+#[tokio::test]
+async fn test_organize_dry_run_returns_summary() {
+    use stash::errors::SkipReason;
+
+    let src = tempdir().unwrap();
+    fs::write(src.path().join("report.pdf"), b"pdf bytes").await.unwrap();
+    fs::write(src.path().join(".hidden"), b"secret").await.unwrap();
+    fs::create_dir(src.path().join("nested")).await.unwrap();
+
+    let options = OrganizeOptions { dry_run: true, ..Default::default() };
+    let summary = organise_files(src.path(), &options).await.unwrap();
+
+    assert_eq!(summary.moved, 1);
+    assert_eq!(summary.errors, 0);
+    assert_eq!(summary.skip_counts[SkipReason::Hidden.as_index()], 1);
+    assert!(src.path().join("report.pdf").exists());
+}
+
+#[tokio::test]
+async fn test_revert_with_prefix_mapping_after_remount() {
+    use stash::utils::PathMapping;
+
+    let parent = tempdir().unwrap();
+    let old_src = parent.path().join("usb1");
+    let dest = parent.path().join("library");
+    fs::create_dir(&old_src).await.unwrap();
+    fs::write(old_src.join("notes.pdf"), b"pdf bytes").await.unwrap();
+
+    let options = OrganizeOptions { portable: true, dest: Some(dest.clone()), ..Default::default() };
+    organise_files(&old_src, &options).await.unwrap();
+
+    // The source drive comes back under a different mount point
+    let new_src = parent.path().join("usb2");
+    fs::rename(&old_src, &new_src).await.unwrap();
+
+    let options = RevertOptions {
+        portable: true,
+        mappings: vec![PathMapping { from: old_src.clone(), to: new_src.clone() }],
+        ..Default::default()
+    };
+    revert_files(&dest, &options).await.unwrap();
+
+    assert!(new_src.join("notes.pdf").exists());
+    assert!(!old_src.exists());
+}
+
+#[tokio::test]
+async fn test_revert_by_original_root_reports_out_of_scope() {
+    use stash::reverter::{revert, RevertScope};
+
+    let parent = tempdir().unwrap();
+    let (src_a, src_b, dest) = (parent.path().join("a"), parent.path().join("b"), parent.path().join("library"));
+    for src in [&src_a, &src_b] {
+        fs::create_dir(src).await.unwrap();
+    }
+    fs::write(src_a.join("from-a.pdf"), b"a bytes").await.unwrap();
+    fs::write(src_b.join("from-b.pdf"), b"b bytes").await.unwrap();
+
+    let options = OrganizeOptions { portable: true, dest: Some(dest.clone()), ..Default::default() };
+    organise_files(&src_a, &options).await.unwrap();
+    organise_files(&src_b, &options).await.unwrap();
+
+    let options = RevertOptions {
+        portable: true,
+        scope: RevertScope::OriginalRoot(src_a.clone()),
+        ..Default::default()
+    };
+    let report = revert(Some(&dest), &options).await.unwrap();
+    assert_eq!((report.candidates, report.moved), (1, 1));
+    assert_eq!(report.out_of_scope.len(), 1);
+    assert!(report.out_of_scope[0].ends_with("from-b.pdf"));
+    assert!(src_a.join("from-a.pdf").exists());
+    assert!(!src_b.join("from-b.pdf").exists());
+
+    // Already-reverted records are neither candidates nor out of scope
+    let options = RevertOptions { portable: true, scope: RevertScope::All, ..Default::default() };
+    let report = revert(Some(&dest), &options).await.unwrap();
+    assert_eq!(report.moved, 1);
+    assert!(report.out_of_scope.is_empty());
+    assert!(src_b.join("from-b.pdf").exists());
+}
+
+#[tokio::test]
+async fn test_portable_revert_without_root_is_rejected() {
+    use stash::reverter::{revert, RevertScope};
+
+    let options = RevertOptions { portable: true, scope: RevertScope::All, ..Default::default() };
+    assert!(revert(None, &options).await.is_err());
+}
+
+#[tokio::test]
+async fn test_organize_multiple_roots_records_run_per_root() {
+    use stash::{index::Db, organizer::organise_roots, reverter::{revert, RevertScope}};
+
+    let parent = tempdir().unwrap();
+    let (src_a, src_b, dest) = (parent.path().join("a"), parent.path().join("b"), parent.path().join("library"));
+    for src in [&src_a, &src_b] {
+        fs::create_dir(src).await.unwrap();
+    }
+    fs::write(src_a.join("from-a.pdf"), b"a bytes").await.unwrap();
+    fs::write(src_a.join("also-a.txt"), b"more a bytes").await.unwrap();
+    fs::write(src_b.join("from-b.pdf"), b"b bytes").await.unwrap();
+
+    let options = OrganizeOptions { portable: true, dest: Some(dest.clone()), ..Default::default() };
+    let summary = organise_roots(&[src_a.clone(), src_b.clone()], &options).await.unwrap();
+    assert_eq!(summary.moved, 3);
+
+    let db = Db::open_portable(&dest).await.unwrap();
+    let mut roots: Vec<_> = db.list_runs().await.unwrap().into_iter().map(|r| (r.root, r.file_count)).collect();
+    roots.sort();
+    assert_eq!(roots, vec![(src_a.clone(), 2), (src_b.clone(), 1)]);
+
+    let options = RevertOptions {
+        portable: true,
+        scope: RevertScope::OriginalRoot(src_b.clone()),
+        ..Default::default()
+    };
+    let report = revert(Some(&dest), &options).await.unwrap();
+    assert_eq!(report.moved, 1);
+    assert!(src_b.join("from-b.pdf").exists());
+    assert!(!src_a.join("from-a.pdf").exists());
+}
+
+#[tokio::test]
+async fn test_revert_spanning_several_batches() {
+    use stash::reverter::REVERT_BATCH;
+
+    let dir = tempdir().unwrap();
+    let count = REVERT_BATCH * 2 + 7;
+    for i in 0..count {
+        fs::write(dir.path().join(format!("note-{}.txt", i)), format!("note {}", i)).await.unwrap();
+    }
+
+    let options = OrganizeOptions { portable: true, ..Default::default() };
+    organise_files(dir.path(), &options).await.unwrap();
+    assert!(!dir.path().join("note-0.txt").exists());
+
+    let options = RevertOptions { portable: true, ..Default::default() };
+    let report = revert_files(dir.path(), &options).await.unwrap();
+    assert_eq!(report.moved, count);
+    for i in 0..count {
+        assert!(dir.path().join(format!("note-{}.txt", i)).exists());
+    }
+
+    // The index now points at the original paths and no journal entries are left
+    let db = stash::index::Db::open_portable(dir.path()).await.unwrap();
+    assert!(db.pending_reverts().await.unwrap().is_empty());
+    let report = revert_files(dir.path(), &options).await.unwrap();
+    assert_eq!(report.moved, 0);
+}
+
+#[tokio::test]
+async fn test_revert_journal_recovers_interrupted_moves() {
+    use stash::{index::Db, reverter::recover_journal};
+
+    let dir = tempdir().unwrap();
+    let (moved, untouched) = (dir.path().join("moved.pdf"), dir.path().join("untouched.pdf"));
+    fs::write(&moved, b"moved bytes").await.unwrap();
+    fs::write(&untouched, b"untouched bytes").await.unwrap();
+    organise_files(dir.path(), &OrganizeOptions { portable: true, ..Default::default() }).await.unwrap();
+
+    // Journal both moves, but "crash" after only the first one happened
+    let db = Db::open_portable(dir.path()).await.unwrap();
+    let organized = db.lookup_full(&moved).await.unwrap().unwrap().dest_path;
+    db.journal_reverts(&[(&moved, &moved, 11), (&untouched, &untouched, 15)]).await.unwrap();
+    fs::rename(&organized, &moved).await.unwrap();
+
+    assert_eq!(recover_journal(&db).await.unwrap(), 1);
+    assert_eq!(db.lookup_full(&moved).await.unwrap().unwrap().dest_path, moved);
+    assert_ne!(db.lookup_full(&untouched).await.unwrap().unwrap().dest_path, untouched);
+    assert!(db.pending_reverts().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_revert_leaves_changed_files_unless_told_to_restore() {
+    use stash::{errors::FileOrganizerError, index::Db, reverter::MismatchPolicy};
+
+    let dir = tempdir().unwrap();
+    let (edited, intact) = (dir.path().join("edited.pdf"), dir.path().join("intact.pdf"));
+    fs::write(&edited, b"original bytes").await.unwrap();
+    fs::write(&intact, b"intact bytes").await.unwrap();
+    organise_files(dir.path(), &OrganizeOptions { portable: true, ..Default::default() }).await.unwrap();
+
+    let db = Db::open_portable(dir.path()).await.unwrap();
+    let organized = db.lookup_full(&edited).await.unwrap().unwrap().dest_path;
+    fs::write(&organized, b"edited after organizing").await.unwrap();
+
+    let options = RevertOptions { portable: true, ..Default::default() };
+    let report = revert_files(dir.path(), &options).await.unwrap();
+    assert_eq!(report.moved, 1);
+    assert!(intact.exists());
+    assert!(organized.exists() && !edited.exists());
+    assert!(matches!(&report.changed[..], [FileOrganizerError::HashMismatch { path, .. }] if *path == organized));
+
+    let options = RevertOptions { portable: true, on_mismatch: MismatchPolicy::Restore, ..Default::default() };
+    let report = revert_files(dir.path(), &options).await.unwrap();
+    assert_eq!((report.moved, report.changed_restored), (1, 1));
+    assert_eq!(fs::read(&edited).await.unwrap(), b"edited after organizing");
+}
+
+#[tokio::test]
+async fn test_revert_selected_files_only() {
+    let src = tempdir().unwrap();
+    let root = src.path();
+    for name in ["a.pdf", "b.pdf", "notes.txt"] {
+        fs::write(root.join(name), name).await.unwrap();
+    }
+    organise_files(root, &OrganizeOptions { portable: true, ..Default::default() }).await.unwrap();
+
+    let by_glob = RevertOptions {
+        portable: true,
+        select: RevertSelection { globs: ExcludeGlobs::new(&["*.txt"]).unwrap(), ..Default::default() },
+        ..Default::default()
+    };
+    let report = revert_files(root, &by_glob).await.unwrap();
+    assert_eq!(report.moved, 1);
+    assert!(root.join("notes.txt").exists());
+    assert!(!root.join("a.pdf").exists());
+
+    // Looked up by the path it came from
+    let by_file = RevertOptions {
+        portable: true,
+        select: RevertSelection { files: vec![root.join("a.pdf")], ..Default::default() },
+        ..Default::default()
+    };
+    let report = revert_files(root, &by_file).await.unwrap();
+    assert_eq!(report.moved, 1);
+    assert!(report.out_of_scope.is_empty());
+    assert!(root.join("a.pdf").exists());
+    assert!(!root.join("b.pdf").exists());
+}
+
+#[tokio::test]
+async fn test_revert_project_moved_whole() {
+    let src = tempdir().unwrap();
+    let dest = tempdir().unwrap();
+    let project = src.path().join("blog");
+    fs::create_dir_all(project.join(".git")).await.unwrap();
+    fs::write(project.join("post.md"), b"# hello").await.unwrap();
+
+    let options = OrganizeOptions {
+        portable: true,
+        dest: Some(dest.path().to_path_buf()),
+        bundles: BundlePolicy::Move,
+        ..Default::default()
+    };
+    assert_eq!(organise_files(src.path(), &options).await.unwrap().moved, 1);
+    assert!(dest.path().join("Code/Projects/blog/post.md").exists());
+
+    let options = RevertOptions { cleanup: true, portable: true, ..Default::default() };
+    let report = revert_files(dest.path(), &options).await.unwrap();
+    assert_eq!(report.moved, 1);
+    assert_eq!(fs::read(project.join("post.md")).await.unwrap(), b"# hello");
+    assert!(project.join(".git").is_dir());
+    assert!(!dest.path().join("Code").exists());
+}
+
+#[tokio::test]
+async fn test_revert_conflicts_follow_the_strategy() {
+    use stash::{index::Db, reverter::ConflictPolicy};
+
+    let dir = tempdir().unwrap();
+    let names = ["renamed.pdf", "skipped.pdf", "replaced.pdf"];
+    for name in names {
+        fs::write(dir.path().join(name), b"organized bytes").await.unwrap();
+    }
+    organise_files(dir.path(), &OrganizeOptions { portable: true, ..Default::default() }).await.unwrap();
+    // Different files take the original names in the meantime
+    for name in names {
+        fs::write(dir.path().join(name), b"newer bytes").await.unwrap();
+    }
+    let db = Db::open_portable(dir.path()).await.unwrap();
+    let skipped_dest = db.lookup_full(&dir.path().join("skipped.pdf")).await.unwrap().unwrap().dest_path;
+    let select = |name: &str| RevertSelection { files: vec![dir.path().join(name)], ..Default::default() };
+
+    let options = RevertOptions { portable: true, select: select("renamed.pdf"), ..Default::default() };
+    let report = revert_files(dir.path(), &options).await.unwrap();
+    let restored_as = dir.path().join("renamed_1.pdf");
+    assert_eq!(report.renamed, vec![(dir.path().join("renamed.pdf"), restored_as.clone())]);
+    assert_eq!(fs::read(dir.path().join("renamed.pdf")).await.unwrap(), b"newer bytes");
+    assert_eq!(fs::read(&restored_as).await.unwrap(), b"organized bytes");
+    // The restored name is recorded, so reverting again leaves it alone
+    assert!(db.revert_targets().await.unwrap().contains(&restored_as));
+    let options = RevertOptions { portable: true, select: select("renamed.pdf"), ..Default::default() };
+    assert_eq!(revert_files(dir.path(), &options).await.unwrap().moved, 0);
+    assert!(restored_as.exists() && !dir.path().join("renamed_2.pdf").exists());
+
+    let options = RevertOptions {
+        portable: true,
+        select: select("skipped.pdf"),
+        on_conflict: ConflictPolicy::Skip,
+        ..Default::default()
+    };
+    let report = revert_files(dir.path(), &options).await.unwrap();
+    assert_eq!((report.moved, report.conflicts_skipped), (0, 1));
+    assert!(skipped_dest.exists());
+
+    let options = RevertOptions {
+        portable: true,
+        select: select("replaced.pdf"),
+        on_conflict: ConflictPolicy::Overwrite,
+        ..Default::default()
+    };
+    let report = revert_files(dir.path(), &options).await.unwrap();
+    assert_eq!((report.moved, report.overwritten), (1, 1));
+    assert_eq!(fs::read(dir.path().join("replaced.pdf")).await.unwrap(), b"organized bytes");
+}
+
+#[tokio::test]
+async fn test_revert_renames_apart_files_restored_to_the_same_name() {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("a.pdf"), b"first").await.unwrap();
+    fs::write(dir.path().join("a_1.pdf"), b"second").await.unwrap();
+    organise_files(dir.path(), &OrganizeOptions { portable: true, ..Default::default() }).await.unwrap();
+    // `a.pdf` is taken again, so it comes back as `a_1.pdf`, the name the
+    // other file comes back to in the same batch
+    fs::write(dir.path().join("a.pdf"), b"newer").await.unwrap();
+
+    let options = RevertOptions { portable: true, ..Default::default() };
+    let report = revert_files(dir.path(), &options).await.unwrap();
+    assert_eq!(report.moved, 2);
+
+    let mut contents = Vec::new();
+    for entry in std::fs::read_dir(dir.path()).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_some_and(|ext| ext == "pdf") {
+            contents.push(fs::read(&path).await.unwrap());
+        }
+    }
+    contents.sort();
+    assert_eq!(contents, [b"first".to_vec(), b"newer".to_vec(), b"second".to_vec()]);
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_revert_keeps_the_file_to_overwrite_when_an_earlier_move_fails() {
+    use std::time::Duration;
+    use stash::reverter::ConflictPolicy;
+
+    let dest = tempdir().unwrap();
+    let (kept, blocked) = (tempdir().unwrap(), tempdir().unwrap());
+    let replaced = kept.path().join("replaced.pdf");
+    fs::write(&replaced, b"organized bytes").await.unwrap();
+    fs::write(blocked.path().join("blocked.pdf"), b"blocked bytes").await.unwrap();
+
+    // Organized last, so reverted first
+    let options = OrganizeOptions {
+        portable: true,
+        dest: Some(dest.path().to_path_buf()),
+        ..Default::default()
+    };
+    organise_files(kept.path(), &options).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+    organise_files(blocked.path(), &options).await.unwrap();
+
+    // The first move fails: its original folder is now a dangling link
+    fs::remove_dir_all(blocked.path()).await.unwrap();
+    std::os::unix::fs::symlink(dest.path().join("gone"), blocked.path()).unwrap();
+    fs::write(&replaced, b"newer bytes").await.unwrap();
+
+    let options = RevertOptions {
+        portable: true,
+        cleanup: false,
+        on_conflict: ConflictPolicy::Overwrite,
+        ..Default::default()
+    };
+    assert!(revert_files(dest.path(), &options).await.is_err());
+    assert_eq!(fs::read(&replaced).await.unwrap(), b"newer bytes");
+}
@@ -58,9 +58,8 @@ async fn test_should_skip_file_identical() {
     fs::write(&original, b"same").await.unwrap();
 
     let hasher = create_hasher(HashAlgo::Blake3);
-    let pb = indicatif::ProgressBar::hidden();
 
-    let result = should_skip_file(&source, &original, hasher, &pb).await.unwrap();
+    let result = should_skip_file(&source, &original, hasher).await.unwrap();
     assert!(result);
 }
 
@@ -74,9 +73,8 @@ async fn test_should_skip_file_different() {
     fs::write(&original, b"bar").await.unwrap();
 
     let hasher = create_hasher(HashAlgo::Blake3);
-    let pb = indicatif::ProgressBar::hidden();
 
-    let result = should_skip_file(&source, &original, hasher, &pb).await.unwrap();
+    let result = should_skip_file(&source, &original, hasher).await.unwrap();
     assert!(!result);
 }
 
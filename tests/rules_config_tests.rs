@@ -0,0 +1,39 @@
+use stash::config::{RuleOverlap, RulesConfig};
+use tempfile::tempdir;
+
+fn load(json: &str) -> RulesConfig {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("rules.json");
+    std::fs::write(&path, json).unwrap();
+    RulesConfig::load_from_file(&path).unwrap()
+}
+
+#[test]
+fn test_overlapping_rules_pick_highest_priority() {
+    let config = load(
+        r#"{ "rules": [
+            { "category": "Images", "extensions": ["png", "svg"], "destination": "Pictures" },
+            { "category": "Design", "extensions": [".SVG"], "destination": "Design", "priority": 5 },
+            { "category": "Icons", "extensions": ["svg"], "destination": "Icons", "priority": 5 }
+        ] }"#,
+    );
+
+    assert_eq!(config.overlaps, vec![RuleOverlap { extension: "svg".into(), rules: vec![1, 2, 0] }]);
+    assert_eq!(config.rule_for("logo.svg").unwrap().category, "Design");
+    assert_eq!(config.rule_for("photo.PNG").unwrap().category, "Images");
+    assert!(config.rule_for("notes.txt").is_none());
+}
+
+#[test]
+fn test_regex_rules_follow_priority() {
+    let config = load(
+        r#"{ "rules": [
+            { "category": "Documents", "extensions": ["pdf"], "destination": "Docs" },
+            { "category": "Invoices", "regex": "^invoice", "destination": "Invoices", "priority": 1 }
+        ] }"#,
+    );
+
+    assert!(config.overlaps.is_empty());
+    assert_eq!(config.rule_for("Invoice-2024.pdf").unwrap().category, "Invoices");
+    assert_eq!(config.rule_for("report.pdf").unwrap().category, "Documents");
+}
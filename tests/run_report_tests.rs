@@ -0,0 +1,93 @@
+use stash::{
+    exclusions::ExcludeGlobs,
+    organizer::{organise_files, OrganizeOptions},
+    run_report::RunReportFormat,
+};
+use tempfile::tempdir;
+
+fn report_files(dir: &std::path::Path) -> Vec<std::path::PathBuf> {
+    let mut files: Vec<_> = std::fs::read_dir(dir).unwrap().map(|e| e.unwrap().path()).collect();
+    files.sort();
+    files
+}
+
+#[tokio::test]
+async fn test_json_report_lists_every_file() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    std::fs::write(root.join("report.pdf"), b"%PDF-1.4 report").unwrap();
+    std::fs::write(root.join("movie.mkv.part"), b"partial").unwrap();
+    let reports = root.join("reports");
+
+    let options = OrganizeOptions {
+        portable: true,
+        exclude: ExcludeGlobs::new(&["*.part"]).unwrap(),
+        report_dir: Some(reports.clone()),
+        ..Default::default()
+    };
+    let summary = organise_files(root, &options).await.unwrap();
+
+    let files = report_files(&reports);
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0].extension().unwrap(), "json");
+    let report: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&files[0]).unwrap()).unwrap();
+    assert_eq!(report["summary"]["moved"], summary.moved);
+    assert_eq!(report["dry_run"], false);
+
+    let entries = report["files"].as_array().unwrap();
+    assert_eq!(entries.len(), summary.processed);
+    let moved = entries.iter().find(|e| e["outcome"] == "moved").unwrap();
+    assert!(moved["src"].as_str().unwrap().ends_with("report.pdf"));
+    assert_eq!(moved["category"], "Documents");
+    assert!(entries.iter().any(|e| e["outcome"] == "skipped" && e["src"].as_str().unwrap().ends_with("movie.mkv.part")));
+
+    // The report directory is not organized, and a second report gets its own file
+    organise_files(root, &options).await.unwrap();
+    let files = report_files(&reports);
+    assert_eq!(files.len(), 2);
+}
+
+#[tokio::test]
+async fn test_quiet_run_returns_the_report_path() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    std::fs::write(root.join("report.pdf"), b"%PDF-1.4 report").unwrap();
+    let reports = root.join("reports");
+
+    let options = OrganizeOptions { portable: true, quiet: true, report_dir: Some(reports.clone()), ..Default::default() };
+    let summary = organise_files(root, &options).await.unwrap();
+
+    assert_eq!(summary.report_path, Some(report_files(&reports)[0].clone()));
+    assert!(summary.failures.is_empty());
+}
+
+#[tokio::test]
+async fn test_html_report_escapes_names() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    std::fs::write(root.join("<b> & notes.txt"), b"some notes").unwrap();
+    let reports = dir.path().join("reports");
+
+    let options = OrganizeOptions {
+        portable: true,
+        dry_run: true,
+        report_dir: Some(reports.clone()),
+        report_format: RunReportFormat::Html,
+        ..Default::default()
+    };
+    organise_files(root, &options).await.unwrap();
+
+    let files = report_files(&reports);
+    assert_eq!(files[0].extension().unwrap(), "html");
+    let html = std::fs::read_to_string(&files[0]).unwrap();
+    assert!(html.contains("(dry run)"));
+    assert!(html.contains("&lt;b&gt; &amp; notes.txt"));
+    assert!(!html.contains("<b> &"));
+}
+
+#[test]
+fn test_report_format_parses() {
+    assert_eq!("HTML".parse::<RunReportFormat>().unwrap(), RunReportFormat::Html);
+    assert_eq!("json".parse::<RunReportFormat>().unwrap(), RunReportFormat::Json);
+    assert!("csv".parse::<RunReportFormat>().is_err());
+}
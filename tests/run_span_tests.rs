@@ -0,0 +1,61 @@
+use std::{
+    io,
+    sync::{Arc, Mutex},
+};
+
+use stash::organizer::{organise_files, OrganizeOptions};
+use tempfile::tempdir;
+
+/// Log lines written by the subscriber, shared with the test
+#[derive(Clone, Default)]
+struct Captured(Arc<Mutex<Vec<u8>>>);
+
+impl io::Write for Captured {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// Current-thread runtime, so the spawned per-file tasks log to the same
+// thread-local subscriber
+#[tokio::test]
+async fn test_file_lines_carry_run_and_file_spans() {
+    let captured = Captured::default();
+    let writer = captured.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .with_writer(move || writer.clone())
+        .with_ansi(false)
+        .with_max_level(tracing::Level::DEBUG)
+        .finish();
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let dir = tempdir().unwrap();
+    std::fs::write(dir.path().join("report.pdf"), b"pdf bytes").unwrap();
+    std::fs::write(dir.path().join("notes.txt"), b"some notes").unwrap();
+
+    let options = OrganizeOptions { portable: true, ..Default::default() };
+    let first = organise_files(dir.path(), &options).await.unwrap();
+    std::fs::write(dir.path().join("song.mp3"), b"ID3").unwrap();
+    let second = organise_files(dir.path(), &options).await.unwrap();
+
+    assert_eq!(first.run_id.len(), 8);
+    assert_ne!(first.run_id, second.run_id);
+
+    let logs = String::from_utf8(captured.0.lock().unwrap().clone()).unwrap();
+    let processed: Vec<&str> = logs.lines().filter(|line| line.contains("Processed")).collect();
+    assert_eq!(processed.len(), 3, "{}", logs);
+
+    let report = processed.iter().find(|line| line.contains("report.pdf")).unwrap();
+    assert!(report.contains(&format!("run{{id={}}}:file{{path=", first.run_id)), "{}", report);
+    assert!(report.contains("category=Documents::Pdf"), "{}", report);
+    assert!(report.contains("classify_ms=") && report.contains("move_ms="), "{}", report);
+    assert!(report.contains("outcome=\"moved\""), "{}", report);
+
+    let song = processed.iter().find(|line| line.contains("song.mp3")).unwrap();
+    assert!(song.contains(&format!("run{{id={}}}", second.run_id)), "{}", song);
+}
@@ -0,0 +1,43 @@
+use stash::{
+    organizer::{organise_files, OrganizeOptions},
+    run_status::{RunResult, RunStatus},
+};
+use tempfile::tempdir;
+
+#[tokio::test]
+async fn test_status_file_written_after_run() {
+    let dir = tempdir().unwrap();
+    let state = tempdir().unwrap();
+    std::fs::write(dir.path().join("report.pdf"), b"pdf bytes").unwrap();
+    let status_file = state.path().join("stash").join("last_run.json");
+
+    let options = OrganizeOptions { portable: true, status_file: Some(status_file.clone()), ..Default::default() };
+    let summary = organise_files(dir.path(), &options).await.unwrap();
+
+    let status = RunStatus::read(&status_file).unwrap();
+    assert_eq!(status.result, RunResult::Ok);
+    assert_eq!(status.roots, vec![dir.path().to_path_buf()]);
+    assert_eq!((status.moved, status.errors), (1, 0));
+    assert_eq!(status.skipped, summary.skip_counts.iter().sum::<usize>());
+    assert!(status.timestamp > 0);
+    assert!(status.error.is_none());
+    assert!(!state.path().join("stash").join("last_run.json.tmp").exists());
+}
+
+#[tokio::test]
+async fn test_status_file_records_failed_run() {
+    let dir = tempdir().unwrap();
+    let status_file = dir.path().join("last_run.json");
+    let missing = dir.path().join("missing");
+
+    let options = OrganizeOptions { dry_run: true, status_file: Some(status_file.clone()), ..Default::default() };
+    assert!(organise_files(&missing, &options).await.is_err());
+
+    let status = RunStatus::read(&status_file).unwrap();
+    assert_eq!(status.result, RunResult::Failed);
+    assert!(status.dry_run);
+    assert!(status.error.is_some());
+
+    let json: serde_json::Value = serde_json::from_slice(&std::fs::read(&status_file).unwrap()).unwrap();
+    assert_eq!(json["result"], "failed");
+}
@@ -0,0 +1,263 @@
+use std::{
+    fs::File,
+    time::{Duration, SystemTime},
+};
+
+use stash::{
+    errors::{FileOrganizerError, SkipReason},
+    scanner::{BundleKind, ScanConfig, Scanner, SymlinkPolicy},
+    utils::{parse_duration, parse_size, parse_throughput},
+};
+use tempfile::tempdir;
+
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Run a scan and return (file names kept, skip reasons) in name order
+fn scan(root: &std::path::Path, config: ScanConfig) -> (Vec<String>, Vec<(String, SkipReason)>) {
+    let mut kept = Vec::new();
+    let mut skipped = Vec::new();
+    for res in Scanner::new(root, ScanConfig { min_depth: 1, ..config }) {
+        match res {
+            Ok(raw) => kept.push(raw.path.file_name().unwrap().to_string_lossy().to_string()),
+            Err(FileOrganizerError::Skipped { path, reason, .. }) => {
+                skipped.push((path.file_name().unwrap().to_string_lossy().to_string(), reason))
+            }
+            Err(e) => panic!("unexpected error: {e}"),
+        }
+    }
+    kept.sort();
+    skipped.sort_by(|a, b| a.0.cmp(&b.0));
+    (kept, skipped)
+}
+
+fn write_aged(root: &std::path::Path, name: &str, len: usize, age: Duration) {
+    let path = root.join(name);
+    std::fs::write(&path, vec![0u8; len]).unwrap();
+    File::options()
+        .write(true)
+        .open(&path)
+        .unwrap()
+        .set_modified(SystemTime::now() - age)
+        .unwrap();
+}
+
+#[test]
+fn test_size_filters() {
+    let dir = tempdir().unwrap();
+    write_aged(dir.path(), "small.bin", 10, Duration::ZERO);
+    write_aged(dir.path(), "medium.bin", 2048, Duration::ZERO);
+    write_aged(dir.path(), "large.bin", 10_000, Duration::ZERO);
+
+    let config = ScanConfig { min_size: Some(1024), max_size: Some(4096), ..Default::default() };
+    let (kept, skipped) = scan(dir.path(), config);
+
+    assert_eq!(kept, vec!["medium.bin"]);
+    assert_eq!(skipped, vec![
+        ("large.bin".to_string(), SkipReason::TooLarge),
+        ("small.bin".to_string(), SkipReason::TooSmall),
+    ]);
+}
+
+#[test]
+fn test_skips_downloads_in_progress() {
+    let dir = tempdir().unwrap();
+    for name in ["movie.mkv.part", "setup.exe.crdownload", "done.zip", "torrent.iso.!qB"] {
+        write_aged(dir.path(), name, 10, Duration::ZERO);
+    }
+    // Safari keeps the partial file inside a `.download` bundle
+    std::fs::create_dir(dir.path().join("archive.zip.download")).unwrap();
+    write_aged(&dir.path().join("archive.zip.download"), "archive.zip", 10, Duration::ZERO);
+
+    let (kept, skipped) = scan(dir.path(), ScanConfig::default());
+    assert_eq!(kept, vec!["done.zip"]);
+    assert_eq!(skipped, vec![
+        ("archive.zip.download".to_string(), SkipReason::Incomplete),
+        ("movie.mkv.part".to_string(), SkipReason::Incomplete),
+        ("setup.exe.crdownload".to_string(), SkipReason::Incomplete),
+        ("torrent.iso.!qB".to_string(), SkipReason::Incomplete),
+    ]);
+}
+
+#[test]
+fn test_age_filters() {
+    let dir = tempdir().unwrap();
+    write_aged(dir.path(), "fresh.txt", 1, Duration::ZERO);
+    write_aged(dir.path(), "week.txt", 1, 8 * DAY);
+    write_aged(dir.path(), "ancient.txt", 1, 400 * DAY);
+
+    let config = ScanConfig { older_than: Some(7 * DAY), ..Default::default() };
+    let (kept, skipped) = scan(dir.path(), config);
+    assert_eq!(kept, vec!["ancient.txt", "week.txt"]);
+    assert_eq!(skipped, vec![("fresh.txt".to_string(), SkipReason::TooNew)]);
+
+    let config = ScanConfig { older_than: Some(7 * DAY), newer_than: Some(30 * DAY), ..Default::default() };
+    let (kept, skipped) = scan(dir.path(), config);
+    assert_eq!(kept, vec!["week.txt"]);
+    assert_eq!(skipped, vec![
+        ("ancient.txt".to_string(), SkipReason::TooOld),
+        ("fresh.txt".to_string(), SkipReason::TooNew),
+    ]);
+}
+
+#[test]
+fn test_parse_size() {
+    assert_eq!(parse_size("500").unwrap(), 500);
+    assert_eq!(parse_size("10KB").unwrap(), 10 * 1024);
+    assert_eq!(parse_size("10mb").unwrap(), 10 * 1024 * 1024);
+    assert_eq!(parse_size("1.5 GB").unwrap(), 3 * 512 * 1024 * 1024);
+    assert!(parse_size("10 parsecs").is_err());
+    assert!(parse_size("MB").is_err());
+}
+
+#[test]
+fn test_parse_throughput() {
+    assert_eq!(parse_throughput("20").unwrap(), 20 * 1024 * 1024);
+    assert_eq!(parse_throughput("2.5").unwrap(), 5 * 512 * 1024);
+    assert_eq!(parse_throughput("500KB/s").unwrap(), 500 * 1024);
+    assert_eq!(parse_throughput("1GB").unwrap(), 1024 * 1024 * 1024);
+    assert!(parse_throughput("0").is_err());
+    assert!(parse_throughput("fast").is_err());
+}
+
+#[test]
+fn test_parse_duration() {
+    assert_eq!(parse_duration("90s").unwrap(), Duration::from_secs(90));
+    assert_eq!(parse_duration("12h").unwrap(), Duration::from_secs(12 * 3600));
+    assert_eq!(parse_duration("7d").unwrap(), 7 * DAY);
+    assert_eq!(parse_duration("2w").unwrap(), 14 * DAY);
+    assert!(parse_duration("7").is_err());
+    assert!(parse_duration("d").is_err());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_owner_filter() {
+    use stash::utils::current_uid;
+
+    let dir = tempdir().unwrap();
+    std::fs::write(dir.path().join("mine.txt"), b"x").unwrap();
+    let uid = current_uid().unwrap();
+
+    let (kept, skipped) = scan(dir.path(), ScanConfig { owner: Some(uid), ..Default::default() });
+    assert_eq!(kept, vec!["mine.txt"]);
+    assert!(skipped.is_empty());
+
+    let (kept, skipped) = scan(dir.path(), ScanConfig { owner: Some(uid.wrapping_add(1)), ..Default::default() });
+    assert!(kept.is_empty());
+    assert_eq!(skipped, vec![("mine.txt".to_string(), SkipReason::NotOwned)]);
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_organize_by_owner_nests_under_owner_name() {
+    use stash::{
+        organizer::{organise_files, OrganizeOptions},
+        utils::{current_uid, user_name},
+    };
+
+    let dir = tempdir().unwrap();
+    std::fs::write(dir.path().join("report.pdf"), b"pdf bytes").unwrap();
+    let owner = current_uid().map(|uid| user_name(uid).unwrap_or_else(|| uid.to_string())).unwrap();
+
+    let options = OrganizeOptions { portable: true, by_owner: true, only_mine: true, ..Default::default() };
+    let summary = organise_files(dir.path(), &options).await.unwrap();
+    assert_eq!(summary.moved, 1);
+    assert!(dir.path().join("Organized").join(&owner).join("Documents").is_dir());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_symlink_policy() {
+    let outside = tempdir().unwrap();
+    std::fs::write(outside.path().join("target.txt"), "hello").unwrap();
+    let dir = tempdir().unwrap();
+    std::fs::write(dir.path().join("plain.txt"), "x").unwrap();
+    std::os::unix::fs::symlink(outside.path().join("target.txt"), dir.path().join("link.txt")).unwrap();
+    std::os::unix::fs::symlink(outside.path().join("gone.txt"), dir.path().join("broken.txt")).unwrap();
+
+    let (kept, skipped) = scan(dir.path(), ScanConfig::default());
+    assert_eq!(kept, vec!["plain.txt"]);
+    assert_eq!(skipped, vec![
+        ("broken.txt".to_string(), SkipReason::Symlink),
+        ("link.txt".to_string(), SkipReason::Symlink),
+    ]);
+
+    let config = ScanConfig { symlinks: SymlinkPolicy::MoveLink, ..Default::default() };
+    let (kept, skipped) = scan(dir.path(), config.clone());
+    assert_eq!(kept, vec!["link.txt", "plain.txt"]);
+    assert_eq!(skipped, vec![("broken.txt".to_string(), SkipReason::MetadataUnreadable)]);
+
+    let link = Scanner::new(dir.path().join("link.txt"), config).next().unwrap().unwrap();
+    assert!(link.is_symlink && link.is_file);
+    assert_eq!(link.size, 5);
+
+    assert!("move-link".parse::<SymlinkPolicy>().is_ok());
+    assert!("copy".parse::<SymlinkPolicy>().is_err());
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_organize_symlinks() {
+    use stash::organizer::{organise_files, OrganizeOptions};
+
+    let organized = |root: &std::path::Path| {
+        walkdir::WalkDir::new(root.join("Organized"))
+            .into_iter()
+            .map(|e| e.unwrap().into_path())
+            .find(|p| p.ends_with("notes.txt"))
+            .unwrap()
+    };
+
+    let outside = tempdir().unwrap();
+    let target = outside.path().join("notes.txt");
+    std::fs::write(&target, "notes").unwrap();
+
+    // Moved as a link, still pointing at the original file
+    let dir = tempdir().unwrap();
+    std::os::unix::fs::symlink(&target, dir.path().join("notes.txt")).unwrap();
+    let options = OrganizeOptions { portable: true, symlinks: SymlinkPolicy::MoveLink, ..Default::default() };
+    assert_eq!(organise_files(dir.path(), &options).await.unwrap().moved, 1);
+    let moved = organized(dir.path());
+    assert_eq!(std::fs::read_link(&moved).unwrap(), target);
+
+    // Replaced by a copy of what it pointed to
+    let dir = tempdir().unwrap();
+    std::os::unix::fs::symlink(&target, dir.path().join("notes.txt")).unwrap();
+    let options = OrganizeOptions { portable: true, symlinks: SymlinkPolicy::Follow, ..Default::default() };
+    assert_eq!(organise_files(dir.path(), &options).await.unwrap().moved, 1);
+    let copied = organized(dir.path());
+    assert!(!std::fs::symlink_metadata(&copied).unwrap().is_symlink());
+    assert_eq!(std::fs::read_to_string(&copied).unwrap(), "notes");
+    assert!(target.exists());
+}
+
+#[test]
+fn test_bundles_are_yielded_whole() {
+    let dir = tempdir().unwrap();
+    std::fs::create_dir_all(dir.path().join("app/node_modules/left-pad")).unwrap();
+    std::fs::write(dir.path().join("app/package.json"), "{}").unwrap();
+    std::fs::write(dir.path().join("app/node_modules/left-pad/index.js"), "module.exports = 1;").unwrap();
+    std::fs::create_dir_all(dir.path().join("Editor.APP/Contents")).unwrap();
+    std::fs::create_dir_all(dir.path().join("plain")).unwrap();
+    std::fs::write(dir.path().join("plain/a.txt"), "a").unwrap();
+
+    assert_eq!(BundleKind::detect(&dir.path().join("app")), Some(BundleKind::Project));
+    assert_eq!(BundleKind::detect(&dir.path().join("Editor.APP")), Some(BundleKind::App));
+    assert_eq!(BundleKind::detect(&dir.path().join("plain")), None);
+
+    let config = ScanConfig { bundles: true, ..Default::default() };
+    let (kept, skipped) = scan(dir.path(), config);
+    assert_eq!(kept, vec!["Editor.APP", "a.txt", "app"]);
+    assert_eq!(skipped, vec![("plain".to_string(), SkipReason::IsDir)]);
+
+    let bundle = Scanner::new(dir.path(), ScanConfig { min_depth: 1, bundles: true, ..Default::default() })
+        .filter_map(|res| res.ok())
+        .find(|raw| raw.path.ends_with("app"))
+        .unwrap();
+    assert!(bundle.is_dir);
+    assert_eq!(bundle.size, 21);
+
+    // Without bundles their files are scanned one by one
+    let (kept, _) = scan(dir.path(), ScanConfig::default());
+    assert_eq!(kept, vec!["a.txt", "index.js", "package.json"]);
+}
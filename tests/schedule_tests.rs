@@ -0,0 +1,71 @@
+use chrono::NaiveTime;
+use stash::schedule::{Pause, Schedule, TimeWindow};
+
+fn at(time: &str) -> NaiveTime {
+    NaiveTime::parse_from_str(time, "%H:%M").unwrap()
+}
+
+#[test]
+fn test_time_windows_parse_and_wrap_past_midnight() {
+    let night: TimeWindow = "22:00-07:00".parse().unwrap();
+    assert!(night.contains(at("23:30")));
+    assert!(night.contains(at("00:00")));
+    assert!(!night.contains(at("07:00")));
+    assert!(!night.contains(at("12:00")));
+    assert_eq!(night.to_string(), "22:00-07:00");
+
+    let early: TimeWindow = "02:00 – 06:00".parse().unwrap();
+    assert!(early.contains(at("02:00")));
+    assert!(!early.contains(at("06:00")));
+
+    assert!("02:00".parse::<TimeWindow>().is_err());
+    assert!("2am-6am".parse::<TimeWindow>().is_err());
+    assert!("25:00-06:00".parse::<TimeWindow>().is_err());
+    assert!("06:00-06:00".parse::<TimeWindow>().is_err());
+}
+
+#[test]
+fn test_schedule_pauses_outside_active_and_inside_quiet_hours() {
+    let schedule: Schedule = serde_json::from_value(serde_json::json!({
+        "active_hours": ["01:00-07:00"],
+        "quiet_hours": ["03:00-03:30"],
+    }))
+    .unwrap();
+    assert!(!schedule.is_empty());
+
+    assert_eq!(schedule.pause_at(at("02:00")), None);
+    assert_eq!(schedule.pause_at(at("12:00")), Some(Pause::OutsideActiveHours));
+    let quiet = schedule.pause_at(at("03:15"));
+    assert!(matches!(quiet, Some(Pause::QuietHours(window)) if window.to_string() == "03:00-03:30"));
+
+    // No windows: any time
+    assert!(Schedule::default().is_empty());
+    assert_eq!(Schedule::default().pause_at(at("12:00")), None);
+    assert_eq!(Schedule::default().pause(), None);
+
+    assert!(serde_json::from_value::<Schedule>(serde_json::json!({ "quiet_hours": ["late"] })).is_err());
+}
+
+#[test]
+fn test_resource_thresholds_are_read_and_disk_space_is_checked() {
+    let schedule: Schedule = serde_json::from_value(serde_json::json!({
+        "min_battery_percent": 20,
+        "max_cpu_load": 0.8,
+        "min_free_space": "5GB",
+    }))
+    .unwrap();
+    assert_eq!(schedule.min_battery_percent, Some(20));
+    assert_eq!(schedule.max_cpu_load, Some(0.8));
+    assert_eq!(schedule.min_free_space, Some(5 << 30));
+    assert!(serde_json::from_value::<Schedule>(serde_json::json!({ "min_free_space": "5 parsecs" })).is_err());
+
+    let dir = tempfile::tempdir().unwrap();
+    let dest = dir.path().join("Organized");
+    let full: Schedule = serde_json::from_value(serde_json::json!({ "min_free_space": u64::MAX })).unwrap();
+    assert!(matches!(full.pause_for(&dest), Some(Pause::LowDiskSpace { path, .. }) if path == dest));
+    let roomy: Schedule = serde_json::from_value(serde_json::json!({ "min_free_space": 1 })).unwrap();
+    assert_eq!(roomy.pause_for(&dest), None);
+
+    assert_eq!(Pause::LowBattery(12).to_string(), "battery low (12%)");
+    assert_eq!(Pause::HighLoad(0.93).to_string(), "CPU load high (93%)");
+}
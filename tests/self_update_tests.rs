@@ -0,0 +1,108 @@
+#![cfg(feature = "self-update")]
+
+use sha2::{Digest, Sha256};
+use std::io::Write;
+
+use stash::self_update::{asset_name, checksum_name, replace_exe, target_triple, unpack_binary, verify_sha256, Release};
+
+#[test]
+fn test_release_from_github_json() {
+    let json = serde_json::json!({
+        "tag_name": "v0.3.1",
+        "assets": [
+            { "name": "stash-v0.3.1-x86_64-unknown-linux-gnu.tar.gz", "browser_download_url": "https://example.com/a" },
+            { "name": "stash-v0.3.1-x86_64-unknown-linux-gnu.sha256", "browser_download_url": "https://example.com/a.sha256" },
+            { "name": "broken" }
+        ]
+    });
+
+    let release = Release::from_json(&json).unwrap();
+    assert_eq!(release.tag, "v0.3.1");
+    assert_eq!(release.version, semver::Version::new(0, 3, 1));
+    assert_eq!(release.assets.len(), 2);
+    let target = "x86_64-unknown-linux-gnu";
+    assert_eq!(release.asset(&asset_name(&release.tag, target)).unwrap().url, "https://example.com/a");
+    assert_eq!(release.asset(&checksum_name(&release.tag, target)).unwrap().url, "https://example.com/a.sha256");
+
+    assert!(Release::from_json(&serde_json::json!({ "tag_name": "nightly" })).is_err());
+}
+
+#[test]
+fn test_verify_sha256_formats() {
+    let data = b"binary";
+    let digest = hex::encode(Sha256::digest(data));
+    let name = asset_name("v0.3.1", &target_triple());
+
+    verify_sha256(data, &digest, &name).unwrap();
+    verify_sha256(data, &format!("{}  other\n{} *{}\n", "0".repeat(64), digest, name), &name).unwrap();
+
+    assert!(verify_sha256(b"tampered", &digest, &name).is_err());
+    assert!(verify_sha256(data, &format!("{}  other", digest), &name).is_err());
+}
+
+#[test]
+fn test_replace_exe_swaps_contents() {
+    let dir = tempfile::tempdir().unwrap();
+    let exe = dir.path().join("stash");
+    std::fs::write(&exe, b"old").unwrap();
+
+    replace_exe(b"new", &exe).unwrap();
+
+    assert_eq!(std::fs::read(&exe).unwrap(), b"new");
+    assert!(!dir.path().join("stash.new").exists());
+}
+
+/// `(target, ext)` of each build in the release workflow's matrix
+fn release_matrix() -> Vec<(String, String)> {
+    let workflow = include_str!("../.github/workflows/release.yml");
+    let value = |line: &str, key: &str| line.trim().trim_start_matches("- ").strip_prefix(key).map(|v| v.trim().to_string());
+    let mut builds = Vec::new();
+    let mut target = None;
+    for line in workflow.lines() {
+        if let Some(t) = value(line, "target:") {
+            target = Some(t);
+        } else if let Some(ext) = value(line, "ext:") {
+            builds.push((target.take().unwrap(), ext));
+        }
+    }
+    builds
+}
+
+#[test]
+fn test_asset_names_match_the_release_workflow() {
+    let matrix = release_matrix();
+    assert!(!matrix.is_empty());
+    for (target, ext) in &matrix {
+        assert_eq!(asset_name("v1.2.0", target), format!("stash-v1.2.0-{}.{}", target, ext));
+        assert_eq!(checksum_name("v1.2.0", target), format!("stash-v1.2.0-{}.sha256", target));
+    }
+
+    // Platforms the workflow builds for find their own archive
+    if std::env::consts::ARCH == "x86_64" && ["linux", "windows", "macos"].contains(&std::env::consts::OS) {
+        assert!(matrix.iter().any(|(target, _)| *target == target_triple()), "{}", target_triple());
+    }
+}
+
+#[test]
+fn test_unpack_binary_from_release_archives() {
+    let binary = format!("stash{}", std::env::consts::EXE_SUFFIX);
+
+    let mut tar = tar::Builder::new(flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default()));
+    for (path, data) in [("stash-v1.2.0/README.md", &b"readme"[..]), (&format!("stash-v1.2.0/{}", binary), b"new binary")] {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o755);
+        header.set_cksum();
+        tar.append_data(&mut header, path, data).unwrap();
+    }
+    let tar_gz = tar.into_inner().unwrap().finish().unwrap();
+    assert_eq!(unpack_binary("stash-v1.2.0-x86_64-unknown-linux-gnu.tar.gz", &tar_gz).unwrap(), b"new binary");
+
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    zip.start_file(format!("stash-v1.2.0/{}", binary), zip::write::SimpleFileOptions::default()).unwrap();
+    zip.write_all(b"new binary").unwrap();
+    let zipped = zip.finish().unwrap().into_inner();
+    assert_eq!(unpack_binary("stash-v1.2.0-x86_64-pc-windows-msvc.zip", &zipped).unwrap(), b"new binary");
+
+    assert!(unpack_binary("stash-v1.2.0-x86_64-unknown-linux-gnu.tar.gz", b"not an archive").is_err());
+}
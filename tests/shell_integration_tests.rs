@@ -0,0 +1,86 @@
+use std::path::Path;
+
+use stash::shell_integration::{
+    dolphin_service_menu, finder_entries, finder_script, install, linux_entries, nautilus_script, uninstall,
+    windows_entries, Integration,
+};
+use tempfile::tempdir;
+
+#[test]
+fn test_windows_entries_cover_folders_and_backgrounds() {
+    let entries = windows_entries(Path::new(r"C:\Program Files\stash\stash.exe"));
+    assert_eq!(entries.len(), 2);
+
+    let Integration::Registry { key, command } = &entries[0] else { panic!("not a registry key") };
+    assert_eq!(key, r"HKCU\Software\Classes\Directory\shell\Stash");
+    assert_eq!(command, r#""C:\Program Files\stash\stash.exe" organize "%1" --interactive"#);
+
+    let Integration::Registry { key, command } = &entries[1] else { panic!("not a registry key") };
+    assert!(key.contains(r"Directory\Background\shell"));
+    assert!(command.contains(r#"organize "%V""#));
+}
+
+#[test]
+fn test_linux_menus_quote_the_binary() {
+    let exe = Path::new("/home/ada/it's here/stash");
+
+    let script = nautilus_script(exe);
+    assert!(script.starts_with("#!/bin/sh\n"));
+    assert!(script.contains(r#"'/home/ada/it'\''s here/stash' organize "$dir" --interactive"#));
+
+    let menu = dolphin_service_menu(exe);
+    assert!(menu.contains("MimeType=inode/directory;"));
+    assert!(menu.contains("Name=Organize with Stash"));
+    assert!(menu.contains(r#"Exec=konsole -e "/home/ada/it's here/stash" organize %f --interactive"#));
+}
+
+#[test]
+fn test_install_and_uninstall_files() {
+    let data_dir = tempdir().unwrap();
+    let entries = linux_entries(Path::new("/usr/bin/stash"), data_dir.path());
+    let script = data_dir.path().join("nautilus/scripts/Organize with Stash");
+    let menu = data_dir.path().join("kio/servicemenus/stash-organize.desktop");
+
+    install(&entries).unwrap();
+    assert!(script.exists() && menu.exists());
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        assert_eq!(std::fs::metadata(&script).unwrap().permissions().mode() & 0o111, 0o111);
+    }
+
+    // Installing again replaces the entries
+    install(&entries).unwrap();
+
+    uninstall(&entries).unwrap();
+    assert!(!script.exists() && !menu.exists());
+    uninstall(&entries).unwrap();
+}
+
+#[test]
+fn test_finder_script_runs_with_config() {
+    let script = finder_script(Path::new("/Applications/stash"), Some(Path::new("/Users/ada/photos.json")));
+    assert!(script.contains(r#"'/Applications/stash' organize "$dir" --yes --progress none --config '/Users/ada/photos.json'"#));
+
+    let script = finder_script(Path::new("/Applications/stash"), None);
+    assert!(!script.contains("--config"));
+}
+
+#[test]
+fn test_install_and_uninstall_finder_workflow() {
+    let services = tempdir().unwrap();
+    let entries = finder_entries(Path::new("/usr/local/bin/stash"), services.path(), Some(Path::new("/tmp/a&b.json")));
+    let workflow = services.path().join("Organize with Stash.workflow");
+
+    install(&entries).unwrap();
+    let info = std::fs::read_to_string(workflow.join("Contents/Info.plist")).unwrap();
+    assert!(info.contains("<string>Organize with Stash</string>") && info.contains("public.folder"));
+    let document = std::fs::read_to_string(workflow.join("Contents/document.wflow")).unwrap();
+    assert!(document.contains("com.apple.Automator.servicesMenu"));
+    assert!(document.contains("--config '/tmp/a&amp;b.json'"));
+    assert!(!document.contains("{script}"));
+
+    uninstall(&entries).unwrap();
+    assert!(!workflow.exists());
+    uninstall(&entries).unwrap();
+}
@@ -0,0 +1,82 @@
+use stash::{
+    index::Db,
+    organizer::{organise_files, OrganizeOptions},
+    snapshot::{capture, compress, decompress, diff, SnapshotEntry},
+};
+use tempfile::tempdir;
+
+#[test]
+fn test_capture_lists_nested_and_hidden_files() {
+    let dir = tempdir().unwrap();
+    std::fs::write(dir.path().join("a.txt"), b"hello").unwrap();
+    std::fs::write(dir.path().join(".hidden"), b"x").unwrap();
+    std::fs::create_dir(dir.path().join("sub")).unwrap();
+    std::fs::write(dir.path().join("sub").join("b.pdf"), b"pdf").unwrap();
+
+    let entries = capture(dir.path()).unwrap();
+    let paths: Vec<_> = entries.iter().map(|e| e.path.as_str()).collect();
+    assert_eq!(paths, vec![".hidden", "a.txt", "sub/b.pdf"]);
+    assert_eq!(entries[1].size, 5);
+    assert!(entries[1].modified.is_some());
+}
+
+#[test]
+fn test_compress_roundtrip() {
+    let dir = tempdir().unwrap();
+    std::fs::write(dir.path().join("a.txt"), b"hello").unwrap();
+
+    let entries = capture(dir.path()).unwrap();
+    let data = compress(&entries).unwrap();
+    assert_eq!(decompress(&data).unwrap(), entries);
+}
+
+#[tokio::test]
+async fn test_organize_records_snapshot_before_moving() {
+    let dir = tempdir().unwrap();
+    std::fs::write(dir.path().join("report.pdf"), b"pdf bytes").unwrap();
+    std::fs::write(dir.path().join(".env"), b"secret").unwrap();
+
+    let options = OrganizeOptions { portable: true, snapshot: true, ..Default::default() };
+    organise_files(dir.path(), &options).await.unwrap();
+    assert!(!dir.path().join("report.pdf").exists());
+
+    let db = Db::open_portable(dir.path()).await.unwrap();
+    let snapshot = db.get_snapshot(1).await.unwrap().unwrap();
+    assert_eq!(snapshot.root, dir.path());
+    let paths: Vec<_> = snapshot.entries.iter().map(|e| e.path.as_str()).collect();
+    assert_eq!(paths, vec![".env", "report.pdf"]);
+
+    assert!(db.get_snapshot(2).await.unwrap().is_none());
+}
+
+fn entry(path: &str, size: u64, modified: i64) -> SnapshotEntry {
+    SnapshotEntry { path: path.into(), size, modified: Some(modified) }
+}
+
+#[test]
+fn test_diff_reports_added_removed_and_changed() {
+    let old = vec![entry("a.txt", 1, 10), entry("b.txt", 2, 10), entry("c.txt", 3, 10)];
+    let new = vec![entry("a.txt", 1, 10), entry("c.txt", 3, 20), entry("d.txt", 4, 30)];
+
+    let result = diff(&old, &new);
+    assert_eq!(result.added, vec![entry("d.txt", 4, 30)]);
+    assert_eq!(result.removed, vec![entry("b.txt", 2, 10)]);
+    assert_eq!(result.changed, vec![(entry("c.txt", 3, 10), entry("c.txt", 3, 20))]);
+    assert!(diff(&old, &old).is_empty());
+}
+
+#[tokio::test]
+async fn test_list_snapshots() {
+    let dir = tempdir().unwrap();
+    std::fs::write(dir.path().join("a.txt"), b"hello").unwrap();
+
+    let db = Db::open_portable(dir.path()).await.unwrap();
+    let entries = capture(dir.path()).unwrap();
+    let first = db.insert_snapshot(dir.path(), &entries).await.unwrap();
+    let second = db.insert_snapshot(dir.path(), &[]).await.unwrap();
+
+    let infos = db.list_snapshots().await.unwrap();
+    assert_eq!(infos.iter().map(|i| i.id).collect::<Vec<_>>(), vec![first, second]);
+    assert_eq!(infos[0].file_count, 1);
+    assert_eq!(infos[1].root, dir.path());
+}
@@ -0,0 +1,79 @@
+use stash::space::{available_space, check, filesystem_id, unusable, Unusable};
+use tempfile::tempdir;
+
+#[test]
+fn test_missing_destination_uses_nearest_existing_ancestor() {
+    let dir = tempdir().unwrap();
+    let dest = dir.path().join("Organized").join("Documents");
+
+    assert_eq!(filesystem_id(&dest).unwrap(), filesystem_id(dir.path()).unwrap());
+    assert!(available_space(&dest).unwrap() > 0);
+}
+
+#[test]
+fn test_moves_within_a_filesystem_need_no_space() {
+    let dir = tempdir().unwrap();
+    let dest = dir.path().join("Organized");
+
+    let shortfalls = check([(dir.path(), dest.as_path(), u64::MAX / 2)]).unwrap();
+    assert!(shortfalls.is_empty());
+}
+
+#[test]
+fn test_copies_across_filesystems_are_checked() {
+    let src = tempdir().unwrap();
+    let Some(dest) = ["/dev/shm", "/run"]
+        .into_iter()
+        .map(std::path::Path::new)
+        .find(|p| p.exists() && filesystem_id(p).ok() != filesystem_id(src.path()).ok())
+    else {
+        return; // only one filesystem available here
+    };
+
+    let free = available_space(dest).unwrap();
+    let shortfalls = check([(src.path(), dest, free + 1)]).unwrap();
+    assert_eq!(shortfalls.len(), 1);
+    assert_eq!(shortfalls[0].needed, free + 1);
+    assert!(check([(src.path(), dest, 1)]).unwrap().is_empty());
+}
+
+#[test]
+fn test_pseudo_and_read_only_filesystems_are_unusable() {
+    let dir = tempdir().unwrap();
+    assert_eq!(unusable(&dir.path().join("Organized/Documents")).unwrap(), None);
+
+    if cfg!(target_os = "linux") {
+        assert_eq!(unusable(std::path::Path::new("/proc/self")).unwrap(), Some(Unusable::Pseudo("proc")));
+    }
+
+    // Any read-only mount this machine happens to have
+    let mounts = std::fs::read_to_string("/proc/mounts").unwrap_or_default();
+    let read_only = mounts.lines().find_map(|line| {
+        let fields: Vec<&str> = line.split(' ').collect();
+        let is_pseudo = ["proc", "sysfs", "cgroup", "cgroup2"].contains(fields.get(2)?);
+        (!is_pseudo && fields.get(3)?.split(',').any(|opt| opt == "ro")).then(|| std::path::PathBuf::from(fields[1]))
+    });
+    if let Some(mount) = read_only.filter(|mount| mount.is_dir()) {
+        assert_eq!(unusable(&mount).unwrap(), Some(Unusable::ReadOnly), "{:?}", mount);
+    }
+}
+
+#[tokio::test]
+async fn test_organizing_a_pseudo_filesystem_fails_early() {
+    use stash::{errors::FileOrganizerError, organizer::{organise_files, OrganizeOptions}};
+
+    if !cfg!(target_os = "linux") {
+        return;
+    }
+    let dest = tempdir().unwrap();
+    for dry_run in [true, false] {
+        let options = OrganizeOptions { dry_run, dest: Some(dest.path().to_path_buf()), portable: true, ..Default::default() };
+        match organise_files(std::path::Path::new("/proc/sys"), &options).await {
+            Err(FileOrganizerError::UnusableFilesystem { path, reason: Unusable::Pseudo("proc") }) => {
+                assert_eq!(path, std::path::Path::new("/proc/sys"));
+            }
+            other => panic!("expected an unusable filesystem, got {:?}", other.map(|s| s.processed)),
+        }
+    }
+    assert_eq!(std::fs::read_dir(dest.path()).unwrap().count(), 0);
+}
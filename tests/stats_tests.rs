@@ -0,0 +1,46 @@
+use std::{io, path::PathBuf, time::Instant};
+
+use stash::{
+    errors::FileOrganizerError,
+    stats::{FileErrorReport, FileOutcome, Stage, Summary},
+};
+
+fn failed(path: &str, stage: Stage, error: FileOrganizerError) -> FileOutcome {
+    FileOutcome::Err(FileErrorReport { path: PathBuf::from(path), stage, error })
+}
+
+#[test]
+fn test_errors_are_grouped_by_stage_and_kind() {
+    let denied = || FileOrganizerError::Io(io::Error::from(io::ErrorKind::PermissionDenied));
+    let mut outcomes: Vec<FileOutcome> = (0..5).map(|i| failed(&format!("/in/locked-{i}.pdf"), Stage::Move, denied())).collect();
+    outcomes.push(failed("/in/odd.bin", Stage::Classify, FileOrganizerError::Classify("bad header".into())));
+    outcomes.push(failed("/in/gone.txt", Stage::Move, FileOrganizerError::Io(io::Error::from(io::ErrorKind::NotFound))));
+    outcomes.push(failed("/in/sector.iso", Stage::Scan, FileOrganizerError::Io(io::Error::from_raw_os_error(5))));
+    outcomes.push(FileOutcome::Skipped { src: "/in/.hidden".into(), reason: stash::errors::SkipReason::Hidden, size: 1 });
+
+    let summary = Summary::from_outcomes(outcomes.len(), &outcomes, Instant::now());
+    assert_eq!(summary.errors, 8);
+
+    let groups: Vec<(Stage, &str, usize)> = summary.error_groups.iter().map(|g| (g.stage, g.kind, g.count)).collect();
+    assert_eq!(
+        groups,
+        [
+            (Stage::Scan, "I/O error", 1),
+            (Stage::Classify, "classifier", 1),
+            (Stage::Move, "permission denied", 5),
+            (Stage::Move, "not found", 1),
+        ]
+    );
+    let denied_group = &summary.error_groups[2];
+    assert_eq!(denied_group.examples, ["/in/locked-0.pdf", "/in/locked-1.pdf", "/in/locked-2.pdf"].map(PathBuf::from));
+
+    colored::control::set_override(false);
+    let text = summary.to_string();
+    assert!(text.contains("Move      permission denied      5    files"), "{}", text);
+    assert!(text.contains("… and 2 more"), "{}", text);
+
+    let json: serde_json::Value = serde_json::from_str(&summary.to_json().unwrap()).unwrap();
+    assert_eq!(json["error_groups"][2]["stage"], "Move");
+    assert_eq!(json["error_groups"][2]["kind"], "permission denied");
+    assert_eq!(json["error_groups"][2]["count"], 5);
+}
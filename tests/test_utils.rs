@@ -18,7 +18,8 @@ pub fn create_test_file(path: &str, size: u64) -> RawFileMetadata {
         permissions: std::fs::Permissions::from_mode(0o644),
         is_file: true,
         is_dir: false,
-        is_symlink: false,   
+        is_symlink: false,
+        owner: None,
     }
 }
 
@@ -45,6 +46,8 @@ pub fn create_test_metadata(path: &Path) -> ClassifiedFileMetadata {
         path: path.to_path_buf(),
         category: FileCategory::Others,
         year: None,
+        month: None,
+        day: None,
         created_date: None,
         modified_date: None,
         file_size: Some(0),
@@ -0,0 +1,134 @@
+use std::time::Duration;
+
+use stash::{
+    dedupe::{apply, DedupeAction, DuplicateSet},
+    index::{Db, DbFileEntry},
+    trash::{empty, list, list_in, restore, run_dir},
+};
+use tempfile::tempdir;
+
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+#[tokio::test]
+async fn test_empty_purges_only_old_runs() {
+    let dir = tempdir().unwrap();
+    let trash = tempdir().unwrap();
+    let keep = dir.path().join("keep.txt");
+    let dup = dir.path().join("dup.txt");
+    std::fs::write(&keep, b"same").unwrap();
+    std::fs::write(&dup, b"same").unwrap();
+
+    let db = Db::open_portable(dir.path()).await.unwrap();
+    let sets = vec![DuplicateSet { files: vec![keep, dup] }];
+    apply(&db, &sets, DedupeAction::Trash, false, trash.path()).await.unwrap();
+
+    // A run trashed long ago, and something that is not a run
+    let old = trash.path().join("1000");
+    std::fs::create_dir(&old).unwrap();
+    std::fs::write(old.join("ancient.log"), b"0123456789").unwrap();
+    std::fs::create_dir(trash.path().join("notes")).unwrap();
+
+    let runs = list(trash.path()).unwrap();
+    assert_eq!(runs.len(), 2);
+    assert_eq!((runs[0].id, runs[0].files, runs[0].bytes), (1000, 1, 10));
+    assert!(runs[1].age() < DAY);
+    assert_eq!((runs[1].files, runs[1].bytes), (1, 4));
+
+    let report = empty(&db, trash.path(), Some(30 * DAY), true).await.unwrap();
+    assert_eq!((report.purged.len(), report.kept), (1, 1));
+    assert!(old.exists());
+
+    let report = empty(&db, trash.path(), Some(30 * DAY), false).await.unwrap();
+    assert_eq!((report.purged.len(), report.kept), (1, 1));
+    assert!(!old.exists());
+    assert_eq!(db.list_actions(false).await.unwrap().len(), 1);
+
+    // Without a cutoff everything goes, along with its undo records
+    let report = empty(&db, trash.path(), None, false).await.unwrap();
+    assert_eq!(report.purged.len(), 1);
+    assert!(list(trash.path()).unwrap().is_empty());
+    assert!(db.list_actions(false).await.unwrap().is_empty());
+    assert!(trash.path().join("notes").exists());
+}
+
+#[tokio::test]
+async fn test_list_in_counts_files_the_index_does_not_track() {
+    let dir = tempdir().unwrap();
+    let trash = tempdir().unwrap();
+    let keep = dir.path().join("keep.txt");
+    let dup = dir.path().join("dup.txt");
+    std::fs::write(&keep, b"same").unwrap();
+    std::fs::write(&dup, b"same").unwrap();
+
+    let portable = Db::open_portable(dir.path()).await.unwrap();
+    let sets = vec![DuplicateSet { files: vec![keep, dup] }];
+    apply(&portable, &sets, DedupeAction::Trash, false, trash.path()).await.unwrap();
+
+    let runs = list_in(&portable, trash.path()).await.unwrap();
+    assert_eq!((runs.len(), runs[0].untracked), (1, Some(0)));
+    // Another index has no undo record for the run
+    let other = Db::new(&dir.path().join("other.db")).await.unwrap();
+    assert_eq!(list_in(&other, trash.path()).await.unwrap()[0].untracked, Some(1));
+}
+
+#[test]
+fn test_runs_started_in_the_same_second_get_their_own_folder() {
+    let trash = tempdir().unwrap();
+    let first = run_dir(trash.path()).unwrap();
+    let second = run_dir(trash.path()).unwrap();
+    assert_ne!(first, second);
+    assert!(first.is_dir() && second.is_dir());
+    assert_eq!(list(trash.path()).unwrap().len(), 2);
+}
+
+#[test]
+fn test_missing_trash_is_empty() {
+    let dir = tempdir().unwrap();
+    assert!(list(&dir.path().join("never-created")).unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_restore_puts_files_back_and_repoints_the_index() {
+    let dir = tempdir().unwrap();
+    let trash = tempdir().unwrap();
+    let keep = dir.path().join("keep.txt");
+    let dup = dir.path().join("dup.txt");
+    std::fs::write(&keep, b"same").unwrap();
+    std::fs::write(&dup, b"same").unwrap();
+
+    let db = Db::open_portable(dir.path()).await.unwrap();
+    // `dup.txt` is an organized file, as it would be when pruned
+    let entry = DbFileEntry {
+        path: dir.path().join("incoming/dup.txt"),
+        size: 4,
+        modified: None,
+        hash: None,
+        category: Some("Documents::Text".to_string()),
+        dest_path: dup.clone(),
+    };
+    db.upsert_file_entries(std::slice::from_ref(&entry)).await.unwrap();
+    let sets = vec![DuplicateSet { files: vec![keep, dup.clone()] }];
+    apply(&db, &sets, DedupeAction::Trash, false, trash.path()).await.unwrap();
+    let run = list(trash.path()).unwrap().remove(0);
+    std::fs::write(run.dir.join("stray.txt"), b"from elsewhere").unwrap();
+
+    let report = restore(&db, trash.path(), &[run.id], true).await.unwrap();
+    assert_eq!(report.restored, vec![(run.dir.join("dup.txt"), dup.clone())]);
+    assert_eq!(report.untracked, vec![run.dir.join("stray.txt")]);
+    assert!(!dup.exists());
+
+    // The path was taken again in the meantime
+    std::fs::write(&dup, b"newer").unwrap();
+    let report = restore(&db, trash.path(), &[], false).await.unwrap();
+    assert!(report.errors.is_empty());
+    let (_, back) = &report.restored[0];
+    assert_ne!(back, &dup);
+    assert_eq!(std::fs::read(back).unwrap(), b"same");
+    assert!(db.list_actions(false).await.unwrap().is_empty());
+    let indexed = db.lookup_full(&entry.path).await.unwrap().unwrap();
+    assert_eq!(&indexed.dest_path, back);
+
+    // The stray file keeps the run folder
+    assert!(run.dir.join("stray.txt").exists());
+    assert!(restore(&db, trash.path(), &[], false).await.unwrap().restored.is_empty());
+}
@@ -0,0 +1,31 @@
+#![cfg(feature = "tui")]
+
+use stash::{
+    organizer::{organise_files, OrganizeOptions},
+    progress::Progress,
+    tui::{Dashboard, LogKind, Phase},
+};
+use tempfile::tempdir;
+
+#[tokio::test]
+async fn test_dashboard_counts_outcomes_by_category() {
+    let dir = tempdir().unwrap();
+    std::fs::write(dir.path().join("photo.jpg"), b"jpeg").unwrap();
+    std::fs::write(dir.path().join("notes.txt"), b"text").unwrap();
+    std::fs::write(dir.path().join("more.txt"), b"more text").unwrap();
+
+    let dashboard = Dashboard::new();
+    let options = OrganizeOptions { portable: true, progress: Progress::new(dashboard.clone()), ..Default::default() };
+    organise_files(dir.path(), &options).await.unwrap();
+
+    let state = dashboard.state();
+    let state = state.lock().unwrap();
+    assert_eq!(state.phase, Phase::Done);
+    assert_eq!((state.done, state.total, state.in_flight), (3, 3, 0));
+    assert_eq!(state.categories["Documents"].files, 2);
+    assert_eq!(state.categories["Documents"].bytes, 13);
+    assert_eq!(state.categories["Images"].files, 1);
+    assert_eq!(state.log.len(), 3);
+    assert!(state.log.iter().all(|line| line.kind == LogKind::Moved));
+    assert_eq!(state.category_names()[0], "Documents");
+}
@@ -0,0 +1,154 @@
+use stash::{
+    errors::FileOrganizerError,
+    guard::Guard,
+    index::{Db, RunMove},
+    instance_lock::InstanceLock,
+    organizer::{organise_files, OrganizeOptions},
+    undo_bundle::{self, ImportOptions, UndoManifest},
+    utils::{remap_path, PathMapping},
+};
+use std::path::{Path, PathBuf};
+use tempfile::tempdir;
+
+/// Organize `root` portably with two files and return the recorded run id
+async fn organize_two_files(root: &Path) -> i64 {
+    std::fs::write(root.join("report.pdf"), b"pdf bytes").unwrap();
+    std::fs::write(root.join("notes.txt"), b"some notes").unwrap();
+
+    let options = OrganizeOptions { portable: true, ..Default::default() };
+    let summary = organise_files(root, &options).await.unwrap();
+    assert_eq!(summary.moved, 2);
+
+    let db = Db::open_portable(root).await.unwrap();
+    let runs = db.list_runs().await.unwrap();
+    assert_eq!(runs.len(), 1);
+    assert_eq!(runs[0].file_count, 2);
+    runs[0].id
+}
+
+#[test]
+fn test_path_mapping_parse_and_apply() {
+    let mapping: PathMapping = "/mnt/usb1=/mnt/usb2".parse().unwrap();
+    assert_eq!(mapping.apply(Path::new("/mnt/usb1/a/b.txt")), Some(PathBuf::from("/mnt/usb2/a/b.txt")));
+    assert_eq!(mapping.apply(Path::new("/mnt/usb1")), Some(PathBuf::from("/mnt/usb2")));
+    // Prefix matching is per component, not per character
+    assert_eq!(mapping.apply(Path::new("/mnt/usb10/a.txt")), None);
+
+    assert_eq!(remap_path(Path::new("/other/a.txt"), &[mapping]), PathBuf::from("/other/a.txt"));
+    assert!("no-separator".parse::<PathMapping>().is_err());
+    assert!("=/new".parse::<PathMapping>().is_err());
+}
+
+#[tokio::test]
+async fn test_export_then_import_on_new_mount_point() {
+    let parent = tempdir().unwrap();
+    let old_root = parent.path().join("usb1");
+    std::fs::create_dir(&old_root).unwrap();
+    let run_id = organize_two_files(&old_root).await;
+
+    let bundle = parent.path().join("undo.tar");
+    let db = Db::open_portable(&old_root).await.unwrap();
+    let manifest = undo_bundle::export(&db, run_id, &bundle).await.unwrap();
+    assert_eq!(manifest.moves.len(), 2);
+    drop(db);
+    assert_eq!(undo_bundle::read_bundle(&bundle).unwrap(), manifest);
+
+    // Same drive, mounted somewhere else
+    let new_root = parent.path().join("usb2");
+    std::fs::rename(&old_root, &new_root).unwrap();
+
+    let options = ImportOptions {
+        mappings: vec![PathMapping { from: old_root.clone(), to: new_root.clone() }],
+        ..Default::default()
+    };
+    let report = undo_bundle::import(&bundle, &options).await.unwrap();
+    assert_eq!(report.reverted, 2);
+    assert!(report.missing.is_empty() && report.changed.is_empty() && report.errors.is_empty());
+    assert_eq!(std::fs::read(new_root.join("report.pdf")).unwrap(), b"pdf bytes");
+    assert_eq!(std::fs::read(new_root.join("notes.txt")).unwrap(), b"some notes");
+}
+
+#[tokio::test]
+async fn test_import_leaves_modified_and_missing_files_alone() {
+    let dir = tempdir().unwrap();
+    let run_id = organize_two_files(dir.path()).await;
+    let db = Db::open_portable(dir.path()).await.unwrap();
+    let moves = db.run_moves(run_id).await.unwrap();
+
+    let bundle = dir.path().join("undo.tar");
+    undo_bundle::export(&db, run_id, &bundle).await.unwrap();
+
+    let (edited, removed) = (&moves[0], &moves[1]);
+    std::fs::write(&edited.dest, b"edited since").unwrap();
+    std::fs::remove_file(&removed.dest).unwrap();
+
+    let dry = ImportOptions { dry_run: true, ..Default::default() };
+    let report = undo_bundle::import(&bundle, &dry).await.unwrap();
+    assert_eq!(report.reverted, 0);
+    assert_eq!(report.changed, vec![edited.dest.clone()]);
+    assert_eq!(report.missing, vec![removed.dest.clone()]);
+    assert!(edited.dest.exists());
+    assert!(!edited.source.exists());
+}
+
+#[tokio::test]
+async fn test_dry_run_records_no_run() {
+    let dir = tempdir().unwrap();
+    std::fs::write(dir.path().join("a.txt"), b"a").unwrap();
+
+    let options = OrganizeOptions { portable: true, dry_run: true, ..Default::default() };
+    organise_files(dir.path(), &options).await.unwrap();
+
+    let db = Db::open_portable(dir.path()).await.unwrap();
+    assert!(db.list_runs().await.unwrap().is_empty());
+    assert!(undo_bundle::export(&db, 1, &dir.path().join("undo.tar")).await.is_err());
+}
+
+#[tokio::test]
+async fn test_import_only_reverts_hashed_files_below_the_run_roots() {
+    let (dir, elsewhere, locks) = (tempdir().unwrap(), tempdir().unwrap(), tempdir().unwrap());
+    let run_id = organize_two_files(dir.path()).await;
+    let db = Db::open_portable(dir.path()).await.unwrap();
+    let mut manifest = UndoManifest::from_run(&db, run_id).await.unwrap();
+    drop(db);
+
+    // A bundle that would pull a file from outside the run, or put one back outside it
+    let outside = elsewhere.path().join("secret.txt");
+    std::fs::write(&outside, b"not stash's").unwrap();
+    let moved = manifest.moves[0].clone();
+    manifest.moves = vec![
+        RunMove { dest: outside.clone(), ..moved.clone() },
+        RunMove { source: elsewhere.path().join("planted.txt"), ..moved.clone() },
+        RunMove { source: dir.path().join("sub/../../planted.txt"), ..moved.clone() },
+        RunMove { hash: None, ..manifest.moves[1].clone() },
+    ];
+    let options = ImportOptions { lock_dir: Some(locks.path().to_path_buf()), ..Default::default() };
+    let report = undo_bundle::apply(&manifest, &options).await.unwrap();
+
+    assert_eq!(report.reverted, 0);
+    assert_eq!(report.errors.len(), 4);
+    assert!(outside.exists());
+    assert!(!elsewhere.path().join("planted.txt").exists());
+    assert!(moved.dest.exists() && manifest.moves[3].dest.exists());
+
+    // The run's roots are checked like a revert's
+    let guard = Guard::new(&[dir.path().to_string_lossy()]).unwrap();
+    let options = ImportOptions { guard, lock_dir: Some(locks.path().to_path_buf()), ..Default::default() };
+    let error = undo_bundle::apply(&manifest, &options).await.unwrap_err();
+    assert!(matches!(error, FileOrganizerError::DangerousRoot { .. }));
+}
+
+#[tokio::test]
+async fn test_import_fails_while_another_run_holds_the_roots() {
+    let (dir, locks) = (tempdir().unwrap(), tempdir().unwrap());
+    let run_id = organize_two_files(dir.path()).await;
+    let db = Db::open_portable(dir.path()).await.unwrap();
+    let manifest = UndoManifest::from_run(&db, run_id).await.unwrap();
+    drop(db);
+
+    let _held = InstanceLock::acquire(locks.path(), [dir.path()], false).await.unwrap();
+    let options = ImportOptions { lock_dir: Some(locks.path().to_path_buf()), ..Default::default() };
+    let error = undo_bundle::apply(&manifest, &options).await.unwrap_err();
+    assert!(matches!(error, FileOrganizerError::AlreadyRunning { .. }));
+    assert!(!dir.path().join("report.pdf").exists());
+}
@@ -0,0 +1,63 @@
+use stash::{
+    errors::FileOrganizerError,
+    hasher::HashAlgo,
+    index::Db,
+    organizer::{organise_files, OrganizeOptions},
+    progress::Progress,
+    verify::verify,
+};
+use tempfile::tempdir;
+
+#[tokio::test]
+async fn test_verify_reports_changed_and_missing_files() {
+    let dir = tempdir().unwrap();
+    for name in ["intact.pdf", "rotten.pdf", "gone.txt"] {
+        std::fs::write(dir.path().join(name), name.as_bytes()).unwrap();
+    }
+    organise_files(dir.path(), &OrganizeOptions { portable: true, ..Default::default() }).await.unwrap();
+
+    let db = Db::open_portable(dir.path()).await.unwrap();
+    let dest = |name: &str| {
+        let db = &db;
+        let path = dir.path().join(name);
+        async move { db.lookup_full(&path).await.unwrap().unwrap().dest_path }
+    };
+    let rotten = dest("rotten.pdf").await;
+    let gone = dest("gone.txt").await;
+    std::fs::write(&rotten, b"flipped bits").unwrap();
+    std::fs::remove_file(&gone).unwrap();
+
+    let report = verify(&db, None, &Progress::silent()).await.unwrap();
+    assert_eq!((report.checked, report.ok), (3, 1));
+    assert!(matches!(&report.mismatched[..], [FileOrganizerError::HashMismatch { path, .. }] if *path == rotten));
+    assert_eq!(report.missing, vec![gone]);
+    assert!(!report.is_clean());
+
+    // Scoped to the documents subtree, only the PDFs are checked
+    let docs = dir.path().join("Organized/Documents/Pdf");
+    let report = verify(&db, Some(&docs), &Progress::silent()).await.unwrap();
+    assert_eq!((report.checked, report.ok, report.missing.len()), (2, 1, 0));
+}
+
+#[tokio::test]
+async fn test_verify_checks_each_file_with_its_recorded_algorithm() {
+    let dir = tempdir().unwrap();
+    std::fs::write(dir.path().join("old.pdf"), b"organized with blake3").unwrap();
+    organise_files(dir.path(), &OrganizeOptions { portable: true, ..Default::default() }).await.unwrap();
+
+    std::fs::write(dir.path().join("new.pdf"), b"organized with xxh3").unwrap();
+    let options = OrganizeOptions { portable: true, hash_algo: HashAlgo::Xxh3, ..Default::default() };
+    organise_files(dir.path(), &options).await.unwrap();
+
+    let db = Db::open_portable(dir.path()).await.unwrap();
+    let hash = |name: &str| {
+        let db = &db;
+        let path = dir.path().join(name);
+        async move { db.lookup_full(&path).await.unwrap().unwrap().hash.unwrap() }
+    };
+    assert!(!hash("old.pdf").await.contains(':'));
+    assert!(hash("new.pdf").await.starts_with("xxh3:"));
+
+    let report = verify(&db, None, &Progress::silent()).await.unwrap();
+    assert_eq!((report.checked, report.ok), (2, 2));
+}
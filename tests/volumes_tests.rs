@@ -0,0 +1,137 @@
+use std::{fs, path::Path, time::Duration};
+
+use stash::{
+    config::OrganizerConfig,
+    guard::Guard,
+    organizer::{organise_files, OrganizeOptions},
+    space::filesystem_id,
+    volumes::{Routes, Volumes},
+};
+use tempfile::{tempdir, tempdir_in, TempDir};
+
+/// A directory on another filesystem than `dir`, if this machine has one
+fn other_filesystem(dir: &Path) -> Option<TempDir> {
+    ["/dev/shm", "/run"]
+        .into_iter()
+        .map(Path::new)
+        .filter(|p| p.exists() && filesystem_id(p).ok() != filesystem_id(dir).ok())
+        .find_map(|p| tempdir_in(p).ok())
+}
+
+#[test]
+fn test_routes_prefer_a_volume_on_the_same_filesystem() {
+    let dir = tempdir().unwrap();
+    let source = dir.path().join("report.pdf");
+    let default = dir.path().join("Organized");
+    let local = dir.path().join("Local");
+
+    let routes = Routes::new(Volumes::from([("Documents", vec![local.clone()])]));
+    assert_eq!(routes.root_for("Documents", &source, &default), local);
+    assert_eq!(routes.root_for("Images", &source, &default), default);
+    assert!(routes.transfer(&source, &local).is_none());
+
+    let Some(other) = other_filesystem(dir.path()) else {
+        return; // only one filesystem available here
+    };
+    let remote = other.path().join("Organized");
+    let routes = Routes::new(Volumes::from([("Documents", vec![remote.clone(), local.clone()])]));
+    assert_eq!(routes.root_for("Documents", &source, &default), local);
+
+    // With only the other filesystem to go to, the file is copied there,
+    // and every root on that filesystem shares one device
+    let routes = Routes::new(Volumes::from([("Documents", vec![remote.clone()])]));
+    assert_eq!(routes.root_for("Documents", &source, &default), remote);
+    let device = routes.transfer(&source, &remote).unwrap();
+    assert_eq!(device.root, remote);
+    let again = routes.transfer(&dir.path().join("notes.pdf"), &other.path().join("Elsewhere")).unwrap();
+    assert_eq!(again.root, remote);
+}
+
+#[tokio::test]
+async fn test_transfers_are_reported_per_device() {
+    let dir = tempdir().unwrap();
+    let Some(other) = other_filesystem(dir.path()) else {
+        return;
+    };
+    let root = dir.path().join("Downloads");
+    fs::create_dir(&root).unwrap();
+    fs::write(root.join("report.pdf"), "%PDF-1.4 report").unwrap();
+    fs::write(root.join("photo.jpg"), [0xFF, 0xD8, 0xFF, 0xE0]).unwrap();
+    let remote = other.path().join("Organized");
+
+    let options = OrganizeOptions {
+        portable: true,
+        volumes: Volumes::from([("Documents", vec![remote.clone()])]),
+        // Temp directories under `/run` or `/dev` look like system ones
+        guard: Guard::disabled(),
+        ..Default::default()
+    };
+
+    // The plan names the device and what would be copied onto it
+    let preview = organise_files(&root, &OrganizeOptions { dry_run: true, ..options.clone() }).await.unwrap();
+    assert_eq!(preview.transfers.len(), 1);
+    assert_eq!(preview.transfers[&remote].files, 1);
+    assert_eq!(preview.transfers[&remote].bytes, 15);
+    assert!(preview.to_string().contains("Transfers by device:"));
+
+    let summary = organise_files(&root, &options).await.unwrap();
+    assert_eq!(summary.moved, 2);
+    assert_eq!(summary.transfers[&remote].files, 1);
+    assert!(remote.join("Documents").exists());
+    assert!(root.join("Organized/Images").exists());
+    assert!(!root.join("Organized/Documents").exists());
+}
+
+#[tokio::test]
+async fn test_transfers_per_device_are_limited() {
+    let dir = tempdir().unwrap();
+    let Some(other) = other_filesystem(dir.path()) else {
+        return;
+    };
+    let source = dir.path().join("clip.mp4");
+    let routes = Routes::new(Volumes::new()).with_transfers_per_device(2);
+    let device = routes.transfer(&source, other.path()).unwrap();
+
+    let first = device.queue().await;
+    let _second = device.queue().await;
+    assert!(tokio::time::timeout(Duration::from_millis(100), device.queue()).await.is_err());
+    drop(first);
+    assert!(tokio::time::timeout(Duration::from_secs(5), device.queue()).await.is_ok());
+
+    // Files waiting for the device don't hold up the run, even one file at a time
+    let root = dir.path().join("Downloads");
+    fs::create_dir(&root).unwrap();
+    for i in 0..5 {
+        fs::write(root.join(format!("report{}.pdf", i)), format!("%PDF-1.4 {}", i)).unwrap();
+    }
+    let options = OrganizeOptions {
+        portable: true,
+        dest: Some(other.path().join("Organized")),
+        max_concurrency: Some(1),
+        transfers_per_device: Some(1),
+        guard: Guard::disabled(),
+        ..Default::default()
+    };
+    let summary = organise_files(&root, &options).await.unwrap();
+    assert_eq!(summary.moved, 5);
+    assert_eq!(summary.transfers[&other.path().join("Organized")].files, 5);
+}
+
+#[test]
+fn test_volumes_config_is_checked() {
+    let config: OrganizerConfig =
+        serde_json::from_str(r#"{ "volumes": { "videos": ["/mnt/media/Organized", "~/Organized"] } }"#).unwrap();
+    config.check().unwrap();
+    let volumes = config.volumes();
+    assert_eq!(volumes["Videos"].len(), 2);
+    assert!(volumes["Videos"][1].is_absolute());
+
+    for bad in [
+        r#"{ "volumes": { "videos": [] } }"#,
+        r#"{ "volumes": { "clips": ["/mnt/media"] } }"#,
+        r#"{ "transfers_per_device": 0 }"#,
+    ] {
+        let config: OrganizerConfig = serde_json::from_str(bad).unwrap();
+        assert!(config.check().is_err(), "{}", bad);
+    }
+}
@@ -0,0 +1,203 @@
+use std::time::Duration;
+
+use notify::{
+    event::{CreateKind, DataChange, ModifyKind, RemoveKind},
+    Event, EventKind,
+};
+use stash::{
+    organizer::OrganizeOptions,
+    schedule::Schedule,
+    watcher::{default_strategy, is_locked, settle_strategy, watch_until, SettleStrategy, SizeStabilitySettle},
+};
+use tempfile::tempdir;
+
+fn event(kind: EventKind, path: &std::path::Path) -> Event {
+    Event::new(kind).add_path(path.to_path_buf())
+}
+
+#[test]
+fn test_size_stability_waits_for_quiet_period() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("download.bin");
+    std::fs::write(&path, b"partial").unwrap();
+
+    let mut strategy = SizeStabilitySettle::new(Duration::from_millis(100));
+    strategy.observe(&event(EventKind::Create(CreateKind::File), &path));
+
+    // First poll records the size; nothing is settled yet
+    assert!(strategy.settled().is_empty());
+
+    // Growth resets the timer
+    std::thread::sleep(Duration::from_millis(120));
+    std::fs::write(&path, b"partial + more").unwrap();
+    strategy.observe(&event(EventKind::Modify(ModifyKind::Data(DataChange::Any)), &path));
+    assert!(strategy.settled().is_empty());
+
+    std::thread::sleep(Duration::from_millis(120));
+    assert_eq!(strategy.settled(), vec![path.clone()]);
+    assert!(strategy.settled().is_empty());
+}
+
+#[test]
+fn test_size_stability_forgets_removed_files() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("gone.txt");
+    std::fs::write(&path, b"x").unwrap();
+
+    let mut strategy = SizeStabilitySettle::new(Duration::ZERO);
+    strategy.observe(&event(EventKind::Create(CreateKind::File), &path));
+    strategy.observe(&event(EventKind::Remove(RemoveKind::File), &path));
+    assert!(strategy.settled().is_empty());
+}
+
+#[test]
+fn test_locked_files_wait_until_released() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("video.mkv");
+    std::fs::write(&path, b"being downloaded").unwrap();
+
+    let holder = std::fs::File::open(&path).unwrap();
+    holder.lock().unwrap();
+    assert!(is_locked(&path));
+
+    let mut strategy = SizeStabilitySettle::new(Duration::ZERO);
+    strategy.observe(&event(EventKind::Create(CreateKind::File), &path));
+    assert!(strategy.settled().is_empty());
+    assert!(strategy.settled().is_empty());
+
+    drop(holder);
+    assert!(!is_locked(&path));
+    assert_eq!(strategy.settled(), vec![path]);
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_close_write_settles_on_close_and_rename() {
+    use notify::event::{AccessKind, AccessMode, RenameMode};
+    use stash::watcher::CloseWriteSettle;
+    use std::path::PathBuf;
+
+    let a = PathBuf::from("/watched/a.pdf");
+    let b = PathBuf::from("/watched/b.pdf");
+    let mut strategy = CloseWriteSettle::default();
+
+    // Writes alone never settle a file
+    strategy.observe(&event(EventKind::Create(CreateKind::File), &a));
+    strategy.observe(&event(EventKind::Modify(ModifyKind::Data(DataChange::Any)), &a));
+    assert!(strategy.settled().is_empty());
+
+    strategy.observe(&event(EventKind::Access(AccessKind::Close(AccessMode::Write)), &a));
+    strategy.observe(&event(EventKind::Modify(ModifyKind::Name(RenameMode::To)), &b));
+    assert_eq!(strategy.settled(), vec![a, b]);
+    assert!(strategy.settled().is_empty());
+}
+
+#[tokio::test]
+async fn test_watch_organizes_new_files() {
+    let dir = tempdir().unwrap();
+    let root = dir.path().to_path_buf();
+    let options = OrganizeOptions { portable: true, ..Default::default() };
+
+    let writer_root = root.clone();
+    let shutdown = async move {
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        std::fs::write(writer_root.join("late.pdf"), b"pdf bytes").unwrap();
+
+        // Stop once the file has been organized (or give up)
+        for _ in 0..100 {
+            if !writer_root.join("late.pdf").exists() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    };
+
+    watch_until(&root, &options, default_strategy(), &Schedule::default(), shutdown).await.unwrap();
+
+    assert!(!root.join("late.pdf").exists());
+    assert!(root.join("Organized").join("Documents").exists());
+}
+
+#[tokio::test]
+async fn test_watch_ignores_own_files() {
+    let dir = tempdir().unwrap();
+    let root = dir.path().to_path_buf();
+    let options = OrganizeOptions { portable: true, ..Default::default() };
+
+    let writer_root = root.clone();
+    let shutdown = async move {
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        std::fs::write(writer_root.join("file_organizer.log.2025-01-31"), b"log line").unwrap();
+        std::fs::write(writer_root.join("late.pdf"), b"pdf bytes").unwrap();
+
+        for _ in 0..100 {
+            if !writer_root.join("late.pdf").exists() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        // Give a stray pass on the log time to happen
+        tokio::time::sleep(Duration::from_millis(300)).await;
+    };
+
+    watch_until(&root, &options, default_strategy(), &Schedule::default(), shutdown).await.unwrap();
+
+    assert!(!root.join("late.pdf").exists());
+    assert!(root.join("file_organizer.log.2025-01-31").exists());
+}
+
+#[tokio::test]
+async fn test_watch_waits_for_downloads_to_finish() {
+    let dir = tempdir().unwrap();
+    let root = dir.path().to_path_buf();
+    let options = OrganizeOptions { portable: true, ..Default::default() };
+
+    let writer_root = root.clone();
+    let shutdown = async move {
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        let partial = writer_root.join("report.pdf.crdownload");
+        std::fs::write(&partial, b"first half").unwrap();
+
+        // Give the watcher a few ticks to (not) pick the partial file up
+        tokio::time::sleep(Duration::from_millis(600)).await;
+        assert!(partial.exists());
+
+        std::fs::write(&partial, b"first half, second half").unwrap();
+        std::fs::rename(&partial, writer_root.join("report.pdf")).unwrap();
+        for _ in 0..100 {
+            if !writer_root.join("report.pdf").exists() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    };
+
+    watch_until(&root, &options, settle_strategy(Some(Duration::from_millis(200))), &Schedule::default(), shutdown).await.unwrap();
+
+    assert!(!root.join("report.pdf").exists());
+    assert!(root.join("Organized/Documents").exists());
+}
+
+#[tokio::test]
+async fn test_watch_holds_files_in_quiet_hours() {
+    let dir = tempdir().unwrap();
+    let root = dir.path().to_path_buf();
+    std::fs::write(root.join("early.pdf"), b"pdf bytes").unwrap();
+    let options = OrganizeOptions { portable: true, ..Default::default() };
+    // Quiet around the clock
+    let schedule: Schedule =
+        serde_json::from_value(serde_json::json!({ "quiet_hours": ["00:00-12:00", "12:00-00:00"] })).unwrap();
+
+    let writer_root = root.clone();
+    let shutdown = async move {
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        std::fs::write(writer_root.join("late.pdf"), b"pdf bytes").unwrap();
+        tokio::time::sleep(Duration::from_millis(800)).await;
+    };
+
+    watch_until(&root, &options, default_strategy(), &schedule, shutdown).await.unwrap();
+
+    assert!(root.join("early.pdf").exists());
+    assert!(root.join("late.pdf").exists());
+    assert!(!root.join("Organized").exists());
+}
@@ -0,0 +1,62 @@
+#![cfg(windows)]
+
+use std::path::Path;
+
+use stash::{
+    file_mover::FileMover,
+    win_path::{extended_length, native_destination, sanitize_name},
+};
+use tempfile::tempdir;
+
+#[test]
+fn test_reserved_names_and_trailing_dots_are_sanitized() {
+    assert_eq!(sanitize_name("CON"), "CON_");
+    assert_eq!(sanitize_name("nul.txt"), "nul_.txt");
+    assert_eq!(sanitize_name("aux.tar.gz"), "aux_.tar.gz");
+    assert_eq!(sanitize_name("Lpt³.log"), "Lpt³_.log");
+    assert_eq!(sanitize_name("Report. . "), "Report");
+    assert_eq!(sanitize_name("..."), "_");
+    assert_eq!(sanitize_name("console.txt"), "console.txt");
+    assert_eq!(sanitize_name("COM10"), "COM10");
+
+    assert_eq!(
+        native_destination(Path::new(r"C:\Users\ada\Organized\PRN\notes. ")),
+        Path::new(r"C:\Users\ada\Organized\PRN_\notes")
+    );
+}
+
+#[test]
+fn test_long_paths_get_the_extended_length_prefix() {
+    let long = format!(r"C:\Users\ada\{}\file.txt", "x".repeat(250));
+    assert_eq!(extended_length(Path::new(&long)), Path::new(&format!(r"\\?\{}", long)));
+    let share = format!(r"\\nas\share\{}", "y".repeat(250));
+    assert_eq!(extended_length(Path::new(&share)), Path::new(&format!(r"\\?\UNC\nas\share\{}", "y".repeat(250))));
+
+    // Short, already prefixed and relative paths are left alone
+    assert_eq!(extended_length(Path::new(r"C:\short")), Path::new(r"C:\short"));
+    let prefixed = format!(r"\\?\{}", long);
+    assert_eq!(extended_length(Path::new(&prefixed)), Path::new(&prefixed));
+    let relative = format!(r"dir\{}", "z".repeat(250));
+    assert_eq!(extended_length(Path::new(&relative)), Path::new(&relative));
+}
+
+#[tokio::test]
+async fn test_move_into_a_path_past_max_path() {
+    let dir = tempdir().unwrap();
+    let src = dir.path().join("deep.txt");
+    std::fs::write(&src, "deep").unwrap();
+    let mut dest = dir.path().to_path_buf();
+    for _ in 0..6 {
+        dest.push("a".repeat(60));
+    }
+    dest.push("deep.txt");
+    assert!(dest.as_os_str().len() > 260);
+
+    let mover = FileMover::new();
+    mover.move_file(&src, &dest).await.unwrap();
+    assert!(!src.exists());
+    assert_eq!(std::fs::read_to_string(extended_length(&dest)).unwrap(), "deep");
+
+    mover.copy_file(&dest, &src).await.unwrap();
+    assert_eq!(std::fs::read_to_string(&src).unwrap(), "deep");
+}
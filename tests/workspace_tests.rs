@@ -0,0 +1,76 @@
+use std::fs;
+
+use stash::{
+    organizer::{organise_files, OrganizeOptions},
+    workspace::{clean_abandoned, Workspace, OWNER_FILE},
+};
+use tempfile::tempdir;
+
+#[test]
+fn test_workspace_is_private_to_its_run_and_removed() {
+    let root = tempdir().unwrap();
+    let work = root.path().join("work");
+
+    let workspace = Workspace::create(&work, "1a2b3c4d").unwrap();
+    assert_eq!(workspace.dir(), work.join("1a2b3c4d"));
+    let owner = fs::read_to_string(workspace.path(OWNER_FILE)).unwrap();
+    assert_eq!(owner.trim(), std::process::id().to_string());
+    fs::write(workspace.subdir("staging").unwrap().join("part"), "data").unwrap();
+
+    // The same run id can't be taken twice, nor escape the root
+    assert!(Workspace::create(&work, "1a2b3c4d").is_err());
+    assert!(Workspace::create(&work, "../elsewhere").is_err());
+    let other = Workspace::create(&work, "5e6f7a8b").unwrap();
+
+    // Live workspaces survive a cleanup
+    assert!(clean_abandoned(&work).unwrap().is_empty());
+    drop(workspace);
+    assert!(!work.join("1a2b3c4d").exists());
+
+    let kept = other.keep();
+    assert!(kept.exists());
+    assert!(clean_abandoned(&root.path().join("missing")).unwrap().is_empty());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_abandoned_workspaces_are_cleaned_up() {
+    let root = tempdir().unwrap();
+    let work = root.path();
+
+    // A run whose process has exited
+    let mut child = std::process::Command::new("true").spawn().unwrap();
+    child.wait().unwrap();
+    let dead = work.join("deadbeef");
+    fs::create_dir(&dead).unwrap();
+    fs::write(dead.join(OWNER_FILE), format!("{}\n", child.id())).unwrap();
+    fs::write(dead.join("plan.json"), "{}").unwrap();
+
+    // One still starting up, without its owner file yet
+    let starting = work.join("0badcafe");
+    fs::create_dir(&starting).unwrap();
+    let live = Workspace::create(work, "c0ffee00").unwrap();
+
+    assert_eq!(clean_abandoned(work).unwrap(), vec![dead.clone()]);
+    assert!(!dead.exists());
+    assert!(starting.exists() && live.dir().exists());
+}
+
+#[tokio::test]
+async fn test_organize_runs_in_a_workspace_it_removes() {
+    let dir = tempdir().unwrap();
+    let work = dir.path().join("work");
+    let root = dir.path().join("inbox");
+    fs::create_dir(&root).unwrap();
+    fs::write(root.join("report.pdf"), "pdf").unwrap();
+
+    let options = OrganizeOptions { portable: true, work_root: Some(work.clone()), ..Default::default() };
+    assert_eq!(organise_files(&root, &options).await.unwrap().moved, 1);
+    assert_eq!(fs::read_dir(&work).unwrap().count(), 0);
+
+    // A dry run needs none
+    fs::remove_dir(&work).unwrap();
+    fs::write(root.join("notes.txt"), "notes").unwrap();
+    organise_files(&root, &OrganizeOptions { dry_run: true, ..options }).await.unwrap();
+    assert!(!work.exists());
+}